@@ -255,7 +255,9 @@ pub fn update(
                                                                     let mut nl = 0;
                                                                     let tokens = prep.interned_input();
                                                                     match prep.operation {
-                                                                        Operation::InternalDiff { algorithm } => {
+                                                                        Operation::InternalDiff {
+                                                                            algorithm, ..
+                                                                        } => {
                                                                             let diff = gix::diff::blob::Diff::compute(
                                                                                 algorithm, &tokens,
                                                                             );
@@ -7,11 +7,56 @@ use gix::{
     prelude::ObjectIdExt,
 };
 
+/// Show statistics about the changed files instead of the changes themselves.
+pub enum Stats {
+    /// Show the changed lines of each file as graph that fits into `width`, followed by a summary.
+    Stat { width: usize },
+    /// Show the added and removed lines of each file.
+    Numstat,
+    /// Only show the summary of all changes.
+    Shortstat,
+    /// Show the share of changes of each directory.
+    Dirstat(gix::diff::stats::dirstat::Options),
+}
+
+impl Stats {
+    /// Parse the comma-separated `params` of `git diff --dirstat=<params>`, with the exception of the `changes` mode,
+    /// which isn't supported.
+    pub fn dirstat_from_params(params: &str) -> anyhow::Result<Self> {
+        use gix::diff::stats::dirstat::{Mode, Options};
+        let mut options = Options::default();
+        for param in params.split(',').filter(|param| !param.is_empty()) {
+            match param {
+                "lines" => options.mode = Mode::Lines,
+                "files" => options.mode = Mode::Files,
+                "cumulative" => options.cumulative = true,
+                "noncumulative" => options.cumulative = false,
+                percent => {
+                    // Like `git`, only the first decimal is used.
+                    let (whole, fraction) = percent.split_once('.').unwrap_or((percent, ""));
+                    let first_decimal = match fraction.chars().next() {
+                        None => Some(0),
+                        Some(digit) => digit.to_digit(10),
+                    };
+                    options.limit_permille = whole
+                        .parse::<u32>()
+                        .ok()
+                        .zip(first_decimal)
+                        .map(|(whole, first_decimal)| whole * 10 + first_decimal)
+                        .with_context(|| format!("Unknown or unsupported dirstat parameter '{param}'"))?;
+                }
+            }
+        }
+        Ok(Stats::Dirstat(options))
+    }
+}
+
 pub fn tree(
     mut repo: gix::Repository,
     out: &mut dyn std::io::Write,
     old_treeish: BString,
     new_treeish: BString,
+    stats: Option<Stats>,
 ) -> anyhow::Result<()> {
     repo.object_cache_size_if_unset(repo.compute_object_cache_size_for_tree_diffs(&**repo.index_or_empty()?));
     repo.objects.refresh = RefreshMode::Never;
@@ -22,6 +67,32 @@ pub fn tree(
     let old_tree = old_tree_id.object()?.peel_to_tree()?;
     let new_tree = new_tree_id.object()?.peel_to_tree()?;
 
+    if let Some(stats) = stats {
+        let files = old_tree.changes()?.file_stats(&new_tree)?;
+        match stats {
+            Stats::Stat { width } => gix::diff::stats::stat(
+                &files,
+                &gix::diff::stats::stat::Options {
+                    width,
+                    ..Default::default()
+                },
+                out,
+            )?,
+            Stats::Numstat => gix::diff::stats::numstat(&files, out)?,
+            Stats::Shortstat => {
+                if !files.is_empty() {
+                    writeln!(out, "{}", gix::diff::stats::Summary::from_files(&files))?;
+                }
+            }
+            Stats::Dirstat(options) => {
+                for dir in gix::diff::stats::dirstat(&files, &options) {
+                    writeln!(out, "{dir}")?;
+                }
+            }
+        }
+        return Ok(());
+    }
+
     let changes = repo.diff_tree_to_tree(&old_tree, &new_tree, None)?;
 
     writeln!(
@@ -33,6 +104,86 @@ pub fn tree(
     Ok(())
 }
 
+/// Show what the merge commit at `revspec` changed relative to all of its parents, like `git show --cc` does,
+/// or like `git show -c` if `options` aren't dense.
+pub fn merge(
+    mut repo: gix::Repository,
+    out: &mut dyn std::io::Write,
+    revspec: BString,
+    options: gix::diff::blob::combined::Options,
+) -> anyhow::Result<()> {
+    use gix::diff::combined::Content;
+
+    repo.object_cache_size_if_unset(repo.compute_object_cache_size_for_tree_diffs(&**repo.index_or_empty()?));
+    repo.objects.refresh = RefreshMode::Never;
+
+    let commit = repo.rev_parse_single(revspec.as_bstr())?.object()?.peel_to_commit()?;
+    let parent_trees = commit
+        .parent_ids()
+        .map(|id| Ok(id.object()?.peel_to_commit()?.tree()?))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // Like `git`, abbreviate ids of objects that don't exist, like those of submodule commits, to the default length.
+    let abbreviate = |id: ObjectId| {
+        id.attach(&repo)
+            .shorten()
+            .map_or_else(|_| id.to_hex_with_len(7).to_string(), |prefix| prefix.to_string())
+    };
+    let null_id = repo.object_hash().null();
+    repo.diff_tree_to_parents(&parent_trees, &commit.tree()?, options, |change| {
+        let location = change.location;
+        writeln!(
+            out,
+            "diff --{} {location}",
+            if options.dense { "cc" } else { "combined" }
+        )?;
+        let parent_ids: Vec<_> = change
+            .parents
+            .iter()
+            .map(|parent| abbreviate(parent.map_or(null_id, |entry| entry.id)))
+            .collect();
+        writeln!(
+            out,
+            "index {}..{}",
+            parent_ids.join(","),
+            abbreviate(change.entry.map_or(null_id, |entry| entry.id))
+        )?;
+
+        let is_added = change.parents.iter().all(Option::is_none);
+        if change.mode_differs() {
+            let parent_modes: Vec<_> = change
+                .parents
+                .iter()
+                .map(|parent| format!("{:06o}", parent.map_or(0, |entry| entry.mode.value())))
+                .collect();
+            match change.entry {
+                Some(entry) if is_added => writeln!(out, "new file mode {:06o}", entry.mode.value())?,
+                Some(entry) => writeln!(out, "mode {}..{:06o}", parent_modes.join(","), entry.mode.value())?,
+                None => writeln!(out, "deleted file mode {}", parent_modes.join(","))?,
+            }
+        }
+        match change.content {
+            Content::Binary => writeln!(out, "Binary files differ")?,
+            Content::Hunks(hunks) => {
+                if is_added {
+                    writeln!(out, "--- /dev/null")?;
+                } else {
+                    writeln!(out, "--- a/{location}")?;
+                }
+                match change.entry {
+                    Some(_) => writeln!(out, "+++ b/{location}")?,
+                    None => writeln!(out, "+++ /dev/null")?,
+                }
+                for hunk in hunks {
+                    hunk.write_to(&mut *out, false)?;
+                }
+            }
+        }
+        Ok::<_, std::io::Error>(std::ops::ControlFlow::Continue(()))
+    })?;
+    Ok(())
+}
+
 fn write_changes(
     repo: &gix::Repository,
     mut out: impl std::io::Write,
@@ -152,12 +303,38 @@ fn resolve_revspec(
     }
 }
 
+/// Show changed words instead of changed lines.
+pub struct WordDiff {
+    /// The way word diffs are displayed.
+    pub mode: gix::diff::blob::word_diff::Mode,
+    /// A regular expression to determine what's a word, overriding the configured one.
+    pub regex: Option<BString>,
+}
+
+#[expect(clippy::too_many_arguments)]
 pub fn file(
     mut repo: gix::Repository,
     out: &mut dyn std::io::Write,
     old_revspec: BString,
     new_revspec: BString,
+    whitespace: gix::diff::blob::whitespace::Options,
+    word_diff: Option<WordDiff>,
+    color_moved: Option<gix::diff::blob::moved::Options>,
+    function_context: bool,
 ) -> Result<(), anyhow::Error> {
+    if let Some(gix::diff::blob::moved::Whitespace {
+        ignore_all_space,
+        ignore_space_change,
+        ignore_space_at_eol,
+        allow_indentation_change: true,
+    }) = color_moved.map(|options| options.whitespace)
+    {
+        anyhow::ensure!(
+            !(ignore_all_space || ignore_space_change || ignore_space_at_eol),
+            "allow-indentation-change cannot be combined with other whitespace modes"
+        );
+    }
+
     repo.object_cache_size_if_unset(repo.compute_object_cache_size_for_tree_diffs(&**repo.index_or_empty()?));
     repo.objects.refresh = RefreshMode::Never;
 
@@ -170,6 +347,7 @@ pub fn file(
         gix::diff::blob::pipeline::Mode::ToGitUnlessBinaryToTextIsPresent,
         worktree_roots,
     )?;
+    resource_cache.options.whitespace = whitespace;
 
     resource_cache.set_resource(
         old_blob_id,
@@ -190,8 +368,13 @@ pub fn file(
 
     use gix::diff::blob::platform::prepare_diff::Operation;
 
-    let algorithm = match outcome.operation {
-        Operation::InternalDiff { algorithm } => algorithm,
+    let (algorithm, whitespace, word_regex, funcname) = match outcome.operation {
+        Operation::InternalDiff {
+            algorithm,
+            whitespace,
+            word_regex,
+            funcname,
+        } => (algorithm, whitespace, word_regex, funcname),
         Operation::ExternalCommand { .. } => {
             unreachable!("We disabled that")
         }
@@ -205,14 +388,59 @@ pub fn file(
         tokens_for_diffing(outcome.new.data.as_slice().unwrap_or_default()),
     );
 
-    let diff = gix::diff::blob::diff_with_slider_heuristics(algorithm, &interner);
-    let rendered = gix::diff::blob::UnifiedDiff::new(
-        &diff,
-        &interner,
-        gix::diff::blob::unified_diff::ConsumeBinaryHunk::new(BString::default(), "\n"),
-        gix::diff::blob::unified_diff::ContextSize::symmetrical(3),
-    )
-    .consume()?;
+    let diff = gix::diff::blob::whitespace::diff_with_slider_heuristics(algorithm, &interner, whitespace);
+    let context_size = gix::diff::blob::unified_diff::ContextSize::symmetrical(3);
+    let functions = funcname
+        .map(gix::diff::blob::funcname::Matcher::new)
+        .transpose()?
+        .unwrap_or_default();
+    let rendered = match (word_diff, color_moved) {
+        (_, Some(options)) => {
+            let mut detector = gix::diff::blob::UnifiedDiff::new(
+                &diff,
+                &interner,
+                gix::diff::blob::moved::Detector::default(),
+                context_size,
+            )
+            .with_whitespace(whitespace)
+            .with_function_names(&functions)
+            .with_function_context(function_context)
+            .consume()?;
+            detector.detect(options);
+            let mut rendered = BString::default();
+            detector.render_file(0, &mut rendered)?;
+            rendered
+        }
+        (Some(WordDiff { mode, regex }), None) => {
+            use gix::diff::blob::word_diff;
+            let word_regex = regex
+                .as_ref()
+                .map(|regex| regex.as_bstr())
+                .or(word_regex)
+                .map(word_diff::WordRegex::new)
+                .transpose()?;
+            gix::diff::blob::UnifiedDiff::new(
+                &diff,
+                &interner,
+                word_diff::WordDiff::new(word_diff::Renderer::new(BString::default(), mode), word_regex.as_ref()),
+                context_size,
+            )
+            .with_whitespace(whitespace)
+            .with_function_names(&functions)
+            .with_function_context(function_context)
+            .consume()?
+        }
+        (None, None) => gix::diff::blob::UnifiedDiff::new(
+            &diff,
+            &interner,
+            gix::diff::blob::unified_diff::ConsumeBinaryHunk::new(BString::default(), "\n"),
+            context_size,
+        )
+        .with_whitespace(whitespace)
+        .with_function_names(&functions)
+        .with_function_context(function_context)
+        .consume()?,
+    };
     write!(out, "{rendered}")?;
 
     Ok(())
@@ -36,7 +36,7 @@ pub(crate) mod function {
         repo: gix::Repository,
         mut progress: P,
         mut out: impl std::io::Write,
-        err: impl std::io::Write,
+        mut err: impl std::io::Write,
         Options {
             format,
             dry_run,
@@ -56,69 +56,113 @@ pub(crate) mod function {
             bail!("JSON output isn't yet supported for fetching.");
         }
 
-        let mut remote = crate::repository::remote::by_name_or_url(&repo, remote.as_deref())?;
-        if !ref_specs.is_empty() {
-            remote.replace_refspecs(ref_specs.iter(), gix::remote::Direction::Fetch)?;
-            remote = remote.with_fetch_tags(gix::remote::fetch::Tags::None);
-        }
-        let res: gix::remote::fetch::Outcome = remote
-            .connect(gix::remote::Direction::Fetch)?
-            .prepare_fetch(&mut progress, Default::default())?
-            .with_dry_run(dry_run)
-            .with_shallow(shallow)
-            .receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+        let group_fetch = match remote.as_deref() {
+            Some(name) => match repo.fetch_remote_group(
+                name,
+                &mut progress,
+                &gix::interrupt::IS_INTERRUPTED,
+                gix::remote::group::fetch::Options {
+                    refspecs: ref_specs.clone(),
+                    dry_run,
+                    shallow: shallow.clone(),
+                    ref_map: Default::default(),
+                },
+            ) {
+                Ok(fetched) => Some(fetched),
+                Err(gix::remote::group::fetch::Error::Group(gix::remote::group::Error::NotFound { .. })) => None,
+                Err(err) => return Err(err.into()),
+            },
+            None => None,
+        };
+        let fetched = match group_fetch {
+            Some(fetched) => fetched,
+            None => {
+                let mut remote = crate::repository::remote::by_name_or_url(&repo, remote.as_deref())?;
+                if !ref_specs.is_empty() {
+                    remote.replace_refspecs(ref_specs.iter(), gix::remote::Direction::Fetch)?;
+                    remote = remote.with_fetch_tags(gix::remote::fetch::Tags::None);
+                }
+                let res = remote
+                    .connect(gix::remote::Direction::Fetch)?
+                    .prepare_fetch(&mut progress, Default::default())?
+                    .with_dry_run(dry_run)
+                    .with_shallow(shallow)
+                    .receive(&mut progress, &gix::interrupt::IS_INTERRUPTED)?;
+                vec![(remote, res)]
+            }
+        };
+        let is_group = fetched.len() > 1;
+        for (remote, res) in fetched {
+            if is_group {
+                writeln!(
+                    out,
+                    "Fetched {}",
+                    remote
+                        .name()
+                        .map_or_else(|| "<unnamed>".into(), |name| name.as_bstr().to_string())
+                )?;
+            }
 
-        if handshake_info {
-            writeln!(out, "Handshake Information")?;
-            writeln!(out, "\t{:?}", res.handshake)?;
-        }
+            if handshake_info {
+                writeln!(out, "Handshake Information")?;
+                writeln!(out, "\t{:?}", res.handshake)?;
+            }
 
-        let ref_specs = remote.refspecs(gix::remote::Direction::Fetch);
-        match res.status {
-            Status::NoPackReceived {
-                update_refs,
-                negotiate,
-                dry_run: _,
-            } => {
-                let negotiate_default = Default::default();
-                print_updates(
-                    &repo,
-                    negotiate.as_ref().unwrap_or(&negotiate_default),
+            let ref_specs = remote.refspecs(gix::remote::Direction::Fetch);
+            match res.status {
+                Status::NoPackReceived {
                     update_refs,
-                    ref_specs,
-                    res.ref_map,
-                    &mut out,
-                    err,
-                )?;
-                if negotiation_info {
-                    print_negotiate_info(&mut out, negotiate.as_ref())?;
-                }
-                if let Some((negotiate, path)) = negotiate.as_ref().zip(open_negotiation_graph) {
-                    render_graph(&repo, &negotiate.graph, &path, progress)?;
-                }
-                Ok::<_, anyhow::Error>(())
-            }
-            Status::Change {
-                update_refs,
-                write_pack_bundle,
-                negotiate,
-            } => {
-                print_updates(&repo, &negotiate, update_refs, ref_specs, res.ref_map, &mut out, err)?;
-                if let Some(data_path) = write_pack_bundle.data_path {
-                    writeln!(out, "pack  file: \"{}\"", data_path.display()).ok();
-                }
-                if let Some(index_path) = write_pack_bundle.index_path {
-                    writeln!(out, "index file: \"{}\"", index_path.display()).ok();
-                }
-                if negotiation_info {
-                    print_negotiate_info(&mut out, Some(&negotiate))?;
+                    negotiate,
+                    dry_run: _,
+                } => {
+                    let negotiate_default = Default::default();
+                    print_updates(
+                        &repo,
+                        negotiate.as_ref().unwrap_or(&negotiate_default),
+                        update_refs,
+                        ref_specs,
+                        res.ref_map,
+                        &mut out,
+                        &mut err,
+                    )?;
+                    if negotiation_info {
+                        print_negotiate_info(&mut out, negotiate.as_ref())?;
+                    }
+                    if let Some((negotiate, path)) = negotiate.as_ref().zip(open_negotiation_graph.as_ref()) {
+                        render_graph(&repo, &negotiate.graph, path, &mut progress)?;
+                    }
+                    Ok::<_, anyhow::Error>(())
                 }
-                if let Some(path) = open_negotiation_graph {
-                    render_graph(&repo, &negotiate.graph, &path, progress)?;
+                Status::Change {
+                    update_refs,
+                    write_pack_bundle,
+                    negotiate,
+                } => {
+                    print_updates(
+                        &repo,
+                        &negotiate,
+                        update_refs,
+                        ref_specs,
+                        res.ref_map,
+                        &mut out,
+                        &mut err,
+                    )?;
+                    if let Some(data_path) = write_pack_bundle.data_path {
+                        writeln!(out, "pack  file: \"{}\"", data_path.display()).ok();
+                    }
+                    if let Some(index_path) = write_pack_bundle.index_path {
+                        writeln!(out, "index file: \"{}\"", index_path.display()).ok();
+                    }
+                    if negotiation_info {
+                        print_negotiate_info(&mut out, Some(&negotiate))?;
+                    }
+                    if let Some(path) = open_negotiation_graph.as_ref() {
+                        render_graph(&repo, &negotiate.graph, path, &mut progress)?;
+                    }
+                    Ok(())
                 }
-                Ok(())
-            }
-        }?;
+            }?;
+        }
         if dry_run {
             writeln!(out, "DRY-RUN: No ref was updated and no pack was received.").ok();
         }
@@ -91,6 +91,7 @@ pub fn show(
                 percentage: Some(percentage),
                 limit: 0,
                 track_empty: false,
+                matching: Default::default(),
             });
             if opts.rewrites.is_some() {
                 opts.dirwalk_options.iter_mut().for_each(|opts| {
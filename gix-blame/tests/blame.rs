@@ -320,7 +320,7 @@ mktest!(
     3
 );
 
-/// As of 2025-12-07, both algorithms are expected to pass. They use `imara-diff` 0.2 under the
+/// As of 2025-12-07, all algorithms are expected to pass. They use `imara-diff` 0.2 under the
 /// hood. One of them failed with `imara-diff` 0.1.
 ///
 /// Context: https://github.com/Byron/gitoxide/pull/1453#issuecomment-2371013904
@@ -329,6 +329,7 @@ fn diff_algorithm_parity() {
     for (case, diff_algorithm) in [
         ("empty-lines-myers", gix_diff::blob::Algorithm::Myers),
         ("empty-lines-histogram", gix_diff::blob::Algorithm::Histogram),
+        ("empty-lines-patience", gix_diff::blob::Algorithm::Patience),
     ] {
         let Fixture {
             odb,
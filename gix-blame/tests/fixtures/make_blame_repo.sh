@@ -128,7 +128,8 @@ git commit -q -m c4.4
 
 echo -e "  line 1\n\n  line 2\n\n  line 3" > empty-lines-histogram.txt
 cp empty-lines-histogram.txt empty-lines-myers.txt
-git add empty-lines-histogram.txt empty-lines-myers.txt
+cp empty-lines-histogram.txt empty-lines-patience.txt
+git add empty-lines-histogram.txt empty-lines-myers.txt empty-lines-patience.txt
 git commit -q -m c4.5
 
 echo -e "line 0\nline 2\nline 3" > sub-directory/sub-directory.txt
@@ -155,7 +156,8 @@ git commit -q -m c5.3
 
 echo -e "  line 1\n\n  line in between\n\n  line 2\n\n  line in between\n\n  line 3" > empty-lines-histogram.txt
 cp empty-lines-histogram.txt empty-lines-myers.txt
-git add empty-lines-histogram.txt empty-lines-myers.txt
+cp empty-lines-histogram.txt empty-lines-patience.txt
+git add empty-lines-histogram.txt empty-lines-myers.txt empty-lines-patience.txt
 git commit -q -m c5.4
 
 mv before-second-rename.txt after-second-rename.txt
@@ -295,3 +297,7 @@ git blame --porcelain empty-lines-histogram.txt > .git/empty-lines-histogram.bas
 git config --local diff.algorithm myers
 
 git blame --porcelain empty-lines-myers.txt > .git/empty-lines-myers.baseline
+
+git config --local diff.algorithm patience
+
+git blame --porcelain empty-lines-patience.txt > .git/empty-lines-patience.baseline
@@ -0,0 +1,221 @@
+use bstr::{BStr, BString};
+
+use crate::{File, file, file::Metadata, url_match, value};
+
+/// Access to values in sections whose subsection is a URL, like `http.<url>.proxy`, on behalf of a given URL.
+///
+/// All methods [normalize](url_match::normalize()) the `url` before matching it against subsections.
+impl File {
+    /// Return all sections named `section_name` which have no subsection or whose subsection is a URL that
+    /// [matches](url_match::matches()) `url`, along with the quality of the match, and which pass `filter`.
+    /// Sections are returned in the order in which they appear in the file.
+    ///
+    /// Subsections that can't be parsed as URL never match.
+    #[must_use]
+    pub fn sections_by_url_filter<'a>(
+        &'a self,
+        section_name: impl AsRef<str>,
+        url: &gix_url::Url,
+        filter: impl FnMut(&Metadata) -> bool + 'a,
+    ) -> Option<impl Iterator<Item = (file::SectionRef<'a>, url_match::Match)> + 'a> {
+        let url = normalized(url);
+        self.sections_by_name_and_filter(section_name, filter)
+            .map(move |sections| {
+                sections.filter_map(move |section| match_quality(&section, &url).map(|quality| (section, quality)))
+            })
+    }
+
+    /// Like [`string_by_url_filter()`](File::string_by_url_filter()), but without filter.
+    pub fn string_by_url(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+    ) -> Option<BString> {
+        self.string_by_url_filter(section_name, value_name, url, |_| true)
+    }
+
+    /// Return the value of `value_name` in a section named `section_name` that applies to `url`,
+    /// with the section containing it passing `filter`.
+    ///
+    /// This resolves keys like `http.<url>.proxy` with the precedence rules of
+    /// [`http.<url>.*`](https://git-scm.com/docs/git-config#Documentation/git-config.txt-httplturlgt):
+    /// the value of the section that [matches best](url_match::Match) is used, and among equally good matches
+    /// the last one wins. Sections without subsection, like `http.proxy`, apply to all URLs but are the weakest match.
+    pub fn string_by_url_filter(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+        filter: impl FnMut(&Metadata) -> bool,
+    ) -> Option<BString> {
+        let value_name = value_name.as_ref();
+        self.sections_by_url_with_value(section_name.as_ref(), value_name, url, filter)
+            .pop()
+            .and_then(|section| section.value(value_name))
+    }
+
+    /// Like [`strings_by_url_filter()`](File::strings_by_url_filter()), but without filter.
+    pub fn strings_by_url(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+    ) -> Option<Vec<BString>> {
+        self.strings_by_url_filter(section_name, value_name, url, |_| true)
+    }
+
+    /// Like [`string_by_url_filter()`](File::string_by_url_filter()), but returns all values of multi-valued keys
+    /// like `http.<url>.extraHeader` in the order in which they were seen.
+    ///
+    /// Like in `git`, a value is included unless a better matching section with the same key was seen before it.
+    pub fn strings_by_url_filter(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+        filter: impl FnMut(&Metadata) -> bool,
+    ) -> Option<Vec<BString>> {
+        let value_name = value_name.as_ref();
+        let sections = self.sections_by_url_with_value(section_name.as_ref(), value_name, url, filter);
+        (!sections.is_empty()).then(|| {
+            sections
+                .into_iter()
+                .flat_map(|section| section.values(value_name))
+                .collect()
+        })
+    }
+
+    /// Like [`path_by_url_filter()`](File::path_by_url_filter()), but without filter.
+    ///
+    /// Note that this path is not vetted and should only point to resources which can't be used
+    /// to pose a security risk.
+    pub fn path_by_url(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+    ) -> Option<crate::Path> {
+        self.path_by_url_filter(section_name, value_name, url, |_| true)
+    }
+
+    /// Like [`string_by_url_filter()`](File::string_by_url_filter()), but returns a path.
+    pub fn path_by_url_filter(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+        filter: impl FnMut(&Metadata) -> bool,
+    ) -> Option<crate::Path> {
+        self.string_by_url_filter(section_name, value_name, url, filter)
+            .map(crate::Path::from)
+    }
+
+    /// Like [`boolean_by_url_filter()`](File::boolean_by_url_filter()), but without filter.
+    pub fn boolean_by_url(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+    ) -> Result<Option<bool>, value::Error> {
+        self.boolean_by_url_filter(section_name, value_name, url, |_| true)
+    }
+
+    /// Like [`string_by_url_filter()`](File::string_by_url_filter()), but returns a boolean, with implicit values being `true`.
+    pub fn boolean_by_url_filter(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+        filter: impl FnMut(&Metadata) -> bool,
+    ) -> Result<Option<bool>, value::Error> {
+        let value_name = value_name.as_ref();
+        match self
+            .sections_by_url_with_value(section_name.as_ref(), value_name, url, filter)
+            .pop()
+            .and_then(|section| section.value_implicit(value_name))
+        {
+            Some(Some(v)) => crate::Boolean::try_from(v).map(|value| Some(value.into())),
+            Some(None) => Ok(Some(true)),
+            None => Ok(None),
+        }
+    }
+
+    /// Like [`integer_by_url_filter()`](File::integer_by_url_filter()), but without filter.
+    pub fn integer_by_url(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+    ) -> Result<Option<i64>, value::Error> {
+        self.integer_by_url_filter(section_name, value_name, url, |_| true)
+    }
+
+    /// Like [`string_by_url_filter()`](File::string_by_url_filter()), but returns an integer.
+    pub fn integer_by_url_filter(
+        &self,
+        section_name: impl AsRef<str>,
+        value_name: impl AsRef<str>,
+        url: &gix_url::Url,
+        filter: impl FnMut(&Metadata) -> bool,
+    ) -> Result<Option<i64>, value::Error> {
+        let Some(int) = self.string_by_url_filter(section_name, value_name, url, filter) else {
+            return Ok(None);
+        };
+        crate::Integer::try_from(BStr::new(&int))
+            .and_then(|b| b.to_decimal().ok_or_else(|| value::Error::new("Integer overflow", int)))
+            .map(Some)
+    }
+
+    /// Return all sections that contain `value_name` and whose values apply to `url`, with the last one being the best match.
+    fn sections_by_url_with_value(
+        &self,
+        section_name: &str,
+        value_name: &str,
+        url: &gix_url::Url,
+        mut filter: impl FnMut(&Metadata) -> bool,
+    ) -> Vec<file::SectionRef<'_>> {
+        let url = normalized(url);
+        let mut best = None;
+        let mut out = Vec::new();
+        let Ok(ids) = self.section_ids_by_name(section_name) else {
+            return out;
+        };
+        for id in ids {
+            let section = self.sections.get(&id).expect("known section id");
+            if !filter(section.meta()) {
+                continue;
+            }
+            let section = file::SectionRef::from_data(section, &self.backing);
+            if !section.body().contains_value_name(value_name) {
+                continue;
+            }
+            let Some(quality) = match_quality(&section, &url) else {
+                continue;
+            };
+            if best.is_some_and(|best| quality < best) {
+                continue;
+            }
+            best = Some(quality);
+            out.push(section);
+        }
+        out
+    }
+}
+
+fn normalized(url: &gix_url::Url) -> gix_url::Url {
+    let mut url = url.clone();
+    url_match::normalize(&mut url);
+    url
+}
+
+fn match_quality(section: &file::SectionRef<'_>, url: &gix_url::Url) -> Option<url_match::Match> {
+    match section.header().subsection_name() {
+        Some(pattern) => {
+            let mut pattern = gix_url::parse(pattern).ok()?;
+            url_match::normalize(&mut pattern);
+            url_match::matches(&pattern, url)
+        }
+        None => Some(url_match::Match::default()),
+    }
+}
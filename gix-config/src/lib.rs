@@ -69,3 +69,5 @@ mod types;
 pub use types::{File, Source};
 ///
 pub mod source;
+/// Match URLs against the URL subsections of keys like `http.<url>.proxy`.
+pub mod url_match;
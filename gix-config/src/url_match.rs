@@ -0,0 +1,137 @@
+use bstr::{BString, ByteSlice};
+
+/// The quality of a match of a URL pattern, as found in the subsection of keys like `http.<url>.proxy`, against a URL.
+///
+/// Better matches compare greater. A longer host in the pattern wins, followed by a longer matching path,
+/// and finally a pattern that matched the user name wins over one that didn't specify a user.
+/// Sections without a URL subsection, like `[http]`, match with the default value, the weakest match possible.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Match {
+    /// The length of the host of the pattern, with wildcards counting as a single character.
+    pub host_len: usize,
+    /// The length of the path that matched, including the trailing slash even if it was implied.
+    pub path_len: usize,
+    /// If `true`, the pattern specified a user which matched the user of the URL.
+    pub user_matched: bool,
+}
+
+/// Normalize `url` in place so it can be compared with [`matches()`] similar to how
+/// [git does it](https://github.com/git/git/blob/be1a02a17ede4082a86dfbfee0f54f345e8b43ac/urlmatch.c#L109).
+///
+/// This lower-cases the host, removes the port if it's the default for `http` and `https` URLs,
+/// assures there is a path and resolves `.` and `..` components within it.
+///
+/// Note that percent-encoding doesn't need to be normalized as [`gix_url::Url`] already stores decoded values.
+pub fn normalize(url: &mut gix_url::Url) {
+    if let Some(host) = url.host.as_mut() {
+        host.make_ascii_lowercase();
+    }
+    let default_port = match url.scheme {
+        gix_url::Scheme::Http => Some(80),
+        gix_url::Scheme::Https => Some(443),
+        _ => None,
+    };
+    if url.port.is_some() && url.port == default_port {
+        url.port = None;
+    }
+    if url.path.is_empty() {
+        if url.host.is_some() {
+            url.path = "/".into();
+        }
+    } else if url.path.starts_with(b"/") {
+        url.path = remove_dot_components(url.path.as_ref());
+    }
+}
+
+/// Return the quality of the match if `pattern` matches `url`, or `None` if it doesn't match.
+/// Both URLs are expected to be [normalized](normalize()).
+///
+/// `pattern` matches if
+///
+/// * the schemes are equal,
+/// * the user is equal if `pattern` specifies one,
+/// * the hosts have the same amount of `.` separated components, each of which is equal, or `*` in `pattern`,
+/// * the ports are equal,
+/// * and the path of `pattern` is equal to the one of `url`, or is a prefix of it ending at a `/`.
+pub fn matches(pattern: &gix_url::Url, url: &gix_url::Url) -> Option<Match> {
+    if pattern.scheme != url.scheme {
+        return None;
+    }
+    let user_matched = match pattern.user() {
+        Some(user) => {
+            if url.user() != Some(user) {
+                return None;
+            }
+            true
+        }
+        None => false,
+    };
+    if !host_matches(pattern.host(), url.host()) || pattern.port != url.port {
+        return None;
+    }
+    let path_len = path_match_len(url.path.as_ref(), pattern.path.as_ref())?;
+    Some(Match {
+        host_len: pattern.host().map_or(0, str::len),
+        path_len,
+        user_matched,
+    })
+}
+
+fn host_matches(pattern: Option<&str>, host: Option<&str>) -> bool {
+    match (pattern, host) {
+        (Some(pattern), Some(host)) => {
+            let mut pattern = pattern.split('.');
+            let mut host = host.split('.');
+            loop {
+                match (pattern.next(), host.next()) {
+                    (Some(pat), Some(component)) => {
+                        if pat != "*" && pat != component {
+                            return false;
+                        }
+                    }
+                    (None, None) => return true,
+                    (Some(_), None) | (None, Some(_)) => return false,
+                }
+            }
+        }
+        (None, None) => true,
+        (Some(_), None) | (None, Some(_)) => false,
+    }
+}
+
+/// Both paths are considered to have an implicit trailing `/` if they don't have one already.
+fn path_match_len(path: &[u8], prefix: &[u8]) -> Option<usize> {
+    if prefix.is_empty() || prefix == b"/" {
+        return (path.is_empty() || path[0] == b'/').then_some(1);
+    }
+    let prefix = prefix.strip_suffix(b"/").unwrap_or(prefix);
+    let rest = path.strip_prefix(prefix)?;
+    (rest.is_empty() || rest[0] == b'/').then_some(prefix.len() + 1)
+}
+
+fn remove_dot_components(path: &[u8]) -> BString {
+    if !path.split_str("/").any(|c| c == b"." || c == b"..") {
+        return path.into();
+    }
+    let mut components = Vec::new();
+    let mut ends_in_dot_component = false;
+    for component in path[1..].split_str("/") {
+        ends_in_dot_component = true;
+        match component {
+            b"." => {}
+            b".." => {
+                components.pop();
+            }
+            component => {
+                ends_in_dot_component = false;
+                components.push(component);
+            }
+        }
+    }
+    if ends_in_dot_component {
+        components.push(b"");
+    }
+    let mut out = BString::from("/");
+    out.extend_from_slice(&components.join(&b'/'));
+    out
+}
@@ -0,0 +1,113 @@
+use gix_config::File;
+
+use crate::file::bstring;
+
+fn url(input: &str) -> gix_url::Url {
+    gix_url::parse(input).expect("valid URL")
+}
+
+fn config() -> crate::Result<File> {
+    Ok(File::try_from(
+        r#"
+[http]
+    proxy = global
+    sslVerify = false
+    extraHeader = global
+[http "https://*.example.com"]
+    proxy = wildcard
+[http "https://git.example.com"]
+    proxy = host
+    extraHeader = host
+[http "https://git.example.com/org/"]
+    proxy = path
+    sslVerify
+[http "https://user@git.example.com/org"]
+    proxy = user
+[http "https://git.example.com/org"]
+    postBuffer = 100
+[http "https://*.example.com"]
+    proxy = wildcard-again
+    extraHeader = ignored-as-less-specific
+[http "not a url"]
+    proxy = never
+[http]
+    extraHeader = ignored-as-least-specific
+"#,
+    )?)
+}
+
+#[test]
+fn values_without_url_apply_to_all_urls() -> crate::Result {
+    let config = config()?;
+    let url = url("https://example.org/repo");
+    assert_eq!(config.string_by_url("http", "proxy", &url), Some(bstring("global")));
+    assert_eq!(config.boolean_by_url("http", "sslVerify", &url)?, Some(false));
+    assert_eq!(
+        config.strings_by_url("http", "extraHeader", &url),
+        Some(vec![bstring("global"), bstring("ignored-as-least-specific")]),
+        "without a more specific match, all values apply"
+    );
+    assert_eq!(config.integer_by_url("http", "postBuffer", &url)?, None);
+    Ok(())
+}
+
+#[test]
+fn the_best_match_wins() -> crate::Result {
+    let config = config()?;
+    for (input, expected) in [
+        ("https://other.example.com", "wildcard-again"),
+        ("https://GIT.example.com:443/", "host"),
+        ("https://git.example.com/organization", "host"),
+        ("https://git.example.com/org", "path"),
+        ("https://git.example.com/org/repo", "path"),
+        ("https://git.example.com/org/./other/../repo", "path"),
+        ("https://user@git.example.com/org/repo", "user"),
+        ("http://git.example.com/org/repo", "global"),
+    ] {
+        assert_eq!(
+            config.string_by_url("http", "proxy", &url(input)),
+            Some(bstring(expected)),
+            "{input}"
+        );
+    }
+
+    let url = url("https://git.example.com/org/repo");
+    assert_eq!(
+        config.boolean_by_url("http", "sslVerify", &url)?,
+        Some(true),
+        "implicit values count"
+    );
+    assert_eq!(config.integer_by_url("http", "postBuffer", &url)?, Some(100));
+    Ok(())
+}
+
+#[test]
+fn multi_values_are_accumulated_until_a_better_match_is_seen() -> crate::Result {
+    let config = config()?;
+    assert_eq!(
+        config.strings_by_url("http", "extraHeader", &url("https://git.example.com/org/repo")),
+        Some(vec![bstring("global"), bstring("host")])
+    );
+    Ok(())
+}
+
+#[test]
+fn filters_are_applied_to_sections() -> crate::Result {
+    let config = config()?;
+    let url = url("https://git.example.com/org/repo");
+    assert_eq!(
+        config.string_by_url_filter("http", "proxy", &url, |_| false),
+        None,
+        "no section passes"
+    );
+    assert_eq!(
+        config
+            .sections_by_url_filter("http", &url, |_| true)
+            .expect("sections exist")
+            .filter_map(|(section, _quality)| section.value("proxy"))
+            .collect::<Vec<_>>(),
+        ["global", "wildcard", "host", "path", "wildcard-again"],
+        "all matching sections are returned in order, irrespective of their quality"
+    );
+    Ok(())
+}
@@ -6,4 +6,5 @@ mod format;
 mod key;
 mod parse;
 mod source;
+mod url_match;
 mod value;
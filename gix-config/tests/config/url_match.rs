@@ -0,0 +1,151 @@
+use gix_config::url_match::{Match, matches, normalize};
+
+fn url(input: &str) -> gix_url::Url {
+    let mut url = gix_url::parse(input).expect("valid URL");
+    normalize(&mut url);
+    url
+}
+
+fn quality(pattern: &str, input: &str) -> Option<Match> {
+    matches(&url(pattern), &url(input))
+}
+
+mod normalize {
+    use super::url;
+
+    #[test]
+    fn default_ports_are_removed_for_http_only() {
+        assert_eq!(url("https://example.com:443/").port, None);
+        assert_eq!(url("http://example.com:80/").port, None);
+        assert_eq!(url("http://example.com:443/").port, Some(443));
+        assert_eq!(
+            url("ssh://example.com:22/path").port,
+            Some(22),
+            "git only knows HTTP ports"
+        );
+    }
+
+    #[test]
+    fn paths_are_made_absolute_and_dot_components_are_resolved() {
+        assert_eq!(url("https://example.com").path, "/");
+        assert_eq!(url("https://example.com/a/./b/../c").path, "/a/c");
+        assert_eq!(
+            url("https://example.com/a/b/..").path,
+            "/a/",
+            "dot components are directories"
+        );
+        assert_eq!(url("https://example.com/a/.").path, "/a/");
+        assert_eq!(
+            url("https://example.com/../../a").path,
+            "/a",
+            "it's not possible to leave the root"
+        );
+        assert_eq!(
+            url("https://example.com/a//b/").path,
+            "/a//b/",
+            "other paths are kept as is"
+        );
+    }
+
+    #[test]
+    fn hosts_are_lower_cased() {
+        let mut url = gix_url::Url::from_parts(
+            gix_url::Scheme::Https,
+            None,
+            None,
+            Some("Example.COM".into()),
+            None,
+            "/".into(),
+            false,
+        )
+        .expect("valid");
+        super::normalize(&mut url);
+        assert_eq!(url.host(), Some("example.com"));
+    }
+}
+
+mod matches {
+    use gix_config::url_match::Match;
+
+    use super::quality;
+
+    #[test]
+    fn scheme_host_and_port_must_be_equal() {
+        assert!(quality("https://example.com", "https://example.com/path").is_some());
+        assert!(quality("https://example.com:443", "https://example.com/path").is_some());
+        assert!(quality("http://example.com", "https://example.com/path").is_none());
+        assert!(quality("https://example.com:8080", "https://example.com/path").is_none());
+        assert!(quality("https://example.org", "https://example.com/path").is_none());
+        assert!(quality("https://com", "https://example.com/path").is_none());
+    }
+
+    #[test]
+    fn host_wildcards_match_a_single_component() {
+        assert!(quality("https://*.example.com", "https://git.example.com").is_some());
+        assert!(quality("https://*.example.com", "https://a.git.example.com").is_none());
+        assert!(quality("https://*.example.com", "https://example.com").is_none());
+        assert!(quality("https://*.*.com", "https://git.example.com").is_some());
+        assert!(quality("https://g*.example.com", "https://git.example.com").is_none());
+    }
+
+    #[test]
+    fn paths_match_by_prefix_at_component_boundaries() {
+        assert_eq!(
+            quality("https://example.com/org", "https://example.com/org/repo")
+                .expect("match")
+                .path_len,
+            5
+        );
+        assert_eq!(
+            quality("https://example.com/org/", "https://example.com/org")
+                .expect("match")
+                .path_len,
+            5,
+            "the trailing slash is implied"
+        );
+        assert!(quality("https://example.com/org", "https://example.com/organization").is_none());
+        assert!(quality("https://example.com/org/repo", "https://example.com/org").is_none());
+        assert_eq!(
+            quality("https://example.com", "https://example.com/org/repo")
+                .expect("match")
+                .path_len,
+            1
+        );
+    }
+
+    #[test]
+    fn users_must_match_if_given_in_the_pattern() {
+        assert_eq!(
+            quality("https://user@example.com", "https://user@example.com"),
+            Some(Match {
+                host_len: 11,
+                path_len: 1,
+                user_matched: true
+            })
+        );
+        assert!(quality("https://user@example.com", "https://example.com").is_none());
+        assert!(quality("https://user@example.com", "https://other@example.com").is_none());
+        assert!(
+            !quality("https://example.com", "https://user@example.com")
+                .expect("match")
+                .user_matched
+        );
+    }
+
+    #[test]
+    fn precedence_prefers_hosts_then_paths_then_users() {
+        let url = "https://user@git.example.com/org/repo";
+        let wildcard_host = quality("https://*.example.com/org/repo", url).expect("match");
+        let host = quality("https://git.example.com", url).expect("match");
+        let host_and_path = quality("https://git.example.com/org", url).expect("match");
+        let host_path_and_user = quality("https://user@git.example.com/org", url).expect("match");
+
+        assert!(wildcard_host < host, "longer hosts win over longer paths");
+        assert!(host < host_and_path);
+        assert!(host_and_path < host_path_and_user);
+        assert!(
+            Match::default() < wildcard_host,
+            "sections without URL are the weakest match"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    io::{BufRead, Read, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    process::Stdio,
+};
+
+use crate::{
+    cache::{Cache, daemon},
+    program::main::Action,
+    protocol::Context,
+};
+
+pub(super) fn invoke(cache: &Cache, action: Action, ctx: Context) -> std::io::Result<Option<Context>> {
+    let mut request = request(action.as_str(), cache);
+    ctx.write_to(&mut request)?;
+    let response = match send(&cache.socket, &request) {
+        Ok(response) => response,
+        Err(err) if is_not_listening(&err) => match action {
+            Action::Store => {
+                spawn_daemon(&cache.socket)?;
+                send(&cache.socket, &request)?
+            }
+            Action::Get | Action::Erase => return Ok(None),
+        },
+        Err(err) => return Err(err),
+    };
+    match action {
+        Action::Get if !response.is_empty() => Context::from_bytes(&response, ctx.options)
+            .map(Some)
+            .map_err(std::io::Error::other),
+        Action::Get | Action::Store | Action::Erase => Ok(None),
+    }
+}
+
+impl Cache {
+    /// Ask the daemon to exit, forgetting all credentials, just like `git credential-cache exit` would.
+    ///
+    /// It's not an error if no daemon is running.
+    pub fn exit(&self) -> std::io::Result<()> {
+        match send(&self.socket, &request("exit", self)) {
+            Err(err) if is_not_listening(&err) => Ok(()),
+            res => res.map(|_| ()),
+        }
+    }
+}
+
+fn request(action: &str, cache: &Cache) -> Vec<u8> {
+    format!("action={action}\ntimeout={}\n", cache.timeout.as_secs()).into_bytes()
+}
+
+fn send(socket: &Path, request: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.write_all(request)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    Ok(response)
+}
+
+fn is_not_listening(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+    )
+}
+
+fn spawn_daemon(socket: &Path) -> std::io::Result<()> {
+    if spawn_git_daemon(socket).unwrap_or(false) {
+        return Ok(());
+    }
+    let listener = daemon::bind(socket)?;
+    std::thread::Builder::new()
+        .name("gix-credential-cache-daemon".into())
+        .spawn(move || daemon::serve(listener))?;
+    Ok(())
+}
+
+/// Return `true` if `git credential-cache--daemon` was started and is listening on `socket`.
+fn spawn_git_daemon(socket: &Path) -> std::io::Result<bool> {
+    let mut child = std::process::Command::new(gix_path::env::exe_invocation())
+        .arg("credential-cache--daemon")
+        .arg(socket)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut line = String::new();
+    std::io::BufReader::new(child.stdout.take().expect("configured"))
+        .read_line(&mut line)
+        .ok();
+    if line == "ok\n" {
+        return Ok(true);
+    }
+    child.kill().ok();
+    child.wait()?;
+    Ok(false)
+}
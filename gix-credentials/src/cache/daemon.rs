@@ -0,0 +1,159 @@
+//! The daemon of the credential cache, compatible with `git credential-cache--daemon`.
+use std::{
+    io::{Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use bstr::ByteSlice;
+
+use crate::{
+    protocol::{Context, ContextOptions},
+    store,
+};
+
+/// The time to wait for a credential to be stored while the cache is empty before exiting.
+const WAIT_FOR_ENTRY: Duration = Duration::from_secs(30);
+/// The time to sleep between checks for new connections.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// The time a client may take to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Listen on `socket`, creating its directory with permissions for the current user only if needed,
+/// and replacing a socket that may have been left behind by a previous daemon.
+///
+/// Like `git`, an existing directory is refused if it's not owned by the current user or accessible by anyone else,
+/// and a file at `socket` that isn't a socket is never removed.
+pub fn bind(socket: &Path) -> std::io::Result<UnixListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    if let Some(dir) = socket.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        match std::fs::metadata(dir) {
+            Ok(meta) => {
+                if meta.permissions().mode() & 0o077 != 0 || !gix_sec::identity::is_path_owned_by_current_user(dir)? {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::PermissionDenied,
+                        format!(
+                            "The permissions on the socket directory '{}' are too loose, other users may be able to read the cached credentials. Consider running 'chmod 0700' on it",
+                            dir.display()
+                        ),
+                    ));
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                use std::os::unix::fs::DirBuilderExt;
+                std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    match std::fs::symlink_metadata(socket) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(socket)?,
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        // Anything else is left for binding to fail on.
+        _ => {}
+    }
+    UnixListener::bind(socket)
+}
+
+/// Serve clients connecting to `listener` until there is nothing left to cache, or until a client asks us to exit.
+///
+/// Just like `git`, an empty cache is kept around for a limited time only, and the socket is removed when done.
+pub fn serve(listener: UnixListener) -> std::io::Result<()> {
+    let socket = listener.local_addr()?.as_pathname().map(ToOwned::to_owned);
+    let res = serve_until_done(&listener);
+    if let Some(socket) = socket {
+        std::fs::remove_file(socket).ok();
+    }
+    res
+}
+
+enum Next {
+    Continue,
+    Exit,
+}
+
+struct Entry {
+    credential: Context,
+    expires_at: Instant,
+}
+
+fn serve_until_done(listener: &UnixListener) -> std::io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let mut entries = Vec::<Entry>::new();
+    let mut wait_for_entry_until = None;
+    loop {
+        let now = Instant::now();
+        entries.retain(|entry| entry.expires_at > now);
+        if entries.is_empty() {
+            if *wait_for_entry_until.get_or_insert(now + WAIT_FOR_ENTRY) <= now {
+                return Ok(());
+            }
+        } else {
+            wait_for_entry_until = None;
+        }
+        match listener.accept() {
+            Ok((stream, _addr)) => {
+                // A misbehaving client must not bring down the daemon.
+                if let Ok(Next::Exit) = handle_client(stream, &mut entries) {
+                    return Ok(());
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn handle_client(mut stream: UnixStream, entries: &mut Vec<Entry>) -> std::io::Result<Next> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request = Vec::new();
+    stream.read_to_end(&mut request)?;
+
+    let mut lines = request.splitn_str(3, "\n");
+    let action = lines.next().and_then(|line| line.strip_prefix(b"action="));
+    let timeout = lines
+        .next()
+        .and_then(|line| line.strip_prefix(b"timeout="))
+        .and_then(|timeout| timeout.to_str().ok()?.parse().ok())
+        .map(Duration::from_secs);
+    let (Some(action), Some(timeout)) = (action, timeout) else {
+        return Ok(Next::Continue);
+    };
+    let ctx = Context::from_bytes(lines.next().unwrap_or_default(), ContextOptions::default())
+        .map_err(std::io::Error::other)?;
+
+    match action {
+        b"get" => {
+            if let Some(entry) = entries
+                .iter()
+                .find(|entry| store::matches(&ctx, &entry.credential, false))
+            {
+                let credential = &entry.credential;
+                Context {
+                    username: credential.username.clone(),
+                    password: credential.password.clone(),
+                    password_expiry_utc: credential.password_expiry_utc,
+                    oauth_refresh_token: credential.oauth_refresh_token.clone(),
+                    ..Default::default()
+                }
+                .write_to(&mut stream)?;
+            }
+        }
+        b"store" if ctx.username.is_some() && ctx.password.is_some() => {
+            entries.retain(|entry| !store::matches(&ctx, &entry.credential, false));
+            entries.push(Entry {
+                credential: ctx,
+                expires_at: Instant::now() + timeout,
+            });
+        }
+        b"erase" => entries.retain(|entry| !store::matches(&ctx, &entry.credential, true)),
+        b"exit" => {
+            stream.write_all(b"\n")?;
+            return Ok(Next::Exit);
+        }
+        _ => {}
+    }
+    Ok(Next::Continue)
+}
@@ -0,0 +1,125 @@
+//! A native implementation of [`git credential-cache`](https://git-scm.com/docs/git-credential-cache),
+//! which keeps credentials in memory of a daemon that is reachable through a unix domain socket.
+//!
+//! The protocol spoken with the daemon is the one of `git`, so `git` and `gix` can share the same daemon.
+use std::{path::PathBuf, time::Duration};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{program::main::Action, protocol::Context};
+
+/// The error returned by [`Cache::from_args()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Unknown argument {arg:?}, only '--timeout <seconds>' and '--socket <path>' are supported")]
+    UnknownArgument { arg: BString },
+    #[error("The '{name}' argument needs a value")]
+    MissingValue { name: &'static str },
+    #[error("The timeout {value:?} isn't a number of seconds")]
+    InvalidTimeout { value: BString },
+}
+
+/// The credential cache, as configured with the `cache` helper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cache {
+    /// The path to the unix domain socket the daemon listens on.
+    pub socket: PathBuf,
+    /// The amount of time for which newly stored credentials are kept.
+    pub timeout: Duration,
+}
+
+/// Initialization
+impl Cache {
+    /// The time for which credentials are cached by default, 15 minutes.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(900);
+
+    /// Create a cache from the arguments of the helper definition, like `--timeout 3600` for `cache --timeout 3600`,
+    /// with the [default socket](Self::default_socket()) and [timeout](Self::DEFAULT_TIMEOUT) used for unset values.
+    pub fn from_args<'a>(args: impl IntoIterator<Item = &'a BStr>) -> Result<Self, Error> {
+        let mut args = args.into_iter();
+        let mut socket = None;
+        let mut timeout = None;
+        while let Some(arg) = args.next() {
+            let (name, value) = match arg.iter().position(|b| *b == b'=') {
+                Some(pos) => (&arg[..pos], Some(&arg[pos + 1..])),
+                None => (arg, None),
+            };
+            match name.as_bytes() {
+                b"--socket" => {
+                    let value = value
+                        .or_else(|| args.next().map(AsRef::as_ref))
+                        .ok_or(Error::MissingValue { name: "--socket" })?;
+                    socket = Some(gix_path::from_bstr(BStr::new(value)).into_owned());
+                }
+                b"--timeout" => {
+                    let value = value
+                        .or_else(|| args.next().map(AsRef::as_ref))
+                        .ok_or(Error::MissingValue { name: "--timeout" })?;
+                    let seconds = std::str::from_utf8(value)
+                        .ok()
+                        .and_then(|value| value.parse().ok())
+                        .ok_or_else(|| Error::InvalidTimeout { value: value.into() })?;
+                    timeout = Some(Duration::from_secs(seconds));
+                }
+                _ => return Err(Error::UnknownArgument { arg: arg.to_owned() }),
+            }
+        }
+        Ok(Cache {
+            socket: socket.or_else(Self::default_socket).unwrap_or_default(),
+            timeout: timeout.unwrap_or(Self::DEFAULT_TIMEOUT),
+        })
+    }
+
+    /// Return `~/.git-credential-cache/socket` if its directory exists, or `$XDG_CACHE_HOME/git/credential/socket` otherwise,
+    /// just like `git` does.
+    pub fn default_socket() -> Option<PathBuf> {
+        let home = gix_path::env::home_dir();
+        if let Some(dir) = home.as_ref().map(|home| home.join(".git-credential-cache")) {
+            if dir.is_dir() {
+                return Some(dir.join("socket"));
+            }
+        }
+        gix_path::env::var("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .or_else(|| home.map(|home| home.join(".cache")))
+            .map(|dir| dir.join("git").join("credential").join("socket"))
+    }
+}
+
+/// Access
+impl Cache {
+    /// Perform `action` with `ctx` just like `git credential-cache` would, with a signature that is compatible with
+    /// [`program::main()`](crate::program::main()).
+    ///
+    /// If no daemon is listening on the socket when credentials are stored, one is started. If `git` is installed,
+    /// `git credential-cache--daemon` is used so the cache outlives the current process. Otherwise, a [native daemon](daemon::serve())
+    /// is run on a thread of the current process.
+    ///
+    /// # Deviation
+    ///
+    /// Without `git`, credentials are only cached as long as the current process runs.
+    /// Only unix is supported, other platforms return an error.
+    pub fn invoke(&self, action: Action, ctx: Context) -> std::io::Result<Option<Context>> {
+        #[cfg(unix)]
+        {
+            client::invoke(self, action, ctx)
+        }
+        #[cfg(not(unix))]
+        {
+            _ = (action, ctx);
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "credential-cache requires unix domain sockets",
+            ))
+        }
+    }
+}
+
+#[cfg(unix)]
+mod client;
+
+///
+#[cfg(unix)]
+pub mod daemon;
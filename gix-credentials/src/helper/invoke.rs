@@ -1,6 +1,11 @@
 use std::io::Read;
 
-use crate::helper::{Action, Context, Error, NextAction, Outcome, Result};
+use bstr::{BStr, ByteSlice};
+
+use crate::{
+    helper::{Action, Context, Error, NextAction, Outcome, Result},
+    program,
+};
 
 impl Action {
     /// Send ourselves to the given `write` which is expected to be credentials-helper compatible
@@ -43,6 +48,9 @@ pub fn invoke(helper: &mut crate::Program, action: &Action) -> Result {
 }
 
 pub(crate) fn raw(helper: &mut crate::Program, action: &Action) -> std::result::Result<Option<Vec<u8>>, Error> {
+    if let Some(res) = native(helper, action) {
+        return res;
+    }
     let (mut stdin, stdout) = helper.start(action)?;
     if let (Action::Get(_), None) = (&action, &stdout) {
         panic!("BUG: `Helper` impls must return an output handle to read output from if Action::Get is provided")
@@ -69,3 +77,79 @@ pub(crate) fn raw(helper: &mut crate::Program, action: &Action) -> std::result::
         Some(stdout) => Ok(Some(stdout)),
     }
 }
+
+/// Run the `store` and `cache` helpers in-process so they work even if `git` doesn't provide them,
+/// or return `None` if `helper` isn't one of them.
+fn native(helper: &crate::Program, action: &Action) -> Option<std::result::Result<Option<Vec<u8>>, Error>> {
+    let program::Kind::ExternalName { name_and_args } = &helper.kind else {
+        return None;
+    };
+    let words = shell_words::split(name_and_args.to_str().ok()?).ok()?;
+    let (name, args) = words.split_first()?;
+    let args = args.iter().map(|arg| BStr::new(arg.as_str()));
+    let failed = |err: std::io::Error| Error::CredentialsHelperFailed { source: err };
+    let invoke: Box<dyn Fn(program::main::Action, Context) -> std::io::Result<Option<Context>>> = match name.as_str() {
+        "store" => {
+            let store = match crate::store::Store::from_args(args) {
+                Ok(store) => store,
+                Err(err) => return Some(Err(failed(std::io::Error::other(err)))),
+            };
+            Box::new(move |action, ctx| store.invoke(action, ctx))
+        }
+        "cache" => {
+            let cache = match crate::cache::Cache::from_args(args) {
+                Ok(cache) => cache,
+                Err(err) => return Some(Err(failed(std::io::Error::other(err)))),
+            };
+            Box::new(move |action, ctx| cache.invoke(action, ctx))
+        }
+        _ => return None,
+    };
+
+    let (native_action, ctx) = match action {
+        Action::Get(ctx) => (program::main::Action::Get, ctx.clone()),
+        Action::Store(input) | Action::Erase(input) => {
+            let ctx = match Context::from_bytes(input, Default::default()) {
+                Ok(ctx) => ctx,
+                Err(err) => return Some(Err(err.into())),
+            };
+            let native_action = if matches!(action, Action::Store(_)) {
+                program::main::Action::Store
+            } else {
+                program::main::Action::Erase
+            };
+            (native_action, ctx)
+        }
+    };
+    Some(
+        invoke(native_action, with_url_destructured(ctx))
+            .map(|outcome| {
+                matches!(action, Action::Get(_)).then(|| {
+                    let mut buf = Vec::new();
+                    outcome
+                        .map(|ctx| ctx.write_to(&mut buf).expect("writing to memory never fails"))
+                        .unwrap_or_default();
+                    buf
+                })
+            })
+            .map_err(failed),
+    )
+}
+
+/// Fill in fields from the `url` of `ctx` that aren't set yet, as helpers only look at the individual fields.
+fn with_url_destructured(mut ctx: Context) -> Context {
+    let Some(url) = ctx.url.take() else {
+        return ctx;
+    };
+    let mut from_url = Context {
+        url: Some(url),
+        ..Default::default()
+    };
+    if from_url.destructure_url_in_place(false).is_ok() {
+        ctx.protocol = ctx.protocol.or(from_url.protocol);
+        ctx.host = ctx.host.or(from_url.host);
+        ctx.path = ctx.path.or(from_url.path);
+        ctx.username = ctx.username.or(from_url.username);
+    }
+    ctx
+}
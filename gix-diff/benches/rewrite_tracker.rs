@@ -224,6 +224,7 @@ fn similarity_renames(count: usize) -> Scenario {
             limit: 0,
             track_empty: false,
             copies: None,
+            matching: Default::default(),
         },
         changes,
         source_tree_changes: Vec::new(),
@@ -257,6 +258,7 @@ fn exhaustive_copy_sources(count: usize) -> Scenario {
                 source: CopySource::FromSetOfModifiedFilesAndAllSources,
                 percentage: None,
             }),
+            matching: Default::default(),
         },
         changes,
         source_tree_changes,
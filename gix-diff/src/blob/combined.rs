@@ -0,0 +1,568 @@
+use bstr::ByteSlice;
+use imara_diff::{Algorithm, InternedInput};
+
+/// A set of parents as bitmask, where bit `n` is set if the parent at index `n` is contained.
+pub type Parents = u64;
+
+/// The error returned by [`diff()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Combined diffs support at most {max} parents, got {actual}", max = Parents::BITS)]
+    TooManyParents { actual: usize },
+}
+
+/// Options for use in [`diff()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The amount of lines to show around changes, just like `git diff --unified=<n>`, defaulting to 3.
+    pub context_size: u32,
+    /// If `true`, like `git diff --cc`, hunks are dropped if the result matches one of the parents in them,
+    /// so that only hunks that differ from all parents, or combine changes of multiple parents, remain.
+    /// Otherwise, like `git diff -c`, all hunks with changes to any of the parents are kept.
+    ///
+    /// It's `true` by default.
+    pub dense: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            context_size: 3,
+            dense: true,
+        }
+    }
+}
+
+/// A line in a [`Hunk`] of a combined diff.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Line<'a> {
+    /// A line of the result, which is different from all `parents`, displayed as `+` in their column.
+    ///
+    /// If `parents` is empty, it's a context line.
+    Result {
+        /// The parents that don't have this line.
+        parents: Parents,
+        /// The line without its line terminator.
+        text: &'a [u8],
+    },
+    /// A line that `parents` had, but which isn't in the result anymore, displayed as `-` in their column.
+    Removed {
+        /// The parents that had this line.
+        parents: Parents,
+        /// The line without its line terminator.
+        text: &'a [u8],
+    },
+}
+
+/// A range of lines, like `-<start>,<len>` or `+<start>,<len>` in the header of a hunk.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LineRange {
+    /// The 1-based line at which the range starts.
+    pub start: u32,
+    /// The amount of lines in the range.
+    pub len: u32,
+}
+
+/// A hunk of a combined diff, with its lines relative to all parents at once.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hunk<'a> {
+    /// The lines of each parent that this hunk covers, in the order of the parents.
+    pub parents: Vec<LineRange>,
+    /// The lines of the result that this hunk covers.
+    pub result: LineRange,
+    /// The closest line before the hunk that looks like the beginning of a function, truncated like `git` does it.
+    pub function: Option<&'a [u8]>,
+    /// The lines of the hunk.
+    pub lines: Vec<Line<'a>>,
+}
+
+impl Hunk<'_> {
+    /// Write this hunk to `out` like `git diff --cc` does, optionally with ANSI `color`.
+    pub fn write_to(&self, mut out: impl std::io::Write, color: bool) -> std::io::Result<()> {
+        let (frag, old, new, reset) = if color {
+            ("\x1b[36m", "\x1b[31m", "\x1b[32m", "\x1b[m")
+        } else {
+            ("", "", "", "")
+        };
+        let num_parents = self.parents.len();
+        let markers = "@".repeat(num_parents + 1);
+
+        write!(out, "{frag}{markers}")?;
+        for range in &self.parents {
+            write!(out, " -{},{}", range.start, range.len)?;
+        }
+        write!(out, " +{},{} {markers}", self.result.start, self.result.len)?;
+        if let Some(function) = self.function {
+            write!(out, "{reset} {reset}")?;
+            out.write_all(function)?;
+        }
+        writeln!(out, "{reset}")?;
+
+        for line in &self.lines {
+            let (color, marker, parents, text) = match *line {
+                Line::Removed { parents, text } => (old, b'-', parents, text),
+                Line::Result { parents, text } => (if parents == 0 { "" } else { new }, b'+', parents, text),
+            };
+            out.write_all(color.as_bytes())?;
+            let markers: Vec<_> = (0..num_parents)
+                .map(|idx| if parents & (1 << idx) != 0 { marker } else { b' ' })
+                .collect();
+            out.write_all(&markers)?;
+            match text.strip_suffix(b"\r") {
+                Some(text) => {
+                    out.write_all(text)?;
+                    write!(out, "{reset}\r")?;
+                }
+                None => {
+                    out.write_all(text)?;
+                    out.write_all(reset.as_bytes())?;
+                }
+            }
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// A line that was removed from some parents.
+struct Lost<'a> {
+    text: &'a [u8],
+    parents: Parents,
+}
+
+/// A line of the result, with one extra line at the end to hold lines removed after the last line.
+#[derive(Default)]
+struct ResultLine<'a> {
+    text: &'a [u8],
+    /// The parents this line was added to.
+    parents: Parents,
+    /// If `true`, this line is displayed in a hunk.
+    is_shown: bool,
+    /// If `true`, this line is just context before changes, and the lines lost before it aren't displayed.
+    no_pre_delete: bool,
+    /// The lines lost before this line, in any parent.
+    lost: Vec<Lost<'a>>,
+    /// The lines lost before this line in the parent currently being processed.
+    lost_in_parent: Vec<&'a [u8]>,
+    /// For each parent, the line number the parent is at when displaying this line, or the lines lost before it.
+    parent_line_numbers: Vec<u32>,
+}
+
+/// Compute the combined diff of the `result` relative to all of its `parents`, using `algorithm`, and configured by
+/// `options`, like `git diff --cc` and `git diff -c` do for merge commits.
+///
+/// Each parent is diffed against the result, and the hunks show all lines of the result that are different from
+/// any of the parents, along with the lines of parents that didn't make it into the result.
+/// Parents that didn't have the file, or a `result` that was deleted, are represented by empty data.
+pub fn diff<'a>(
+    algorithm: Algorithm,
+    parents: &[&'a [u8]],
+    result: &'a [u8],
+    options: Options,
+) -> Result<Vec<Hunk<'a>>, Error> {
+    let num_parents = parents.len();
+    if num_parents > Parents::BITS as usize {
+        return Err(Error::TooManyParents { actual: num_parents });
+    }
+
+    let mut lines: Vec<_> = result
+        .lines_with_terminator()
+        .map(|line| ResultLine {
+            text: line.strip_suffix(b"\n").unwrap_or(line),
+            parent_line_numbers: vec![0; num_parents],
+            ..Default::default()
+        })
+        .collect();
+    let num_lines = lines.len();
+    // One to hang the lines lost after the last line, and one to know where each parent ends.
+    for _ in 0..2 {
+        lines.push(ResultLine {
+            parent_line_numbers: vec![0; num_parents],
+            ..Default::default()
+        });
+    }
+
+    for (parent_idx, parent) in parents.iter().enumerate() {
+        match parents[..parent_idx].iter().position(|other| other == parent) {
+            Some(same_parent_idx) => reuse_parent(&mut lines, parent_idx, same_parent_idx),
+            None => diff_parent(&mut lines, algorithm, parent, result, parent_idx),
+        }
+    }
+
+    let has_hunks = make_hunks(&mut lines, num_lines, num_parents, options);
+    Ok(if has_hunks {
+        collect_hunks(&lines, num_lines, options)
+    } else {
+        Vec::new()
+    })
+}
+
+/// Diff `parent` against `result` and record which lines were added to it, and which were lost.
+fn diff_parent<'a>(
+    lines: &mut [ResultLine<'a>],
+    algorithm: Algorithm,
+    parent: &'a [u8],
+    result: &'a [u8],
+    parent_idx: usize,
+) {
+    let parent_mask: Parents = 1 << parent_idx;
+    let input = InternedInput::new(
+        crate::blob::sources::byte_lines(parent),
+        crate::blob::sources::byte_lines(result),
+    );
+    let diff = crate::blob::diff_with_slider_heuristics(algorithm, &input);
+    for hunk in diff.hunks() {
+        // Lost lines hang on the first added line, or the line after the removed ones.
+        let bucket = &mut lines[hunk.after.start as usize];
+        bucket.lost_in_parent.extend(hunk.before.clone().map(|token_idx| {
+            let line = input.interner[input.before[token_idx as usize]];
+            line.strip_suffix(b"\n").unwrap_or(line)
+        }));
+        for line in &mut lines[hunk.after.start as usize..hunk.after.end as usize] {
+            line.parents |= parent_mask;
+        }
+    }
+
+    let num_lines = lines.len() - 2;
+    let mut parent_line_number = 1;
+    for (line_idx, line) in lines[..=num_lines].iter_mut().enumerate() {
+        line.parent_line_numbers[parent_idx] = parent_line_number;
+        let lost_in_parent = std::mem::take(&mut line.lost_in_parent);
+        coalesce_lost_lines(&mut line.lost, lost_in_parent, parent_mask);
+
+        parent_line_number += line.lost.iter().filter(|lost| lost.parents & parent_mask != 0).count() as u32;
+        if line_idx < num_lines && line.parents & parent_mask == 0 {
+            parent_line_number += 1;
+        }
+    }
+    lines[num_lines + 1].parent_line_numbers[parent_idx] = parent_line_number;
+}
+
+/// Make the parent at `parent_idx` look like the parent at `same_parent_idx`, as they are the same.
+fn reuse_parent(lines: &mut [ResultLine<'_>], parent_idx: usize, same_parent_idx: usize) {
+    let (mask, same_mask): (Parents, Parents) = (1 << parent_idx, 1 << same_parent_idx);
+    for line in lines {
+        line.parent_line_numbers[parent_idx] = line.parent_line_numbers[same_parent_idx];
+        for lost in &mut line.lost {
+            if lost.parents & same_mask != 0 {
+                lost.parents |= mask;
+            }
+        }
+        if line.parents & same_mask != 0 {
+            line.parents |= mask;
+        }
+    }
+}
+
+/// Merge the lines `lost_in_parent` into the `lost` lines of all previous parents, so that the same lines
+/// lost from multiple parents are only displayed once, by finding their longest common subsequence.
+fn coalesce_lost_lines<'a>(lost: &mut Vec<Lost<'a>>, lost_in_parent: Vec<&'a [u8]>, parent_mask: Parents) {
+    if lost_in_parent.is_empty() {
+        return;
+    }
+    if lost.is_empty() {
+        lost.extend(lost_in_parent.into_iter().map(|text| Lost {
+            text,
+            parents: parent_mask,
+        }));
+        return;
+    }
+
+    #[derive(Copy, Clone)]
+    enum Direction {
+        Match,
+        Base,
+        New,
+    }
+    let (base_len, new_len) = (lost.len(), lost_in_parent.len());
+    let width = new_len + 1;
+    let mut lcs = vec![0_usize; (base_len + 1) * width];
+    let mut directions = vec![Direction::Base; (base_len + 1) * width];
+    directions[1..width].fill(Direction::New);
+    for i in 1..=base_len {
+        for j in 1..=new_len {
+            let (cell, direction) = if lost[i - 1].text == lost_in_parent[j - 1] {
+                (lcs[(i - 1) * width + j - 1] + 1, Direction::Match)
+            } else if lcs[i * width + j - 1] >= lcs[(i - 1) * width + j] {
+                (lcs[i * width + j - 1], Direction::New)
+            } else {
+                (lcs[(i - 1) * width + j], Direction::Base)
+            };
+            lcs[i * width + j] = cell;
+            directions[i * width + j] = direction;
+        }
+    }
+
+    let mut base = std::mem::take(lost).into_iter().rev();
+    let mut new = lost_in_parent.into_iter().rev();
+    let mut merged = Vec::with_capacity(base_len + new_len);
+    let (mut i, mut j) = (base_len, new_len);
+    while i != 0 || j != 0 {
+        match directions[i * width + j] {
+            Direction::Match => {
+                let mut line = base.next().expect("in bounds");
+                line.parents |= parent_mask;
+                merged.push(line);
+                new.next();
+                i -= 1;
+                j -= 1;
+            }
+            Direction::New => {
+                merged.push(Lost {
+                    text: new.next().expect("in bounds"),
+                    parents: parent_mask,
+                });
+                j -= 1;
+            }
+            Direction::Base => {
+                merged.push(base.next().expect("in bounds"));
+                i -= 1;
+            }
+        }
+    }
+    merged.reverse();
+    *lost = merged;
+}
+
+fn is_interesting(line: &ResultLine<'_>) -> bool {
+    line.parents != 0 || !line.lost.is_empty()
+}
+
+/// Mark all lines that are to be shown, and return `true` if there is any.
+fn make_hunks(lines: &mut [ResultLine<'_>], num_lines: usize, num_parents: usize, options: Options) -> bool {
+    let all_parents: Parents = if num_parents == Parents::BITS as usize {
+        Parents::MAX
+    } else {
+        (1 << num_parents) - 1
+    };
+    for line in lines[..=num_lines].iter_mut() {
+        line.is_shown = is_interesting(line);
+    }
+    if !options.dense {
+        return give_context(lines, num_lines, options.context_size as usize);
+    }
+
+    // Look at each hunk, and if the result matches one of the parents, i.e. all changes
+    // are relative to the same parents, and not all of them, it's not interesting.
+    let context = options.context_size as usize;
+    let mut i = 0;
+    while i <= num_lines {
+        while i <= num_lines && !lines[i].is_shown {
+            i += 1;
+        }
+        if num_lines < i {
+            break;
+        }
+        let hunk_begin = i;
+        let mut j = i + 1;
+        while j <= num_lines {
+            if !lines[j].is_shown {
+                // Look beyond the end to see if there is an interesting line within context.
+                let mut lookahead = adjust_hunk_tail(lines, hunk_begin, j);
+                lookahead = (lookahead + context).min(num_lines + 1);
+                let mut continues = false;
+                while lookahead != 0 && j < lookahead {
+                    lookahead -= 1;
+                    if lines[lookahead].is_shown {
+                        continues = true;
+                        break;
+                    }
+                }
+                if !continues {
+                    break;
+                }
+                j = lookahead;
+            }
+            j += 1;
+        }
+        let hunk_end = j;
+
+        let mut same_diff: Parents = 0;
+        let mut has_interesting = false;
+        'lines: for line in &lines[i..hunk_end] {
+            let diffs = (line.parents != 0)
+                .then_some(line.parents)
+                .into_iter()
+                .chain(line.lost.iter().map(|lost| lost.parents));
+            for this_diff in diffs {
+                if same_diff == 0 {
+                    same_diff = this_diff;
+                } else if same_diff != this_diff {
+                    has_interesting = true;
+                    break 'lines;
+                }
+            }
+        }
+        if !has_interesting && same_diff != all_parents {
+            for line in &mut lines[hunk_begin..hunk_end] {
+                line.is_shown = false;
+            }
+        }
+        i = hunk_end;
+    }
+    give_context(lines, num_lines, context)
+}
+
+/// `i` points at the first uninteresting line. If the last line of the hunk was only interesting
+/// because of lost lines before it, it's already a context line, so pull the end of the hunk in by one.
+fn adjust_hunk_tail(lines: &[ResultLine<'_>], hunk_begin: usize, i: usize) -> usize {
+    if hunk_begin < i && lines[i - 1].parents == 0 {
+        i - 1
+    } else {
+        i
+    }
+}
+
+/// Return the index of the first line at or after `i` that is shown, or not shown if `look_for_hidden` is `true`.
+fn find_next(lines: &[ResultLine<'_>], mut i: usize, num_lines: usize, look_for_hidden: bool) -> usize {
+    while i <= num_lines {
+        if lines[i].is_shown != look_for_hidden {
+            return i;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Show context lines around the lines that are shown, and connect groups of shown lines if their gap is small.
+/// Return `true` if any line is shown.
+fn give_context(lines: &mut [ResultLine<'_>], num_lines: usize, context: usize) -> bool {
+    let mut i = find_next(lines, 0, num_lines, false);
+    if num_lines < i {
+        return false;
+    }
+
+    while i <= num_lines {
+        // Show a few lines before the first shown line.
+        for line in &mut lines[i.saturating_sub(context)..i] {
+            if !line.is_shown {
+                line.no_pre_delete = true;
+            }
+            line.is_shown = true;
+        }
+
+        loop {
+            let mut j = find_next(lines, i, num_lines, true);
+            if num_lines < j {
+                return true;
+            }
+            let k = find_next(lines, j, num_lines, false);
+            j = adjust_hunk_tail(lines, i, j);
+
+            if k < j + context {
+                // The gap between `j` and the next shown line `k` is small, so show all of it.
+                for line in &mut lines[j..k] {
+                    line.is_shown = true;
+                }
+                i = k;
+                continue;
+            }
+
+            // Show a few lines after the last shown line.
+            i = k;
+            for line in &mut lines[j..(j + context).min(num_lines + 1)] {
+                line.is_shown = true;
+            }
+            break;
+        }
+    }
+    true
+}
+
+/// Return the hunks made of all lines that are shown.
+fn collect_hunks<'a>(lines: &[ResultLine<'a>], num_lines: usize, options: Options) -> Vec<Hunk<'a>> {
+    let num_parents = lines[0].parent_line_numbers.len();
+    let mut hunks = Vec::new();
+    let mut line_idx = 0;
+    loop {
+        let mut function = None;
+        while line_idx <= num_lines && !lines[line_idx].is_shown {
+            if looks_like_function(lines[line_idx].text) {
+                function = Some(lines[line_idx].text);
+            }
+            line_idx += 1;
+        }
+        if num_lines < line_idx {
+            break;
+        }
+        let hunk_end = (line_idx + 1..=num_lines)
+            .find(|idx| !lines[*idx].is_shown)
+            .unwrap_or(num_lines + 1);
+        let mut result_len = hunk_end - line_idx;
+        if num_lines < hunk_end {
+            // The last line only holds the lines lost after the last line of the result.
+            result_len -= 1;
+        }
+        let mut null_context = 0;
+        if options.context_size == 0 {
+            // Lines which only hang the lost lines in front of them aren't shown. Unlike `git`, don't count
+            // the line past the end, as it was already taken into account.
+            null_context = lines[line_idx..hunk_end.min(num_lines)]
+                .iter()
+                .filter(|line| line.parents == 0)
+                .count();
+            result_len -= null_context;
+        }
+
+        let mut hunk = Hunk {
+            parents: (0..num_parents)
+                .map(|parent_idx| {
+                    let start = lines[line_idx].parent_line_numbers[parent_idx];
+                    LineRange {
+                        start,
+                        len: lines[hunk_end].parent_line_numbers[parent_idx] - start - null_context as u32,
+                    }
+                })
+                .collect(),
+            result: LineRange {
+                start: line_idx as u32 + 1,
+                len: result_len as u32,
+            },
+            function: function.and_then(truncate_function),
+            lines: Vec::new(),
+        };
+        while line_idx < hunk_end {
+            let line = &lines[line_idx];
+            line_idx += 1;
+            if !line.no_pre_delete {
+                hunk.lines.extend(line.lost.iter().map(|lost| Line::Removed {
+                    parents: lost.parents,
+                    text: lost.text,
+                }));
+            }
+            if num_lines < line_idx {
+                break;
+            }
+            if line.parents == 0 && options.context_size == 0 {
+                continue;
+            }
+            hunk.lines.push(Line::Result {
+                parents: line.parents,
+                text: line.text,
+            });
+        }
+        hunks.push(hunk);
+    }
+    hunks
+}
+
+fn looks_like_function(line: &[u8]) -> bool {
+    line.first()
+        .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_' || *b == b'$')
+}
+
+/// Truncate `line` to at most 40 bytes, and just like `git`, cut off its last non-whitespace character, along with
+/// all trailing whitespace.
+fn truncate_function(line: &[u8]) -> Option<&[u8]> {
+    let end = line
+        .iter()
+        .take(40)
+        .take_while(|b| **b != 0)
+        .enumerate()
+        .filter(|(_, b)| !b" \t\n\r".contains(b))
+        .map(|(idx, _)| idx)
+        .last()
+        .unwrap_or(0);
+    (end != 0).then(|| &line[..end])
+}
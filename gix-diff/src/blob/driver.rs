@@ -0,0 +1,227 @@
+use bstr::{BStr, ByteSlice};
+
+use crate::blob::{
+    Driver,
+    funcname::{Pattern, Syntax},
+};
+
+/// A diff driver that `git` knows without configuration, as defined in its `userdiff.c`.
+struct Builtin {
+    name: &'static str,
+    funcname: &'static str,
+    ignore_case: bool,
+    word_regex: &'static str,
+}
+
+/// Built-in drivers
+impl Driver {
+    /// Return the built-in driver named `name`, like `rust` or `python`, which `git` uses if `diff=<name>` is set
+    /// via git-attributes. Configuration of a driver with the same name is expected to override its values.
+    ///
+    /// Built-in drivers have a [function name pattern](Driver::funcname) and a [word regex](Driver::word_regex),
+    /// where the latter is translated into the syntax of the `regex` crate.
+    pub fn builtin(name: &BStr) -> Option<Driver> {
+        BUILTIN
+            .binary_search_by(|builtin| builtin.name.as_bytes().as_bstr().cmp(name))
+            .ok()
+            .map(|idx| BUILTIN[idx].to_driver())
+    }
+
+    /// Return all built-in drivers, see [`builtin()`](Self::builtin()).
+    pub fn builtins() -> impl Iterator<Item = Driver> {
+        BUILTIN.iter().map(Builtin::to_driver)
+    }
+}
+
+impl Builtin {
+    fn to_driver(&self) -> Driver {
+        Driver {
+            name: self.name.into(),
+            funcname: Some(Pattern {
+                pattern: self.funcname.into(),
+                syntax: Syntax::Extended,
+                ignore_case: self.ignore_case,
+            }),
+            word_regex: Some(self.word_regex.as_bytes().as_bstr().into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// All built-in drivers, sorted by name.
+const BUILTIN: &[Builtin] = &[
+    Builtin {
+        name: "ada",
+        funcname: "!^(.*[ \t])?(is[ \t]+new|renames|is[ \t]+separate)([ \t].*)?$\n\
+        !^[ \t]*with[ \t].*$\n\
+        ^[ \t]*((procedure|function)[ \t]+.*)$\n\
+        ^[ \t]*((package|protected|task)[ \t]+.*)$",
+        ignore_case: true,
+        word_regex: "[a-zA-Z][a-zA-Z0-9_]*|[-+]?[0-9][0-9#_.aAbBcCdDeEfF]*([eE][+-]?[0-9_]+)?|=>|\\.\\.|\\*\\*|:=|/=|>=|<=|<<|>>|<>|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "bash",
+        funcname: "^[ \t]*(([a-zA-Z_][a-zA-Z0-9_]*[ \t]*\\([ \t]*\\))|(function[ \t]+[a-zA-Z_][a-zA-Z0-9_]*(([ \t]*\\([ \t]*\\))|([ \t]+)))[ \t]*(\\{|\\(\\(?|\\[\\[))",
+        ignore_case: false,
+        word_regex: "[^ \t]+|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "bibtex",
+        funcname: "(@[a-zA-Z]{1,}[ \t]*\\{{0,1}[ \t]*[^ \t\"@',\\#}{~%]*).*$",
+        ignore_case: false,
+        word_regex: "[={}\"]|[^={}\" \t]+|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "cpp",
+        funcname: "!^[ \t]*[A-Za-z_][A-Za-z_0-9]*:[[:space:]]*($|/[/*])\n\
+        ^((::[[:space:]]*)?[A-Za-z_].*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[0-9][0-9.]*([Ee][-+]?[0-9]+)?[fFlLuU]*|0[xXbB][0-9a-fA-F]+[lLuU]*|\\.[0-9][0-9]*([Ee][-+]?[0-9]+)?[fFlL]?|[-+*/<>%&^|=!]=|--|\\+\\+|<<=?|>>=?|&&|\\|\\||::|->\\*?|\\.\\*|<=>|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "csharp",
+        funcname: "!^[ \t]*(do|while|for|if|else|instanceof|new|return|switch|case|throw|catch|using)\n\
+        ^[ \t]*(((static|public|internal|private|protected|new|virtual|sealed|override|unsafe|async)[ \t]+)*[][<>@.~_[:alnum:]]+[ \t]+[<>@._[:alnum:]]+[ \t]*\\(.*\\))[ \t]*$\n\
+        ^[ \t]*(((static|public|internal|private|protected|new|virtual|sealed|override|unsafe)[ \t]+)*[][<>@.~_[:alnum:]]+[ \t]+[@._[:alnum:]]+)[ \t]*$\n\
+        ^[ \t]*(((static|public|internal|private|protected|new|unsafe|sealed|abstract|partial)[ \t]+)*(class|enum|interface|struct|record)[ \t]+.*)$\n\
+        ^[ \t]*(namespace[ \t]+.*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+[fFlL]?|0[xXbB]?[0-9a-fA-F]+[lL]?|[-+*/<>%&^|=!]=|--|\\+\\+|<<=?|>>=?|&&|\\|\\||::|->|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "css",
+        funcname: "![:;][[:space:]]*$\n\
+        ^[:[@.#]?[_a-z0-9].*$",
+        ignore_case: true,
+        word_regex: "-?[_a-zA-Z][-_a-zA-Z0-9]*|-?[0-9]+|\\#[0-9a-fA-F]+|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "dts",
+        funcname: "!;\n\
+        !=\n\
+        ^[ \t]*((/[ \t]*\\{|&?[a-zA-Z_]).*)",
+        ignore_case: false,
+        word_regex: "[a-zA-Z0-9,._+?#-]+|[-+*/%&^|!~]|>>|<<|&&|\\|\\||[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "elixir",
+        funcname: "^[ \t]*((def(macro|module|impl|protocol|p)?|test)[ \t].*)$",
+        ignore_case: false,
+        word_regex: "[@:]?[a-zA-Z0-9@_?!]+|[-+]?0[xob][0-9a-fA-F]+|[-+]?[0-9][0-9_.]*([eE][-+]?[0-9_]+)?|:?(\\+\\+|--|\\.\\.|~~~|<>|\\^\\^\\^|<?\\|>|<<<?|>?>>|<<?~|~>?>|<~>|<=|>=|===?|!==?|=~|&&&?|\\|\\|\\|?|=>|<-|\\\\\\\\|->)|:?%[A-Za-z0-9_.]\\{\\}?|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "fortran",
+        funcname: "!^([C*]|[ \t]*!)\n\
+        !^[ \t]*MODULE[ \t]+PROCEDURE[ \t]\n\
+        ^[ \t]*((END[ \t]+)?(PROGRAM|MODULE|BLOCK[ \t]+DATA|([^!'\" \t]+[ \t]+)*(SUBROUTINE|FUNCTION))[ \t]+[A-Z].*)$",
+        ignore_case: true,
+        word_regex: "[a-zA-Z][a-zA-Z0-9_]*|\\.([Ee][Qq]|[Nn][Ee]|[Gg][TtEe]|[Ll][TtEe]|[Tt][Rr][Uu][Ee]|[Ff][Aa][Ll][Ss][Ee]|[Aa][Nn][Dd]|[Oo][Rr]|[Nn]?[Ee][Qq][Vv]|[Nn][Oo][Tt])\\.|[-+]?[0-9.]+([AaIiDdEeFfLlTtXx][Ss]?[-+]?[0-9.]*)?(_[a-zA-Z0-9][a-zA-Z0-9_]*)?|//|\\*\\*|::|[/<>=]=|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "fountain",
+        funcname: "^((\\.[^.]|(int|ext|est|int\\.?/ext|i/e)[. ]).*)$",
+        ignore_case: true,
+        word_regex: "[^ \t-]+|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "golang",
+        funcname: "^[ \t]*(func[ \t]*.*(\\{[ \t]*)?)\n\
+        ^[ \t]*(type[ \t].*(struct|interface)[ \t]*(\\{[ \t]*)?)",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.eE]+i?|0[xX]?[0-9a-fA-F]+i?|[-+*/<>%&^|=!:]=|--|\\+\\+|<<=?|>>=?|&\\^=?|&&|\\|\\||<-|\\.{3}|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "html",
+        funcname: "^[ \t]*(<[Hh][1-6]([ \t].*)?>.*)$",
+        ignore_case: false,
+        word_regex: "[^<>= \t]+|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "java",
+        funcname: "!^[ \t]*(catch|do|for|if|instanceof|new|return|switch|throw|while)\n\
+        ^[ \t]*(([a-z]+[ \t]+)*(class|enum|interface)[ \t]+[A-Za-z][A-Za-z0-9_$]*[ \t]+.*)$\n\
+        ^[ \t]*(([A-Za-z_<>&][][?&<>.,A-Za-z_0-9]*[ \t]+)+[A-Za-z_][A-Za-z_0-9]*[ \t]*\\([^;]*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+[fFlL]?|0[xXbB]?[0-9a-fA-F]+[lL]?|[-+*/<>%&^|=!]=|--|\\+\\+|<<=?|>>>?=?|&&|\\|\\||[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "kotlin",
+        funcname: "^[ \t]*(([a-z]+[ \t]+)*(fun|class|interface)[ \t]+.*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|0[xXbB][0-9a-fA-F_]+[lLuU]*|[0-9][0-9_]*([.][0-9_]*)?([Ee][-+]?[0-9]+)?[fFlLuU]*|[.][0-9][0-9_]*([Ee][-+]?[0-9]+)?[fFlLuU]?|[-+*/<>%&^|=!]==?|--|\\+\\+|<<=|>>=|&&|\\|\\||->|\\.\\*|!!|[?:.][.:]|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "markdown",
+        funcname: "^ {0,3}#{1,6}[ \t].*",
+        ignore_case: false,
+        word_regex: "[^<>= \t]+|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "matlab",
+        funcname: "^[[:space:]]*((classdef|function)[[:space:]].*)$|^(%%%?|##)[[:space:]].*$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+|[=~<>]=|\\.[*/\\^']|\\|\\||&&|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "objc",
+        funcname: "!^[ \t]*(do|for|if|else|return|switch|while)\n\
+        ^[ \t]*([-+][ \t]*\\([ \t]*[A-Za-z_][A-Za-z_0-9* \t]*\\)[ \t]*[A-Za-z_].*)$\n\
+        ^[ \t]*(([A-Za-z_][A-Za-z_0-9]*[ \t]+)+[A-Za-z_][A-Za-z_0-9]*[ \t]*\\([^;]*)$\n\
+        ^(@(implementation|interface|protocol)[ \t].*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+[fFlL]?|0[xXbB]?[0-9a-fA-F]+[lL]?|[-+*/<>%&^|=!]=|--|\\+\\+|<<=?|>>=?|&&|\\|\\||::|->|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "pascal",
+        funcname: "^(((class[ \t]+)?(procedure|function)|constructor|destructor|interface|implementation|initialization|finalization)[ \t]*.*)$\n\
+        ^(.*=[ \t]*(class|record).*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+|0[xXbB]?[0-9a-fA-F]+|<>|<=|>=|:=|\\.\\.|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "perl",
+        funcname: "^package .*\n\
+        ^sub [[:alnum:]_':]+[ \t]*(\\([^)]*\\)[ \t]*)?(:[^;#]*)?(\\{[ \t]*)?(#.*)?$\n\
+        ^(BEGIN|END|INIT|CHECK|UNITCHECK|AUTOLOAD|DESTROY)[ \t]*(\\{[ \t]*)?(#.*)?$\n\
+        ^=head[0-9] .*",
+        ignore_case: false,
+        word_regex: "[[:alpha:]_'][[:alnum:]_']*|0[xb]?[0-9a-fA-F_]*|[0-9a-fA-F_]+(\\.[0-9a-fA-F_]+)?([eE][-+]?[0-9_]+)?|=>|-[rwxoRWXOezsfdlpSugkbctTBMAC>]|~~|::|&&=|\\|\\|=|//=|\\*\\*=|&&|\\|\\||//|\\+\\+|--|\\*\\*|\\.\\.\\.?|[-+*/%.^&<>=!|]=|=~|!~|<<|<>|<=>|>>|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "php",
+        funcname: "^[\t ]*(((public|protected|private|static|abstract|final)[\t ]+)*function.*)$\n\
+        ^[\t ]*((((final|abstract)[\t ]+)?class|enum|interface|trait).*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+|0[xXbB]?[0-9a-fA-F]+|[-+*/<>%&^|=!.]=|--|\\+\\+|<<=?|>>=?|===|&&|\\|\\||::|->|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "python",
+        funcname: "^[ \t]*((class|(async[ \t]+)?def)[ \t].*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+[jJlL]?|0[xX]?[0-9a-fA-F]+[lL]?|[-+*/<>%&^|=!]=|//=?|<<=?|>>=?|\\*\\*=?|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "ruby",
+        funcname: "^[ \t]*((class|module|def)[ \t].*)$",
+        ignore_case: false,
+        word_regex: "(@|@@|\\$)?[a-zA-Z_][a-zA-Z0-9_]*|[-+0-9.e]+|0[xXbB]?[0-9a-fA-F]+|\\?(\\\\C-)?(\\\\M-)?.|//=?|[-+*/<>%&^|=!]=|<<=?|>>=?|===|\\.{1,3}|::|[!=]~|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "rust",
+        funcname: "^[\t ]*((pub(\\([^\\)]+\\))?[\t ]+)?((async|const|unsafe|extern([\t ]+\"[^\"]+\"))[\t ]+)?(struct|enum|union|mod|trait|fn|impl|macro_rules!)[< \t]+[^;]*)$",
+        ignore_case: false,
+        word_regex: "[a-zA-Z_][a-zA-Z0-9_]*|[0-9][0-9_a-fA-Fiosuxz]*(\\.([0-9]*[eE][+-]?)?[0-9_fF]*)?|[-+*\\/<>%&^|=!:]=|<<=?|>>=?|&&|\\|\\||->|=>|\\.{2}=|\\.{3}|::|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "scheme",
+        funcname: "^[\t ]*(\\(((define|def(struct|syntax|class|method|rules|record|proto|alias)?)[-*/ \t]|(library|module|struct|class)[*+ \t]).*)$",
+        ignore_case: false,
+        word_regex: "\\|([^\\\\]*)\\||([^\\]\\[)(}{\\[ \t])+|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+    Builtin {
+        name: "tex",
+        funcname: "^(\\\\((sub)*section|chapter|part)\\*{0,1}\\{.*)$",
+        ignore_case: false,
+        word_regex: "\\\\[a-zA-Z@]+|\\\\.|[a-zA-Z0-9\\x80-\\xff]+|[^[:space:]]|[\\xc0-\\xff][\\x80-\\xbf]+",
+    },
+];
@@ -0,0 +1,202 @@
+use std::{iter::Peekable, str::Chars};
+
+use bstr::{BStr, BString, ByteSlice};
+
+/// The flavor of regular expressions used in a [`Pattern`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Syntax {
+    /// POSIX basic regular expressions, as used by `diff.<driver>.funcname`.
+    Basic,
+    /// POSIX extended regular expressions, as used by `diff.<driver>.xfuncname` and by all built-in drivers.
+    #[default]
+    Extended,
+}
+
+/// The regular expressions to find lines that start a function, like `diff.<driver>.xfuncname`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    /// One regular expression per line, where lines starting with `!` are negated, i.e. lines they match
+    /// are never considered to start a function.
+    ///
+    /// The first regular expression that matches determines the outcome. If it isn't negated, its first capture group
+    /// is the function name, or the whole match if there is no such group.
+    pub pattern: BString,
+    /// The flavor of regular expressions of `pattern`.
+    pub syntax: Syntax,
+    /// If `true`, letters are matched regardless of their case.
+    pub ignore_case: bool,
+}
+
+/// A way to find lines that start a function, and to extract the function name that is shown in hunk headers,
+/// just like `git` does.
+///
+/// Its default implementation uses `git`'s heuristic of treating all lines as function lines that start with
+/// a letter, `_` or `$`, which is used if a file doesn't have a diff driver with a [`Pattern`].
+#[derive(Default, Debug, Clone)]
+pub struct Matcher {
+    /// Each regular expression along with a flag indicating whether it's negated, or empty for the default heuristic.
+    regexes: Vec<(bool, regex::bytes::Regex)>,
+}
+
+///
+pub mod matcher {
+    use bstr::BString;
+
+    /// The error returned by [Matcher::new()](super::Matcher::new()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error("The function name pattern '{pattern}' isn't valid UTF-8")]
+        Utf8 { pattern: BString },
+        #[error("Could not compile the function name pattern '{pattern}'")]
+        Compile { pattern: BString, source: regex::Error },
+    }
+}
+
+impl Matcher {
+    /// Compile all regular expressions in `pattern`.
+    ///
+    /// Note that POSIX regular expressions are translated into the syntax of the `regex` crate, which prefers the
+    /// first matching alternative instead of the longest one, but otherwise is compatible for all practical purposes.
+    pub fn new(pattern: &Pattern) -> Result<Self, matcher::Error> {
+        let pattern_str = pattern.pattern.to_str().map_err(|_| matcher::Error::Utf8 {
+            pattern: pattern.pattern.clone(),
+        })?;
+        let regexes = pattern_str
+            .split('\n')
+            .map(|line| {
+                let (negated, line) = line.strip_prefix('!').map_or((false, line), |line| (true, line));
+                regex::bytes::RegexBuilder::new(&translate(line, pattern.syntax))
+                    .unicode(false)
+                    .case_insensitive(pattern.ignore_case)
+                    .build()
+                    .map(|regex| (negated, regex))
+                    .map_err(|err| matcher::Error::Compile {
+                        pattern: line.into(),
+                        source: err,
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Matcher { regexes })
+    }
+
+    /// Return the function name if `line` starts a function, or `None` otherwise.
+    ///
+    /// The returned name isn't truncated, and may still have trailing whitespace, including the newline of `line`.
+    pub fn function_name<'a>(&self, line: &'a [u8]) -> Option<&'a BStr> {
+        if self.regexes.is_empty() {
+            return line
+                .first()
+                .is_some_and(|&b| b.is_ascii_alphabetic() || b == b'_' || b == b'$')
+                .then(|| line.as_bstr());
+        }
+        let line = line
+            .strip_suffix(b"\r\n")
+            .or_else(|| line.strip_suffix(b"\n"))
+            .unwrap_or(line);
+        let (negated, captures) = self
+            .regexes
+            .iter()
+            .find_map(|(negated, regex)| Some((*negated, regex.captures(line)?)))?;
+        if negated {
+            return None;
+        }
+        let name = captures
+            .get(1)
+            .or_else(|| captures.get(0))
+            .expect("the whole match is always present");
+        Some(name.as_bytes().as_bstr())
+    }
+
+    /// Return `true` if `line` starts a function.
+    pub fn is_match(&self, line: &[u8]) -> bool {
+        self.function_name(line).is_some()
+    }
+}
+
+/// Translate the POSIX regular expression `pattern` of `syntax` into the syntax of the `regex` crate.
+///
+/// In bracket expressions, POSIX treats `[`, `\` and a leading `]` literally, while basic regular expressions
+/// use `\(`, `\)`, `\{`, `\}`, `\|`, `\+` and `\?` as operators and their unescaped characters as literals.
+fn translate(pattern: &str, syntax: Syntax) -> String {
+    let basic = syntax == Syntax::Basic;
+    let mut out = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    // In basic regular expressions, a `*` is a literal if there is nothing to repeat.
+    let mut at_start = true;
+    while let Some(c) = chars.next() {
+        let mut starts_expression = false;
+        match c {
+            '[' => translate_bracket(&mut chars, &mut out),
+            '\\' => match chars.next() {
+                Some(op @ ('(' | '|')) if basic => {
+                    out.push(op);
+                    starts_expression = true;
+                }
+                Some(op @ (')' | '{' | '}' | '+' | '?')) if basic => out.push(op),
+                Some(c) => {
+                    out.push('\\');
+                    out.push(c);
+                }
+                None => out.push_str(r"\\"),
+            },
+            '(' | ')' | '{' | '}' | '|' | '+' | '?' if basic => {
+                out.push('\\');
+                out.push(c);
+            }
+            '*' if basic && at_start => out.push_str(r"\*"),
+            '^' if at_start => {
+                out.push(c);
+                starts_expression = true;
+            }
+            c => out.push(c),
+        }
+        at_start = starts_expression;
+    }
+    out
+}
+
+/// Translate the bracket expression in `chars` right after its opening `[` into `out`.
+fn translate_bracket(chars: &mut Peekable<Chars<'_>>, out: &mut String) {
+    out.push('[');
+    if chars.next_if_eq(&'^').is_some() {
+        out.push('^');
+    }
+    let mut first = true;
+    while let Some(c) = chars.next() {
+        match c {
+            ']' if !first => {
+                out.push(']');
+                return;
+            }
+            '[' if matches!(chars.peek(), Some(':' | '.' | '=')) => {
+                let kind = chars.next().expect("peeked");
+                let mut name = String::new();
+                while let Some(c) = chars.next() {
+                    if c == kind && chars.next_if_eq(&']').is_some() {
+                        break;
+                    }
+                    name.push(c);
+                }
+                if kind == ':' {
+                    out.push_str("[:");
+                    out.push_str(&name);
+                    out.push_str(":]");
+                } else {
+                    // Collating elements and equivalence classes are only supported for single characters.
+                    name.chars().for_each(|c| push_bracket_literal(c, out));
+                }
+            }
+            '-' if !first && chars.peek().is_some_and(|&c| c != ']') => out.push('-'),
+            c => push_bracket_literal(c, out),
+        }
+        first = false;
+    }
+}
+
+fn push_bracket_literal(c: char, out: &mut String) {
+    if matches!(c, '[' | ']' | '\\' | '-' | '^' | '&' | '~') {
+        out.push('\\');
+    }
+    out.push(c);
+}
@@ -9,6 +9,20 @@ pub use imara_diff::*;
 pub mod unified_diff;
 pub use unified_diff::impls::UnifiedDiff;
 
+/// Facilities to refine the hunks of a unified diff into spans of removed and added words.
+pub mod word_diff;
+
+/// Facilities to compute the diff of a merge result relative to all of its parents at once, like `git diff --cc` does.
+pub mod combined;
+
+/// Facilities to detect blocks of removed lines that were added elsewhere, like `git diff --color-moved` does.
+pub mod moved;
+
+/// Facilities to find lines that start a function, to show the function a hunk is in like `git` does.
+pub mod funcname;
+
+mod driver;
+
 /// Compute a diff with Git's slider heuristics to produce more intuitive diffs.
 ///
 /// This function uses [`Diff`] from `imara-diff`
@@ -43,6 +57,9 @@ pub mod pipeline;
 ///
 pub mod platform;
 
+///
+pub mod whitespace;
+
 /// Information about the diff performed to detect similarity.
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 pub struct DiffLineStats {
@@ -83,6 +100,12 @@ pub struct Driver {
     pub command: Option<BString>,
     /// The per-driver algorithm to use.
     pub algorithm: Option<Algorithm>,
+    /// The regular expression that determines what's a word in word diffs, which can be compiled with
+    /// [`WordRegex::new()`](word_diff::WordRegex::new()).
+    pub word_regex: Option<BString>,
+    /// The regular expressions to find lines that start a function, for showing function names in hunk headers,
+    /// which can be compiled with [`Matcher::new()`](funcname::Matcher::new()).
+    pub funcname: Option<funcname::Pattern>,
     /// The external filter program to call like `<binary_to_text_command> /path/to/blob` which outputs a textual version of the provided
     /// binary file.
     /// Note that it's invoked with a shell if arguments are given.
@@ -0,0 +1,601 @@
+//! Use a [`Detector`](crate::blob::moved::Detector) as delegate of a [`UnifiedDiff`](crate::blob::UnifiedDiff)
+//! for each diffed file in turn to collect their hunks, to then [detect](crate::blob::moved::Detector::detect()) moved
+//! lines within and across all of them.
+//! Moved lines are available as [structured data](crate::blob::moved::Detector::blocks()), and can be
+//! [rendered](crate::blob::moved::Detector::render_file()) in color just like `git` does.
+use std::ops::Range;
+
+use bstr::{BStr, ByteSlice, ByteVec};
+
+use crate::blob::{
+    unified_diff::{ConsumeBinaryHunkDelegate, ConsumeHunk, DiffLineKind, HunkHeader},
+    whitespace,
+};
+
+/// The way moved lines are grouped into blocks and displayed, like `--color-moved=<mode>` in `git diff`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Each removed line that was added elsewhere is considered moved, and vice versa, no matter how short it is.
+    Plain,
+    /// Moved lines are grouped into blocks of consecutive lines that were moved together, and blocks with fewer than
+    /// 20 alphanumeric characters are not considered moved.
+    Blocks,
+    /// Like [`Blocks`](Mode::Blocks), but adjacent blocks are distinguished by [alternating](Moved::alternate) between two colors.
+    #[default]
+    Zebra,
+    /// Like [`Zebra`](Mode::Zebra), but lines within blocks are [dimmed](Moved::dimmed), so that only the boundaries
+    /// of blocks stand out.
+    DimmedZebra,
+}
+
+/// Options to control how whitespace is handled when finding moved lines, like `--color-moved-ws=<modes>` in `git diff`.
+///
+/// Note that these don't affect the diff itself.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Whitespace {
+    /// Ignore whitespace when comparing lines, like `ignore-all-space`.
+    pub ignore_all_space: bool,
+    /// Ignore changes in the amount of whitespace, like `ignore-space-change`.
+    pub ignore_space_change: bool,
+    /// Ignore changes in whitespace at the end of lines, like `ignore-space-at-eol`.
+    pub ignore_space_at_eol: bool,
+    /// Ignore the indentation of lines, as long as it changed by the same amount for all lines of a block,
+    /// like `allow-indentation-change`.
+    ///
+    /// `git` refuses to combine this with other options, which is why these are ignored if this one is set.
+    pub allow_indentation_change: bool,
+}
+
+/// Options for use in [`Detector::detect()`].
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The way moved lines are grouped into blocks.
+    pub mode: Mode,
+    /// How to handle whitespace when comparing lines.
+    pub whitespace: Whitespace,
+}
+
+/// Information about a moved line.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Moved {
+    /// If `true`, the line is part of a block that directly follows another block of moved lines of the same kind,
+    /// and should be displayed in an alternative color.
+    ///
+    /// This is only set in [`Mode::Zebra`] and [`Mode::DimmedZebra`].
+    pub alternate: bool,
+    /// If `true`, the line isn't at the boundary between adjacent blocks and can be displayed less prominently.
+    ///
+    /// This is only set in [`Mode::DimmedZebra`].
+    pub dimmed: bool,
+}
+
+/// A removed or added line of a hunk, or a context line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Line {
+    /// The kind of line.
+    pub kind: DiffLineKind,
+    /// If this is a removed or added line, information about its move or `None` if it wasn't moved.
+    pub moved: Option<Moved>,
+    /// The range of the text of the line in the buffer of the [`Detector`].
+    text: Range<usize>,
+}
+
+/// A hunk of one of the diffs consumed by a [`Detector`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hunk {
+    /// The index of the diff in the order in which their hunks were consumed, including diffs without any hunk.
+    pub file: usize,
+    /// The hunk offsets in lines, just like in a unified diff.
+    pub header: HunkHeader,
+    /// The range of the lines of the hunk in [`Detector::lines()`].
+    pub lines: Range<usize>,
+}
+
+/// A block of consecutive removed or added lines that were moved together.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Block {
+    /// The index of the diff the block is in, see [`Hunk::file`].
+    pub file: usize,
+    /// Whether the lines were removed here, or added here.
+    pub kind: DiffLineKind,
+    /// The range of the lines of the block in [`Detector::lines()`].
+    pub lines: Range<usize>,
+    /// If `true`, the block directly follows another block and is displayed in an alternative color.
+    pub alternate: bool,
+}
+
+/// An adapter with [`ConsumeHunk`] implementation for use in [`UnifiedDiff`](crate::blob::UnifiedDiff), to collect
+/// the hunks of one or more diffs so that moved lines can be detected across all of them.
+///
+/// Each time a [`UnifiedDiff`](crate::blob::UnifiedDiff) finishes, the hunks it produced are associated with the
+/// next [file index](Hunk::file), which makes it possible to use the same instance for all files of a tree diff.
+#[derive(Default, Debug, Clone)]
+pub struct Detector {
+    /// The text of all lines.
+    text: Vec<u8>,
+    lines: Vec<Line>,
+    hunks: Vec<Hunk>,
+    blocks: Vec<Block>,
+    /// The index of the file whose hunks are currently consumed.
+    file: usize,
+}
+
+/// Access
+impl Detector {
+    /// Return all consumed hunks, in the order in which they were consumed.
+    pub fn hunks(&self) -> &[Hunk] {
+        &self.hunks
+    }
+
+    /// Return all lines of all hunks, in the order in which they were consumed.
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Return the text of `line`, with its line terminator if it had one when it was consumed.
+    pub fn text(&self, line: &Line) -> &BStr {
+        self.text[line.text.clone()].as_bstr()
+    }
+
+    /// Return all blocks of moved lines found by the last call to [`detect()`](Self::detect()), in the order in which
+    /// their lines were consumed.
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+}
+
+impl ConsumeHunk for Detector {
+    type Out = Self;
+
+    fn consume_hunk(&mut self, header: HunkHeader, lines: &[(DiffLineKind, &[u8])]) -> std::io::Result<()> {
+        let first_line = self.lines.len();
+        for &(kind, text) in lines {
+            let start = self.text.len();
+            self.text.extend_from_slice(text);
+            self.lines.push(Line {
+                kind,
+                moved: None,
+                text: start..self.text.len(),
+            });
+        }
+        self.hunks.push(Hunk {
+            file: self.file,
+            header,
+            lines: first_line..self.lines.len(),
+        });
+        Ok(())
+    }
+
+    fn finish(mut self) -> Self::Out {
+        self.file += 1;
+        self
+    }
+}
+
+/// The state of a removed or added line while detecting moved lines.
+#[derive(Default, Clone, Copy)]
+struct Entry {
+    /// An identifier shared by all lines that are considered equal.
+    id: usize,
+    /// The line that directly follows this one if it is of the same kind.
+    next_line: Option<usize>,
+    /// The width of the indentation, or `None` if the line is blank.
+    indent_width: Option<isize>,
+    /// The amount of ASCII alphanumeric characters in the line.
+    alnum_count: usize,
+    moved: bool,
+    alternate: bool,
+    dimmed: bool,
+    /// `true` if this is the first line of a block.
+    block_start: bool,
+}
+
+/// A block from which lines may have been moved, along with the change of indentation of its lines.
+struct PotentialBlock {
+    /// The last line of the block that matched.
+    matched: usize,
+    /// The change in indentation of all lines of the block, or `None` if it's not yet known as all lines so far were blank.
+    indent_delta: Option<isize>,
+}
+
+/// The minimal amount of alphanumeric characters in a block for it to be considered moved.
+const MIN_ALNUM_COUNT: usize = 20;
+
+/// Moved-line detection
+impl Detector {
+    /// Find all removed lines that were added elsewhere and vice versa in all consumed hunks as configured by `options`,
+    /// and update [`Line::moved`] and [`blocks()`](Self::blocks()) accordingly.
+    ///
+    /// Lines are matched within the same diff and across diffs, and blocks never span multiple hunks.
+    /// This follows the implementation of `git` closely, so calling it with the hunks of all files of a tree diff
+    /// yields the same result as `git diff --color-moved`.
+    pub fn detect(&mut self, options: Options) {
+        // Like the symbols `git` emits, but with `None` for everything that isn't a removed or added line.
+        let mut symbols = Vec::with_capacity(self.lines.len() + self.hunks.len());
+        for hunk in &self.hunks {
+            symbols.push(None);
+            symbols.extend(hunk.lines.clone().map(|idx| match self.lines[idx].kind {
+                DiffLineKind::Context => None,
+                DiffLineKind::Add | DiffLineKind::Remove => Some(idx),
+            }));
+        }
+
+        let mut entries = vec![Entry::default(); self.lines.len()];
+        let (added, removed) = self.add_lines_to_move_detection(&symbols, &mut entries, options.whitespace);
+        mark_moved(&self.lines, &symbols, &mut entries, &added, &removed, options);
+        if options.mode == Mode::DimmedZebra {
+            dim_moved_lines(&symbols, &mut entries);
+        }
+
+        self.blocks.clear();
+        let mut prev_moved = None;
+        for &symbol in &symbols {
+            let Some(idx) = symbol else {
+                prev_moved = None;
+                continue;
+            };
+            let entry = &entries[idx];
+            let kind = self.lines[idx].kind;
+            self.lines[idx].moved = entry.moved.then_some(Moved {
+                alternate: entry.alternate,
+                dimmed: entry.dimmed,
+            });
+            if !entry.moved {
+                prev_moved = None;
+                continue;
+            }
+            let continues_block = prev_moved.is_some_and(|prev: usize| self.lines[prev].kind == kind)
+                && (options.mode == Mode::Plain || !entry.block_start);
+            match self.blocks.last_mut() {
+                Some(block) if continues_block => block.lines.end = idx + 1,
+                _ => self.blocks.push(Block {
+                    file: self.hunks[self.hunks.partition_point(|hunk| hunk.lines.end <= idx)].file,
+                    kind,
+                    lines: idx..idx + 1,
+                    alternate: entry.alternate,
+                }),
+            }
+            prev_moved = Some(idx);
+        }
+    }
+
+    /// Assign an identifier to each removed and added line so that equal lines share it, and link consecutive lines
+    /// of the same kind, like `add_lines_to_move_detection()` in `git`.
+    ///
+    /// Return the added and removed lines for each identifier respectively, with the last line first.
+    fn add_lines_to_move_detection(
+        &self,
+        symbols: &[Option<usize>],
+        entries: &mut [Entry],
+        options: Whitespace,
+    ) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+        let normalize = whitespace::Options {
+            ignore_all_space: options.ignore_all_space,
+            ignore_space_change: options.ignore_space_change,
+            ignore_space_at_eol: options.ignore_space_at_eol,
+            ..Default::default()
+        };
+        let mut ids = std::collections::HashMap::<Vec<u8>, usize>::new();
+        let (mut added, mut removed) = (Vec::<Vec<usize>>::new(), Vec::<Vec<usize>>::new());
+        let mut prev_line = None;
+        let mut key = Vec::new();
+        for &symbol in symbols {
+            let Some(idx) = symbol else {
+                prev_line = None;
+                continue;
+            };
+            let line = &self.lines[idx];
+            let text = &self.text[line.text.clone()];
+
+            key.clear();
+            let entry = &mut entries[idx];
+            entry.alnum_count = text.iter().filter(|b| b.is_ascii_alphanumeric()).count();
+            if options.allow_indentation_change {
+                let (offset, width) = indentation(text);
+                entry.indent_width = width;
+                let content = &text[offset..];
+                key.extend_from_slice(content.strip_suffix(b"\n").unwrap_or(content));
+            } else {
+                normalize.normalize(text, &mut key);
+            }
+            let next_id = ids.len();
+            entry.id = *ids.entry(key.clone()).or_insert(next_id);
+            if entry.id == next_id {
+                added.push(Vec::new());
+                removed.push(Vec::new());
+            }
+
+            if let Some(prev) = prev_line.filter(|&prev: &usize| self.lines[prev].kind == line.kind) {
+                entries[prev].next_line = Some(idx);
+            }
+            prev_line = Some(idx);
+            match line.kind {
+                DiffLineKind::Add => added[entries[idx].id].push(idx),
+                DiffLineKind::Remove => removed[entries[idx].id].push(idx),
+                DiffLineKind::Context => unreachable!("context lines aren't symbols"),
+            }
+        }
+        for lines in added.iter_mut().chain(removed.iter_mut()) {
+            lines.reverse();
+        }
+        (added, removed)
+    }
+}
+
+/// Return the byte offset at which the content of `line` starts after its indentation, along with the width of its
+/// indentation or `None` if it's blank, like `fill_es_indent_data()` in `git`.
+fn indentation(line: &[u8]) -> (usize, Option<isize>) {
+    const TAB_WIDTH: isize = 8;
+    let mut offset = 0;
+    while matches!(line.get(offset), Some(b'\x0c' | b'\x0b'))
+        || (line.get(offset) == Some(&b'\r') && offset + 1 < line.len())
+    {
+        offset += 1;
+    }
+
+    let mut width = 0;
+    loop {
+        match line.get(offset) {
+            Some(b' ') => {
+                width += 1;
+                offset += 1;
+            }
+            Some(b'\t') => {
+                width += TAB_WIDTH - (width % TAB_WIDTH);
+                offset += 1;
+                while line.get(offset) == Some(&b'\t') {
+                    width += TAB_WIDTH;
+                    offset += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+
+    if line[offset..].iter().all(u8::is_ascii_whitespace) {
+        (line.len(), None)
+    } else {
+        (offset, Some(width))
+    }
+}
+
+/// Mark all moved lines, like `mark_color_as_moved()` in `git`.
+fn mark_moved(
+    lines: &[Line],
+    symbols: &[Option<usize>],
+    entries: &mut [Entry],
+    added: &[Vec<usize>],
+    removed: &[Vec<usize>],
+    options: Options,
+) {
+    let mut potential_blocks = Vec::<PotentialBlock>::new();
+    let mut flipped_block = false;
+    let mut block_length = 0;
+    let mut moved_kind = None;
+
+    let mut n = 0;
+    while n < symbols.len() {
+        let line = symbols[n];
+        let kind = line.map(|idx| lines[idx].kind);
+        let mut matches = line
+            .map(|idx| match lines[idx].kind {
+                DiffLineKind::Add => removed[entries[idx].id].as_slice(),
+                DiffLineKind::Remove => added[entries[idx].id].as_slice(),
+                DiffLineKind::Context => unreachable!("context lines aren't symbols"),
+            })
+            .filter(|matches| !matches.is_empty());
+        if line.is_none() {
+            flipped_block = false;
+        }
+
+        if !potential_blocks.is_empty() && (matches.is_none() || kind != moved_kind) {
+            if !adjust_last_block(symbols, entries, n, block_length, options.mode) && block_length > 1 {
+                // Rewind in case there is another match starting at the second line of the block.
+                matches = None;
+                n -= block_length;
+            }
+            potential_blocks.clear();
+            block_length = 0;
+            flipped_block = false;
+        }
+        let (Some(idx), Some(matches)) = (line, matches) else {
+            moved_kind = None;
+            n += 1;
+            continue;
+        };
+
+        if options.mode == Mode::Plain {
+            entries[idx].moved = true;
+            n += 1;
+            continue;
+        }
+
+        advance_potential_blocks(entries, idx, &mut potential_blocks, options.whitespace);
+
+        if potential_blocks.is_empty() {
+            let contiguous = adjust_last_block(symbols, entries, n, block_length, options.mode);
+            if !contiguous && block_length > 1 {
+                // Rewind in case there is another match starting at the second line of the block.
+                n -= block_length;
+            } else {
+                let indent_width = entries[idx].indent_width;
+                potential_blocks.extend(matches.iter().map(|&matched| {
+                    PotentialBlock {
+                        matched,
+                        indent_delta: indent_width
+                            .zip(entries[matched].indent_width)
+                            .map(|(width, matched_width)| width - matched_width),
+                    }
+                }));
+            }
+
+            flipped_block = contiguous && !potential_blocks.is_empty() && moved_kind == kind && !flipped_block;
+            moved_kind = if potential_blocks.is_empty() { None } else { kind };
+            block_length = 0;
+            entries[idx].block_start = !potential_blocks.is_empty();
+        }
+
+        if !potential_blocks.is_empty() {
+            block_length += 1;
+            let entry = &mut entries[idx];
+            entry.moved = true;
+            entry.alternate = flipped_block && options.mode != Mode::Blocks;
+        }
+        n += 1;
+    }
+    adjust_last_block(symbols, entries, n, block_length, options.mode);
+}
+
+/// Keep only the potential blocks whose next line matches the line at `idx`, like `pmb_advance_or_null()` in `git`.
+fn advance_potential_blocks(
+    entries: &[Entry],
+    idx: usize,
+    potential_blocks: &mut Vec<PotentialBlock>,
+    options: Whitespace,
+) {
+    let line = &entries[idx];
+    potential_blocks.retain_mut(|block| {
+        let Some(cur) = entries[block.matched].next_line else {
+            return false;
+        };
+        let is_match = entries[cur].id == line.id
+            && (!options.allow_indentation_change
+                // Like `cmp_in_block_with_wsd()` in `git`, where blank lines match no matter their indentation.
+                || match (entries[cur].indent_width, line.indent_width) {
+                    (None, _) => true,
+                    (Some(cur_width), Some(width)) => {
+                        let delta = width - cur_width;
+                        *block.indent_delta.get_or_insert(delta) == delta
+                    }
+                    (Some(_), None) => false,
+                });
+        block.matched = cur;
+        is_match
+    });
+}
+
+/// Unmark the `block_length` lines before the symbol at `n` if they have too few alphanumeric characters to be
+/// considered a moved block, like `adjust_last_block()` in `git`.
+///
+/// Return `true` if there is a block and it is kept.
+fn adjust_last_block(
+    symbols: &[Option<usize>],
+    entries: &mut [Entry],
+    n: usize,
+    block_length: usize,
+    mode: Mode,
+) -> bool {
+    if mode == Mode::Plain {
+        return block_length != 0;
+    }
+    let block = symbols[n - block_length..n].iter().flatten();
+    let mut alnum_count = 0;
+    for &idx in block.clone() {
+        alnum_count += entries[idx].alnum_count;
+        if alnum_count >= MIN_ALNUM_COUNT {
+            return true;
+        }
+    }
+    for &idx in block {
+        let entry = &mut entries[idx];
+        entry.moved = false;
+        entry.alternate = false;
+        entry.block_start = false;
+    }
+    false
+}
+
+/// Mark moved lines that aren't at the boundary of a block as dimmed, like `dim_moved_lines()` in `git`.
+fn dim_moved_lines(symbols: &[Option<usize>], entries: &mut [Entry]) {
+    for (n, &symbol) in symbols.iter().enumerate() {
+        let Some(idx) = symbol.filter(|&idx| entries[idx].moved) else {
+            continue;
+        };
+        let prev = n.checked_sub(1).and_then(|prev| symbols[prev]);
+        let next = symbols.get(n + 1).copied().flatten();
+        let entry = entries[idx];
+
+        let is_same_block = |other: Option<usize>| {
+            other.is_some_and(|other| (entries[other].moved, entries[other].alternate) == (true, entry.alternate))
+        };
+        let is_interesting_bound = |other: Option<usize>| {
+            other.is_some_and(|other| entries[other].moved && entries[other].alternate != entry.alternate)
+        };
+        if !(is_same_block(prev) && is_same_block(next)) && (is_interesting_bound(prev) || is_interesting_bound(next)) {
+            continue;
+        }
+        entries[idx].dimmed = true;
+    }
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[m";
+
+/// Rendering
+impl Detector {
+    /// Render all hunks of the diff with the given `file` index in color, like `git diff --color-moved` does, and pass
+    /// them to `delegate` along with their header that is terminated with a newline.
+    ///
+    /// Removed and added lines use the default colors of `git`, with moved lines highlighted according to [`Line::moved`],
+    /// so [`detect()`](Self::detect()) should have been called before.
+    /// Note that unlike `git`, whitespace errors aren't highlighted.
+    pub fn render_file<D>(&self, file: usize, delegate: &mut D) -> std::io::Result<()>
+    where
+        D: ConsumeBinaryHunkDelegate,
+    {
+        let mut hunk_buf = Vec::new();
+        for hunk in self.hunks.iter().filter(|hunk| hunk.file == file) {
+            let header_str = format!("{header}\n", header = hunk.header.colored());
+            hunk_buf.clear();
+            for line in &self.lines[hunk.lines.clone()] {
+                let text = self.text(line);
+                let text = text.strip_suffix(b"\n").unwrap_or(text);
+                let (text, cr) = match text.strip_suffix(b"\r") {
+                    Some(text) => (text, "\r"),
+                    None => (text, ""),
+                };
+                let color = line.color();
+                hunk_buf.push_str(color);
+                hunk_buf.push(line.kind.to_prefix() as u8);
+                if line.kind == DiffLineKind::Add {
+                    // `git` checks added lines for whitespace errors, which is why their content is colored separately.
+                    hunk_buf.push_str(RESET);
+                    if !text.is_empty() {
+                        hunk_buf.push_str(color);
+                        hunk_buf.extend_from_slice(text);
+                        hunk_buf.push_str(RESET);
+                    }
+                } else {
+                    hunk_buf.extend_from_slice(text);
+                    hunk_buf.push_str(RESET);
+                }
+                hunk_buf.push_str(cr);
+                hunk_buf.push(b'\n');
+            }
+            delegate.consume_binary_hunk(hunk.header, &header_str, &hunk_buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl Line {
+    /// Return the color with which `git` displays this line by default.
+    fn color(&self) -> &'static str {
+        let Some(Moved { alternate, dimmed }) = self.moved else {
+            return match self.kind {
+                DiffLineKind::Context => "",
+                DiffLineKind::Remove => RED,
+                DiffLineKind::Add => GREEN,
+            };
+        };
+        match (self.kind, alternate, dimmed) {
+            (_, false, true) => "\x1b[2m",
+            (_, true, true) => "\x1b[2;3m",
+            (DiffLineKind::Add, false, false) => "\x1b[1;36m",
+            (DiffLineKind::Add, true, false) => "\x1b[1;33m",
+            (_, false, false) => "\x1b[1;35m",
+            (_, true, false) => "\x1b[1;34m",
+        }
+    }
+}
@@ -290,6 +290,27 @@ pub mod prepare_diff {
             /// * the platforms own configuration (typically from git-config)
             /// * the default algorithm
             algorithm: imara_diff::Algorithm,
+            /// The way whitespace should affect the comparison of lines, as configured in the platform's
+            /// [options](super::Options::whitespace).
+            ///
+            /// Use it with [`whitespace::diff_with_slider_heuristics()`](crate::blob::whitespace::diff_with_slider_heuristics())
+            /// to compute the diff.
+            whitespace: crate::blob::whitespace::Options,
+            /// The regular expression that determines what's a word in word diffs, which is one of (in order, first set one wins):
+            ///
+            /// * the override of the old resource's driver
+            /// * the override of the new resource's driver
+            /// * the platforms own configuration (typically from git-config)
+            ///
+            /// Compile it with [`WordRegex::new()`](crate::blob::word_diff::WordRegex::new()), or use sequences of
+            /// non-whitespace characters as words if it is `None`.
+            word_regex: Option<&'a BStr>,
+            /// The regular expressions to find lines that start a function, which is the override of the old resource's driver,
+            /// or the one of the new resource's driver.
+            ///
+            /// Compile it with [`Matcher::new()`](crate::blob::funcname::Matcher::new()), or use
+            /// [`Matcher::default()`](crate::blob::funcname::Matcher::default()) if it is `None`.
+            funcname: Option<&'a crate::blob::funcname::Pattern>,
         },
         /// Run the external diff program according as configured in the `source`-resources driver.
         /// This only happens if [Options::skip_internal_diff_if_external_is_configured](super::Options::skip_internal_diff_if_external_is_configured)
@@ -393,11 +414,16 @@ pub mod prepare_diff_command {
 }
 
 /// Options for use in [Platform::new()].
-#[derive(Default, Copy, Clone)]
+#[derive(Default, Clone)]
 pub struct Options {
     /// The algorithm to use when diffing.
     /// If unset, it uses the [default algorithm](Algorithm::default()).
     pub algorithm: Option<Algorithm>,
+    /// Control how whitespace affects the comparison of lines, which by default doesn't ignore any whitespace.
+    pub whitespace: crate::blob::whitespace::Options,
+    /// The regular expression that determines what's a word in word diffs, typically from `diff.wordRegex`,
+    /// unless a driver overrides it.
+    pub word_regex: Option<BString>,
     /// If `true`, default `false`, then an external `diff` configured using gitattributes and drivers,
     /// will cause the built-in diff [to be skipped](prepare_diff::Operation::ExternalCommand).
     /// Otherwise, the internal diff is called despite the configured external diff, which is
@@ -638,6 +664,23 @@ impl Platform {
                 .and_then(|idx| self.filter.drivers[idx].algorithm)
                 .or(self.options.algorithm)
                 .unwrap_or_default(),
+            whitespace: self.options.whitespace,
+            word_regex: [old, new]
+                .into_iter()
+                .find_map(|value| {
+                    value
+                        .conversion
+                        .driver_index
+                        .and_then(|idx| self.filter.drivers[idx].word_regex.as_ref())
+                })
+                .or(self.options.word_regex.as_ref())
+                .map(|regex| regex.as_bstr()),
+            funcname: [old, new].into_iter().find_map(|value| {
+                value
+                    .conversion
+                    .driver_index
+                    .and_then(|idx| self.filter.drivers[idx].funcname.as_ref())
+            }),
         };
         Ok(out)
     }
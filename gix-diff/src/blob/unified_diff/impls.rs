@@ -1,9 +1,13 @@
-use bstr::{BString, ByteSlice, ByteVec};
-use imara_diff::{Diff, InternedInput, Interner, Token};
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use imara_diff::{Diff, Hunk, InternedInput, Token};
 use std::fmt::Write;
 use std::{hash::Hash, ops::Range};
 
-use super::{ConsumeBinaryHunk, ConsumeBinaryHunkDelegate, ConsumeHunk, ContextSize, DiffLineKind, HunkHeader};
+use crate::blob::{funcname::Matcher, whitespace};
+
+use super::{
+    ConsumeBinaryHunk, ConsumeBinaryHunkDelegate, ConsumeHunk, ContextSize, DiffLineKind, FunctionName, HunkHeader,
+};
 
 /// A helper that renders a [`Diff`] as unified diff output.
 /// It can be used to create a textual diff in the format typically output by `git`
@@ -14,30 +18,28 @@ where
     D: ConsumeHunk,
 {
     diff: &'a Diff,
-    before: &'a [Token],
-    after: &'a [Token],
-    interner: &'a Interner<T>,
-
-    /// The 0-based start position in the 'before' tokens for the accumulated hunk for display in the header.
-    before_hunk_start: u32,
-    /// The size of the accumulated 'before' hunk in lines for display in the header.
-    before_hunk_len: u32,
-    /// The 0-based start position in the 'after' tokens for the accumulated hunk for display in the header.
-    after_hunk_start: u32,
-    /// The size of the accumulated 'after' hunk in lines.
-    after_hunk_len: u32,
-    // An index into `before` and the context line to print next,
-    // or `None` if this value was never computed to be the correct starting point for an accumulated hunk.
-    ctx_pos: Option<u32>,
+    input: &'a InternedInput<T>,
 
     /// Symmetrical context before and after the changed hunk.
     ctx_size: u32,
+    /// Determines which changes are ignorable.
+    whitespace: whitespace::Options,
+    /// Finds lines that start a function, if function names should be shown in hunk headers.
+    function_names: Option<&'a Matcher>,
+    /// If `true`, hunks are expanded to contain the whole function they are in.
+    function_context: bool,
+    /// The matcher to use for the function context if there is no matcher for function names.
+    default_matcher: Matcher,
 
     buffer: Vec<(DiffLineKind, &'a [u8])>,
 
     delegate: D,
+}
 
-    err: Option<std::io::Error>,
+/// A change of the diff, along with the information if it could be ignored.
+struct Change {
+    hunk: Hunk,
+    ignore: bool,
 }
 
 impl<'a, T, D> UnifiedDiff<'a, T, D>
@@ -52,130 +54,280 @@ where
     pub fn new(diff: &'a Diff, input: &'a InternedInput<T>, consume_hunk: D, context_size: ContextSize) -> Self {
         Self {
             diff,
-            interner: &input.interner,
-            before: &input.before,
-            after: &input.after,
-
-            before_hunk_start: 0,
-            before_hunk_len: 0,
-            after_hunk_len: 0,
-            after_hunk_start: 0,
-            ctx_pos: None,
+            input,
 
             ctx_size: context_size.symmetrical,
+            whitespace: Default::default(),
+            function_names: None,
+            function_context: false,
+            default_matcher: Matcher::default(),
 
             buffer: Vec::with_capacity(8),
             delegate: consume_hunk,
-
-            err: None,
         }
     }
 
-    fn print_tokens(&mut self, tokens: &[Token], line_type: DiffLineKind) {
-        for &token in tokens {
-            let content = self.interner[token].as_ref();
-            self.buffer.push((line_type, content));
-        }
+    /// Use `options` to determine which changes to skip as they only affect blank lines,
+    /// if [`ignore_blank_lines`](whitespace::Options::ignore_blank_lines) is set.
+    ///
+    /// Just like in `git`, such changes are still displayed if they are close enough to other changes.
+    /// Note that all other options are expected to have been used to compute the `diff`,
+    /// typically with [`whitespace::diff_with_slider_heuristics()`].
+    pub fn with_whitespace(mut self, options: whitespace::Options) -> Self {
+        self.whitespace = options;
+        self
     }
 
-    fn flush_accumulated_hunk(&mut self) -> std::io::Result<()> {
-        if self.nothing_to_flush() {
-            return Ok(());
-        }
-
-        let ctx_pos = self.ctx_pos.expect("has been set if we started a hunk");
-        let end = (ctx_pos + self.ctx_size).min(self.before.len() as u32);
-        self.print_context_and_update_pos(ctx_pos..end, end);
+    /// Use `matcher` to find the function each hunk is in, and pass its name along with the [hunk header](HunkHeader::function_name).
+    ///
+    /// Just like in `git`, it's the closest line before the hunk in the old version that starts a function.
+    /// Use [`Matcher::default()`] for `git`'s heuristic that is used if the diff driver of a file doesn't have a pattern.
+    pub fn with_function_names(mut self, matcher: &'a Matcher) -> Self {
+        self.function_names = Some(matcher);
+        self
+    }
 
-        let hunk_start = self.before_hunk_start + 1;
-        let hunk_end = self.after_hunk_start + 1;
+    /// If `enabled`, expand each hunk to contain the whole function it is in, like `git diff --function-context` does.
+    ///
+    /// Functions are found with the matcher passed to [`with_function_names()`](Self::with_function_names()),
+    /// or with `git`'s default heuristic otherwise.
+    pub fn with_function_context(mut self, enabled: bool) -> Self {
+        self.function_context = enabled;
+        self
+    }
 
-        let header = HunkHeader {
-            before_hunk_start: hunk_start,
-            before_hunk_len: self.before_hunk_len,
-            after_hunk_start: hunk_end,
-            after_hunk_len: self.after_hunk_len,
+    fn print_tokens(&mut self, range: Range<u32>, line_type: DiffLineKind) {
+        let tokens = match line_type {
+            DiffLineKind::Remove => &self.input.before,
+            DiffLineKind::Context | DiffLineKind::Add => &self.input.after,
         };
+        for &token in &tokens[range.start as usize..range.end as usize] {
+            self.buffer.push((line_type, self.input.interner[token].as_ref()));
+        }
+    }
 
-        self.delegate.consume_hunk(header, &self.buffer)?;
+    /// Pass all `changes`, which are displayed together, as a single hunk with its context to our delegate.
+    /// The hunk spans the `before` and `after` lines, and `function_name` is the name to show in its header.
+    fn consume_changes(
+        &mut self,
+        changes: &[Change],
+        before: Range<u32>,
+        after: Range<u32>,
+        function_name: FunctionName,
+    ) -> std::io::Result<()> {
+        // Like `git`, print context lines from the new version as these may differ if whitespace is ignored.
+        self.buffer.clear();
+        let mut ctx_pos = after.start;
+        for change in changes {
+            self.print_tokens(ctx_pos..change.hunk.after.start, DiffLineKind::Context);
+            self.print_tokens(change.hunk.before.clone(), DiffLineKind::Remove);
+            self.print_tokens(change.hunk.after.clone(), DiffLineKind::Add);
+            ctx_pos = change.hunk.after.end;
+        }
+        self.print_tokens(ctx_pos..after.end, DiffLineKind::Context);
 
-        self.reset_hunks();
-        Ok(())
+        let header = HunkHeader {
+            before_hunk_start: before.start + 1,
+            before_hunk_len: before.end - before.start,
+            after_hunk_start: after.start + 1,
+            after_hunk_len: after.end - after.start,
+            function_name,
+        };
+        self.delegate.consume_hunk(header, &self.buffer)
     }
 
-    fn print_context_and_update_pos(&mut self, print: Range<u32>, move_to: u32) {
-        self.print_tokens(
-            &self.before[print.start as usize..print.end as usize],
-            DiffLineKind::Context,
-        );
+    fn matcher(&self) -> &Matcher {
+        self.function_names.unwrap_or(&self.default_matcher)
+    }
 
-        let len = print.end - print.start;
-        self.ctx_pos = Some(move_to);
-        self.before_hunk_len += len;
-        self.after_hunk_len += len;
+    fn line(&self, tokens: &[Token], idx: i64) -> &[u8] {
+        self.input.interner[tokens[idx as usize]].as_ref()
     }
 
-    fn reset_hunks(&mut self) {
-        self.buffer.clear();
-        self.before_hunk_len = 0;
-        self.after_hunk_len = 0;
+    /// Return `true` if line `idx` of `tokens`, the old or new version, starts a function.
+    fn is_function_line(&self, tokens: &[Token], idx: i64) -> bool {
+        self.matcher().is_match(self.line(tokens, idx))
     }
 
-    fn nothing_to_flush(&self) -> bool {
-        self.before_hunk_len == 0 && self.after_hunk_len == 0
+    /// Return `true` if line `idx` of the old version only consists of whitespace.
+    fn is_empty_line(&self, idx: i64) -> bool {
+        self.line(&self.input.before, idx)
+            .iter()
+            .all(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
     }
 
-    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
-        if self.err.is_some() {
-            return;
-        }
-        let start_next_hunk = self
-            .ctx_pos
-            .is_some_and(|ctx_pos| before.start - ctx_pos > 2 * self.ctx_size);
-        if start_next_hunk {
-            if let Err(err) = self.flush_accumulated_hunk() {
-                self.err = Some(err);
-                return;
+    /// Return the first line of the old version from `start` towards `limit`, excluding `limit` itself, that starts a function,
+    /// just like `get_func_line()` in `git`.
+    fn find_function_line(&self, start: i64, limit: i64) -> Option<i64> {
+        let step = if start > limit { -1 } else { 1 };
+        let num_lines = self.input.before.len() as i64;
+        let mut idx = start;
+        while idx != limit && (0..num_lines).contains(&idx) {
+            if self.is_function_line(&self.input.before, idx) {
+                return Some(idx);
             }
-            let ctx_pos = before.start - self.ctx_size;
-            self.ctx_pos = Some(ctx_pos);
-            self.before_hunk_start = ctx_pos;
-            self.after_hunk_start = after.start - self.ctx_size;
+            idx += step;
         }
-        let ctx_pos = match self.ctx_pos {
-            None => {
-                // TODO: can this be made so the code above does the job?
-                let ctx_pos = before.start.saturating_sub(self.ctx_size);
-                self.before_hunk_start = ctx_pos;
-                self.after_hunk_start = after.start.saturating_sub(self.ctx_size);
-                ctx_pos
-            }
-            Some(pos) => pos,
-        };
-        self.print_context_and_update_pos(ctx_pos..before.start, before.end);
-        self.before_hunk_len += before.end - before.start;
-        self.after_hunk_len += after.end - after.start;
-
-        self.print_tokens(
-            &self.before[before.start as usize..before.end as usize],
-            DiffLineKind::Remove,
-        );
-        self.print_tokens(&self.after[after.start as usize..after.end as usize], DiffLineKind::Add);
+        None
     }
 
     /// Consume all hunks from `diff` and return the delegate's final output.
     pub fn consume(mut self) -> std::io::Result<D::Out> {
-        for hunk in self.diff.hunks() {
-            self.process_change(hunk.before, hunk.after);
+        let changes: Vec<_> = self
+            .diff
+            .hunks()
+            .map(|hunk| Change {
+                ignore: self.whitespace.is_ignorable(&hunk, self.input),
+                hunk,
+            })
+            .collect();
+        let ctx = i64::from(self.ctx_size);
+        let (num_before, num_after) = (self.input.before.len() as i64, self.input.after.len() as i64);
+        let end = |change: &Change| (i64::from(change.hunk.before.end), i64::from(change.hunk.after.end));
+
+        let mut function_line_prev = -1;
+        let mut function_name = FunctionName::default();
+        let mut pos = 0;
+        // This follows `xdl_emit_diff()` in `git`, with `first` being the first change that is shown,
+        // and `last` being the last one, while `pos` is the first change after the previous hunk.
+        while let Some(hunk) = next_hunk(&changes[pos..], self.ctx_size) {
+            let (mut first, mut last) = (pos + hunk.start, pos + hunk.end - 1);
+            let (mut s1, mut s2);
+            loop {
+                let change = &changes[first].hunk;
+                s1 = (i64::from(change.before.start) - ctx).max(0);
+                s2 = (i64::from(change.after.start) - ctx).max(0);
+                if !self.function_context {
+                    break;
+                }
+                let mut i1 = i64::from(change.before.start);
+                if i1 >= num_before {
+                    // No additional context is needed if a whole function was appended.
+                    if (i64::from(change.after.start)..num_after).any(|i2| self.is_function_line(&self.input.after, i2))
+                    {
+                        break;
+                    }
+                    i1 = num_before - 1;
+                }
+                let mut fs1 = self.find_function_line(i1, -1).unwrap_or(-1);
+                while fs1 > 0 && !self.is_empty_line(fs1 - 1) && !self.is_function_line(&self.input.before, fs1 - 1) {
+                    fs1 -= 1;
+                }
+                let fs1 = fs1.max(0);
+                if fs1 < s1 {
+                    s2 = (s2 - (s1 - fs1)).max(0);
+                    s1 = fs1;
+
+                    // Show ignored changes after all if the context now reaches into them.
+                    while pos != first && end(&changes[pos]).0 <= s1 && end(&changes[pos]).1 <= s2 {
+                        pos += 1;
+                    }
+                    if pos != first {
+                        first = pos;
+                        continue;
+                    }
+                }
+                break;
+            }
+
+            let (mut e1, mut e2);
+            loop {
+                let (end1, end2) = end(&changes[last]);
+                let ctx_after = ctx.min(num_before - end1).min(num_after - end2);
+                e1 = end1 + ctx_after;
+                e2 = end2 + ctx_after;
+                if !self.function_context {
+                    break;
+                }
+                let mut fe1 = self.find_function_line(end1, num_before).unwrap_or(-1);
+                while fe1 > 0 && self.is_empty_line(fe1 - 1) {
+                    fe1 -= 1;
+                }
+                if fe1 < 0 {
+                    fe1 = num_before;
+                }
+                if fe1 > e1 {
+                    e2 = (e2 + (fe1 - e1)).min(num_after);
+                    e1 = fe1;
+                }
+
+                // Include the next change if it overlaps, and find the new end.
+                if let Some(next) = changes.get(last + 1) {
+                    let l = i64::from(next.hunk.before.start).min(num_before - 1);
+                    if l - ctx <= e1 || self.find_function_line(l, e1).is_none() {
+                        last += 1;
+                        continue;
+                    }
+                }
+                break;
+            }
+
+            if let Some(matcher) = self.function_names {
+                if let Some(idx) = self.find_function_line(s1 - 1, function_line_prev) {
+                    let line = self.line(&self.input.before, idx);
+                    function_name = FunctionName::new(matcher.function_name(line).expect("matched before"));
+                }
+                function_line_prev = s1 - 1;
+            }
+            self.consume_changes(
+                &changes[first..=last],
+                s1 as u32..e1 as u32,
+                s2 as u32..e2 as u32,
+                function_name,
+            )?;
+            pos = last + 1;
         }
-        if let Err(err) = self.flush_accumulated_hunk() {
-            self.err = Some(err);
+        Ok(self.delegate.finish())
+    }
+}
+
+/// Return the range of `changes` to display in the next hunk, or `None` if there is no hunk left.
+///
+/// Changes are merged into the same hunk if their context would overlap, and ignorable changes
+/// are skipped unless they are close enough to other changes, just like `xdl_get_hunk()` in `git`.
+fn next_hunk(changes: &[Change], ctx_size: u32) -> Option<Range<usize>> {
+    let max_common = 2 * ctx_size;
+    let max_ignorable = ctx_size;
+
+    // Skip ignorable changes that are too far before other changes.
+    let mut first = 0;
+    for (idx, change) in changes.iter().enumerate().take_while(|(_, change)| change.ignore) {
+        if changes
+            .get(idx + 1)
+            .is_none_or(|next| next.hunk.before.start - change.hunk.before.end >= max_ignorable)
+        {
+            first = idx + 1;
+        }
+    }
+    let changes = &changes[first..];
+    if changes.is_empty() {
+        return None;
+    }
+
+    let mut last = 0;
+    let mut ignored = 0;
+    for (idx, pair) in changes.windows(2).enumerate() {
+        let (prev, change) = (&pair[0].hunk, &pair[1]);
+        let idx = idx + 1;
+        let distance = change.hunk.before.start - prev.before.end;
+        if distance > max_common {
+            break;
         }
-        if let Some(err) = self.err {
-            return Err(err);
+        if distance < max_ignorable && (!change.ignore || last == idx - 1) {
+            last = idx;
+            ignored = 0;
+        } else if distance < max_ignorable && change.ignore {
+            ignored += change.hunk.after.len() as u32;
+        } else if last != idx - 1 && change.hunk.before.start + ignored - changes[last].hunk.before.end > max_common {
+            break;
+        } else if !change.ignore {
+            last = idx;
+            ignored = 0;
+        } else {
+            ignored += change.hunk.after.len() as u32;
         }
-        Ok(self.delegate.finish())
     }
+    Some(first..first + last + 1)
 }
 
 /// An implementation that fails if the input isn't UTF-8.
@@ -265,12 +417,89 @@ impl DiffLineKind {
     }
 }
 
+impl HunkHeader {
+    /// Return a copy of this header without its [function name](HunkHeader::function_name), which is useful to display
+    /// the line numbers differently from the function name.
+    pub fn without_function_name(&self) -> Self {
+        HunkHeader {
+            function_name: FunctionName::default(),
+            ..*self
+        }
+    }
+
+    /// Return a way to display this header in the default colors of `git`.
+    pub(crate) fn colored(&self) -> impl std::fmt::Display + '_ {
+        ColoredHunkHeader(self)
+    }
+}
+
+struct ColoredHunkHeader<'a>(&'a HunkHeader);
+
+impl std::fmt::Display for ColoredHunkHeader<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const CYAN: &str = "\x1b[36m";
+        const RESET: &str = "\x1b[m";
+        write!(f, "{CYAN}{}{RESET}", self.0.without_function_name())?;
+        if !self.0.function_name.is_empty() {
+            write!(f, " {RESET}{}{RESET}", self.0.function_name.as_bstr())?;
+        }
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for HunkHeader {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "@@ -{},{} +{},{} @@",
             self.before_hunk_start, self.before_hunk_len, self.after_hunk_start, self.after_hunk_len
-        )
+        )?;
+        if !self.function_name.is_empty() {
+            write!(f, " {}", self.function_name.as_bstr())?;
+        }
+        Ok(())
+    }
+}
+
+impl FunctionName {
+    pub(super) const MAX_LEN: usize = 80;
+
+    /// Create a new instance from the beginning of `name`, truncated to 80 bytes and without trailing whitespace.
+    pub fn new(name: &[u8]) -> Self {
+        let mut name = &name[..name.len().min(Self::MAX_LEN)];
+        while let Some((last, rest)) = name.split_last() {
+            if !matches!(last, b' ' | b'\t' | b'\n' | b'\r') {
+                break;
+            }
+            name = rest;
+        }
+        let mut buf = [0; Self::MAX_LEN];
+        buf[..name.len()].copy_from_slice(name);
+        FunctionName {
+            buf,
+            len: name.len() as u8,
+        }
+    }
+
+    /// Return the name itself.
+    pub fn as_bstr(&self) -> &BStr {
+        self.buf[..self.len as usize].as_bstr()
+    }
+
+    /// Return `true` if there is no name.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for FunctionName {
+    fn default() -> Self {
+        FunctionName::new(&[])
+    }
+}
+
+impl std::fmt::Debug for FunctionName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_bstr(), f)
     }
 }
@@ -49,6 +49,19 @@ pub struct HunkHeader {
     pub after_hunk_start: u32,
     /// The size of the 'after' hunk in lines.
     pub after_hunk_len: u32,
+    /// The name of the function the hunk is a part of, which is empty unless
+    /// [function names](crate::blob::UnifiedDiff::with_function_names()) are enabled.
+    pub function_name: FunctionName,
+}
+
+/// The name of the function that a hunk is a part of, shown after the line numbers of a [`HunkHeader`].
+///
+/// Just like in `git`, it's the beginning of the line that starts the function, truncated to 80 bytes and
+/// without trailing whitespace, or empty if no function was found.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct FunctionName {
+    buf: [u8; FunctionName::MAX_LEN],
+    len: u8,
 }
 
 /// An adapter with [`ConsumeHunk`] implementation to call a delegate which receives each stringified hunk.
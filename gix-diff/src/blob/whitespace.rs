@@ -0,0 +1,147 @@
+use imara_diff::{Algorithm, Diff, Hunk, IndentLevel, InternedInput, Interner, PositionalIndentHeuristic, Token};
+
+/// Options to control how whitespace affects the comparison of lines, similar to the `--ignore-*` flags of `git diff`.
+///
+/// Lines are compared in a normalized form, but diffs still refer to the original lines,
+/// so that these are what's displayed.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// Ignore whitespace when comparing lines, even if one line has whitespace where the other has none,
+    /// like `--ignore-all-space`.
+    pub ignore_all_space: bool,
+    /// Ignore changes in the amount of whitespace, so that one or more whitespace characters compare equal to
+    /// any other sequence of whitespace, and whitespace at the end of lines is ignored, like `--ignore-space-change`.
+    pub ignore_space_change: bool,
+    /// Ignore changes in whitespace at the end of lines, like `--ignore-space-at-eol`.
+    pub ignore_space_at_eol: bool,
+    /// Ignore a carriage-return at the end of lines, like `--ignore-cr-at-eol`.
+    pub ignore_cr_at_eol: bool,
+    /// Ignore changes whose removed and added lines are all blank, unless they are close to other changes,
+    /// like `--ignore-blank-lines`.
+    ///
+    /// If any of the other options is set, lines that consist only of whitespace are considered blank,
+    /// otherwise only empty lines are.
+    /// Note that this doesn't affect the diff itself, but is applied when consuming its hunks,
+    /// see [`is_ignorable()`](Self::is_ignorable()).
+    pub ignore_blank_lines: bool,
+}
+
+impl Options {
+    /// Return `true` if lines are compared in a normalized form, i.e. if any option but
+    /// [`ignore_blank_lines`](Self::ignore_blank_lines) is set.
+    pub fn normalizes_lines(&self) -> bool {
+        self.ignore_all_space || self.ignore_space_change || self.ignore_space_at_eol || self.ignore_cr_at_eol
+    }
+
+    /// Return `true` if `line`, with or without its line terminator, is considered blank.
+    pub fn is_blank_line(&self, line: &[u8]) -> bool {
+        if self.normalizes_lines() {
+            line.iter().copied().all(is_space)
+        } else {
+            line.strip_suffix(b"\n").unwrap_or(line).is_empty()
+        }
+    }
+
+    /// Return `true` if `hunk` of a diff of `input` only removes and adds blank lines, and should be ignored
+    /// as [`ignore_blank_lines`](Self::ignore_blank_lines) is set.
+    ///
+    /// Note that `git` still displays such hunks if they are close enough to other changes to share their context,
+    /// which is what [`UnifiedDiff`](crate::blob::UnifiedDiff) does as well.
+    pub fn is_ignorable<T: AsRef<[u8]>>(&self, hunk: &Hunk, input: &InternedInput<T>) -> bool {
+        self.ignore_blank_lines
+            && input.before[hunk.before.start as usize..hunk.before.end as usize]
+                .iter()
+                .chain(&input.after[hunk.after.start as usize..hunk.after.end as usize])
+                .all(|&token| self.is_blank_line(input.interner[token].as_ref()))
+    }
+
+    /// Append `line`, with or without its line terminator, to `out` in the form in which it should be compared.
+    pub(crate) fn normalize(&self, line: &[u8], out: &mut Vec<u8>) {
+        let (line, has_newline) = match line.strip_suffix(b"\n") {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        if !(self.ignore_all_space || self.ignore_space_change || self.ignore_space_at_eol) {
+            // Do not ignore a carriage-return at the end of an incomplete line, just like `git`.
+            let line = if self.ignore_cr_at_eol && has_newline {
+                line.strip_suffix(b"\r").unwrap_or(line)
+            } else {
+                line
+            };
+            out.extend_from_slice(line);
+            return;
+        }
+
+        let mut rest = line;
+        while let Some((&byte, tail)) = rest.split_first() {
+            if !is_space(byte) {
+                out.push(byte);
+                rest = tail;
+                continue;
+            }
+            let num_spaces = 1 + tail.iter().take_while(|&&b| is_space(b)).count();
+            let (spaces, tail) = rest.split_at(num_spaces);
+            rest = tail;
+            let at_eol = rest.is_empty();
+            if self.ignore_all_space || at_eol {
+                continue;
+            } else if self.ignore_space_change {
+                out.push(b' ');
+            } else {
+                out.extend_from_slice(spaces);
+            }
+        }
+    }
+}
+
+/// Like [`diff_with_slider_heuristics()`](crate::blob::diff_with_slider_heuristics()), but compare lines of `input`
+/// as configured by `options`.
+///
+/// The returned diff refers to the lines in `input`, and slider heuristics use their original indentation
+/// just like `git` does.
+/// Note that hunks that should be ignored according to [`Options::ignore_blank_lines`] are still part of the diff,
+/// use [`Options::is_ignorable()`] to skip them.
+pub fn diff_with_slider_heuristics<T: AsRef<[u8]>>(
+    algorithm: Algorithm,
+    input: &InternedInput<T>,
+    options: Options,
+) -> Diff {
+    if !options.normalizes_lines() {
+        return crate::blob::diff_with_slider_heuristics(algorithm, input);
+    }
+
+    let mut interner = Interner::new(input.interner.num_tokens() as usize);
+    let mut normalized_by_token = vec![None; input.interner.num_tokens() as usize];
+    let mut buf = Vec::new();
+    let mut normalize = |&token: &Token| {
+        *normalized_by_token[token.0 as usize].get_or_insert_with(|| {
+            buf.clear();
+            options.normalize(input.interner[token].as_ref(), &mut buf);
+            interner.intern(buf.clone())
+        })
+    };
+    let before: Vec<_> = input.before.iter().map(&mut normalize).collect();
+    let after: Vec<_> = input.after.iter().map(&mut normalize).collect();
+    let normalized = InternedInput {
+        before,
+        after,
+        interner,
+    };
+
+    let mut diff = Diff::compute(algorithm, &normalized);
+    let indent_at = |tokens: &[Token], pos: usize| {
+        IndentLevel::for_ascii_line(input.interner[tokens[pos]].as_ref().iter().copied(), 8)
+    };
+    diff.postprocess_with_heuristics(
+        &normalized.before,
+        &normalized.after,
+        PositionalIndentHeuristic::new(|pos| indent_at(&input.before, pos)),
+        PositionalIndentHeuristic::new(|pos| indent_at(&input.after, pos)),
+    );
+    diff
+}
+
+/// Like `XDL_ISSPACE()` in `git`.
+fn is_space(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r')
+}
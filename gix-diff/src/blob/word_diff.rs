@@ -0,0 +1,442 @@
+use std::{fmt::Write, ops::Range};
+
+use bstr::{BStr, ByteSlice, ByteVec};
+use imara_diff::{Algorithm, Diff, InternedInput};
+
+use crate::blob::unified_diff::{ConsumeBinaryHunkDelegate, ConsumeHunk, DiffLineKind, HunkHeader};
+
+/// The way word diffs are rendered by [`Renderer`], like `--word-diff=<mode>` in `git diff`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Enclose removed words in `[-` and `-]`, and added words in `{+` and `+}`.
+    #[default]
+    Plain,
+    /// A line-based format for use by scripts, where spans of unchanged, removed and added text are put on lines of their own,
+    /// prefixed with ` `, `-` and `+` respectively.
+    /// Newlines of the input are displayed as `~` on a line of their own.
+    Porcelain,
+    /// Display removed words in red and added words in green using ANSI escape codes, like `--color-words`.
+    Color,
+}
+
+/// The kind of text in a span of a word diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum SpanKind {
+    /// A line that exists in both the old and the new version, added based on [`ContextSize`](crate::blob::unified_diff::ContextSize).
+    Context,
+    /// Text within changed lines which is the same in both versions, as it is in the new version.
+    ///
+    /// This also means that changes in whitespace between words are not displayed.
+    Unchanged,
+    /// Text that was removed from the old version.
+    Removed,
+    /// Text that was added in the new version.
+    Added,
+}
+
+/// A regular expression to determine what's a word, like `diff.wordRegex` or `diff.<driver>.wordRegex`.
+///
+/// Without it, words are sequences of non-whitespace characters.
+#[derive(Debug, Clone)]
+pub struct WordRegex(regex::bytes::Regex);
+
+///
+pub mod word_regex {
+    use bstr::BString;
+
+    /// The error returned by [WordRegex::new()](super::WordRegex::new()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error("The word regex '{pattern}' isn't valid UTF-8")]
+        Utf8 { pattern: BString },
+        #[error("Could not compile the word regex '{pattern}'")]
+        Compile { pattern: BString, source: regex::Error },
+    }
+}
+
+impl WordRegex {
+    /// Compile `pattern` to match words.
+    ///
+    /// Just like in `git`, it's matched against bytes, and `^` and `$` also match at the beginning and end of lines.
+    /// Note that the pattern uses the syntax of the `regex` crate, which is largely compatible with the extended
+    /// regular expressions that `git` uses, including character classes like `[[:space:]]`.
+    pub fn new(pattern: &BStr) -> Result<Self, word_regex::Error> {
+        let pattern_str = pattern.to_str().map_err(|_| word_regex::Error::Utf8 {
+            pattern: pattern.to_owned(),
+        })?;
+        regex::bytes::RegexBuilder::new(pattern_str)
+            .unicode(false)
+            .multi_line(true)
+            .build()
+            .map(WordRegex)
+            .map_err(|err| word_regex::Error::Compile {
+                pattern: pattern.to_owned(),
+                source: err,
+            })
+    }
+}
+
+/// Find the first word in `text` at or after `start`, either using `word_regex` or by splitting at whitespace,
+/// just like `find_word_boundaries()` in `git`.
+///
+/// Words never span multiple lines.
+fn next_word(word_regex: Option<&WordRegex>, text: &[u8], mut start: usize) -> Option<Range<usize>> {
+    match word_regex {
+        Some(WordRegex(regex)) => loop {
+            if start >= text.len() {
+                return None;
+            }
+            let word = regex.find(&text[start..])?;
+            let word_start = start + word.start();
+            let word_end = word
+                .as_bytes()
+                .find_byte(b'\n')
+                .map_or(start + word.end(), |newline| word_start + newline);
+            if word_start != word_end {
+                return Some(word_start..word_end);
+            }
+            start = word_start + 1;
+        },
+        None => {
+            let word_start = start + text.get(start..)?.iter().position(|&b| !is_space(b))?;
+            let word_end = text[word_start..]
+                .iter()
+                .position(|&b| is_space(b))
+                .map_or(text.len(), |len| word_start + len);
+            Some(word_start..word_end)
+        }
+    }
+}
+
+/// Like `isspace()` in `git`.
+fn is_space(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// A trait for use in conjunction with [`WordDiff`].
+pub trait ConsumeWordHunk {
+    /// The item this instance produces after consuming all hunks.
+    type Out;
+
+    /// Consume a single hunk which is represented by its `spans` of text, each of which contains a [`SpanKind`]
+    /// and the text of the span, which may span multiple lines.
+    /// [Context lines](SpanKind::Context) and the text of the last span of changed lines always end in a newline.
+    /// The `header` specifies the hunk offsets in lines, like it does in a unified diff.
+    ///
+    /// After this method returned its first error, it will not be called anymore.
+    fn consume_word_hunk(&mut self, header: HunkHeader, spans: &[(SpanKind, &[u8])]) -> std::io::Result<()>;
+
+    /// Called after the last hunk is consumed to produce an output.
+    fn finish(self) -> Self::Out;
+}
+
+/// An adapter with [`ConsumeHunk`] implementation for use in [`UnifiedDiff`](crate::blob::UnifiedDiff), to turn the
+/// removed and added lines of each hunk into spans of removed and added words, like `git diff --word-diff` does.
+///
+/// Consecutive removed and added lines are split into words, which are diffed with the Myers algorithm, and the text
+/// between words is taken from the new version.
+pub struct WordDiff<'a, D> {
+    /// The delegate to receive the spans of each hunk.
+    pub delegate: D,
+    /// Determines what's a word, or `None` to use sequences of non-whitespace characters.
+    word_regex: Option<&'a WordRegex>,
+
+    /// The text of all removed lines that weren't yet diffed.
+    removed: Vec<u8>,
+    /// The text of all added lines that weren't yet diffed.
+    added: Vec<u8>,
+    /// The text of all spans of the current hunk.
+    text: Vec<u8>,
+    /// The spans of the current hunk, referring to `text`.
+    spans: Vec<(SpanKind, Range<usize>)>,
+    removed_words: Vec<Range<usize>>,
+    added_words: Vec<Range<usize>>,
+}
+
+impl<'a, D> WordDiff<'a, D>
+where
+    D: ConsumeWordHunk,
+{
+    /// Create a new instance that passes the spans of each hunk to `delegate`, and which uses `word_regex` to find words,
+    /// or considers sequences of non-whitespace characters words if it is `None`.
+    pub fn new(delegate: D, word_regex: Option<&'a WordRegex>) -> Self {
+        WordDiff {
+            delegate,
+            word_regex,
+            removed: Vec::new(),
+            added: Vec::new(),
+            text: Vec::new(),
+            spans: Vec::new(),
+            removed_words: Vec::new(),
+            added_words: Vec::new(),
+        }
+    }
+
+    /// Turn all removed and added lines seen so far into spans, like `diff_words_show()` in `git`.
+    fn flush(&mut self) {
+        if self.added.is_empty() {
+            if !self.removed.is_empty() {
+                push_span(&mut self.text, &mut self.spans, SpanKind::Removed, &self.removed);
+                self.removed.clear();
+            }
+            return;
+        }
+
+        split_words(self.word_regex, &self.removed, &mut self.removed_words);
+        split_words(self.word_regex, &self.added, &mut self.added_words);
+        let mut input = InternedInput::default();
+        input.update_before(self.removed_words.iter().map(|word| &self.removed[word.clone()]));
+        input.update_after(self.added_words.iter().map(|word| &self.added[word.clone()]));
+        let mut diff = Diff::compute(Algorithm::Myers, &input);
+        diff.postprocess_no_heuristic(&input);
+
+        let mut pos = 0;
+        for hunk in diff.hunks() {
+            let removed = text_range(&self.removed_words, hunk.before);
+            let added = text_range(&self.added_words, hunk.after);
+            if pos != added.start {
+                push_span(
+                    &mut self.text,
+                    &mut self.spans,
+                    SpanKind::Unchanged,
+                    &self.added[pos..added.start],
+                );
+            }
+            if !removed.is_empty() {
+                push_span(
+                    &mut self.text,
+                    &mut self.spans,
+                    SpanKind::Removed,
+                    &self.removed[removed],
+                );
+            }
+            if !added.is_empty() {
+                push_span(
+                    &mut self.text,
+                    &mut self.spans,
+                    SpanKind::Added,
+                    &self.added[added.clone()],
+                );
+            }
+            pos = added.end;
+        }
+        if pos != self.added.len() {
+            push_span(&mut self.text, &mut self.spans, SpanKind::Unchanged, &self.added[pos..]);
+        }
+        self.removed.clear();
+        self.added.clear();
+    }
+}
+
+/// Store the ranges of all words in `text` in `out`.
+fn split_words(word_regex: Option<&WordRegex>, text: &[u8], out: &mut Vec<Range<usize>>) {
+    out.clear();
+    let mut pos = 0;
+    while let Some(word) = next_word(word_regex, text, pos) {
+        pos = word.end;
+        out.push(word);
+    }
+}
+
+/// Return the range of text spanned by the `words` in `range`, or the empty range after the word preceding it.
+fn text_range(words: &[Range<usize>], range: Range<u32>) -> Range<usize> {
+    let range = range.start as usize..range.end as usize;
+    if range.is_empty() {
+        let end = range.start.checked_sub(1).map_or(0, |prev| words[prev].end);
+        end..end
+    } else {
+        words[range.start].start..words[range.end - 1].end
+    }
+}
+
+fn push_span(text: &mut Vec<u8>, spans: &mut Vec<(SpanKind, Range<usize>)>, kind: SpanKind, data: &[u8]) {
+    let start = text.len();
+    text.extend_from_slice(data);
+    spans.push((kind, start..text.len()));
+}
+
+/// Append `line` to `out`, along with a newline if it doesn't have one.
+fn push_line(out: &mut Vec<u8>, line: &[u8]) {
+    out.extend_from_slice(line);
+    if !line.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+}
+
+impl<D> ConsumeHunk for WordDiff<'_, D>
+where
+    D: ConsumeWordHunk,
+{
+    type Out = D::Out;
+
+    fn consume_hunk(&mut self, header: HunkHeader, lines: &[(DiffLineKind, &[u8])]) -> std::io::Result<()> {
+        self.text.clear();
+        self.spans.clear();
+        for &(kind, line) in lines {
+            match kind {
+                DiffLineKind::Remove => push_line(&mut self.removed, line),
+                DiffLineKind::Add => push_line(&mut self.added, line),
+                DiffLineKind::Context => {
+                    self.flush();
+                    let start = self.text.len();
+                    push_line(&mut self.text, line);
+                    self.spans.push((SpanKind::Context, start..self.text.len()));
+                }
+            }
+        }
+        self.flush();
+
+        let spans: Vec<_> = self
+            .spans
+            .iter()
+            .map(|(kind, range)| (*kind, &self.text[range.clone()]))
+            .collect();
+        self.delegate.consume_word_hunk(header, &spans)
+    }
+
+    fn finish(self) -> Self::Out {
+        self.delegate.finish()
+    }
+}
+
+/// An adapter with [`ConsumeWordHunk`] implementation to render the spans of each hunk in the given [`Mode`],
+/// and to pass the rendered hunk to a delegate.
+pub struct Renderer<D> {
+    /// The way hunks are rendered.
+    pub mode: Mode,
+    /// The delegate to receive rendered hunks.
+    pub delegate: D,
+
+    header_buf: String,
+    hunk_buf: Vec<u8>,
+}
+
+impl<D> Renderer<D>
+where
+    D: ConsumeBinaryHunkDelegate,
+{
+    /// Create a new instance that renders hunks according to `mode` and passes them to `delegate`,
+    /// along with their header that is terminated with a newline.
+    pub fn new(delegate: D, mode: Mode) -> Self {
+        Renderer {
+            mode,
+            delegate,
+            header_buf: String::new(),
+            hunk_buf: Vec::with_capacity(128),
+        }
+    }
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[m";
+
+/// How to render a span of words.
+struct Style {
+    color: &'static str,
+    prefix: &'static str,
+    suffix: &'static str,
+}
+
+impl Mode {
+    fn style(self, kind: SpanKind) -> Style {
+        let (color, prefix, suffix) = match (self, kind) {
+            (_, SpanKind::Context) => unreachable!("context lines are rendered separately"),
+            (Mode::Plain | Mode::Color, SpanKind::Unchanged) => ("", "", ""),
+            (Mode::Plain, SpanKind::Removed) => ("", "[-", "-]"),
+            (Mode::Plain, SpanKind::Added) => ("", "{+", "+}"),
+            (Mode::Porcelain, SpanKind::Unchanged) => ("", " ", "\n"),
+            (Mode::Porcelain, SpanKind::Removed) => ("", "-", "\n"),
+            (Mode::Porcelain, SpanKind::Added) => ("", "+", "\n"),
+            (Mode::Color, SpanKind::Removed) => (RED, "", ""),
+            (Mode::Color, SpanKind::Added) => (GREEN, "", ""),
+        };
+        Style { color, prefix, suffix }
+    }
+
+    fn newline(self) -> &'static str {
+        match self {
+            Mode::Plain | Mode::Color => "\n",
+            Mode::Porcelain => "~\n",
+        }
+    }
+}
+
+/// Write `words` to `out` in `mode`, styling each line separately, like `fn_out_diff_words_write_helper()` in `git`.
+fn write_words(out: &mut Vec<u8>, mode: Mode, kind: SpanKind, mut words: &[u8]) {
+    let style = mode.style(kind);
+    while !words.is_empty() {
+        let (line, rest) = match words.find_byte(b'\n') {
+            Some(pos) => (&words[..pos], Some(&words[pos + 1..])),
+            None => (words, None),
+        };
+        if !line.is_empty() {
+            out.push_str(style.color);
+            out.push_str(style.prefix);
+            out.extend_from_slice(line);
+            out.push_str(style.suffix);
+            if !style.color.is_empty() {
+                out.push_str(RESET);
+            }
+        }
+        let Some(rest) = rest else { break };
+        out.push_str(mode.newline());
+        words = rest;
+    }
+}
+
+/// Write the context `line` to `out` in `mode`, just like `git` does.
+fn write_context_line(out: &mut Vec<u8>, mode: Mode, line: &[u8]) {
+    match mode {
+        Mode::Plain => out.extend_from_slice(line),
+        Mode::Porcelain => {
+            out.push(b' ');
+            out.extend_from_slice(line);
+            out.push_str(mode.newline());
+        }
+        Mode::Color => {
+            let line = line.strip_suffix(b"\n").unwrap_or(line);
+            let (line, cr) = match line.strip_suffix(b"\r") {
+                Some(line) => (line, "\r"),
+                None => (line, ""),
+            };
+            out.extend_from_slice(line);
+            if !line.is_empty() {
+                out.push_str(RESET);
+            }
+            out.push_str(cr);
+            out.push(b'\n');
+        }
+    }
+}
+
+impl<D> ConsumeWordHunk for Renderer<D>
+where
+    D: ConsumeBinaryHunkDelegate,
+{
+    type Out = D;
+
+    fn consume_word_hunk(&mut self, header: HunkHeader, spans: &[(SpanKind, &[u8])]) -> std::io::Result<()> {
+        self.header_buf.clear();
+        match self.mode {
+            Mode::Plain | Mode::Porcelain => writeln!(self.header_buf, "{header}"),
+            Mode::Color => writeln!(self.header_buf, "{}", header.colored()),
+        }
+        .map_err(std::io::Error::other)?;
+
+        let buf = &mut self.hunk_buf;
+        buf.clear();
+        for &(kind, text) in spans {
+            match kind {
+                SpanKind::Context => write_context_line(buf, self.mode, text),
+                SpanKind::Unchanged | SpanKind::Removed | SpanKind::Added => write_words(buf, self.mode, kind, text),
+            }
+        }
+        self.delegate.consume_binary_hunk(header, &self.header_buf, buf)
+    }
+
+    fn finish(self) -> Self::Out {
+        self.delegate
+    }
+}
@@ -33,6 +33,8 @@ pub struct Rewrites {
     /// The amount of files to consider for fuzzy rename or copy tracking. Defaults to 1000, meaning that only 1000*1000
     /// combinations can be tested for fuzzy matches, i.e. the ones that try to find matches by comparing similarity.
     /// If 0, there is no limit.
+    /// This field is similar to `diff.renameLimit`, which is only applied like that with [`Matching::Git`](rewrites::Matching::Git)
+    /// as otherwise, only `limit` combinations can be tested.
     ///
     /// If the limit would not be enough to test the entire set of combinations, the algorithm will trade in precision and not
     /// run the fuzzy version of identity tests at all. That way results are never partial.
@@ -42,6 +44,10 @@ pub struct Rewrites {
     ///
     /// Leaving this off usually leads to better results as empty files don't have a unique-enough identity.
     pub track_empty: bool,
+
+    /// How to choose the sources of destinations among all candidates when matching by similarity,
+    /// defaulting to [`Matching::Simple`](rewrites::Matching::Simple).
+    pub matching: rewrites::Matching,
 }
 
 /// Contains a [Tracker](rewrites::Tracker) to detect rewrites.
@@ -67,3 +73,6 @@ pub use index::function::diff as index;
 ///
 #[cfg(feature = "blob")]
 pub mod blob;
+
+/// Statistics about changed files, rendered like `git diff --numstat`, `--stat`, `--shortstat` and `--dirstat` do.
+pub mod stats;
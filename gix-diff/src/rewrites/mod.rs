@@ -30,6 +30,26 @@ pub enum CopySource {
     FromSetOfModifiedFilesAndAllSources,
 }
 
+/// Determine how the sources of renames and copies are chosen among all candidates when matching by similarity.
+///
+/// Note that matches by identity are always found first, preferring sources with the destination's file name.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Matching {
+    /// One destination after another is assigned the most similar source, preferring sources with the same
+    /// file name if similarity is equal. Similarity is determined by diffing lines.
+    #[default]
+    Simple,
+    /// Find the same renames as `git` does, at a higher cost.
+    ///
+    /// Sources and destinations are paired up if their file names are unique among all candidates and if they
+    /// are very similar. Then all remaining pairs are scored with the same similarity estimate that `git` uses,
+    /// to assign the most similar pairs first, preferring sources with the destination's file name if
+    /// scores are equal.
+    /// Only if there are more than [`limit`](Rewrites::limit) squared pairs to score, are no
+    /// more matches found by similarity.
+    Git,
+}
+
 /// Under which circumstances we consider a file to be a copy.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Copies {
@@ -71,6 +91,7 @@ impl Default for Rewrites {
             percentage: Some(0.5),
             limit: 1000,
             track_empty: false,
+            matching: Matching::Simple,
         }
     }
 }
@@ -1,12 +1,16 @@
 //! ### Deviation
 //!
-//! Note that the algorithm implemented here is in many ways different from what `git` does.
+//! Note that with [`Matching::Simple`](crate::rewrites::Matching::Simple), the algorithm implemented here
+//! is in many ways different from what `git` does.
 //!
 //! - it's less sophisticated than `git`, but prefers a candidate whose file name matches the
 //!   destination's in identity matches, and uses that as a tie-breaker for similarity matches.
+//! - each destination is assigned its most similar source in turn, instead of assigning the most similar pairs first.
 //! - the set used for copy-detection is probably smaller by default.
-// TODO: Rewrite this based on what Git actually this, as long as there are test-cases for any 'complication'.
-//       In practice, even this simplified version seems to have worked pretty well.
+//!
+//! [`Matching::Git`](crate::rewrites::Matching::Git) finds the same renames as `git` does instead,
+//! but copies are still only found in the set of modified files, or in the whole source tree.
+//! Further, modified files are compared in their current state, whereas `git` uses their previous state as source of copies.
 
 use std::ops::Range;
 
@@ -16,10 +20,12 @@ use gix_object::tree::{EntryKind, EntryMode};
 use crate::{
     Rewrites,
     blob::{DiffLineStats, ResourceKind, platform::prepare_diff::Operation},
-    rewrites::{CopySource, Outcome, Tracker, tracker::visit::SourceKind},
+    rewrites::{CopySource, Matching, Outcome, Tracker, tracker::visit::SourceKind},
     tree::visit::{Action, ChangeId, Relation},
 };
 
+mod git;
+
 /// The kind of a change.
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, PartialEq, Eq)]
 pub enum ChangeKind {
@@ -141,6 +147,8 @@ pub mod emit {
     pub enum Error {
         #[error("Could not find blob for similarity checking")]
         FindExistingBlob(#[from] gix_object::find::existing_object::Error),
+        #[error("Could not find blob for similarity estimation")]
+        FindBlob(#[source] gix_object::find::Error),
         #[error("Could not obtain exhaustive item set to use as possible sources for copy detection")]
         GetItemsForExhaustiveCopyDetection(#[source] Box<dyn std::error::Error + Send + Sync>),
         #[error(transparent)]
@@ -389,7 +397,10 @@ impl<T: Change> Tracker<T> {
         {
             return Ok(());
         }
-        if needs_second_pass {
+        if let (true, Matching::Git) = (needs_second_pass, self.rewrites.matching) {
+            let percentage = percentage.expect("it's set to something below 1.0 and we assured this");
+            self.match_pairs_by_similarity_like_git(cb, percentage, kind, out, diff_cache, objects)?;
+        } else if needs_second_pass {
             let is_limited = if self.rewrites.limit == 0 {
                 false
             } else {
@@ -778,7 +789,7 @@ fn find_match<'a, T: Change>(
             stats.num_similarity_checks += 1;
             *num_checks += 1;
             match prep.operation {
-                Operation::InternalDiff { algorithm } => {
+                Operation::InternalDiff { algorithm, .. } => {
                     let tokens = crate::blob::InternedInput::new(prep.old.intern_source(), prep.new.intern_source());
                     let diff = crate::blob::Diff::compute(algorithm, &tokens);
                     let removed_bytes = diff::removed_bytes(&diff, &tokens);
@@ -0,0 +1,411 @@
+//! Find renames and copies by similarity just like `git` does in `diffcore-rename.c`, using similarity estimates
+//! as computed in `diffcore-delta.c`.
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use bstr::BStr;
+use gix_hash::ObjectId;
+
+use super::{Change, ChangeKind, Item, emit, filename, visit};
+use crate::{
+    blob::{DiffLineStats, ResourceKind, platform::prepare_diff::Operation, platform::resource::Data},
+    rewrites::{Outcome, Tracker},
+    tree::visit::{Action, Relation},
+};
+
+/// The score of a perfect match, with all other scores being relative to it.
+const MAX_SCORE: u64 = 60000;
+/// The amount of best candidates to remember for each destination.
+const NUM_CANDIDATES_PER_DESTINATION: usize = 4;
+
+impl<T: Change> Tracker<T> {
+    /// Find the sources of all destinations that weren't matched by identity yet, like `git` does.
+    ///
+    /// First, pair sources and destinations whose file names are unique among all remaining sources and destinations
+    /// respectively, if they are similar enough. Then, if the rename limit permits, score all remaining pairs to
+    /// remember the best candidates of each destination, and assign the best-scoring pairs first.
+    pub(super) fn match_pairs_by_similarity_like_git(
+        &mut self,
+        cb: &mut impl FnMut(visit::Destination<'_, T>, Option<visit::Source<'_, T>>) -> Action,
+        percentage: f32,
+        kind: visit::SourceKind,
+        out: &mut Outcome,
+        diff_cache: &mut crate::blob::Platform,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<(), emit::Error> {
+        let min_score = ((percentage * MAX_SCORE as f32).round() as u64).min(MAX_SCORE);
+        let mut spans = HashMap::new();
+
+        if kind == visit::SourceKind::Rename && self.rewrites.copies.is_none() {
+            let min_basename_score = min_score + (MAX_SCORE - min_score) / 2;
+            let mut pairs = Vec::new();
+            let (sources, destinations) = self.sources_and_destinations(kind);
+            let destination_by_name = unique_by_filename(&destinations, &self.items, &self.path_backing);
+            for (name, src_idx) in unique_by_filename(&sources, &self.items, &self.path_backing) {
+                let Some(dst_idx) = destination_by_name.get(name).copied() else {
+                    continue;
+                };
+                let score =
+                    self.estimate_similarity(src_idx, dst_idx, min_score, &mut spans, out, diff_cache, objects)?;
+                if score >= min_basename_score {
+                    pairs.push((src_idx, dst_idx, score));
+                }
+            }
+            if self.emit_pairs(cb, pairs, kind, diff_cache, objects)?.is_break() {
+                return Ok(());
+            }
+        }
+
+        let (sources, destinations) = self.sources_and_destinations(kind);
+        let limit = self.rewrites.limit;
+        let permutations = sources.len() * destinations.len();
+        if limit != 0 && permutations > limit.saturating_mul(limit) {
+            match kind {
+                visit::SourceKind::Rename => {
+                    out.num_similarity_checks_skipped_for_rename_tracking_due_to_limit = permutations;
+                }
+                visit::SourceKind::Copy => {
+                    out.num_similarity_checks_skipped_for_copy_tracking_due_to_limit = permutations;
+                }
+            }
+            return Ok(());
+        }
+
+        let mut candidates = Vec::with_capacity(destinations.len() * NUM_CANDIDATES_PER_DESTINATION);
+        for &dst_idx in &destinations {
+            let mut best = [None; NUM_CANDIDATES_PER_DESTINATION];
+            let dst_name = filename(self.items[dst_idx].location(&self.path_backing));
+            for &src_idx in &sources {
+                let candidate = Candidate {
+                    src_idx,
+                    dst_idx,
+                    score: self
+                        .estimate_similarity(src_idx, dst_idx, min_score, &mut spans, out, diff_cache, objects)?,
+                    has_same_filename: filename(self.items[src_idx].location(&self.path_backing)) == dst_name,
+                };
+                record_if_better(&mut best, candidate);
+            }
+            candidates.extend(best);
+        }
+        candidates.sort_by(|a, b| compare_candidates(a.as_ref(), b.as_ref()));
+
+        let mut pairs = Vec::new();
+        let mut is_destination_done = vec![false; self.items.len()];
+        let mut is_source_used = vec![false; self.items.len()];
+        for candidate in candidates.into_iter().map_while(|c| c.filter(|c| c.score >= min_score)) {
+            if is_destination_done[candidate.dst_idx]
+                || (kind == visit::SourceKind::Rename && is_source_used[candidate.src_idx])
+            {
+                continue;
+            }
+            is_destination_done[candidate.dst_idx] = true;
+            is_source_used[candidate.src_idx] = true;
+            pairs.push((candidate.src_idx, candidate.dst_idx, candidate.score));
+        }
+        let _ = self.emit_pairs(cb, pairs, kind, diff_cache, objects)?;
+        Ok(())
+    }
+
+    /// Return the indices of items that can still be sources and destinations for `kind`, each sorted by location.
+    fn sources_and_destinations(&self, kind: visit::SourceKind) -> (Vec<usize>, Vec<usize>) {
+        let is_tracked = |item: &Item<T>| {
+            item.change.entry_mode().is_no_tree()
+                && (self.rewrites.track_empty
+                    || matches!(item.change.relation(), Some(Relation::ChildOfParent(_)))
+                    || {
+                        let id = item.change.id();
+                        id != ObjectId::empty_blob(id.kind())
+                    })
+        };
+        let (mut sources, mut destinations) = (Vec::new(), Vec::new());
+        for (idx, item) in self.items.iter().enumerate().filter(|(_, item)| is_tracked(item)) {
+            match (item.change.kind(), kind) {
+                (ChangeKind::Addition, _) if !item.emitted => destinations.push(idx),
+                (ChangeKind::Deletion, visit::SourceKind::Rename) if !item.emitted => sources.push(idx),
+                (ChangeKind::Modification, visit::SourceKind::Copy) => sources.push(idx),
+                _ => {}
+            }
+        }
+        let by_location = |a: &usize, b: &usize| {
+            self.items[*a]
+                .location(&self.path_backing)
+                .cmp(self.items[*b].location(&self.path_backing))
+        };
+        sources.sort_by(by_location);
+        destinations.sort_by(by_location);
+        (sources, destinations)
+    }
+
+    /// Estimate how much of the destination at `dst_idx` was taken from the source at `src_idx`,
+    /// as a score from 0 to [`MAX_SCORE`]. Scores that can't reach `min_score` due to the difference in size are 0.
+    ///
+    /// Only regular files are compared by similarity, everything else has to match by identity.
+    #[expect(clippy::too_many_arguments)]
+    fn estimate_similarity(
+        &self,
+        src_idx: usize,
+        dst_idx: usize,
+        min_score: u64,
+        spans: &mut HashMap<usize, Option<Spans>>,
+        out: &mut Outcome,
+        diff_cache: &mut crate::blob::Platform,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<u64, emit::Error> {
+        let (src, dst) = (&self.items[src_idx], &self.items[dst_idx]);
+        let dst_mode = dst.change.entry_mode();
+        if !dst_mode.is_blob() || !src.change.entry_mode().is_blob() || !src.entry_mode_compatible(dst_mode) {
+            return Ok(0);
+        }
+        out.num_similarity_checks += 1;
+        if !spans.contains_key(&src_idx) || !spans.contains_key(&dst_idx) {
+            self.set_resources(src_idx, dst_idx, diff_cache, objects)?;
+            let prep = diff_cache.prepare_diff()?;
+            let mut buf = Vec::new();
+            for (idx, data, id) in [
+                (src_idx, prep.old.data, prep.old.id),
+                (dst_idx, prep.new.data, prep.new.id),
+            ] {
+                if spans.contains_key(&idx) {
+                    continue;
+                }
+                let item_spans = match data {
+                    Data::Buffer { buf, .. } => Some(Spans::new(buf, true)),
+                    Data::Binary { .. } => objects
+                        .try_find(id, &mut buf)
+                        .map_err(emit::Error::FindBlob)?
+                        .map(|data| Spans::new(data.data, false)),
+                    Data::Missing => None,
+                };
+                spans.insert(idx, item_spans);
+            }
+        }
+        let (Some(Some(src)), Some(Some(dst))) = (spans.get(&src_idx), spans.get(&dst_idx)) else {
+            return Ok(0);
+        };
+
+        let max_size = src.size.max(dst.size);
+        let delta_size = max_size - src.size.min(dst.size);
+        if max_size * (MAX_SCORE - min_score) < delta_size * MAX_SCORE || dst.size == 0 {
+            return Ok(0);
+        }
+        Ok(src.count_copied_to(dst) * MAX_SCORE / max_size)
+    }
+
+    fn set_resources(
+        &self,
+        src_idx: usize,
+        dst_idx: usize,
+        diff_cache: &mut crate::blob::Platform,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<(), emit::Error> {
+        for (idx, kind) in [
+            (src_idx, ResourceKind::OldOrSource),
+            (dst_idx, ResourceKind::NewOrDestination),
+        ] {
+            let item = &self.items[idx];
+            let (id, mode) = item.change.id_and_entry_mode();
+            diff_cache.set_resource(
+                id.to_owned(),
+                mode.kind(),
+                item.location(&self.path_backing),
+                kind,
+                objects,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Emit all `(src_idx, dst_idx, score)` `pairs` in order of their destination's location and mark them as emitted.
+    /// The sources of copies are not marked, so they remain visible as modifications.
+    fn emit_pairs(
+        &mut self,
+        cb: &mut impl FnMut(visit::Destination<'_, T>, Option<visit::Source<'_, T>>) -> Action,
+        mut pairs: Vec<(usize, usize, u64)>,
+        kind: visit::SourceKind,
+        diff_cache: &mut crate::blob::Platform,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<Action, emit::Error> {
+        pairs.sort_by(|a, b| {
+            self.items[a.1]
+                .location(&self.path_backing)
+                .cmp(self.items[b.1].location(&self.path_backing))
+        });
+        for (src_idx, dst_idx, score) in pairs {
+            let diff = self.diff_line_stats(src_idx, dst_idx, score, diff_cache, objects)?;
+            let (src, dst) = (&self.items[src_idx], &self.items[dst_idx]);
+            let (id, entry_mode) = src.change.id_and_entry_mode();
+            let src = visit::Source {
+                entry_mode,
+                id: id.to_owned(),
+                kind,
+                location: src.location(&self.path_backing),
+                change: &src.change,
+                diff: Some(diff),
+            };
+            let dst = visit::Destination {
+                change: dst.change.clone(),
+                location: dst.location(&self.path_backing),
+            };
+            let res = cb(dst, Some(src));
+
+            // Like `git`, keep reporting modified files as such even if they are the source of copies.
+            if kind == visit::SourceKind::Rename {
+                self.items[src_idx].emitted = true;
+            }
+            self.items[dst_idx].emitted = true;
+
+            if res.is_break() {
+                return Ok(res);
+            }
+        }
+        Ok(std::ops::ControlFlow::Continue(()))
+    }
+
+    /// Compute the line-based diff statistics of a pair that was found to be similar with the given `score`.
+    /// Line counts are 0 if one of them is binary.
+    fn diff_line_stats(
+        &self,
+        src_idx: usize,
+        dst_idx: usize,
+        score: u64,
+        diff_cache: &mut crate::blob::Platform,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<DiffLineStats, emit::Error> {
+        let mut stats = DiffLineStats {
+            similarity: score as f32 / MAX_SCORE as f32,
+            ..Default::default()
+        };
+        self.set_resources(src_idx, dst_idx, diff_cache, objects)?;
+        let prep = diff_cache.prepare_diff()?;
+        match prep.operation {
+            Operation::InternalDiff { algorithm, .. } => {
+                let tokens = crate::blob::InternedInput::new(prep.old.intern_source(), prep.new.intern_source());
+                let diff = crate::blob::Diff::compute(algorithm, &tokens);
+                stats.removals = diff.count_removals();
+                stats.insertions = diff.count_additions();
+                stats.before = tokens.before.len();
+                stats.after = tokens.after.len();
+            }
+            Operation::ExternalCommand { .. } => {
+                unreachable!("we have disabled this possibility with an option")
+            }
+            Operation::SourceOrDestinationIsBinary => {}
+        }
+        Ok(stats)
+    }
+}
+
+/// Return `(filename, item_idx)` pairs of all `indices` whose file name isn't shared with any other of the `indices`.
+fn unique_by_filename<'a, T: Change>(
+    indices: &[usize],
+    items: &[Item<T>],
+    path_backing: &'a [u8],
+) -> HashMap<&'a BStr, usize> {
+    let mut by_name = HashMap::<_, Option<usize>>::new();
+    for &idx in indices {
+        by_name
+            .entry(filename(items[idx].location(path_backing)))
+            .and_modify(|idx| *idx = None)
+            .or_insert(Some(idx));
+    }
+    by_name
+        .into_iter()
+        .filter_map(|(name, idx)| idx.map(|idx| (name, idx)))
+        .collect()
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Candidate {
+    src_idx: usize,
+    dst_idx: usize,
+    score: u64,
+    has_same_filename: bool,
+}
+
+/// Order better candidates first, preferring the ones with the same file name if the score is the same,
+/// and put unset candidates last.
+fn compare_candidates(a: Option<&Candidate>, b: Option<&Candidate>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => b
+            .score
+            .cmp(&a.score)
+            .then_with(|| b.has_same_filename.cmp(&a.has_same_filename)),
+    }
+}
+
+/// Replace the worst of the `best` candidates with `candidate` if it's better.
+fn record_if_better(best: &mut [Option<Candidate>; NUM_CANDIDATES_PER_DESTINATION], candidate: Candidate) {
+    let mut worst = 0;
+    for idx in 1..best.len() {
+        if compare_candidates(best[idx].as_ref(), best[worst].as_ref()) == Ordering::Greater {
+            worst = idx;
+        }
+    }
+    if compare_candidates(best[worst].as_ref(), Some(&candidate)) == Ordering::Greater {
+        best[worst] = Some(candidate);
+    }
+}
+
+/// The amount of bytes in each chunk of data, keyed by the hash of the chunk.
+struct Spans {
+    /// The size of the data the spans were computed from.
+    size: u64,
+    /// `(hash, num_bytes)` pairs, sorted by hash.
+    counts: Vec<(u32, u64)>,
+}
+
+impl Spans {
+    /// Cut `data` into chunks that end with a newline, or after 64 bytes, whichever comes first, and
+    /// count the bytes of all chunks with the same hash.
+    /// If `is_text`, carriage returns in front of newlines are ignored.
+    fn new(data: &[u8], is_text: bool) -> Self {
+        /// A prime between 2^16 and 2^17.
+        const HASH_BASE: u32 = 107927;
+        let mut counts = HashMap::<u32, u64>::new();
+        let (mut num_bytes, mut accum1, mut accum2) = (0, 0u32, 0u32);
+        for (idx, &byte) in data.iter().enumerate() {
+            if is_text && byte == b'\r' && data.get(idx + 1) == Some(&b'\n') {
+                continue;
+            }
+            let prev_accum1 = accum1;
+            accum1 = (accum1 << 7) ^ (accum2 >> 25);
+            accum2 = (accum2 << 7) ^ (prev_accum1 >> 25);
+            accum1 = accum1.wrapping_add(byte.into());
+            num_bytes += 1;
+            if num_bytes < 64 && byte != b'\n' {
+                continue;
+            }
+            *counts
+                .entry(accum1.wrapping_add(accum2.wrapping_mul(0x61)) % HASH_BASE)
+                .or_default() += num_bytes;
+            (num_bytes, accum1, accum2) = (0, 0, 0);
+        }
+        if num_bytes > 0 {
+            *counts
+                .entry(accum1.wrapping_add(accum2.wrapping_mul(0x61)) % HASH_BASE)
+                .or_default() += num_bytes;
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_unstable();
+        Spans {
+            size: data.len() as u64,
+            counts,
+        }
+    }
+
+    /// Return the amount of bytes in `dst` that seem to be copied from `self`.
+    fn count_copied_to(&self, dst: &Spans) -> u64 {
+        let mut dst_counts = dst.counts.iter().peekable();
+        let mut copied = 0;
+        for (hash, src_count) in &self.counts {
+            while dst_counts.next_if(|(dst_hash, _)| dst_hash < hash).is_some() {}
+            if let Some((_, dst_count)) = dst_counts.next_if(|(dst_hash, _)| dst_hash == hash) {
+                copied += src_count.min(dst_count);
+            }
+        }
+        copied
+    }
+}
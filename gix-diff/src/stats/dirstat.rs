@@ -0,0 +1,137 @@
+use bstr::BString;
+
+/// Determine how much each file contributes to the changes of its directories.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Count the changed lines of each file, like `git diff --dirstat=lines`.
+    ///
+    /// Binary files count one line for every 64 bytes of their sizes before and after the change.
+    #[default]
+    Lines,
+    /// Count each changed file once, like `git diff --dirstat=files`.
+    ///
+    /// Files without [changes to their content](crate::stats::Counts::is_empty()) don't count.
+    Files,
+}
+
+/// Options for use in [`dirstat()`](super::dirstat()).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// How to count the changes of each file.
+    pub mode: Mode,
+    /// If `true`, the changes of directories that are displayed still count towards their parent directory,
+    /// like `git diff --dirstat=cumulative`.
+    pub cumulative: bool,
+    /// The share of all changes in permille a directory needs to be displayed, defaulting to 30, i.e. 3%.
+    pub limit_permille: u32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            mode: Mode::default(),
+            cumulative: false,
+            limit_permille: 30,
+        }
+    }
+}
+
+/// A directory along with its share of all changes, as returned by [`dirstat()`](super::dirstat()).
+///
+/// Use its [`Display`](std::fmt::Display) implementation to produce the line `git diff --dirstat` would print.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Directory {
+    /// The location of the directory, with a trailing slash.
+    pub location: BString,
+    /// The share of all changes that happened in this directory, in permille.
+    pub permille: u32,
+}
+
+impl std::fmt::Display for Directory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:4}.{}% {}", self.permille / 10, self.permille % 10, self.location)
+    }
+}
+
+pub(super) mod function {
+    use bstr::{BStr, ByteSlice};
+
+    use super::{Directory, Mode, Options};
+    use crate::stats::{Counts, File};
+
+    /// Return all directories of `files` that received at least [a share](Options::limit_permille) of all changes,
+    /// like `git diff --dirstat` does, and configured by `options`.
+    ///
+    /// Directories are ordered so that subdirectories come before their parent directory, and changes to the top-level
+    /// directory are never returned. A directory is also not returned if all of its changes are in a single subdirectory.
+    pub fn dirstat(files: &[File], options: &Options) -> Vec<Directory> {
+        let mut damages: Vec<_> = files
+            .iter()
+            .map(|file| (file.location.as_bstr(), damage(file.counts, options.mode)))
+            .collect();
+        let changed: u64 = damages.iter().map(|(_, damage)| damage).sum();
+        let mut out = Vec::new();
+        if changed == 0 {
+            return out;
+        }
+        damages.sort_by(|a, b| a.0.cmp(b.0));
+        gather(&mut damages.as_slice(), "".into(), changed, options, &mut out);
+        out
+    }
+
+    fn damage(counts: Counts, mode: Mode) -> u64 {
+        match mode {
+            Mode::Files => u64::from(!counts.is_empty()),
+            Mode::Lines => match counts {
+                Counts::Lines { insertions, removals } => insertions + removals,
+                Counts::Binary {
+                    size_before,
+                    size_after,
+                } => (size_before + size_after).div_ceil(64),
+            },
+        }
+    }
+
+    /// Consume all `damages` in `base` and return the sum of their damages which weren't reported yet.
+    fn gather(
+        damages: &mut &[(&BStr, u64)],
+        base: &BStr,
+        changed: u64,
+        options: &Options,
+        out: &mut Vec<Directory>,
+    ) -> u64 {
+        let (mut sum, mut sources) = (0, 0);
+        while let Some(&(location, damage)) = damages.first() {
+            if !location.starts_with(base) {
+                break;
+            }
+            match location[base.len()..].find_byte(b'/') {
+                Some(slash_pos) => {
+                    let dir = location[..base.len() + slash_pos + 1].as_bstr();
+                    sum += gather(damages, dir, changed, options, out);
+                    sources += 1;
+                }
+                None => {
+                    sum += damage;
+                    *damages = &damages[1..];
+                    sources += 2;
+                }
+            }
+        }
+
+        // Changes in the top-level aren't reported, nor are those which all come from a single subdirectory.
+        if !base.is_empty() && sources != 1 && sum != 0 {
+            let permille = (sum * 1000 / changed) as u32;
+            if permille >= options.limit_permille {
+                out.push(Directory {
+                    location: base.to_owned(),
+                    permille,
+                });
+                if !options.cumulative {
+                    return 0;
+                }
+            }
+        }
+        sum
+    }
+}
@@ -0,0 +1,186 @@
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+/// The amount of change a single [`File`] underwent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Counts {
+    /// The file is text, and lines were added or removed.
+    Lines {
+        /// The amount of lines that were added.
+        insertions: u64,
+        /// The amount of lines that were removed.
+        removals: u64,
+    },
+    /// The file is binary, so only its size in bytes before and after the change is known.
+    ///
+    /// Both sizes are 0 if the content didn't change, for instance if only the mode or the location of the file changed.
+    Binary {
+        /// The size of the file before the change, or 0 if it was added.
+        size_before: u64,
+        /// The size of the file after the change, or 0 if it was deleted.
+        size_after: u64,
+    },
+}
+
+impl Counts {
+    /// Return `true` if no lines or bytes were changed.
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            Counts::Lines { insertions, removals } => insertions == 0 && removals == 0,
+            Counts::Binary {
+                size_before,
+                size_after,
+            } => size_before == 0 && size_after == 0,
+        }
+    }
+}
+
+/// The statistics of a single file that changed between two trees, similar to a line of `git diff --numstat`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct File {
+    /// The location of the file before it was renamed or copied to `location`, or `None` if it kept its location.
+    pub source_location: Option<BString>,
+    /// The location of the file after the change, or its previous location if it was deleted.
+    pub location: BString,
+    /// The amount of change in lines or bytes.
+    pub counts: Counts,
+}
+
+impl File {
+    /// Return the location of this file as `git` would display it, which is either its location or the
+    /// [rename arrow](rename_location()) from its source location.
+    ///
+    /// Note that the location isn't quoted, even if it contains special characters.
+    pub fn display_location(&self) -> BString {
+        match &self.source_location {
+            Some(source_location) => rename_location(source_location.as_ref(), self.location.as_ref()),
+            None => self.location.clone(),
+        }
+    }
+}
+
+/// Return `source` and `destination` like `git` displays a renamed file, which is `source => destination`,
+/// with the common leading and trailing directories pulled out of a pair of braces, like `dir/{old => new}/file`.
+pub fn rename_location(source: &BStr, destination: &BStr) -> BString {
+    let (a, b) = (source.as_bytes(), destination.as_bytes());
+    // Like C strings, pretend there is a null-terminator at the end of both paths.
+    let byte_at = |path: &[u8], idx: usize| path.get(idx).copied().unwrap_or(0);
+
+    let mut prefix_len = 0;
+    for (idx, (lhs, rhs)) in a.iter().zip(b).enumerate() {
+        if lhs != rhs {
+            break;
+        }
+        if *lhs == b'/' {
+            prefix_len = idx + 1;
+        }
+    }
+
+    // If there is a common prefix, it ends in a slash which we may see again here, so the suffix can include it.
+    let suffix_stop = prefix_len.saturating_sub(1);
+    let mut suffix_len = 0;
+    let (mut a_idx, mut b_idx) = (a.len(), b.len());
+    loop {
+        if byte_at(a, a_idx) != byte_at(b, b_idx) {
+            break;
+        }
+        if byte_at(a, a_idx) == b'/' {
+            suffix_len = a.len() - a_idx;
+        }
+        if a_idx == suffix_stop || b_idx == suffix_stop {
+            break;
+        }
+        a_idx -= 1;
+        b_idx -= 1;
+    }
+
+    // Prefix and suffix may overlap by the slash they share.
+    let a_mid_len = a.len().saturating_sub(prefix_len + suffix_len);
+    let b_mid_len = b.len().saturating_sub(prefix_len + suffix_len);
+    let has_braces = prefix_len + suffix_len > 0;
+
+    let mut out = BString::default();
+    if has_braces {
+        out.push_str(&a[..prefix_len]);
+        out.push(b'{');
+    }
+    out.push_str(&a[prefix_len..][..a_mid_len]);
+    out.push_str(" => ");
+    out.push_str(&b[prefix_len..][..b_mid_len]);
+    if has_braces {
+        out.push(b'}');
+        out.push_str(&a[a.len() - suffix_len..]);
+    }
+    out
+}
+
+/// The summary of the statistics of multiple files, like `git diff --shortstat` displays it.
+///
+/// Use its [`Display`](std::fmt::Display) implementation to produce the line `git` would print.
+#[derive(Default, Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Summary {
+    /// The amount of changed files, including binary files.
+    pub files_changed: u64,
+    /// The total amount of lines added to text files.
+    pub insertions: u64,
+    /// The total amount of lines removed from text files.
+    pub removals: u64,
+}
+
+impl Summary {
+    /// Summarize the statistics of all `files`.
+    pub fn from_files(files: &[File]) -> Self {
+        files.iter().fold(Summary::default(), |mut summary, file| {
+            summary.files_changed += 1;
+            if let Counts::Lines { insertions, removals } = file.counts {
+                summary.insertions += insertions;
+                summary.removals += removals;
+            }
+            summary
+        })
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: u64| if count == 1 { "" } else { "s" };
+        let Summary {
+            files_changed,
+            insertions,
+            removals,
+        } = *self;
+        if files_changed == 0 {
+            return f.write_str(" 0 files changed");
+        }
+        write!(f, " {files_changed} file{} changed", plural(files_changed))?;
+        if insertions != 0 || removals == 0 {
+            write!(f, ", {insertions} insertion{}(+)", plural(insertions))?;
+        }
+        if removals != 0 || insertions == 0 {
+            write!(f, ", {removals} deletion{}(-)", plural(removals))?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a line with the added and removed lines of each of `files` to `out`, like `git diff --numstat` does.
+///
+/// Binary files display `-` instead of their counts.
+pub fn numstat(files: &[File], mut out: impl std::io::Write) -> std::io::Result<()> {
+    for file in files {
+        match file.counts {
+            Counts::Lines { insertions, removals } => write!(out, "{insertions}\t{removals}\t")?,
+            Counts::Binary { .. } => out.write_all(b"-\t-\t")?,
+        }
+        out.write_all(&file.display_location())?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+///
+pub mod stat;
+pub use stat::function::stat;
+
+///
+pub mod dirstat;
+pub use dirstat::function::dirstat;
@@ -0,0 +1,215 @@
+/// Options for use in [`stat()`](super::stat()).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The maximum width of each line, like `git diff --stat=<width>`, which defaults to 80.
+    ///
+    /// Note that lines of binary files may exceed this width if it's too small to fit their sizes.
+    pub width: usize,
+    /// The maximum width of the file location on each line, like `git diff --stat-name-width=<width>`,
+    /// or `None` to only limit it by `width`.
+    ///
+    /// Locations that are too long are abbreviated from the left with `...`.
+    pub name_width: Option<usize>,
+    /// The maximum width of the graph of `+` and `-` characters, like `git diff --stat-graph-width=<width>`,
+    /// or `None` to only limit it by `width`.
+    pub graph_width: Option<usize>,
+    /// The maximum amount of files to display, like `git diff --stat-count=<count>`, or `None` to display all of them.
+    ///
+    /// Files that aren't displayed are indicated by a line with `...`, but still count towards the summary line.
+    pub count: Option<usize>,
+    /// If `true`, the `+` and `-` characters as well as the sizes of binary files are colored like `git` does it.
+    pub color: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            width: 80,
+            name_width: None,
+            graph_width: None,
+            count: None,
+            color: false,
+        }
+    }
+}
+
+pub(super) mod function {
+    use bstr::{BStr, ByteSlice};
+
+    use super::Options;
+    use crate::stats::{Counts, File, Summary};
+
+    /// Write a line for each of `files` with its location, the amount of changed lines and a graph of `+` and `-` characters
+    /// to `out`, followed by a [summary line](Summary), like `git diff --stat` does, and configured by `options`.
+    ///
+    /// Nothing is written if `files` is empty.
+    pub fn stat(files: &[File], options: &Options, mut out: impl std::io::Write) -> std::io::Result<()> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        let (add_color, del_color, reset) = if options.color {
+            ("\x1b[32m", "\x1b[31m", "\x1b[m")
+        } else {
+            ("", "", "")
+        };
+
+        let count = options.count.map_or(files.len(), |count| count.min(files.len()));
+        let shown_files = &files[..count];
+        let names: Vec<_> = shown_files.iter().map(File::display_location).collect();
+
+        let (mut max_len, mut max_change, mut bin_width, mut number_width) = (0, 0, 0, 0);
+        for (file, name) in shown_files.iter().zip(&names) {
+            max_len = max_len.max(display_width(name.as_ref()));
+            match file.counts {
+                Counts::Lines { insertions, removals } => max_change = max_change.max(insertions + removals),
+                Counts::Binary {
+                    size_before,
+                    size_after,
+                } => {
+                    // "Bin XXX -> YYY bytes"
+                    bin_width = bin_width.max(14 + decimal_width(size_after) + decimal_width(size_before));
+                    // Display change counts aligned with "Bin".
+                    number_width = 3;
+                }
+            }
+        }
+
+        // Of the total `width`, the location is given up to 5/8th, and the rest goes to the graph and the
+        // constant parts, which are " ", " | " as well as the amount of changed lines.
+        let number_width = number_width.max(decimal_width(max_change)) as i64;
+        let width = (options.width as i64).max(16 + 6 + number_width);
+        let max_len = max_len as i64;
+        let max_change = max_change as i64;
+        let bin_width = bin_width as i64;
+        let max_graph_width = options
+            .graph_width
+            .filter(|width| *width != 0)
+            .map(|width| width as i64);
+
+        let mut graph_width = if max_change + 4 > bin_width {
+            max_change
+        } else {
+            bin_width - 4
+        };
+        if let Some(max_graph_width) = max_graph_width {
+            graph_width = graph_width.min(max_graph_width);
+        }
+        let mut name_width = options
+            .name_width
+            .filter(|width| *width != 0)
+            .map_or(max_len, |width| (width as i64).min(max_len));
+
+        if name_width + number_width + 6 + graph_width > width {
+            if graph_width > width * 3 / 8 - number_width - 6 {
+                graph_width = (width * 3 / 8 - number_width - 6).max(6);
+            }
+            if let Some(max_graph_width) = max_graph_width {
+                graph_width = graph_width.min(max_graph_width);
+            }
+            if name_width > width - number_width - 6 - graph_width {
+                name_width = width - number_width - 6 - graph_width;
+            } else {
+                graph_width = width - number_width - 6 - name_width;
+            }
+        }
+
+        let number_width = number_width as usize;
+        for (file, name) in shown_files.iter().zip(&names) {
+            let mut name = name.as_bstr();
+            let mut prefix = "";
+            let mut len = name_width;
+            if name_width < display_width(name) as i64 {
+                prefix = "...";
+                len = (len - 3).max(0);
+                while display_width(name) as i64 > len {
+                    let first_char_len = name.char_indices().next().map_or(name.len(), |(_, end, _)| end);
+                    name = name[first_char_len..].as_bstr();
+                }
+                if let Some(slash_pos) = name.find_byte(b'/') {
+                    name = name[slash_pos..].as_bstr();
+                }
+            }
+            let padding = (len - display_width(name) as i64).max(0) as usize;
+
+            out.write_all(b" ")?;
+            out.write_all(prefix.as_bytes())?;
+            out.write_all(name)?;
+            match file.counts {
+                Counts::Binary {
+                    size_before,
+                    size_after,
+                } => {
+                    write!(out, "{:padding$} | {:>number_width$}", "", "Bin")?;
+                    if size_before != 0 || size_after != 0 {
+                        write!(
+                            out,
+                            " {del_color}{size_before}{reset} -> {add_color}{size_after}{reset} bytes"
+                        )?;
+                    }
+                    out.write_all(b"\n")?;
+                }
+                Counts::Lines { insertions, removals } => {
+                    let total = insertions + removals;
+                    let (mut add, mut del) = (insertions as i64, removals as i64);
+                    if graph_width <= max_change {
+                        let mut total = scale_linear(add + del, graph_width, max_change);
+                        if total < 2 && add != 0 && del != 0 {
+                            total = 2;
+                        }
+                        if add < del {
+                            add = scale_linear(add, graph_width, max_change);
+                            del = total - add;
+                        } else {
+                            del = scale_linear(del, graph_width, max_change);
+                            add = total - del;
+                        }
+                    }
+                    write!(
+                        out,
+                        "{:padding$} | {total:>number_width$}{}",
+                        "",
+                        if total != 0 { " " } else { "" }
+                    )?;
+                    write_graph(&mut out, '+', add, add_color, reset)?;
+                    write_graph(&mut out, '-', del, del_color, reset)?;
+                    out.write_all(b"\n")?;
+                }
+            }
+        }
+
+        if count < files.len() {
+            out.write_all(b" ...\n")?;
+        }
+        writeln!(out, "{}", Summary::from_files(files))
+    }
+
+    /// Scale `it` linearly to fit into `width` columns, but make sure that at least one column is used if `it` isn't 0.
+    fn scale_linear(it: i64, width: i64, max_change: i64) -> i64 {
+        if it == 0 {
+            return 0;
+        }
+        1 + (it * (width - 1) / max_change)
+    }
+
+    fn write_graph(
+        out: &mut impl std::io::Write,
+        ch: char,
+        count: i64,
+        color: &str,
+        reset: &str,
+    ) -> std::io::Result<()> {
+        if count <= 0 {
+            return Ok(());
+        }
+        let graph: String = std::iter::repeat_n(ch, count as usize).collect();
+        write!(out, "{color}{graph}{reset}")
+    }
+
+    fn display_width(name: &BStr) -> usize {
+        name.chars().count()
+    }
+
+    fn decimal_width(num: u64) -> usize {
+        num.checked_ilog10().map_or(1, |log| log as usize + 1)
+    }
+}
@@ -0,0 +1,180 @@
+use gix_diff::blob::{
+    Algorithm,
+    combined::{Hunk, Line, LineRange, Options},
+};
+use gix_object::bstr::{BString, ByteSlice};
+use pretty_assertions::assert_eq;
+
+use super::hunks_of_baseline;
+
+#[test]
+fn baseline() -> crate::Result {
+    let worktree_path = crate::scripted_fixture_read_only("make_diff_for_combined_repo.sh")?;
+    let asset_dir = worktree_path.join("assets");
+
+    let mut num_baselines = 0;
+    let mut mismatches = Vec::new();
+    for entry in std::fs::read_dir(&worktree_path)? {
+        let file_name = entry?.file_name();
+        let file_name = file_name.to_str().expect("ascii filename");
+        let Some(name) = file_name.strip_suffix(".baseline") else {
+            continue;
+        };
+        let [name, mode, context_size] = name.split('.').collect::<Vec<_>>()[..] else {
+            unreachable!("BUG: need file named '<name>.<mode>.<context-size>.baseline'")
+        };
+        let (dense, color) = match mode {
+            "cc" => (true, false),
+            "c" => (false, false),
+            "color" => (true, true),
+            other => unreachable!("BUG: '{other}' is not a supported mode"),
+        };
+
+        let mut parents = Vec::new();
+        for parent in 1.. {
+            match std::fs::read(asset_dir.join(format!("{name}_parent{parent}.blob"))) {
+                Ok(data) => parents.push(data),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let result = std::fs::read(asset_dir.join(format!("{name}_result.blob")))?;
+        let expected = hunks_of_baseline(&std::fs::read(worktree_path.join(file_name))?);
+        num_baselines += 1;
+        if parents.contains(&result) {
+            // `git` doesn't show paths which didn't change relative to one of the parents.
+            assert_eq!(expected, "", "{file_name}: a path that matches a parent isn't shown");
+            continue;
+        }
+
+        let parents: Vec<_> = parents.iter().map(Vec::as_slice).collect();
+        let hunks = gix_diff::blob::combined::diff(
+            Algorithm::Myers,
+            &parents,
+            &result,
+            Options {
+                context_size: context_size.parse()?,
+                dense,
+            },
+        )?;
+        let mut actual = Vec::new();
+        for hunk in &hunks {
+            hunk.write_to(&mut actual, color)?;
+        }
+        let actual = BString::from(actual);
+        if actual != expected && !(context_size == "0" && has_git_end_of_file_quirk(&actual, &expected)) {
+            mismatches.push((file_name.to_owned(), actual, expected));
+        }
+    }
+    assert_eq!(num_baselines, 27 * 3 * 3, "all baselines were checked");
+    if let Some((file_name, actual, expected)) = mismatches.first() {
+        let names: Vec<_> = mismatches.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(
+            actual, expected,
+            "{file_name} is the first of mismatching baselines {names:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn dense_diffs_only_keep_hunks_that_differ_from_all_parents() -> crate::Result {
+    let parents: [&[u8]; 2] = [b"a\nb\nc\nd\ne\nf\ng\nh\ni\n", b"a\nB\nc\nd\ne\nf\ng\nh\ni\n"];
+    let result = b"a\nB\nc\nd\ne\nf\ng\nh\nevil\n";
+
+    let hunks = gix_diff::blob::combined::diff(Algorithm::Myers, &parents, result, Options::default())?;
+    assert_eq!(
+        hunks,
+        [Hunk {
+            parents: vec![LineRange { start: 6, len: 4 }, LineRange { start: 6, len: 4 }],
+            result: LineRange { start: 6, len: 4 },
+            function: None,
+            lines: vec![
+                Line::Result { parents: 0, text: b"f" },
+                Line::Result { parents: 0, text: b"g" },
+                Line::Result { parents: 0, text: b"h" },
+                Line::Removed {
+                    parents: 0b11,
+                    text: b"i"
+                },
+                Line::Result {
+                    parents: 0b11,
+                    text: b"evil"
+                },
+            ],
+        }],
+        "the change taken from the second parent isn't shown, only the evil one"
+    );
+
+    let hunks = gix_diff::blob::combined::diff(
+        Algorithm::Myers,
+        &parents,
+        result,
+        Options {
+            dense: false,
+            context_size: 1,
+        },
+    )?;
+    let mut out = Vec::new();
+    for hunk in &hunks {
+        hunk.write_to(&mut out, false)?;
+    }
+    insta::assert_snapshot!(out.as_bstr(), @r"
+    @@@ -1,3 -1,3 +1,3 @@@
+      a
+    - b
+    + B
+      c
+    @@@ -8,2 -8,2 +8,2 @@@
+      h
+    --i
+    ++evil
+    ");
+    Ok(())
+}
+
+#[test]
+fn results_that_match_a_parent_have_no_dense_hunks() -> crate::Result {
+    let parents: [&[u8]; 2] = [b"a\nb\n", b"a\nB\n"];
+    let hunks = gix_diff::blob::combined::diff(Algorithm::Histogram, &parents, b"a\nB\n", Options::default())?;
+    assert_eq!(hunks, []);
+    Ok(())
+}
+
+/// Without context, `git` counts the line past the end of the result as context line to hide when the last hunk
+/// displays lines removed at the end, so all lengths in its header are one less than they should be, and wrap around.
+/// Return `true` if that's the only difference between `actual` and `expected`.
+fn has_git_end_of_file_quirk(actual: &[u8], expected: &[u8]) -> bool {
+    let is_header = |line: &&[u8]| line.starts_with(b"@@") || line.starts_with(b"\x1b[36m@@");
+    let (actual_lines, expected_lines): (Vec<_>, Vec<_>) = (actual.lines().collect(), expected.lines().collect());
+    let Some(last_header) = expected_lines.iter().rposition(is_header) else {
+        return false;
+    };
+    if actual_lines.len() != expected_lines.len()
+        || actual_lines
+            .iter()
+            .zip(&expected_lines)
+            .enumerate()
+            .any(|(idx, (actual, expected))| idx != last_header && actual != expected)
+    {
+        return false;
+    }
+    let ranges = |line: &[u8]| -> Vec<(u64, u64)> {
+        line.split_str(" ")
+            .filter(|token| token.starts_with(b"-") || token.starts_with(b"+"))
+            .map(|range| {
+                let (start, len) = range[1..].split_once_str(",").expect("start and length");
+                (
+                    start.to_str().expect("ascii").parse().expect("number"),
+                    len.to_str().expect("ascii").parse().expect("number"),
+                )
+            })
+            .collect()
+    };
+    let (actual, expected) = (ranges(actual_lines[last_header]), ranges(expected_lines[last_header]));
+    actual.len() == expected.len()
+        && actual
+            .iter()
+            .zip(&expected)
+            .all(|(actual, expected)| actual.0 == expected.0 && actual.1.wrapping_sub(1) == expected.1)
+}
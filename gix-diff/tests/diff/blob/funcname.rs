@@ -0,0 +1,157 @@
+use gix_diff::blob::{
+    Algorithm, Driver, InternedInput, UnifiedDiff,
+    funcname::{Matcher, Pattern, Syntax},
+    unified_diff::{ConsumeBinaryHunk, ContextSize},
+    whitespace,
+    word_diff::WordRegex,
+};
+use pretty_assertions::assert_eq;
+
+use super::{GitHunks, hunks_of_baseline};
+
+#[test]
+fn baseline() -> crate::Result {
+    let worktree_path = crate::scripted_fixture_read_only("make_diff_for_funcname_repo.sh")?;
+
+    let mut num_baselines = 0;
+    let mut mismatches = Vec::new();
+    for entry in std::fs::read_dir(&worktree_path)? {
+        let file_name = entry?.file_name();
+        let file_name = file_name.to_str().expect("ascii filename");
+        let Some(name) = file_name.strip_suffix(".baseline") else {
+            continue;
+        };
+        let [name, driver, mode] = name.split('.').collect::<Vec<_>>()[..] else {
+            unreachable!("BUG: need file named '<name>.<driver>.<mode>.baseline'")
+        };
+        let (function_context, ignore_blank_lines) = match mode {
+            "names" => (false, false),
+            "function-context" => (true, false),
+            "ignore-blank-lines" => (true, true),
+            other => unreachable!("BUG: '{other}' is not a supported mode"),
+        };
+        let pattern = match driver {
+            "none" => None,
+            "custom" | "basic" => {
+                let (key, syntax) = if driver == "custom" {
+                    ("xfuncname", Syntax::Extended)
+                } else {
+                    ("funcname", Syntax::Basic)
+                };
+                Some(Pattern {
+                    pattern: std::fs::read(worktree_path.join(format!("{driver}.{key}")))?.into(),
+                    syntax,
+                    ignore_case: false,
+                })
+            }
+            builtin => Some(
+                Driver::builtin(builtin.into())
+                    .and_then(|driver| driver.funcname)
+                    .expect("all other drivers are built-in"),
+            ),
+        };
+        let matcher = pattern.as_ref().map(Matcher::new).transpose()?.unwrap_or_default();
+
+        let path = format!("{name}.{driver}");
+        let before = std::fs::read(worktree_path.join("before").join(&path))?;
+        let after = std::fs::read(worktree_path.join("after").join(&path))?;
+        let input = InternedInput::new(
+            gix_diff::blob::sources::byte_lines(&before),
+            gix_diff::blob::sources::byte_lines(&after),
+        );
+        let whitespace = whitespace::Options {
+            ignore_blank_lines,
+            ..Default::default()
+        };
+        let diff = whitespace::diff_with_slider_heuristics(Algorithm::Myers, &input, whitespace);
+        let actual = UnifiedDiff::new(
+            &diff,
+            &input,
+            ConsumeBinaryHunk::new(GitHunks::default(), "\n"),
+            ContextSize::symmetrical(3),
+        )
+        .with_whitespace(whitespace)
+        .with_function_names(&matcher)
+        .with_function_context(function_context)
+        .consume()?
+        .out;
+
+        let expected = hunks_of_baseline(&std::fs::read(worktree_path.join(file_name))?);
+        if actual != expected {
+            mismatches.push((file_name.to_owned(), actual, expected));
+        }
+        num_baselines += 1;
+    }
+    assert_eq!(num_baselines, (28 + 8) * 2 + 1, "all baselines were checked");
+    if let Some((file_name, actual, expected)) = mismatches.first() {
+        let names: Vec<_> = mismatches.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(
+            actual, expected,
+            "{file_name} is the first of mismatching baselines {names:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn builtin_drivers_have_valid_patterns() -> crate::Result {
+    let mut num_drivers = 0;
+    for driver in Driver::builtins() {
+        let funcname = driver
+            .funcname
+            .as_ref()
+            .expect("all built-in drivers have a function name pattern");
+        Matcher::new(funcname)?;
+        WordRegex::new(driver.word_regex.as_ref().expect("all have a word regex").as_ref())?;
+        assert_eq!(
+            Driver::builtin(driver.name.as_ref()),
+            Some(driver),
+            "it can be found by name"
+        );
+        num_drivers += 1;
+    }
+    assert_eq!(num_drivers, 25);
+    assert_eq!(Driver::builtin("unknown".into()), None);
+    Ok(())
+}
+
+#[test]
+fn matcher_without_pattern_uses_identifiers_at_line_start() {
+    let matcher = Matcher::default();
+    for line in ["fn main() {\n", "_private:\n", "$var\n"] {
+        assert_eq!(matcher.function_name(line.as_bytes()), Some(line.into()));
+    }
+    for line in ["    indented\n", "1 number\n", "\n", "", "# comment\n"] {
+        assert_eq!(matcher.function_name(line.as_bytes()), None);
+    }
+}
+
+#[test]
+fn matcher_translates_posix_syntax() -> crate::Result {
+    let matcher = Matcher::new(&Pattern {
+        pattern: "!^[]x[:space:]\\-]\n^[^[:space:]]*+ \\(.*\\)$".into(),
+        syntax: Syntax::Basic,
+        ignore_case: false,
+    })?;
+    assert_eq!(
+        matcher.function_name(b"def+ (name)?\r\n"),
+        Some("(name)?".into()),
+        "groups and their literal parenthesis are swapped, and the newline isn't matched"
+    );
+    for negated in ["] name", "x+ name", "-+ name", "\t+ name", "\\+ name"] {
+        assert_eq!(
+            matcher.function_name(negated.as_bytes()),
+            None,
+            "the first matching expression decides, and backslashes in brackets are literal"
+        );
+    }
+
+    let matcher = Matcher::new(&Pattern {
+        pattern: "^(def|class) .*".into(),
+        syntax: Syntax::Extended,
+        ignore_case: true,
+    })?;
+    assert_eq!(matcher.function_name(b"CLASS Name\n"), Some("CLASS".into()));
+    assert_eq!(matcher.function_name(b"(def) name\n"), None);
+    Ok(())
+}
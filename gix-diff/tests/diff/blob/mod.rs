@@ -1,4 +1,73 @@
+mod combined;
+mod funcname;
+mod moved;
 pub(crate) mod pipeline;
 mod platform;
 mod slider;
 mod unified_diff;
+mod whitespace;
+mod word_diff;
+
+use gix_diff::blob::unified_diff::{ConsumeBinaryHunkDelegate, HunkHeader};
+use gix_object::bstr::{BString, ByteSlice, ByteVec};
+
+/// Return all hunks of the possibly colored `git diff` output in `baseline`, without the `\ No newline at end of file` markers.
+pub(crate) fn hunks_of_baseline(baseline: &[u8]) -> BString {
+    let mut out = BString::default();
+    for line in baseline
+        .lines_with_terminator()
+        .skip_while(|line| !line.starts_with(b"@@") && !line.starts_with(b"\x1b[36m@@"))
+        .filter(|line| !line.starts_with(b"\\"))
+    {
+        out.push_str(line);
+        if !line.ends_with(b"\n") {
+            out.push(b'\n');
+        }
+    }
+    out
+}
+
+/// Collect all hunks with headers like `git` produces them, which omits line counts of 1,
+/// uses the line before the hunk as start of empty ranges, and appends the function name if there is one.
+#[derive(Default)]
+pub(crate) struct GitHunks {
+    /// If `true`, color the hunk headers like `git diff --color` does.
+    color: bool,
+    pub(crate) out: BString,
+}
+
+impl GitHunks {
+    pub(crate) fn new(color: bool) -> Self {
+        GitHunks {
+            color,
+            out: BString::default(),
+        }
+    }
+}
+
+impl ConsumeBinaryHunkDelegate for GitHunks {
+    fn consume_binary_hunk(&mut self, header: HunkHeader, _header_str: &str, hunk: &[u8]) -> std::io::Result<()> {
+        let range = |start: u32, len: u32| match len {
+            0 => format!("{},0", start - 1),
+            1 => start.to_string(),
+            _ => format!("{start},{len}"),
+        };
+        let ranges = format!(
+            "@@ -{} +{} @@",
+            range(header.before_hunk_start, header.before_hunk_len),
+            range(header.after_hunk_start, header.after_hunk_len)
+        );
+        if self.color {
+            self.out.push_str(format!("\x1b[36m{ranges}\x1b[m"));
+        } else {
+            self.out.push_str(ranges);
+        }
+        if !header.function_name.is_empty() {
+            self.out.push(b' ');
+            self.out.push_str(header.function_name.as_bstr());
+        }
+        self.out.push(b'\n');
+        self.out.push_str(hunk);
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use gix_diff::blob::{
+    Algorithm, InternedInput, UnifiedDiff,
+    moved::{Block, Detector, Mode, Moved, Options, Whitespace},
+    unified_diff::{ContextSize, DiffLineKind},
+};
+use gix_object::bstr::{BString, ByteSlice};
+use pretty_assertions::assert_eq;
+
+use super::GitHunks;
+
+#[test]
+fn baseline() -> crate::Result {
+    let worktree_path = crate::scripted_fixture_read_only("make_diff_for_moved_repo.sh")?;
+
+    let mut num_baselines = 0;
+    let mut mismatches = Vec::new();
+    for entry in std::fs::read_dir(&worktree_path)? {
+        let file_name = entry?.file_name();
+        let file_name = file_name.to_str().expect("ascii filename");
+        let Some(name) = file_name.strip_suffix(".baseline") else {
+            continue;
+        };
+        let [_name, mode, ws] = name.split('.').collect::<Vec<_>>()[..] else {
+            unreachable!("BUG: need file named '<name>.<mode>.<ws>.baseline'")
+        };
+        let mode = match mode {
+            "plain" => Mode::Plain,
+            "blocks" => Mode::Blocks,
+            "zebra" => Mode::Zebra,
+            "dimmed-zebra" => Mode::DimmedZebra,
+            other => unreachable!("BUG: '{other}' is not a supported mode"),
+        };
+        let mut whitespace = Whitespace::default();
+        match ws {
+            "no" => {}
+            "ignore-all-space" => whitespace.ignore_all_space = true,
+            "ignore-space-change" => whitespace.ignore_space_change = true,
+            "ignore-space-at-eol" => whitespace.ignore_space_at_eol = true,
+            "allow-indentation-change" => whitespace.allow_indentation_change = true,
+            other => unreachable!("BUG: '{other}' is not a supported whitespace option"),
+        }
+
+        let (files, expected) = files_and_hunks_of_baseline(&std::fs::read(worktree_path.join(file_name))?);
+        let mut detector = Detector::default();
+        for (old, new) in &files {
+            let read = |path: &Option<String>| -> std::io::Result<Vec<u8>> {
+                path.as_ref()
+                    .map_or(Ok(Vec::new()), |path| std::fs::read(worktree_path.join(path)))
+            };
+            let (before, after) = (read(old)?, read(new)?);
+            let input = InternedInput::new(
+                gix_diff::blob::sources::byte_lines(&before),
+                gix_diff::blob::sources::byte_lines(&after),
+            );
+            let diff = gix_diff::blob::diff_with_slider_heuristics(Algorithm::Myers, &input);
+            detector = UnifiedDiff::new(&diff, &input, detector, ContextSize::symmetrical(3)).consume()?;
+        }
+        detector.detect(Options { mode, whitespace });
+
+        let mut actual = GitHunks::new(true);
+        for file in 0..files.len() {
+            detector.render_file(file, &mut actual)?;
+        }
+        if actual.out != expected {
+            mismatches.push((file_name.to_owned(), actual.out, expected));
+        }
+        num_baselines += 1;
+    }
+    assert_eq!(num_baselines, 5 * 4 * 5, "all baselines were checked");
+    if let Some((file_name, actual, expected)) = mismatches.first() {
+        let names: Vec<_> = mismatches.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(
+            actual, expected,
+            "{file_name} is the first of mismatching baselines {names:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn blocks_are_found_across_files() -> crate::Result {
+    let files = [
+        (
+            "a\nfirst line of a moved block\nsecond line of a moved block\nb\nanother moved block of lines\n",
+            "a\nb\n",
+        ),
+        ("unchanged\n", "unchanged\n"),
+        (
+            "c\n",
+            "c\nanother moved block of lines\nfirst line of a moved block\nsecond line of a moved block\n",
+        ),
+    ];
+    let mut detector = Detector::default();
+    for (before, after) in files {
+        let input = InternedInput::new(before, after);
+        let diff = gix_diff::blob::diff_with_slider_heuristics(Algorithm::Myers, &input);
+        detector = UnifiedDiff::new(&diff, &input, detector, ContextSize::symmetrical(0)).consume()?;
+    }
+    assert_eq!(
+        detector.hunks().iter().map(|hunk| hunk.file).collect::<Vec<_>>(),
+        [0, 0, 2],
+        "the diff without hunks still counts as file"
+    );
+
+    detector.detect(Options {
+        mode: Mode::DimmedZebra,
+        ..Default::default()
+    });
+    assert_eq!(
+        detector.blocks(),
+        [
+            Block {
+                file: 0,
+                kind: DiffLineKind::Remove,
+                lines: 0..2,
+                alternate: false,
+            },
+            Block {
+                file: 0,
+                kind: DiffLineKind::Remove,
+                lines: 2..3,
+                alternate: false,
+            },
+            Block {
+                file: 2,
+                kind: DiffLineKind::Add,
+                lines: 3..4,
+                alternate: false,
+            },
+            Block {
+                file: 2,
+                kind: DiffLineKind::Add,
+                lines: 4..6,
+                alternate: true,
+            },
+        ]
+    );
+    let actual: Vec<_> = detector
+        .lines()
+        .iter()
+        .map(|line| (detector.text(line).trim_end().to_str_lossy().into_owned(), line.moved))
+        .collect();
+    let moved = |alternate, dimmed| Some(Moved { alternate, dimmed });
+    assert_eq!(
+        actual,
+        [
+            ("first line of a moved block".into(), moved(false, true)),
+            ("second line of a moved block".into(), moved(false, true)),
+            ("another moved block of lines".into(), moved(false, true)),
+            ("another moved block of lines".into(), moved(false, false)),
+            ("first line of a moved block".into(), moved(true, false)),
+            ("second line of a moved block".into(), moved(true, true)),
+        ],
+        "only lines at the boundary of adjacent blocks aren't dimmed"
+    );
+    Ok(())
+}
+
+/// The old and new path of a diffed file, or `None` if it didn't exist.
+type Paths = (Option<String>, Option<String>);
+
+/// Return the paths of all files in the `git diff --no-index` output in `baseline`, along with all of their hunks.
+fn files_and_hunks_of_baseline(baseline: &[u8]) -> (Vec<Paths>, BString) {
+    let path = |line: &[u8], prefix: &str| {
+        let path = line
+            .strip_suffix(b"\x1b[m\n")
+            .expect("colored header line")
+            .to_str()
+            .expect("valid UTF-8");
+        (path != "/dev/null").then(|| path.strip_prefix(prefix).expect("path with prefix").to_owned())
+    };
+
+    let mut files = Vec::new();
+    let mut hunks = BString::default();
+    let mut in_hunks = false;
+    for line in baseline.lines_with_terminator() {
+        if let Some(old) = line.strip_prefix(b"\x1b[1m--- ") {
+            files.push((path(old, "a/"), None));
+        } else if let Some(new) = line.strip_prefix(b"\x1b[1m+++ ") {
+            files.last_mut().expect("old path comes first").1 = path(new, "b/");
+        } else if line.starts_with(b"\x1b[1mdiff ") {
+            in_hunks = false;
+        } else if line.starts_with(b"\x1b[36m@@") || in_hunks {
+            in_hunks = true;
+            hunks.extend_from_slice(line);
+        }
+    }
+    (files, hunks)
+}
@@ -62,7 +62,10 @@ fn resources_of_worktree_and_odb_and_check_link() -> crate::Result {
     assert_eq!(
         out.operation,
         Operation::InternalDiff {
-            algorithm: Algorithm::Histogram
+            algorithm: Algorithm::Histogram,
+            whitespace: Default::default(),
+            word_regex: None,
+            funcname: None,
         },
         "it ends up with the default, as it's not overridden anywhere"
     );
@@ -116,7 +119,10 @@ fn resources_of_worktree_and_odb_and_check_link() -> crate::Result {
     assert_eq!(
         out.operation,
         Operation::InternalDiff {
-            algorithm: Algorithm::Histogram
+            algorithm: Algorithm::Histogram,
+            whitespace: Default::default(),
+            word_regex: None,
+            funcname: None,
         },
         "it would still diff, despite this being blob-with-link now. But that's fine."
     );
@@ -264,7 +270,8 @@ fn diff_performed_despite_external_command() -> crate::Result {
         matches!(
             out.operation,
             Operation::InternalDiff {
-                algorithm: Algorithm::Myers
+                algorithm: Algorithm::Myers,
+                ..
             }
         ),
         "by default, we prepare for internal diffs, unless external commands are enabled.\
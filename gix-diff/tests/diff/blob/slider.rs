@@ -3,9 +3,18 @@ use pretty_assertions::StrComparison;
 
 #[test]
 fn baseline() -> gix_testtools::Result {
+    assert_matches_baseline("make_diff_for_sliders_repo.sh")
+}
+
+#[test]
+fn algorithms_baseline() -> gix_testtools::Result {
+    assert_matches_baseline("make_diff_for_algorithms_repo.sh")
+}
+
+fn assert_matches_baseline(script_name: &str) -> gix_testtools::Result {
     use gix_diff::blob::{self, Algorithm, InternedInput, diff_with_slider_heuristics};
 
-    let worktree_path = crate::scripted_fixture_read_only("make_diff_for_sliders_repo.sh")?;
+    let worktree_path = crate::scripted_fixture_read_only(script_name)?;
     let asset_dir = worktree_path.join("assets");
 
     let dir = std::fs::read_dir(&worktree_path)?;
@@ -33,6 +42,7 @@ fn baseline() -> gix_testtools::Result {
                 Algorithm::Myers => Algorithm::Myers,
                 Algorithm::Histogram => Algorithm::Histogram,
                 Algorithm::MyersMinimal => Algorithm::MyersMinimal,
+                Algorithm::Patience => Algorithm::Patience,
             },
             &input,
         );
@@ -118,6 +128,8 @@ mod baseline {
         let algorithm = match algorithm {
             "myers" => Algorithm::Myers,
             "histogram" => Algorithm::Histogram,
+            "minimal" => Algorithm::MyersMinimal,
+            "patience" => Algorithm::Patience,
             other => unreachable!("BUG: '{other}' is not a supported algorithm"),
         };
 
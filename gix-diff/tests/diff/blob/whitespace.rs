@@ -0,0 +1,170 @@
+use gix_diff::blob::{
+    Algorithm, InternedInput, UnifiedDiff,
+    unified_diff::{ConsumeBinaryHunk, ContextSize},
+    whitespace,
+};
+use pretty_assertions::assert_eq;
+
+use super::{GitHunks, hunks_of_baseline};
+
+#[test]
+fn baseline() -> crate::Result {
+    let worktree_path = crate::scripted_fixture_read_only("make_diff_for_whitespace_repo.sh")?;
+    let asset_dir = worktree_path.join("assets");
+
+    let mut num_baselines = 0;
+    let mut mismatches = Vec::new();
+    for entry in std::fs::read_dir(&worktree_path)? {
+        let file_name = entry?.file_name();
+        let file_name = file_name.to_str().expect("ascii filename");
+        let Some(name) = file_name.strip_suffix(".baseline") else {
+            continue;
+        };
+        let [name, algorithm, options] = name.split('.').collect::<Vec<_>>()[..] else {
+            unreachable!("BUG: need file named '<name>.<algorithm>.<options>.baseline'")
+        };
+        let algorithm = match algorithm {
+            "myers" => Algorithm::Myers,
+            "patience" => Algorithm::Patience,
+            other => unreachable!("BUG: '{other}' is not a supported algorithm"),
+        };
+        let options = parse_options(options);
+
+        let before = std::fs::read(asset_dir.join(format!("{name}_before.blob")))?;
+        let after = std::fs::read(asset_dir.join(format!("{name}_after.blob")))?;
+        let input = InternedInput::new(
+            gix_diff::blob::sources::byte_lines(&before),
+            gix_diff::blob::sources::byte_lines(&after),
+        );
+        let diff = whitespace::diff_with_slider_heuristics(algorithm, &input, options);
+        let actual = UnifiedDiff::new(
+            &diff,
+            &input,
+            ConsumeBinaryHunk::new(GitHunks::default(), "\n"),
+            ContextSize::symmetrical(3),
+        )
+        .with_whitespace(options)
+        .consume()?
+        .out;
+
+        let expected = hunks_of_baseline(&std::fs::read(worktree_path.join(file_name))?);
+        if actual != expected {
+            mismatches.push((file_name.to_owned(), actual, expected));
+        }
+        num_baselines += 1;
+    }
+    assert_eq!(num_baselines, 29 * 2 * 9, "all baselines were checked");
+    if let Some((file_name, actual, expected)) = mismatches.first() {
+        let names: Vec<_> = mismatches.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(
+            actual, expected,
+            "{file_name} is the first of mismatching baselines {names:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn context_lines_are_taken_from_the_new_version() -> crate::Result {
+    let before = "a\n  b\nc\nd\ne\n";
+    let after = "a\nb \nc\nX\ne\n";
+    let options = whitespace::Options {
+        ignore_all_space: true,
+        ..Default::default()
+    };
+
+    let input = InternedInput::new(before, after);
+    let diff = whitespace::diff_with_slider_heuristics(Algorithm::Myers, &input, options);
+    let actual = UnifiedDiff::new(
+        &diff,
+        &input,
+        ConsumeBinaryHunk::new(GitHunks::default(), "\n"),
+        ContextSize::symmetrical(3),
+    )
+    .consume()?
+    .out;
+    assert_eq!(
+        actual, "@@ -1,5 +1,5 @@\n a\n b \n c\n-d\n+X\n e\n",
+        "the second line differs only in whitespace, and is displayed as in the new version"
+    );
+    Ok(())
+}
+
+#[test]
+fn ignorable_blank_lines_are_only_shown_close_to_other_changes() -> crate::Result {
+    let before = "a\n\nb\nc\nd\ne\nf\ng\nh\n";
+    let after = "a\nb\nc\n\nd\nE\nf\ng\nh\n\n";
+    let options = whitespace::Options {
+        ignore_blank_lines: true,
+        ..Default::default()
+    };
+
+    let input = InternedInput::new(before, after);
+    let diff = whitespace::diff_with_slider_heuristics(Algorithm::Myers, &input, options);
+    let render = |context_size| {
+        UnifiedDiff::new(
+            &diff,
+            &input,
+            ConsumeBinaryHunk::new(GitHunks::default(), "\n"),
+            context_size,
+        )
+        .with_whitespace(options)
+        .consume()
+        .map(|hunks| hunks.out)
+    };
+    insta::assert_snapshot!(render(ContextSize::symmetrical(3))?, @r"
+    @@ -1,9 +1,9 @@
+     a
+    -
+     b
+     c
+    +
+     d
+    -e
+    +E
+     f
+     g
+     h
+    ");
+    insta::assert_snapshot!(render(ContextSize::symmetrical(1))?, @r"
+    @@ -5,3 +5,3 @@
+     d
+    -e
+    +E
+     f
+    ");
+
+    assert!(
+        diff.hunks().any(|hunk| options.is_ignorable(&hunk, &input)),
+        "the diff itself still contains the ignorable hunks"
+    );
+    Ok(())
+}
+
+#[test]
+fn blank_lines_are_whitespace_only_if_whitespace_is_ignored() {
+    let mut options = whitespace::Options::default();
+    assert!(options.is_blank_line(b""));
+    assert!(options.is_blank_line(b"\n"));
+    assert!(!options.is_blank_line(b" \t\n"));
+
+    options.ignore_cr_at_eol = true;
+    assert!(options.is_blank_line(b" \t\r\n"));
+    assert!(!options.is_blank_line(b" a\n"));
+}
+
+fn parse_options(options: &str) -> whitespace::Options {
+    let mut out = whitespace::Options::default();
+    for option in options.split('+') {
+        match option {
+            "none" => {}
+            "w" => out.ignore_all_space = true,
+            "b" => out.ignore_space_change = true,
+            "eol" => out.ignore_space_at_eol = true,
+            "cr" => out.ignore_cr_at_eol = true,
+            "blank" => out.ignore_blank_lines = true,
+            other => unreachable!("BUG: '{other}' is not a supported option"),
+        }
+    }
+    out
+}
@@ -0,0 +1,151 @@
+use gix_diff::blob::{
+    Algorithm, InternedInput, UnifiedDiff,
+    unified_diff::{ContextSize, HunkHeader},
+    word_diff::{ConsumeWordHunk, Mode, Renderer, SpanKind, WordDiff, WordRegex},
+};
+use gix_object::bstr::{BString, ByteSlice};
+use pretty_assertions::assert_eq;
+
+use super::{GitHunks, hunks_of_baseline};
+
+#[test]
+fn baseline() -> crate::Result {
+    let worktree_path = crate::scripted_fixture_read_only("make_diff_for_word_diff_repo.sh")?;
+    let asset_dir = worktree_path.join("assets");
+
+    let mut num_baselines = 0;
+    let mut mismatches = Vec::new();
+    for entry in std::fs::read_dir(&worktree_path)? {
+        let file_name = entry?.file_name();
+        let file_name = file_name.to_str().expect("ascii filename");
+        let Some(name) = file_name.strip_suffix(".baseline") else {
+            continue;
+        };
+        let [name, mode, regex] = name.split('.').collect::<Vec<_>>()[..] else {
+            unreachable!("BUG: need file named '<name>.<mode>.<regex>.baseline'")
+        };
+        let mode = match mode {
+            "plain" => Mode::Plain,
+            "porcelain" => Mode::Porcelain,
+            "color" => Mode::Color,
+            other => unreachable!("BUG: '{other}' is not a supported mode"),
+        };
+        let regex = match regex {
+            "none" => None,
+            name => Some(WordRegex::new(
+                std::fs::read(worktree_path.join(format!("{name}.regex")))?.as_bstr(),
+            )?),
+        };
+
+        let before = std::fs::read(asset_dir.join(format!("{name}_before.blob")))?;
+        let after = std::fs::read(asset_dir.join(format!("{name}_after.blob")))?;
+        let input = InternedInput::new(
+            gix_diff::blob::sources::byte_lines(&before),
+            gix_diff::blob::sources::byte_lines(&after),
+        );
+        let diff = gix_diff::blob::diff_with_slider_heuristics(Algorithm::Myers, &input);
+        let actual = UnifiedDiff::new(
+            &diff,
+            &input,
+            WordDiff::new(Renderer::new(GitHunks::new(mode == Mode::Color), mode), regex.as_ref()),
+            ContextSize::symmetrical(3),
+        )
+        .consume()?
+        .out;
+
+        let expected = hunks_of_baseline(&std::fs::read(worktree_path.join(file_name))?);
+        if actual != expected {
+            mismatches.push((file_name.to_owned(), actual, expected));
+        }
+        num_baselines += 1;
+    }
+    assert_eq!(num_baselines, 25 * 3 * 3, "all baselines were checked");
+    if let Some((file_name, actual, expected)) = mismatches.first() {
+        let names: Vec<_> = mismatches.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(
+            actual, expected,
+            "{file_name} is the first of mismatching baselines {names:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn spans_refer_to_words_and_whitespace_of_the_new_version() -> crate::Result {
+    let before = "a\nThe  quick brown fox\nb\n";
+    let after = "a\nThe quick red\tfox jumps\nb\n";
+    let input = InternedInput::new(before, after);
+    let diff = gix_diff::blob::diff_with_slider_heuristics(Algorithm::Myers, &input);
+
+    let actual = UnifiedDiff::new(
+        &diff,
+        &input,
+        WordDiff::new(Spans::default(), None),
+        ContextSize::symmetrical(1),
+    )
+    .consume()?;
+    assert_eq!(
+        actual,
+        [
+            (SpanKind::Context, "a\n"),
+            (SpanKind::Unchanged, "The quick "),
+            (SpanKind::Removed, "brown"),
+            (SpanKind::Added, "red"),
+            (SpanKind::Unchanged, "\tfox "),
+            (SpanKind::Added, "jumps"),
+            (SpanKind::Unchanged, "\n"),
+            (SpanKind::Context, "b\n"),
+        ]
+        .into_iter()
+        .map(|(kind, text)| (kind, text.into()))
+        .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn word_regex_determines_words() -> crate::Result {
+    let before = "foo(bar, baz)\n";
+    let after = "foo(bar,baz, qux)\n";
+    let input = InternedInput::new(before, after);
+    let diff = gix_diff::blob::diff_with_slider_heuristics(Algorithm::Myers, &input);
+
+    let render = |regex: Option<&WordRegex>| {
+        UnifiedDiff::new(
+            &diff,
+            &input,
+            WordDiff::new(Renderer::new(String::new(), Mode::Plain), regex),
+            ContextSize::symmetrical(3),
+        )
+        .consume()
+    };
+    insta::assert_snapshot!(render(None)?, @r"
+    @@ -1,1 +1,1 @@
+    [-foo(bar, baz)-]{+foo(bar,baz, qux)+}
+    ");
+    insta::assert_snapshot!(render(Some(&WordRegex::new("[a-z]+|[^[:space:]]".into())?))?, @r"
+    @@ -1,1 +1,1 @@
+    foo(bar,baz{+, qux+})
+    ");
+
+    let err = WordRegex::new("[a-z".into()).unwrap_err();
+    assert_eq!(err.to_string(), "Could not compile the word regex '[a-z'");
+    Ok(())
+}
+
+#[derive(Default)]
+struct Spans(Vec<(SpanKind, BString)>);
+
+impl ConsumeWordHunk for Spans {
+    type Out = Vec<(SpanKind, BString)>;
+
+    fn consume_word_hunk(&mut self, _header: HunkHeader, spans: &[(SpanKind, &[u8])]) -> std::io::Result<()> {
+        self.0
+            .extend(spans.iter().map(|&(kind, text)| (kind, text.as_bstr().to_owned())));
+        Ok(())
+    }
+
+    fn finish(self) -> Self::Out {
+        self.0
+    }
+}
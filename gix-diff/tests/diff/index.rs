@@ -1077,6 +1077,7 @@ fn realistic_renames_3_without_identity() -> crate::Result {
             percentage: None,
             limit: 0,
             track_empty: false,
+            matching: Default::default(),
         }),
     )?;
 
@@ -1205,6 +1206,7 @@ fn realistic_renames_3_without_identity() -> crate::Result {
             percentage: None,
             limit: 0,
             track_empty: false,
+            matching: Default::default(),
         }),
         Some("src/plumbing/m*"),
     )?;
@@ -1239,6 +1241,7 @@ fn realistic_renames_3_without_identity() -> crate::Result {
             percentage: None,
             limit: 0,
             track_empty: false,
+            matching: Default::default(),
         }),
         Some("src/plumbing-renamed/m*"),
     )?;
@@ -1277,6 +1280,7 @@ fn unmerged_entries_and_intent_to_add() -> crate::Result {
             percentage: None,
             limit: 0,
             track_empty: false,
+            matching: Default::default(),
         }),
     )?;
 
@@ -77,6 +77,7 @@ fn assert_hash_agnostic_patch_eq(actual: &str, expected: &str) {
 mod blob;
 mod index;
 mod rewrites;
+mod stats;
 mod tree;
 mod tree_with_rewrites;
 
@@ -28,6 +28,7 @@ fn rename_by_id() -> crate::Result {
             percentage: None,
             limit,
             track_empty: false,
+            matching: Default::default(),
         };
         let mut track = util::new_tracker(rewrites);
         assert!(
@@ -82,6 +83,7 @@ fn copy_by_similarity_reports_limit_if_encountered() -> crate::Result {
         percentage: None,
         limit: 1,
         track_empty: false,
+        matching: Default::default(),
     };
     let mut track = util::new_tracker(rewrites);
     let odb = util::add_retained_blobs(
@@ -135,6 +137,7 @@ fn copy_by_id() -> crate::Result {
             percentage: None,
             limit,
             track_empty: false,
+            matching: Default::default(),
         };
         let mut track = util::new_tracker(rewrites);
         let odb = util::add_retained_blobs(
@@ -213,6 +216,7 @@ fn copy_by_id_search_in_all_sources() -> crate::Result {
             percentage: None,
             limit,
             track_empty: false,
+            matching: Default::default(),
         };
         let mut track = util::new_tracker(rewrites);
         let odb = util::add_retained_blobs(
@@ -296,6 +300,7 @@ fn copy_by_50_percent_similarity() -> crate::Result {
         percentage: None,
         limit: 0,
         track_empty: false,
+        matching: Default::default(),
     };
     let mut track = util::new_tracker(rewrites);
     let odb = util::add_retained_blobs(
@@ -379,6 +384,7 @@ fn copy_by_id_in_additions_only() -> crate::Result {
         percentage: None,
         limit: 0,
         track_empty: false,
+        matching: Default::default(),
     };
     let mut track = util::new_tracker(rewrites);
     let odb = util::add_retained_blobs(
@@ -430,6 +436,7 @@ fn rename_by_similarity_reports_limit_if_encountered() -> crate::Result {
         percentage: Some(0.5),
         limit: 1,
         track_empty: false,
+        matching: Default::default(),
     };
     let mut track = util::new_tracker(rewrites);
     let odb = util::add_retained_blobs(
@@ -476,6 +483,7 @@ fn rename_by_50_percent_similarity() -> crate::Result {
         percentage: Some(0.5),
         limit: 0,
         track_empty: false,
+        matching: Default::default(),
     };
     let mut track = util::new_tracker(rewrites);
     let odb = util::add_retained_blobs(
@@ -561,6 +569,7 @@ fn rename_by_similarity_prefers_stronger_match_over_same_filename_match() -> cra
         percentage: Some(0.5),
         limit: 0,
         track_empty: false,
+        matching: Default::default(),
     };
     let mut track = util::new_tracker(rewrites);
     let odb = util::add_retained_blobs(
@@ -639,6 +648,7 @@ fn directory_renames_by_id_can_fail_gracefully() -> crate::Result {
         percentage: Some(0.5),
         limit: 0,
         track_empty: false,
+        matching: Default::default(),
     };
     let mut track = util::new_tracker(rename_by_similarity);
     let tree_dst_id = 1;
@@ -788,6 +798,7 @@ fn simple_directory_rename_by_id() -> crate::Result {
         percentage: None,
         limit: 0,
         track_empty: false,
+        matching: Default::default(),
     };
     let mut track = util::new_tracker(renames_by_identity);
     let tree_dst_id = 1;
@@ -1000,6 +1011,7 @@ fn rename_tracking_is_order_independent() -> crate::Result {
         percentage: None,
         limit: 0,
         track_empty: false,
+        matching: Default::default(),
     };
     let changes = vec![
         (Change::deletion(), "src", "identical\n"),
@@ -1041,6 +1053,7 @@ fn copy_source_selection_is_order_independent() -> crate::Result {
         percentage: None,
         limit: 0,
         track_empty: false,
+        matching: Default::default(),
     };
     // The blob id of "a"; all destinations and sources share it, so the copy source is ambiguous and
     // would otherwise be picked based on push order.
@@ -0,0 +1,189 @@
+use gix_diff::stats::{Counts, File, Summary, dirstat, rename_location};
+
+#[test]
+fn rename_location_compresses_common_directories_like_git() {
+    for (source, destination, expected) in [
+        ("a/b", "a/c/b", "a/{ => c}/b"),
+        ("abc/f", "abd/f", "{abc => abd}/f"),
+        ("dir/file.rs", "dir/file.txt", "dir/{file.rs => file.txt}"),
+        ("one/two/three/f", "one/three/f", "one/{two => }/three/f"),
+        ("file", "other.txt", "file => other.txt"),
+        ("pre/fix.rs", "prefix.rs", "pre/fix.rs => prefix.rs"),
+        ("same/dir/a", "same/dir/b", "same/dir/{a => b}"),
+        ("x/y/z", "x/z", "x/{y => }/z"),
+    ] {
+        assert_eq!(
+            rename_location(source.into(), destination.into()),
+            expected,
+            "{source} -> {destination}"
+        );
+    }
+}
+
+#[test]
+fn summary_is_displayed_like_shortstat() {
+    let summary = |files_changed, insertions, removals| {
+        Summary {
+            files_changed,
+            insertions,
+            removals,
+        }
+        .to_string()
+    };
+    assert_eq!(summary(0, 0, 0), " 0 files changed");
+    assert_eq!(summary(1, 1, 0), " 1 file changed, 1 insertion(+)");
+    assert_eq!(summary(2, 0, 3), " 2 files changed, 3 deletions(-)");
+    assert_eq!(
+        summary(1, 0, 0),
+        " 1 file changed, 0 insertions(+), 0 deletions(-)",
+        "without changed lines, both counts are shown"
+    );
+    assert_eq!(summary(3, 2, 1), " 3 files changed, 2 insertions(+), 1 deletion(-)");
+
+    assert_eq!(
+        Summary::from_files(&[lines("a", 2, 1), binary("b", 10, 20), lines("c", 1, 0)]),
+        Summary {
+            files_changed: 3,
+            insertions: 3,
+            removals: 1,
+        },
+        "binary files are counted, but don't contribute lines"
+    );
+}
+
+#[test]
+fn stat_scales_the_graph_and_abbreviates_locations() -> std::io::Result<()> {
+    let files = [
+        lines("a-rather-long-directory-name/with-a-long-file-name.rs", 1000, 10),
+        lines("short.rs", 1, 1),
+        binary("image.png", 0, 2048),
+        File {
+            source_location: Some("dir/old.rs".into()),
+            location: "dir/new.rs".into(),
+            counts: Counts::Lines {
+                insertions: 0,
+                removals: 0,
+            },
+        },
+    ];
+    let render = |options: gix_diff::stats::stat::Options| -> std::io::Result<String> {
+        let mut out = Vec::new();
+        gix_diff::stats::stat(&files, &options, &mut out)?;
+        Ok(String::from_utf8(out).expect("valid UTF-8"))
+    };
+
+    insta::assert_snapshot!(render(gix_diff::stats::stat::Options {
+        width: 40,
+        ..Default::default()
+    })?, @r"
+     ...h-a-long-file-name.rs | 1010 +++++-
+     short.rs                 |    2 +-
+     image.png                |  Bin 0 -> 2048 bytes
+     dir/{old.rs => new.rs}   |    0
+     4 files changed, 1001 insertions(+), 11 deletions(-)
+    ");
+    insta::assert_snapshot!(render(gix_diff::stats::stat::Options {
+        count: Some(1),
+        color: true,
+        ..Default::default()
+    })?, @"
+     .../with-a-long-file-name.rs                       | 1010 \x1b[32m+++++++++++++++++++\x1b[m\x1b[31m-\x1b[m
+     ...
+     4 files changed, 1001 insertions(+), 11 deletions(-)
+    ");
+    assert_eq!(
+        render(Default::default())?.lines().last(),
+        Some(" 4 files changed, 1001 insertions(+), 11 deletions(-)")
+    );
+
+    let mut out = Vec::new();
+    gix_diff::stats::stat(&[], &Default::default(), &mut out)?;
+    assert!(out.is_empty(), "nothing is written without files");
+    Ok(())
+}
+
+#[test]
+fn numstat_displays_binary_files_and_renames() -> std::io::Result<()> {
+    let mut out = Vec::new();
+    gix_diff::stats::numstat(
+        &[
+            lines("a", 1, 2),
+            binary("b", 1, 2),
+            File {
+                source_location: Some("c/old".into()),
+                location: "c/new".into(),
+                counts: Counts::Lines {
+                    insertions: 3,
+                    removals: 0,
+                },
+            },
+        ],
+        &mut out,
+    )?;
+    assert_eq!(out, b"1\t2\ta\n-\t-\tb\n3\t0\tc/{old => new}\n");
+    Ok(())
+}
+
+#[test]
+fn dirstat_aggregates_changes_of_directories() {
+    let files = [
+        lines("top.rs", 10, 0),
+        lines("a/one.rs", 20, 0),
+        lines("a/b/two.rs", 30, 0),
+        lines("a/b/three.rs", 10, 0),
+        lines("c/d/four.rs", 29, 0),
+        lines("e/renamed.rs", 0, 0),
+        binary("e/image.png", 64, 0),
+    ];
+    let render = |options: dirstat::Options| {
+        gix_diff::stats::dirstat(&files, &options)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        render(Default::default()),
+        ["  40.0% a/b/", "  20.0% a/", "  29.0% c/d/"],
+        "`c/` isn't shown as all its changes come from a single directory, and `e/` is below the limit"
+    );
+    assert_eq!(
+        render(dirstat::Options {
+            cumulative: true,
+            ..Default::default()
+        }),
+        ["  40.0% a/b/", "  60.0% a/", "  29.0% c/d/"]
+    );
+    assert_eq!(
+        render(dirstat::Options {
+            mode: dirstat::Mode::Files,
+            limit_permille: 0,
+            ..Default::default()
+        }),
+        ["  33.3% a/b/", "  16.6% a/", "  16.6% c/d/", "  16.6% e/"],
+        "files without changes don't count"
+    );
+    assert!(
+        gix_diff::stats::dirstat(&[lines("a/renamed.rs", 0, 0)], &Default::default()).is_empty(),
+        "nothing is returned without changes"
+    );
+}
+
+fn lines(location: &str, insertions: u64, removals: u64) -> File {
+    File {
+        source_location: None,
+        location: location.into(),
+        counts: Counts::Lines { insertions, removals },
+    }
+}
+
+fn binary(location: &str, size_before: u64, size_after: u64) -> File {
+    File {
+        source_location: None,
+        location: location.into(),
+        counts: Counts::Binary {
+            size_before,
+            size_after,
+        },
+    }
+}
@@ -1,6 +1,6 @@
 use gix_diff::{
     Rewrites,
-    rewrites::{Copies, CopySource},
+    rewrites::{Copies, CopySource, Matching},
     tree::{recorder::Location, visit::Relation},
     tree_with_rewrites::{Change, Options},
 };
@@ -1683,6 +1683,7 @@ fn realistic_renames_3_without_identity() -> crate::Result {
                 percentage: None,
                 limit: 0,
                 track_empty: false,
+                matching: Default::default(),
             }),
         },
     )?;
@@ -1887,6 +1888,86 @@ fn realistic_renames_3_without_identity() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn git_compatible_matching_finds_the_same_renames_and_copies_as_git() -> crate::Result {
+    let root = crate::scripted_fixture_read_only("make_diff_for_git_renames_repo.sh")?;
+    let renames = |percentage: f32, limit: usize| Rewrites {
+        percentage: Some(percentage),
+        limit,
+        matching: Matching::Git,
+        ..Default::default()
+    };
+    let copies = |source| Rewrites {
+        copies: Some(Copies {
+            source,
+            percentage: Some(0.5),
+        }),
+        matching: Matching::Git,
+        ..Default::default()
+    };
+    let mut cases = vec![
+        ("basename", "default", renames(0.5, 1000)),
+        ("ambiguous_basename", "default", renames(0.5, 1000)),
+        ("best_pairs", "default", renames(0.5, 1000)),
+        ("best_pairs", "80", renames(0.8, 1000)),
+        ("estimate", "default", renames(0.5, 1000)),
+        ("limit", "default", renames(0.5, 1000)),
+        ("limit", "limit-1", renames(0.5, 1)),
+        ("limit", "limit-2", renames(0.5, 2)),
+        ("copies", "default", copies(CopySource::FromSetOfModifiedFiles)),
+        (
+            "copies",
+            "harder",
+            copies(CopySource::FromSetOfModifiedFilesAndAllSources),
+        ),
+    ];
+    for name in ["random1", "random2", "random3"] {
+        cases.push((name, "default", renames(0.5, 1000)));
+        cases.push((name, "30", renames(0.3, 1000)));
+    }
+
+    for (name, suffix, rewrites) in cases {
+        let before = format!("{name}.before.tree");
+        let after = format!("{name}.after.tree");
+        let (changes, _out) = collect_changes_at(
+            repo_with_trees_at(root.clone(), Some(&before), Some(&after))?,
+            Options {
+                location: Some(Location::Path),
+                rewrites: Some(rewrites),
+            },
+        )?;
+        let mut actual: Vec<_> = changes
+            .iter()
+            .filter(|change| !change.entry_mode().is_tree())
+            .map(|change| match change {
+                Change::Addition { location, .. } => format!("A\t{location}"),
+                Change::Deletion { location, .. } => format!("D\t{location}"),
+                Change::Modification { location, .. } => format!("M\t{location}"),
+                Change::Rewrite {
+                    source_location,
+                    location,
+                    diff,
+                    copy,
+                    ..
+                } => {
+                    // Scores are rendered like `git` does, relative to its maximum score of 60000.
+                    let score = diff.map_or(100, |diff| (diff.similarity * 60000.0).round() as u32 * 100 / 60000);
+                    let status = if *copy { 'C' } else { 'R' };
+                    format!("{status}{score:03}\t{source_location}\t{location}")
+                }
+            })
+            .collect();
+        actual.sort_by(|a, b| a.rsplit('\t').next().cmp(&b.rsplit('\t').next()));
+        let expected = std::fs::read_to_string(root.join(format!("{name}.{suffix}.baseline")))?;
+        assert_eq!(
+            actual,
+            expected.lines().collect::<Vec<_>>(),
+            "{name}.{suffix}: the result matches `git diff --name-status`"
+        );
+    }
+    Ok(())
+}
+
 mod util {
     use std::{
         convert::Infallible,
@@ -1908,7 +1989,14 @@ mod util {
         lhs: impl Into<Option<&'static str>>,
         rhs: impl Into<Option<&'static str>>,
     ) -> gix_testtools::Result<(Vec<u8>, Vec<u8>, gix_diff::blob::Platform, gix_odb::Handle)> {
-        let root = repo_workdir()?;
+        repo_with_trees_at(repo_workdir()?, lhs.into(), rhs.into())
+    }
+
+    pub fn repo_with_trees_at(
+        root: PathBuf,
+        lhs: Option<&str>,
+        rhs: Option<&str>,
+    ) -> gix_testtools::Result<(Vec<u8>, Vec<u8>, gix_diff::blob::Platform, gix_odb::Handle)> {
         let odb = gix_odb::at_opts(
             root.join(".git/objects"),
             Vec::new(),
@@ -1917,8 +2005,8 @@ mod util {
                 ..Default::default()
             },
         )?;
-        let lhs = read_tree(&odb, &root, lhs.into())?;
-        let rhs = read_tree(&odb, &root, rhs.into())?;
+        let lhs = read_tree(&odb, &root, lhs)?;
+        let rhs = read_tree(&odb, &root, rhs)?;
 
         let cache = gix_diff::blob::Platform::new(
             Default::default(),
@@ -1951,7 +2039,13 @@ mod util {
         rhs: impl Into<Option<&'static str>>,
         options: gix_diff::tree_with_rewrites::Options,
     ) -> gix_testtools::Result<(Vec<gix_diff::tree_with_rewrites::Change>, Option<rewrites::Outcome>)> {
-        let (from, to, mut cache, odb) = repo_with_trees(lhs, rhs)?;
+        collect_changes_at(repo_with_trees(lhs, rhs)?, options)
+    }
+
+    pub fn collect_changes_at(
+        (from, to, mut cache, odb): (Vec<u8>, Vec<u8>, gix_diff::blob::Platform, gix_odb::Handle),
+        options: gix_diff::tree_with_rewrites::Options,
+    ) -> gix_testtools::Result<(Vec<gix_diff::tree_with_rewrites::Change>, Option<rewrites::Outcome>)> {
         let mut out = Vec::new();
         let hash_kind = fixture_hash_kind();
         let rewrites_info = gix_diff::tree_with_rewrites(
@@ -1980,4 +2074,6 @@ mod util {
         Ok(buf)
     }
 }
-use util::{collect_changes, collect_changes_opts, repo_with_trees, repo_workdir};
+use util::{
+    collect_changes, collect_changes_at, collect_changes_opts, repo_with_trees, repo_with_trees_at, repo_workdir,
+};
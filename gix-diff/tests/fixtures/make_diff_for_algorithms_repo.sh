@@ -0,0 +1,190 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of `git diff` with various algorithms in the layout expected by the slider tests:
+# `assets/<id>.blob` files and `<before-id>-<after-id>.<algorithm>.baseline` files containing the diff.
+mkdir assets
+
+function baseline() {
+  local name=${1:?first argument is the name of the case}
+  for algorithm in myers minimal patience; do
+    # Strip the function context from hunk headers as it's not part of what's compared.
+    { git diff --no-index --no-color --diff-algorithm="$algorithm" \
+      "assets/${name}_before.blob" "assets/${name}_after.blob" || test $? = 1; } \
+      | sed -E 's/^(@@ [^@]+ @@).*/\1/' > "${name}_before-${name}_after.${algorithm}.baseline"
+  done
+}
+
+function random_lines() {
+  local seed=${1:?first argument is the seed} count=${2:?second argument is the line count} vocabulary=${3:?third argument is the amount of distinct lines}
+  awk -v seed="$seed" -v count="$count" -v vocabulary="$vocabulary" \
+    'BEGIN { srand(seed); for (i = 0; i < count; i++) print "line " int(rand() * vocabulary) }'
+}
+
+function mutate_lines() {
+  local seed=${1:?first argument is the seed} vocabulary=${2:?second argument is the amount of distinct lines}
+  awk -v seed="$seed" -v vocabulary="$vocabulary" '
+    BEGIN { srand(seed) }
+    {
+      r = rand()
+      if (r < 0.1) next
+      else if (r < 0.2) print "line " int(rand() * vocabulary)
+      else print
+      if (rand() < 0.1) print "new " int(rand() * vocabulary)
+    }'
+}
+
+function code_lines() {
+  local seed=${1:?first argument is the seed} count=${2:?second argument is the line count}
+  awk -v seed="$seed" -v count="$count" '
+    BEGIN {
+      srand(seed)
+      for (i = 0; i < count; i++) {
+        r = rand()
+        if (r < 0.3) print ""
+        else if (r < 0.5) print "}"
+        else if (r < 0.7) print "    x"
+        else print "        y" int(rand() * 3)
+      }
+    }'
+}
+
+cat <<'EOF' >assets/moved_function_before.blob
+#include <stdio.h>
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("Your answer is: ");
+        printf("%d\n", foo);
+    }
+}
+
+int fact(int n)
+{
+    if(n > 1)
+    {
+        return fact(n-1) * n;
+    }
+    return 1;
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fact(10));
+}
+EOF
+
+cat <<'EOF' >assets/moved_function_after.blob
+#include <stdio.h>
+
+int fib(int n)
+{
+    if(n > 2)
+    {
+        return fib(n-1) + fib(n-2);
+    }
+    return 1;
+}
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf("%d\n", foo);
+    }
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fib(10));
+}
+EOF
+baseline moved_function
+
+cat <<'EOF' >assets/unique_anchors_before.blob
+a
+b
+c
+a
+b
+c
+unique one
+x
+y
+unique two
+z
+EOF
+
+cat <<'EOF' >assets/unique_anchors_after.blob
+unique two
+a
+b
+c
+unique one
+x
+a
+b
+z
+EOF
+baseline unique_anchors
+
+printf 'a\nb\na\nb\n' >assets/no_unique_lines_before.blob
+printf 'b\na\nb\na\nb\n' >assets/no_unique_lines_after.blob
+baseline no_unique_lines
+
+printf 'one\ntwo\nthree\n' >assets/nothing_in_common_before.blob
+printf 'four\nfive\n' >assets/nothing_in_common_after.blob
+baseline nothing_in_common
+
+printf 'same\nstart\nsame\nend\nsame\n' >assets/repeated_common_ends_before.blob
+printf 'same\nbeginning\nsame\nend\nsame\n' >assets/repeated_common_ends_after.blob
+baseline repeated_common_ends
+
+for seed in $(seq 1 40); do
+  name=random${seed}
+  vocabulary=$(( seed % 4 == 0 ? 8 : 40 + seed * 5 ))
+  random_lines "$seed" $(( 20 + seed * 7 )) "$vocabulary" >"assets/${name}_before.blob"
+  mutate_lines "$(( seed + 1000 ))" "$vocabulary" <"assets/${name}_before.blob" >"assets/${name}_after.blob"
+  baseline "$name"
+done
+
+# Small files with only a few distinct lines, which occur often enough for the approximated square root
+# of the file size to decide if they are considered common.
+for seed in $(seq 130 140); do
+  name=small${seed}
+  vocabulary=$(( seed % 5 + 3 ))
+  random_lines "$seed" $(( 16 + seed % 40 )) "$vocabulary" >"assets/${name}_before.blob"
+  mutate_lines "$(( seed + 1000 ))" "$vocabulary" <"assets/${name}_before.blob" >"assets/${name}_after.blob"
+  baseline "$name"
+done
+
+# A line that occurs often in the other file is pruned if it's surrounded by lines that don't occur there,
+# even with `--minimal`.
+printf 'a1\na2\na3\na4\nx\na5\na6\na7\na8\n' >assets/pruned_common_line_before.blob
+printf 'x\ny\nx\ny\nx\ny\nx\n' >assets/pruned_common_line_after.blob
+baseline pruned_common_line
+
+# Lines in the common prefix and postfix count towards how often lines occur, which decides if they are pruned.
+for seed in $(seq 90 95); do
+  name=frequent${seed}
+  vocabulary=$(( 40 + seed % 60 ))
+  random_lines "$seed" $(( 20 + seed % 200 )) "$vocabulary" >"assets/${name}_before.blob"
+  mutate_lines "$(( seed + 1000 ))" "$vocabulary" <"assets/${name}_before.blob" >"assets/${name}_after.blob"
+  baseline "$name"
+done
+
+# Indented lines and blank lines appended to the end of a file, with blank lines sprinkled in,
+# so the indent heuristic has to consider the end of the file and the blank lines before it.
+for seed in $(seq 200 250); do
+  name=eof${seed}
+  code_lines "$seed" $(( 5 + seed % 20 )) >"assets/${name}_before.blob"
+  { cat "assets/${name}_before.blob"; code_lines $(( seed + 7 )) $(( 1 + seed % 4 )); } \
+    | awk -v seed="$seed" 'BEGIN { srand(seed) } { print; if (rand() < 0.08) print "" }' >"assets/${name}_after.blob"
+  baseline "$name"
+done
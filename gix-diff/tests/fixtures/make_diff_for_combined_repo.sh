@@ -0,0 +1,127 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of combined diffs of merge commits:
+# `assets/<name>_parent<n>.blob` and `assets/<name>_result.blob` files, along with
+# `<name>.<mode>.<context>.baseline` files containing the output of `git show` for a merge commit of
+# the parents that has the result as content.
+git init -q
+mkdir assets
+
+function baseline() {
+  local name=${1:?first argument is the name of the case}
+  local blob tree merge
+  local parents=() count=0
+  for blob in "assets/${name}"_parent*.blob; do
+    tree=$(printf '100644 blob %s\tfile\n' "$(git hash-object -w "$blob")" | git mktree)
+    count=$((count + 1))
+    parents+=(-p "$(git commit-tree -m "parent ${count}" "$tree")")
+  done
+  tree=$(printf '100644 blob %s\tfile\n' "$(git hash-object -w "assets/${name}_result.blob")" | git mktree)
+  merge=$(git commit-tree -m merge "${parents[@]}" "$tree")
+
+  for context in 3 1 0; do
+    git show --format= --no-color --cc -U"$context" "$merge" > "${name}.cc.${context}.baseline"
+    git show --format= --no-color -c -U"$context" "$merge" > "${name}.c.${context}.baseline"
+    git show --format= --color=always --cc -U"$context" "$merge" > "${name}.color.${context}.baseline"
+  done
+}
+
+# Write `$count` lines of a base version to each of the parents, with lines changed, removed or added,
+# and write the result by picking the version of a random parent for each line of the base,
+# or changing it in the result only.
+function random_merge() {
+  local seed=${1:?first argument is the seed} count=${2:?second argument is the line count}
+  local vocabulary=${3:?third argument is the amount of distinct words} num_parents=${4:?fourth argument is the amount of parents}
+  local name=${5:?fifth argument is the name of the case}
+  awk -v seed="$seed" -v count="$count" -v vocabulary="$vocabulary" -v num_parents="$num_parents" -v name="$name" '
+    function line(r) {
+      r = rand()
+      if (r < 0.1) return ""
+      if (r < 0.2) return "fn f" int(rand() * vocabulary) "() {"
+      if (r < 0.3) return "}"
+      return "    w" int(rand() * vocabulary) (rand() < 0.5 ? "" : " v" int(rand() * vocabulary))
+    }
+    BEGIN {
+      srand(seed)
+      for (i = 0; i < count; i++) {
+        base = line()
+        for (p = 1; p <= num_parents; p++) {
+          r = rand()
+          if (r < 0.7) version[p] = base "\n"
+          else if (r < 0.8) version[p] = line() "\n"
+          else if (r < 0.9) version[p] = ""
+          else version[p] = base "\n" line() "\n"
+          printf "%s", version[p] > ("assets/" name "_parent" p ".blob")
+        }
+        r = rand()
+        if (r < 0.1) {
+          printf "%s\n", line() > ("assets/" name "_result.blob")
+        } else if (r < 0.15) {
+          printf "%s", version[1] version[num_parents] > ("assets/" name "_result.blob")
+        } else {
+          printf "%s", version[1 + int(rand() * num_parents)] > ("assets/" name "_result.blob")
+        }
+      }
+    }'
+}
+
+printf 'a\nb\nc\nd\ne\nf\ng\nh\n' >assets/evil_parent1.blob
+printf 'a\nB\nc\nd\ne\nf\ng\nh\n' >assets/evil_parent2.blob
+printf 'a\nB\nc\nd\nevil\nf\ng\nh\n' >assets/evil_result.blob
+baseline evil
+
+printf 'one\ntwo\nthree\nfour\n' >assets/resolution_parent1.blob
+printf 'one\n2\nthree\nfour\n' >assets/resolution_parent2.blob
+printf 'one\ntwo and 2\nthree\nfour\n' >assets/resolution_result.blob
+baseline resolution
+
+printf 'one\ntwo\nthree\n' >assets/both_sides_parent1.blob
+printf 'zero\none\ntwo\nthree\n' >assets/both_sides_parent2.blob
+printf 'one\ntwo\nthree\nfour\n' >assets/both_sides_parent3.blob
+printf 'zero\none\ntwo\nthree\nfour\nfive\n' >assets/both_sides_result.blob
+baseline both_sides
+
+printf 'same\nlines\nhere\nold\n' >assets/identical_parents_parent1.blob
+printf 'same\nlines\nhere\nold\n' >assets/identical_parents_parent2.blob
+printf 'same\nlines\nhere\nother\n' >assets/identical_parents_parent3.blob
+printf 'same\nlines\nhere\nnew\n' >assets/identical_parents_result.blob
+baseline identical_parents
+
+cat <<'EOF' >assets/function_parent1.blob
+int main(int argc, char **argv)
+{
+	int a = 1;
+	int b = 2;
+	int c = 3;
+	int d = 4;
+	return a + b + c + d;
+}
+
+static int helper_with_a_very_long_name_that_is_truncated(void)
+{
+	int x = 1;
+	int y = 2;
+	int z = 3;
+	return x;
+}
+EOF
+sed 's/int b = 2/int b = 20/; s/int y = 2/int y = 20/' assets/function_parent1.blob >assets/function_parent2.blob
+sed 's/int b = 2/int b = 200/; s/int y = 2/int y = 20/; s/return x/return x + z/' assets/function_parent1.blob >assets/function_result.blob
+baseline function
+
+printf 'a\r\nb\r\nc\r\n' >assets/crlf_parent1.blob
+printf 'a\r\nB\r\nc\r\n' >assets/crlf_parent2.blob
+printf 'a\r\nb and B\r\nc\r\n' >assets/crlf_result.blob
+baseline crlf
+
+printf 'a\nb\nc\n' >assets/removed_lines_parent1.blob
+printf 'a\nb\nc\nd\n' >assets/removed_lines_parent2.blob
+printf 'b\n' >assets/removed_lines_result.blob
+baseline removed_lines
+
+for seed in $(seq 1 20); do
+  name=random${seed}
+  random_merge "$seed" $(( 10 + seed * 3 )) $(( seed % 2 == 0 ? 5 : 30 )) $(( seed % 4 == 0 ? 3 : 2 )) "$name"
+  baseline "$name"
+done
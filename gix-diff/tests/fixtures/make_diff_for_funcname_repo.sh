@@ -0,0 +1,106 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of function names in hunk headers, and of `git diff --function-context`:
+# `before/<name>.<driver>` and `after/<name>.<driver>` files to diff with the driver named by their extension,
+# along with `<name>.<driver>.<mode>.baseline` files for the `names` and `function-context` modes, and the
+# `ignore-blank-lines` mode that also shows the function context.
+# `none` files don't have a driver, and the patterns of configured drivers are in `<driver>.<key>` files.
+git init -q
+mkdir before after
+
+drivers=(ada bash bibtex cpp csharp css dts elixir fortran fountain golang html java kotlin markdown matlab objc
+         pascal perl php python ruby rust scheme tex)
+for driver in "${drivers[@]}" custom basic; do
+  echo "*.$driver diff=$driver" >> .gitattributes
+done
+
+printf '%s' $'!^section skipped\n^section (.*)$' > custom.xfuncname
+git config diff.custom.xfuncname "$(cat custom.xfuncname)"
+printf '%s' '^begin \(.*\)$' > basic.funcname
+git config diff.basic.funcname "$(cat basic.funcname)"
+
+# Write a preamble followed by functions that start with the given lines, each with a body of eight lines.
+function functions() {
+  echo "preamble"
+  for header in "$@"; do
+    echo "$header"
+    seq 8 | sed 's/^/    /; s/$/ filler/'
+    echo
+  done
+}
+
+# Write `before/$1` with functions starting with the remaining arguments, and `after/$1` with changes at the end
+# of the first function, within the second, and at the end of the third.
+function sample() {
+  local file=${1:?first argument is the file name}
+  shift
+  functions "$@" > "before/$file"
+  sed -e '9s/filler/changed/' -e '18d' -e '30a\    appended' "before/$file" > "after/$file"
+}
+
+sample sample.ada "with Ada.Text_IO;" "procedure First is" "function Second return Integer is" "package body Third is"
+sample sample.bash "first() {" "function second {" "third () {"
+sample sample.bibtex "@article{first," "@book{second," "@misc{third,"
+sample sample.cpp "label:" "int first(int a)" "static void second(void)" "class Third {"
+sample sample.csharp "    public void First()" "    private int Second(int a)" "class Third"
+sample sample.css ".first {" "#second {" "BODY {"
+sample sample.dts "/ {" "    first: node@1 {" "&second {"
+sample sample.elixir "  def first do" "  defp second(a) do" "defmodule Third do"
+sample sample.fortran "SUBROUTINE FIRST" "      REAL FUNCTION SECOND(X)" "program third"
+sample sample.fountain "INT. FIRST ROOM - DAY" "ext. second place - night" ".THIRD"
+sample sample.golang "func first() {" "func (s *S) second() {" "type Third struct {"
+sample sample.html "<h1>First</h1>" "<h2 id=x>Second</h2>" "<H3>Third</H3>"
+sample sample.java "    public void first(int a) {" "    private static int second() {" "class Third implements X {"
+sample sample.kotlin "fun first() {" "private fun second() {" "class Third {"
+sample sample.markdown "# First" "## Second" "   ### Third"
+sample sample.matlab "function first()" "classdef Second" "%% Third"
+sample sample.objc "- (void)first" "+ (int)second:(int)a" "@implementation Third"
+sample sample.pascal "procedure First;" "function Second: Integer;" "TThird = class(TObject)"
+sample sample.perl "sub first {" "package Second;" "=head1 Third"
+sample sample.php "function first() {" "    public static function second() {" "final class Third {"
+sample sample.python "def first():" "    async def second(self):" "class Third:"
+sample sample.ruby "def first" "  module Second" "class Third"
+sample sample.rust "fn first() {" "pub(crate) async fn second() {" "impl Third for X {"
+sample sample.scheme "(define (first x)" "(define-syntax second" "(library (third)"
+sample sample.tex '\section{First}' '\subsection*{Second}' '\chapter{Third}'
+sample sample.custom "section first" "section skipped" "section third"
+sample sample.basic "begin first" "ignored second" "begin third"
+sample sample.none "first" "_second" "\$third"
+
+# A function name that is longer than 80 bytes is truncated.
+functions "a function with a name that is so long that it doesn't fit into the hunk header, which truncates it" \
+  > before/long.none
+sed '9s/filler/changed/' before/long.none > after/long.none
+
+# Changes before the first function, and far apart in the same function, where the name is taken from the previous hunk.
+{ seq 8 | sed 's/^/    /'; echo "the only function"; seq 30 | sed 's/^/    /'; } > before/apart.none
+sed -e '2s/$/ changed/' -e '14s/$/ changed/' -e '34s/$/ changed/' before/apart.none > after/apart.none
+
+# A function is appended, which doesn't need any context, and lines are appended to the last function, which do.
+functions "first" "second" > before/appended.none
+{ cat before/appended.none; echo "third"; echo "    body"; } > after/appended.none
+functions "first" "second" | sed '$d' > before/extended.none
+{ cat before/extended.none; echo "    body"; } > after/extended.none
+
+# Functions without changes between changed functions are not shown, unless they are too close to changes.
+functions "first" "second" "third" "fourth" > before/between.none
+sed -e '4s/filler/changed/' -e '33s/filler/changed/' before/between.none > after/between.none
+functions "first" "second" "third" | sed -e '12,19d' > before/close.none
+sed -e '4s/filler/changed/' -e '18s/filler/changed/' before/close.none > after/close.none
+
+# Comments before a function are part of it, but blank lines after a function aren't.
+{ echo "first"; echo "    body"; echo; echo; echo "# a comment"; echo "second"; seq 8 | sed 's/^/    /'; } > before/comment.none
+sed '10s/$/ changed/' before/comment.none > after/comment.none
+
+# Ignored changes of blank lines are shown if the function context reaches them.
+{ echo "first"; seq 12 | sed 's/^/    /'; } > before/blank.none
+sed -e '2a\\' -e '12s/$/ changed/' before/blank.none > after/blank.none
+git diff --no-index --function-context --ignore-blank-lines before/blank.none after/blank.none \
+  > blank.none.ignore-blank-lines.baseline || test $? = 1
+
+for before in before/*; do
+  name=${before#before/}
+  git diff --no-index "$before" "after/$name" > "$name.names.baseline" || test $? = 1
+  git diff --no-index --function-context "$before" "after/$name" > "$name.function-context.baseline" || test $? = 1
+done
@@ -0,0 +1,148 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of renames and copies as `git diff` finds them:
+# `<name>.before.tree` and `<name>.after.tree` files with the trees to diff, along with
+# `<name>.<suffix>.baseline` files with the `git diff --name-status` output for the arguments in `baseline()`.
+git init -q
+git config diff.renames false
+printf "*.tree\n*.baseline\n" >> .git/info/exclude
+
+function lines() {
+  local prefix=${1:?first argument is the line prefix} count=${2:?second argument is the amount of lines}
+  seq "$count" | sed "s/^/${prefix} line /"
+}
+
+function start() {
+  git rm -rqf --ignore-unmatch . && git clean -qfdx -e '*.tree' -e '*.baseline'
+}
+
+function snapshot() {
+  local name=${1:?first argument is the name of the case} state=${2:?second argument is either 'before' or 'after'}
+  git add -A && git commit -q --allow-empty -m "${name} ${state}"
+  git rev-parse "HEAD^{tree}" > "${name}.${state}.tree"
+}
+
+function baseline() {
+  local name=${1:?first argument is the name of the case} suffix=${2:?second argument is the baseline suffix}
+  shift 2
+  git diff --name-status "$@" HEAD~ HEAD > "${name}.${suffix}.baseline"
+}
+
+# A source with the same, unique file name is preferred if it's similar enough, even if another one is more similar.
+start
+mkdir a b
+lines file 20 > a/file.txt
+{ lines file 20; echo x; echo y; } > b/other.txt
+snapshot basename before
+mkdir c
+{ lines file 20; echo x; } > c/file.txt
+git rm -q a/file.txt b/other.txt
+snapshot basename after
+baseline basename default -M
+
+# Names that aren't unique are matched by similarity only.
+start
+mkdir a b c d
+lines file 20 > a/file.txt
+{ lines file 20; echo x; echo y; } > b/file.txt
+snapshot ambiguous_basename before
+{ lines file 20; echo x; } > c/file.txt
+echo unrelated > d/file.txt
+git rm -q a/file.txt b/file.txt
+snapshot ambiguous_basename after
+baseline ambiguous_basename default -M
+
+# The most similar pairs are assigned first, even if that leaves a destination with a less similar source.
+start
+lines b 20 > b-source
+{ lines b 14; lines a 6; } > a-source
+snapshot best_pairs before
+{ lines b 17; lines x 3; } > d1
+{ lines b 19; lines y 1; } > d2
+git rm -q a-source b-source
+snapshot best_pairs after
+baseline best_pairs default -M
+baseline best_pairs 80 -M80%
+
+# The order of lines doesn't matter for the similarity estimate, and neither do carriage returns in text.
+start
+lines reordered 20 > reordered
+lines crlf 20 | sed 's/$/\r/' > crlf
+{ printf 'binary\0'; lines binary 20; } > binary.bin
+snapshot estimate before
+lines reordered 20 | sort -r > reordered-moved
+{ lines crlf 20; lines added 1; } > crlf-moved
+{ printf 'binary\0'; lines binary 19; } > binary-moved.bin
+git rm -q reordered crlf binary.bin
+snapshot estimate after
+baseline estimate default -M
+
+# Exact renames, and renames by file name are found even if the rename limit is exceeded.
+start
+mkdir old new
+lines exact 10 > old/exact
+lines basename 10 > old/basename
+lines first 10 > old/first
+lines second 10 > old/second
+snapshot limit before
+mv old/exact new/exact-moved
+{ lines basename 10; lines added 1; } > new/basename
+rm old/basename
+{ lines first 10; lines added 1; } > new/first-moved
+{ lines second 10; lines added 1; } > new/second-moved
+rm old/first old/second
+snapshot limit after
+baseline limit default -M
+baseline limit limit-1 -M -l1
+baseline limit limit-2 -M -l2
+
+# Copies are found in modified files, and don't use file names.
+# Note that `git` copies from the previous version of modified files, so it's as similar as the current one here.
+start
+mkdir a b
+lines modified 20 > a/file
+lines deleted 20 > a/deleted
+lines unmodified 20 > unmodified
+snapshot copies before
+{ lines modified 19; echo "modified line XX"; } > a/file
+{ lines modified 19; lines added 1; } > b/file
+{ lines deleted 20; lines added 1; } > b/deleted-moved
+{ lines unmodified 20; lines added 1; } > b/unmodified-copy
+git rm -q a/deleted
+snapshot copies after
+baseline copies default -C
+baseline copies harder -C -C
+
+# Many sources and destinations with shared lines, to have many candidates for each destination.
+for seed in 1 2 3; do
+  name=random${seed}
+  start
+  mkdir src
+  awk -v seed="$seed" 'BEGIN {
+    srand(seed)
+    for (f = 0; f < 12; f++) {
+      for (i = 0; i < 20; i++) printf "line %d\n", int(rand() * 40) > ("src/file" f)
+    }
+  }'
+  snapshot "$name" before
+  mkdir dst
+  awk -v seed="$seed" 'BEGIN {
+    srand(seed * 1000)
+    for (f = 0; f < 12; f++) {
+      src = "src/file" int(rand() * 12)
+      dst = "dst/" (rand() < 0.3 ? "file" f : "other" f)
+      while ((getline line < src) > 0) {
+        r = rand()
+        if (r < 0.1) continue
+        print line > dst
+        if (r > 0.9) printf "new %d\n", int(rand() * 40) > dst
+      }
+      close(src)
+    }
+  }'
+  git rm -rq src
+  snapshot "$name" after
+  baseline "$name" default -M
+  baseline "$name" 30 -M30%
+done
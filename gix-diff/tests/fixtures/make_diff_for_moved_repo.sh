@@ -0,0 +1,154 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of moved lines as `git diff --color-moved` finds them:
+# `<name>/before` and `<name>/after` directories with the files to diff, along with
+# `<name>.<mode>.<ws>.baseline` files with the colored `git diff` output for each `--color-moved` mode and
+# `--color-moved-ws` option.
+
+function baselines() {
+  local name=${1:?first argument is the name of the case}
+  for mode in plain blocks zebra dimmed-zebra; do
+    for ws in no ignore-all-space ignore-space-change ignore-space-at-eol allow-indentation-change; do
+      git -c core.whitespace=-blank-at-eof diff --no-index --no-renames --color=always \
+        --color-moved="$mode" --color-moved-ws="$ws" "$name/before" "$name/after" > "$name.$mode.$ws.baseline" || test $? = 1
+    done
+  done
+}
+
+# A block that moves within a file, along with lines that are too short to be a block of their own.
+mkdir -p same_file/before same_file/after
+cat > same_file/before/file <<EOF
+fn first() {
+    let value = compute_something_interesting();
+    println!("the value is {value}");
+}
+
+fn second() {
+    unrelated_code_that_stays();
+}
+x
+EOF
+cat > same_file/after/file <<EOF
+fn second() {
+    unrelated_code_that_stays();
+}
+x
+fn first() {
+    let value = compute_something_interesting();
+    println!("the value is {value}");
+}
+y
+}
+EOF
+baselines same_file
+
+# Blocks move across files, and a deleted file reappears within another one.
+mkdir -p across_files/before across_files/after
+cat > across_files/before/a <<EOF
+mod a {
+    pub fn helper_to_move(input: usize) -> usize {
+        input * 2 + some_offset_value()
+    }
+    pub fn stays_here() {}
+}
+EOF
+cat > across_files/before/deleted <<EOF
+const DELETED_BUT_NOT_FORGOTTEN: &str = "the content of the deleted file";
+const ANOTHER_CONSTANT_VALUE: u32 = 42;
+EOF
+cat > across_files/before/b <<EOF
+mod b {
+    pub fn other() {}
+}
+EOF
+cat > across_files/after/a <<EOF
+mod a {
+    pub fn stays_here() {}
+}
+EOF
+cat > across_files/after/b <<EOF
+mod b {
+    pub fn helper_to_move(input: usize) -> usize {
+        input * 2 + some_offset_value()
+    }
+    pub fn other() {}
+}
+const DELETED_BUT_NOT_FORGOTTEN: &str = "the content of the deleted file";
+const ANOTHER_CONSTANT_VALUE: u32 = 42;
+EOF
+baselines across_files
+
+# Blocks from different places end up next to each other, and alternate in color.
+mkdir -p adjacent_blocks/before adjacent_blocks/after
+cat > adjacent_blocks/before/file <<EOF
+first block of lines that is moved
+and it has a second line as well
+stays 1
+stays 2
+stays 3
+second block that is moved, too
+with its own second line
+stays 4
+stays 5
+stays 6
+EOF
+cat > adjacent_blocks/after/file <<EOF
+stays 1
+stays 2
+stays 3
+stays 4
+stays 5
+stays 6
+second block that is moved, too
+with its own second line
+first block of lines that is moved
+and it has a second line as well
+a new line that wasn't moved
+EOF
+baselines adjacent_blocks
+
+# Short blocks aren't moved, and a block that turns out to be too short is retried from its second line.
+mkdir -p short_blocks/before short_blocks/after
+{ echo a; echo "}"; seq 4 | sed 's/^/stays /'; echo "}"; echo "first long line that is moved"; echo "second long line that is moved"
+  seq 5 10 | sed 's/^/stays /'; echo b; } > short_blocks/before/file
+{ echo b; seq 10 | sed 's/^/stays /'; echo a; echo "}"; echo "first long line that is moved"; echo "second long line that is moved"
+} > short_blocks/after/file
+baselines short_blocks
+
+# Moved lines with changed whitespace, which only match with some of the whitespace options.
+mkdir -p whitespace/before whitespace/after
+cat > whitespace/before/file <<EOF
+fn outer() {
+    let indented_value = 1;
+    let another_value = 2;
+
+    call_with(indented_value, another_value);
+}
+let  spaced   =  "line with changed internal spaces";
+let unspaced = "line with changed spaces at the end";	
+let eol = "line with spaces removed at the end"  
+unchanged line in between
+EOF
+cat > whitespace/after/file <<EOF
+unchanged line in between
+fn outer() {
+    if condition {
+        let indented_value = 1;
+        let another_value = 2;
+
+        call_with(indented_value, another_value);
+    }
+}
+let spaced = "line with changed internal spaces";
+letunspaced="line with changed spaces at the end";
+let unspaced = "line with changed spaces at the end";
+let eol = "line with spaces removed at the end"
+EOF
+cat > whitespace/after/inconsistent <<EOF
+      let indented_value = 1;
+  let another_value = 2;
+
+            call_with(indented_value, another_value);
+EOF
+baselines whitespace
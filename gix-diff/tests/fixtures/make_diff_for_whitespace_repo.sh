@@ -0,0 +1,125 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of `git diff` with various options to ignore whitespace:
+# `assets/<name>_before.blob` and `assets/<name>_after.blob` files, along with
+# `<name>.<algorithm>.<options>.baseline` files containing the diff.
+mkdir assets
+
+function baseline() {
+  local name=${1:?first argument is the name of the case}
+  for algorithm in myers patience; do
+    for options in none w b eol cr blank w+blank b+blank cr+blank; do
+      local args=()
+      case "$options" in
+        *w*) args+=(--ignore-all-space) ;;&
+        *b) args+=(--ignore-space-change) ;;&
+        b+*) args+=(--ignore-space-change) ;;&
+        *eol*) args+=(--ignore-space-at-eol) ;;&
+        *cr*) args+=(--ignore-cr-at-eol) ;;&
+        *blank*) args+=(--ignore-blank-lines) ;;
+      esac
+      # Strip the function context from hunk headers as it's not part of what's compared.
+      { git diff --no-index --no-color --diff-algorithm="$algorithm" ${args[@]+"${args[@]}"} \
+        "assets/${name}_before.blob" "assets/${name}_after.blob" || test $? = 1; } \
+        | sed -E 's/^(@@ [^@]+ @@).*/\1/' > "${name}.${algorithm}.${options}.baseline"
+    done
+  done
+}
+
+# Write lines to `$before` and `$after`, where the same content is written with different whitespace,
+# along with blank lines, whitespace-only lines and actual changes.
+function random_pair() {
+  local seed=${1:?first argument is the seed} count=${2:?second argument is the line count} vocabulary=${3:?third argument is the amount of distinct words}
+  local before=${4:?fourth argument is the before file} after=${5:?fifth argument is the after file}
+  awk -v seed="$seed" -v count="$count" -v vocabulary="$vocabulary" -v before="$before" -v after="$after" '
+    function space(r) {
+      r = rand()
+      return r < 0.6 ? " " : (r < 0.8 ? "  " : (r < 0.9 ? "\t" : " \t "))
+    }
+    function indent(n, s) {
+      s = ""
+      for (n = int(rand() * 3); n > 0; n--) s = s (rand() < 0.7 ? "    " : "\t")
+      return s
+    }
+    function content(r) {
+      r = rand()
+      if (r < 0.12) return ""
+      return "w" int(rand() * vocabulary) (rand() < 0.5 ? "" : " v" int(rand() * vocabulary))
+    }
+    function render(c, r, s) {
+      if (c == "") {
+        r = rand()
+        return r < 0.6 ? "" : (r < 0.8 ? "  " : "\t")
+      }
+      s = indent() c
+      if (rand() < 0.3) gsub(/ /, space(), s)
+      if (rand() < 0.1) gsub(/ /, "", s)
+      if (rand() < 0.2) s = s space()
+      if (rand() < 0.1) s = s "\r"
+      return s
+    }
+    BEGIN {
+      srand(seed)
+      for (i = 0; i < count; i++) {
+        c = content()
+        line = render(c)
+        r = rand()
+        if (r < 0.06) {
+          print line > before
+        } else if (r < 0.12) {
+          print render(content()) > after
+        } else if (r < 0.16) {
+          print line > before
+          print render(content()) > after
+        } else {
+          print line > before
+          print (rand() < 0.5 ? line : render(c)) > after
+        }
+        if (rand() < 0.06) print render(rand() < 0.5 ? "" : content()) > after
+      }
+    }'
+}
+
+cat <<'EOF' >assets/reindented_before.blob
+fn main() {
+    let x = 1;
+    if x > 0 {
+        println!("positive");
+    }
+
+    println!("done");
+}
+EOF
+
+cat <<'EOF' >assets/reindented_after.blob
+fn main() {
+    let x = 1;
+    for _ in 0..3 {
+        if x > 0 {
+            println!("positive");
+        }
+
+        println!("done");
+    }
+}
+EOF
+baseline reindented
+
+printf 'a\n\n\nb\nc\nd\ne\nf\ng\nh\ni\n\nj\n' >assets/blank_lines_before.blob
+printf 'a\nb\nc\n\nd\nE\nf\ng\nh\ni\nj\n\n\n' >assets/blank_lines_after.blob
+baseline blank_lines
+
+printf 'one\r\ntwo\nthree\r\nfour' >assets/crlf_before.blob
+printf 'one\ntwo\r\nthree\r\nfour\r' >assets/crlf_after.blob
+baseline crlf
+
+printf 'a  b\n\tc d \nef\n' >assets/inner_space_before.blob
+printf 'a b\n  c\td\ne f\n' >assets/inner_space_after.blob
+baseline inner_space
+
+for seed in $(seq 1 25); do
+  name=random${seed}
+  random_pair "$seed" $(( 15 + seed * 4 )) $(( seed % 3 == 0 ? 4 : 20 )) "assets/${name}_before.blob" "assets/${name}_after.blob"
+  baseline "$name"
+done
@@ -0,0 +1,149 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of `git diff --word-diff`:
+# `assets/<name>_before.blob` and `assets/<name>_after.blob` files, along with
+# `<name>.<mode>.<regex>.baseline` files containing the diff, and `<regex>.regex` files with the word regex.
+mkdir assets
+
+printf '%s' '[a-zA-Z_][a-zA-Z0-9_]*|[0-9]+|[^[:space:]]' > code.regex
+printf '%s' '[^[:space:]]' > char.regex
+
+function baseline() {
+  local name=${1:?first argument is the name of the case}
+  for mode in plain porcelain color; do
+    for regex in none code char; do
+      local args=(--word-diff="$mode")
+      if [ "$regex" != none ]; then
+        args+=(--word-diff-regex="$(cat "$regex.regex")")
+      fi
+      # Strip the function context from hunk headers as it's not part of what's compared.
+      { git diff --no-index "${args[@]}" \
+        "assets/${name}_before.blob" "assets/${name}_after.blob" || test $? = 1; } \
+        | sed -E 's/^((\x1b\[36m)?@@ [^@]+ @@(\x1b\[m)?).*/\1/' > "${name}.${mode}.${regex}.baseline"
+    done
+  done
+}
+
+# Write words to `$before` and `$after`, with some of them changed, removed or added,
+# and lines that are changed, removed or added.
+function random_pair() {
+  local seed=${1:?first argument is the seed} count=${2:?second argument is the line count} vocabulary=${3:?third argument is the amount of distinct words}
+  local before=${4:?fourth argument is the before file} after=${5:?fifth argument is the after file}
+  awk -v seed="$seed" -v count="$count" -v vocabulary="$vocabulary" -v before="$before" -v after="$after" '
+    function word(r) {
+      r = rand()
+      if (r < 0.1) return "(" int(rand() * vocabulary) ")"
+      if (r < 0.2) return "x" int(rand() * vocabulary) ","
+      return "w" int(rand() * vocabulary)
+    }
+    function space(r) {
+      r = rand()
+      return r < 0.8 ? " " : (r < 0.9 ? "  " : "\t")
+    }
+    BEGIN {
+      srand(seed)
+      for (i = 0; i < count; i++) {
+        r = rand()
+        if (r < 0.1) {
+          printf "\n" > before
+          printf "\n" > after
+          continue
+        }
+        b = ""; a = ""
+        for (n = 1 + int(rand() * 8); n > 0; n--) {
+          w = word()
+          r = rand()
+          if (r < 0.08) {
+            b = b w space()
+          } else if (r < 0.16) {
+            a = a w space()
+          } else if (r < 0.26) {
+            b = b w space()
+            a = a word() space()
+          } else {
+            b = b w space()
+            a = a w (rand() < 0.9 ? " " : space())
+          }
+        }
+        r = rand()
+        if (r < 0.05) {
+          print b > before
+        } else if (r < 0.1) {
+          print a > after
+        } else {
+          print b > before
+          print a > after
+        }
+      }
+    }'
+}
+
+cat <<'EOF' >assets/prose_before.blob
+The quick brown fox jumps over the lazy dog.
+It was the best of times, it was the worst of times.
+
+Call me Ishmael. Some years ago - never mind how long precisely -
+having little or no money in my purse, and nothing particular
+to interest me on shore, I thought I would sail about a little.
+
+Unchanged paragraph
+with several lines
+of content.
+EOF
+
+cat <<'EOF' >assets/prose_after.blob
+The quick red fox leaps over the lazy dog.
+It was the best of times,   it was the worst of times.
+
+Call me Ishmael. Some years ago - never mind how long exactly -
+having little money in my purse, and nothing
+particular to interest me on shore, I thought I would sail about a little
+and see the watery part of the world.
+
+Unchanged paragraph
+with several lines
+of content.
+EOF
+baseline prose
+
+cat <<'EOF' >assets/code_before.blob
+fn main() {
+    let value = compute(1, 2);
+    println!("{}", value);
+}
+
+fn compute(a: u32, b: u32) -> u32 {
+    a + b
+}
+EOF
+
+cat <<'EOF' >assets/code_after.blob
+fn main() {
+    let result = compute(10, 2);
+    println!("{result}");
+}
+
+fn compute(a: u64, b: u64) -> u64 {
+    a * b
+}
+EOF
+baseline code
+
+printf 'a\nb\nc\nd\ne\nf\ng\n' >assets/removal_before.blob
+printf 'a\nd\ne\nf\ng\n' >assets/removal_after.blob
+baseline removal
+
+printf 'a\nb\nc\n' >assets/addition_before.blob
+printf 'a\n  new line\nb\nc\nmore\n' >assets/addition_after.blob
+baseline addition
+
+printf 'one two\nthree four\n' >assets/rewrap_before.blob
+printf 'one\ntwo three\n\nfour\n' >assets/rewrap_after.blob
+baseline rewrap
+
+for seed in $(seq 1 20); do
+  name=random${seed}
+  random_pair "$seed" $(( 10 + seed * 3 )) $(( seed % 2 == 0 ? 5 : 30 )) "assets/${name}_before.blob" "assets/${name}_after.blob"
+  baseline "$name"
+done
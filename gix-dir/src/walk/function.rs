@@ -104,6 +104,14 @@ pub fn walk(
         return Ok((out, root.to_owned()));
     }
 
+    if let Some(cache) = ctx.untracked_cache.as_deref_mut() {
+        let use_for_lookup = root == worktree_root
+            && options.for_deletion.is_none()
+            && options.emit_ignored.is_none()
+            && !options.emit_pruned
+            && ctx.excludes.is_some();
+        cache.begin_walk(use_for_lookup, use_for_lookup && ctx.pathspec.patterns().len() == 0);
+    }
     let mut state = readdir::State::new(worktree_root, ctx.current_dir, options.for_deletion.is_some());
     let may_collapse = root != worktree_root && state.may_collapse(&current);
     let (action, _) = readdir::recursive(
@@ -1,4 +1,8 @@
-use std::{collections::BTreeSet, path::PathBuf, sync::atomic::AtomicBool};
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::PathBuf,
+    sync::atomic::AtomicBool,
+};
 
 use bstr::{BStr, BString};
 
@@ -260,6 +264,32 @@ pub struct Context<'a> {
     ///
     /// If the `traversal_root` is not in the `worktree_root` passed to [walk()](crate::walk()).
     pub explicit_traversal_root: Option<&'a std::path::Path>,
+    /// If not `None`, the untracked cache to use for skipping directories whose contents didn't change since they were
+    /// last seen, and to update with what's seen during the traversal.
+    ///
+    /// It's only used if the traversal starts at the worktree root, doesn't emit ignored or pruned entries, isn't for deletion
+    /// and if `excludes` are set. It's only updated if additionally, the `pathspec` is empty.
+    pub untracked_cache: Option<&'a mut UntrackedCache>,
+}
+
+/// A way to use and maintain the untracked cache, as stored in the `UNTR` extension of the index, during a [`walk()`](function::walk()).
+///
+/// Directories are validated by their stat information and the object id of their `.gitignore` file, and if both are unchanged,
+/// their entries are obtained from the cache and the index instead of reading the directory.
+/// Directories that have to be read are recorded so the cache can be [written back](Self::into_extension()) if
+/// it [was modified](Self::is_modified()).
+pub struct UntrackedCache {
+    cache: gix_index::extension::UntrackedCache,
+    /// A mapping of worktree-relative directory paths to the index of the directory in `cache`.
+    by_path: HashMap<BString, usize>,
+    object_hash: gix_index::hash::Kind,
+    stat_options: gix_index::entry::stat::Options,
+    statistics: untracked_cache::Statistics,
+    is_modified: bool,
+    use_for_lookup: bool,
+    record_changes: bool,
+    /// The directory at which a change to the exclude file was detected, which invalidates all of its sub-directories as well.
+    excludes_changed_at: Option<BString>,
 }
 
 /// Additional information collected as outcome of [`walk()`](function::walk()).
@@ -308,3 +338,6 @@ pub enum Error {
 mod classify;
 pub(crate) mod function;
 mod readdir;
+
+///
+pub mod untracked_cache;
@@ -15,9 +15,35 @@ use crate::{
         EmissionMode::CollapseDirectory,
         Error, ForDeletionMode, Options, Outcome, classify,
         function::{can_recurse, emit_entry},
+        untracked_cache,
     },
 };
 
+/// The entries of a directory, either read from disk or provided by the untracked cache.
+enum Entries<I> {
+    Disk(I),
+    Cached(std::vec::IntoIter<untracked_cache::CachedEntry>),
+}
+
+enum DirEntry {
+    Disk(gix_fs::read_dir::DirEntry),
+    Cached(untracked_cache::CachedEntry),
+}
+
+impl<I> Iterator for Entries<I>
+where
+    I: Iterator<Item = std::io::Result<gix_fs::read_dir::DirEntry>>,
+{
+    type Item = std::io::Result<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Entries::Disk(entries) => entries.next().map(|res| res.map(DirEntry::Disk)),
+            Entries::Cached(entries) => entries.next().map(|entry| Ok(DirEntry::Cached(entry))),
+        }
+    }
+}
+
 /// ### Deviation
 ///
 /// Git mostly silently ignores IO errors and stops iterating seemingly quietly, while we error loudly.
@@ -36,11 +62,25 @@ pub(super) fn recursive(
     if ctx.should_interrupt.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
         return Err(Error::Interrupted);
     }
-    out.read_dir_calls += 1;
-    let entries = gix_fs::read_dir(current, opts.precompose_unicode).map_err(|err| Error::ReadDir {
-        path: current.to_owned(),
-        source: err,
-    })?;
+    let (entries, mut record) = match ctx
+        .untracked_cache
+        .as_deref_mut()
+        .map(|cache| cache.lookup(current, current_bstr.as_bstr(), ctx.index))
+    {
+        Some(untracked_cache::Lookup::Hit(cached)) => (Entries::Cached(cached.into_iter()), None),
+        lookup => {
+            out.read_dir_calls += 1;
+            let entries = gix_fs::read_dir(current, opts.precompose_unicode).map_err(|err| Error::ReadDir {
+                path: current.to_owned(),
+                source: err,
+            })?;
+            let record = match lookup {
+                Some(untracked_cache::Lookup::Miss(Some(record))) => Some((record, Vec::new(), Vec::new())),
+                _ => None,
+            };
+            (Entries::Disk(entries), record)
+        }
+    };
 
     let mut num_entries = 0;
     let mark = state.mark(may_collapse);
@@ -58,7 +98,13 @@ pub(super) fn recursive(
         if prev_len != 0 {
             current_bstr.push(b'/');
         }
-        let file_name = entry.file_name();
+        let (file_name, disk_kind) = match &entry {
+            DirEntry::Disk(entry) => (entry.file_name(), None),
+            DirEntry::Cached(entry) => (
+                Cow::Owned(gix_path::from_bstr(entry.name.as_bstr()).into_owned().into_os_string()),
+                entry.kind,
+            ),
+        };
         current_bstr.extend_from_slice(
             gix_path::try_os_str_into_bstr(Cow::Borrowed(file_name.as_ref()))
                 .expect("no illformed UTF-8")
@@ -70,19 +116,23 @@ pub(super) fn recursive(
             current,
             current_bstr,
             if prev_len == 0 { 0 } else { prev_len + 1 },
-            None,
-            || entry.file_type().ok().map(Into::into),
+            disk_kind,
+            || match &entry {
+                DirEntry::Disk(entry) => entry.file_type().ok().map(Into::into),
+                DirEntry::Cached(_) => None,
+            },
             opts,
             ctx,
         )?;
 
-        if can_recurse(
+        let can_recurse = can_recurse(
             current_bstr.as_bstr(),
             info,
             opts.for_deletion,
             false, /* is root */
             delegate,
-        ) {
+        );
+        if can_recurse {
             let subdir_may_collapse = state.may_collapse(current);
             let (action, subdir_prevent_collapse) = recursive(
                 subdir_may_collapse,
@@ -120,10 +170,38 @@ pub(super) fn recursive(
                 }
             }
         }
+        if let Some((_, untracked, sub_directories)) = record.as_mut() {
+            let name = current_bstr[if prev_len == 0 { 0 } else { prev_len + 1 }..].as_bstr();
+            if info.status == Status::Untracked {
+                if !info.disk_kind.is_some_and(|kind| kind.is_dir()) {
+                    untracked.push(name.to_owned());
+                } else if !can_recurse
+                    || ctx
+                        .untracked_cache
+                        .as_deref()
+                        .is_some_and(|cache| cache.contains_untracked(current_bstr.as_bstr()))
+                {
+                    // Like Git, only list directories that actually contain untracked files.
+                    let mut name = name.to_owned();
+                    name.push(b'/');
+                    untracked.push(name);
+                }
+            }
+            if can_recurse {
+                sub_directories.push(name.to_owned());
+            }
+        }
         current_bstr.truncate(prev_len);
         current.pop();
     }
 
+    if let Some(cache) = ctx.untracked_cache.as_deref_mut() {
+        if let Some((record, untracked, sub_directories)) = record {
+            cache.record(current_bstr.as_bstr(), record, untracked, sub_directories);
+        }
+        cache.leave_directory(current_bstr.as_bstr());
+    }
+
     let res = mark.reduce_held_entries(
         num_entries,
         state,
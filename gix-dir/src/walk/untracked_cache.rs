@@ -0,0 +1,449 @@
+use std::{collections::HashMap, path::Path};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_index::{
+    entry::stat,
+    extension::untracked_cache::{Directory, OidStat},
+    hash::ObjectId,
+};
+
+use crate::{entry, walk::UntrackedCache};
+
+/// The directory flags we record the cache with, matching `DIR_SHOW_OTHER_DIRECTORIES | DIR_HIDE_EMPTY_DIRECTORIES`,
+/// which is what Git uses for `git status` with `status.showUntrackedFiles=normal`.
+pub const DIR_FLAGS: u32 = 0x2 | 0x4;
+
+/// The name of the per-directory exclude file we record object ids for.
+pub const EXCLUDE_FILENAME_PER_DIR: &str = ".gitignore";
+
+/// Statistics about the use of the untracked cache during one or more walks.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Statistics {
+    /// The amount of directories whose entries were provided by the cache, without reading them from disk.
+    pub hits: u32,
+    /// The amount of directories that had to be read from disk as the cache didn't know them, or knew them in an outdated state.
+    pub misses: u32,
+    /// The amount of directories whose information was updated in the cache.
+    pub updated: u32,
+}
+
+/// An entry in a directory as provided by the cache, instead of being read from disk.
+pub(crate) struct CachedEntry {
+    pub name: BString,
+    pub kind: Option<entry::Kind>,
+}
+
+/// What to remember about a directory that was read from disk to update the cache later.
+pub(crate) struct Record {
+    stat: gix_index::entry::Stat,
+    exclude_file_oid: Option<ObjectId>,
+}
+
+/// The result of [`UntrackedCache::lookup()`].
+pub(crate) enum Lookup {
+    /// The directory is unchanged, and these are all of its entries that aren't ignored.
+    Hit(Vec<CachedEntry>),
+    /// The directory needs to be read from disk, and what's seen should be recorded using the given information, if `Some`.
+    Miss(Option<Record>),
+}
+
+/// Compute the object id of an exclude file with `data`, the way Git records it in the untracked cache.
+///
+/// Note that Git appends a newline to non-empty files before hashing them, so the id differs from the id
+/// of the blob with `data`.
+pub fn exclude_file_id(
+    mut data: Vec<u8>,
+    object_hash: gix_index::hash::Kind,
+) -> Result<ObjectId, gix_index::hash::hasher::Error> {
+    if data.is_empty() {
+        return Ok(object_hash.empty_blob());
+    }
+    data.push(b'\n');
+    gix_object::compute_hash(object_hash, gix_object::Kind::Blob, &data)
+}
+
+/// Lifecycle
+impl UntrackedCache {
+    /// Create a new instance from `cache`, which is usually obtained from the index, to use it during the walk and update it
+    /// with what's seen.
+    ///
+    /// * `identifier` - something identifying the location of the worktree and the system it is on. If it doesn't match the one
+    ///   stored in `cache`, the cache is discarded.
+    /// * `info_exclude` and `excludes_file` - the current state of `$GIT_DIR/info/exclude` and `core.excludesFile` respectively,
+    ///   which, if they don't match the recorded state, also cause the cache to be discarded.
+    /// * `object_hash` - the kind of hash to use when computing the object ids of `.gitignore` files.
+    /// * `stat_options` - configure how stat information of directories is compared.
+    ///
+    /// If `cache` is `None` or can't be used, a new empty cache is created instead, which will be populated during the walk.
+    pub fn new(
+        cache: Option<gix_index::extension::UntrackedCache>,
+        identifier: BString,
+        info_exclude: Option<OidStat>,
+        excludes_file: Option<OidStat>,
+        object_hash: gix_index::hash::Kind,
+        stat_options: stat::Options,
+    ) -> Self {
+        let ids_match = |recorded: Option<&OidStat>, current: Option<&OidStat>| {
+            recorded.map(|s| s.id).filter(|id| !id.is_null()) == current.map(|s| s.id)
+        };
+        let cache = cache.filter(|cache| {
+            cache.identifier() == identifier.as_bstr()
+                && cache.dir_flags() == DIR_FLAGS
+                && cache.exclude_filename_per_dir() == EXCLUDE_FILENAME_PER_DIR
+                && ids_match(cache.info_exclude(), info_exclude.as_ref())
+                && ids_match(cache.excludes_file(), excludes_file.as_ref())
+        });
+        let is_modified = cache.is_none();
+        let cache = cache.unwrap_or_else(|| {
+            gix_index::extension::UntrackedCache::new(
+                identifier,
+                info_exclude,
+                excludes_file,
+                EXCLUDE_FILENAME_PER_DIR.into(),
+                DIR_FLAGS,
+            )
+        });
+
+        let mut by_path = HashMap::new();
+        let mut path = BString::default();
+        if !cache.directories().is_empty() {
+            index_directories(cache.directories(), 0, &mut path, &mut by_path, 0);
+        }
+        UntrackedCache {
+            cache,
+            by_path,
+            object_hash,
+            stat_options,
+            statistics: Statistics::default(),
+            is_modified,
+            use_for_lookup: false,
+            record_changes: false,
+            excludes_changed_at: None,
+        }
+    }
+
+    /// Turn this instance into the untracked cache extension, for storage in the index.
+    pub fn into_extension(self) -> gix_index::extension::UntrackedCache {
+        self.cache
+    }
+}
+
+/// Access
+impl UntrackedCache {
+    /// Return statistics about the use of the cache so far.
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Return `true` if the cache was changed compared to the one it was created from, and should thus be written back.
+    pub fn is_modified(&self) -> bool {
+        self.is_modified
+    }
+
+    /// Return the extension as it is currently known.
+    pub fn extension(&self) -> &gix_index::extension::UntrackedCache {
+        &self.cache
+    }
+}
+
+fn index_directories(
+    directories: &[Directory],
+    index: usize,
+    path: &mut BString,
+    out: &mut HashMap<BString, usize>,
+    depth: usize,
+) {
+    // Prevent endless recursion on malformed input, it's just a cache.
+    if depth > directories.len() {
+        return;
+    }
+    let Some(dir) = directories.get(index) else {
+        return;
+    };
+    let prev_len = path.len();
+    if !dir.name.is_empty() {
+        if !path.is_empty() {
+            path.push(b'/');
+        }
+        path.extend_from_slice(&dir.name);
+    }
+    out.insert(path.clone(), index);
+    for sub_dir in &dir.sub_directories {
+        index_directories(directories, *sub_dir, path, out, depth + 1);
+    }
+    path.truncate(prev_len);
+}
+
+/// Walk integration
+impl UntrackedCache {
+    pub(crate) fn begin_walk(&mut self, use_for_lookup: bool, record_changes: bool) {
+        self.use_for_lookup = use_for_lookup;
+        self.record_changes = record_changes;
+        self.excludes_changed_at = None;
+    }
+
+    /// Check if the directory at `dir` with worktree-relative path `rela_path` is known and unchanged, and if so,
+    /// return all of its non-ignored entries using information from `index`.
+    pub(crate) fn lookup(&mut self, dir: &Path, rela_path: &BStr, index: &gix_index::State) -> Lookup {
+        if !self.use_for_lookup {
+            return Lookup::Miss(None);
+        }
+        let stat = match gix_index::fs::Metadata::from_path_no_follow(dir)
+            .ok()
+            .and_then(|md| gix_index::entry::Stat::from_fs(&md).ok())
+        {
+            Some(stat) => stat,
+            None => {
+                self.statistics.misses += 1;
+                return Lookup::Miss(None);
+            }
+        };
+        let exclude_file_oid = self.exclude_file_oid(dir, rela_path, index);
+        let record = self.record_changes.then_some(Record { stat, exclude_file_oid });
+
+        let in_changed_excludes = self
+            .excludes_changed_at
+            .as_ref()
+            .is_some_and(|changed| is_in_directory(rela_path, changed.as_bstr()));
+        let cached = self.by_path.get(rela_path).map(|idx| &self.cache.directories()[*idx]);
+        let Some(cached) = cached else {
+            self.statistics.misses += 1;
+            return Lookup::Miss(record);
+        };
+        if cached.exclude_file_oid.filter(|id| !id.is_null()) != exclude_file_oid {
+            if self.excludes_changed_at.is_none() {
+                self.excludes_changed_at = Some(rela_path.to_owned());
+            }
+            self.statistics.misses += 1;
+            return Lookup::Miss(record);
+        }
+        let is_valid = !in_changed_excludes
+            && !cached.check_only
+            && cached.stat.is_some_and(|cached_stat| {
+                cached_stat.matches(&stat, self.stat_options)
+                    && !cached_stat.is_racy(index.timestamp(), self.stat_options)
+            });
+        if !is_valid {
+            self.statistics.misses += 1;
+            return Lookup::Miss(record);
+        }
+
+        let Some(entries) = cached_entries(cached, &self.cache, dir, rela_path, index) else {
+            self.statistics.misses += 1;
+            return Lookup::Miss(record);
+        };
+        self.statistics.hits += 1;
+        Lookup::Hit(entries)
+    }
+
+    /// Return `true` if the directory at `rela_path` is known to contain untracked entries.
+    pub(crate) fn contains_untracked(&self, rela_path: &BStr) -> bool {
+        self.by_path
+            .get(rela_path)
+            .is_some_and(|idx| !self.cache.directories()[*idx].untracked_entries.is_empty())
+    }
+
+    /// Remember that the directory at `rela_path` was read from disk, with `untracked` entries and `sub_directories` that
+    /// were recursed into.
+    ///
+    /// Untracked directories are marked as *check-only* like Git does, as Git only shows them instead of their contents.
+    pub(crate) fn record(
+        &mut self,
+        rela_path: &BStr,
+        record: Record,
+        mut untracked: Vec<BString>,
+        sub_directories: Vec<BString>,
+    ) {
+        let sub_directories: Vec<_> = sub_directories
+            .into_iter()
+            .map(|name| {
+                let idx = self.directory_index(join(rela_path, name.as_bstr()).as_bstr());
+                let is_untracked = untracked
+                    .iter()
+                    .any(|entry| entry.strip_suffix(b"/").is_some_and(|dir| dir == name.as_slice()));
+                self.cache.directories_mut()[idx].check_only = is_untracked;
+                idx
+            })
+            .collect();
+        untracked.sort();
+        let idx = self.directory_index(rela_path);
+        let dir = &mut self.cache.directories_mut()[idx];
+        dir.stat = Some(record.stat);
+        dir.exclude_file_oid = record.exclude_file_oid;
+        dir.untracked_entries = untracked;
+        dir.sub_directories = sub_directories;
+        self.statistics.updated += 1;
+        self.is_modified = true;
+    }
+
+    /// Call once the directory at `rela_path` was fully processed.
+    pub(crate) fn leave_directory(&mut self, rela_path: &BStr) {
+        if self.excludes_changed_at.as_ref().is_some_and(|p| p == rela_path) {
+            self.excludes_changed_at = None;
+        }
+    }
+
+    /// Return the index of the directory at `rela_path`, creating it and all of its parents if needed.
+    fn directory_index(&mut self, rela_path: &BStr) -> usize {
+        if let Some(idx) = self.by_path.get(rela_path) {
+            return *idx;
+        }
+        let (parent, name) = match rela_path.rfind_byte(b'/') {
+            Some(pos) => (Some(rela_path[..pos].as_bstr()), rela_path[pos + 1..].as_bstr()),
+            None if rela_path.is_empty() => (None, rela_path),
+            None => (Some("".into()), rela_path),
+        };
+        let parent_idx = parent.map(|parent| self.directory_index(parent));
+        let directories = self.cache.directories_mut();
+        let idx = directories.len();
+        directories.push(Directory {
+            name: name.to_owned(),
+            untracked_entries: Vec::new(),
+            sub_directories: Vec::new(),
+            stat: None,
+            exclude_file_oid: None,
+            check_only: false,
+        });
+        if let Some(parent_idx) = parent_idx {
+            directories[parent_idx].sub_directories.push(idx);
+        }
+        self.by_path.insert(rela_path.to_owned(), idx);
+        self.is_modified = true;
+        idx
+    }
+
+    /// Obtain the id of the per-directory exclude file in `dir`, preferring the id of an up-to-date `index` entry
+    /// to avoid reading the file, just like Git does.
+    fn exclude_file_oid(&self, dir: &Path, rela_path: &BStr, index: &gix_index::State) -> Option<ObjectId> {
+        let rela_exclude_path = join(rela_path, EXCLUDE_FILENAME_PER_DIR.into());
+        let path = dir.join(EXCLUDE_FILENAME_PER_DIR);
+        let entry = index.entry_by_path(rela_exclude_path.as_bstr());
+        if let Some(entry) = entry.filter(|entry| {
+            entry.flags.contains(gix_index::entry::Flags::UPTODATE)
+                || gix_index::fs::Metadata::from_path_no_follow(&path)
+                    .ok()
+                    .and_then(|md| gix_index::entry::Stat::from_fs(&md).ok())
+                    .is_some_and(|stat| {
+                        entry.stat.matches(&stat, self.stat_options)
+                            && !entry.stat.is_racy(index.timestamp(), self.stat_options)
+                    })
+        }) {
+            return Some(entry.id);
+        }
+        let data = std::fs::read(path).ok()?;
+        if let Some(entry) = entry.filter(|entry| {
+            gix_object::compute_hash(self.object_hash, gix_object::Kind::Blob, &data).ok() == Some(entry.id)
+        }) {
+            return Some(entry.id);
+        }
+        exclude_file_id(data, self.object_hash).ok()
+    }
+}
+
+/// Combine all tracked entries in `index` directly within `rela_path` with the untracked entries and
+/// sub-directories of the `cached` directory. Return `None` if the entries can't be determined, or if there are none.
+fn cached_entries(
+    cached: &Directory,
+    cache: &gix_index::extension::UntrackedCache,
+    dir: &Path,
+    rela_path: &BStr,
+    index: &gix_index::State,
+) -> Option<Vec<CachedEntry>> {
+    let mut out = Vec::new();
+    let prefix = if rela_path.is_empty() {
+        BString::default()
+    } else {
+        let mut prefix = rela_path.to_owned();
+        prefix.push(b'/');
+        prefix
+    };
+    let entries = if prefix.is_empty() {
+        index.entries()
+    } else {
+        index.prefixed_entries(prefix.as_bstr()).unwrap_or_default()
+    };
+    for entry in entries {
+        if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) {
+            continue;
+        }
+        let path = entry.path(index);
+        let Some(relative) = path.strip_prefix(prefix.as_slice()) else {
+            continue;
+        };
+        let (name, kind) = match relative.find_byte(b'/') {
+            Some(pos) => (&relative[..pos], entry::Kind::Directory),
+            None => (
+                relative,
+                if entry.mode == gix_index::entry::Mode::SYMLINK {
+                    entry::Kind::Symlink
+                } else if entry.mode.is_submodule() || entry.mode.is_sparse() {
+                    entry::Kind::Directory
+                } else {
+                    entry::Kind::File
+                },
+            ),
+        };
+        if out.last().is_some_and(|last: &CachedEntry| last.name == name) {
+            continue;
+        }
+        out.push(CachedEntry {
+            name: name.into(),
+            kind: Some(kind),
+        });
+    }
+
+    for name in &cached.untracked_entries {
+        let (name, kind) = match name.strip_suffix(b"/") {
+            Some(dir_name) => (dir_name.as_bstr(), Some(entry::Kind::Directory)),
+            None => {
+                let kind = gix_path::try_from_bstr(name.as_bstr())
+                    .ok()
+                    .and_then(|name| dir.join(name).symlink_metadata().ok())?
+                    .file_type()
+                    .into();
+                (name.as_bstr(), Some(kind))
+            }
+        };
+        out.push(CachedEntry {
+            name: name.to_owned(),
+            kind,
+        });
+    }
+    for sub_dir in cached
+        .sub_directories
+        .iter()
+        .filter_map(|idx| cache.directories().get(*idx))
+    {
+        out.push(CachedEntry {
+            name: sub_dir.name.clone(),
+            kind: Some(entry::Kind::Directory),
+        });
+    }
+
+    // Directories with only ignored entries would look empty, which is why they are always read from disk.
+    if out.is_empty() {
+        return None;
+    }
+    out.sort_by(|a, b| a.name.cmp(&b.name));
+    out.dedup_by(|a, b| a.name == b.name);
+    Some(out)
+}
+
+fn join(dir: &BStr, name: &BStr) -> BString {
+    if dir.is_empty() {
+        name.to_owned()
+    } else {
+        let mut out = dir.to_owned();
+        out.push(b'/');
+        out.extend_from_slice(name);
+        out
+    }
+}
+
+/// Return `true` if `rela_path` is `dir` or inside of it.
+fn is_in_directory(rela_path: &BStr, dir: &BStr) -> bool {
+    dir.is_empty()
+        || rela_path
+            .strip_prefix(dir.as_bytes())
+            .is_some_and(|rest| rest.is_empty() || rest[0] == b'/')
+}
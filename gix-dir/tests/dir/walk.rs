@@ -4859,3 +4859,117 @@ fn in_repo_hidden_worktree() -> crate::Result {
     }
     Ok(())
 }
+
+#[test]
+fn untracked_cache_avoids_reading_unchanged_directories() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("many.sh")?;
+    let root = tmp.path().join("untracked-cache");
+
+    let mut cache = walk::UntrackedCache::new(
+        None,
+        "test".into(),
+        None,
+        None,
+        gix_index::hash::Kind::Sha1,
+        gix_index::entry::stat::Options {
+            use_nsec: true,
+            ..Default::default()
+        },
+    );
+    let walk_with_cache = |cache: &mut walk::UntrackedCache| {
+        try_collect_filtered_opts_collect(
+            &root,
+            None,
+            |keep, ctx| {
+                walk(
+                    &root,
+                    ctx,
+                    walk::Options {
+                        emit_untracked: CollapseDirectory,
+                        ..options()
+                    },
+                    keep,
+                )
+            },
+            None::<&str>,
+            Options {
+                untracked_cache: Some(cache),
+                ..Default::default()
+            },
+        )
+    };
+
+    let ((out, _root), expected_entries) = walk_with_cache(&mut cache)?;
+    assert_eq!(out.read_dir_calls, 4);
+    assert_eq!(
+        expected_entries,
+        [
+            entry("dir/new-dir", Untracked, Directory),
+            entry("dir/untracked", Untracked, File),
+            entry("untracked", Untracked, File),
+        ]
+    );
+    assert_eq!(
+        *cache.statistics(),
+        walk::untracked_cache::Statistics {
+            hits: 0,
+            misses: 4,
+            updated: 4,
+        },
+        "initially nothing is known, and everything is recorded"
+    );
+    assert!(cache.is_modified());
+
+    let ((out, _root), entries) = walk_with_cache(&mut cache)?;
+    assert_eq!(
+        out.read_dir_calls, 1,
+        "only the untracked directory is read as Git only knows if it contains untracked files"
+    );
+    assert_eq!(entries, expected_entries, "the cache yields the same result");
+    assert_eq!(
+        *cache.statistics(),
+        walk::untracked_cache::Statistics {
+            hits: 3,
+            misses: 5,
+            updated: 5,
+        }
+    );
+
+    std::fs::write(root.join("dir").join("sub").join("new"), [])?;
+    let ((out, _root), entries) = walk_with_cache(&mut cache)?;
+    assert_eq!(out.read_dir_calls, 2, "the changed directory is read as well");
+    assert_eq!(
+        entries,
+        [
+            entry("dir/new-dir", Untracked, Directory),
+            entry("dir/sub/new", Untracked, File),
+            entry("dir/untracked", Untracked, File),
+            entry("untracked", Untracked, File),
+        ]
+    );
+    assert_eq!(
+        *cache.statistics(),
+        walk::untracked_cache::Statistics {
+            hits: 5,
+            misses: 7,
+            updated: 7,
+        }
+    );
+
+    let cache = cache.into_extension();
+    assert_eq!(cache.directories().len(), 4, "each directory is stored once");
+    let root_dir = &cache.directories()[0];
+    assert_eq!(root_dir.name, "", "the root directory comes first");
+    assert_eq!(root_dir.untracked_entries, ["untracked"]);
+    let dir = cache
+        .directories()
+        .iter()
+        .find(|d| d.name == "dir")
+        .expect("dir is present");
+    assert_eq!(dir.untracked_entries, ["new-dir/", "untracked"]);
+    assert!(
+        cache.directories().iter().any(|d| d.name == "new-dir" && d.check_only),
+        "untracked directories are only checked for untracked files, just like Git does it"
+    );
+    Ok(())
+}
@@ -459,3 +459,14 @@ git clone dir-with-tracked-file in-repo-hidden-worktree
   touch hidden/file
   git worktree add -b worktree-branch hidden/subdir/worktree
 )
+
+git init untracked-cache
+(cd untracked-cache
+  mkdir -p dir/sub
+  touch a dir/b dir/sub/c
+  git add .
+  git commit -m "init"
+
+  mkdir dir/new-dir
+  touch untracked dir/untracked dir/new-dir/file
+)
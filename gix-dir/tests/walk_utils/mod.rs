@@ -283,6 +283,7 @@ pub fn try_collect_filtered_opts(
         fresh_index,
         git_dir,
         should_interrupt,
+        untracked_cache,
     }: Options<'_>,
 ) -> Result<(walk::Outcome, PathBuf), walk::Error> {
     let git_dir = worktree_root.join(git_dir.unwrap_or(".git"));
@@ -299,6 +300,10 @@ pub fn try_collect_filtered_opts(
             .expect("valid index")
         },
     );
+    if untracked_cache.is_some() {
+        // Pretend the index was written after all changes, so no directory is racily modified.
+        index.set_timestamp((std::time::SystemTime::now() + std::time::Duration::from_secs(60)).into());
+    }
     if fresh_index {
         index
             .entries_mut()
@@ -359,6 +364,7 @@ pub fn try_collect_filtered_opts(
             objects: &gix_object::find::Never,
             explicit_traversal_root,
             should_interrupt,
+            untracked_cache,
         },
     )
 }
@@ -367,6 +373,7 @@ pub struct Options<'a> {
     pub fresh_index: bool,
     pub git_dir: Option<&'a str>,
     pub should_interrupt: Option<&'a AtomicBool>,
+    pub untracked_cache: Option<&'a mut walk::UntrackedCache>,
 }
 
 impl<'a> Options<'a> {
@@ -384,6 +391,7 @@ impl Default for Options<'_> {
             fresh_index: true,
             git_dir: None,
             should_interrupt: None,
+            untracked_cache: None,
         }
     }
 }
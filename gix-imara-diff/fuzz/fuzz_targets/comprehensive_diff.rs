@@ -39,6 +39,7 @@ fn do_fuzz(
         Algorithm::Histogram,
         Algorithm::Myers,
         Algorithm::MyersMinimal,
+        Algorithm::Patience,
     ] {
         // Compute diff
         let mut diff = Diff::compute(algorithm, &input);
@@ -12,10 +12,11 @@
 //! not just lists and strings and even allows reusing large parts of the computation when
 //! comparing the same file to multiple different files.
 //!
-//! Imara-diff provides two diff algorithms:
+//! Imara-diff provides three diff algorithms:
 //!
 //! * The linear-space variant of the well known [**Myers** algorithm](http://www.xmailserver.org/diff2.pdf)
 //! * The **Histogram** algorithm which is a variant of the patience diff algorithm.
+//! * The **Patience** algorithm, matching the output of `git diff --patience`.
 //!
 //! Myers algorithm has been enhanced with preprocessing and multiple heuristics to ensure fast runtime in pathological
 //! cases to avoid quadratic time complexity and closely matches the behavior of gnu-diff and git.
@@ -152,7 +153,9 @@ use crate::{
     util::{strip_common_postfix, strip_common_prefix},
 };
 
-pub use crate::slider_heuristic::{IndentHeuristic, IndentLevel, NoSliderHeuristic, SliderHeuristic};
+pub use crate::slider_heuristic::{
+    IndentHeuristic, IndentLevel, NoSliderHeuristic, PositionalIndentHeuristic, SliderHeuristic,
+};
 pub use intern::{InternedInput, Interner, Token, TokenSource};
 #[cfg(feature = "unified_diff")]
 pub use unified_diff::{BasicLineDiffPrinter, UnifiedDiff, UnifiedDiffConfig, UnifiedDiffPrinter};
@@ -160,6 +163,7 @@ pub use unified_diff::{BasicLineDiffPrinter, UnifiedDiff, UnifiedDiffConfig, Uni
 mod histogram;
 mod intern;
 mod myers;
+mod patience;
 mod postprocess;
 mod slider_heuristic;
 pub mod sources;
@@ -224,6 +228,16 @@ pub enum Algorithm {
     /// a minimal edit sequence.
     /// This can mean significant slowdown in pathological cases.
     MyersMinimal,
+    /// The [`patience` diff algorithm described by Bram Cohen's blog post](https://bramcohen.livejournal.com/73318.html),
+    /// implemented to produce the same edit sequences as `git diff --patience`.
+    ///
+    /// Tokens that occur exactly once in both sequences are matched by their longest common subsequence, and
+    /// serve as anchors between which the algorithm recurses. Ranges without any unique common token
+    /// fall back on Myers algorithm (with heuristics).
+    ///
+    /// It's usually slower than the `Histogram` algorithm, which produces similar results, and should be used
+    /// mainly to obtain the same diffs as `git` when configured with `diff.algorithm=patience`.
+    Patience,
 }
 
 /// Represents the difference between two sequences of tokens.
@@ -271,6 +285,18 @@ impl Diff {
         self.added.clear();
         self.removed.resize(before.len(), false);
         self.added.resize(after.len(), false);
+        if algorithm == Algorithm::Patience {
+            // Stripping common tokens up-front changes which tokens are unique, which is why `git` doesn't do it either.
+            patience::diff(before, after, &mut self.removed, &mut self.added);
+            return;
+        }
+        if let Algorithm::Myers | Algorithm::MyersMinimal = algorithm {
+            // Occurrences of tokens in the common prefix and postfix affect which tokens are pruned, which is why
+            // `git` doesn't strip them up-front either.
+            let minimal = algorithm == Algorithm::MyersMinimal;
+            myers::diff(before, after, &mut self.removed, &mut self.added, minimal);
+            return;
+        }
         let common_prefix = strip_common_prefix(&mut before, &mut after) as usize;
         let common_postfix = strip_common_postfix(&mut before, &mut after);
         let range = common_prefix..self.removed.len() - common_postfix as usize;
@@ -279,8 +305,9 @@ impl Diff {
         let added = &mut self.added[range];
         match algorithm {
             Algorithm::Histogram => histogram::diff(before, after, removed, added, num_tokens),
-            Algorithm::Myers => myers::diff(before, after, removed, added, false),
-            Algorithm::MyersMinimal => myers::diff(before, after, removed, added, true),
+            Algorithm::Myers | Algorithm::MyersMinimal | Algorithm::Patience => {
+                unreachable!("handled before stripping common tokens")
+            }
         }
     }
 
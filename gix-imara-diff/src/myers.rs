@@ -14,6 +14,9 @@ mod slice;
 
 /// Computes a diff using the Myers algorithm.
 ///
+/// Like `git`, `before` and `after` are expected to be the entire sequences to diff, including their common prefix and postfix,
+/// as these affect how often tokens occur.
+///
 /// # Parameters
 ///
 /// * `before` - The token sequence from the first file
@@ -3,11 +3,15 @@
 
 use crate::intern::Token;
 use crate::myers::sqrt;
+use crate::util::{common_postfix, common_prefix};
 
-/// Preprocesses token sequences by removing tokens that don't appear in the other sequence.
+/// Preprocesses token sequences by removing tokens that don't appear in the other sequence,
+/// after skipping their common prefix and postfix.
 ///
 /// This optimization reduces the problem size for the Myers algorithm, improving performance
 /// for files with many unique tokens.
+/// Like `git`, occurrences of tokens are counted in the entire sequences, and tokens that occur very frequently
+/// are only kept if they aren't surrounded by tokens that are removed.
 pub fn preprocess<'a>(
     before: &[Token],
     after: &[Token],
@@ -15,8 +19,22 @@ pub fn preprocess<'a>(
     added: &'a mut [bool],
 ) -> (PreprocessedFile, PreprocessedFile) {
     let (occurrences_before, occurrences_after) = token_occurrences(before, after);
-    let file1 = PreprocessedFile::new(&occurrences_before, before, removed);
-    let file2 = PreprocessedFile::new(&occurrences_after, after, added);
+    let prefix = common_prefix(before, after) as usize;
+    let postfix = common_postfix(&before[prefix..], &after[prefix..]) as usize;
+    let range_before = prefix..before.len() - postfix;
+    let range_after = prefix..after.len() - postfix;
+    let file1 = PreprocessedFile::new(
+        &occurrences_before[range_before.clone()],
+        &before[range_before],
+        prefix,
+        removed,
+    );
+    let file2 = PreprocessedFile::new(
+        &occurrences_after[range_after.clone()],
+        &after[range_after],
+        prefix,
+        added,
+    );
     (file1, file2)
 }
 
@@ -98,8 +116,15 @@ pub struct PreprocessedFile {
 }
 
 impl PreprocessedFile {
-    fn new(token_occurrences: &[Occurrences], tokens: &[Token], changed: &mut [bool]) -> PreprocessedFile {
-        let (tokens, indices) = prune_unmatched_tokens(tokens, token_occurrences, changed);
+    /// Create a new instance from `tokens` that start at `offset` in the file that `changed` belongs to.
+    fn new(
+        token_occurrences: &[Occurrences],
+        tokens: &[Token],
+        offset: usize,
+        changed: &mut [bool],
+    ) -> PreprocessedFile {
+        let (tokens, indices) = prune_unmatched_tokens(tokens, token_occurrences, &mut changed[offset..]);
+        let indices = indices.into_iter().map(|idx| idx + offset as u32).collect();
         PreprocessedFile { indices, tokens }
     }
 }
@@ -129,12 +154,15 @@ fn prune_unmatched_tokens(
         .unzip()
 }
 
+/// Like `xdl_clean_mmatch()` in `git`, prune a common token at `pos` only if it's part of a run of
+/// unmatched and common tokens which mostly consists of unmatched tokens, both before and after `pos`.
 // TODO do not unnecessarily rescan lines
 fn should_prune_common_line(token_status: &[Occurrences], pos: usize) -> bool {
     const WINDOW_SIZE: usize = 100;
 
     let mut unmatched_before = 0;
-    let mut common_before = 0;
+    // The token at `pos` is common, and counted once for each direction.
+    let mut common_before = 1;
 
     let start = pos.saturating_sub(WINDOW_SIZE);
     for status in token_status[start..pos].iter().rev() {
@@ -153,10 +181,10 @@ fn should_prune_common_line(token_status: &[Occurrences], pos: usize) -> bool {
         return false;
     }
 
-    let end = token_status.len().min(pos + WINDOW_SIZE);
+    let end = token_status.len().min(pos + 1 + WINDOW_SIZE);
     let mut unmatched_after = 0;
-    let mut common_after = 0;
-    for status in token_status[pos..end].iter() {
+    let mut common_after = 1;
+    for status in token_status[pos + 1..end].iter() {
         match status {
             Occurrences::None => {
                 unmatched_after += 1;
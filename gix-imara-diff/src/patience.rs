@@ -0,0 +1,168 @@
+use hashbrown::HashMap;
+use hashbrown::hash_map::Entry;
+
+use crate::intern::Token;
+use crate::myers;
+
+/// Where a token that occurs in `before` was seen in `after`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Occurrence {
+    /// The token wasn't seen in `after` yet.
+    Absent,
+    /// The token was seen exactly once in `before`, and in `after` at the given position.
+    Unique(u32),
+    /// The token occurs more than once in `before` or `after`.
+    Repeated,
+}
+
+/// A distinct token of `before`, in order of its first occurrence.
+struct Line {
+    /// The position of the first occurrence in `before`.
+    before: u32,
+    after: Occurrence,
+}
+
+/// Computes a diff using the patience algorithm, matching the output of `git diff --patience`.
+///
+/// Tokens that occur exactly once in both `before` and `after` are matched using their longest
+/// common subsequence, and the result is used to anchor the diff. Common tokens adjacent to these anchors
+/// are matched as well, and the algorithm recurses into the remaining ranges between anchors.
+/// If a range has tokens in common, but none of them is unique, it falls back to Myers algorithm.
+///
+/// # Parameters
+///
+/// * `before` - The token sequence from the first file, before changes.
+/// * `after` - The token sequence from the second file, after changes.
+/// * `removed` - Output array marking removed tokens
+/// * `added` - Output array marking added tokens
+pub fn diff(before: &[Token], after: &[Token], removed: &mut [bool], added: &mut [bool]) {
+    if before.is_empty() {
+        added.fill(true);
+        return;
+    } else if after.is_empty() {
+        removed.fill(true);
+        return;
+    }
+
+    let Some(anchors) = unique_common_subsequence(before, after) else {
+        removed.fill(true);
+        added.fill(true);
+        return;
+    };
+    if anchors.is_empty() {
+        myers::diff(before, after, removed, added, false);
+        return;
+    }
+
+    let (mut pos_before, mut pos_after) = (0, 0);
+    let mut anchors = anchors.as_slice();
+    loop {
+        // Grow the common ranges around the next anchor in both directions.
+        let (next_before, next_after) = match anchors.first() {
+            Some(&(anchor_before, anchor_after)) => {
+                let (mut next_before, mut next_after) = (anchor_before as usize, anchor_after as usize);
+                while next_before > pos_before
+                    && next_after > pos_after
+                    && before[next_before - 1] == after[next_after - 1]
+                {
+                    next_before -= 1;
+                    next_after -= 1;
+                }
+                (next_before, next_after)
+            }
+            None => (before.len(), after.len()),
+        };
+        while pos_before < next_before && pos_after < next_after && before[pos_before] == after[pos_after] {
+            pos_before += 1;
+            pos_after += 1;
+        }
+        if next_before > pos_before || next_after > pos_after {
+            diff(
+                &before[pos_before..next_before],
+                &after[pos_after..next_after],
+                &mut removed[pos_before..next_before],
+                &mut added[pos_after..next_after],
+            );
+        }
+
+        let Some(mut anchor) = anchors.first().copied() else {
+            return;
+        };
+        anchors = &anchors[1..];
+        // Skip over consecutive anchors as there is nothing to diff between them.
+        while let Some(&next) = anchors.first() {
+            if next != (anchor.0 + 1, anchor.1 + 1) {
+                break;
+            }
+            anchor = next;
+            anchors = &anchors[1..];
+        }
+        (pos_before, pos_after) = (anchor.0 as usize + 1, anchor.1 as usize + 1);
+    }
+}
+
+/// Return the longest common subsequence of all tokens that occur exactly once in both `before` and `after`,
+/// as pairs of positions into `before` and `after`.
+///
+/// Return `None` if `before` and `after` have no token in common at all.
+fn unique_common_subsequence(before: &[Token], after: &[Token]) -> Option<Vec<(u32, u32)>> {
+    let mut line_by_token = HashMap::<Token, usize>::with_capacity(before.len());
+    let mut lines = Vec::<Line>::new();
+    for (pos, &token) in before.iter().enumerate() {
+        match line_by_token.entry(token) {
+            Entry::Occupied(entry) => lines[*entry.get()].after = Occurrence::Repeated,
+            Entry::Vacant(entry) => {
+                entry.insert(lines.len());
+                lines.push(Line {
+                    before: pos as u32,
+                    after: Occurrence::Absent,
+                });
+            }
+        }
+    }
+
+    let mut has_matches = false;
+    for (pos, token) in after.iter().enumerate() {
+        if let Some(&idx) = line_by_token.get(token) {
+            has_matches = true;
+            let line = &mut lines[idx];
+            line.after = match line.after {
+                Occurrence::Absent => Occurrence::Unique(pos as u32),
+                Occurrence::Unique(_) | Occurrence::Repeated => Occurrence::Repeated,
+            };
+        }
+    }
+    if !has_matches {
+        return None;
+    }
+
+    // Patience sorting: `tails[n]` is the index of the line that ends the best known common subsequence
+    // of length `n + 1`, and `previous` links each line to its predecessor in that subsequence.
+    let after_pos = |idx: usize| match lines[idx].after {
+        Occurrence::Unique(pos) => pos,
+        Occurrence::Absent | Occurrence::Repeated => unreachable!("only unique lines are sorted"),
+    };
+    let mut tails = Vec::<usize>::new();
+    let mut previous = vec![None; lines.len()];
+    for (idx, line) in lines.iter().enumerate() {
+        let Occurrence::Unique(pos) = line.after else {
+            continue;
+        };
+        let len = tails.partition_point(|&tail| after_pos(tail) < pos);
+        previous[idx] = len.checked_sub(1).map(|prev| tails[prev]);
+        if len == tails.len() {
+            tails.push(idx);
+        } else {
+            tails[len] = idx;
+        }
+    }
+
+    let mut out = Vec::with_capacity(tails.len());
+    let mut cursor = tails.last().copied();
+    while let Some(idx) = cursor {
+        out.push((lines[idx].before, after_pos(idx)));
+        cursor = previous[idx];
+    }
+    out.reverse();
+    Some(out)
+}
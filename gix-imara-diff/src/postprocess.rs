@@ -19,28 +19,32 @@ impl Diff {
     /// * `after` - The token sequence from the second file, after changes
     /// * `heuristic` - The slider heuristic to use for positioning hunks
     pub fn postprocess_with(&mut self, before: &[Token], after: &[Token], mut heuristic: impl SliderHeuristic) {
-        Postprocessor {
-            added: &mut self.added,
-            removed: &mut self.removed,
-            tokens: after,
-            hunk: Hunk {
-                before: 0..0,
-                after: 0..0,
-            },
-            heuristic: &mut heuristic,
-        }
-        .run();
-        Postprocessor {
-            added: &mut self.removed,
-            removed: &mut self.added,
-            tokens: before,
-            hunk: Hunk {
-                before: 0..0,
-                after: 0..0,
-            },
-            heuristic: &mut heuristic,
-        }
-        .run()
+        // Like `git`, process removals first as the result depends on the order.
+        Postprocessor::new(&mut self.removed, &self.added, before, &mut heuristic).run();
+        Postprocessor::new(&mut self.added, &self.removed, after, &mut heuristic).run();
+    }
+
+    /// Postprocesses the diff like [`postprocess_with`](Self::postprocess_with), but uses
+    /// `before_heuristic` when positioning hunks in `before`, and `after_heuristic` when positioning hunks in `after`.
+    ///
+    /// This is useful for heuristics that need to know which token sequence they operate on,
+    /// like [`PositionalIndentHeuristic`](crate::PositionalIndentHeuristic).
+    ///
+    /// # Parameters
+    ///
+    /// * `before` - The token sequence from the first file, before changes
+    /// * `after` - The token sequence from the second file, after changes
+    /// * `before_heuristic` - The slider heuristic to use for positioning hunks in `before`
+    /// * `after_heuristic` - The slider heuristic to use for positioning hunks in `after`
+    pub fn postprocess_with_heuristics(
+        &mut self,
+        before: &[Token],
+        after: &[Token],
+        mut before_heuristic: impl SliderHeuristic,
+        mut after_heuristic: impl SliderHeuristic,
+    ) {
+        Postprocessor::new(&mut self.removed, &self.added, before, &mut before_heuristic).run();
+        Postprocessor::new(&mut self.added, &self.removed, after, &mut after_heuristic).run();
     }
 
     /// Postprocesses the diff using an `InternedInput` and a custom heuristic.
@@ -71,7 +75,20 @@ struct Postprocessor<'a, H> {
     heuristic: &'a mut H,
 }
 
-impl<H: SliderHeuristic> Postprocessor<'_, H> {
+impl<'a, H: SliderHeuristic> Postprocessor<'a, H> {
+    fn new(added: &'a mut [bool], removed: &'a [bool], tokens: &'a [Token], heuristic: &'a mut H) -> Self {
+        Postprocessor {
+            added,
+            removed,
+            tokens,
+            hunk: Hunk {
+                before: 0..0,
+                after: 0..0,
+            },
+            heuristic,
+        }
+    }
+
     fn run(mut self) {
         loop {
             // find a hunk
@@ -71,33 +71,71 @@ impl<IndentOfToken> IndentHeuristic<IndentOfToken> {
 
 impl<IndentOfToken: Fn(Token) -> IndentLevel> SliderHeuristic for IndentHeuristic<IndentOfToken> {
     fn best_slider_end(&mut self, tokens: &[Token], hunk: Range<u32>, earliest_end: u32) -> u32 {
-        const MAX_SLIDING: u32 = 100;
-        // This is a pure insertion that can be moved freely up and down.
-        // To get more intuitive results, apply a heuristic.
-        let mut top_slider_end = earliest_end;
-        // TODO: why is this needed
-        if top_slider_end < hunk.start - 1 {
-            top_slider_end = hunk.start - 1;
-        }
-        if hunk.end > top_slider_end + MAX_SLIDING {
-            top_slider_end = hunk.end - MAX_SLIDING;
-        }
-        let group_size = hunk.end - hunk.start;
-        let mut best_score = Score::for_range(
-            top_slider_end - group_size..top_slider_end,
-            tokens,
-            &self.indent_of_token,
-        );
-        let mut best_slider_end = top_slider_end;
-        for slider_end in (top_slider_end + 1)..=hunk.end {
-            let score = Score::for_range(slider_end - group_size..slider_end, tokens, &self.indent_of_token);
-            if score.is_improvement_over(best_score) {
-                best_score = score;
-                best_slider_end = slider_end;
-            }
+        best_slider_end_by_indent(tokens.len(), hunk, earliest_end, |idx| {
+            (self.indent_of_token)(tokens[idx])
+        })
+    }
+}
+
+/// A slider heuristic like [`IndentHeuristic`], but which obtains the indentation level of a line
+/// by its position in the token sequence instead of by its token.
+///
+/// This is useful if tokens don't represent lines verbatim, for instance if lines are compared after
+/// normalizing their whitespace, so that equal tokens may have different indentation levels.
+/// As positions are only meaningful for one of the token sequences, it's typically used with
+/// [`Diff::postprocess_with_heuristics()`](crate::Diff::postprocess_with_heuristics()).
+pub struct PositionalIndentHeuristic<IndentAtPosition> {
+    /// A function that computes the indentation level for the line at a given position.
+    indent_at_position: IndentAtPosition,
+}
+
+impl<IndentAtPosition> PositionalIndentHeuristic<IndentAtPosition> {
+    /// Creates a new `PositionalIndentHeuristic` with the given indentation function.
+    ///
+    /// # Parameters
+    ///
+    /// * `indent_at_position` - A function that takes the position of a token and returns the indentation level of its line
+    pub fn new(indent_at_position: IndentAtPosition) -> Self {
+        Self { indent_at_position }
+    }
+}
+
+impl<IndentAtPosition: Fn(usize) -> IndentLevel> SliderHeuristic for PositionalIndentHeuristic<IndentAtPosition> {
+    fn best_slider_end(&mut self, tokens: &[Token], hunk: Range<u32>, earliest_end: u32) -> u32 {
+        best_slider_end_by_indent(tokens.len(), hunk, earliest_end, &self.indent_at_position)
+    }
+}
+
+/// Find the best end of the slider `hunk` among `num_tokens`, with `indent_at` returning the indentation
+/// level of the line at the given position.
+fn best_slider_end_by_indent(
+    num_tokens: usize,
+    hunk: Range<u32>,
+    earliest_end: u32,
+    indent_at: impl Fn(usize) -> IndentLevel,
+) -> u32 {
+    const MAX_SLIDING: u32 = 100;
+    // This is a pure insertion that can be moved freely up and down.
+    // To get more intuitive results, apply a heuristic.
+    let mut top_slider_end = earliest_end;
+    // TODO: why is this needed
+    if top_slider_end < hunk.start - 1 {
+        top_slider_end = hunk.start - 1;
+    }
+    if hunk.end > top_slider_end + MAX_SLIDING {
+        top_slider_end = hunk.end - MAX_SLIDING;
+    }
+    let group_size = hunk.end - hunk.start;
+    let mut best_score = Score::for_range(top_slider_end - group_size..top_slider_end, num_tokens, &indent_at);
+    let mut best_slider_end = top_slider_end;
+    for slider_end in (top_slider_end + 1)..=hunk.end {
+        let score = Score::for_range(slider_end - group_size..slider_end, num_tokens, &indent_at);
+        if score.is_improvement_over(best_score) {
+            best_score = score;
+            best_slider_end = slider_end;
         }
-        best_slider_end
     }
+    best_slider_end
 }
 
 /// Represents the indentation level of a line.
@@ -187,6 +225,8 @@ impl IndentLevel {
 pub struct Indents {
     /// Indentation level of the current line/token.
     indent: IndentLevel,
+    /// Whether the current position is past the last line.
+    at_eof: bool,
     /// Indentation level of the previous non-blank line.
     prev_indent: IndentLevel,
     /// Indentation level of the next non-blank line.
@@ -201,16 +241,15 @@ pub struct Indents {
 const MAX_BLANKS: usize = 20;
 
 impl Indents {
-    fn at_token(tokens: &[Token], token_idx: usize, indent_of_token: impl Fn(Token) -> IndentLevel) -> Indents {
-        let (leading_blank_lines, indent_previous_line) = tokens[..token_idx]
-            .iter()
+    fn at_token(num_tokens: usize, token_idx: usize, indent_at: impl Fn(usize) -> IndentLevel) -> Indents {
+        let (leading_blank_lines, indent_previous_line) = (0..token_idx)
             .rev()
             .enumerate()
-            .find_map(|(i, &token)| {
+            .find_map(|(i, idx)| {
                 if i == MAX_BLANKS {
                     Some((i, IndentLevel(0)))
                 } else {
-                    let level = indent_of_token(token);
+                    let level = indent_at(idx);
                     if level == IndentLevel::BLANK {
                         None
                     } else {
@@ -219,18 +258,17 @@ impl Indents {
                 }
             })
             .unwrap_or((token_idx, IndentLevel::BLANK));
-        let at_eof = token_idx == tokens.len();
+        let at_eof = token_idx == num_tokens;
         let (trailing_blank_lines, indent_next_line) = if at_eof {
             (0, IndentLevel::BLANK)
         } else {
-            tokens[token_idx + 1..]
-                .iter()
+            (token_idx + 1..num_tokens)
                 .enumerate()
-                .find_map(|(i, &token)| {
+                .find_map(|(i, idx)| {
                     if i == MAX_BLANKS {
                         Some((i, IndentLevel(0)))
                     } else {
-                        let level = indent_of_token(token);
+                        let level = indent_at(idx);
                         if level == IndentLevel::BLANK {
                             None
                         } else {
@@ -238,13 +276,16 @@ impl Indents {
                         }
                     }
                 })
-                .unwrap_or((token_idx, IndentLevel::BLANK))
+                .unwrap_or((num_tokens - token_idx - 1, IndentLevel::BLANK))
+        };
+        let indent = if token_idx < num_tokens {
+            indent_at(token_idx)
+        } else {
+            IndentLevel::BLANK
         };
-        let indent = tokens
-            .get(token_idx)
-            .map_or(IndentLevel::BLANK, |&token| indent_of_token(token));
         Indents {
             indent,
+            at_eof,
             prev_indent: indent_previous_line,
             next_indent: indent_next_line,
             leading_blanks: leading_blank_lines as u8,
@@ -257,7 +298,7 @@ impl Indents {
         if self.prev_indent == IndentLevel::BLANK && self.leading_blanks == 0 {
             penalty += START_OF_FILE_PENALTY;
         }
-        if self.next_indent == IndentLevel::BLANK && self.trailing_blanks == 0 {
+        if self.at_eof {
             penalty += END_OF_FILE_PENALTY;
         }
 
@@ -344,9 +385,9 @@ struct Score {
 }
 
 impl Score {
-    fn for_range(range: Range<u32>, tokens: &[Token], indent_of_token: impl Fn(Token) -> IndentLevel) -> Score {
-        Indents::at_token(tokens, range.start as usize, &indent_of_token).score()
-            + Indents::at_token(tokens, range.end as usize, &indent_of_token).score()
+    fn for_range(range: Range<u32>, num_tokens: usize, indent_at: impl Fn(usize) -> IndentLevel) -> Score {
+        Indents::at_token(num_tokens, range.start as usize, &indent_at).score()
+            + Indents::at_token(num_tokens, range.end as usize, &indent_at).score()
     }
 }
 
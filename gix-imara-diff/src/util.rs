@@ -52,8 +52,10 @@ pub fn strip_common_postfix(file1: &mut &[Token], file2: &mut &[Token]) -> u32 {
 }
 
 /// Computes an approximation of the square root using bit operations.
+///
+/// Like `xdl_bogosqrt()` in `git`, the result is rounded up to the next power of two.
 pub fn sqrt(val: usize) -> u32 {
-    let nbits = (usize::BITS - val.leading_zeros()) / 2;
+    let nbits = (usize::BITS - val.leading_zeros()).div_ceil(2);
     1 << nbits
 }
 
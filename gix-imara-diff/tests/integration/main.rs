@@ -9,7 +9,7 @@ use gix_imara_diff::InternedInput;
 use gix_imara_diff::sources::words;
 use gix_imara_diff::{Algorithm, Diff, UnifiedDiffConfig};
 
-const ALL_ALGORITHMS: [Algorithm; 2] = [Algorithm::Histogram, Algorithm::Myers];
+const ALL_ALGORITHMS: [Algorithm; 3] = [Algorithm::Histogram, Algorithm::Myers, Algorithm::Patience];
 
 mod fuzzed {
     use std::time::{Duration, Instant};
@@ -35,7 +35,12 @@ mod fuzzed {
     ) {
         let input = InternedInput::new(before, after);
 
-        for algorithm in [Algorithm::Histogram, Algorithm::Myers, Algorithm::MyersMinimal] {
+        for algorithm in [
+            Algorithm::Histogram,
+            Algorithm::Myers,
+            Algorithm::MyersMinimal,
+            Algorithm::Patience,
+        ] {
             let mut diff = Diff::compute(algorithm, &input);
 
             let _ = diff.count_additions();
@@ -550,6 +550,8 @@ impl State {
     ///
     /// Alternatively, make sure to call [`sort_entries()`][State::sort_entries()] before entry lookup by path to restore
     /// the invariant.
+    ///
+    /// The untracked cache, if present, is [invalidated](extension::UntrackedCache::invalidate_path()) for `path`.
     pub fn dangerously_push_entry(
         &mut self,
         stat: entry::Stat,
@@ -558,6 +560,9 @@ impl State {
         mode: entry::Mode,
         path: &BStr,
     ) {
+        if let Some(untracked) = self.untracked.as_mut() {
+            untracked.invalidate_path(path);
+        }
         let path = {
             let path_start = self.path_backing.len();
             self.path_backing.push_str(path);
@@ -597,6 +602,7 @@ impl State {
     ///
     /// Note that the memory used for the removed entries paths is not freed, as it's append-only, and
     /// that some extensions might refer to paths which are now deleted.
+    /// The untracked cache, if present, is [invalidated](extension::UntrackedCache::invalidate_path()) for each removed path.
     ///
     /// ### Performance
     ///
@@ -605,9 +611,15 @@ impl State {
     pub fn remove_entries(&mut self, mut should_remove: impl FnMut(usize, &BStr, &mut Entry) -> bool) {
         let mut index = 0;
         let paths = &self.path_backing;
+        let untracked = &mut self.untracked;
         self.entries.retain_mut(|e| {
             let path = e.path_in(paths);
             let res = !should_remove(index, path, e);
+            if !res {
+                if let Some(untracked) = untracked.as_mut() {
+                    untracked.invalidate_path(path);
+                }
+            }
             index += 1;
             res
         });
@@ -619,8 +631,13 @@ impl State {
     ///
     /// Note that the memory used for the removed entries paths is not freed, as it's append-only, and
     /// that some extensions might refer to paths which are now deleted.
+    /// The untracked cache, if present, is [invalidated](extension::UntrackedCache::invalidate_path()) for the removed path.
     pub fn remove_entry_at_index(&mut self, index: usize) -> Entry {
-        self.entries.remove(index)
+        let entry = self.entries.remove(index);
+        if let Some(untracked) = self.untracked.as_mut() {
+            untracked.invalidate_path(entry.path_in(&self.path_backing));
+        }
+        entry
     }
 }
 
@@ -650,10 +667,30 @@ impl State {
     pub fn untracked(&self) -> Option<&extension::UntrackedCache> {
         self.untracked.as_ref()
     }
+    /// Obtain the untracked extension for modification.
+    pub fn untracked_mut(&mut self) -> Option<&mut extension::UntrackedCache> {
+        self.untracked.as_mut()
+    }
+    /// Set the untracked extension to `cache`, returning the previous value.
+    pub fn set_untracked(&mut self, cache: Option<extension::UntrackedCache>) -> Option<extension::UntrackedCache> {
+        std::mem::replace(&mut self.untracked, cache)
+    }
+    /// Remove the untracked extension.
+    pub fn remove_untracked(&mut self) -> Option<extension::UntrackedCache> {
+        self.untracked.take()
+    }
     /// Obtain the fsmonitor extension.
     pub fn fs_monitor(&self) -> Option<&extension::FsMonitor> {
         self.fs_monitor.as_ref()
     }
+    /// Set the fsmonitor extension to `fs_monitor`, returning the previous value.
+    pub fn set_fs_monitor(&mut self, fs_monitor: Option<extension::FsMonitor>) -> Option<extension::FsMonitor> {
+        std::mem::replace(&mut self.fs_monitor, fs_monitor)
+    }
+    /// Remove the fsmonitor extension.
+    pub fn remove_fs_monitor(&mut self) -> Option<extension::FsMonitor> {
+        self.fs_monitor.take()
+    }
     /// Return `true` if the end-of-index extension was present when decoding this index.
     pub fn had_end_of_index_marker(&self) -> bool {
         self.end_of_index_at_decode_time
@@ -2,6 +2,8 @@ use crate::{Entry, State, entry};
 
 impl Entry {
     /// Serialize ourselves to `out` with path access via `state`, without padding.
+    ///
+    /// If the [`STRIP_NAME`](entry::Flags::STRIP_NAME) flag is set, the path is omitted.
     pub fn write_to(&self, mut out: impl std::io::Write, state: &State) -> std::io::Result<()> {
         let stat = self.stat;
         out.write_all(&stat.ctime.secs.to_be_bytes())?;
@@ -15,7 +17,11 @@ impl Entry {
         out.write_all(&stat.gid.to_be_bytes())?;
         out.write_all(&stat.size.to_be_bytes())?;
         out.write_all(self.id.as_bytes())?;
-        let path = self.path(state);
+        let path = if self.flags.contains(entry::Flags::STRIP_NAME) {
+            Default::default()
+        } else {
+            self.path(state)
+        };
         let path_len: u16 = if path.len() >= entry::Flags::PATH_LEN.bits() as usize {
             entry::Flags::PATH_LEN.bits() as u16
         } else {
@@ -1,22 +1,30 @@
 use bstr::BString;
 
 use crate::{
-    extension::{FsMonitor, Signature},
+    entry,
+    extension::{FsMonitor, Signature, trimmed_bitmap},
     util::{read_u32, read_u64, split_at_byte_exclusive},
 };
 
-#[derive(Clone)]
-#[expect(
-    dead_code,
-    reason = "for the time when we actually support the git status daemon, this just models its data."
-)]
+/// The token the filesystem monitor uses to know which changes to report.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Token {
-    V1 { nanos_since_1970: u64 },
-    V2 { token: BString },
+    /// The token of the version 1 protocol, a timestamp.
+    V1 {
+        /// The time of the last query in nanoseconds since the UNIX epoch.
+        nanos_since_1970: u64,
+    },
+    /// The token of the version 2 protocol, an opaque string provided by the filesystem monitor.
+    V2 {
+        /// The token as returned by the filesystem monitor during the last query.
+        token: BString,
+    },
 }
 
+/// The signature of the fsmonitor extension.
 pub const SIGNATURE: Signature = *b"FSMN";
 
+/// Decode the fsmonitor extension from `data`, which is expected to exclude the signature and the size.
 pub fn decode(data: &[u8]) -> Option<FsMonitor> {
     let (version, data) = read_u32(data)?;
     let (token, data) = match version {
@@ -49,3 +57,80 @@ pub fn decode(data: &[u8]) -> Option<FsMonitor> {
 
     FsMonitor { token, entry_dirty }.into()
 }
+
+/// Lifecycle
+impl FsMonitor {
+    /// Create a new instance that remembers `token` for the next query of the filesystem monitor.
+    ///
+    /// All entries are considered dirty until it is written, at which point entries with the
+    /// [`FSMONITOR_VALID`](entry::Flags::FSMONITOR_VALID) flag are recorded as clean.
+    pub fn new(token: Token) -> Self {
+        FsMonitor {
+            token,
+            entry_dirty: gix_bitmap::ewah::Vec::from_bits(&[]).expect("empty bitmaps are always valid"),
+        }
+    }
+}
+
+/// Access
+impl FsMonitor {
+    /// Return the token to pass to the filesystem monitor to learn about all changes since it was obtained.
+    pub fn token(&self) -> &Token {
+        &self.token
+    }
+
+    /// Call `f(entry_index)` for each entry that was marked as dirty at the time the extension was written,
+    /// i.e. for each entry that the filesystem monitor can't vouch for.
+    ///
+    /// Returns `None` if the bitmap refers to more than `num_entries` entries, which invalidates it.
+    pub fn for_each_dirty_entry(&self, num_entries: usize, mut f: impl FnMut(usize)) -> Option<()> {
+        if self.entry_dirty.num_bits() > num_entries {
+            return None;
+        }
+        self.entry_dirty.for_each_set_bit(|idx| {
+            f(idx);
+            Some(())
+        })
+    }
+}
+
+impl FsMonitor {
+    /// Serialize this instance to `out`, marking all `entries` without the
+    /// [`FSMONITOR_VALID`](entry::Flags::FSMONITOR_VALID) flag as dirty.
+    ///
+    /// Entries marked for [removal](entry::Flags::REMOVE) are skipped as they are not written either.
+    pub fn write_to(&self, mut out: impl std::io::Write, entries: &[crate::Entry]) -> std::io::Result<()> {
+        let mut buf = Vec::<u8>::new();
+        match &self.token {
+            Token::V1 { nanos_since_1970 } => {
+                buf.extend_from_slice(&1_u32.to_be_bytes());
+                buf.extend_from_slice(&nanos_since_1970.to_be_bytes());
+            }
+            Token::V2 { token } => {
+                buf.extend_from_slice(&2_u32.to_be_bytes());
+                buf.extend_from_slice(token);
+                buf.push(0);
+            }
+        }
+
+        let dirty: Vec<_> = entries
+            .iter()
+            .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+            .map(|e| !e.flags.contains(entry::Flags::FSMONITOR_VALID))
+            .collect();
+        let mut bitmap = Vec::new();
+        trimmed_bitmap(&dirty)
+            .ok_or_else(|| std::io::Error::other("too many entries for the fsmonitor extension"))?
+            .write_to(&mut bitmap)?;
+        buf.extend_from_slice(&(bitmap.len() as u32).to_be_bytes());
+        buf.extend_from_slice(&bitmap);
+
+        out.write_all(&SIGNATURE)?;
+        out.write_all(
+            &u32::try_from(buf.len())
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "fsmonitor extension exceeds 4GB"))?
+                .to_be_bytes(),
+        )?;
+        out.write_all(&buf)
+    }
+}
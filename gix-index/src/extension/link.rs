@@ -66,6 +66,31 @@ pub(crate) fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Result<Link, d
 }
 
 impl Link {
+    /// Serialize this instance to `out`, writing empty bitmaps if there are none.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        let mut buf = Vec::<u8>::new();
+        buf.extend_from_slice(self.shared_index_checksum.as_bytes());
+        match &self.bitmaps {
+            Some(bitmaps) => {
+                bitmaps.delete.write_to(&mut buf)?;
+                bitmaps.replace.write_to(&mut buf)?;
+            }
+            None => {
+                let empty = gix_bitmap::ewah::Vec::from_bits(&[]).expect("empty bitmaps are always valid");
+                empty.write_to(&mut buf)?;
+                empty.write_to(&mut buf)?;
+            }
+        }
+
+        out.write_all(&SIGNATURE)?;
+        out.write_all(
+            &u32::try_from(buf.len())
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "link extension exceeds 4GB"))?
+                .to_be_bytes(),
+        )?;
+        out.write_all(&buf)
+    }
+
     pub(crate) fn dissolve_into(
         self,
         split_index: &mut crate::File,
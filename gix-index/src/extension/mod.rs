@@ -44,7 +44,7 @@ pub struct Link {
 }
 
 /// The extension for untracked files.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct UntrackedCache {
     /// Something identifying the location and machine that this cache is for.
     /// Should the repository be copied to a different machine, the entire cache can immediately be invalidated.
@@ -62,10 +62,6 @@ pub struct UntrackedCache {
 }
 
 /// The extension for keeping state on recent information provided by the filesystem monitor.
-#[expect(
-    dead_code,
-    reason = "for the time when we know what to do with it, for now we just ser-de it"
-)]
 #[derive(Clone)]
 pub struct FsMonitor {
     token: fs_monitor::Token,
@@ -75,7 +71,8 @@ pub struct FsMonitor {
 
 mod iter;
 
-pub(crate) mod fs_monitor;
+///
+pub mod fs_monitor;
 
 ///
 pub mod decode;
@@ -98,3 +95,10 @@ pub mod untracked_cache;
 
 ///
 pub mod sparse;
+
+/// Create a bitmap from `bits` which, like Git, only stores bits up to the last one that is set,
+/// or return `None` if there are too many bits.
+pub(crate) fn trimmed_bitmap(bits: &[bool]) -> Option<gix_bitmap::ewah::Vec> {
+    let len = bits.iter().rposition(|bit| *bit).map_or(0, |pos| pos + 1);
+    gix_bitmap::ewah::Vec::from_bits(&bits[..len])
+}
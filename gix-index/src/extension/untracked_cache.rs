@@ -3,11 +3,33 @@ use gix_hash::ObjectId;
 
 use crate::{
     entry,
-    extension::{Signature, UntrackedCache},
+    extension::{Signature, UntrackedCache, trimmed_bitmap},
     util::{read_u32, split_at_byte_exclusive, var_int},
 };
 
 impl UntrackedCache {
+    /// Create a new instance without any directory information, for the machine and location denoted by `identifier`.
+    ///
+    /// `info_exclude` and `excludes_file` are the stat and object ids of `.git/info/exclude` and `core.excludesFile`
+    /// respectively, `exclude_filename_per_dir` is usually `.gitignore`, and `dir_flags` are the flags used when populating
+    /// the cache, which Git will compare to its own to decide if the cache is usable.
+    pub fn new(
+        identifier: BString,
+        info_exclude: Option<OidStat>,
+        excludes_file: Option<OidStat>,
+        exclude_filename_per_dir: BString,
+        dir_flags: u32,
+    ) -> Self {
+        UntrackedCache {
+            identifier,
+            info_exclude,
+            excludes_file,
+            exclude_filename_per_dir,
+            dir_flags,
+            directories: Vec::new(),
+        }
+    }
+
     /// Something identifying the location and machine that this cache is for.
     pub fn identifier(&self) -> &bstr::BStr {
         self.identifier.as_ref()
@@ -37,10 +59,17 @@ impl UntrackedCache {
     pub fn directories(&self) -> &[Directory] {
         &self.directories
     }
+
+    /// A list of directories and sub-directories, with `directories[0]` being the root, for modification.
+    ///
+    /// Note that only directories reachable from the root through [`Directory::sub_directories`] will be written.
+    pub fn directories_mut(&mut self) -> &mut Vec<Directory> {
+        &mut self.directories
+    }
 }
 
 /// A structure to track filesystem stat information along with an object id, linking a worktree file with what's in our ODB.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct OidStat {
     /// The file system stat information
     pub stat: entry::Stat,
@@ -61,7 +90,7 @@ impl OidStat {
 }
 
 /// A directory with information about its untracked files, and its sub-directories
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Directory {
     /// The directories name, or an empty string if this is the root directory.
     pub name: BString,
@@ -70,11 +99,12 @@ pub struct Directory {
     /// indices for sub-directories similar to this one.
     pub sub_directories: Vec<usize>,
 
-    /// The directories stat data, if available or valid // TODO: or is it the exclude file?
+    /// The directories stat data at the time its untracked entries were recorded, or `None` if the directory
+    /// has to be read again.
     pub stat: Option<entry::Stat>,
     /// The oid of a .gitignore file, if it exists
     pub exclude_file_oid: Option<ObjectId>,
-    /// TODO: figure out what this really does
+    /// If `true`, the directory was only checked for having any untracked files, so its untracked entries may be incomplete.
     pub check_only: bool,
 }
 
@@ -114,6 +144,125 @@ impl Directory {
 /// Only used as an indicator
 pub const SIGNATURE: Signature = *b"UNTR";
 
+impl UntrackedCache {
+    /// Mark all directories leading to `path` as invalid so they are read again when the cache is used next, similar to
+    /// what Git does when adding or removing the index entry at `path`.
+    pub fn invalidate_path(&mut self, path: &bstr::BStr) {
+        use bstr::ByteSlice;
+
+        let mut idx = 0;
+        let mut components = path.split_str("/").peekable();
+        while let Some(dir) = self.directories.get_mut(idx) {
+            dir.stat = None;
+            dir.untracked_entries.clear();
+            let Some(component) = components.next().filter(|_| components.peek().is_some()) else {
+                break;
+            };
+            let directories = &self.directories;
+            let Some(sub_dir) = directories[idx]
+                .sub_directories
+                .iter()
+                .copied()
+                .find(|idx| directories.get(*idx).is_some_and(|d| d.name == component))
+            else {
+                break;
+            };
+            idx = sub_dir;
+        }
+    }
+
+    /// Serialize this instance to `out`, assuming object hashes are of type `object_hash`.
+    ///
+    /// Directories are written depth-first, starting at the root, which is the order in which they are decoded as well.
+    pub fn write_to(&self, mut out: impl std::io::Write, object_hash: gix_hash::Kind) -> std::io::Result<()> {
+        use crate::write::util;
+
+        let null = ObjectId::null(object_hash);
+        let mut buf = Vec::<u8>::new();
+        util::var_int(&mut buf, self.identifier.len() as u64)?;
+        buf.extend_from_slice(&self.identifier);
+        let default_stat = entry::Stat::default();
+        for oid_stat in [&self.info_exclude, &self.excludes_file] {
+            util::stat(&mut buf, oid_stat.as_ref().map_or(&default_stat, |s| &s.stat))?;
+        }
+        buf.extend_from_slice(&self.dir_flags.to_be_bytes());
+        for oid_stat in [&self.info_exclude, &self.excludes_file] {
+            buf.extend_from_slice(oid_stat.as_ref().map_or(&null, |s| &s.id).as_bytes());
+        }
+        buf.extend_from_slice(&self.exclude_filename_per_dir);
+        buf.push(0);
+
+        if self.directories.is_empty() {
+            util::var_int(&mut buf, 0)?;
+        } else {
+            let mut blocks = Vec::new();
+            let mut order = Vec::new();
+            write_directory_block(&self.directories, 0, &mut blocks, &mut order)?;
+            util::var_int(&mut buf, order.len() as u64)?;
+            buf.extend_from_slice(&blocks);
+
+            let directories = || order.iter().map(|idx| &self.directories[*idx]);
+            let valid: Vec<_> = directories().map(|d| d.stat.is_some()).collect();
+            let check_only: Vec<_> = directories().map(|d| d.check_only).collect();
+            let hash_valid: Vec<_> = directories()
+                .map(|d| d.exclude_file_oid.is_some_and(|id| !id.is_null()))
+                .collect();
+            for bits in [&valid, &check_only, &hash_valid] {
+                trimmed_bitmap(bits)
+                    .ok_or_else(|| std::io::Error::other("too many directories in untracked cache"))?
+                    .write_to(&mut buf)?;
+            }
+            for stat in directories().filter_map(|d| d.stat.as_ref()) {
+                util::stat(&mut buf, stat)?;
+            }
+            for id in directories().filter_map(|d| d.exclude_file_oid.filter(|id| !id.is_null())) {
+                buf.extend_from_slice(id.as_bytes());
+            }
+            buf.push(0);
+        }
+
+        out.write_all(&SIGNATURE)?;
+        out.write_all(
+            &u32::try_from(buf.len())
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "untracked extension exceeds 4GB"))?
+                .to_be_bytes(),
+        )?;
+        out.write_all(&buf)
+    }
+}
+
+fn write_directory_block(
+    directories: &[Directory],
+    index: usize,
+    out: &mut Vec<u8>,
+    order: &mut Vec<usize>,
+) -> std::io::Result<()> {
+    use crate::write::util;
+
+    let dir = directories
+        .get(index)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "sub-directory index out of bounds"))?;
+    if order.len() > directories.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "untracked cache directories form a cycle",
+        ));
+    }
+    order.push(index);
+    util::var_int(out, dir.untracked_entries.len() as u64)?;
+    util::var_int(out, dir.sub_directories.len() as u64)?;
+    out.extend_from_slice(&dir.name);
+    out.push(0);
+    for name in &dir.untracked_entries {
+        out.extend_from_slice(name);
+        out.push(0);
+    }
+    for sub_dir_index in &dir.sub_directories {
+        write_directory_block(directories, *sub_dir_index, out, order)?;
+    }
+    Ok(())
+}
+
 /// Decode an untracked cache extension from `data`, assuming object hashes are of type `object_hash`.
 pub fn decode(data: &[u8], object_hash: gix_hash::Kind, alloc_limit_bytes: Option<usize>) -> Option<UntrackedCache> {
     if data.last().is_none_or(|b| *b != 0) {
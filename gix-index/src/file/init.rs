@@ -95,8 +95,14 @@ impl File {
         };
 
         let (state, checksum) = State::from_bytes(&data, mtime, object_hash, options)?;
-        let mut file = File { state, path, checksum };
+        let mut file = File {
+            state,
+            path,
+            checksum,
+            shared_index_checksum: None,
+        };
         if let Some(mut link) = file.link.take() {
+            file.shared_index_checksum = Some(link.shared_index_checksum);
             link.dissolve_into(&mut file, object_hash, skip_hash, options)?;
         }
 
@@ -111,6 +117,7 @@ impl File {
             state,
             path: path.into(),
             checksum: None,
+            shared_index_checksum: None,
         }
     }
 }
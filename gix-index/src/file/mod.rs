@@ -30,6 +30,7 @@ mod impl_ {
                     .debug_struct("File")
                     .field("path", &self.path.display())
                     .field("checksum", &self.checksum)
+                    .field("shared_index_checksum", &self.shared_index_checksum)
                     .field("object_hash", &self.state.object_hash)
                     .field("timestamp", &self.state.timestamp)
                     .field("version", &self.state.version)
@@ -110,6 +111,14 @@ mod access {
         pub fn checksum(&self) -> Option<gix_hash::ObjectId> {
             self.checksum
         }
+
+        /// The checksum of the shared index that stores most of our entries if this index was read from disk as split index
+        /// or [written][File::write()] as such, or `None` if all entries are stored in the index file itself.
+        ///
+        /// Note that the entries of the shared index are always merged into this instance when reading it.
+        pub fn shared_index_checksum(&self) -> Option<gix_hash::ObjectId> {
+            self.shared_index_checksum
+        }
     }
 }
 
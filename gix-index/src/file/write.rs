@@ -1,4 +1,6 @@
-use crate::{File, Version, write};
+use std::{borrow::Cow, io::Write, path::Path};
+
+use crate::{Entry, File, Version, entry, extension, write};
 
 /// The error produced by [`File::write()`].
 #[derive(Debug, thiserror::Error)]
@@ -19,20 +21,32 @@ impl File {
     /// Note that the `tree` (tree-cache) extension is written as-is and is **not** recomputed or
     /// invalidated to match the current entries; see [`File::write()`] for the implications and the
     /// recommended workaround.
+    ///
+    /// Also note that [`split_index`](write::Options::split_index) is ignored as the index is always written as a whole.
     pub fn write_to(
+        &self,
+        out: impl std::io::Write,
+        options: write::Options,
+    ) -> Result<(Version, gix_hash::ObjectId), gix_hash::io::Error> {
+        self.write_entries_to(out, options, self.entries(), None)
+    }
+
+    fn write_entries_to(
         &self,
         mut out: impl std::io::Write,
         options: write::Options,
+        entries: &[Entry],
+        link: Option<&extension::Link>,
     ) -> Result<(Version, gix_hash::ObjectId), gix_hash::io::Error> {
         let _span = gix_features::trace::detail!("gix_index::File::write_to()", skip_hash = options.skip_hash);
         let (version, hash) = if options.skip_hash {
             let out: &mut dyn std::io::Write = &mut out;
-            let version = self.state.write_to(out, options)?;
+            let version = self.state.write_entries_to(out, options.extensions, entries, link)?;
             (version, self.state.object_hash.null())
         } else {
             let mut hasher = gix_hash::io::Write::new(&mut out, self.state.object_hash);
             let out: &mut dyn std::io::Write = &mut hasher;
-            let version = self.state.write_to(out, options)?;
+            let version = self.state.write_entries_to(out, options.extensions, entries, link)?;
             (version, hasher.hash.try_finalize()?)
         };
         out.write_all(hash.as_slice())?;
@@ -63,20 +77,199 @@ impl File {
     /// index.write(gix_index::write::Options::default())?;
     /// ```
     ///
+    /// ### Split index
+    ///
+    /// If [`split_index`](write::Options::split_index) is set, only the entries that differ from the shared index
+    /// are written, and the shared index is placed next to our path as `sharedindex.<checksum>`.
+    /// A new shared index is written if there is none yet, or if too many entries are only stored in the split index.
+    /// Sparse indices are always written as a whole, as are regular indices if [`only_if_split`](write::SplitIndex::only_if_split) is set.
+    ///
     /// [issue #2421]: https://github.com/GitoxideLabs/gitoxide/issues/2421
     pub fn write(&mut self, options: write::Options) -> Result<(), Error> {
         let _span = gix_features::trace::detail!("gix_index::File::write()", path = ?self.path);
+        let (entries, link) = match options.split_index.filter(|split_index| {
+            !self.is_sparse() && (!split_index.only_if_split || self.shared_index_checksum.is_some())
+        }) {
+            Some(split_index) => {
+                let (entries, link) = self.prepare_split_index(split_index)?;
+                (Cow::Owned(entries), Some(link))
+            }
+            None => (Cow::Borrowed(self.entries()), None),
+        };
         let mut lock = std::io::BufWriter::with_capacity(
             64 * 1024,
             gix_lock::File::acquire_to_update_resource(&self.path, gix_lock::acquire::Fail::Immediately, None)?,
         );
-        let (version, digest) = self.write_to(&mut lock, options)?;
+        let (version, digest) = self.write_entries_to(&mut lock, options, &entries, link.as_ref())?;
         match lock.into_inner() {
             Ok(lock) => lock.commit()?,
             Err(err) => return Err(Error::Io(err.into_error().into())),
         };
         self.state.version = version;
         self.checksum = Some(digest);
+        self.shared_index_checksum = link.map(|link| link.shared_index_checksum);
         Ok(())
     }
 }
+
+/// Split index
+impl File {
+    /// Return the entries to write into the split index along with the `link` extension that refers to the shared index,
+    /// which is written if needed.
+    fn prepare_split_index(&self, options: write::SplitIndex) -> Result<(Vec<Entry>, extension::Link), Error> {
+        let dir = self.directory();
+        let shared = self.shared_index_checksum.and_then(|checksum| {
+            File::at(
+                shared_index_path(dir, checksum),
+                self.object_hash,
+                true,
+                crate::decode::Options {
+                    expected_checksum: Some(checksum),
+                    ..Default::default()
+                },
+            )
+            .ok()
+            .map(|shared| (checksum, shared))
+        });
+
+        if let Some((checksum, shared)) = shared {
+            let mut delete = vec![false; shared.entries().len()];
+            let mut replace = vec![false; shared.entries().len()];
+            let (mut replaced, mut added) = (Vec::new(), Vec::new());
+            let mut shared_entries = shared.entries().iter().enumerate().peekable();
+            let mut num_entries = 0;
+            for entry in self
+                .entries()
+                .iter()
+                .filter(|e| !e.flags.contains(entry::Flags::REMOVE))
+            {
+                num_entries += 1;
+                let key = (entry.path(self), entry.stage_raw());
+                while let Some((idx, _)) = shared_entries.next_if(|(_, s)| (s.path(&shared), s.stage_raw()) < key) {
+                    delete[idx] = true;
+                }
+                match shared_entries.next_if(|(_, s)| (s.path(&shared), s.stage_raw()) == key) {
+                    Some((idx, shared_entry)) => {
+                        if !is_same_at_rest(entry, shared_entry) {
+                            replace[idx] = true;
+                            let mut entry = entry.clone();
+                            entry.flags.insert(entry::Flags::STRIP_NAME);
+                            replaced.push(entry);
+                        }
+                    }
+                    None => added.push(entry.clone()),
+                }
+            }
+            for (idx, _) in shared_entries {
+                delete[idx] = true;
+            }
+
+            if !too_many_unshared_entries(options.max_percent_change, added.len(), num_entries) {
+                // Signal that the shared index is still in use to prevent it from expiring.
+                let path = shared_index_path(dir, checksum);
+                filetime::set_file_mtime(path, filetime::FileTime::now()).ok();
+
+                replaced.extend(added);
+                let link = extension::Link {
+                    shared_index_checksum: checksum,
+                    bitmaps: Some(extension::link::Bitmaps {
+                        delete: to_bitmap(&delete)?,
+                        replace: to_bitmap(&replace)?,
+                    }),
+                };
+                return Ok((replaced, link));
+            }
+        }
+
+        let checksum = self.write_shared_index(dir)?;
+        if let Some(expire) = options.shared_index_expire {
+            remove_expired_shared_indices(dir, checksum, expire);
+        }
+        Ok((
+            Vec::new(),
+            extension::Link {
+                shared_index_checksum: checksum,
+                bitmaps: None,
+            },
+        ))
+    }
+
+    /// Write all of our entries without any extension into a new shared index in `dir`, and return its checksum.
+    fn write_shared_index(&self, dir: &Path) -> Result<gix_hash::ObjectId, Error> {
+        let mut buf = Vec::new();
+        let mut hasher = gix_hash::io::Write::new(&mut buf, self.object_hash);
+        self.state
+            .write_entries_to(&mut hasher, write::Extensions::None, self.entries(), None)?;
+        let checksum = hasher.hash.try_finalize().map_err(gix_hash::io::Error::from)?;
+        buf.extend_from_slice(checksum.as_slice());
+
+        let mut lock = gix_lock::File::acquire_to_update_resource(
+            shared_index_path(dir, checksum),
+            gix_lock::acquire::Fail::Immediately,
+            None,
+        )?;
+        lock.write_all(&buf).map_err(gix_hash::io::Error::from)?;
+        lock.commit()?;
+        Ok(checksum)
+    }
+
+    fn directory(&self) -> &Path {
+        match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        }
+    }
+}
+
+fn shared_index_path(dir: &Path, checksum: gix_hash::ObjectId) -> std::path::PathBuf {
+    dir.join(format!("sharedindex.{checksum}"))
+}
+
+/// Return `true` if the entries stored only in the split index make up more than `max_percent_change` of all entries.
+fn too_many_unshared_entries(max_percent_change: u8, unshared: usize, total: usize) -> bool {
+    match max_percent_change {
+        0 => true,
+        100.. => false,
+        max => total as u64 * u64::from(max) < unshared as u64 * 100,
+    }
+}
+
+/// Compare everything that is stored for an entry, except for the path.
+fn is_same_at_rest(a: &Entry, b: &Entry) -> bool {
+    let at_rest = entry::Flags::STAGE_MASK
+        | entry::Flags::EXTENDED
+        | entry::Flags::ASSUME_VALID
+        | entry::Flags::INTENT_TO_ADD
+        | entry::Flags::SKIP_WORKTREE;
+    a.stat == b.stat && a.id == b.id && a.mode == b.mode && (a.flags & at_rest) == (b.flags & at_rest)
+}
+
+fn to_bitmap(bits: &[bool]) -> Result<gix_bitmap::ewah::Vec, gix_hash::io::Error> {
+    extension::trimmed_bitmap(bits)
+        .ok_or_else(|| std::io::Error::other("too many entries for the link extension").into())
+}
+
+/// Delete all shared indices in `dir` other than the one with `current` checksum that weren't used since `expire`.
+/// Failures are ignored as these files are merely a waste of space.
+fn remove_expired_shared_indices(dir: &Path, current: gix_hash::ObjectId, expire: std::time::SystemTime) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let current = format!("sharedindex.{current}");
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !name.starts_with("sharedindex.") || name == current {
+            continue;
+        }
+        if entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|mtime| mtime <= expire)
+        {
+            std::fs::remove_file(entry.path()).ok();
+        }
+    }
+}
@@ -77,6 +77,8 @@ pub struct File {
     pub(crate) path: PathBuf,
     /// The checksum of all bytes prior to the checksum itself.
     pub(crate) checksum: Option<gix_hash::ObjectId>,
+    /// The checksum of the shared index our entries are partially stored in, if this index was read from or written as split index.
+    pub(crate) shared_index_checksum: Option<gix_hash::ObjectId>,
 }
 
 /// The type to use and store paths to all entries.
@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use crate::{State, Version, entry, extension, write::util::CountBytes};
+use crate::{Entry, State, Version, entry, extension, write::util::CountBytes};
 
 /// A way to specify which of the optional extensions to write.
 #[derive(Default, Debug, Copy, Clone)]
@@ -19,6 +19,10 @@ pub enum Extensions {
         tree_cache: bool,
         /// Write the end-of-index-entry extension.
         end_of_index_entry: bool,
+        /// Write the untracked-cache extension, if present.
+        untracked_cache: bool,
+        /// Write the filesystem-monitor extension, if present.
+        fs_monitor: bool,
     },
     /// Write no optional extension at all for what should be the smallest possible index
     None,
@@ -33,9 +37,13 @@ impl Extensions {
             Extensions::Given {
                 tree_cache,
                 end_of_index_entry,
+                untracked_cache,
+                fs_monitor,
             } => match signature {
                 extension::tree::SIGNATURE => tree_cache,
                 extension::end_of_index_entry::SIGNATURE => end_of_index_entry,
+                extension::untracked_cache::SIGNATURE => untracked_cache,
+                extension::fs_monitor::SIGNATURE => fs_monitor,
                 _ => &false,
             }
             .then(|| signature),
@@ -56,6 +64,43 @@ pub struct Options {
     /// via [`File::write()`](crate::File::write()) and [`File::write_to()`](crate::File::write_to()).
     /// Note that
     pub skip_hash: bool,
+    /// If `Some`, write the index as split index which stores only the changes compared to a shared index,
+    /// and rewrite the shared index as needed.
+    ///
+    /// This value is typically controlled by `core.splitIndex` and is only respected when the index is written via
+    /// [`File::write()`](crate::File::write()), which needs to know where to place the shared index.
+    /// If `None`, the index is written as a whole, which also turns a split index back into a regular one.
+    pub split_index: Option<SplitIndex>,
+}
+
+/// Options to control how the split index is written by [`File::write()`](crate::File::write()).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitIndex {
+    /// The percentage of entries that may be stored only in the split index before all entries are written into a new shared index.
+    ///
+    /// With `0`, a new shared index is written each time, and with `100` the shared index is never rewritten once it exists.
+    /// This value is typically controlled by `splitIndex.maxPercentChange` and defaults to `20`.
+    pub max_percent_change: u8,
+    /// Shared index files in the same directory as the index that weren't used since this time will be deleted when a
+    /// new shared index is written, or never if `None`.
+    ///
+    /// This value is typically controlled by `splitIndex.sharedIndexExpire` and defaults to two weeks ago.
+    pub shared_index_expire: Option<std::time::SystemTime>,
+    /// If `true`, only an index that is already split is written as split index, while a regular one stays regular.
+    ///
+    /// This is the behaviour if `core.splitIndex` isn't set, and defaults to `false`.
+    pub only_if_split: bool,
+}
+
+impl Default for SplitIndex {
+    fn default() -> Self {
+        SplitIndex {
+            max_percent_change: 20,
+            shared_index_expire: std::time::SystemTime::now()
+                .checked_sub(std::time::Duration::from_secs(14 * 24 * 60 * 60)),
+            only_if_split: false,
+        }
+    }
 }
 
 impl State {
@@ -67,22 +112,31 @@ impl State {
     pub fn write_to(
         &self,
         out: impl std::io::Write,
-        Options {
-            extensions,
-            skip_hash: _,
-        }: Options,
+        Options { extensions, .. }: Options,
     ) -> Result<Version, gix_hash::io::Error> {
         let _span = gix_features::trace::detail!("gix_index::State::write()");
+        self.write_entries_to(out, extensions, self.entries(), None)
+    }
+
+    /// Write `entries` instead of our own entries, along with our extensions and the `link` extension if it is set.
+    ///
+    /// Entries with the [`STRIP_NAME`](entry::Flags::STRIP_NAME) flag are written without path, as is needed for
+    /// entries that replace the ones in a shared index.
+    pub(crate) fn write_entries_to(
+        &self,
+        out: impl std::io::Write,
+        extensions: Extensions,
+        entries_to_write: &[Entry],
+        link: Option<&extension::Link>,
+    ) -> Result<Version, gix_hash::io::Error> {
         let version = self.detect_required_version();
 
         let mut write = CountBytes::new(out);
-        let num_entries: u32 = self
-            .entries()
+        let num_entries: u32 = entries_to_write
             .len()
             .try_into()
             .expect("definitely not 4billion entries");
-        let removed_entries: u32 = self
-            .entries()
+        let removed_entries: u32 = entries_to_write
             .iter()
             .filter(|e| e.flags.contains(entry::Flags::REMOVE))
             .count()
@@ -90,8 +144,8 @@ impl State {
             .expect("definitely not too many entries");
 
         let offset_to_entries = header(&mut write, version, num_entries - removed_entries)?;
-        let offset_to_extensions = entries(&mut write, self, offset_to_entries)?;
-        let (extension_toc, out) = self.write_extensions(write, offset_to_extensions, extensions)?;
+        let offset_to_extensions = entries(&mut write, self, entries_to_write, offset_to_entries)?;
+        let (extension_toc, out) = self.write_extensions(write, offset_to_extensions, extensions, link)?;
 
         if num_entries > 0
             && extensions
@@ -110,17 +164,35 @@ impl State {
         mut write: CountBytes<T>,
         offset_to_extensions: u32,
         extensions: Extensions,
+        link: Option<&extension::Link>,
     ) -> std::io::Result<(Vec<(extension::Signature, u32)>, T)>
     where
         T: std::io::Write,
     {
         type WriteExtFn<'a> = &'a dyn Fn(&mut dyn std::io::Write) -> Option<std::io::Result<extension::Signature>>;
         let extensions: &[WriteExtFn<'_>] = &[
+            &|write| link.map(|link| link.write_to(write).map(|_| extension::link::SIGNATURE)),
             &|write| {
                 extensions
                     .should_write(extension::tree::SIGNATURE)
                     .and_then(|signature| self.tree().map(|tree| tree.write_to(write).map(|_| signature)))
             },
+            &|write| {
+                extensions
+                    .should_write(extension::untracked_cache::SIGNATURE)
+                    .and_then(|signature| {
+                        self.untracked()
+                            .map(|untracked| untracked.write_to(write, self.object_hash).map(|_| signature))
+                    })
+            },
+            &|write| {
+                extensions
+                    .should_write(extension::fs_monitor::SIGNATURE)
+                    .and_then(|signature| {
+                        self.fs_monitor()
+                            .map(|fs_monitor| fs_monitor.write_to(write, self.entries()).map(|_| signature))
+                    })
+            },
             &|write| {
                 self.is_sparse()
                     .then(|| extension::sparse::write_to(write).map(|_| extension::sparse::SIGNATURE))
@@ -168,8 +240,13 @@ fn header<T: std::io::Write>(
     Ok(out.count)
 }
 
-fn entries<T: std::io::Write>(out: &mut CountBytes<T>, state: &State, header_size: u32) -> Result<u32, std::io::Error> {
-    for entry in state.entries() {
+fn entries<T: std::io::Write>(
+    out: &mut CountBytes<T>,
+    state: &State,
+    entries: &[Entry],
+    header_size: u32,
+) -> Result<u32, std::io::Error> {
+    for entry in entries {
         if entry.flags.contains(entry::Flags::REMOVE) {
             continue;
         }
@@ -186,7 +263,7 @@ fn entries<T: std::io::Write>(out: &mut CountBytes<T>, state: &State, header_siz
     Ok(out.count)
 }
 
-mod util {
+pub(crate) mod util {
     pub struct CountBytes<T> {
         pub count: u32,
         pub inner: T,
@@ -218,4 +295,39 @@ mod util {
             self.inner.flush()
         }
     }
+
+    /// Write `n` as variable-length integer in the format understood by [`var_int()`](crate::util::var_int()).
+    pub fn var_int(out: &mut dyn std::io::Write, mut n: u64) -> std::io::Result<()> {
+        let mut buf = [0u8; 10];
+        let mut pos = buf.len() - 1;
+        buf[pos] = n as u8 & 0b0111_1111;
+        loop {
+            n >>= 7;
+            if n == 0 {
+                break;
+            }
+            n -= 1;
+            pos -= 1;
+            buf[pos] = 0b1000_0000 | (n as u8 & 0b0111_1111);
+        }
+        out.write_all(&buf[pos..])
+    }
+
+    /// Write `stat` in its 36 byte on-disk representation, as used by extensions.
+    pub fn stat(out: &mut dyn std::io::Write, stat: &crate::entry::Stat) -> std::io::Result<()> {
+        for field in [
+            stat.ctime.secs,
+            stat.ctime.nsecs,
+            stat.mtime.secs,
+            stat.mtime.nsecs,
+            stat.dev,
+            stat.ino,
+            stat.uid,
+            stat.gid,
+            stat.size,
+        ] {
+            out.write_all(&field.to_be_bytes())?;
+        }
+        Ok(())
+    }
 }
@@ -295,6 +295,45 @@ fn remove_entry_at_index() {
     assert_eq!(file.entries().len(), 0);
 }
 
+#[test]
+fn removing_entries_invalidates_the_untracked_cache() -> crate::Result {
+    let path = crate::fixture_index_path_needs_archive("untracked_cache_nested");
+    let mut file = gix_index::File::at(path, gix_testtools::object_hash(), false, Default::default())?;
+    let is_valid = |file: &gix_index::File| -> Vec<bool> {
+        file.untracked()
+            .expect("present")
+            .directories()
+            .iter()
+            .map(|d| d.stat().is_some())
+            .collect()
+    };
+    assert_eq!(is_valid(&file), [true; 6]);
+
+    file.remove_entries(|_, path, _| path == "tracked-root-one");
+    assert_eq!(
+        is_valid(&file),
+        [false, true, true, true, true, true],
+        "only the root contains the removed entry"
+    );
+
+    let idx = file
+        .entry_index_by_path("tracked-dir-with-ignore/tracked-file".into())
+        .expect("present");
+    file.remove_entry_at_index(idx);
+    assert_eq!(
+        is_valid(&file),
+        [false, false, true, true, true, true],
+        "all directories leading to the removed entry are invalidated"
+    );
+    assert!(
+        file.untracked().expect("present").directories()[1]
+            .untracked_entries()
+            .is_empty(),
+        "untracked entries of invalid directories are cleared, just like Git does"
+    );
+    Ok(())
+}
+
 #[test]
 fn sort_entries() {
     let mut file = Fixture::Generated("v4_more_files_IEOT").open();
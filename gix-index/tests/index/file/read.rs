@@ -163,6 +163,7 @@ fn v2_with_multiple_entries_without_eoie_ext() {
             checksum: Some(
                 Sha1(43bcf12743f506ab5fefaf13f8f5a7eed3d747fe),
             ),
+            shared_index_checksum: None,
             object_hash: [redacted],
             timestamp: FileTime { ... },
             version: V2,
@@ -294,6 +295,7 @@ fn untr_extension_empty() {
             checksum: Some(
                 Oid(1),
             ),
+            shared_index_checksum: None,
             object_hash: [redacted],
             timestamp: FileTime { ... },
             version: V2,
@@ -336,6 +338,7 @@ fn untr_extension_populated() {
             checksum: Some(
                 Oid(1),
             ),
+            shared_index_checksum: None,
             object_hash: [redacted],
             timestamp: FileTime { ... },
             version: V2,
@@ -440,6 +443,7 @@ fn untr_extension_nested() {
             checksum: Some(
                 Oid(1),
             ),
+            shared_index_checksum: None,
             object_hash: [redacted],
             timestamp: FileTime { ... },
             version: V2,
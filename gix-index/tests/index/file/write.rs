@@ -15,6 +15,8 @@ fn roundtrips() -> crate::Result {
             options_with(write::Extensions::Given {
                 tree_cache: true,
                 end_of_index_entry: true,
+                untracked_cache: false,
+                fs_monitor: false,
             }),
         ),
         (Generated("v2_empty"), only_tree_ext()),
@@ -58,6 +60,7 @@ fn skip_hash() -> crate::Result {
     expected.write(Options {
         extensions: Default::default(),
         skip_hash: false,
+        split_index: None,
     })?;
 
     let actual = gix_index::File::at(
@@ -75,6 +78,7 @@ fn skip_hash() -> crate::Result {
     expected.write(Options {
         extensions: Default::default(),
         skip_hash: true,
+        split_index: None,
     })?;
 
     let actual = gix_index::File::at(
@@ -88,6 +92,180 @@ fn skip_hash() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn untracked_cache_roundtrips() -> crate::Result {
+    for name in [
+        "untracked_cache_empty",
+        "untracked_cache_populated",
+        "untracked_cache_nested",
+    ] {
+        let path = crate::fixture_index_path_needs_archive(name);
+        let expected = gix_index::File::at(&path, gix_testtools::object_hash(), false, Default::default())?;
+        let expected_bytes = std::fs::read(&path)?;
+        let mut out_bytes = Vec::new();
+        expected.write_to(&mut out_bytes, options_with(write::Extensions::All))?;
+
+        let (actual, _) = State::from_bytes(
+            &out_bytes,
+            FileTime::now(),
+            gix_testtools::object_hash(),
+            Default::default(),
+        )?;
+        assert!(
+            expected.untracked().is_some(),
+            "{name}: fixture must have an untracked cache"
+        );
+        assert_eq!(
+            actual.untracked(),
+            expected.untracked(),
+            "{name}: untracked cache mismatch"
+        );
+        assert_eq!(
+            extension_bytes(&out_bytes, extension::untracked_cache::SIGNATURE),
+            extension_bytes(&expected_bytes, extension::untracked_cache::SIGNATURE),
+            "{name}: the serialized extension is byte-for-byte the same as the one written by Git"
+        );
+
+        let mut out_bytes = Vec::new();
+        expected.write_to(&mut out_bytes, options_with(write::Extensions::None))?;
+        let (actual, _) = State::from_bytes(
+            &out_bytes,
+            FileTime::now(),
+            gix_testtools::object_hash(),
+            Default::default(),
+        )?;
+        assert!(
+            actual.untracked().is_none(),
+            "{name}: optional extensions can be skipped"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn fs_monitor_records_entries_without_valid_flag_as_dirty() -> crate::Result {
+    let mut expected = Generated("v2_more_files").open();
+    assert!(expected.entries().len() > 2, "need some entries to mark");
+    let token = extension::fs_monitor::Token::V2 { token: "token".into() };
+    expected.set_fs_monitor(Some(extension::FsMonitor::new(token.clone())));
+    for entry in expected.entries_mut().iter_mut().skip(2) {
+        entry.flags.insert(entry::Flags::FSMONITOR_VALID);
+    }
+
+    let mut out_bytes = Vec::new();
+    expected.write_to(&mut out_bytes, options_with(write::Extensions::All))?;
+    let (actual, _) = State::from_bytes(
+        &out_bytes,
+        FileTime::now(),
+        gix_testtools::object_hash(),
+        Default::default(),
+    )?;
+    let fs_monitor = actual.fs_monitor().expect("extension was written");
+    assert_eq!(fs_monitor.token(), &token);
+    let mut dirty = Vec::new();
+    fs_monitor
+        .for_each_dirty_entry(actual.entries().len(), |idx| dirty.push(idx))
+        .expect("bitmap is valid");
+    assert_eq!(dirty, [0, 1], "only entries without the valid flag are dirty");
+
+    let mut out_bytes = Vec::new();
+    expected.write_to(&mut out_bytes, options_with(write::Extensions::None))?;
+    let (actual, _) = State::from_bytes(
+        &out_bytes,
+        FileTime::now(),
+        gix_testtools::object_hash(),
+        Default::default(),
+    )?;
+    assert!(actual.fs_monitor().is_none(), "optional extensions can be skipped");
+    Ok(())
+}
+
+#[test]
+fn split_index_only_stores_changes_to_the_shared_index() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable(
+        std::path::Path::new("make_index").join("v2_split_vs_regular_index.sh"),
+    )?;
+    let git_dir = tmp.path().join("split").join(".git");
+    let path = git_dir.join("index");
+    let mut index = gix_index::File::at(&path, gix_testtools::object_hash(), false, Default::default())?;
+    let shared = index.shared_index_checksum().expect("fixture uses a split index");
+    let shared_path = |checksum: gix_hash::ObjectId| git_dir.join(format!("sharedindex.{checksum}"));
+    let entries_of = |index: &gix_index::File| -> Vec<_> {
+        index
+            .entries()
+            .iter()
+            .map(|e| (e.path(index).to_owned(), e.id, e.stat))
+            .collect()
+    };
+
+    index.entries_mut()[0].stat.size += 1;
+    let mut split_index = write::SplitIndex {
+        max_percent_change: 100,
+        shared_index_expire: None,
+        only_if_split: true,
+    };
+    let options = |split_index| Options {
+        extensions: Default::default(),
+        skip_hash: false,
+        split_index,
+    };
+    index.write(options(Some(split_index)))?;
+    assert_eq!(index.shared_index_checksum(), Some(shared), "the shared index is kept");
+    let actual = gix_index::File::at(&path, gix_testtools::object_hash(), false, Default::default())?;
+    assert_eq!(
+        entries_of(&actual),
+        entries_of(&index),
+        "entries are merged with the shared index"
+    );
+    assert_eq!(actual.shared_index_checksum(), Some(shared));
+
+    split_index.max_percent_change = 0;
+    index.write(options(Some(split_index)))?;
+    let new_shared = index.shared_index_checksum().expect("still split");
+    assert_ne!(new_shared, shared, "a new shared index was written");
+    assert!(shared_path(new_shared).is_file());
+    assert!(
+        shared_path(shared).is_file(),
+        "the old one doesn't expire without configuration"
+    );
+    let actual = gix_index::File::at(&path, gix_testtools::object_hash(), false, Default::default())?;
+    assert_eq!(entries_of(&actual), entries_of(&index));
+
+    split_index.shared_index_expire = Some(std::time::SystemTime::now() + std::time::Duration::from_secs(60));
+    index.write(options(Some(split_index)))?;
+    assert_eq!(
+        index.shared_index_checksum(),
+        Some(new_shared),
+        "the same entries produce the same shared index"
+    );
+    assert!(
+        shared_path(new_shared).is_file(),
+        "the current shared index never expires"
+    );
+    assert!(!shared_path(shared).exists(), "unused shared indices expire");
+
+    index.write(options(None))?;
+    assert_eq!(index.shared_index_checksum(), None);
+    let actual = gix_index::File::at(&path, gix_testtools::object_hash(), false, Default::default())?;
+    assert_eq!(actual.shared_index_checksum(), None, "the index is now a regular one");
+    assert_eq!(entries_of(&actual), entries_of(&index));
+
+    split_index.only_if_split = true;
+    index.write(options(Some(split_index)))?;
+    assert_eq!(
+        index.shared_index_checksum(),
+        None,
+        "regular indices stay regular unless splitting is requested"
+    );
+    Ok(())
+}
+
+fn extension_bytes(index: &[u8], signature: extension::Signature) -> Option<&[u8]> {
+    let start = index.windows(signature.len()).position(|w| w == signature)?;
+    let size = u32::from_be_bytes(index[start + 4..start + 8].try_into().unwrap()) as usize;
+    Some(&index[start..start + 8 + size])
+}
+
 #[test]
 fn roundtrips_sparse_index() -> crate::Result {
     // NOTE: I initially tried putting these fixtures into the main roundtrip test above,
@@ -169,10 +347,14 @@ fn state_comparisons_with_various_extension_configurations() {
             options_with(write::Extensions::Given {
                 tree_cache: true,
                 end_of_index_entry: false,
+                untracked_cache: false,
+                fs_monitor: false,
             }),
             options_with(write::Extensions::Given {
                 tree_cache: false,
                 end_of_index_entry: true,
+                untracked_cache: true,
+                fs_monitor: false,
             }),
         ] {
             let expected = fixture.open();
@@ -314,8 +496,11 @@ fn only_tree_ext() -> Options {
         extensions: write::Extensions::Given {
             end_of_index_entry: false,
             tree_cache: true,
+            untracked_cache: false,
+            fs_monitor: false,
         },
         skip_hash: false,
+        split_index: None,
     }
 }
 
@@ -323,5 +508,6 @@ fn options_with(extensions: write::Extensions) -> Options {
     Options {
         extensions,
         skip_hash: false,
+        split_index: None,
     }
 }
@@ -4,7 +4,7 @@ use gix_object::FindExt;
 
 use crate::{
     blob::builtin_driver,
-    commit::{Error, Options},
+    commit::{Error, Options, Strategy},
 };
 
 /// Like [`tree()`](crate::tree()), but it takes only two commits, `our_commit` and `their_commit` to automatically
@@ -24,9 +24,15 @@ use crate::{
 /// the ancestor-label isn't explicitly set so that the merge base label becomes the shortened `id`.
 /// Note that it's a dyn closure only to make it possible to recursively call this function in case of multiple merge-bases.
 ///
+/// `open_submodule(path)` provides the repository of a submodule whose commit was changed on both sides, so it can be
+/// merged as described for [`tree()`](crate::tree()).
+///
 /// `write_object` is used only if it's allowed to merge multiple merge-bases into one, and if there
 /// are multiple merge bases, and to write merged buffers as blobs.
 ///
+/// The [`strategy`](Options::strategy) decides if the trees are merged at all, and if *their* tree has to be shifted
+/// to match a subtree of *our* tree first, which also writes trees to `objects`.
+///
 /// ### Performance
 ///
 /// Note that `objects` *should* have an object cache to greatly accelerate tree-retrieval.
@@ -51,13 +57,53 @@ pub fn commit<'objects>(
     blob_merge: &mut crate::blob::Platform,
     objects: &'objects (impl gix_object::FindObjectOrHeader + gix_object::Write),
     abbreviate_hash: &mut dyn FnMut(&gix_hash::oid) -> String,
+    open_submodule: &mut crate::tree::submodule::Open<'_>,
     options: Options,
 ) -> Result<super::Outcome<'objects>, Error> {
     let merge_bases = gix_revision::merge_base(our_commit, &[their_commit], graph)?;
-    let mut virtual_merge_bases = Vec::new();
     let mut state = gix_diff::tree::State::default();
+    let our_tree_id = objects.find_commit(&our_commit, &mut state.buf1)?.tree();
+    merge_into_tree(
+        our_commit,
+        our_tree_id,
+        their_commit,
+        merge_bases,
+        labels,
+        graph,
+        &mut state,
+        diff_resource_cache,
+        blob_merge,
+        objects,
+        abbreviate_hash,
+        open_submodule,
+        options,
+    )
+}
+
+/// Merge `their_commit` into `our_tree_id`, which is the tree of `our_commit` or a tree that merged it with other commits,
+/// using `merge_bases` that were computed for both sides, and `state` for diffing trees.
+///
+/// All other parameters are the same as for [`commit()`].
+#[expect(clippy::too_many_arguments)]
+pub(super) fn merge_into_tree<'objects>(
+    our_commit: gix_hash::ObjectId,
+    our_tree_id: gix_hash::ObjectId,
+    their_commit: gix_hash::ObjectId,
+    merge_bases: Option<nonempty::NonEmpty<gix_hash::ObjectId>>,
+    labels: builtin_driver::text::Labels<'_>,
+    graph: &mut gix_revwalk::Graph<'_, '_, gix_revwalk::graph::Commit<gix_revision::merge_base::Flags>>,
+    state: &mut gix_diff::tree::State,
+    diff_resource_cache: &mut gix_diff::blob::Platform,
+    blob_merge: &mut crate::blob::Platform,
+    objects: &'objects (impl gix_object::FindObjectOrHeader + gix_object::Write),
+    abbreviate_hash: &mut dyn FnMut(&gix_hash::oid) -> String,
+    open_submodule: &mut crate::tree::submodule::Open<'_>,
+    options: Options,
+) -> Result<super::Outcome<'objects>, Error> {
+    let mut virtual_merge_bases = Vec::new();
+    let mut buf = Vec::new();
     let mut commit_to_tree =
-        |commit_id: gix_hash::ObjectId| objects.find_commit(&commit_id, &mut state.buf1).map(|c| c.tree());
+        |commit_id: gix_hash::ObjectId| objects.find_commit(&commit_id, &mut buf).map(|c| c.tree());
 
     let (merge_base_tree_id, ancestor_name): (_, Cow<'_, str>) = match merge_bases.clone() {
         Some(base_commit) if base_commit.len() == 1 => (
@@ -65,7 +111,7 @@ pub fn commit<'objects>(
             abbreviate_hash(base_commit.first()).into(),
         ),
         Some(base_commits) => {
-            let virtual_base_tree = if options.use_first_merge_base {
+            let virtual_base_tree = if options.use_first_merge_base || options.strategy == Strategy::Ours {
                 commit_to_tree(*base_commits.first())?
             } else {
                 let mut base_commits: Vec<_> = base_commits.into();
@@ -80,6 +126,7 @@ pub fn commit<'objects>(
                     blob_merge,
                     objects,
                     abbreviate_hash,
+                    open_submodule,
                     options.tree_merge.clone(),
                 )?;
                 virtual_merge_bases = Vec::from(out.virtual_merge_bases);
@@ -104,19 +151,34 @@ pub fn commit<'objects>(
         labels.ancestor = Some(ancestor_name.as_ref().into());
     }
 
-    let our_tree_id = objects.find_commit(&our_commit, &mut state.buf1)?.tree();
-    let their_tree_id = objects.find_commit(&their_commit, &mut state.buf1)?.tree();
+    let mut their_tree_id = commit_to_tree(their_commit)?;
+    let mut base_tree_id = merge_base_tree_id;
+    let subtree_prefix = match options.strategy {
+        Strategy::Ours => {
+            // Merging our tree with itself yields it unchanged, without doing any work.
+            their_tree_id = our_tree_id;
+            base_tree_id = our_tree_id;
+            None
+        }
+        Strategy::Ort => options.subtree_prefix.as_ref().map(|prefix| Some(prefix.as_ref())),
+        Strategy::Subtree => Some(options.subtree_prefix.as_ref().map(AsRef::as_ref)),
+    };
+    if let Some(prefix) = subtree_prefix {
+        their_tree_id = crate::commit::shift_tree(&our_tree_id, &their_tree_id, prefix, objects)?;
+        base_tree_id = crate::commit::shift_tree(&our_tree_id, &base_tree_id, prefix, objects)?;
+    }
 
     let outcome = crate::tree(
-        &merge_base_tree_id,
+        &base_tree_id,
         &our_tree_id,
         &their_tree_id,
         labels,
         objects,
         |buf| objects.write_buf(gix_object::Kind::Blob, buf),
-        &mut state,
+        state,
         diff_resource_cache,
         blob_merge,
+        open_submodule,
         options.tree_merge,
     )?;
 
@@ -17,6 +17,31 @@ pub enum Error {
     },
     #[error("Could not find ancestor, our or their commit to extract tree from")]
     FindCommit(#[from] gix_object::find::existing_object::Error),
+    #[error(transparent)]
+    SubtreeShift(#[from] subtree::Error),
+    #[error("Failed to write the merged tree of one of the commits of an octopus merge")]
+    WriteTree(gix_object::write::Error),
+}
+
+/// The strategy to use when merging commits, similar to `git merge --strategy`.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Strategy {
+    /// Merge the trees of both sides structurally, and merge multiple merge-bases into a virtual one.
+    ///
+    /// This is what Git does with its default `ort` strategy.
+    #[default]
+    Ort,
+    /// Ignore all changes of *their* side and keep *our* tree, like `git merge -s ours` does.
+    ///
+    /// Note that this is different from resolving conflicts with *ours*, which is done with
+    /// [`tree::Options::tree_conflicts`](crate::tree::Options::tree_conflicts) and the
+    /// [blob-merge options](crate::blob::platform::merge::Options).
+    Ours,
+    /// Like [`Ort`](Self::Ort), but shift *their* tree and the merge-base tree so that they match a subtree of *our* tree,
+    /// or the other way around, like `git merge -s subtree` does.
+    ///
+    /// The subtree is detected automatically unless [`subtree_prefix`](Options::subtree_prefix) is set.
+    Subtree,
 }
 
 /// A way to configure [`commit()`](crate::commit()).
@@ -30,6 +55,15 @@ pub struct Options {
     // TODO: test
     #[doc(alias = "no_recursive", alias = "git2")]
     pub use_first_merge_base: bool,
+    /// The strategy to use for merging the commits.
+    pub strategy: Strategy,
+    /// If `Some(prefix)`, shift *their* tree and the merge-base tree by the `/`-separated path `prefix`, which is a subtree of
+    /// *our* tree, or vice versa, to make them match.
+    ///
+    /// This is what `git merge -Xsubtree=<prefix>` does, and it also works with the [`Ort`](Strategy::Ort) strategy.
+    /// See [`shift_tree()`] for details.
+    #[doc(alias = "subtree_shift")]
+    pub subtree_prefix: Option<bstr::BString>,
 }
 
 /// The result of [`commit()`](crate::commit()).
@@ -55,6 +89,14 @@ pub struct Outcome<'a> {
 
 pub(super) mod function;
 
+///
+pub mod octopus;
+pub use octopus::function::octopus;
+
+///
+pub mod subtree;
+pub use subtree::function::shift_tree;
+
 ///
 pub mod virtual_merge_base;
 pub use virtual_merge_base::function::virtual_merge_base;
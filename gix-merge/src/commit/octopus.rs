@@ -0,0 +1,157 @@
+/// The outcome produced by [`commit::octopus()`](crate::commit::octopus()).
+#[derive(Clone)]
+pub struct Outcome<'a> {
+    /// The outcome of the last tree-merge, whose tree contains the changes of all merged commits.
+    ///
+    /// If no tree-merge was needed as all commits could be fast-forwarded to or were already merged,
+    /// it's an unchanged tree without any conflicts.
+    pub tree_merge: crate::tree::Outcome<'a>,
+    /// The commits that were merged in the order they were merged in, including the ones that were fast-forwarded to.
+    ///
+    /// If the last one has unresolved conflicts, these are the conflicts in [`tree_merge`](Self::tree_merge).
+    pub merged_commits: Vec<gix_hash::ObjectId>,
+    /// The commits that were already reachable from *our* commit or one of the commits merged before,
+    /// and which thus didn't have to be merged.
+    pub up_to_date_commits: Vec<gix_hash::ObjectId>,
+    /// The commits that weren't merged as the merge of the last of the [merged commits](Self::merged_commits)
+    /// had unresolved conflicts, which makes the merge fail.
+    ///
+    /// Note that the merge of the last commit may still have unresolved conflicts even if this is empty.
+    pub unmerged_commits: Vec<gix_hash::ObjectId>,
+    /// A list of virtual commits that were created to merge multiple merge-bases into one for any of the merges.
+    /// As they are not reachable by anything they will be garbage collected, but knowing them provides options.
+    pub virtual_merge_bases: Vec<gix_hash::ObjectId>,
+}
+
+pub(super) mod function {
+    use gix_object::FindExt;
+
+    use crate::{
+        blob::builtin_driver,
+        commit::{Error, Options, Strategy, function::merge_into_tree},
+    };
+
+    /// Merge all `their_commits` into `our_commit` one after another, like `git merge -s octopus` does, to produce
+    /// a tree for a commit with `our_commit` and all `their_commits` as parents.
+    ///
+    /// Each of `their_commits` is merged with the tree that is the result of all previous merges, using the merge-bases
+    /// between it and `our_commit` as well as all commits merged so far.
+    /// Commits that are already reachable aren't merged, and as long as no actual merge was needed, Git's default
+    /// [strategy](Strategy::Ort) will fast-forward to a commit if it descends from the commit merged before.
+    /// If a merge has unresolved conflicts, the merge stops right there and all following commits are left
+    /// [unmerged](super::Outcome::unmerged_commits).
+    /// Conflicts are considered unresolved according to [`fail_on_conflict`](crate::tree::Options::fail_on_conflict), or
+    /// its default if unset.
+    ///
+    /// `labels` are used for each merge, but if [`other`](builtin_driver::text::Labels::other) is unset, it's set to the
+    /// abbreviated hash of the commit being merged.
+    ///
+    /// All other parameters are the same as for [`commit()`](crate::commit()), and `options` are used for each merge.
+    ///
+    /// ### Deviation
+    ///
+    /// * Git doesn't track renames in octopus merges, and uses only trivial content merges. Here, each merge is like
+    ///   a merge of two commits, which may resolve more conflicts than Git would.
+    /// * Git will refuse to continue after any conflict, even if it was resolved, while here only unresolved conflicts
+    ///   stop the merge.
+    #[expect(clippy::too_many_arguments)]
+    pub fn octopus<'objects>(
+        our_commit: gix_hash::ObjectId,
+        their_commits: &[gix_hash::ObjectId],
+        labels: builtin_driver::text::Labels<'_>,
+        graph: &mut gix_revwalk::Graph<'_, '_, gix_revwalk::graph::Commit<gix_revision::merge_base::Flags>>,
+        diff_resource_cache: &mut gix_diff::blob::Platform,
+        blob_merge: &mut crate::blob::Platform,
+        objects: &'objects (impl gix_object::FindObjectOrHeader + gix_object::Write),
+        abbreviate_hash: &mut dyn FnMut(&gix_hash::oid) -> String,
+        open_submodule: &mut crate::tree::submodule::Open<'_>,
+        options: Options,
+    ) -> Result<super::Outcome<'objects>, Error> {
+        let mut state = gix_diff::tree::State::default();
+        let mut tree_id = objects.find_commit(&our_commit, &mut state.buf1)?.tree();
+        let unresolved = options.tree_merge.fail_on_conflict.unwrap_or_default();
+        let allow_fast_forward = options.strategy == Strategy::Ort;
+
+        // All commits that the merged tree is made of, with the first one being the one to fast-forward from.
+        let mut merged = vec![our_commit];
+        let mut tree_merge = None::<crate::tree::Outcome<'objects>>;
+        let (mut merged_commits, mut up_to_date_commits, mut unmerged_commits) = (Vec::new(), Vec::new(), Vec::new());
+        let mut virtual_merge_bases = Vec::new();
+        for (idx, &their_commit) in their_commits.iter().enumerate() {
+            if tree_merge
+                .as_ref()
+                .is_some_and(|outcome| outcome.has_unresolved_conflicts(unresolved))
+            {
+                unmerged_commits = their_commits[idx..].to_vec();
+                break;
+            }
+            let merge_bases = gix_revision::merge_base(their_commit, &merged, graph)?;
+            if merge_bases.as_ref().is_some_and(|bases| bases.contains(&their_commit)) {
+                up_to_date_commits.push(their_commit);
+                continue;
+            }
+            if allow_fast_forward
+                && tree_merge.is_none()
+                && merge_bases
+                    .as_ref()
+                    .is_some_and(|bases| bases.len() == 1 && *bases.first() == merged[0])
+            {
+                merged = vec![their_commit];
+                tree_id = objects.find_commit(&their_commit, &mut state.buf1)?.tree();
+                merged_commits.push(their_commit);
+                continue;
+            }
+
+            if let Some(mut previous) = tree_merge.take() {
+                tree_id = previous
+                    .tree
+                    .write(|tree| objects.write(tree))
+                    .map_err(Error::WriteTree)?;
+            }
+            let their_name = labels.other.is_none().then(|| abbreviate_hash(&their_commit));
+            let labels = builtin_driver::text::Labels {
+                other: labels.other.or(their_name.as_deref().map(Into::into)),
+                ..labels
+            };
+            let outcome = merge_into_tree(
+                our_commit,
+                tree_id,
+                their_commit,
+                merge_bases,
+                labels,
+                graph,
+                &mut state,
+                diff_resource_cache,
+                blob_merge,
+                objects,
+                abbreviate_hash,
+                open_submodule,
+                options.clone(),
+            )?;
+            merged.push(their_commit);
+            merged_commits.push(their_commit);
+            virtual_merge_bases.extend(outcome.virtual_merge_bases);
+            tree_merge = Some(outcome.tree_merge);
+        }
+
+        let tree_merge = match tree_merge {
+            Some(outcome) => outcome,
+            None => crate::tree::Outcome {
+                tree: gix_object::tree::Editor::new(
+                    objects.find_tree(&tree_id, &mut state.buf1)?.to_owned(),
+                    objects,
+                    tree_id.kind(),
+                ),
+                conflicts: Vec::new(),
+                failed_on_first_unresolved_conflict: false,
+            },
+        };
+        Ok(super::Outcome {
+            tree_merge,
+            merged_commits,
+            up_to_date_commits,
+            unmerged_commits,
+            virtual_merge_bases,
+        })
+    }
+}
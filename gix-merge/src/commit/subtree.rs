@@ -0,0 +1,236 @@
+/// The error returned by [`shift_tree()`](crate::commit::shift_tree()).
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not find a tree to match it against another one")]
+    FindTree(#[from] gix_object::find::existing_object::Error),
+    #[error("Could not find a tree to look up the subtree to shift a tree by")]
+    FindTreeIter(#[from] gix_object::find::existing_iter::Error),
+    #[error("Could not look up the subtree to shift a tree by")]
+    LookupEntry(#[from] gix_object::find::Error),
+    #[error("Could not place their tree into a subtree of our tree")]
+    EditTree(#[from] gix_object::tree::editor::Error),
+    #[error("Failed to write a tree that contains their shifted tree")]
+    WriteTree(gix_object::write::Error),
+}
+
+pub(super) mod function {
+    use bstr::{BStr, BString, ByteSlice, ByteVec};
+    use gix_hash::{ObjectId, oid};
+    use gix_object::{FindExt, tree::EntryMode};
+
+    use super::Error;
+
+    /// Return the id of `their_tree` shifted so that it matches `our_tree`, like Git does for
+    /// [subtree merges](crate::commit::Strategy::Subtree).
+    ///
+    /// If `prefix` is `None`, find the subtree of `our_tree` that is most similar to `their_tree`, or the subtree of
+    /// `their_tree` that is most similar to `our_tree`, looking at most two levels deep.
+    /// Otherwise, `prefix` is the `/`-separated path of the subtree to consider. If it's only a subtree of `our_tree`, `their_tree`
+    /// is shifted down, and if it's only a subtree of `their_tree`, `their_tree` is shifted up. If it's a subtree of both, the
+    /// shift is only performed if it makes the trees more similar.
+    ///
+    /// If `their_tree` has to be shifted down, the result is `our_tree` with its subtree replaced by `their_tree`, which is written
+    /// to `objects`. If it has to be shifted up, the result is the subtree of `their_tree`, and otherwise it's `their_tree` itself.
+    pub fn shift_tree(
+        our_tree: &oid,
+        their_tree: &oid,
+        prefix: Option<&BStr>,
+        objects: &(impl gix_object::FindObjectOrHeader + gix_object::Write),
+    ) -> Result<ObjectId, Error> {
+        match prefix {
+            None => shift_tree_auto(our_tree, their_tree, objects),
+            Some(prefix) => shift_tree_by(our_tree, their_tree, prefix, objects),
+        }
+    }
+
+    fn shift_tree_auto(
+        our_tree: &oid,
+        their_tree: &oid,
+        objects: &(impl gix_object::FindObjectOrHeader + gix_object::Write),
+    ) -> Result<ObjectId, Error> {
+        const RECURSE_LIMIT: usize = 2;
+        let score = score_trees(our_tree, their_tree, objects)?;
+        let mut add = (score, BString::default());
+        match_trees(our_tree, their_tree, &mut add, "".into(), RECURSE_LIMIT, objects)?;
+        let mut del = (score, BString::default());
+        match_trees(their_tree, our_tree, &mut del, "".into(), RECURSE_LIMIT, objects)?;
+
+        if add.0 < del.0 {
+            if del.1.is_empty() {
+                return Ok(their_tree.to_owned());
+            }
+            return Ok(lookup_tree(their_tree, del.1.as_ref(), objects)?
+                .expect("the best match was found in their tree")
+                .to_owned());
+        }
+        if add.1.is_empty() {
+            return Ok(their_tree.to_owned());
+        }
+        splice_tree(our_tree, add.1.as_ref(), their_tree, objects)
+    }
+
+    fn shift_tree_by(
+        our_tree: &oid,
+        their_tree: &oid,
+        prefix: &BStr,
+        objects: &(impl gix_object::FindObjectOrHeader + gix_object::Write),
+    ) -> Result<ObjectId, Error> {
+        let our_subtree = lookup_tree(our_tree, prefix, objects)?;
+        let their_subtree = lookup_tree(their_tree, prefix, objects)?;
+        let (shift_down, shift_up) = match (our_subtree, their_subtree) {
+            (Some(our_subtree), Some(their_subtree)) => {
+                let mut best_score = score_trees(our_tree, their_tree, objects)?;
+                let mut candidate = (false, false);
+                let score = score_trees(&our_subtree, their_tree, objects)?;
+                if score > best_score {
+                    candidate = (true, false);
+                    best_score = score;
+                }
+                if score_trees(&their_subtree, our_tree, objects)? > best_score {
+                    candidate = (false, true);
+                }
+                candidate
+            }
+            (our_subtree, their_subtree) => (our_subtree.is_some(), their_subtree.is_some()),
+        };
+
+        if shift_down {
+            splice_tree(our_tree, prefix, their_tree, objects)
+        } else if shift_up {
+            Ok(their_subtree.expect("only set if their subtree exists"))
+        } else {
+            Ok(their_tree.to_owned())
+        }
+    }
+
+    /// Find the subtree of `one` which is most similar to `two`, and update `best` with its score and path if it's better.
+    fn match_trees(
+        one: &oid,
+        two: &oid,
+        best: &mut (i64, BString),
+        base: &BStr,
+        recurse_limit: usize,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<(), Error> {
+        let mut buf = Vec::new();
+        let subtrees: Vec<_> = objects
+            .find_tree(one, &mut buf)?
+            .entries
+            .into_iter()
+            .filter(|entry| entry.mode.is_tree())
+            .map(|entry| (entry.filename.to_owned(), entry.oid.to_owned()))
+            .collect();
+        for (name, id) in subtrees {
+            let mut path = base.to_owned();
+            path.push_str(&name);
+            let score = score_trees(&id, two, objects)?;
+            if best.0 < score {
+                *best = (score, path.clone());
+            }
+            if recurse_limit != 0 {
+                path.push_byte(b'/');
+                match_trees(&id, two, best, path.as_ref(), recurse_limit - 1, objects)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute how similar the trees `one` and `two` are, with the highest score for the most similar trees.
+    fn score_trees(one: &oid, two: &oid, objects: &impl gix_object::FindObjectOrHeader) -> Result<i64, Error> {
+        let (mut buf_one, mut buf_two) = (Vec::new(), Vec::new());
+        let one = objects.find_tree(one, &mut buf_one)?;
+        let two = objects.find_tree(two, &mut buf_two)?;
+        let (mut one, mut two) = (one.entries.iter().peekable(), two.entries.iter().peekable());
+
+        let mut score = 0;
+        loop {
+            score += match (one.peek(), two.peek()) {
+                (Some(a), Some(b)) => match a.cmp(b) {
+                    std::cmp::Ordering::Less => score_missing(one.next().expect("peeked").mode),
+                    std::cmp::Ordering::Greater => score_missing(two.next().expect("peeked").mode),
+                    std::cmp::Ordering::Equal => {
+                        let (a, b) = (one.next().expect("peeked"), two.next().expect("peeked"));
+                        if a.oid == b.oid {
+                            score_matches(a.mode, b.mode)
+                        } else {
+                            score_differs(a.mode, b.mode)
+                        }
+                    }
+                },
+                (Some(_), None) => score_missing(one.next().expect("peeked").mode),
+                (None, Some(_)) => score_missing(two.next().expect("peeked").mode),
+                (None, None) => break,
+            };
+        }
+        Ok(score)
+    }
+
+    fn score_missing(mode: EntryMode) -> i64 {
+        if mode.is_tree() {
+            -1000
+        } else if mode.is_link() {
+            -500
+        } else {
+            -50
+        }
+    }
+
+    fn score_differs(a: EntryMode, b: EntryMode) -> i64 {
+        if a.is_tree() != b.is_tree() {
+            -100
+        } else if a.is_link() != b.is_link() {
+            -50
+        } else {
+            -5
+        }
+    }
+
+    fn score_matches(a: EntryMode, b: EntryMode) -> i64 {
+        if a.is_tree() != b.is_tree() {
+            -100
+        } else if a.is_link() != b.is_link() {
+            -50
+        } else if a.is_tree() {
+            1000
+        } else if a.is_link() {
+            500
+        } else {
+            250
+        }
+    }
+
+    /// Return the id of the tree at `path` within `tree`, or `None` if there is no tree at `path`.
+    fn lookup_tree(
+        tree: &oid,
+        path: &BStr,
+        objects: &impl gix_object::FindObjectOrHeader,
+    ) -> Result<Option<ObjectId>, Error> {
+        let mut buf = Vec::new();
+        let mut lookup_buf = Vec::new();
+        let entry = objects.find_tree_iter(tree, &mut buf)?.lookup_entry(
+            objects,
+            &mut lookup_buf,
+            path.split_str("/").filter(|component| !component.is_empty()),
+        )?;
+        Ok(entry.filter(|entry| entry.mode.is_tree()).map(|entry| entry.oid))
+    }
+
+    /// Replace the tree at `path` in `tree` with `subtree`, and return the id of the written tree.
+    fn splice_tree(
+        tree: &oid,
+        path: &BStr,
+        subtree: &oid,
+        objects: &(impl gix_object::FindObjectOrHeader + gix_object::Write),
+    ) -> Result<ObjectId, Error> {
+        let mut buf = Vec::new();
+        let root = objects.find_tree(tree, &mut buf)?.to_owned();
+        let mut editor = gix_object::tree::Editor::new(root, objects, tree.kind());
+        editor.upsert(
+            path.split_str("/").filter(|component| !component.is_empty()),
+            gix_object::tree::EntryKind::Tree,
+            subtree.to_owned(),
+        )?;
+        editor.write(|tree| objects.write(tree)).map_err(Error::WriteTree)
+    }
+}
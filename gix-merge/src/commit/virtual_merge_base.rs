@@ -42,8 +42,8 @@ pub(super) mod function {
     /// Note that `first_commit` and `second_commit` are expected to have been popped off `others`, so `first_commit`
     /// was the last provided merge-base of function that provides multiple merge-bases for a pair of commits.
     ///
-    /// The parameters `graph`, `diff_resource_cache`, `blob_merge`, `objects`, `abbreviate_hash` and `options` are passed
-    /// directly to [`tree()`](crate::tree()) for merging the trees of two merge-bases at a time.
+    /// The parameters `graph`, `diff_resource_cache`, `blob_merge`, `objects`, `abbreviate_hash`, `open_submodule` and `options`
+    /// are passed directly to [`tree()`](crate::tree()) for merging the trees of two merge-bases at a time.
     /// Note that most of `options` are overwritten to match the requirements of a merge-base merge.
     #[expect(clippy::too_many_arguments)]
     pub fn virtual_merge_base<'objects>(
@@ -55,6 +55,7 @@ pub(super) mod function {
         blob_merge: &mut crate::blob::Platform,
         objects: &'objects (impl gix_object::FindObjectOrHeader + gix_object::Write),
         abbreviate_hash: &mut dyn FnMut(&gix_hash::oid) -> String,
+        open_submodule: &mut crate::tree::submodule::Open<'_>,
         mut options: crate::tree::Options,
     ) -> Result<super::Outcome, crate::commit::Error> {
         let mut merged_commit_id = first_commit;
@@ -84,10 +85,13 @@ pub(super) mod function {
                 blob_merge,
                 objects,
                 abbreviate_hash,
+                open_submodule,
                 crate::commit::Options {
                     allow_missing_merge_base: false,
                     tree_merge: options.clone(),
                     use_first_merge_base: false,
+                    strategy: crate::commit::Strategy::Ort,
+                    subtree_prefix: None,
                 },
             )?;
             // This shouldn't happen, but if for some buggy reason it does, we rather bail.
@@ -1,6 +1,6 @@
 use std::{borrow::Cow, convert::Infallible};
 
-use bstr::{BString, ByteSlice};
+use bstr::{BStr, BString, ByteSlice};
 use gix_diff::{tree::recorder::Location, tree_with_rewrites::Change};
 use gix_hash::ObjectId;
 use gix_object::{
@@ -11,7 +11,7 @@ use gix_object::{
 use crate::tree::{
     Conflict, ConflictIndexEntry, ConflictIndexEntryPathHint, ConflictMapping,
     ConflictMapping::{Original, Swapped},
-    ContentMerge, Error, Options, Outcome, Resolution, ResolutionFailure, ResolveWith,
+    ContentMerge, Error, Options, Outcome, Resolution, ResolutionFailure, ResolveWith, submodule,
     utils::{
         ChangeList, ChangeListRef, PossibleConflict, TrackedChange, TreeNodes, apply_change, perform_blob_merge,
         possibly_rewritten_location, rewrite_location_with_renamed_directory, to_components, track,
@@ -30,6 +30,8 @@ use crate::tree::{
 /// * `diff_resource_cache` is used for similarity checks.
 /// * `blob_merge` is a pre-configured platform to merge any content.
 ///     - Note that it shouldn't be allowed to read from the worktree, given that this is a tree-merge.
+/// * `open_submodule(path)` provides the repository of the submodule at `path` if both sides changed its commit, to
+///   fast-forward it like Git does. If it returns `None`, such changes are [unresolved](submodule::Failure::NotCheckedOut).
 /// * `options` are used to affect how the merge is performed.
 ///
 /// ### Unbiased (Ours x Theirs == Theirs x Ours)
@@ -65,6 +67,7 @@ pub fn tree<'objects, E>(
     diff_state: &mut gix_diff::tree::State,
     diff_resource_cache: &mut gix_diff::blob::Platform,
     blob_merge: &mut crate::blob::Platform,
+    open_submodule: &mut submodule::Open<'_>,
     options: Options,
 ) -> Result<Outcome<'objects>, Error>
 where
@@ -500,6 +503,72 @@ where
                                     }
                                 }
                             }
+                            (
+                                Change::Modification {
+                                    location,
+                                    previous_id,
+                                    previous_entry_mode,
+                                    entry_mode: our_mode,
+                                    id: our_id,
+                                    ..
+                                },
+                                Change::Modification {
+                                    entry_mode: their_mode,
+                                    id: their_id,
+                                    ..
+                                },
+                            ) if our_mode.is_commit() && their_mode.is_commit() && our_id != their_id => {
+                                let resolution = merge_submodule(
+                                    location.as_bstr(),
+                                    previous_entry_mode.is_commit().then_some(*previous_id),
+                                    (*our_id, *their_id),
+                                    outer_side,
+                                    &mut editor,
+                                    open_submodule,
+                                    &options,
+                                )?;
+                                if should_fail_on_conflict(Conflict::maybe_resolved(
+                                    resolution,
+                                    (ours, theirs, Original, outer_side),
+                                    [
+                                        index_entry(previous_entry_mode, previous_id),
+                                        index_entry(our_mode, our_id),
+                                        index_entry(their_mode, their_id),
+                                    ],
+                                )) {
+                                    break 'outer;
+                                }
+                            }
+                            (
+                                Change::Addition {
+                                    location,
+                                    entry_mode: our_mode,
+                                    id: our_id,
+                                    ..
+                                },
+                                Change::Addition {
+                                    entry_mode: their_mode,
+                                    id: their_id,
+                                    ..
+                                },
+                            ) if our_mode.is_commit() && their_mode.is_commit() && our_id != their_id => {
+                                let resolution = merge_submodule(
+                                    location.as_bstr(),
+                                    None,
+                                    (*our_id, *their_id),
+                                    outer_side,
+                                    &mut editor,
+                                    open_submodule,
+                                    &options,
+                                )?;
+                                if should_fail_on_conflict(Conflict::maybe_resolved(
+                                    resolution,
+                                    (ours, theirs, Original, outer_side),
+                                    [None, index_entry(our_mode, our_id), index_entry(their_mode, their_id)],
+                                )) {
+                                    break 'outer;
+                                }
+                            }
                             (
                                 Change::Modification {
                                     location,
@@ -1283,6 +1352,48 @@ fn apply_our_resolution(
     Ok(apply_change(editor, ours, None)?)
 }
 
+/// Merge the commits `our_id` and `their_id` of the submodule at `location` like Git does, using `base_id` as merge-base
+/// if there is one, and update `editor` with the result.
+///
+/// Note that the sides may be swapped according to `outer_side`.
+fn merge_submodule(
+    location: &BStr,
+    base_id: Option<ObjectId>,
+    (our_id, their_id): (ObjectId, ObjectId),
+    outer_side: ConflictMapping,
+    editor: &mut tree::Editor<'_>,
+    open_submodule: &mut submodule::Open<'_>,
+    options: &Options,
+) -> Result<Result<Resolution, ResolutionFailure>, Error> {
+    let (our_id, their_id) = match outer_side {
+        Original => (our_id, their_id),
+        Swapped => (their_id, our_id),
+    };
+    // Merges of merge-bases don't need suggestions, and keep the merge-base on conflict.
+    let is_virtual_merge_base = options.marker_size_multiplier > 0;
+    Ok(
+        match submodule::function::merge(
+            location,
+            base_id,
+            our_id,
+            their_id,
+            !is_virtual_merge_base,
+            open_submodule,
+        )? {
+            Ok(commit) => {
+                editor.upsert(to_components(location), EntryKind::Commit, commit)?;
+                Ok(Resolution::OursModifiedTheirsModifiedThenSubmoduleFastForward { commit })
+            }
+            Err(reason) => {
+                if !is_virtual_merge_base && options.tree_conflicts != Some(ResolveWith::Ancestor) {
+                    editor.upsert(to_components(location), EntryKind::Commit, our_id)?;
+                }
+                Err(ResolutionFailure::OursModifiedTheirsModifiedSubmodule { reason })
+            }
+        },
+    )
+}
+
 fn involves_submodule(a: &EntryMode, b: &EntryMode) -> bool {
     a.is_commit() || b.is_commit()
 }
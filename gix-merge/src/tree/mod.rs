@@ -23,6 +23,8 @@ pub enum Error {
     WriteBlobToOdb(Box<dyn std::error::Error + Send + Sync + 'static>),
     #[error("The merge was performed, but the binary merge result couldn't be selected as it wasn't found")]
     MergeResourceNotFound,
+    #[error(transparent)]
+    Submodule(#[from] submodule::Error),
 }
 
 /// The outcome produced by [`tree()`](crate::tree()).
@@ -252,6 +254,7 @@ impl Conflict {
                         Resolution::OursModifiedTheirsModifiedThenBlobContentMerge { merged_blob } => {
                             content_merge_unresolved(merged_blob)
                         }
+                        Resolution::OursModifiedTheirsModifiedThenSubmoduleFastForward { .. } => false,
                     },
                     Err(_failure) => true,
                 }
@@ -301,6 +304,7 @@ impl Conflict {
                     renamed_unique_path_to_modified_blob: _,
                 }
                 | ResolutionFailure::OursAddedTheirsAddedTypeMismatch { .. }
+                | ResolutionFailure::OursModifiedTheirsModifiedSubmodule { .. }
                 | ResolutionFailure::OursDeletedTheirsRenamed => None,
             }
         }
@@ -310,6 +314,7 @@ impl Conflict {
                 Resolution::SourceLocationAffectedByRename { .. } => None,
                 Resolution::OursModifiedTheirsRenamedAndChangedThenRename { merged_blob, .. } => *merged_blob,
                 Resolution::OursModifiedTheirsModifiedThenBlobContentMerge { merged_blob } => Some(*merged_blob),
+                Resolution::OursModifiedTheirsModifiedThenSubmoduleFastForward { .. } => None,
             },
             Err(failure) => failure_merged_blob(failure),
         }
@@ -349,6 +354,12 @@ pub enum Resolution {
         /// The outcome of the content merge.
         merged_blob: ContentMerge,
     },
+    /// *ours* and *theirs* changed the commit of the same submodule, and one of these commits contains the other one,
+    /// so the submodule was fast-forwarded to it.
+    OursModifiedTheirsModifiedThenSubmoduleFastForward {
+        /// The commit of the side that contains the other one, which the submodule now points to.
+        commit: gix_hash::ObjectId,
+    },
     /// This is a resolution failure was forcefully turned into a usable resolution, i.e. [making a choice](ResolveWith)
     /// is turned into a valid resolution.
     Forced(ResolutionFailure),
@@ -392,6 +403,12 @@ pub enum ResolutionFailure {
     OursDeletedTheirsRenamed,
     /// *ours* was modified and *theirs* was deleted. We keep the modified one and ignore the deletion.
     OursModifiedTheirsDeleted,
+    /// *ours* and *theirs* changed or added the same submodule with different commits, which couldn't be merged.
+    /// We keep *ours* in the tree, like Git does.
+    OursModifiedTheirsModifiedSubmodule {
+        /// Why the commits couldn't be merged, which may include suggestions for a merge commit in the submodule.
+        reason: submodule::Failure,
+    },
     /// *ours* and *theirs* are in an untested state so it can't be handled yet, and is considered a conflict
     /// without adding our *or* their side to the resulting tree.
     Unknown,
@@ -455,6 +472,8 @@ pub enum ResolveWith {
 }
 
 pub(super) mod function;
+///
+pub mod submodule;
 mod utils;
 ///
 pub mod apply_index_entries {
@@ -521,7 +540,8 @@ pub mod apply_index_entries {
                             final_location.as_ref().map(|p| p.as_bstr()),
                             conflict.changes_in_resolution().1.location(),
                         ),
-                        Resolution::OursModifiedTheirsModifiedThenBlobContentMerge { .. } => {
+                        Resolution::OursModifiedTheirsModifiedThenBlobContentMerge { .. }
+                        | Resolution::OursModifiedTheirsModifiedThenSubmoduleFastForward { .. } => {
                             (None, conflict.ours.location())
                         }
                     },
@@ -535,6 +555,7 @@ pub mod apply_index_entries {
                         ResolutionFailure::OursModifiedTheirsRenamedTypeMismatch
                         | ResolutionFailure::OursDeletedTheirsRenamed
                         | ResolutionFailure::OursModifiedTheirsDeleted
+                        | ResolutionFailure::OursModifiedTheirsModifiedSubmodule { .. }
                         | ResolutionFailure::Unknown => (None, conflict.ours.location()),
                         ResolutionFailure::OursModifiedTheirsDirectoryThenOursRenamed {
                             renamed_unique_path_to_modified_blob,
@@ -0,0 +1,230 @@
+use bstr::{BStr, BString};
+use gix_hash::ObjectId;
+
+/// Access to the repository of a submodule, to merge the commits that both sides of a merge changed its gitlink to.
+pub trait Repository {
+    /// Return the objects of the submodule, which should contain the commits of both sides and of their merge-base.
+    fn objects(&self) -> &dyn gix_object::Find;
+
+    /// Return the commits that all references of the submodule point to, including `HEAD`, similar to `git rev-list --all`.
+    ///
+    /// They are used to find merge commits that contain the commits of both sides, and are only needed if neither side
+    /// contains the other.
+    fn reference_tips(&mut self) -> Result<Vec<ObjectId>, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
+/// A function to open the repository of the submodule at the given repository-relative path,
+/// or return `None` if it's not available, usually because it isn't checked out.
+pub type Open<'a> =
+    dyn FnMut(&BStr) -> Result<Option<Box<dyn Repository>>, Box<dyn std::error::Error + Send + Sync + 'static>> + 'a;
+
+/// The reason why the commits of a submodule couldn't be merged, as part of a
+/// [resolution failure](super::ResolutionFailure::OursModifiedTheirsModifiedSubmodule).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// The repository of the submodule isn't available, usually because it isn't checked out.
+    NotCheckedOut,
+    /// There is no common ancestor to merge the commits with, as both sides added the submodule,
+    /// or at least one side turned another kind of entry into a submodule.
+    NoMergeBase,
+    /// At least one of the commits of *ours*, *theirs* or their merge-base isn't present in the submodule repository.
+    CommitsNotPresent,
+    /// *ours* or *theirs* doesn't contain the commit of the merge-base, so the submodule may have been rewound.
+    CommitsDontFollowMergeBase,
+    /// Neither *ours* nor *theirs* contains the other one, so both would have to be merged within the submodule.
+    Diverged {
+        /// The merge commits in the submodule which contain both sides, without the ones that contain another one of them.
+        /// These are possible resolutions that should be confirmed by the user.
+        ///
+        /// It's empty if there is no such merge, or if there was no search as merge-bases were merged.
+        possible_merges: Vec<ObjectId>,
+    },
+}
+
+/// The error returned when merging the commits of submodules as part of a [tree-merge](crate::tree()).
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not open the repository of the submodule at '{path}'")]
+    Open {
+        path: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error("Could not obtain the commits that the references of the submodule at '{path}' point to")]
+    ReferenceTips {
+        path: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error("Could not find a commit in the submodule at '{path}'")]
+    FindCommit {
+        path: BString,
+        source: gix_object::find::Error,
+    },
+    #[error(transparent)]
+    MergeBase(#[from] gix_revision::merge_base::Error),
+}
+
+pub(super) mod function {
+    use std::collections::{HashMap, HashSet};
+
+    use bstr::BStr;
+    use gix_hash::{ObjectId, oid};
+
+    use super::{Error, Failure, Open};
+
+    /// Merge the submodule at `path`, whose gitlinks were changed from `base` to `ours` and `theirs`, like Git does.
+    ///
+    /// Return the commit to fast-forward to if one side contains the other, or the reason why this isn't possible.
+    /// `base` is `None` if there is no gitlink to use as merge-base.
+    /// If `find_merges` is `true`, merges that contain both sides are searched in the submodule if both sides diverged.
+    pub(in crate::tree) fn merge(
+        path: &BStr,
+        base: Option<ObjectId>,
+        ours: ObjectId,
+        theirs: ObjectId,
+        find_merges: bool,
+        open_submodule: &mut Open<'_>,
+    ) -> Result<Result<ObjectId, Failure>, Error> {
+        let Some(mut repo) = open_submodule(path).map_err(|source| Error::Open {
+            path: path.to_owned(),
+            source,
+        })?
+        else {
+            return Ok(Err(Failure::NotCheckedOut));
+        };
+        let Some(base) = base else {
+            return Ok(Err(Failure::NoMergeBase));
+        };
+
+        {
+            let objects = Objects(repo.objects());
+            let mut buf = Vec::new();
+            for id in [base, ours, theirs] {
+                if parents_of(&objects, &id, &mut buf, path)?.is_none() {
+                    return Ok(Err(Failure::CommitsNotPresent));
+                }
+            }
+
+            let mut graph = gix_revwalk::Graph::new(objects, None);
+            if !is_ancestor(base, ours, &mut graph)? || !is_ancestor(base, theirs, &mut graph)? {
+                return Ok(Err(Failure::CommitsDontFollowMergeBase));
+            }
+            if is_ancestor(ours, theirs, &mut graph)? {
+                return Ok(Ok(theirs));
+            }
+            if is_ancestor(theirs, ours, &mut graph)? {
+                return Ok(Ok(ours));
+            }
+        }
+
+        let possible_merges = if find_merges {
+            let tips = repo.reference_tips().map_err(|source| Error::ReferenceTips {
+                path: path.to_owned(),
+                source,
+            })?;
+            find_first_merges(&Objects(repo.objects()), tips, ours, theirs, path)?
+        } else {
+            Vec::new()
+        };
+        Ok(Err(Failure::Diverged { possible_merges }))
+    }
+
+    /// Find all merge commits reachable from `tips` which contain both `ours` and `theirs`, like
+    /// `git rev-list --merges --ancestry-path ^ours <tips>` does, and keep only those that don't contain any other of them.
+    ///
+    /// Note that `ours` and `theirs` must not contain each other.
+    fn find_first_merges(
+        objects: &Objects<'_>,
+        tips: Vec<ObjectId>,
+        ours: ObjectId,
+        theirs: ObjectId,
+        path: &BStr,
+    ) -> Result<Vec<ObjectId>, Error> {
+        // Learn the parents of all commits reachable from the tips, but not from `ours`, as only descendants of `ours` matter.
+        let mut buf = Vec::new();
+        let mut commits = Vec::new();
+        let mut parents_by_commit = HashMap::new();
+        let mut queue = tips;
+        while let Some(id) = queue.pop() {
+            if id == ours || parents_by_commit.contains_key(&id) {
+                continue;
+            }
+            let Some(parents) = parents_of(objects, &id, &mut buf, path)? else {
+                continue;
+            };
+            queue.extend(parents.iter().rev().copied());
+            commits.push(id);
+            parents_by_commit.insert(id, parents);
+        }
+
+        let mut children_by_commit = HashMap::<ObjectId, Vec<ObjectId>>::new();
+        for (child, parents) in &parents_by_commit {
+            for parent in parents {
+                children_by_commit.entry(*parent).or_default().push(*child);
+            }
+        }
+        let descendants_of = |id: ObjectId| {
+            let mut out = HashSet::new();
+            let mut queue = vec![id];
+            while let Some(id) = queue.pop() {
+                for child in children_by_commit.get(&id).into_iter().flatten() {
+                    if out.insert(*child) {
+                        queue.push(*child);
+                    }
+                }
+            }
+            out
+        };
+
+        let (contains_ours, contains_theirs) = (descendants_of(ours), descendants_of(theirs));
+        let merges: Vec<_> = commits
+            .into_iter()
+            .filter(|id| parents_by_commit[id].len() > 1 && contains_ours.contains(id) && contains_theirs.contains(id))
+            .collect();
+        let contains_other_merge: HashSet<_> = merges.iter().flat_map(|merge| descendants_of(*merge)).collect();
+        Ok(merges
+            .into_iter()
+            .filter(|merge| !contains_other_merge.contains(merge))
+            .collect())
+    }
+
+    /// Return `true` if `ancestor` is reachable from `commit`.
+    fn is_ancestor(
+        ancestor: ObjectId,
+        commit: ObjectId,
+        graph: &mut gix_revwalk::Graph<'_, '_, gix_revwalk::graph::Commit<gix_revision::merge_base::Flags>>,
+    ) -> Result<bool, Error> {
+        Ok(gix_revision::merge_base(ancestor, &[commit], graph)?.is_some_and(|bases| bases.contains(&ancestor)))
+    }
+
+    /// Return the parents of the commit `id`, or `None` if it doesn't exist or isn't a commit.
+    fn parents_of(
+        objects: &Objects<'_>,
+        id: &oid,
+        buf: &mut Vec<u8>,
+        path: &BStr,
+    ) -> Result<Option<Vec<ObjectId>>, Error> {
+        use gix_object::Find;
+        Ok(objects
+            .try_find(id, buf)
+            .map_err(|source| Error::FindCommit {
+                path: path.to_owned(),
+                source,
+            })?
+            .and_then(gix_object::Data::try_into_commit_iter)
+            .map(|commit| commit.parent_ids().collect()))
+    }
+
+    /// Make the objects of a submodule usable where sized types are needed.
+    struct Objects<'a>(&'a dyn gix_object::Find);
+
+    impl gix_object::Find for Objects<'_> {
+        fn try_find<'a>(
+            &self,
+            id: &oid,
+            buffer: &'a mut Vec<u8>,
+        ) -> Result<Option<gix_object::Data<'a>>, gix_object::find::Error> {
+            self.0.try_find(id, buffer)
+        }
+    }
+}
@@ -0,0 +1,116 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of commit merges with merge strategies and strategy options, writing the tree of each merge
+# to `.git/<case>.tree`. Merges that fail due to conflicts don't have a baseline.
+
+function tick () {
+  if test -z "${tick+set}"
+  then
+    tick=1112911993
+  else
+    tick=$(($tick + 60))
+  fi
+  GIT_COMMITTER_DATE="$tick -0700"
+  GIT_AUTHOR_DATE="$tick -0700"
+  export GIT_COMMITTER_DATE GIT_AUTHOR_DATE
+}
+
+function commit() {
+  tick
+  git add -A
+  git commit -q -m "${1:?the commit message}"
+}
+
+# Replace the line with the given number in the given file with the given content.
+function replace_line() {
+  local file=${1:?the file to change}
+  sed "${2:?the line number}s/.*/${3:?the new content}/" "$file" >"$file.tmp"
+  mv "$file.tmp" "$file"
+}
+
+# Merge the given commits with the given options into the current branch, and record the merged tree as `$name`.
+function baseline() {
+  local name=${1:?the name of the case}
+  shift
+  tick
+  git merge -q --no-edit "$@"
+  git rev-parse HEAD^{tree} > ".git/$name.tree"
+}
+
+git init -q
+git checkout -q -b main
+
+seq 10 >a
+seq 11 20 >b
+seq 21 30 >c
+commit base
+git tag base
+
+git checkout -q -b topic1 main
+replace_line a 2 topic1
+commit topic1
+
+git checkout -q -b topic2 main
+replace_line b 5 topic2
+commit topic2
+
+git checkout -q -b topic3 main
+echo "new" >d
+commit topic3
+
+git checkout -q -b conflicting main
+replace_line a 2 conflicting
+commit conflicting
+
+git checkout -q main
+replace_line c 8 main
+commit main
+
+git checkout -q -b octopus main
+baseline octopus topic1 topic2 topic3
+
+git checkout -q -b octopus-up-to-date main
+baseline octopus-up-to-date topic1 base topic2
+
+git checkout -q -b octopus-fast-forward base
+baseline octopus-fast-forward topic1 topic2 topic3
+
+git checkout -q -b ours main
+baseline ours -s ours topic1 topic2
+
+git checkout -q -b x-ours topic1
+baseline x-ours -Xours conflicting
+
+git checkout -q -b x-theirs topic1
+baseline x-theirs -Xtheirs conflicting
+
+git checkout -q --orphan library
+git rm -q -rf .
+seq 10 >lib.c
+mkdir include
+seq 5 >include/lib.h
+commit library
+
+git checkout -q -b project main
+git merge -q -s ours --no-commit --allow-unrelated-histories library
+git read-tree --prefix=lib/ -u library
+commit "add library"
+
+git checkout -q library
+replace_line lib.c 3 library
+commit "library update"
+
+git checkout -q project
+replace_line lib/include/lib.h 3 project
+replace_line a 9 project
+commit "project update"
+
+git checkout -q -b subtree project
+baseline subtree -s subtree library
+
+git checkout -q -b subtree-prefix project
+baseline subtree-prefix -Xsubtree=lib library
+
+git checkout -q -b subtree-up library
+baseline subtree-up -s subtree project
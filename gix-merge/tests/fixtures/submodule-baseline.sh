@@ -0,0 +1,143 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of merges of commits that change the submodule `sub` on both sides, writing the tree of each merge
+# to `.git/<case>.tree`, and what Git reports about it to `.git/<case>.messages`.
+# The commits of the submodule are written to `.git/sub-<name>` so they can be referred to, along with
+# `.git/sub-tips` with the commits that its references point to.
+# `added` is a clone of the submodule that both sides of a merge add, while `unregistered` isn't a known submodule.
+
+function tick () {
+  if test -z "${tick+set}"
+  then
+    tick=1112911993
+  else
+    tick=$(($tick + 60))
+  fi
+  GIT_COMMITTER_DATE="$tick -0700"
+  GIT_AUTHOR_DATE="$tick -0700"
+  export GIT_COMMITTER_DATE GIT_AUTHOR_DATE
+}
+
+function sub_commit() {
+  local name=${1:?the name of the commit}
+  tick
+  echo "$name" >"sub/$name.t"
+  git -C sub add "$name.t"
+  git -C sub commit -q -m "$name"
+  git -C sub tag "$name"
+  git -C sub rev-parse HEAD > ".git/sub-$name"
+}
+
+function sub_merge() {
+  local name=${1:?the name of the merge commit}
+  shift
+  tick
+  git -C sub merge -q --no-ff --no-edit -m "$name" "$@"
+  git -C sub rev-parse HEAD > ".git/sub-$name"
+}
+
+# Commit the superproject with the submodule pointing to the given commit.
+function set_sub() {
+  local id=${1:?the commit the submodule should point to}
+  local path=${2:-sub}
+  tick
+  git update-index --add --cacheinfo "160000,$id,$path"
+  git commit -q -m "$path at $id"
+}
+
+# Merge `theirs` into `ours` like `git merge` would, and record the result as `$name`.
+function baseline() {
+  local name=${1:?the name of the case}
+  local ours=${2:?our side}
+  local theirs=${3:?their side}
+  local out
+  out=$(git merge-tree --write-tree --no-messages "$ours" "$theirs" || test $? = 1)
+  echo "$out" | head -n 1 > ".git/$name.tree"
+  git merge-tree --write-tree --name-only "$ours" "$theirs" > ".git/$name.messages" || test $? = 1
+}
+
+git init -q
+git checkout -q -b main
+
+git init -q sub
+git -C sub checkout -q -b main
+sub_commit c0
+git -C sub checkout -q -b a
+sub_commit a1
+sub_commit a2
+git -C sub checkout -q -b b c0
+sub_commit b1
+git -C sub checkout -q -b merged a
+git -C sub reset -q --hard a1
+sub_merge m b
+git -C sub checkout -q -b merged-again merged
+git -C sub checkout -q -b d c0
+sub_commit d1
+git -C sub checkout -q merged-again
+sub_merge m2 d
+git -C sub checkout -q -b c c0
+sub_commit c1
+git -C sub checkout -q main
+git -C sub for-each-ref --format='%(objectname)' > .git/sub-tips
+git -C sub rev-parse HEAD >> .git/sub-tips
+
+cat <<EOF >.gitmodules
+[submodule "sub"]
+	path = sub
+	url = ./sub
+[submodule "added"]
+	path = added
+	url = ./sub
+EOF
+git add .gitmodules
+set_sub "$(cat .git/sub-c0)"
+git tag base
+
+git checkout -q -b ours-a1 base
+set_sub "$(cat .git/sub-a1)"
+
+git checkout -q -b theirs-a2 base
+set_sub "$(cat .git/sub-a2)"
+
+git checkout -q -b theirs-b1 base
+set_sub "$(cat .git/sub-b1)"
+
+git checkout -q -b theirs-c1 base
+set_sub "$(cat .git/sub-c1)"
+
+git checkout -q -b theirs-missing base
+set_sub "$(git rev-parse HEAD:.gitmodules)"
+
+git checkout -q -b base2 base
+set_sub "$(cat .git/sub-a1)"
+
+git checkout -q -b ours-a2 base2
+set_sub "$(cat .git/sub-a2)"
+
+git checkout -q -b theirs-rewound base2
+set_sub "$(cat .git/sub-c0)"
+
+git checkout -q -b ours-added base
+set_sub "$(cat .git/sub-a1)" added
+
+git checkout -q -b theirs-added base
+set_sub "$(cat .git/sub-b1)" added
+
+git checkout -q -b ours-unregistered base
+set_sub "$(cat .git/sub-a1)" unregistered
+
+git checkout -q -b theirs-unregistered base
+set_sub "$(cat .git/sub-b1)" unregistered
+
+git checkout -q main
+git clone -q sub added
+
+baseline fast-forward ours-a1 theirs-a2
+baseline fast-forward-reversed theirs-a2 ours-a1
+baseline diverged-with-merge ours-a1 theirs-b1
+baseline diverged ours-a1 theirs-c1
+baseline commits-not-present ours-a1 theirs-missing
+baseline rewound ours-a2 theirs-rewound
+baseline added-differently ours-added theirs-added
+baseline not-checked-out ours-unregistered theirs-unregistered
@@ -999,8 +999,6 @@ git init submodule-both-modify
 	tick
 	git commit -m root
 
-	git branch expected
-
 	git checkout -b A main
 	(cd sub
 	 echo A > file
@@ -1023,7 +1021,10 @@ git init submodule-both-modify
 	tick
 	git commit -m b
 
-	# We cannot handle submodules yet and thus mark them as conflicted, always if they mismatch at least.
+	# Git fast-forwards to 'B' as it finds the repository of 'sub' even though it isn't registered, but
+	# the baseline doesn't provide it. Thus the submodule is conflicted and we keep *ours*.
+	git branch expected A
+	git branch expected-reversed B
 	rm .git/index
 	git update-index --index-info <<EOF
 160000 $(oid e835c0c403c8e494c0ca98f3d25d0b8464c18d38) 1	sub
@@ -1275,7 +1276,7 @@ baseline added-file-changed-content-and-mode A-B A B "We improve on executable b
 
 baseline type-change-and-renamed A-B A B
 baseline change-and-delete A-B A B
-baseline submodule-both-modify A-B A B "We can't handle submodules yet and just mark them as conflicting. This is planned to be improved."
+baseline submodule-both-modify A-B A B "The submodule repository isn't available, so unlike Git we can't fast-forward it and keep ours."
 baseline both-modify-union-attr A-B A B
 baseline both-modify-union-attr A-B-diff3 A B
 baseline both-modify-binary A-B A B
@@ -1726,7 +1727,7 @@ EOF
 (cd submodule-both-modify
   rm .git/index
   # There is only one submodule. 'A' and 'B' change it in a fast-forwardable manner,
-  # but we can't handle this at all yet, and thus have to consider it irreconcilable.
+  # but without its repository we have to consider it irreconcilable.
   # The 'ancestor' resolution just keeps what was.
   git update-index --index-info <<EOF
 160000 commit $(oid e835c0c403c8e494c0ca98f3d25d0b8464c18d38)	sub
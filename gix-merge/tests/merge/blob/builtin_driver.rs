@@ -424,6 +424,52 @@ mod text {
         }
     }
 
+    mod patience {
+        use bstr::ByteSlice;
+        use gix_merge::blob::{Resolution, builtin_driver};
+        use imara_diff::InternedInput;
+
+        /// The classic example for the patience algorithm: ours adds a function before another one, and removes
+        /// a third one, while theirs changes a line in the function that ours kept.
+        #[test]
+        fn unique_lines_anchor_the_merge_where_myers_conflicts() {
+            let base = b"#include <stdio.h>\n\n// Frobs foo heartily\nint frobnitz(int foo)\n{\n    int i;\n    for(i = 0; i < 10; i++)\n    {\n        printf(\"Your answer is: \");\n        printf(\"%d\\n\", foo);\n    }\n}\n\nint fact(int n)\n{\n    if(n > 1)\n    {\n        return fact(n-1) * n;\n    }\n    return 1;\n}\n\nint main(int argc, char **argv)\n{\n    frobnitz(fact(10));\n}\n";
+            let ours = b"#include <stdio.h>\n\nint fib(int n)\n{\n    if(n > 2)\n    {\n        return fib(n-1) + fib(n-2);\n    }\n    return 1;\n}\n\n// Frobs foo heartily\nint frobnitz(int foo)\n{\n    int i;\n    for(i = 0; i < 10; i++)\n    {\n        printf(\"%d\\n\", foo);\n    }\n}\n\nint main(int argc, char **argv)\n{\n    frobnitz(fib(10));\n}\n";
+            let theirs = base.replace("    int i;", "    int i = 0;");
+
+            let labels = builtin_driver::text::Labels {
+                ancestor: Some("base".into()),
+                current: Some("ours".into()),
+                other: Some("theirs".into()),
+            };
+            let merge = |diff_algorithm| {
+                let options = builtin_driver::text::Options {
+                    diff_algorithm,
+                    ..Default::default()
+                };
+                let mut out = Vec::new();
+                let mut input = InternedInput::default();
+                let res = builtin_driver::text(&mut out, &mut input, labels, ours, base, &theirs, options);
+                (res, out)
+            };
+
+            let (res, _) = merge(imara_diff::Algorithm::Myers);
+            assert_eq!(
+                res,
+                Resolution::Conflict,
+                "Myers matches the braces of the removed function with the added one"
+            );
+
+            let (res, out) = merge(imara_diff::Algorithm::Patience);
+            assert_eq!(res, Resolution::Complete);
+            assert_eq!(
+                out.as_bstr(),
+                ours.replace("    int i;", "    int i = 0;").as_bstr(),
+                "both changes are applied"
+            );
+        }
+    }
+
     mod baseline {
         use std::path::Path;
 
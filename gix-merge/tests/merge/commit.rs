@@ -0,0 +1,255 @@
+use std::path::{Path, PathBuf};
+
+use gix_hash::ObjectId;
+use gix_merge::{
+    blob::builtin_driver::text::{Conflict, Labels},
+    commit::{Options, Strategy},
+    tree::TreatAsUnresolved,
+};
+use gix_object::Write;
+
+use crate::tree::{basic_merge_options, new_blob_merge_platform, new_diff_resource_cache};
+
+#[test]
+fn octopus() -> crate::Result {
+    let fixture = Fixture::new()?;
+    for (case, ours, theirs, expected_up_to_date) in [
+        ("octopus", "main", &["topic1", "topic2", "topic3"][..], &[][..]),
+        ("octopus-up-to-date", "main", &["topic1", "base", "topic2"], &["base"]),
+        ("octopus-fast-forward", "base", &["topic1", "topic2", "topic3"], &[]),
+    ] {
+        let mut out = fixture.octopus(ours, theirs, basic_merge_options())?;
+        let actual = out.tree_merge.tree.write(|tree| fixture.odb.write(tree))?;
+        assert_eq!(
+            actual,
+            fixture.expected_tree(case)?,
+            "{case}: the tree matches the one of Git"
+        );
+        assert!(out.tree_merge.conflicts.is_empty(), "{case}: there were no conflicts");
+        assert_eq!(
+            out.up_to_date_commits,
+            fixture.revs(expected_up_to_date)?,
+            "{case}: reachable commits don't need a merge"
+        );
+        let expected_merged: Vec<_> = theirs
+            .iter()
+            .filter(|name| !expected_up_to_date.contains(name))
+            .copied()
+            .collect();
+        assert_eq!(out.merged_commits, fixture.revs(&expected_merged)?, "{case}");
+        assert!(out.unmerged_commits.is_empty(), "{case}");
+        assert!(
+            out.virtual_merge_bases.is_empty(),
+            "{case}: there is only a single merge-base"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn octopus_stops_at_first_unresolved_conflict() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let out = fixture.octopus("main", &["topic1", "conflicting", "topic2"], basic_merge_options())?;
+    assert!(out.tree_merge.has_unresolved_conflicts(TreatAsUnresolved::default()));
+    assert_eq!(out.merged_commits, fixture.revs(&["topic1", "conflicting"])?);
+    assert_eq!(
+        out.unmerged_commits,
+        fixture.revs(&["topic2"])?,
+        "Git refuses to continue after a conflict as well"
+    );
+
+    let out = fixture.octopus("main", &["topic1", "topic2", "conflicting"], basic_merge_options())?;
+    assert!(
+        out.tree_merge.has_unresolved_conflicts(TreatAsUnresolved::default()),
+        "the last merge can also conflict, leaving the conflicts for the caller"
+    );
+    assert!(out.unmerged_commits.is_empty());
+    Ok(())
+}
+
+#[test]
+fn strategies_and_strategy_options() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let mut ours = basic_merge_options();
+    ours.strategy = Strategy::Ours;
+    let mut x_ours = basic_merge_options();
+    x_ours.tree_merge.blob_merge.text.conflict = Conflict::ResolveWithOurs;
+    let mut x_theirs = basic_merge_options();
+    x_theirs.tree_merge.blob_merge.text.conflict = Conflict::ResolveWithTheirs;
+    let mut subtree = basic_merge_options();
+    subtree.strategy = Strategy::Subtree;
+    let mut subtree_prefix = basic_merge_options();
+    subtree_prefix.subtree_prefix = Some("lib".into());
+
+    for (case, our_name, their_name, options) in [
+        ("ours", "main", "topic1", ours.clone()),
+        ("x-ours", "topic1", "conflicting", x_ours),
+        ("x-theirs", "topic1", "conflicting", x_theirs),
+        ("subtree", "project", "library", subtree.clone()),
+        ("subtree-prefix", "project", "library", subtree_prefix),
+        ("subtree-up", "library", "project", subtree),
+    ] {
+        let mut out = fixture.commit(our_name, their_name, options)?;
+        let actual = out.tree_merge.tree.write(|tree| fixture.odb.write(tree))?;
+        assert_eq!(
+            actual,
+            fixture.expected_tree(case)?,
+            "{case}: the tree matches the one of Git"
+        );
+        assert!(
+            !out.tree_merge.has_unresolved_conflicts(TreatAsUnresolved::default()),
+            "{case}"
+        );
+    }
+
+    let mut out = fixture.octopus("main", &["topic1", "topic2"], ours)?;
+    let actual = out.tree_merge.tree.write(|tree| fixture.odb.write(tree))?;
+    assert_eq!(
+        actual,
+        fixture.expected_tree("ours")?,
+        "octopus merges can keep our tree as well"
+    );
+    assert_eq!(out.merged_commits, fixture.revs(&["topic1", "topic2"])?);
+    Ok(())
+}
+
+#[test]
+fn shift_tree_by_prefix() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let project = fixture.tree_of("project")?;
+    let library = fixture.tree_of("library")?;
+    assert_eq!(
+        gix_merge::commit::shift_tree(&project, &library, Some("missing".into()), &fixture.odb)?,
+        library,
+        "prefixes that aren't in either tree are ignored"
+    );
+    assert_eq!(
+        gix_merge::commit::shift_tree(&library, &project, Some("lib/".into()), &fixture.odb)?,
+        fixture.tree_of("project:lib")?,
+        "their tree is shifted up if the prefix is only in their tree, and trailing slashes don't matter"
+    );
+
+    let shifted = gix_merge::commit::shift_tree(&project, &library, Some("lib/include".into()), &fixture.odb)?;
+    assert_eq!(
+        fixture.lookup(shifted, "lib/include")?,
+        library,
+        "their tree is shifted down if the prefix is only in our tree, even if it's not similar"
+    );
+    assert_eq!(
+        fixture.lookup(shifted, "a")?,
+        fixture.tree_of("project:a")?,
+        "everything else is taken from our tree"
+    );
+    Ok(())
+}
+
+struct Fixture {
+    root: PathBuf,
+    odb: gix_odb::memory::Proxy<gix_odb::Handle>,
+}
+
+impl Fixture {
+    fn new() -> crate::Result<Self> {
+        let root = gix_testtools::scripted_fixture_read_only("commit-baseline.sh")?;
+        let object_hash = gix_testtools::object_hash();
+        let odb = gix_odb::at_opts(
+            root.join(".git/objects"),
+            Vec::new(),
+            gix_odb::store::init::Options {
+                object_hash,
+                ..Default::default()
+            },
+        )?;
+        Ok(Fixture {
+            odb: gix_odb::memory::Proxy::new(odb, object_hash),
+            root,
+        })
+    }
+
+    fn commit(&self, ours: &str, theirs: &str, options: Options) -> crate::Result<gix_merge::commit::Outcome<'_>> {
+        let mut graph = gix_revwalk::Graph::new(&self.odb, None);
+        Ok(gix_merge::commit(
+            self.rev(ours)?,
+            self.rev(theirs)?,
+            labels(),
+            &mut graph,
+            &mut new_diff_resource_cache(&self.root),
+            &mut new_blob_merge_platform(&self.root, None),
+            &self.odb,
+            &mut |id| id.to_hex_with_len(7).to_string(),
+            &mut |_| Ok(None),
+            options,
+        )?)
+    }
+
+    fn octopus(
+        &self,
+        ours: &str,
+        theirs: &[&str],
+        options: Options,
+    ) -> crate::Result<gix_merge::commit::octopus::Outcome<'_>> {
+        let mut graph = gix_revwalk::Graph::new(&self.odb, None);
+        Ok(gix_merge::commit::octopus(
+            self.rev(ours)?,
+            &self.revs(theirs)?,
+            labels(),
+            &mut graph,
+            &mut new_diff_resource_cache(&self.root),
+            &mut new_blob_merge_platform(&self.root, None),
+            &self.odb,
+            &mut |id| id.to_hex_with_len(7).to_string(),
+            &mut |_| Ok(None),
+            options,
+        )?)
+    }
+
+    fn rev(&self, name: &str) -> crate::Result<ObjectId> {
+        let refs = self.root.join(".git/refs");
+        let path = [refs.join("heads").join(name), refs.join("tags").join(name)]
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or_else(|| format!("no branch or tag named '{name}'"))?;
+        read_id(&path)
+    }
+
+    fn revs(&self, names: &[&str]) -> crate::Result<Vec<ObjectId>> {
+        names.iter().map(|name| self.rev(name)).collect()
+    }
+
+    fn tree_of(&self, spec: &str) -> crate::Result<ObjectId> {
+        use gix_object::FindExt;
+        let (name, path) = spec.split_once(':').unwrap_or((spec, ""));
+        let tree = self.odb.find_commit(&self.rev(name)?, &mut Vec::new())?.tree();
+        if path.is_empty() {
+            return Ok(tree);
+        }
+        self.lookup(tree, path)
+    }
+
+    fn lookup(&self, tree: ObjectId, path: &str) -> crate::Result<ObjectId> {
+        use gix_object::FindExt;
+        let (mut buf, mut lookup_buf) = (Vec::new(), Vec::new());
+        let entry = self
+            .odb
+            .find_tree_iter(&tree, &mut buf)?
+            .lookup_entry_by_path(&self.odb, &mut lookup_buf, path)?
+            .ok_or_else(|| format!("'{path}' doesn't exist in {tree}"))?;
+        Ok(entry.oid)
+    }
+
+    fn expected_tree(&self, case: &str) -> crate::Result<ObjectId> {
+        read_id(&self.root.join(".git").join(format!("{case}.tree")))
+    }
+}
+
+fn read_id(path: &Path) -> crate::Result<ObjectId> {
+    Ok(ObjectId::from_hex(std::fs::read_to_string(path)?.trim().as_bytes())?)
+}
+
+fn labels() -> Labels<'static> {
+    Labels {
+        ancestor: None,
+        current: Some("ours".into()),
+        other: None,
+    }
+}
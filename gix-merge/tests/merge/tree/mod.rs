@@ -68,6 +68,7 @@ fn run_baseline() -> crate::Result {
             &mut blob_merge,
             &odb,
             &mut |id| id.to_hex_with_len(7).to_string(),
+            &mut |_| Ok(None),
             options.clone(),
         )?
         .tree_merge;
@@ -175,6 +176,7 @@ fn run_baseline() -> crate::Result {
                 &mut blob_merge,
                 &odb,
                 &mut |id| id.to_hex_with_len(7).to_string(),
+                &mut |_| Ok(None),
                 options.clone(),
             )?
             .tree_merge;
@@ -215,10 +217,12 @@ fn run_baseline() -> crate::Result {
     Ok(())
 }
 
-fn basic_merge_options() -> Options {
+pub(crate) fn basic_merge_options() -> Options {
     gix_merge::commit::Options {
         allow_missing_merge_base: true,
         use_first_merge_base: false,
+        strategy: Default::default(),
+        subtree_prefix: None,
         tree_merge: gix_merge::tree::Options {
             symlink_conflicts: None,
             tree_conflicts: None,
@@ -227,6 +231,7 @@ fn basic_merge_options() -> Options {
                 percentage: Some(0.5),
                 limit: 0,
                 track_empty: false,
+                matching: Default::default(),
             }),
             blob_merge: gix_merge::blob::platform::merge::Options::default(),
             blob_merge_command_ctx: Default::default(),
@@ -236,7 +241,7 @@ fn basic_merge_options() -> Options {
     }
 }
 
-fn new_diff_resource_cache(root: &Path) -> gix_diff::blob::Platform {
+pub(crate) fn new_diff_resource_cache(root: &Path) -> gix_diff::blob::Platform {
     gix_diff::blob::Platform::new(
         Default::default(),
         gix_diff::blob::Pipeline::new(Default::default(), Default::default(), Vec::new(), Default::default()),
@@ -251,7 +256,7 @@ fn new_diff_resource_cache(root: &Path) -> gix_diff::blob::Platform {
     )
 }
 
-fn new_blob_merge_platform(
+pub(crate) fn new_blob_merge_platform(
     root: &Path,
     large_file_threshold_bytes: impl Into<Option<u64>>,
 ) -> gix_merge::blob::Platform {
@@ -284,3 +289,4 @@ fn new_blob_merge_platform(
 }
 
 mod baseline;
+mod submodule;
@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+
+use bstr::ByteSlice;
+use gix_hash::ObjectId;
+use gix_merge::tree::{
+    Resolution, ResolutionFailure, ResolveWith, TreatAsUnresolved,
+    submodule::{Failure, Repository},
+};
+use gix_object::Write;
+
+use crate::tree::{basic_merge_options, new_blob_merge_platform, new_diff_resource_cache};
+
+#[test]
+fn gitlinks_changed_on_both_sides_are_merged_like_git() -> crate::Result {
+    let fixture = Fixture::new()?;
+    for (case, ours, theirs, expected) in [
+        ("fast-forward", "ours-a1", "theirs-a2", Ok("a2")),
+        ("fast-forward-reversed", "theirs-a2", "ours-a1", Ok("a2")),
+        (
+            "diverged-with-merge",
+            "ours-a1",
+            "theirs-b1",
+            Err(Failure::Diverged {
+                possible_merges: vec![fixture.sub_commit("m")?],
+            }),
+        ),
+        (
+            "diverged",
+            "ours-a1",
+            "theirs-c1",
+            Err(Failure::Diverged {
+                possible_merges: Vec::new(),
+            }),
+        ),
+        (
+            "commits-not-present",
+            "ours-a1",
+            "theirs-missing",
+            Err(Failure::CommitsNotPresent),
+        ),
+        (
+            "rewound",
+            "ours-a2",
+            "theirs-rewound",
+            Err(Failure::CommitsDontFollowMergeBase),
+        ),
+        (
+            "added-differently",
+            "ours-added",
+            "theirs-added",
+            Err(Failure::NoMergeBase),
+        ),
+        (
+            "not-checked-out",
+            "ours-unregistered",
+            "theirs-unregistered",
+            Err(Failure::NotCheckedOut),
+        ),
+    ] {
+        let mut out = fixture.merge(ours, theirs, None)?;
+        let actual = out.tree.write(|tree| fixture.odb.write(tree))?;
+        assert_eq!(
+            actual,
+            fixture.expected_tree(case)?,
+            "{case}: the tree matches the one of Git"
+        );
+        assert_eq!(out.conflicts.len(), 1, "{case}: only the submodule was merged");
+        let conflict = &out.conflicts[0];
+        match expected {
+            Ok(name) => {
+                assert!(
+                    matches!(
+                        conflict.resolution,
+                        Ok(Resolution::OursModifiedTheirsModifiedThenSubmoduleFastForward { commit })
+                            if commit == fixture.sub_commit(name)?
+                    ),
+                    "{case}: {:?}",
+                    conflict.resolution
+                );
+                assert!(!out.has_unresolved_conflicts(TreatAsUnresolved::forced_resolution()));
+            }
+            Err(expected_reason) => {
+                assert!(
+                    matches!(
+                        &conflict.resolution,
+                        Err(ResolutionFailure::OursModifiedTheirsModifiedSubmodule { reason }) if *reason == expected_reason
+                    ),
+                    "{case}: {:?}",
+                    conflict.resolution
+                );
+                assert!(out.has_unresolved_conflicts(TreatAsUnresolved::undecidable()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn unmerged_gitlinks_are_staged_and_can_be_resolved_with_the_ancestor() -> crate::Result {
+    let fixture = Fixture::new()?;
+    let out = fixture.merge("theirs-b1", "ours-a1", None)?;
+    let [base, ours, theirs] = out.conflicts[0].entries();
+    assert_eq!(
+        [base, ours, theirs].map(|entry| entry.map(|entry| (entry.mode.is_commit(), entry.id))),
+        [
+            Some((true, fixture.sub_commit("c0")?)),
+            Some((true, fixture.sub_commit("b1")?)),
+            Some((true, fixture.sub_commit("a1")?)),
+        ],
+        "all stages are gitlinks, with *ours* and *theirs* matching the sides of the merge"
+    );
+
+    let mut out = fixture.merge("ours-a1", "theirs-b1", Some(ResolveWith::Ancestor))?;
+    let actual = out.tree.write(|tree| fixture.odb.write(tree))?;
+    assert_eq!(actual, fixture.tree_of("base")?, "the submodule is left unchanged");
+    assert!(
+        !out.has_unresolved_conflicts(TreatAsUnresolved::git()),
+        "the conflict was resolved by force"
+    );
+    Ok(())
+}
+
+struct Fixture {
+    root: PathBuf,
+    odb: gix_odb::memory::Proxy<gix_odb::Handle>,
+}
+
+impl Fixture {
+    fn new() -> crate::Result<Self> {
+        let root = gix_testtools::scripted_fixture_read_only("submodule-baseline.sh")?;
+        let object_hash = gix_testtools::object_hash();
+        let odb = open_odb(&root.join(".git/objects"))?;
+        Ok(Fixture {
+            odb: gix_odb::memory::Proxy::new(odb, object_hash),
+            root,
+        })
+    }
+
+    fn merge(
+        &self,
+        ours: &str,
+        theirs: &str,
+        tree_conflicts: Option<ResolveWith>,
+    ) -> crate::Result<gix_merge::tree::Outcome<'_>> {
+        let mut graph = gix_revwalk::Graph::new(&self.odb, None);
+        let mut options = basic_merge_options();
+        options.tree_merge.tree_conflicts = tree_conflicts;
+        Ok(gix_merge::commit(
+            self.rev(ours)?,
+            self.rev(theirs)?,
+            Default::default(),
+            &mut graph,
+            &mut new_diff_resource_cache(&self.root),
+            &mut new_blob_merge_platform(&self.root, None),
+            &self.odb,
+            &mut |id| id.to_hex_with_len(7).to_string(),
+            &mut |path| {
+                let git_dir = self.root.join(path.to_str()?).join(".git");
+                if !git_dir.is_dir() {
+                    return Ok(None);
+                }
+                Ok(Some(Box::new(Submodule {
+                    objects: open_odb(&git_dir.join("objects"))?,
+                    tips: self.root.join(".git").join(format!("{path}-tips")),
+                })))
+            },
+            options,
+        )?
+        .tree_merge)
+    }
+
+    fn rev(&self, name: &str) -> crate::Result<ObjectId> {
+        let refs = self.root.join(".git/refs");
+        let path = [refs.join("heads").join(name), refs.join("tags").join(name)]
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or_else(|| format!("no branch or tag named '{name}'"))?;
+        read_id(&path)
+    }
+
+    fn tree_of(&self, name: &str) -> crate::Result<ObjectId> {
+        use gix_object::FindExt;
+        Ok(self.odb.find_commit(&self.rev(name)?, &mut Vec::new())?.tree())
+    }
+
+    fn sub_commit(&self, name: &str) -> crate::Result<ObjectId> {
+        read_id(&self.root.join(".git").join(format!("sub-{name}")))
+    }
+
+    fn expected_tree(&self, case: &str) -> crate::Result<ObjectId> {
+        read_id(&self.root.join(".git").join(format!("{case}.tree")))
+    }
+}
+
+/// A submodule whose reference tips were recorded by the fixture script.
+struct Submodule {
+    objects: gix_odb::Handle,
+    tips: PathBuf,
+}
+
+impl Repository for Submodule {
+    fn objects(&self) -> &dyn gix_object::Find {
+        &self.objects
+    }
+
+    fn reference_tips(&mut self) -> Result<Vec<ObjectId>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        std::fs::read_to_string(&self.tips)?
+            .lines()
+            .map(|line| Ok(ObjectId::from_hex(line.as_bytes())?))
+            .collect()
+    }
+}
+
+fn open_odb(objects_dir: &Path) -> std::io::Result<gix_odb::Handle> {
+    gix_odb::at_opts(
+        objects_dir,
+        Vec::new(),
+        gix_odb::store::init::Options {
+            object_hash: gix_testtools::object_hash(),
+            ..Default::default()
+        },
+    )
+}
+
+fn read_id(path: &Path) -> crate::Result<ObjectId> {
+    Ok(ObjectId::from_hex(std::fs::read_to_string(path)?.trim().as_bytes())?)
+}
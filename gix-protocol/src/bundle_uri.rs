@@ -0,0 +1,448 @@
+//! Support for the `bundle-uri` V2 command, with which servers point clients to bundles to download before fetching,
+//! along with the format of bundle lists and the header of the bundle files themselves.
+//!
+//! Bundle lists are sent as `bundle.*` key-value pairs, either in response to the `bundle-uri` command, or as git-config file
+//! when downloading a URI that points to a list instead of a bundle.
+use bstr::{BStr, BString, ByteSlice};
+use gix_hash::ObjectId;
+
+/// How the bundles of a [`List`] relate to each other.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// All bundles are needed to obtain the complete data, as they may build on each other.
+    #[default]
+    All,
+    /// Any of the bundles contains the complete data, so they are alternatives, typically in different locations.
+    Any,
+}
+
+/// A hint on how to select bundles of a [`List`] to download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Heuristic {
+    /// Bundles have a [creation token](Bundle::creation_token) which increases with each bundle,
+    /// so bundles with lower tokens should be applied first.
+    CreationToken,
+}
+
+/// A bundle as listed in a [`List`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bundle {
+    /// The identifier of the bundle in the list, like `<id>` in `bundle.<id>.uri`.
+    pub id: BString,
+    /// The location to download the bundle or another bundle list from.
+    ///
+    /// It may be relative, in which case it is relative to the location of the list, or to the remote if it was received
+    /// via the `bundle-uri` command.
+    pub uri: BString,
+    /// The value of `bundle.<id>.creationToken`, which orders bundles if the list uses [`Heuristic::CreationToken`].
+    pub creation_token: Option<u64>,
+    /// The value of `bundle.<id>.filter` if the bundle only contains a subset of the objects, like `blob:none`.
+    pub filter: Option<BString>,
+}
+
+/// A list of bundles along with information on how to use them.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct List {
+    /// The value of `bundle.version`, which is `1` if it was set.
+    pub version: Option<u32>,
+    /// The value of `bundle.mode`.
+    pub mode: Mode,
+    /// The value of `bundle.heuristic`, if it is known.
+    pub heuristic: Option<Heuristic>,
+    /// All bundles in the order they were first mentioned.
+    pub bundles: Vec<Bundle>,
+}
+
+///
+pub mod list {
+    use bstr::BString;
+
+    /// The error returned by [`List::from_key_values()`](super::List::from_key_values()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error("Bundle list version {version:?} is not supported")]
+        UnsupportedVersion { version: BString },
+        #[error("The bundle list mode {mode:?} is unknown")]
+        UnknownMode { mode: BString },
+        #[error("The creation token {value:?} of bundle '{id}' is not a number")]
+        InvalidCreationToken { id: BString, value: BString },
+        #[error("The bundle '{id}' has no URI")]
+        MissingUri { id: BString },
+    }
+}
+
+impl List {
+    /// Parse a list from `key=value` `lines` as sent in response to the `bundle-uri` command, ignoring lines without `=`.
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a BStr>) -> Result<Self, list::Error> {
+        Self::from_key_values(lines.into_iter().filter_map(|line| {
+            let line = line.strip_suffix(b"\n").unwrap_or(line);
+            line.split_once_str("=")
+                .map(|(key, value)| (key.as_bstr(), value.as_bstr()))
+        }))
+    }
+
+    /// Parse a list from `pairs` of keys like `bundle.mode` or `bundle.<id>.uri` and their values.
+    ///
+    /// Keys outside the `bundle` section as well as unknown keys are ignored, and bundles are kept in the order they were first mentioned.
+    /// Names of sections and keys are case-insensitive, while bundle identifiers are case-sensitive.
+    pub fn from_key_values<'a>(pairs: impl IntoIterator<Item = (&'a BStr, &'a BStr)>) -> Result<Self, list::Error> {
+        let mut list = List::default();
+        for (key, value) in pairs {
+            let Some(rest) = key
+                .get(.."bundle.".len())
+                .filter(|section| section.eq_ignore_ascii_case(b"bundle."))
+                .map(|_| &key["bundle.".len()..])
+            else {
+                continue;
+            };
+            match rest.rfind_byte(b'.') {
+                None => {
+                    if rest.eq_ignore_ascii_case(b"version") {
+                        if value != "1" {
+                            return Err(list::Error::UnsupportedVersion { version: value.into() });
+                        }
+                        list.version = Some(1);
+                    } else if rest.eq_ignore_ascii_case(b"mode") {
+                        list.mode = match value.as_bytes() {
+                            b"all" => Mode::All,
+                            b"any" => Mode::Any,
+                            _ => return Err(list::Error::UnknownMode { mode: value.into() }),
+                        };
+                    } else if rest.eq_ignore_ascii_case(b"heuristic") {
+                        list.heuristic = (value == "creationToken").then_some(Heuristic::CreationToken);
+                    }
+                }
+                Some(pos) => {
+                    let (id, name) = (rest[..pos].as_bstr(), &rest[pos + 1..]);
+                    let idx = match list.bundles.iter().position(|bundle| bundle.id == id) {
+                        Some(idx) => idx,
+                        None => {
+                            list.bundles.push(Bundle {
+                                id: id.to_owned(),
+                                uri: BString::default(),
+                                creation_token: None,
+                                filter: None,
+                            });
+                            list.bundles.len() - 1
+                        }
+                    };
+                    let bundle = &mut list.bundles[idx];
+                    if name.eq_ignore_ascii_case(b"uri") {
+                        bundle.uri = value.to_owned();
+                    } else if name.eq_ignore_ascii_case(b"creationToken") {
+                        bundle.creation_token = Some(
+                            value
+                                .to_str()
+                                .ok()
+                                .and_then(|value| value.parse().ok())
+                                .ok_or_else(|| list::Error::InvalidCreationToken {
+                                    id: id.to_owned(),
+                                    value: value.to_owned(),
+                                })?,
+                        );
+                    } else if name.eq_ignore_ascii_case(b"filter") {
+                        bundle.filter = Some(value.to_owned());
+                    }
+                }
+            }
+        }
+        if let Some(bundle) = list.bundles.iter().find(|bundle| bundle.uri.is_empty()) {
+            return Err(list::Error::MissingUri { id: bundle.id.clone() });
+        }
+        Ok(list)
+    }
+
+    /// Return the list as pairs of keys and values, suitable to be sent in response to the `bundle-uri` command as `key=value`.
+    pub fn to_key_values(&self) -> Vec<(BString, BString)> {
+        let mut out = vec![
+            ("bundle.version".into(), self.version.unwrap_or(1).to_string().into()),
+            (
+                "bundle.mode".into(),
+                match self.mode {
+                    Mode::All => "all",
+                    Mode::Any => "any",
+                }
+                .into(),
+            ),
+        ];
+        if let Some(Heuristic::CreationToken) = self.heuristic {
+            out.push(("bundle.heuristic".into(), "creationToken".into()));
+        }
+        for bundle in &self.bundles {
+            out.push((format!("bundle.{}.uri", bundle.id).into(), bundle.uri.clone()));
+            if let Some(token) = bundle.creation_token {
+                out.push((
+                    format!("bundle.{}.creationToken", bundle.id).into(),
+                    token.to_string().into(),
+                ));
+            }
+            if let Some(filter) = &bundle.filter {
+                out.push((format!("bundle.{}.filter", bundle.id).into(), filter.clone()));
+            }
+        }
+        out
+    }
+}
+
+/// The header of a bundle file, which is followed by a pack.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Header {
+    /// The version of the bundle format, `2` or `3`.
+    pub version: u8,
+    /// The kind of hash used by all objects in the bundle, as set by the `@object-format` capability of version 3.
+    pub object_hash: gix_hash::Kind,
+    /// The value of the `@filter` capability if the pack only contains a subset of the objects.
+    pub filter: Option<BString>,
+    /// Commits along with an optional comment which are needed to use the pack, as it doesn't contain them or anything reachable from them.
+    pub prerequisites: Vec<(ObjectId, BString)>,
+    /// The references in the bundle, with the object they point to.
+    pub refs: Vec<(ObjectId, BString)>,
+}
+
+///
+pub mod header {
+    use bstr::BString;
+
+    /// The error returned by [`Header::from_read()`](super::Header::from_read()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("The signature {line:?} doesn't indicate a bundle of version 2 or 3")]
+        UnknownSignature { line: BString },
+        #[error("The bundle requires the unknown capability {capability:?}")]
+        UnknownCapability { capability: BString },
+        #[error("The bundle uses the unknown object format {format:?}")]
+        UnknownObjectFormat { format: BString },
+        #[error("The header line {line:?} could not be parsed")]
+        MalformedLine { line: BString },
+        #[error("The bundle header ended unexpectedly")]
+        UnexpectedEof,
+    }
+}
+
+impl Header {
+    /// Read the header of a bundle from `read`, leaving it positioned at the start of the pack that follows it.
+    pub fn from_read(read: &mut impl std::io::BufRead) -> Result<Self, header::Error> {
+        let mut line = Vec::new();
+        let mut next_line = |line: &mut Vec<u8>| -> Result<(), header::Error> {
+            line.clear();
+            if read.read_until(b'\n', line)? == 0 || line.last() != Some(&b'\n') {
+                return Err(header::Error::UnexpectedEof);
+            }
+            line.pop();
+            Ok(())
+        };
+
+        next_line(&mut line)?;
+        let version = match line.as_slice() {
+            b"# v2 git bundle" => 2,
+            b"# v3 git bundle" => 3,
+            _ => return Err(header::Error::UnknownSignature { line: line.into() }),
+        };
+        let mut object_hash = None;
+        let (mut filter, mut prerequisites, mut refs) = (None, Vec::new(), Vec::new());
+        loop {
+            next_line(&mut line)?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some(capability) = line.strip_prefix(b"@").filter(|_| version == 3) {
+                let (name, value) = capability
+                    .split_once_str("=")
+                    .map_or((capability, None), |(name, value)| (name, Some(value)));
+                match name {
+                    b"object-format" => object_hash = Some(parse_object_format(value.unwrap_or_default())?),
+                    b"filter" => filter = value.map(Into::into),
+                    _ => {
+                        return Err(header::Error::UnknownCapability {
+                            capability: capability.into(),
+                        });
+                    }
+                }
+                continue;
+            }
+            let (is_prerequisite, line_without_prefix) = match line.strip_prefix(b"-") {
+                Some(rest) => (true, rest),
+                None => (false, line.as_slice()),
+            };
+            let hex_len = match object_hash {
+                Some(kind) => kind,
+                None => *object_hash.insert(parse_object_format(b"sha1")?),
+            }
+            .len_in_hex();
+            let id = line_without_prefix
+                .get(..hex_len)
+                .and_then(|hex| ObjectId::from_hex(hex).ok())
+                .ok_or_else(|| header::Error::MalformedLine {
+                    line: line.clone().into(),
+                })?;
+            let rest = &line_without_prefix[hex_len..];
+            let rest = match rest.strip_prefix(b" ") {
+                Some(rest) => rest,
+                None if rest.is_empty() && is_prerequisite => rest,
+                None => {
+                    return Err(header::Error::MalformedLine {
+                        line: line.clone().into(),
+                    });
+                }
+            };
+            if is_prerequisite {
+                prerequisites.push((id, rest.into()));
+            } else if rest.is_empty() {
+                return Err(header::Error::MalformedLine {
+                    line: line.clone().into(),
+                });
+            } else {
+                refs.push((id, rest.into()));
+            }
+        }
+        Ok(Header {
+            version,
+            object_hash: match object_hash {
+                Some(kind) => kind,
+                None => parse_object_format(b"sha1")?,
+            },
+            filter,
+            prerequisites,
+            refs,
+        })
+    }
+}
+
+fn parse_object_format(format: &[u8]) -> Result<gix_hash::Kind, header::Error> {
+    format
+        .to_str()
+        .ok()
+        .and_then(|format| format.parse().ok())
+        .ok_or_else(|| header::Error::UnknownObjectFormat { format: format.into() })
+}
+
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+mod error {
+    /// The error returned by invoking a [`super::BundleUriCommand`].
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Transport(#[from] gix_transport::client::Error),
+        #[error(transparent)]
+        DecodePacketline(#[from] gix_transport::packetline::decode::Error),
+        #[error(transparent)]
+        List(#[from] super::list::Error),
+        #[error("The server doesn't support the 'bundle-uri' command")]
+        Unsupported,
+        #[error(transparent)]
+        ArgumentValidation(#[from] crate::command::validate_argument_prefixes::Error),
+    }
+
+    impl gix_transport::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::Io(err) => err.is_spurious(),
+                Error::Transport(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+}
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use error::Error;
+
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub(crate) mod function {
+    use bstr::BString;
+    use gix_transport::client::Capabilities;
+
+    use super::{Error, List};
+    use crate::Command;
+    #[cfg(feature = "async-client")]
+    use crate::transport::client::async_io::TransportV2Ext as _;
+    #[cfg(feature = "blocking-client")]
+    use crate::transport::client::blocking_io::TransportV2Ext as _;
+
+    /// A command to obtain the [list of bundles](List) a server advertises to bootstrap clones with.
+    ///
+    /// Its invocation uses the same implementation with either blocking or asynchronous I/O.
+    pub struct BundleUriCommand<'a> {
+        capabilities: &'a Capabilities,
+        features: Vec<crate::command::Feature>,
+    }
+
+    macro_rules! invoke {
+        ($name:ident, $bisync:path, $transport:path, $mode:literal) => {
+            /// Invoke the `bundle-uri` V2 command on `transport` and parse the received bundle list,
+            /// or fail with [`Error::Unsupported`] if the server doesn't advertise the command.
+            ///
+            /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+            #[$bisync]
+            pub async fn $name(self, mut transport: impl $transport, trace: bool) -> Result<List, Error> {
+                let _span = gix_features::trace::detail!("gix_protocol::BundleUriCommand::invoke()", mode = $mode);
+                if !Self::is_supported(self.capabilities) {
+                    return Err(Error::Unsupported);
+                }
+                Command::BundleUri.validate_argument_prefixes(
+                    gix_transport::Protocol::V2,
+                    self.capabilities,
+                    &[],
+                    &self.features,
+                )?;
+
+                let mut response = transport
+                    .invoke(
+                        Command::BundleUri.as_str(),
+                        self.features.into_iter(),
+                        None::<std::iter::Empty<BString>>,
+                        trace,
+                    )
+                    .await?;
+                let mut lines = Vec::<BString>::new();
+                while let Some(line) = response
+                    .readline()
+                    .await
+                    .transpose()?
+                    .transpose()?
+                    .and_then(|line| line.as_bstr())
+                {
+                    lines.push(line.to_owned());
+                }
+                Ok(List::from_lines(lines.iter().map(AsRef::as_ref))?)
+            }
+        };
+    }
+
+    impl<'a> BundleUriCommand<'a> {
+        /// Build a command to obtain the bundle list from the given server `capabilities`,
+        /// using `agent` information to identify ourselves.
+        pub fn new(capabilities: &'a Capabilities, agent: crate::command::Feature) -> Self {
+            let mut features = Command::BundleUri.default_features(gix_transport::Protocol::V2, capabilities);
+            features.push(agent);
+            Self { capabilities, features }
+        }
+
+        /// Return `true` if the server with `capabilities` supports the `bundle-uri` command.
+        pub fn is_supported(capabilities: &Capabilities) -> bool {
+            capabilities.contains(Command::BundleUri.as_str())
+        }
+
+        #[cfg(feature = "async-client")]
+        invoke!(
+            invoke_async,
+            ::bisync::asynchronous::bisync,
+            crate::transport::client::async_io::Transport,
+            "async"
+        );
+
+        #[cfg(feature = "blocking-client")]
+        invoke!(
+            invoke_blocking,
+            ::bisync::synchronous::bisync,
+            crate::transport::client::blocking_io::Transport,
+            "blocking"
+        );
+    }
+}
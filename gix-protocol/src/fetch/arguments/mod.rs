@@ -140,9 +140,16 @@ impl Arguments {
         }
     }
     /// Deepen the commit history in a relative instead of absolute fashion.
+    ///
+    /// With protocol V0 and V1 this is a capability of the first `want` line, so it must be called before [`want()`](Self::want()).
     pub fn deepen_relative(&mut self) {
         debug_assert!(self.deepen_relative, "'deepen-relative' feature required");
         if self.deepen_relative {
+            #[cfg(any(feature = "async-client", feature = "blocking-client"))]
+            if self.version != gix_transport::Protocol::V2 {
+                self.add_feature("deepen-relative");
+                return;
+            }
             self.args.push("deepen-relative".into());
         }
     }
@@ -220,7 +227,8 @@ impl Arguments {
                 let baked_features = features
                     .iter()
                     .filter(
-                        |(f, _)| *f != "include-tag", /* not a capability in that sense, needs to be turned on by caller later */
+                        // not a capability in that sense, needs to be turned on by caller later
+                        |(f, _)| *f != "include-tag" && *f != "deepen-relative",
                     )
                     .map(|(n, v)| match v {
                         Some(v) => format!("{n}={v}"),
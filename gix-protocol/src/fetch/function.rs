@@ -104,6 +104,9 @@ where
             let mut rounds = Vec::new();
             let is_stateless = arguments.is_stateless(!transport.connection_persists_across_multiple_requests());
             let mut state = negotiate::one_round::State::new(is_stateless);
+            // V0 and V1 send shallow updates in the response to the first request only, or repeat them in each response
+            // when stateless, so like `git` we use the first ones we see.
+            let mut shallow_updates = None;
             let reader = 'negotiation: loop {
                 let _round = gix_trace::detail!("negotiate round", round = rounds.len() + 1);
                 progress.step();
@@ -130,6 +133,15 @@ where
                 let response =
                     crate::fetch::Response::from_line_reader(protocol_version, &mut reader, is_done, !is_done).await?;
                 let has_pack = response.has_pack();
+                if shallow_updates.is_none() && !response.shallows.is_empty() {
+                    shallow_updates = Some(response.shallows.clone());
+                }
+                if is_done && !has_pack {
+                    // The remote had its last chance, there is nothing more we could send to convince it.
+                    return Err(Error::Negotiate(negotiate::Error::NegotiationFailed {
+                        rounds: rounds.len(),
+                    }));
+                }
                 previous_response = Some(response);
                 if has_pack {
                     progress.step();
@@ -144,6 +156,9 @@ where
             drop(negotiate_span);
 
             let mut previous_response = previous_response.expect("knowledge of a pack means a response was received");
+            if let Some(shallow_updates) = shallow_updates {
+                previous_response.shallows = shallow_updates;
+            }
             previous_response.append_v1_shallow_updates(v1_shallow_updates);
             if !previous_response.shallow_updates().is_empty() && shallow_lock.is_none() {
                 if reject_shallow_remote {
@@ -165,8 +180,18 @@ where
             drop(reader);
 
             if let Some(shallow_lock) = shallow_lock {
-                if !previous_response.shallow_updates().is_empty() {
-                    gix_shallow::write(shallow_lock, shallow_commits, previous_response.shallow_updates())?;
+                // Like `git`, only keep shallow commits advertised by a shallow remote if we actually received them.
+                let shallow_updates: Vec<_> = previous_response
+                    .shallow_updates()
+                    .iter()
+                    .filter(|update| match update {
+                        gix_shallow::Update::Shallow(id) => negotiate.has_shallow_commit(id),
+                        gix_shallow::Update::Unshallow(_) => true,
+                    })
+                    .copied()
+                    .collect();
+                if !shallow_updates.is_empty() {
+                    gix_shallow::write(shallow_lock, shallow_commits, &shallow_updates)?;
                 }
             }
             Ok(Some(Outcome {
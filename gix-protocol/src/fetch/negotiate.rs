@@ -406,6 +406,8 @@ pub mod one_round {
         ///
         /// Only set when we are stateless as we have to resend known common commits each round.
         pub(super) common_commits: Option<Vec<gix_hash::ObjectId>>,
+        /// The same commits as in `common_commits`, to quickly check if a commit is known to be in common already.
+        pub(super) common_commits_seen: std::collections::HashSet<gix_hash::ObjectId>,
     }
 
     impl State {
@@ -418,6 +420,7 @@ pub mod one_round {
                 seen_ack: false,
                 in_vain: 0,
                 common_commits: connection_is_stateless.then(Vec::new),
+                common_commits_seen: Default::default(),
             }
         }
     }
@@ -458,8 +461,10 @@ pub fn one_round(
                 Acknowledgement::Common(id) => {
                     seen_ack = true;
                     negotiator.in_common_with_remote(*id, graph)?;
-                    if let Some(common) = &mut state.common_commits {
-                        common.push(*id);
+                    if let Some(common) = state.common_commits.as_mut() {
+                        if state.common_commits_seen.insert(*id) {
+                            common.push(*id);
+                        }
                     }
                 }
                 Acknowledgement::Ready => {
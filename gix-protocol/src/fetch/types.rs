@@ -82,6 +82,14 @@ mod with_fetch {
             arguments: &mut fetch::Arguments,
             previous_response: Option<&fetch::Response>,
         ) -> Result<(negotiate::Round, bool), negotiate::Error>;
+        /// Return `true` if the shallow commit `id` sent by the remote exists locally now that the pack was received.
+        ///
+        /// Shallow commits that don't exist aren't written to the shallow file, just like `git` prunes them.
+        /// The default implementation keeps all of them.
+        fn has_shallow_commit(&mut self, id: &gix_hash::oid) -> bool {
+            _ = id;
+            true
+        }
     }
 
     /// The outcome of [`fetch()`](crate::fetch()).
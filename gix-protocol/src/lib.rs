@@ -7,6 +7,7 @@
 //! * execute a [`Command`]
 //!     - [list references](LsRefsCommand)
 //!          - create a mapping between [refspecs and references](fetch::RefMap)
+//!     - [obtain bundles to download](BundleUriCommand), optionally
 //!     - [receive a pack](fetch())
 //!
 //! ## Feature Flags
@@ -30,6 +31,8 @@ pub enum Command {
     LsRefs,
     /// Fetch a pack.
     Fetch,
+    /// Obtain a list of bundles to download before fetching.
+    BundleUri,
 }
 pub mod command;
 
@@ -64,10 +67,18 @@ pub use handshake::function::handshake;
 #[cfg(feature = "handshake")]
 pub use handshake::hero::Handshake;
 
+///
+pub mod bundle_uri;
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use bundle_uri::function::BundleUriCommand;
+
 ///
 pub mod ls_refs;
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub use ls_refs::function::LsRefsCommand;
 
+#[cfg(feature = "server")]
+pub mod server;
+
 mod util;
 pub use util::*;
@@ -0,0 +1,29 @@
+use std::{cell::RefCell, io::Write};
+
+use gix_packetline::{Channel, blocking_io::encode};
+
+/// The largest amount of data per packet line with `side-band-64k`, which is also used in protocol version 2.
+pub(crate) const LARGE_BAND_SIZE: usize = 65520 - 4 - 1;
+/// The largest amount of data per packet line with `side-band`.
+pub(crate) const SMALL_BAND_SIZE: usize = 1000 - 4 - 1;
+
+/// A writer to send data on a sideband `channel` in packet lines with up to `size` bytes.
+pub(crate) struct Band<'a, 'b> {
+    pub out: &'a RefCell<&'b mut dyn Write>,
+    pub channel: Channel,
+    pub size: usize,
+}
+
+impl Write for Band<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut out = self.out.borrow_mut();
+        for chunk in buf.chunks(self.size) {
+            encode::band_to_write(self.channel, chunk, &mut **out)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.borrow_mut().flush()
+    }
+}
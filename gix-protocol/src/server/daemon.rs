@@ -0,0 +1,166 @@
+//! Serving repositories over `git://` connections, like `git daemon` does.
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use bstr::{BString, ByteSlice};
+use gix_packetline::{PacketLineRef, blocking_io::encode};
+
+use super::{Handler, Mode, Request, lines::Lines};
+
+///
+pub mod parse {
+    use bstr::BString;
+
+    /// The error returned by [`parse_request()`](super::parse_request()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error("The request {request:?} did not name a service and a path separated by a space")]
+        Malformed { request: BString },
+        #[error("Unknown service '{name}'")]
+        UnknownService { name: BString },
+        #[error("The host {host:?} could not be parsed")]
+        InvalidHost { host: BString },
+    }
+}
+
+/// The error returned by [`serve_connection()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not read the request line")]
+    ReadRequest(#[source] std::io::Error),
+    #[error("The request line was not a data line")]
+    MissingRequest,
+    #[error(transparent)]
+    Parse(#[from] parse::Error),
+    #[error("Failed to serve {path:?}")]
+    Handle {
+        path: BString,
+        #[source]
+        source: super::Error,
+    },
+}
+
+/// Parse the `line` sent as first packet line by `git://` clients, like `git-upload-pack /project.git\0host=example.com\0\0version=2\0`.
+pub fn parse_request(line: &[u8]) -> Result<Request, parse::Error> {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let (command, rest) = line.split_once_str(b"\0").unwrap_or((line, &[]));
+    let (service, path) = command
+        .split_once_str(b" ")
+        .filter(|(_, path)| !path.is_empty())
+        .ok_or_else(|| parse::Error::Malformed { request: line.into() })?;
+    let service =
+        super::service_from_name(service).ok_or_else(|| parse::Error::UnknownService { name: service.into() })?;
+
+    let mut host = None;
+    let mut fields = rest.split_str(b"\0");
+    for field in fields.by_ref() {
+        match field.strip_prefix(b"host=") {
+            Some(value) => host = Some(parse_host(value)?),
+            // The empty field separates the extra parameters.
+            None if field.is_empty() => break,
+            None => {}
+        }
+    }
+    Ok(Request {
+        service,
+        path: path.into(),
+        protocol: super::protocol_from_parameters(fields.map(ByteSlice::as_bstr)),
+        host,
+    })
+}
+
+fn parse_host(value: &[u8]) -> Result<(BString, Option<u16>), parse::Error> {
+    let invalid = || parse::Error::InvalidHost { host: value.into() };
+    let (host, port) = match value.strip_prefix(b"[") {
+        Some(bracketed) => {
+            let (host, rest) = bracketed.split_once_str(b"]").ok_or_else(invalid)?;
+            match rest {
+                [] => (host, None),
+                [b':', port @ ..] => (host, Some(port)),
+                _ => return Err(invalid()),
+            }
+        }
+        None => match value.split_once_str(b":") {
+            Some((host, port)) => (host, Some(port)),
+            None => (value, None),
+        },
+    };
+    let port = port
+        .map(|port| {
+            port.to_str()
+                .ok()
+                .and_then(|port| port.parse().ok())
+                .ok_or_else(invalid)
+        })
+        .transpose()?;
+    Ok((host.into(), port))
+}
+
+/// Serve a single `git://` connection by reading the request line from `input` and passing it to `handler`,
+/// which converses with the client through `input` and `output`, which is flushed in the end.
+///
+/// Errors are also sent to the client as `ERR` packet line, which it shows to the user.
+pub fn serve_connection(handler: &dyn Handler, input: &mut dyn Read, output: &mut dyn Write) -> Result<(), Error> {
+    let res = (|| {
+        let request = match Lines::new(input).next().map_err(Error::ReadRequest)? {
+            Some(PacketLineRef::Data(line)) => parse_request(line)?,
+            _ => return Err(Error::MissingRequest),
+        };
+        handler
+            .handle(&request, Mode::Stateful, input, output)
+            .and_then(|()| output.flush().map_err(Into::into))
+            .map_err(|source| Error::Handle {
+                path: request.path,
+                source,
+            })
+    })();
+    if let Err(err) = &res {
+        let message = match err {
+            Error::Handle { source, .. } => source.to_string(),
+            err => err.to_string(),
+        };
+        encode::error_to_write(message.as_bytes(), &mut *output).ok();
+        output.flush().ok();
+    }
+    res
+}
+
+/// Accept connections on `listener` and [serve](serve_connection()) each one on its own thread using `handler`.
+///
+/// Connections that can't be accepted are logged and skipped, like `git daemon` does. This function returns once
+/// `should_interrupt` was set when the next connection is accepted or fails to be accepted, after all connections have been served.
+pub fn serve(listener: &TcpListener, handler: &(dyn Handler + Sync), should_interrupt: &AtomicBool) {
+    std::thread::scope(|scope| {
+        loop {
+            let accepted = listener.accept();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return;
+            }
+            let (stream, _peer) = match accepted {
+                Ok(connection) => connection,
+                Err(_err) => {
+                    gix_trace::warn!("Could not accept connection: {_err}");
+                    continue;
+                }
+            };
+            scope.spawn(move || {
+                let mut input = match stream.try_clone() {
+                    Ok(input) => input,
+                    Err(_err) => {
+                        gix_trace::warn!("Could not clone connection from {_peer}: {_err}");
+                        return;
+                    }
+                };
+                let mut output = std::io::BufWriter::new(stream);
+                if let Err(_err) = serve_connection(handler, &mut input, &mut output) {
+                    gix_trace::warn!("Failed to serve connection from {_peer}: {_err}");
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,204 @@
+//! Serving repositories via smart-HTTP, independently of the HTTP server that receives the requests.
+use std::io::{BufRead, Read, Write};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_packetline::blocking_io::encode;
+use gix_transport::Service;
+
+use super::{Handler, Mode};
+
+/// The parts of an HTTP request needed to serve it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Request<'a> {
+    /// The request method, like `GET` or `POST`.
+    pub method: &'a str,
+    /// The path of the URL relative to the location at which repositories are served, like `/project.git/info/refs`.
+    pub path: &'a BStr,
+    /// The query of the URL without the leading `?`, like `service=git-upload-pack`.
+    pub query: Option<&'a str>,
+    /// The value of the `Git-Protocol` header, like `version=2`.
+    pub git_protocol: Option<&'a str>,
+    /// The value of the `Content-Type` header.
+    pub content_type: Option<&'a str>,
+    /// The value of the `Content-Encoding` header, like `gzip`.
+    pub content_encoding: Option<&'a str>,
+}
+
+/// The status and headers of the response to a [`Request`], whose body was written by [`handle()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The HTTP status code, like `200`.
+    pub status: u16,
+    /// The headers to send, like `("Content-Type", "application/x-git-upload-pack-result")`.
+    pub headers: Vec<(&'static str, String)>,
+}
+
+impl Response {
+    fn new(status: u16, content_type: impl Into<String>) -> Self {
+        Response {
+            status,
+            headers: vec![
+                ("Content-Type", content_type.into()),
+                ("Cache-Control", "no-cache, max-age=0, must-revalidate".into()),
+                ("Pragma", "no-cache".into()),
+                ("Expires", "Fri, 01 Jan 1980 00:00:00 GMT".into()),
+            ],
+        }
+    }
+
+    fn error(status: u16, message: &str, out: &mut dyn Write) -> Self {
+        out.write_all(message.as_bytes()).ok();
+        out.write_all(b"\n").ok();
+        Response::new(status, "text/plain")
+    }
+}
+
+/// Serve `request` with `handler`, reading the request body from `body` and writing the response body to `out`.
+///
+/// Requests for `…/info/refs?service=git-upload-pack` receive the advertisement, and requests to `…/git-upload-pack`
+/// are answered without it. The same is true for `git-receive-pack`.
+/// The path of the repository is passed to `handler` without the suffix identifying the endpoint, like `/project.git`.
+/// Note that the `dumb` HTTP protocol isn't supported.
+///
+/// As the returned status is only known once the body was written, `out` should buffer it if the status has to be sent first.
+/// Errors that occur after the handler started writing are sent as `ERR` packet line.
+pub fn handle(request: &Request<'_>, handler: &dyn Handler, body: &mut dyn Read, out: &mut dyn Write) -> Response {
+    let (repository, service, mode) = match route(request) {
+        Ok(route) => route,
+        Err((status, message)) => return Response::error(status, &message, out),
+    };
+    let mut gzip_body;
+    let body: &mut dyn Read = match request.content_encoding {
+        None | Some("identity") => body,
+        Some("gzip" | "x-gzip") => {
+            gzip_body = Gunzip {
+                inner: std::io::BufReader::new(body),
+                state: gix_zlib::Decompress::new_gzip(),
+            };
+            &mut gzip_body
+        }
+        Some(encoding) => {
+            return Response::error(415, &format!("Content-Encoding '{encoding}' is not supported"), out);
+        }
+    };
+
+    let server_request = super::Request {
+        service,
+        path: repository,
+        protocol: super::protocol_from_parameters(
+            request
+                .git_protocol
+                .into_iter()
+                .flat_map(|value| value.split(':'))
+                .map(Into::into),
+        ),
+        host: None,
+    };
+    let mut out = Output {
+        inner: out,
+        preamble: Vec::new(),
+        written: false,
+    };
+    if mode == Mode::AdvertiseOnly {
+        encode::text_to_write(format!("# service={}", service.as_str()).as_bytes(), &mut out.preamble)
+            .expect("writing to memory works");
+        encode::flush_to_write(&mut out.preamble).expect("writing to memory works");
+    }
+    let res = handler.handle(&server_request, mode, body, &mut out);
+
+    let content_type = match mode {
+        Mode::AdvertiseOnly => format!("application/x-{}-advertisement", service.as_str()),
+        Mode::Stateless | Mode::Stateful => format!("application/x-{}-result", service.as_str()),
+    };
+    match res {
+        Ok(()) => Response::new(200, content_type),
+        Err(err) if !out.written => {
+            use super::Error;
+            let status = match &err {
+                Error::NotFound { .. } => 404,
+                Error::ServiceDisabled { .. } => 403,
+                Error::UploadPack(_) | Error::ReceivePack(_) => 400,
+                Error::Io(_) | Error::Other(_) => 500,
+            };
+            Response::error(status, &err.to_string(), out.inner)
+        }
+        Err(err) => {
+            encode::error_to_write(err.to_string().as_bytes(), &mut out).ok();
+            Response::new(200, content_type)
+        }
+    }
+}
+
+/// Return the repository path, the service and the mode for `request`, or the status and message of the error response.
+fn route(request: &Request<'_>) -> Result<(BString, Service, Mode), (u16, String)> {
+    let not_found = || (404, format!("{} not found", request.path));
+    let (repository, endpoint) = request.path.rsplit_once_str(b"/").ok_or_else(not_found)?;
+    if endpoint == b"refs" {
+        let repository = repository.strip_suffix(b"/info").ok_or_else(not_found)?;
+        expect_method(request, "GET")?;
+        let service = request
+            .query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .find_map(|param| param.strip_prefix("service="))
+            .ok_or_else(|| (403, "The 'dumb' HTTP protocol isn't supported".to_owned()))?;
+        let service = super::service_from_name(service.as_bytes())
+            .ok_or_else(|| (403, format!("Unsupported service '{service}'")))?;
+        return Ok((repository.into(), service, Mode::AdvertiseOnly));
+    }
+
+    let service = super::service_from_name(endpoint).ok_or_else(not_found)?;
+    expect_method(request, "POST")?;
+    let expected_content_type = format!("application/x-{}-request", service.as_str());
+    if request.content_type != Some(expected_content_type.as_str()) {
+        return Err((415, format!("Expected Content-Type '{expected_content_type}'")));
+    }
+    Ok((repository.into(), service, Mode::Stateless))
+}
+
+fn expect_method(request: &Request<'_>, method: &str) -> Result<(), (u16, String)> {
+    if request.method == method {
+        Ok(())
+    } else {
+        Err((
+            405,
+            format!("Method '{}' is not allowed, expected '{method}'", request.method),
+        ))
+    }
+}
+
+/// Decompress the `gzip` stream read from `inner`.
+struct Gunzip<R> {
+    inner: std::io::BufReader<R>,
+    state: gix_zlib::Decompress,
+}
+
+impl<R: Read> Read for Gunzip<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || self.inner.fill_buf()?.is_empty() {
+            return Ok(0);
+        }
+        gix_zlib::stream::inflate::read(&mut self.inner, &mut self.state, buf)
+    }
+}
+
+/// Write the `preamble` before the first byte written by the handler, and remember if anything was written yet.
+struct Output<'a> {
+    inner: &'a mut dyn Write,
+    preamble: Vec<u8>,
+    written: bool,
+}
+
+impl Write for Output<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.written {
+            self.inner.write_all(&self.preamble)?;
+            self.written = true;
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,58 @@
+use std::io::{Read, Write};
+
+use gix_packetline::{
+    PacketLineRef,
+    blocking_io::encode,
+    decode::{self, PacketLineOrWantedSize},
+};
+
+/// Read packet lines one at a time without reading ahead, so the input can be handed on at any time.
+pub(crate) struct Lines<'a> {
+    input: &'a mut dyn Read,
+    buf: Vec<u8>,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(input: &'a mut dyn Read) -> Self {
+        Lines { input, buf: Vec::new() }
+    }
+
+    /// Return the next line, or `None` if the input ended before it.
+    pub fn next(&mut self) -> std::io::Result<Option<PacketLineRef<'_>>> {
+        let mut prefix = [0; 4];
+        if self.input.read(&mut prefix[..1])? == 0 {
+            return Ok(None);
+        }
+        self.input.read_exact(&mut prefix[1..])?;
+        let len = match decode::hex_prefix(&prefix).map_err(std::io::Error::other)? {
+            PacketLineOrWantedSize::Line(line) => {
+                return Ok(Some(match line {
+                    PacketLineRef::Flush => PacketLineRef::Flush,
+                    PacketLineRef::Delimiter => PacketLineRef::Delimiter,
+                    PacketLineRef::ResponseEnd => PacketLineRef::ResponseEnd,
+                    PacketLineRef::Data(_) => unreachable!("prefixes never decode to data lines"),
+                }));
+            }
+            PacketLineOrWantedSize::Wanted(len) => len as usize,
+        };
+        self.buf.resize(len, 0);
+        self.input.read_exact(&mut self.buf)?;
+        Ok(Some(PacketLineRef::Data(&self.buf)))
+    }
+
+    /// Like [`next()`](Self::next()), but turns the end of input into an error.
+    pub fn next_required(&mut self) -> std::io::Result<PacketLineRef<'_>> {
+        self.next()?
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "the client hung up unexpectedly"))
+    }
+}
+
+/// Write `line` as text packet line to `out`.
+pub(crate) fn text(out: &mut dyn Write, line: impl AsRef<[u8]>) -> std::io::Result<()> {
+    encode::text_to_write(line.as_ref(), out).map(|_| ())
+}
+
+/// Return `line` without its trailing newline.
+pub(crate) fn trim(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\n").unwrap_or(line)
+}
@@ -0,0 +1,93 @@
+//! The server side of the protocol, to serve repositories to `git` clients.
+//!
+//! A server receives a [`Request`] for a [service](Service), either from a [`git://` connection](daemon) or via
+//! [smart-HTTP](http), and passes it to a [`Handler`] provided by the application, which typically locates
+//! the repository and then lets [`upload_pack::serve()`] or [`receive_pack::serve()`] talk to the client.
+use bstr::{BStr, BString};
+use gix_transport::{Protocol, Service};
+
+/// A request for a service as made by a client, independently of how it reached the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// The service the client wants to use.
+    pub service: Service,
+    /// The path to the repository as sent by the client, like `/project.git`.
+    pub path: BString,
+    /// The protocol version requested by the client, which is [V0](Protocol::V0) if it didn't ask for a particular version.
+    pub protocol: Protocol,
+    /// The host and port the client connected to, if it sent it.
+    pub host: Option<(BString, Option<u16>)>,
+}
+
+/// The way the conversation with the client is structured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Send the advertisement, and then answer requests until the client hangs up, as over `git://` connections.
+    Stateful,
+    /// Only send the advertisement, as for `GET …/info/refs` requests.
+    AdvertiseOnly,
+    /// Answer a single request without sending the advertisement first, as for `POST …/git-upload-pack` requests.
+    Stateless,
+}
+
+/// The implementation of services provided by the application hosting the repositories.
+pub trait Handler {
+    /// Perform `request` in the given `mode` by reading the client's messages from `input` and writing responses to `output`.
+    fn handle(
+        &self,
+        request: &Request,
+        mode: Mode,
+        input: &mut dyn std::io::Read,
+        output: &mut dyn std::io::Write,
+    ) -> Result<(), Error>;
+}
+
+/// The error returned by [`Handler::handle()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("access denied or repository not exported: {path}")]
+    NotFound { path: BString },
+    #[error("service not enabled: '{}'", service.as_git_subcommand())]
+    ServiceDisabled { service: Service },
+    #[error(transparent)]
+    UploadPack(#[from] upload_pack::Error),
+    #[error(transparent)]
+    ReceivePack(#[from] receive_pack::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+/// Return the highest protocol version mentioned in `parameters` like `version=2`, or [V0](Protocol::V0).
+fn protocol_from_parameters<'a>(parameters: impl IntoIterator<Item = &'a BStr>) -> Protocol {
+    parameters
+        .into_iter()
+        .filter_map(|param| match param.strip_prefix(b"version=")? {
+            b"1" => Some(Protocol::V1),
+            b"2" => Some(Protocol::V2),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(Protocol::V0)
+}
+
+/// Return the service named like `git-upload-pack`.
+fn service_from_name(name: &[u8]) -> Option<Service> {
+    [Service::UploadPack, Service::ReceivePack]
+        .into_iter()
+        .find(|service| service.as_str().as_bytes() == name)
+}
+
+///
+pub mod daemon;
+///
+pub mod http;
+///
+pub mod receive_pack;
+///
+pub mod upload_pack;
+
+mod band;
+mod lines;
@@ -0,0 +1,227 @@
+use std::{
+    cell::RefCell,
+    io::{BufReader, Read, Write},
+};
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+use gix_packetline::{Channel, PacketLineRef, blocking_io::encode};
+use gix_transport::Protocol;
+
+use super::{Command, Error, Options, Target, Update};
+use crate::{
+    handshake::Ref,
+    server::{
+        Mode,
+        band::{Band, LARGE_BAND_SIZE, SMALL_BAND_SIZE},
+        lines::{Lines, text, trim},
+    },
+};
+
+/// Talk to a client that wants to push into `target` in the given `protocol` version and `mode`,
+/// reading its messages and the pack from `input` and writing responses to `output`, configured by `options`.
+///
+/// As there is no protocol version 2 for pushes, clients asking for it are served with version 0 like `git` does.
+/// Pushes from shallow repositories aren't supported.
+pub fn serve(
+    target: &dyn Target,
+    protocol: Protocol,
+    mode: Mode,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    options: &Options,
+) -> Result<(), Error> {
+    let object_hash = target.object_hash();
+    if mode != Mode::Stateless {
+        if protocol == Protocol::V1 {
+            text(output, "version 1")?;
+        }
+        advertise(&target.refs().map_err(Error::ListRefs)?, object_hash, options, output)?;
+        output.flush()?;
+        if mode == Mode::AdvertiseOnly {
+            return Ok(());
+        }
+    }
+
+    let mut lines = Lines::new(input);
+    let mut commands = Vec::new();
+    let mut capabilities = Vec::<BString>::new();
+    loop {
+        let line = match lines.next()? {
+            // The client only wanted to see the advertisement, or had nothing to push.
+            None if commands.is_empty() => return Ok(()),
+            Some(PacketLineRef::Flush) => break,
+            Some(PacketLineRef::Data(line)) => trim(line),
+            line => return Err(unexpected(line)),
+        };
+        if line.starts_with(b"shallow ") {
+            return Err(Error::UnsupportedArgument { argument: line.into() });
+        }
+        let (command, features) = line.split_once_str(b"\0").unwrap_or((line, b""));
+        if commands.is_empty() {
+            capabilities.extend(features.fields().map(Into::into));
+        }
+        commands.push(parse_command(command)?);
+    }
+    if commands.is_empty() {
+        return Ok(());
+    }
+    let has = |name: &str| capabilities.iter().any(|capability| capability == name);
+    if let Some(requested) = capabilities
+        .iter()
+        .find_map(|capability| capability.strip_prefix(b"object-format="))
+    {
+        if requested != object_hash.to_string().as_bytes() {
+            return Err(Error::ObjectFormatMismatch {
+                requested: requested.into(),
+                actual: object_hash,
+            });
+        }
+    }
+    let mut push_options = Vec::new();
+    if has("push-options") {
+        loop {
+            match lines.next_required()? {
+                PacketLineRef::Flush => break,
+                PacketLineRef::Data(option) => push_options.push(BString::from(trim(option))),
+                line => return Err(unexpected(Some(line))),
+            }
+        }
+    }
+
+    let update = Update {
+        commands: &commands,
+        push_options: &push_options,
+        atomic: has("atomic"),
+        quiet: has("quiet"),
+    };
+    let band_size = if has("side-band-64k") {
+        Some(LARGE_BAND_SIZE)
+    } else if has("side-band") {
+        Some(SMALL_BAND_SIZE)
+    } else {
+        None
+    };
+    let outcome = {
+        let out = RefCell::new(&mut *output as &mut dyn Write);
+        let mut progress_band;
+        let mut sink = std::io::sink();
+        let progress: &mut dyn Write = match band_size {
+            Some(size) => {
+                // Send whole lines to avoid many tiny packets.
+                progress_band = std::io::LineWriter::new(Band {
+                    out: &out,
+                    channel: Channel::Progress,
+                    size,
+                });
+                &mut progress_band
+            }
+            None => &mut sink,
+        };
+        // Objects are only sent if at least one reference isn't deleted.
+        let mut pack = BufReader::new(input);
+        let pack = commands
+            .iter()
+            .any(|command| !command.is_delete())
+            .then_some(&mut pack as &mut dyn std::io::BufRead);
+        target.receive(&update, pack, progress).map_err(Error::Receive)?
+    };
+    if outcome.commands.len() != commands.len() {
+        return Err(Error::OutcomeMismatch {
+            expected: commands.len(),
+            actual: outcome.commands.len(),
+        });
+    }
+
+    let report_v2 = has("report-status-v2");
+    if report_v2 || has("report-status") {
+        let mut report = Vec::new();
+        match &outcome.unpack_error {
+            None => text(&mut report, "unpack ok")?,
+            Some(err) => text(&mut report, format!("unpack {err}"))?,
+        }
+        for (command, result) in commands.iter().zip(&outcome.commands) {
+            match result {
+                Ok(()) => {
+                    text(&mut report, format!("ok {}", command.name))?;
+                    // References are never renamed, so unlike `old-oid` and `new-oid`, `refname` is left out like `git` does.
+                    if report_v2 {
+                        text(&mut report, format!("option old-oid {}", command.old))?;
+                        text(&mut report, format!("option new-oid {}", command.new))?;
+                    }
+                }
+                Err(reason) => text(&mut report, format!("ng {} {reason}", command.name))?,
+            }
+        }
+        encode::flush_to_write(&mut report)?;
+        match band_size {
+            Some(size) => {
+                for chunk in report.chunks(size) {
+                    encode::band_to_write(Channel::Data, chunk, &mut *output)?;
+                }
+            }
+            None => output.write_all(&report)?,
+        }
+    }
+    if band_size.is_some() {
+        encode::flush_to_write(&mut *output)?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+fn advertise(refs: &[Ref], object_hash: gix_hash::Kind, options: &Options, out: &mut dyn Write) -> Result<(), Error> {
+    let capabilities = format!(
+        "report-status report-status-v2 delete-refs side-band-64k quiet atomic ofs-delta push-options object-format={object_hash} agent={}",
+        options.agent
+    );
+    let mut lines = refs.iter().filter_map(|r| match r {
+        Ref::Direct { full_ref_name, object }
+        | Ref::Peeled {
+            full_ref_name,
+            tag: object,
+            ..
+        } => full_ref_name.starts_with(b"refs/").then_some((object, full_ref_name)),
+        Ref::Symbolic { .. } | Ref::Unborn { .. } => None,
+    });
+    match lines.next() {
+        Some((id, name)) => text(out, format!("{id} {name}\0{capabilities}"))?,
+        None => text(out, format!("{} capabilities^{{}}\0{capabilities}", object_hash.null()))?,
+    }
+    for (id, name) in lines {
+        text(out, format!("{id} {name}"))?;
+    }
+    encode::flush_to_write(out)?;
+    Ok(())
+}
+
+/// Parse `line` like `<old> <new> <name>`.
+fn parse_command(line: &[u8]) -> Result<Command, Error> {
+    let mut fields = line.splitn(3, |b| *b == b' ');
+    let (Some(old), Some(new), Some(name)) = (fields.next(), fields.next(), fields.next()) else {
+        return Err(Error::UnexpectedLine { line: line.into() });
+    };
+    let parse_id = |hex: &[u8]| {
+        ObjectId::from_hex(hex).map_err(|source| Error::DecodeObjectId {
+            line: line.into(),
+            source,
+        })
+    };
+    Ok(Command {
+        old: parse_id(old)?,
+        new: parse_id(new)?,
+        name: name.into(),
+    })
+}
+
+fn unexpected(line: Option<PacketLineRef<'_>>) -> Error {
+    match line {
+        None => std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "the client hung up unexpectedly").into(),
+        Some(line) => Error::UnexpectedLine {
+            line: match line.as_slice() {
+                Some(data) => data.as_bstr().to_owned(),
+                None => format!("{line:?}").into(),
+            },
+        },
+    }
+}
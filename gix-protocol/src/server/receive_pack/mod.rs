@@ -0,0 +1,109 @@
+//! The `receive-pack` service, which receives packs and reference updates from clients that push.
+use bstr::BString;
+use gix_hash::ObjectId;
+
+use crate::handshake::Ref;
+
+/// The repository into which [`serve()`] receives pushes.
+pub trait Target {
+    /// The kind of hash used by all objects of the repository.
+    fn object_hash(&self) -> gix_hash::Kind;
+
+    /// Return all references that may be advertised, in the order they should be advertised.
+    ///
+    /// Only references starting with `refs/` are advertised, and annotated tags aren't peeled.
+    fn refs(&self) -> Result<Vec<Ref>, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+    /// Apply `update`, after reading the pack with all objects needed by it from `pack`, while sending human-readable
+    /// messages for the user to `progress`, like the output of hooks.
+    ///
+    /// `pack` is `None` if all commands delete references, and it must not be read beyond the end of the pack otherwise.
+    /// Failures to receive the pack or to update references are reported to the client via the returned [`Outcome`],
+    /// while errors are considered fatal and end the conversation.
+    fn receive(
+        &self,
+        update: &Update<'_>,
+        pack: Option<&mut dyn std::io::BufRead>,
+        progress: &mut dyn std::io::Write,
+    ) -> Result<Outcome, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
+/// A request to change a reference as sent by the client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Command {
+    /// The id the reference is expected to point to, or the null id if it is expected to not exist.
+    pub old: ObjectId,
+    /// The id the reference should point to, or the null id if it should be deleted.
+    pub new: ObjectId,
+    /// The full name of the reference, like `refs/heads/main`.
+    pub name: BString,
+}
+
+impl Command {
+    /// Return `true` if the reference should be created.
+    pub fn is_create(&self) -> bool {
+        self.old.is_null()
+    }
+
+    /// Return `true` if the reference should be deleted.
+    pub fn is_delete(&self) -> bool {
+        self.new.is_null()
+    }
+}
+
+/// All changes the client wants to make to the repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Update<'a> {
+    /// The changes to the references, in the order they were sent.
+    pub commands: &'a [Command],
+    /// The options passed with `git push --push-option`.
+    pub push_options: &'a [BString],
+    /// If `true`, either all commands have to succeed, or none of them may be applied.
+    pub atomic: bool,
+    /// If `true`, the client doesn't want to see progress messages, while other messages should still be sent.
+    pub quiet: bool,
+}
+
+/// The result of [`Target::receive()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The reason for why the pack couldn't be received, or `None` if it was received or if there was none.
+    pub unpack_error: Option<BString>,
+    /// The result of each command of the [`Update`] in the same order, with `Err` holding the reason for rejecting it.
+    pub commands: Vec<Result<(), BString>>,
+}
+
+/// Options for use in [`serve()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The name of the server to advertise to clients, like `git/oxide-1.0`.
+    pub agent: String,
+}
+
+/// The error returned by [`serve()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Could not list references")]
+    ListRefs(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("Could not receive the push")]
+    Receive(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("Unexpected line from the client: {line:?}")]
+    UnexpectedLine { line: BString },
+    #[error("Unsupported argument '{argument}'")]
+    UnsupportedArgument { argument: BString },
+    #[error("The object id in {line:?} could not be decoded")]
+    DecodeObjectId {
+        line: BString,
+        source: gix_hash::decode::Error,
+    },
+    #[error("The client uses the object format '{requested}', but the repository uses '{actual}'")]
+    ObjectFormatMismatch { requested: BString, actual: gix_hash::Kind },
+    #[error("The receiver reported {actual} results for {expected} commands")]
+    OutcomeMismatch { expected: usize, actual: usize },
+}
+
+mod function;
+pub use function::serve;
@@ -0,0 +1,541 @@
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    io::{Read, Write},
+};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::ObjectId;
+use gix_packetline::Channel;
+use gix_transport::Protocol;
+
+use super::{Error, Options, Pack, Source};
+use crate::{
+    handshake::Ref,
+    server::{
+        Mode,
+        band::{Band, LARGE_BAND_SIZE, SMALL_BAND_SIZE},
+        lines::{text, trim},
+    },
+};
+
+/// Talk to a client that wants to fetch from `source` in the given `protocol` version and `mode`,
+/// reading its messages from `input` and writing responses to `output`, configured by `options`.
+///
+/// Only wants for objects pointed to by the advertised references are honored, and clients can't request shallow clones
+/// or filters.
+pub fn serve(
+    source: &dyn Source,
+    protocol: Protocol,
+    mode: Mode,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    options: &Options,
+) -> Result<(), Error> {
+    match protocol {
+        Protocol::V2 => v2::serve(source, mode, input, output, options),
+        Protocol::V0 | Protocol::V1 => v1::serve(source, protocol, mode, input, output, options),
+    }
+}
+
+mod v1 {
+    use std::{
+        collections::HashSet,
+        io::{Read, Write},
+    };
+
+    use bstr::{BString, ByteSlice};
+    use gix_packetline::{PacketLineRef, blocking_io::encode};
+    use gix_transport::Protocol;
+
+    use super::{
+        LARGE_BAND_SIZE, SMALL_BAND_SIZE, advertised, check_wants, parse_id, send_pack, text, trim, unexpected,
+    };
+    use crate::{
+        handshake::Ref,
+        server::{
+            Mode,
+            lines::Lines,
+            upload_pack::{Error, Options, Pack, Source},
+        },
+    };
+
+    pub(super) fn serve(
+        source: &dyn Source,
+        protocol: Protocol,
+        mode: Mode,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+        options: &Options,
+    ) -> Result<(), Error> {
+        let refs = source.refs().map_err(Error::ListRefs)?;
+        if mode != Mode::Stateless {
+            if protocol == Protocol::V1 {
+                text(output, "version 1")?;
+            }
+            advertise(&refs, source.object_hash(), options, output)?;
+            output.flush()?;
+            if mode == Mode::AdvertiseOnly {
+                return Ok(());
+            }
+        }
+
+        let mut lines = Lines::new(input);
+        let mut wants = Vec::new();
+        let mut capabilities = Vec::<BString>::new();
+        loop {
+            let line = match lines.next()? {
+                // The client only wanted to see the advertisement.
+                None if wants.is_empty() => return Ok(()),
+                Some(PacketLineRef::Flush) => break,
+                Some(PacketLineRef::Data(line)) => trim(line),
+                line => return Err(unexpected(line)),
+            };
+            let Some(want) = line.strip_prefix(b"want ") else {
+                return Err(
+                    if line.starts_with(b"shallow ") || line.starts_with(b"deepen") || line.starts_with(b"filter ") {
+                        Error::UnsupportedArgument { argument: line.into() }
+                    } else {
+                        Error::UnexpectedLine { line: line.into() }
+                    },
+                );
+            };
+            let (hex, features) = want.split_once_str(b" ").unwrap_or((want, b""));
+            wants.push(parse_id(line, hex)?);
+            if wants.len() == 1 {
+                capabilities.extend(features.fields().map(Into::into));
+            }
+        }
+        if wants.is_empty() {
+            return Ok(());
+        }
+        check_wants(&refs, &wants)?;
+
+        let has = |name: &str| capabilities.iter().any(|capability| capability == name);
+        let multi_ack = if has("multi_ack_detailed") {
+            2
+        } else {
+            u8::from(has("multi_ack"))
+        };
+        let mut common = Vec::new();
+        let mut seen = HashSet::new();
+        loop {
+            let line = match lines.next_required()? {
+                PacketLineRef::Flush => {
+                    if common.is_empty() || multi_ack > 0 {
+                        text(output, "NAK")?;
+                    }
+                    output.flush()?;
+                    if mode == Mode::Stateless {
+                        return Ok(());
+                    }
+                    continue;
+                }
+                PacketLineRef::Data(line) => trim(line),
+                line => return Err(unexpected(Some(line))),
+            };
+            if line == b"done" {
+                break;
+            }
+            let Some(hex) = line.strip_prefix(b"have ") else {
+                return Err(Error::UnexpectedLine { line: line.into() });
+            };
+            let id = parse_id(line, hex)?;
+            if seen.insert(id) && source.contains(&id) {
+                common.push(id);
+                match multi_ack {
+                    2 => text(output, format!("ACK {id} common"))?,
+                    1 => text(output, format!("ACK {id} continue"))?,
+                    _ if common.len() == 1 => text(output, format!("ACK {id}"))?,
+                    _ => {}
+                }
+            }
+        }
+        match common.last() {
+            Some(last) if multi_ack > 0 => text(output, format!("ACK {last}"))?,
+            Some(_) => {}
+            None => text(output, "NAK")?,
+        }
+
+        let band_size = if has("side-band-64k") {
+            Some(LARGE_BAND_SIZE)
+        } else if has("side-band") {
+            Some(SMALL_BAND_SIZE)
+        } else {
+            None
+        };
+        let pack = Pack {
+            wants: &wants,
+            haves: &common,
+            include_tag: has("include-tag"),
+            ofs_delta: has("ofs-delta"),
+            thin: has("thin-pack"),
+        };
+        send_pack(source, &pack, band_size, !has("no-progress"), output)?;
+        if band_size.is_some() {
+            encode::flush_to_write(&mut *output)?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    fn advertise(
+        refs: &[Ref],
+        object_hash: gix_hash::Kind,
+        options: &Options,
+        out: &mut dyn Write,
+    ) -> Result<(), Error> {
+        let mut capabilities = vec![
+            "multi_ack_detailed multi_ack side-band-64k side-band ofs-delta thin-pack no-progress include-tag"
+                .to_owned(),
+        ];
+        if let Some(Ref::Symbolic { target, .. }) = refs
+            .iter()
+            .find(|r| matches!(r, Ref::Symbolic { full_ref_name, .. } if full_ref_name == "HEAD"))
+        {
+            capabilities.push(format!("symref=HEAD:{target}"));
+        }
+        capabilities.push(format!("object-format={object_hash}"));
+        capabilities.push(format!("agent={}", options.agent));
+        let capabilities = capabilities.join(" ");
+
+        let mut lines = refs.iter().flat_map(advertised);
+        match lines.next() {
+            Some((id, name)) => text(out, format!("{id} {name}\0{capabilities}"))?,
+            None => text(out, format!("{} capabilities^{{}}\0{capabilities}", object_hash.null()))?,
+        }
+        for (id, name) in lines {
+            text(out, format!("{id} {name}"))?;
+        }
+        encode::flush_to_write(out)?;
+        Ok(())
+    }
+}
+
+mod v2 {
+    use std::{
+        collections::HashSet,
+        io::{Read, Write},
+    };
+
+    use bstr::{BString, ByteVec};
+    use gix_packetline::{PacketLineRef, blocking_io::encode};
+
+    use super::{LARGE_BAND_SIZE, check_wants, parse_id, send_pack, text, trim, unexpected};
+    use crate::{
+        handshake::Ref,
+        server::{
+            Mode,
+            lines::Lines,
+            upload_pack::{Error, Options, Pack, Source},
+        },
+    };
+
+    pub(super) fn serve(
+        source: &dyn Source,
+        mode: Mode,
+        input: &mut dyn Read,
+        output: &mut dyn Write,
+        options: &Options,
+    ) -> Result<(), Error> {
+        let object_hash = source.object_hash();
+        if mode != Mode::Stateless {
+            text(output, "version 2")?;
+            text(output, format!("agent={}", options.agent))?;
+            text(output, "ls-refs=unborn")?;
+            text(output, "fetch")?;
+            text(output, "server-option")?;
+            text(output, format!("object-format={object_hash}"))?;
+            if options.bundle_list.is_some() {
+                text(output, "bundle-uri")?;
+            }
+            encode::flush_to_write(&mut *output)?;
+            output.flush()?;
+            if mode == Mode::AdvertiseOnly {
+                return Ok(());
+            }
+        }
+
+        let mut lines = Lines::new(input);
+        loop {
+            let mut command = None;
+            let mut args = Vec::<BString>::new();
+            let mut is_first_line = true;
+            loop {
+                let line = match lines.next()? {
+                    // The client hung up, or ended the interaction with an empty request.
+                    None | Some(PacketLineRef::Flush) if is_first_line => return Ok(()),
+                    None => return Err(unexpected(None)),
+                    Some(PacketLineRef::Flush) => break,
+                    Some(PacketLineRef::Delimiter) => {
+                        loop {
+                            match lines.next_required()? {
+                                PacketLineRef::Flush => break,
+                                PacketLineRef::Data(arg) => args.push(trim(arg).into()),
+                                line => return Err(unexpected(Some(line))),
+                            }
+                        }
+                        break;
+                    }
+                    Some(PacketLineRef::Data(line)) => trim(line),
+                    line => return Err(unexpected(line)),
+                };
+                is_first_line = false;
+                if let Some(name) = line.strip_prefix(b"command=") {
+                    command = Some(BString::from(name));
+                } else if let Some(requested) = line.strip_prefix(b"object-format=") {
+                    if requested != object_hash.to_string().as_bytes() {
+                        return Err(Error::ObjectFormatMismatch {
+                            requested: requested.into(),
+                            actual: object_hash,
+                        });
+                    }
+                }
+            }
+
+            match command.as_ref().map(|name| name.as_slice()) {
+                Some(b"ls-refs") => ls_refs(source, &args, output)?,
+                Some(b"fetch") => fetch(source, &args, output)?,
+                Some(b"bundle-uri") if options.bundle_list.is_some() => {
+                    bundle_uri(options.bundle_list.as_ref(), &args, output)?;
+                }
+                _ => {
+                    return Err(Error::UnknownCommand {
+                        command: command.unwrap_or_default(),
+                    });
+                }
+            }
+            output.flush()?;
+            if mode == Mode::Stateless {
+                return Ok(());
+            }
+        }
+    }
+
+    fn ls_refs(source: &dyn Source, args: &[BString], out: &mut dyn Write) -> Result<(), Error> {
+        let (mut symrefs, mut peel, mut unborn) = (false, false, false);
+        let mut prefixes = Vec::new();
+        for arg in args {
+            match arg.as_slice() {
+                b"symrefs" => symrefs = true,
+                b"peel" => peel = true,
+                b"unborn" => unborn = true,
+                arg => match arg.strip_prefix(b"ref-prefix ") {
+                    Some(prefix) => prefixes.push(prefix),
+                    None => return Err(Error::UnsupportedArgument { argument: arg.into() }),
+                },
+            }
+        }
+
+        for r in source.refs().map_err(Error::ListRefs)? {
+            let (Ref::Direct { full_ref_name, .. }
+            | Ref::Peeled { full_ref_name, .. }
+            | Ref::Symbolic { full_ref_name, .. }
+            | Ref::Unborn { full_ref_name, .. }) = &r;
+            if !prefixes.is_empty() && !prefixes.iter().any(|prefix| full_ref_name.starts_with(prefix)) {
+                continue;
+            }
+            let (id, symref_target, peeled) = match &r {
+                Ref::Direct { object, .. } => (Some(*object), None, None),
+                Ref::Peeled { tag, object, .. } => (Some(*tag), None, Some(*object)),
+                Ref::Symbolic {
+                    target, tag, object, ..
+                } => (Some(tag.unwrap_or(*object)), Some(target), tag.map(|_| *object)),
+                Ref::Unborn { target, .. } => (None, Some(target), None),
+            };
+            let mut line = match id {
+                Some(id) => BString::from(format!("{id} {full_ref_name}")),
+                None if unborn => BString::from(format!("unborn {full_ref_name}")),
+                None => continue,
+            };
+            if let Some(target) = symref_target.filter(|_| symrefs) {
+                line.push_str(" symref-target:");
+                line.push_str(target);
+            }
+            if let Some(object) = peeled.filter(|_| peel) {
+                line.push_str(" peeled:");
+                line.push_str(object.to_hex().to_string());
+            }
+            text(out, line)?;
+        }
+        encode::flush_to_write(out)?;
+        Ok(())
+    }
+
+    fn bundle_uri(list: Option<&crate::bundle_uri::List>, args: &[BString], out: &mut dyn Write) -> Result<(), Error> {
+        if let Some(arg) = args.first() {
+            return Err(Error::UnsupportedArgument { argument: arg.clone() });
+        }
+        for (key, value) in list.map(crate::bundle_uri::List::to_key_values).unwrap_or_default() {
+            let mut line = key;
+            line.push(b'=');
+            line.push_str(value);
+            text(out, line)?;
+        }
+        encode::flush_to_write(out)?;
+        Ok(())
+    }
+
+    fn fetch(source: &dyn Source, args: &[BString], out: &mut dyn Write) -> Result<(), Error> {
+        let (mut wants, mut haves) = (Vec::new(), Vec::new());
+        let (mut done, mut thin, mut progress, mut include_tag, mut ofs_delta) = (false, false, true, false, false);
+        for arg in args {
+            match arg.as_slice() {
+                b"done" => done = true,
+                b"thin-pack" => thin = true,
+                b"no-progress" => progress = false,
+                b"include-tag" => include_tag = true,
+                b"ofs-delta" => ofs_delta = true,
+                arg => {
+                    if let Some(hex) = arg.strip_prefix(b"want ") {
+                        wants.push(parse_id(arg, hex)?);
+                    } else if let Some(hex) = arg.strip_prefix(b"have ") {
+                        haves.push(parse_id(arg, hex)?);
+                    } else {
+                        return Err(Error::UnsupportedArgument { argument: arg.into() });
+                    }
+                }
+            }
+        }
+        if wants.is_empty() {
+            return Err(Error::MissingWants);
+        }
+        check_wants(&source.refs().map_err(Error::ListRefs)?, &wants)?;
+
+        let mut seen = HashSet::new();
+        let common: Vec<_> = haves
+            .into_iter()
+            .filter(|id| seen.insert(*id) && source.contains(id))
+            .collect();
+        if !done {
+            text(out, "acknowledgments")?;
+            if common.is_empty() {
+                text(out, "NAK")?;
+                encode::flush_to_write(out)?;
+                return Ok(());
+            }
+            for id in &common {
+                text(out, format!("ACK {id}"))?;
+            }
+            // Rather than finding out if the wants are reachable from the common objects, we are done once there is any.
+            text(out, "ready")?;
+            encode::delim_to_write(&mut *out)?;
+        }
+
+        text(out, "packfile")?;
+        let pack = Pack {
+            wants: &wants,
+            haves: &common,
+            include_tag,
+            ofs_delta,
+            thin,
+        };
+        send_pack(source, &pack, Some(LARGE_BAND_SIZE), progress, out)?;
+        encode::flush_to_write(out)?;
+        Ok(())
+    }
+}
+
+/// Return the object ids and names to advertise for `r`, including peeled tags.
+fn advertised(r: &Ref) -> Vec<(ObjectId, BString)> {
+    let peeled = |name: &BStr| {
+        let mut name = name.to_owned();
+        name.push_str("^{}");
+        name
+    };
+    match r {
+        Ref::Direct { full_ref_name, object } => vec![(*object, full_ref_name.clone())],
+        Ref::Peeled {
+            full_ref_name,
+            tag,
+            object,
+        }
+        | Ref::Symbolic {
+            full_ref_name,
+            tag: Some(tag),
+            object,
+            ..
+        } => vec![(*tag, full_ref_name.clone()), (*object, peeled(full_ref_name.as_ref()))],
+        Ref::Symbolic {
+            full_ref_name,
+            tag: None,
+            object,
+            ..
+        } => vec![(*object, full_ref_name.clone())],
+        Ref::Unborn { .. } => Vec::new(),
+    }
+}
+
+/// Assure that all `wants` are pointed to by `refs`.
+fn check_wants(refs: &[Ref], wants: &[ObjectId]) -> Result<(), Error> {
+    let tips: HashSet<_> = refs.iter().flat_map(advertised).map(|(id, _)| id).collect();
+    match wants.iter().find(|id| !tips.contains(*id)) {
+        Some(id) => Err(Error::NotOurRef { id: *id }),
+        None => Ok(()),
+    }
+}
+
+/// Send the pack described by `pack` to `out`, multiplexed with progress if `band_size` is set.
+fn send_pack(
+    source: &dyn Source,
+    pack: &Pack<'_>,
+    band_size: Option<usize>,
+    progress: bool,
+    out: &mut dyn Write,
+) -> Result<(), Error> {
+    let Some(band_size) = band_size else {
+        return source
+            .write_pack(pack, out, &mut std::io::sink())
+            .map_err(Error::WritePack);
+    };
+    let out = RefCell::new(out);
+    let res = {
+        let mut data = std::io::BufWriter::with_capacity(
+            band_size,
+            Band {
+                out: &out,
+                channel: Channel::Data,
+                size: band_size,
+            },
+        );
+        let mut progress_band = Band {
+            out: &out,
+            channel: Channel::Progress,
+            size: band_size,
+        };
+        let mut sink = std::io::sink();
+        let progress: &mut dyn Write = if progress { &mut progress_band } else { &mut sink };
+        source
+            .write_pack(pack, &mut data, progress)
+            .map_err(Error::WritePack)
+            .and_then(|()| data.flush().map_err(Into::into))
+    };
+    if let Err(Error::WritePack(err)) = &res {
+        let mut error_band = Band {
+            out: &out,
+            channel: Channel::Error,
+            size: band_size,
+        };
+        error_band.write_all(format!("{err}\n").as_bytes()).ok();
+    }
+    res
+}
+
+fn parse_id(line: &[u8], hex: &[u8]) -> Result<ObjectId, Error> {
+    ObjectId::from_hex(hex).map_err(|source| Error::DecodeObjectId {
+        line: line.into(),
+        source,
+    })
+}
+
+fn unexpected(line: Option<gix_packetline::PacketLineRef<'_>>) -> Error {
+    match line {
+        None => std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "the client hung up unexpectedly").into(),
+        Some(line) => Error::UnexpectedLine {
+            line: match line.as_slice() {
+                Some(data) => data.as_bstr().to_owned(),
+                None => format!("{line:?}").into(),
+            },
+        },
+    }
+}
@@ -0,0 +1,83 @@
+//! The `upload-pack` service, which sends packs to clients that fetch or clone.
+use bstr::BString;
+use gix_hash::ObjectId;
+
+use crate::handshake::Ref;
+
+/// The repository whose references and objects are served by [`serve()`].
+pub trait Source {
+    /// The kind of hash used by all objects of the repository.
+    fn object_hash(&self) -> gix_hash::Kind;
+
+    /// Return all references that may be advertised, in the order they should be advertised, which is `HEAD` first.
+    ///
+    /// Objects that clients may want have to be pointed to by one of these references.
+    fn refs(&self) -> Result<Vec<Ref>, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+    /// Return `true` if the object `id` exists in the repository.
+    fn contains(&self, id: &gix_hash::oid) -> bool;
+
+    /// Write a pack as described by `pack` to `out`, while sending human-readable progress messages to `progress`.
+    fn write_pack(
+        &self,
+        pack: &Pack<'_>,
+        out: &mut dyn std::io::Write,
+        progress: &mut dyn std::io::Write,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
+/// A description of the pack to send to the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pack<'a> {
+    /// The objects the client wants, which are to be sent along with all objects reachable from them.
+    pub wants: &'a [ObjectId],
+    /// The objects the client has, which don't have to be sent along with all objects reachable from them.
+    pub haves: &'a [ObjectId],
+    /// If `true`, annotated tags pointing to objects in the pack should be sent as well.
+    pub include_tag: bool,
+    /// If `true`, deltas may refer to their base object by offset within the pack.
+    pub ofs_delta: bool,
+    /// If `true`, deltas may refer to base objects reachable from `haves` which are not part of the pack.
+    pub thin: bool,
+}
+
+/// Options for use in [`serve()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The name of the server to advertise to clients, like `git/oxide-1.0`.
+    pub agent: String,
+    /// If set, the `bundle-uri` command is advertised in protocol V2, and clients asking for it receive this list.
+    pub bundle_list: Option<crate::bundle_uri::List>,
+}
+
+/// The error returned by [`serve()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Could not list references")]
+    ListRefs(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("Could not write the pack")]
+    WritePack(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("Unexpected line from the client: {line:?}")]
+    UnexpectedLine { line: BString },
+    #[error("Unknown command '{command}'")]
+    UnknownCommand { command: BString },
+    #[error("Unsupported argument '{argument}'")]
+    UnsupportedArgument { argument: BString },
+    #[error("The object id in {line:?} could not be decoded")]
+    DecodeObjectId {
+        line: BString,
+        source: gix_hash::decode::Error,
+    },
+    #[error("not our ref {id}")]
+    NotOurRef { id: ObjectId },
+    #[error("The client did not say which objects it wants")]
+    MissingWants,
+    #[error("The client uses the object format '{requested}', but the repository uses '{actual}'")]
+    ObjectFormatMismatch { requested: BString, actual: gix_hash::Kind },
+}
+
+mod function;
+pub use function::serve;
@@ -0,0 +1,162 @@
+use gix_protocol::bundle_uri::{Bundle, Header, Heuristic, List, Mode, header, list};
+
+mod list_parsing {
+    use bstr::ByteSlice;
+
+    use super::*;
+
+    #[test]
+    fn from_lines() -> crate::Result {
+        let list = List::from_lines(
+            [
+                "bundle.version=1\n",
+                "bundle.mode=all\n",
+                "bundle.heuristic=creationToken\n",
+                "bundle.base.uri=https://cdn.example.com/base.bundle\n",
+                "bundle.base.creationToken=1\n",
+                "bundle.Incremental.uri=incremental.bundle\n",
+                "bundle.Incremental.CREATIONTOKEN=2\n",
+                "bundle.Incremental.filter=blob:none\n",
+                "bundle.base.unknown=ignored\n",
+                "other.key=ignored\n",
+                "no equals sign\n",
+            ]
+            .iter()
+            .map(|line| line.as_bytes().as_bstr()),
+        )?;
+        assert_eq!(
+            list,
+            List {
+                version: Some(1),
+                mode: Mode::All,
+                heuristic: Some(Heuristic::CreationToken),
+                bundles: vec![
+                    Bundle {
+                        id: "base".into(),
+                        uri: "https://cdn.example.com/base.bundle".into(),
+                        creation_token: Some(1),
+                        filter: None,
+                    },
+                    Bundle {
+                        id: "Incremental".into(),
+                        uri: "incremental.bundle".into(),
+                        creation_token: Some(2),
+                        filter: Some("blob:none".into()),
+                    },
+                ],
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_through_key_values() -> crate::Result {
+        let list = List {
+            version: Some(1),
+            mode: Mode::Any,
+            heuristic: None,
+            bundles: vec![Bundle {
+                id: "a.b".into(),
+                uri: "file:///bundles/a.bundle".into(),
+                creation_token: None,
+                filter: None,
+            }],
+        };
+        let pairs = list.to_key_values();
+        assert_eq!(pairs[2], ("bundle.a.b.uri".into(), "file:///bundles/a.bundle".into()));
+        let parsed = List::from_key_values(pairs.iter().map(|(key, value)| (key.as_ref(), value.as_ref())))?;
+        assert_eq!(parsed, list, "identifiers may contain dots");
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let parse = |lines: &[&str]| List::from_lines(lines.iter().map(|line| line.as_bytes().as_bstr()));
+        assert!(matches!(
+            parse(&["bundle.version=2"]),
+            Err(list::Error::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(
+            parse(&["bundle.mode=some"]),
+            Err(list::Error::UnknownMode { .. })
+        ));
+        assert!(matches!(
+            parse(&["bundle.a.uri=a", "bundle.a.creationToken=first"]),
+            Err(list::Error::InvalidCreationToken { .. })
+        ));
+        assert!(matches!(
+            parse(&["bundle.a.creationToken=1"]),
+            Err(list::Error::MissingUri { id }) if id == "a"
+        ));
+    }
+}
+
+mod header_parsing {
+    use gix_hash::ObjectId;
+
+    use super::*;
+
+    const A: &str = "1111111111111111111111111111111111111111";
+    const B: &str = "2222222222222222222222222222222222222222";
+
+    #[test]
+    fn v2() -> crate::Result {
+        let input = format!("# v2 git bundle\n-{A} first commit\n{B} refs/heads/main\n{B} HEAD\n\nPACK");
+        let mut read = input.as_bytes();
+        let header = Header::from_read(&mut read)?;
+        assert_eq!(
+            header,
+            Header {
+                version: 2,
+                object_hash: gix_hash::Kind::Sha1,
+                filter: None,
+                prerequisites: vec![(ObjectId::from_hex(A.as_bytes())?, "first commit".into())],
+                refs: vec![
+                    (ObjectId::from_hex(B.as_bytes())?, "refs/heads/main".into()),
+                    (ObjectId::from_hex(B.as_bytes())?, "HEAD".into()),
+                ],
+            }
+        );
+        assert_eq!(read, b"PACK", "the reader is positioned at the pack");
+        Ok(())
+    }
+
+    #[test]
+    fn v3_with_capabilities() -> crate::Result {
+        let input = format!("# v3 git bundle\n@object-format=sha1\n@filter=blob:none\n-{A}\n{B} refs/heads/main\n\n");
+        let header = Header::from_read(&mut input.as_bytes())?;
+        assert_eq!(header.version, 3);
+        assert_eq!(header.filter, Some("blob:none".into()));
+        assert_eq!(
+            header.prerequisites,
+            [(ObjectId::from_hex(A.as_bytes())?, "".into())],
+            "comments are optional"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let parse = |input: &str| Header::from_read(&mut input.as_bytes());
+        assert!(matches!(
+            parse("[bundle]\n\turi = x\n"),
+            Err(header::Error::UnknownSignature { .. })
+        ));
+        assert!(matches!(
+            parse("# v3 git bundle\n@unknown\n\n"),
+            Err(header::Error::UnknownCapability { .. })
+        ));
+        assert!(matches!(
+            parse("# v3 git bundle\n@object-format=md5\n\n"),
+            Err(header::Error::UnknownObjectFormat { .. })
+        ));
+        assert!(matches!(
+            parse(&format!("# v2 git bundle\n{A}\n\n")),
+            Err(header::Error::MalformedLine { .. })
+        ));
+        assert!(matches!(
+            parse(&format!("# v2 git bundle\n{A} refs/heads/main\n")),
+            Err(header::Error::UnexpectedEof)
+        ));
+    }
+}
@@ -5,11 +5,14 @@ pub fn fixture_bytes(path: &str) -> Vec<u8> {
         .expect("fixture to be present and readable")
 }
 
+mod bundle_uri;
 mod command;
 pub mod fetch;
 mod handshake;
 pub use fetch::_impl::{FetchConnection, fetch};
 pub mod remote_progress;
+#[cfg(feature = "server")]
+mod server;
 
 #[gix_protocol::bisync::bisync]
 #[cfg_attr(feature = "blocking-client", test)]
@@ -0,0 +1,132 @@
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_protocol::server::daemon::{self, parse};
+
+use super::{MAIN, Repo, Serve, lines, packetlines};
+
+mod parse_request {
+    use gix_protocol::server::daemon::{parse, parse_request};
+    use gix_transport::{Protocol, Service};
+
+    #[test]
+    fn service_path_and_host() -> crate::Result {
+        let request = parse_request(b"git-upload-pack /project.git\0host=example.com:9419\0")?;
+        assert_eq!(request.service, Service::UploadPack);
+        assert_eq!(request.path, "/project.git");
+        assert_eq!(request.protocol, Protocol::V0);
+        assert_eq!(request.host, Some(("example.com".into(), Some(9419))));
+
+        let request = parse_request(b"git-receive-pack /~user/project\n")?;
+        assert_eq!(request.service, Service::ReceivePack);
+        assert_eq!(request.path, "/~user/project", "a trailing newline is ignored");
+        assert_eq!(request.host, None);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_hosts() -> crate::Result {
+        let request = parse_request(b"git-upload-pack /p\0host=[::1]:1234\0")?;
+        assert_eq!(request.host, Some(("::1".into(), Some(1234))));
+        let request = parse_request(b"git-upload-pack /p\0host=[::1]\0")?;
+        assert_eq!(request.host, Some(("::1".into(), None)));
+        Ok(())
+    }
+
+    #[test]
+    fn extra_parameters_select_the_protocol() -> crate::Result {
+        let request = parse_request(b"git-upload-pack /p\0host=localhost\0\0version=2\0")?;
+        assert_eq!(request.protocol, Protocol::V2);
+        assert_eq!(request.host, Some(("localhost".into(), None)));
+
+        let request = parse_request(b"git-upload-pack /p\0\0version=1\0unknown\0")?;
+        assert_eq!(request.protocol, Protocol::V1);
+
+        let request = parse_request(b"git-upload-pack /p\0host=localhost\0version=2\0")?;
+        assert_eq!(
+            request.protocol,
+            Protocol::V0,
+            "parameters must follow an empty field to be considered"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            parse_request(b"git-upload-pack"),
+            Err(parse::Error::Malformed { .. })
+        ));
+        assert!(matches!(
+            parse_request(b"git-upload-pack \0host=localhost"),
+            Err(parse::Error::Malformed { .. })
+        ));
+        assert!(matches!(
+            parse_request(b"git-upload-archive /p"),
+            Err(parse::Error::UnknownService { .. })
+        ));
+        assert!(matches!(
+            parse_request(b"git-upload-pack /p\0host=localhost:port\0"),
+            Err(parse::Error::InvalidHost { .. })
+        ));
+        assert!(matches!(
+            parse_request(b"git-upload-pack /p\0host=[::1\0"),
+            Err(parse::Error::InvalidHost { .. })
+        ));
+    }
+}
+
+#[test]
+fn serve_connection() -> crate::Result {
+    let mut out = Vec::new();
+    let input = packetlines(&["git-upload-pack /repo\0host=localhost\0\0version=2\0", "0000"]);
+    daemon::serve_connection(&Serve(Repo::new()), &mut input.as_slice(), &mut out)?;
+    assert_eq!(lines(&out)[0], "version 2\n", "the protocol version was passed on");
+    Ok(())
+}
+
+#[test]
+fn serve_connection_sends_errors_to_the_client() {
+    let mut out = Vec::new();
+    let input = packetlines(&["git-receive-pack /repo\0host=localhost\0"]);
+    let err = daemon::serve_connection(&Serve(Repo::new()), &mut input.as_slice(), &mut out).unwrap_err();
+    assert!(matches!(err, daemon::Error::Handle { .. }));
+    assert_eq!(lines(&out), ["ERR service not enabled: 'receive-pack'"]);
+
+    let mut out = Vec::new();
+    let err = daemon::serve_connection(&Serve(Repo::new()), &mut b"".as_slice(), &mut out).unwrap_err();
+    assert!(matches!(err, daemon::Error::MissingRequest));
+
+    let mut out = Vec::new();
+    let input = packetlines(&["git-upload-archive /repo\0"]);
+    let err = daemon::serve_connection(&Serve(Repo::new()), &mut input.as_slice(), &mut out).unwrap_err();
+    assert!(matches!(err, daemon::Error::Parse(parse::Error::UnknownService { .. })));
+    assert_eq!(lines(&out), ["ERR Unknown service 'git-upload-archive'"]);
+}
+
+#[test]
+fn serve() -> crate::Result {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let should_interrupt = AtomicBool::new(false);
+    let handler = Serve(Repo::new());
+    std::thread::scope(|scope| -> crate::Result {
+        let server = scope.spawn(|| daemon::serve(&listener, &handler, &should_interrupt));
+
+        let mut connection = TcpStream::connect(addr)?;
+        connection.write_all(&packetlines(&["git-upload-pack /repo\0host=localhost\0", "0000"]))?;
+        let mut out = Vec::new();
+        connection.read_to_end(&mut out)?;
+        let out = lines(&out);
+        assert!(out[0].starts_with(&format!("{MAIN} HEAD\0")), "{out:?}");
+        assert_eq!(out.last().map(String::as_str), Some("0000"));
+
+        should_interrupt.store(true, Ordering::Relaxed);
+        TcpStream::connect(addr)?;
+        server.join().expect("no panic");
+        Ok(())
+    })
+}
@@ -0,0 +1,223 @@
+use gix_protocol::server::{
+    Error, Handler, Mode,
+    http::{self, Request, Response},
+};
+
+use super::{MAIN, Repo, Serve, lines, packetlines};
+
+fn handle(request: Request<'_>, body: &[u8]) -> (Response, Vec<u8>) {
+    let mut out = Vec::new();
+    let response = http::handle(&request, &Serve(Repo::new()), &mut &*body, &mut out);
+    (response, out)
+}
+
+fn header<'a>(response: &'a Response, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .iter()
+        .find_map(|(header, value)| (*header == name).then_some(value.as_str()))
+}
+
+#[test]
+fn info_refs_advertisement() {
+    let (response, body) = handle(
+        Request {
+            method: "GET",
+            path: "/repo.git/info/refs".into(),
+            query: Some("service=git-upload-pack"),
+            ..Default::default()
+        },
+        b"",
+    );
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        header(&response, "Content-Type"),
+        Some("application/x-git-upload-pack-advertisement")
+    );
+    assert_eq!(
+        header(&response, "Cache-Control"),
+        Some("no-cache, max-age=0, must-revalidate")
+    );
+    let out = lines(&body);
+    assert_eq!(out[..2], ["# service=git-upload-pack\n", "0000"]);
+    assert!(out[2].starts_with(&format!("{MAIN} HEAD\0")), "V0 is the default");
+
+    let (response, body) = handle(
+        Request {
+            method: "GET",
+            path: "/repo.git/info/refs".into(),
+            query: Some("other=1&service=git-upload-pack"),
+            git_protocol: Some("unknown=1:version=2"),
+            ..Default::default()
+        },
+        b"",
+    );
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        lines(&body)[2],
+        "version 2\n",
+        "the version is taken from the Git-Protocol header"
+    );
+}
+
+#[test]
+fn upload_pack_request() {
+    // `printf '0014command=ls-refs\n0001000csymrefs\n0000' | gzip -n`
+    let gzipped_body = [
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x33, 0x30, 0x30, 0x34, 0x49, 0xce, 0xcf, 0xcd,
+        0x4d, 0xcc, 0x4b, 0xb1, 0xcd, 0x29, 0xd6, 0x2d, 0x4a, 0x4d, 0x2b, 0xe6, 0x32, 0x30, 0x30, 0x30, 0x04, 0xe2,
+        0xe4, 0xe2, 0xca, 0x5c, 0x18, 0xdf, 0x00, 0x00, 0xca, 0x14, 0x74, 0x61, 0x28, 0x00, 0x00, 0x00,
+    ];
+    let plain_body = packetlines(&["command=ls-refs\n", "0001", "symrefs\n", "0000"]);
+    for (content_encoding, body) in [(None, plain_body.as_slice()), (Some("gzip"), &gzipped_body)] {
+        let (response, body) = handle(
+            Request {
+                method: "POST",
+                path: "/repo.git/git-upload-pack".into(),
+                git_protocol: Some("version=2"),
+                content_type: Some("application/x-git-upload-pack-request"),
+                content_encoding,
+                ..Default::default()
+            },
+            body,
+        );
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            header(&response, "Content-Type"),
+            Some("application/x-git-upload-pack-result")
+        );
+        assert_eq!(
+            lines(&body)[0],
+            format!("{MAIN} HEAD symref-target:refs/heads/main\n"),
+            "there is no advertisement in stateless requests"
+        );
+    }
+}
+
+#[test]
+fn errors_before_the_body_is_written_set_the_status() {
+    for (request, expected_status) in [
+        (
+            Request {
+                method: "POST",
+                path: "/repo.git/info/refs".into(),
+                query: Some("service=git-upload-pack"),
+                ..Default::default()
+            },
+            405,
+        ),
+        (
+            Request {
+                method: "GET",
+                path: "/repo.git/info/refs".into(),
+                ..Default::default()
+            },
+            403,
+        ),
+        (
+            Request {
+                method: "GET",
+                path: "/repo.git/info/refs".into(),
+                query: Some("service=git-upload-archive"),
+                ..Default::default()
+            },
+            403,
+        ),
+        (
+            Request {
+                method: "GET",
+                path: "/repo.git/HEAD".into(),
+                ..Default::default()
+            },
+            404,
+        ),
+        (
+            Request {
+                method: "GET",
+                path: "/repo.git/git-upload-pack".into(),
+                ..Default::default()
+            },
+            405,
+        ),
+        (
+            Request {
+                method: "POST",
+                path: "/repo.git/git-upload-pack".into(),
+                content_type: Some("text/plain"),
+                ..Default::default()
+            },
+            415,
+        ),
+        (
+            Request {
+                method: "POST",
+                path: "/repo.git/git-upload-pack".into(),
+                content_type: Some("application/x-git-upload-pack-request"),
+                content_encoding: Some("br"),
+                ..Default::default()
+            },
+            415,
+        ),
+        (
+            Request {
+                method: "GET",
+                path: "/repo.git/info/refs".into(),
+                query: Some("service=git-receive-pack"),
+                ..Default::default()
+            },
+            403,
+        ),
+    ] {
+        let (response, body) = handle(request, b"");
+        assert_eq!(response.status, expected_status, "{request:?}");
+        assert_eq!(header(&response, "Content-Type"), Some("text/plain"));
+        assert!(body.ends_with(b"\n"), "there is a message for the user");
+    }
+
+    let (response, body) = handle(
+        Request {
+            method: "POST",
+            path: "/repo.git/git-upload-pack".into(),
+            content_type: Some("application/x-git-upload-pack-request"),
+            ..Default::default()
+        },
+        &packetlines(&["want 5555555555555555555555555555555555555555\n", "0000"]),
+    );
+    assert_eq!(response.status, 400);
+    assert_eq!(body, b"not our ref 5555555555555555555555555555555555555555\n");
+}
+
+#[test]
+fn errors_after_the_body_was_written_are_sent_to_the_client() {
+    struct FailAfterWriting;
+    impl Handler for FailAfterWriting {
+        fn handle(
+            &self,
+            _request: &gix_protocol::server::Request,
+            _mode: Mode,
+            _input: &mut dyn std::io::Read,
+            output: &mut dyn std::io::Write,
+        ) -> Result<(), Error> {
+            output.write_all(b"0009data\n")?;
+            Err(Error::Io(std::io::Error::other("failure")))
+        }
+    }
+
+    let mut body = Vec::new();
+    let response = http::handle(
+        &Request {
+            method: "GET",
+            path: "/repo.git/info/refs".into(),
+            query: Some("service=git-upload-pack"),
+            ..Default::default()
+        },
+        &FailAfterWriting,
+        &mut std::io::empty(),
+        &mut body,
+    );
+    assert_eq!(response.status, 200, "the status was already sent");
+    assert_eq!(
+        lines(&body),
+        ["# service=git-upload-pack\n", "0000", "data\n", "ERR failure"]
+    );
+}
@@ -0,0 +1,209 @@
+use std::io::Write;
+
+use gix_hash::ObjectId;
+use gix_protocol::{
+    handshake::Ref,
+    server::{self, Error, Handler, Mode, Request},
+    transport::Service,
+};
+
+mod daemon;
+mod http;
+mod receive_pack;
+mod upload_pack;
+
+/// Convert a hexadecimal hash into its corresponding `ObjectId` or _panic_.
+fn oid(hex: &str) -> ObjectId {
+    ObjectId::from_hex(hex.as_bytes()).expect("40 bytes hex")
+}
+
+const MAIN: &str = "1111111111111111111111111111111111111111";
+const TAG: &str = "2222222222222222222222222222222222222222";
+const OTHER: &str = "3333333333333333333333333333333333333333";
+const COMMON: &str = "4444444444444444444444444444444444444444";
+const UNKNOWN: &str = "5555555555555555555555555555555555555555";
+
+/// A repository that writes a description of the requested pack instead of the pack itself.
+struct Repo {
+    refs: Vec<Ref>,
+}
+
+impl Repo {
+    fn new() -> Self {
+        Repo {
+            refs: vec![
+                Ref::Symbolic {
+                    full_ref_name: "HEAD".into(),
+                    target: "refs/heads/main".into(),
+                    tag: None,
+                    object: oid(MAIN),
+                },
+                Ref::Direct {
+                    full_ref_name: "refs/heads/main".into(),
+                    object: oid(MAIN),
+                },
+                Ref::Direct {
+                    full_ref_name: "refs/heads/other".into(),
+                    object: oid(OTHER),
+                },
+                Ref::Peeled {
+                    full_ref_name: "refs/tags/v1".into(),
+                    tag: oid(TAG),
+                    object: oid(MAIN),
+                },
+            ],
+        }
+    }
+
+    fn unborn() -> Self {
+        Repo {
+            refs: vec![Ref::Unborn {
+                full_ref_name: "HEAD".into(),
+                target: "refs/heads/main".into(),
+            }],
+        }
+    }
+}
+
+impl server::upload_pack::Source for Repo {
+    fn object_hash(&self) -> gix_hash::Kind {
+        gix_hash::Kind::Sha1
+    }
+
+    fn refs(&self) -> Result<Vec<Ref>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(self.refs.clone())
+    }
+
+    fn contains(&self, id: &gix_hash::oid) -> bool {
+        [MAIN, TAG, OTHER, COMMON].into_iter().any(|hex| oid(hex) == id)
+    }
+
+    fn write_pack(
+        &self,
+        pack: &server::upload_pack::Pack<'_>,
+        out: &mut dyn Write,
+        progress: &mut dyn Write,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let hex = |ids: &[ObjectId]| ids.iter().map(ToString::to_string).collect::<Vec<_>>().join(",");
+        writeln!(progress, "writing")?;
+        write!(
+            out,
+            "PACK wants={} haves={} include-tag={} ofs-delta={} thin={}",
+            hex(pack.wants),
+            hex(pack.haves),
+            pack.include_tag,
+            pack.ofs_delta,
+            pack.thin
+        )?;
+        Ok(())
+    }
+}
+
+impl server::receive_pack::Target for Repo {
+    fn object_hash(&self) -> gix_hash::Kind {
+        gix_hash::Kind::Sha1
+    }
+
+    fn refs(&self) -> Result<Vec<Ref>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(self.refs.clone())
+    }
+
+    /// Describe what was received as progress, accept all commands but those for `refs/heads/rejected`,
+    /// and fail to unpack packs that contain `corrupt`.
+    fn receive(
+        &self,
+        update: &server::receive_pack::Update<'_>,
+        pack: Option<&mut dyn std::io::BufRead>,
+        progress: &mut dyn Write,
+    ) -> Result<server::receive_pack::Outcome, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let pack = match pack {
+            Some(pack) => {
+                let mut buf = String::new();
+                pack.read_to_string(&mut buf)?;
+                Some(buf)
+            }
+            None => None,
+        };
+        writeln!(
+            progress,
+            "pack={pack:?} options={:?} atomic={} quiet={}",
+            update.push_options, update.atomic, update.quiet
+        )?;
+        Ok(server::receive_pack::Outcome {
+            unpack_error: pack
+                .filter(|pack| pack.contains("corrupt"))
+                .map(|_| "index-pack abnormal exit".into()),
+            commands: update
+                .commands
+                .iter()
+                .map(|command| {
+                    if command.name == "refs/heads/rejected" {
+                        Err("hook declined".into())
+                    } else {
+                        Ok(())
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+fn options() -> server::upload_pack::Options {
+    server::upload_pack::Options {
+        agent: "git/test".into(),
+        bundle_list: None,
+    }
+}
+
+/// Serve `upload-pack` from the contained repository, and reject `receive-pack`.
+struct Serve(Repo);
+
+impl Handler for Serve {
+    fn handle(
+        &self,
+        request: &Request,
+        mode: Mode,
+        input: &mut dyn std::io::Read,
+        output: &mut dyn Write,
+    ) -> Result<(), Error> {
+        match request.service {
+            Service::UploadPack => Ok(server::upload_pack::serve(
+                &self.0,
+                request.protocol,
+                mode,
+                input,
+                output,
+                &options(),
+            )?),
+            Service::ReceivePack => Err(Error::ServiceDisabled {
+                service: request.service,
+            }),
+        }
+    }
+}
+
+/// Encode `lines` as packet lines, with `0000` and `0001` being flush and delimiter packets.
+fn packetlines(lines: &[&str]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for line in lines {
+        match *line {
+            "0000" | "0001" => out.extend_from_slice(line.as_bytes()),
+            line => out.extend_from_slice(format!("{:04x}{line}", line.len() + 4).as_bytes()),
+        }
+    }
+    out
+}
+
+/// Decode the packet lines in `buf`, with flush and delimiter packets turned into `0000` and `0001`.
+fn lines(mut buf: &[u8]) -> Vec<String> {
+    let mut out = Vec::new();
+    while !buf.is_empty() {
+        let len = usize::from_str_radix(std::str::from_utf8(&buf[..4]).expect("ascii"), 16).expect("hex length");
+        match len {
+            0 | 1 => out.push(format!("{len:04}")),
+            _ => out.push(String::from_utf8_lossy(&buf[4..len]).into_owned()),
+        }
+        buf = &buf[len.max(4)..];
+    }
+    out
+}
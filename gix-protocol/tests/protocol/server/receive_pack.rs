@@ -0,0 +1,226 @@
+use gix_protocol::server::{Mode, receive_pack};
+use gix_transport::Protocol;
+
+use super::{MAIN, OTHER, Repo, TAG, UNKNOWN, lines, packetlines};
+
+fn serve(repo: &Repo, protocol: Protocol, mode: Mode, input: &[u8]) -> Result<Vec<u8>, receive_pack::Error> {
+    let mut out = Vec::new();
+    receive_pack::serve(
+        repo,
+        protocol,
+        mode,
+        &mut &*input,
+        &mut out,
+        &receive_pack::Options {
+            agent: "git/test".into(),
+        },
+    )?;
+    Ok(out)
+}
+
+/// Return `input` as packet lines, followed by `pack`.
+fn request(input: &[&str], pack: &str) -> Vec<u8> {
+    let mut out = packetlines(input);
+    out.extend_from_slice(pack.as_bytes());
+    out
+}
+
+/// Decode the packet lines multiplexed in the sideband `lines`, with `0000` marking the end of the pack.
+fn demultiplex(lines: &[String]) -> Vec<String> {
+    let mut out = Vec::new();
+    for line in lines {
+        match line.as_bytes().first() {
+            Some(1) => out.extend(super::lines(&line.as_bytes()[1..])),
+            Some(2) => out.push(format!("progress: {}", &line[1..])),
+            _ => out.push(line.clone()),
+        }
+    }
+    out
+}
+
+const CAPABILITIES: &str = "report-status report-status-v2 delete-refs side-band-64k quiet atomic ofs-delta push-options object-format=sha1 agent=git/test";
+
+#[test]
+fn advertisement() -> crate::Result {
+    let out = lines(&serve(&Repo::new(), Protocol::V0, Mode::AdvertiseOnly, &[])?);
+    assert_eq!(
+        out,
+        [
+            format!("{MAIN} refs/heads/main\0{CAPABILITIES}\n"),
+            format!("{OTHER} refs/heads/other\n"),
+            format!("{TAG} refs/tags/v1\n"),
+            "0000".into(),
+        ],
+        "HEAD isn't advertised, and tags aren't peeled"
+    );
+
+    let out = lines(&serve(&Repo::new(), Protocol::V1, Mode::AdvertiseOnly, &[])?);
+    assert_eq!(out[0], "version 1\n");
+    let out = lines(&serve(&Repo::new(), Protocol::V2, Mode::AdvertiseOnly, &[])?);
+    assert!(out[0].starts_with(MAIN), "V2 isn't available for pushes, so V0 is used");
+
+    let out = lines(&serve(&Repo::unborn(), Protocol::V0, Mode::Stateful, &[])?);
+    assert_eq!(
+        out,
+        [
+            format!("{} capabilities^{{}}\0{CAPABILITIES}\n", gix_hash::Kind::Sha1.null()),
+            "0000".into(),
+        ],
+        "a client that hangs up right away just sees the advertisement"
+    );
+    Ok(())
+}
+
+#[test]
+fn push_with_sideband() -> crate::Result {
+    let null = gix_hash::Kind::Sha1.null();
+    let out = serve(
+        &Repo::new(),
+        Protocol::V0,
+        Mode::Stateless,
+        &request(
+            &[
+                &format!("{MAIN} {UNKNOWN} refs/heads/main\0report-status-v2 side-band-64k push-options atomic\n"),
+                &format!("{null} {OTHER} refs/heads/rejected\n"),
+                "0000",
+                "ci.skip\n",
+                "0000",
+            ],
+            "PACK",
+        ),
+    )?;
+    assert_eq!(
+        demultiplex(&lines(&out)),
+        [
+            "progress: pack=Some(\"PACK\") options=[\"ci.skip\"] atomic=true quiet=false\n",
+            "unpack ok\n",
+            "ok refs/heads/main\n",
+            format!("option old-oid {MAIN}\n").as_str(),
+            format!("option new-oid {UNKNOWN}\n").as_str(),
+            "ng refs/heads/rejected hook declined\n",
+            "0000",
+            "0000",
+        ],
+        "the report is sent on the data channel, followed by a flush packet, with the version 2 format adding the object ids of updated references"
+    );
+    Ok(())
+}
+
+#[test]
+fn push_without_sideband() -> crate::Result {
+    let out = serve(
+        &Repo::new(),
+        Protocol::V0,
+        Mode::Stateful,
+        &request(
+            &[&format!("{MAIN} {UNKNOWN} refs/heads/main\0report-status\n"), "0000"],
+            "PACK corrupt",
+        ),
+    )?;
+    assert_eq!(
+        lines(&out)[4..],
+        ["unpack index-pack abnormal exit\n", "ok refs/heads/main\n", "0000"],
+        "progress isn't shown, and the unpack error is reported verbatim"
+    );
+
+    let out = serve(
+        &Repo::new(),
+        Protocol::V0,
+        Mode::Stateless,
+        &request(&[&format!("{MAIN} {UNKNOWN} refs/heads/main\n"), "0000"], "PACK"),
+    )?;
+    assert!(out.is_empty(), "without report-status, there is nothing to say");
+    Ok(())
+}
+
+#[test]
+fn deletions_come_without_pack() -> crate::Result {
+    let null = gix_hash::Kind::Sha1.null();
+    let out = serve(
+        &Repo::new(),
+        Protocol::V0,
+        Mode::Stateless,
+        &request(
+            &[
+                &format!("{OTHER} {null} refs/heads/other\0report-status side-band-64k\n"),
+                "0000",
+            ],
+            "",
+        ),
+    )?;
+    assert_eq!(
+        demultiplex(&lines(&out)),
+        [
+            "progress: pack=None options=[] atomic=false quiet=false\n",
+            "unpack ok\n",
+            "ok refs/heads/other\n",
+            "0000",
+            "0000",
+        ]
+    );
+
+    let out = serve(
+        &Repo::new(),
+        Protocol::V0,
+        Mode::Stateless,
+        &request(
+            &[
+                &format!("{OTHER} {null} refs/heads/other\0report-status side-band-64k quiet\n"),
+                "0000",
+            ],
+            "",
+        ),
+    )?;
+    assert_eq!(
+        demultiplex(&lines(&out)),
+        [
+            "progress: pack=None options=[] atomic=false quiet=true\n",
+            "unpack ok\n",
+            "ok refs/heads/other\n",
+            "0000",
+            "0000"
+        ],
+        "quiet clients still receive messages, it's up to the receiver to not send progress"
+    );
+    Ok(())
+}
+
+#[test]
+fn nothing_to_push() -> crate::Result {
+    let out = serve(&Repo::new(), Protocol::V0, Mode::Stateless, &packetlines(&["0000"]))?;
+    assert!(out.is_empty());
+    Ok(())
+}
+
+#[test]
+fn errors() {
+    let err = serve(
+        &Repo::new(),
+        Protocol::V0,
+        Mode::Stateless,
+        &packetlines(&[&format!("shallow {MAIN}\n"), "0000"]),
+    )
+    .unwrap_err();
+    assert!(matches!(err, receive_pack::Error::UnsupportedArgument { .. }));
+
+    let err = serve(
+        &Repo::new(),
+        Protocol::V0,
+        Mode::Stateless,
+        &packetlines(&[&format!("{MAIN} {UNKNOWN}\n"), "0000"]),
+    )
+    .unwrap_err();
+    assert!(matches!(err, receive_pack::Error::UnexpectedLine { .. }));
+
+    let err = serve(
+        &Repo::new(),
+        Protocol::V0,
+        Mode::Stateless,
+        &packetlines(&[
+            &format!("{MAIN} {UNKNOWN} refs/heads/main\0object-format=sha256\n"),
+            "0000",
+        ]),
+    )
+    .unwrap_err();
+    assert!(matches!(err, receive_pack::Error::ObjectFormatMismatch { .. }));
+}
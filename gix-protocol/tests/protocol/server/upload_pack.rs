@@ -0,0 +1,384 @@
+use gix_protocol::server::{Mode, upload_pack};
+use gix_transport::Protocol;
+
+use super::{COMMON, MAIN, OTHER, Repo, TAG, UNKNOWN, lines, options, packetlines};
+
+fn serve(repo: &Repo, protocol: Protocol, mode: Mode, input: &[&str]) -> Result<Vec<String>, upload_pack::Error> {
+    Ok(lines(&serve_raw(repo, protocol, mode, input)?))
+}
+
+fn serve_raw(repo: &Repo, protocol: Protocol, mode: Mode, input: &[&str]) -> Result<Vec<u8>, upload_pack::Error> {
+    let mut out = Vec::new();
+    upload_pack::serve(
+        repo,
+        protocol,
+        mode,
+        &mut packetlines(input).as_slice(),
+        &mut out,
+        &options(),
+    )?;
+    Ok(out)
+}
+
+const V1_CAPABILITIES: &str =
+    "multi_ack_detailed multi_ack side-band-64k side-band ofs-delta thin-pack no-progress include-tag";
+
+mod v1 {
+    use gix_protocol::server::{Mode, upload_pack};
+    use gix_transport::Protocol;
+
+    use super::{COMMON, MAIN, OTHER, Repo, TAG, UNKNOWN, V1_CAPABILITIES, serve, serve_raw};
+
+    #[test]
+    fn advertisement() -> crate::Result {
+        let out = serve(&Repo::new(), Protocol::V0, Mode::AdvertiseOnly, &[])?;
+        assert_eq!(
+            out,
+            [
+                format!(
+                    "{MAIN} HEAD\0{V1_CAPABILITIES} symref=HEAD:refs/heads/main object-format=sha1 agent=git/test\n"
+                ),
+                format!("{MAIN} refs/heads/main\n"),
+                format!("{OTHER} refs/heads/other\n"),
+                format!("{TAG} refs/tags/v1\n"),
+                format!("{MAIN} refs/tags/v1^{{}}\n"),
+                "0000".into(),
+            ]
+        );
+
+        let out = serve(&Repo::new(), Protocol::V1, Mode::AdvertiseOnly, &[])?;
+        assert_eq!(out[0], "version 1\n", "V1 is announced before the advertisement");
+        assert_eq!(out.len(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn advertisement_without_refs() -> crate::Result {
+        let out = serve(&Repo::unborn(), Protocol::V0, Mode::Stateful, &[])?;
+        assert_eq!(
+            out,
+            [
+                format!(
+                    "{} capabilities^{{}}\0{V1_CAPABILITIES} object-format=sha1 agent=git/test\n",
+                    gix_hash::Kind::Sha1.null()
+                ),
+                "0000".into(),
+            ],
+            "a client that hangs up right away just sees the advertisement"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn stateless_negotiation_round() -> crate::Result {
+        let out = serve(
+            &Repo::new(),
+            Protocol::V0,
+            Mode::Stateless,
+            &[
+                &format!("want {MAIN} multi_ack_detailed side-band-64k ofs-delta\n"),
+                "0000",
+                &format!("have {UNKNOWN}\n"),
+                &format!("have {COMMON}\n"),
+                "0000",
+            ],
+        )?;
+        assert_eq!(
+            out,
+            [format!("ACK {COMMON} common\n"), "NAK\n".into()],
+            "only objects we have are acknowledged, and the round ends after the flush"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_with_sideband() -> crate::Result {
+        let out = serve(
+            &Repo::new(),
+            Protocol::V0,
+            Mode::Stateful,
+            &[
+                &format!("want {MAIN} multi_ack_detailed side-band-64k thin-pack include-tag agent=git/2\n"),
+                &format!("want {OTHER}\n"),
+                "0000",
+                &format!("have {COMMON}\n"),
+                "done\n",
+            ],
+        )?;
+        assert_eq!(
+            out[6..],
+            [
+                format!("ACK {COMMON} common\n"),
+                format!("ACK {COMMON}\n"),
+                "\u{2}writing\n".into(),
+                format!("\u{1}PACK wants={MAIN},{OTHER} haves={COMMON} include-tag=true ofs-delta=false thin=true"),
+                "0000".into(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_without_sideband_and_multi_ack() -> crate::Result {
+        let out = serve_raw(
+            &Repo::new(),
+            Protocol::V0,
+            Mode::Stateless,
+            &[&format!("want {MAIN} ofs-delta\n"), "0000", "done\n"],
+        )?;
+        assert_eq!(
+            out,
+            format!("0008NAK\nPACK wants={MAIN} haves= include-tag=false ofs-delta=true thin=false").as_bytes(),
+            "the pack follows without packet line framing or progress"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn wants_must_be_advertised() {
+        let err = serve(
+            &Repo::new(),
+            Protocol::V0,
+            Mode::Stateful,
+            &[&format!("want {COMMON}\n"), "0000", "done\n"],
+        )
+        .unwrap_err();
+        assert!(matches!(err, upload_pack::Error::NotOurRef { .. }));
+        assert_eq!(err.to_string(), format!("not our ref {COMMON}"));
+    }
+
+    #[test]
+    fn shallow_requests_are_rejected() {
+        let err = serve(
+            &Repo::new(),
+            Protocol::V0,
+            Mode::Stateless,
+            &[&format!("want {MAIN}\n"), "deepen 1\n", "0000"],
+        )
+        .unwrap_err();
+        assert!(matches!(err, upload_pack::Error::UnsupportedArgument { .. }));
+    }
+}
+
+mod v2 {
+    use gix_protocol::server::{Mode, upload_pack};
+    use gix_transport::Protocol;
+
+    use super::{COMMON, MAIN, OTHER, Repo, TAG, UNKNOWN, serve};
+
+    #[test]
+    fn advertisement() -> crate::Result {
+        let out = serve(&Repo::new(), Protocol::V2, Mode::AdvertiseOnly, &[])?;
+        assert_eq!(
+            out,
+            [
+                "version 2\n",
+                "agent=git/test\n",
+                "ls-refs=unborn\n",
+                "fetch\n",
+                "server-option\n",
+                "object-format=sha1\n",
+                "0000"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bundle_uri() -> crate::Result {
+        let list = gix_protocol::bundle_uri::List {
+            version: Some(1),
+            mode: gix_protocol::bundle_uri::Mode::All,
+            heuristic: None,
+            bundles: vec![gix_protocol::bundle_uri::Bundle {
+                id: "base".into(),
+                uri: "https://cdn.example.com/base.bundle".into(),
+                creation_token: None,
+                filter: None,
+            }],
+        };
+        let options = upload_pack::Options {
+            bundle_list: Some(list),
+            ..super::super::options()
+        };
+        let serve = |mode, input: &[&str]| -> Result<Vec<String>, upload_pack::Error> {
+            let mut out = Vec::new();
+            upload_pack::serve(
+                &Repo::new(),
+                Protocol::V2,
+                mode,
+                &mut super::super::packetlines(input).as_slice(),
+                &mut out,
+                &options,
+            )?;
+            Ok(super::super::lines(&out))
+        };
+
+        let out = serve(Mode::AdvertiseOnly, &[])?;
+        assert_eq!(out[6], "bundle-uri\n", "the command is advertised if there is a list");
+
+        let out = serve(Mode::Stateless, &["command=bundle-uri\n", "0000"])?;
+        assert_eq!(
+            out,
+            [
+                "bundle.version=1\n",
+                "bundle.mode=all\n",
+                "bundle.base.uri=https://cdn.example.com/base.bundle\n",
+                "0000"
+            ]
+        );
+
+        let err = serve(Mode::Stateless, &["command=bundle-uri\n", "0001", "unknown\n", "0000"]).unwrap_err();
+        assert!(matches!(err, upload_pack::Error::UnsupportedArgument { .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn ls_refs() -> crate::Result {
+        let out = serve(
+            &Repo::new(),
+            Protocol::V2,
+            Mode::Stateless,
+            &[
+                "command=ls-refs\n",
+                "object-format=sha1\n",
+                "0001",
+                "peel\n",
+                "symrefs\n",
+                "0000",
+            ],
+        )?;
+        assert_eq!(
+            out,
+            [
+                format!("{MAIN} HEAD symref-target:refs/heads/main\n"),
+                format!("{MAIN} refs/heads/main\n"),
+                format!("{OTHER} refs/heads/other\n"),
+                format!("{TAG} refs/tags/v1 peeled:{MAIN}\n"),
+                "0000".into(),
+            ]
+        );
+
+        let out = serve(
+            &Repo::new(),
+            Protocol::V2,
+            Mode::Stateless,
+            &["command=ls-refs\n", "0001", "ref-prefix refs/tags/\n", "0000"],
+        )?;
+        assert_eq!(out, [format!("{TAG} refs/tags/v1\n"), "0000".into()]);
+        Ok(())
+    }
+
+    #[test]
+    fn ls_refs_unborn() -> crate::Result {
+        let input = &["command=ls-refs\n", "0001", "symrefs\n", "unborn\n", "0000"];
+        let out = serve(&Repo::unborn(), Protocol::V2, Mode::Stateless, input)?;
+        assert_eq!(out, ["unborn HEAD symref-target:refs/heads/main\n", "0000"]);
+
+        let out = serve(
+            &Repo::unborn(),
+            Protocol::V2,
+            Mode::Stateless,
+            &["command=ls-refs\n", "0001", "symrefs\n", "0000"],
+        )?;
+        assert_eq!(out, ["0000"], "unborn refs are only listed if asked for");
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_with_negotiation() -> crate::Result {
+        let out = serve(
+            &Repo::new(),
+            Protocol::V2,
+            Mode::Stateless,
+            &[
+                "command=fetch\n",
+                "0001",
+                "thin-pack\n",
+                "no-progress\n",
+                "ofs-delta\n",
+                &format!("want {OTHER}\n"),
+                &format!("have {UNKNOWN}\n"),
+                &format!("have {COMMON}\n"),
+                "0000",
+            ],
+        )?;
+        assert_eq!(
+            out,
+            [
+                "acknowledgments\n".to_string(),
+                format!("ACK {COMMON}\n"),
+                "ready\n".into(),
+                "0001".into(),
+                "packfile\n".into(),
+                format!("\u{1}PACK wants={OTHER} haves={COMMON} include-tag=false ofs-delta=true thin=true"),
+                "0000".into(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fetch_without_common_objects_needs_another_round() -> crate::Result {
+        let out = serve(
+            &Repo::new(),
+            Protocol::V2,
+            Mode::Stateful,
+            &[
+                "command=fetch\n",
+                "0001",
+                &format!("want {MAIN}\n"),
+                &format!("have {UNKNOWN}\n"),
+                "0000",
+                "command=fetch\n",
+                "0001",
+                &format!("want {MAIN}\n"),
+                "done\n",
+                "0000",
+            ],
+        )?;
+        assert_eq!(
+            out[7..],
+            [
+                "acknowledgments\n".to_string(),
+                "NAK\n".into(),
+                "0000".into(),
+                "packfile\n".into(),
+                "\u{2}writing\n".into(),
+                format!("\u{1}PACK wants={MAIN} haves= include-tag=false ofs-delta=false thin=false"),
+                "0000".into(),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let err = serve(
+            &Repo::new(),
+            Protocol::V2,
+            Mode::Stateless,
+            &["command=fetch\n", "object-format=sha256\n", "0000"],
+        )
+        .unwrap_err();
+        assert!(matches!(err, upload_pack::Error::ObjectFormatMismatch { .. }));
+
+        let err = serve(
+            &Repo::new(),
+            Protocol::V2,
+            Mode::Stateless,
+            &["command=fetch\n", "0000"],
+        )
+        .unwrap_err();
+        assert!(matches!(err, upload_pack::Error::MissingWants));
+
+        let err = serve(
+            &Repo::new(),
+            Protocol::V2,
+            Mode::Stateless,
+            &["command=bundle-uri\n", "0000"],
+        )
+        .unwrap_err();
+        assert!(matches!(err, upload_pack::Error::UnknownCommand { .. }));
+    }
+}
@@ -36,8 +36,11 @@ impl Transaction<'_, '_> {
             PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
         );
 
+        // Edits that were split off a symbolic ref only log if the value actually changes, just like their referent.
+        let split_parents: Vec<_> = updates.iter().filter_map(|edit| edit.parent_index).collect();
+
         // Perform updates first so live commits remain referenced
-        for change in &mut updates {
+        for (idx, change) in updates.iter_mut().enumerate() {
             assert!(!change.update.deref, "Deref mode is turned into splits and turned off");
             match &change.update.change {
                 // reflog first, then reference
@@ -72,7 +75,12 @@ impl Transaction<'_, '_> {
                             }
                         };
                         if let Some((previous, new_oid)) = log_update {
-                            let do_update = previous.as_ref() != Some(new_oid);
+                            // If enabled, an explicit request to only update the reflog is honored even if the value doesn't change,
+                            // which is needed to record switching between branches that point to the same commit.
+                            let do_update = previous.as_ref() != Some(new_oid)
+                                || (self.log_unchanged
+                                    && matches!(log.mode, RefLog::Only)
+                                    && !split_parents.contains(&idx));
                             if do_update {
                                 self.store.reflog_create_or_append(
                                     change.update.name.as_ref(),
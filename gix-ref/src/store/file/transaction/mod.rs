@@ -73,6 +73,7 @@ impl file::Store {
             packed_transaction: None,
             updates: None,
             packed_refs: PackedRefs::default(),
+            log_unchanged: false,
         }
     }
 }
@@ -83,6 +84,14 @@ impl<'p> Transaction<'_, 'p> {
         self.packed_refs = packed_refs;
         self
     }
+
+    /// If `toggle` is `true`, edits that only update the reflog with [`RefLog::Only`](crate::transaction::RefLog::Only)
+    /// write a log entry even if the reference already has the new value, which is what Git does when switching between
+    /// branches that point to the same commit. It's `false` by default, so unchanged references are never logged.
+    pub fn log_unchanged_values(mut self, toggle: bool) -> Self {
+        self.log_unchanged = toggle;
+        self
+    }
 }
 
 impl std::fmt::Debug for Transaction<'_, '_> {
@@ -67,6 +67,7 @@ impl Transaction<'_, '_> {
         packed: Option<&packed::Buffer>,
         change: &mut Edit,
         direct_to_packed_refs: bool,
+        log_unchanged: bool,
     ) -> Result<(), Error> {
         use std::io::Write;
         assert!(
@@ -80,6 +81,7 @@ impl Transaction<'_, '_> {
         // returning the configured validation error.
         store.check_windows_device_name(change.update.name.as_ref())?;
 
+        let mut symbolic_previous_oid = None;
         let lock = match &mut change.update.change {
             Change::Delete { expected, .. } => {
                 let (base, relative_path) = store.reference_path_with_base(change.update.name.as_ref());
@@ -126,7 +128,7 @@ impl Transaction<'_, '_> {
 
                 Some(lock)
             }
-            Change::Update { expected, new, .. } => {
+            Change::Update { expected, new, log } => {
                 let (base, relative_path) = store.reference_path_with_base(change.update.name.as_ref());
                 let obtain_lock = || {
                     gix_lock::File::acquire_to_update_resource(
@@ -196,6 +198,16 @@ impl Transaction<'_, '_> {
                     }
                 }
 
+                // Detaching a symbolic ref still moves away from the object it pointed to, which is what the reflog should show.
+                // Reflog-only edits are treated the same if they should be logged even if the object doesn't change.
+                if let (Target::Object(_), Some(Target::Symbolic(name))) =
+                    (&new, existing_ref.as_ref().map(|existing| &existing.target))
+                {
+                    if log.mode == RefLog::AndReference || log_unchanged {
+                        symbolic_previous_oid = Self::peel_existing_ref(store, name.as_ref(), packed)?;
+                    }
+                }
+
                 let (is_effective, is_symbolic) = if let Some(existing) = existing_ref {
                     let (effective, is_symbolic) = new_would_change_existing(new, &existing.target);
                     *expected = PreviousValue::MustExistAndMatch(existing.target);
@@ -219,8 +231,35 @@ impl Transaction<'_, '_> {
             }
         };
         change.lock = lock;
+        if symbolic_previous_oid.is_some() {
+            change.leaf_referent_previous_oid = symbolic_previous_oid;
+        }
         Ok(())
     }
+
+    /// Follow the symbolic reference `name` to the object it ultimately points to, if it exists.
+    fn peel_existing_ref(
+        store: &file::Store,
+        name: &FullNameRef,
+        packed: Option<&packed::Buffer>,
+    ) -> Result<Option<gix_hash::ObjectId>, Error> {
+        let mut name = name.to_owned();
+        // Like Git, give up on overly long chains of symbolic references.
+        for _ in 0..5 {
+            match Self::read_existing_ref(store, name.as_ref(), packed)? {
+                Some(Reference {
+                    target: Target::Object(oid),
+                    ..
+                }) => return Ok(Some(oid)),
+                Some(Reference {
+                    target: Target::Symbolic(next),
+                    ..
+                }) => name = next,
+                None => break,
+            }
+        }
+        Ok(None)
+    }
 }
 
 impl Transaction<'_, '_> {
@@ -388,6 +427,7 @@ impl Transaction<'_, '_> {
                     self.packed_refs,
                     PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
                 ),
+                self.log_unchanged,
             ) {
                 let err = match err {
                     Error::LockAcquire {
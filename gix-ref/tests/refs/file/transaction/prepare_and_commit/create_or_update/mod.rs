@@ -763,6 +763,92 @@ fn write_reference_to_which_head_points_to_does_not_update_heads_reflog_even_tho
     Ok(())
 }
 
+#[test]
+fn detaching_symbolic_head_logs_the_previously_peeled_id() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    assert_eq!(store.find_loose("HEAD")?.kind(), gix_ref::Kind::Symbolic);
+
+    let new_id = hex_to_id("01dd4e2a978a9f5bd773dae6da7aa4a5ac1cdbbc");
+    store
+        .transaction()
+        .prepare(
+            Some(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: "checkout: moving from main to 01dd4e2a".into(),
+                    },
+                    expected: PreviousValue::MustExist,
+                    new: Target::Object(new_id),
+                },
+                name: "HEAD".try_into()?,
+                deref: false,
+            }),
+            Fail::Immediately,
+            Fail::Immediately,
+        )?
+        .commit(committer().to_ref(&mut TimeBuf::default()))?;
+
+    assert_eq!(store.find_loose("HEAD")?.target, Target::Object(new_id));
+    assert_eq!(
+        reflog_lines(&store, "HEAD")?.last().expect("at least one line"),
+        &log_line(
+            hex_to_id("02a7a22d90d7c02fb494ed25551850b868e634f0"),
+            new_id,
+            "checkout: moving from main to 01dd4e2a"
+        ),
+        "the previous id is the one the symbolic ref pointed to"
+    );
+    Ok(())
+}
+
+#[test]
+fn reflog_only_edits_of_unchanged_values_are_logged_only_if_enabled() -> crate::Result {
+    let (_keep, store) = store_writable("make_repo_for_reflog.sh")?;
+    let main_id = hex_to_id("02a7a22d90d7c02fb494ed25551850b868e634f0");
+    let edit = || -> crate::Result<RefEdit> {
+        Ok(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::Only,
+                    force_create_reflog: false,
+                    message: "log only".into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Object(main_id),
+            },
+            name: "refs/heads/main".try_into()?,
+            deref: false,
+        })
+    };
+    let num_lines = reflog_lines(&store, "refs/heads/main")?.len();
+
+    store
+        .transaction()
+        .prepare(Some(edit()?), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref(&mut TimeBuf::default()))?;
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/main")?.len(),
+        num_lines,
+        "by default, nothing is logged if the value doesn't change"
+    );
+
+    store
+        .transaction()
+        .log_unchanged_values(true)
+        .prepare(Some(edit()?), Fail::Immediately, Fail::Immediately)?
+        .commit(committer().to_ref(&mut TimeBuf::default()))?;
+    assert_eq!(
+        reflog_lines(&store, "refs/heads/main")?
+            .last()
+            .expect("at least one line"),
+        &log_line(main_id, main_id, "log only"),
+        "if enabled, the unchanged value is logged"
+    );
+    Ok(())
+}
+
 #[test]
 fn packed_refs_are_looked_up_when_checking_existing_values() -> crate::Result {
     let (_keep, store) = store_writable("make_packed_ref_repository.sh")?;
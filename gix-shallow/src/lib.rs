@@ -69,10 +69,12 @@ pub mod write {
         /// after applying all `updates`.
         ///
         /// If this leaves the list of shallow commits empty, the file is removed.
+        /// Commits that are listed more than once are written only once.
         ///
         /// ### Deviation
         ///
-        /// Git also prunes the set of shallow commits while writing, we don't until we support some sort of pruning.
+        /// Git also prunes shallow commits that don't exist in the object database while writing, which is left to the caller
+        /// as we don't have access to objects.
         pub fn write(
             mut file: gix_lock::File,
             shallow_commits: Option<nonempty::NonEmpty<gix_hash::ObjectId>>,
@@ -97,6 +99,7 @@ pub mod write {
                 return Ok(());
             }
             shallow_commits.sort();
+            shallow_commits.dedup();
             let mut buf = Vec::<u8>::new();
             for commit in shallow_commits {
                 commit.write_hex_to(&mut buf).map_err(Error::Io)?;
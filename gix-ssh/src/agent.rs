@@ -0,0 +1,104 @@
+//! A client for `ssh-agent` and compatible programs, which hold private keys and sign on behalf of their users.
+//!
+//! The protocol is described in [draft-miller-ssh-agent](https://datatracker.ietf.org/doc/html/draft-miller-ssh-agent).
+use std::{
+    io::{Read, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+};
+
+use crate::wire;
+
+const FAILURE: u8 = 5;
+const REQUEST_IDENTITIES: u8 = 11;
+const IDENTITIES_ANSWER: u8 = 12;
+const SIGN_REQUEST: u8 = 13;
+const SIGN_RESPONSE: u8 = 14;
+
+/// Ask for `rsa-sha2-512` signatures when signing with RSA keys.
+const RSA_SHA2_512: u32 = 4;
+
+/// The largest response we accept.
+const MAX_RESPONSE_LEN: usize = 256 * 1024;
+
+/// The error returned when talking to an agent.
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not communicate with the agent")]
+    Io(#[from] std::io::Error),
+    #[error("The agent sent a malformed response")]
+    Decode(#[from] wire::Error),
+    #[error("The agent sent a key or signature that could not be decoded")]
+    Key(#[from] ssh_key::Error),
+    #[error("The agent refused the request")]
+    Refused,
+    #[error("The agent responded with message {actual} instead of {expected}")]
+    UnexpectedResponse { expected: u8, actual: u8 },
+}
+
+/// A connection to an agent.
+pub struct Client {
+    stream: UnixStream,
+}
+
+impl Client {
+    /// Connect to the agent listening on the socket at `path`, typically the value of `SSH_AUTH_SOCK`.
+    pub fn connect(path: &Path) -> Result<Self, Error> {
+        Ok(Client {
+            stream: UnixStream::connect(path)?,
+        })
+    }
+
+    /// Return the public keys of all identities held by the agent.
+    pub fn identities(&mut self) -> Result<Vec<ssh_key::PublicKey>, Error> {
+        let response = self.request(&[REQUEST_IDENTITIES], IDENTITIES_ANSWER)?;
+        let mut response = wire::Reader::new(&response[1..]);
+        let count = response.u32()?;
+        let mut keys = Vec::new();
+        for _ in 0..count {
+            let blob = response.string()?;
+            let comment = response.utf8()?;
+            // Skip keys we can't use, like certificates.
+            if let Ok(mut key) = ssh_key::PublicKey::from_bytes(blob) {
+                key.set_comment(comment);
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Ask the agent to sign `data` with the private key belonging to `key`.
+    ///
+    /// RSA keys are asked to produce `rsa-sha2-512` signatures.
+    pub fn sign(&mut self, key: &ssh_key::PublicKey, data: &[u8]) -> Result<ssh_key::Signature, Error> {
+        let flags = if key.algorithm().is_rsa() { RSA_SHA2_512 } else { 0 };
+        let request = wire::Message::new(SIGN_REQUEST)
+            .string(key.to_bytes()?)
+            .string(data)
+            .u32(flags);
+        let response = self.request(request.as_ref(), SIGN_RESPONSE)?;
+        let signature = wire::Reader::new(&response[1..]).string()?;
+        Ok(ssh_key::Signature::try_from(signature)?)
+    }
+
+    fn request(&mut self, message: &[u8], expected: u8) -> Result<Vec<u8>, Error> {
+        let mut frame = Vec::with_capacity(4 + message.len());
+        wire::put_string(&mut frame, message);
+        self.stream.write_all(&frame)?;
+
+        let mut len = [0u8; 4];
+        self.stream.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len == 0 || len > MAX_RESPONSE_LEN {
+            return Err(wire::Error::Truncated.into());
+        }
+        let mut response = vec![0; len];
+        self.stream.read_exact(&mut response)?;
+        match response[0] {
+            actual if actual == expected => Ok(response),
+            FAILURE => Err(Error::Refused),
+            actual => Err(Error::UnexpectedResponse { expected, actual }),
+        }
+    }
+}
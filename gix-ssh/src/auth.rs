@@ -0,0 +1,336 @@
+//! User authentication as described in [RFC 4252](https://www.rfc-editor.org/rfc/rfc4252) and
+//! [RFC 4256](https://www.rfc-editor.org/rfc/rfc4256).
+//!
+//! Methods are tried in the order `publickey`, with the keys of an agent followed by key files, `keyboard-interactive`
+//! and `password`, skipping all methods the server doesn't accept.
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use signature::Signer;
+
+use crate::{Transport, msg, transport, wire};
+
+/// How often to ask for passwords and passphrases before giving up.
+const MAX_PROMPTS: usize = 3;
+
+/// The error returned by [`authenticate()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Transport(#[from] transport::Error),
+    #[error("The server sent a malformed message during authentication")]
+    Decode(#[from] wire::Error),
+    #[cfg(unix)]
+    #[error("Could not authenticate using the agent")]
+    Agent(#[from] crate::agent::Error),
+    #[error("Could not encode a key or signature")]
+    Key(#[from] ssh_key::Error),
+    #[error("Could not sign with the private key")]
+    Sign(#[from] signature::Error),
+    #[error("Permission denied for user '{user}', the server would accept the methods {methods:?}")]
+    Denied {
+        user: String,
+        methods: Vec<String>,
+        /// The last error that occurred when prompting the user.
+        #[source]
+        prompt: Option<gix_prompt::Error>,
+    },
+}
+
+/// The options for use in [`authenticate()`].
+#[derive(Default, Clone)]
+pub struct Options {
+    /// The name of the user to log in as.
+    pub user: String,
+    /// The paths to files with private keys in the OpenSSH format, tried in order.
+    ///
+    /// Files that don't exist or can't be read are skipped.
+    pub identity_files: Vec<PathBuf>,
+    /// The path to the socket of an `ssh-agent` whose keys are tried before `identity_files`.
+    ///
+    /// Agents are only supported on Unix.
+    pub agent: Option<PathBuf>,
+    /// The options for asking for passphrases of key files, passwords and answers to `keyboard-interactive` prompts.
+    pub prompt: gix_prompt::Options,
+}
+
+/// Authenticate as the user in `options` over `transport` to `host`, which is used in prompts.
+pub fn authenticate<S: Read + Write>(transport: &mut Transport<S>, host: &str, options: &Options) -> Result<(), Error> {
+    transport.write_message(wire::Message::new(msg::SERVICE_REQUEST).string("ssh-userauth"))?;
+    loop {
+        let message = transport.read_message()?;
+        match message[0] {
+            msg::SERVICE_ACCEPT => break,
+            msg::EXT_INFO => continue,
+            actual => {
+                return Err(transport::Error::UnexpectedMessage {
+                    expected: msg::SERVICE_ACCEPT,
+                    actual,
+                }
+                .into());
+            }
+        }
+    }
+
+    let mut auth = Authenticator {
+        transport,
+        user: &options.user,
+        methods: Vec::new(),
+        prompt_error: None,
+    };
+    if auth.request(
+        wire::Message::new(msg::USERAUTH_REQUEST)
+            .string(&options.user)
+            .string("ssh-connection")
+            .string("none"),
+    )? {
+        return Ok(());
+    }
+
+    if auth.allows("publickey") {
+        let mut tried = Vec::new();
+        #[cfg(unix)]
+        if let Some(path) = options.agent.as_deref() {
+            match crate::agent::Client::connect(path) {
+                Ok(mut agent) => {
+                    for key in agent.identities()? {
+                        tried.push(key.key_data().clone());
+                        if auth.publickey(&key, |data| Ok(agent.sign(&key, data)?))? {
+                            return Ok(());
+                        }
+                    }
+                }
+                Err(_err) => gix_trace::warn!("Could not connect to ssh-agent at '{}': {}", path.display(), _err),
+            }
+        }
+        for path in &options.identity_files {
+            let Some(key) = read_private_key(path) else { continue };
+            if tried.contains(key.public_key().key_data()) || !auth.query_publickey(key.public_key())? {
+                continue;
+            }
+            let Some(key) = auth.decrypt(key, path, &options.prompt) else {
+                continue;
+            };
+            if auth.publickey(key.public_key(), |data| Ok(key.try_sign(data)?))? {
+                return Ok(());
+            }
+        }
+    }
+
+    if auth.allows("keyboard-interactive") && auth.keyboard_interactive(&options.prompt)? {
+        return Ok(());
+    }
+
+    if auth.allows("password") {
+        for _ in 0..MAX_PROMPTS {
+            let prompt = format!("{}@{host}'s password: ", options.user);
+            let Some(password) = auth.ask(&prompt, gix_prompt::Mode::Hidden, &options.prompt) else {
+                break;
+            };
+            let request = wire::Message::new(msg::USERAUTH_REQUEST)
+                .string(&options.user)
+                .string("ssh-connection")
+                .string("password")
+                .bool(false)
+                .string(password);
+            if auth.request(request)? {
+                return Ok(());
+            }
+            if !auth.allows("password") {
+                break;
+            }
+        }
+    }
+
+    Err(Error::Denied {
+        user: options.user.clone(),
+        methods: auth.methods,
+        prompt: auth.prompt_error,
+    })
+}
+
+struct Authenticator<'a, S> {
+    transport: &'a mut Transport<S>,
+    user: &'a str,
+    /// The methods the server accepts, as sent with the last failure.
+    methods: Vec<String>,
+    prompt_error: Option<gix_prompt::Error>,
+}
+
+impl<S: Read + Write> Authenticator<'_, S> {
+    fn allows(&self, method: &str) -> bool {
+        self.methods.iter().any(|allowed| allowed == method)
+    }
+
+    /// Send `request` and return `true` if it succeeded.
+    fn request(&mut self, request: wire::Message) -> Result<bool, Error> {
+        self.transport.write_message(request)?;
+        let response = self.response()?;
+        self.outcome(&response)
+    }
+
+    /// Return the next message that isn't a banner.
+    fn response(&mut self) -> Result<Vec<u8>, Error> {
+        loop {
+            let message = self.transport.read_message()?;
+            match message[0] {
+                msg::USERAUTH_BANNER | msg::EXT_INFO => continue,
+                _ => return Ok(message),
+            }
+        }
+    }
+
+    /// Return `true` if `response` indicates success, or `false` if the request failed.
+    fn outcome(&mut self, response: &[u8]) -> Result<bool, Error> {
+        match response[0] {
+            msg::USERAUTH_SUCCESS => Ok(true),
+            msg::USERAUTH_FAILURE => {
+                self.methods = wire::Reader::new(&response[1..])
+                    .name_list()?
+                    .into_iter()
+                    .map(ToOwned::to_owned)
+                    .collect();
+                Ok(false)
+            }
+            actual => Err(transport::Error::UnexpectedMessage {
+                expected: msg::USERAUTH_FAILURE,
+                actual,
+            }
+            .into()),
+        }
+    }
+
+    /// Ask the server if it would accept `key`, without proving that we own it.
+    fn query_publickey(&mut self, key: &ssh_key::PublicKey) -> Result<bool, Error> {
+        let request = wire::Message::new(msg::USERAUTH_REQUEST)
+            .string(self.user)
+            .string("ssh-connection")
+            .string("publickey")
+            .bool(false)
+            .string(signature_algorithm(key))
+            .string(key.to_bytes()?);
+        self.transport.write_message(request)?;
+        let response = self.response()?;
+        if response[0] == msg::USERAUTH_PK_OK {
+            return Ok(true);
+        }
+        self.outcome(&response)?;
+        Ok(false)
+    }
+
+    /// Authenticate with `key`, proving that we own it by signing with `sign`.
+    fn publickey(
+        &mut self,
+        key: &ssh_key::PublicKey,
+        sign: impl FnOnce(&[u8]) -> Result<ssh_key::Signature, Error>,
+    ) -> Result<bool, Error> {
+        let request = wire::Message::new(msg::USERAUTH_REQUEST)
+            .string(self.user)
+            .string("ssh-connection")
+            .string("publickey")
+            .bool(true)
+            .string(signature_algorithm(key))
+            .string(key.to_bytes()?);
+        let mut signed_data = Vec::new();
+        wire::put_string(&mut signed_data, self.transport.session_id());
+        signed_data.extend_from_slice(request.as_ref());
+        let signature: Vec<u8> = sign(&signed_data)?.try_into()?;
+        self.request(request.string(signature))
+    }
+
+    fn keyboard_interactive(&mut self, prompt_options: &gix_prompt::Options) -> Result<bool, Error> {
+        let request = wire::Message::new(msg::USERAUTH_REQUEST)
+            .string(self.user)
+            .string("ssh-connection")
+            .string("keyboard-interactive")
+            .string("")
+            .string("");
+        self.transport.write_message(request)?;
+        loop {
+            let response = self.response()?;
+            if response[0] != msg::USERAUTH_INFO_REQUEST {
+                return self.outcome(&response);
+            }
+            let mut info = wire::Reader::new(&response[1..]);
+            let _name = info.utf8()?;
+            let _instruction = info.utf8()?;
+            let _language = info.utf8()?;
+            let num_prompts = info.u32()?;
+            let mut reply = wire::Message::new(msg::USERAUTH_INFO_RESPONSE).u32(num_prompts);
+            for _ in 0..num_prompts {
+                let prompt = info.utf8()?;
+                let echo = info.bool()?;
+                let mode = if echo {
+                    gix_prompt::Mode::Visible
+                } else {
+                    gix_prompt::Mode::Hidden
+                };
+                // Sending the next request aborts this one, so we can try other methods.
+                let Some(answer) = self.ask(prompt, mode, prompt_options) else {
+                    return Ok(false);
+                };
+                reply = reply.string(answer);
+            }
+            self.transport.write_message(reply)?;
+        }
+    }
+
+    /// Return `key` with its private parts decrypted, asking for the passphrase if needed.
+    fn decrypt(
+        &mut self,
+        key: ssh_key::PrivateKey,
+        path: &Path,
+        prompt_options: &gix_prompt::Options,
+    ) -> Option<ssh_key::PrivateKey> {
+        if !key.is_encrypted() {
+            return Some(key);
+        }
+        let prompt = format!("Enter passphrase for key '{}': ", path.display());
+        for _ in 0..MAX_PROMPTS {
+            let passphrase = self.ask(&prompt, gix_prompt::Mode::Hidden, prompt_options)?;
+            match key.decrypt(passphrase) {
+                Ok(key) => return Some(key),
+                Err(_err) => gix_trace::warn!("Could not decrypt key at '{}': {}", path.display(), _err),
+            }
+        }
+        None
+    }
+
+    /// Ask the user with `prompt`, using `mode` unless prompting is disabled, and remember errors.
+    fn ask(&mut self, prompt: &str, mode: gix_prompt::Mode, options: &gix_prompt::Options) -> Option<String> {
+        let mut options = options.clone();
+        if options.mode != gix_prompt::Mode::Disable {
+            options.mode = mode;
+        }
+        gix_prompt::ask(prompt, &options)
+            .map_err(|err| self.prompt_error = Some(err))
+            .ok()
+    }
+}
+
+/// Return the name of the algorithm to sign with `key`, which for RSA keys is always `rsa-sha2-512`.
+fn signature_algorithm(key: &ssh_key::PublicKey) -> String {
+    match key.algorithm() {
+        ssh_key::Algorithm::Rsa { .. } => "rsa-sha2-512".into(),
+        other => other.as_str().into(),
+    }
+}
+
+/// Read the private key at `path`, or return `None` if that's not possible.
+fn read_private_key(path: &Path) -> Option<ssh_key::PrivateKey> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(_err) => {
+            if _err.kind() != std::io::ErrorKind::NotFound {
+                gix_trace::warn!("Could not read key at '{}': {}", path.display(), _err);
+            }
+            return None;
+        }
+    };
+    ssh_key::PrivateKey::from_openssh(data)
+        .map_err(|_err| gix_trace::warn!("Could not parse key at '{}': {}", path.display(), _err))
+        .ok()
+}
@@ -0,0 +1,333 @@
+//! Channels of the connection protocol as described in [RFC 4254](https://www.rfc-editor.org/rfc/rfc4254), which
+//! are used to run commands on the remote host or to reach other hosts through it.
+//!
+//! Each [`Channel`] owns its transport, so only one channel can be open per connection.
+use std::io::{Read, Write};
+
+use bstr::ByteSlice;
+
+use crate::{Transport, msg, transport, wire};
+
+/// The channel number we use, as there is only one channel per connection.
+const LOCAL_ID: u32 = 0;
+/// The amount of bytes the server may send before we grant more.
+const WINDOW_SIZE: u32 = 2 * 1024 * 1024;
+/// The largest amount of data we accept in a single message.
+const MAX_PACKET_SIZE: u32 = 32 * 1024;
+/// The kind of extended data that represents the standard error of a command.
+const EXTENDED_DATA_STDERR: u32 = 1;
+
+/// The error returned when opening or using a [`Channel`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Transport(#[from] transport::Error),
+    #[error("The server sent a malformed message on the channel")]
+    Decode(#[from] wire::Error),
+    #[error("The server refused to open a channel with reason {reason}: {description}")]
+    OpenFailed { reason: u32, description: String },
+    #[error("The server refused the '{request}' request")]
+    RequestFailed { request: &'static str },
+    #[error("The channel was closed by the server")]
+    Closed,
+}
+
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Transport(transport::Error::Io(err)) => err,
+            Error::Closed => std::io::Error::new(std::io::ErrorKind::BrokenPipe, err),
+            err => std::io::Error::other(err),
+        }
+    }
+}
+
+/// A channel to a command running on the remote host, or to another host reached through it, which can be read from and written to.
+///
+/// Reading yields the standard output of a remote command, while its standard error is collected
+/// and made available through [`stderr()`](Self::stderr()).
+/// If the command exits with a non-zero status, reaching the end of its output fails with an error containing its standard error.
+pub struct Channel<S> {
+    transport: Transport<S>,
+    remote_id: u32,
+    /// The amount of bytes we may still send.
+    remote_window: u32,
+    remote_max_packet: u32,
+    /// The amount of bytes received and consumed that weren't granted to the server again.
+    consumed: u32,
+    /// Received data that wasn't read yet, starting at `pos`.
+    buf: Vec<u8>,
+    pos: usize,
+    stderr: Vec<u8>,
+    exit_status: Option<u32>,
+    /// The server won't send more data.
+    eof: bool,
+    /// The server closed the channel, and we may not send anymore.
+    closed: bool,
+}
+
+/// Lifecycle
+impl<S: Read + Write> Channel<S> {
+    /// Open a `session` channel over an authenticated `transport`, to then [`exec()`](Self::exec()) a command.
+    pub fn open_session(transport: Transport<S>) -> Result<Self, Error> {
+        Self::open(transport, "session", |msg| msg)
+    }
+
+    /// Open a channel to `port` on `host` as reached from the remote host over an authenticated `transport`,
+    /// as used to connect through jump hosts.
+    pub fn open_direct_tcpip(transport: Transport<S>, host: &str, port: u16) -> Result<Self, Error> {
+        Self::open(transport, "direct-tcpip", |msg| {
+            msg.string(host).u32(port.into()).string("127.0.0.1").u32(0)
+        })
+    }
+
+    fn open(
+        mut transport: Transport<S>,
+        kind: &str,
+        type_specific_data: impl FnOnce(wire::Message) -> wire::Message,
+    ) -> Result<Self, Error> {
+        transport.write_message(type_specific_data(
+            wire::Message::new(msg::CHANNEL_OPEN)
+                .string(kind)
+                .u32(LOCAL_ID)
+                .u32(WINDOW_SIZE)
+                .u32(MAX_PACKET_SIZE),
+        ))?;
+        loop {
+            let message = transport.read_message()?;
+            let mut fields = wire::Reader::new(&message[1..]);
+            match message[0] {
+                msg::CHANNEL_OPEN_CONFIRMATION => {
+                    let _recipient = fields.u32()?;
+                    return Ok(Channel {
+                        remote_id: fields.u32()?,
+                        remote_window: fields.u32()?,
+                        remote_max_packet: fields.u32()?,
+                        transport,
+                        consumed: 0,
+                        buf: Vec::new(),
+                        pos: 0,
+                        stderr: Vec::new(),
+                        exit_status: None,
+                        eof: false,
+                        closed: false,
+                    });
+                }
+                msg::CHANNEL_OPEN_FAILURE => {
+                    let _recipient = fields.u32()?;
+                    return Err(Error::OpenFailed {
+                        reason: fields.u32()?,
+                        description: fields.string()?.to_str_lossy().into_owned(),
+                    });
+                }
+                msg::GLOBAL_REQUEST => reply_to_global_request(&mut transport, &message)?,
+                // Unrelated messages, like `EXT_INFO`.
+                _ => {}
+            }
+        }
+    }
+
+    /// Ask the server to set the environment variable `name` to `value` for the command to execute.
+    ///
+    /// Note that servers typically ignore all variables that aren't explicitly allowed, like `GIT_PROTOCOL`,
+    /// so this request doesn't wait for a reply.
+    pub fn set_env(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        let request = wire::Message::new(msg::CHANNEL_REQUEST)
+            .u32(self.remote_id)
+            .string("env")
+            .bool(false)
+            .string(name)
+            .string(value);
+        self.transport.write_message(request)?;
+        Ok(())
+    }
+
+    /// Execute `command` on the remote host, whose standard input and output are connected to this channel.
+    pub fn exec(&mut self, command: &str) -> Result<(), Error> {
+        let request = wire::Message::new(msg::CHANNEL_REQUEST)
+            .u32(self.remote_id)
+            .string("exec")
+            .bool(true)
+            .string(command);
+        self.transport.write_message(request)?;
+        loop {
+            let message = self.transport.read_message()?;
+            match message[0] {
+                msg::CHANNEL_SUCCESS => return Ok(()),
+                msg::CHANNEL_FAILURE => return Err(Error::RequestFailed { request: "exec" }),
+                _ => self.handle(&message)?,
+            }
+            if self.closed {
+                return Err(Error::Closed);
+            }
+        }
+    }
+
+    /// Tell the server that we won't send any more data.
+    pub fn send_eof(&mut self) -> Result<(), Error> {
+        if !self.closed {
+            self.transport
+                .write_message(wire::Message::new(msg::CHANNEL_EOF).u32(self.remote_id))?;
+        }
+        Ok(())
+    }
+}
+
+/// Access
+impl<S> Channel<S> {
+    /// Return everything the remote command wrote to its standard error so far.
+    pub fn stderr(&self) -> &[u8] {
+        &self.stderr
+    }
+
+    /// Return the exit status of the remote command, if it is known.
+    pub fn exit_status(&self) -> Option<u32> {
+        self.exit_status
+    }
+}
+
+impl<S: Read + Write> Channel<S> {
+    /// Process a `message` that was received for this channel, or for the connection in general.
+    fn handle(&mut self, message: &[u8]) -> Result<(), Error> {
+        let mut fields = wire::Reader::new(&message[1..]);
+        match message[0] {
+            msg::CHANNEL_DATA => {
+                fields.u32()?;
+                let data = fields.string()?;
+                self.buf.drain(..self.pos);
+                self.pos = 0;
+                self.buf.extend_from_slice(data);
+            }
+            msg::CHANNEL_EXTENDED_DATA => {
+                fields.u32()?;
+                let kind = fields.u32()?;
+                let data = fields.string()?;
+                if kind == EXTENDED_DATA_STDERR {
+                    self.stderr.extend_from_slice(data);
+                }
+                // Nobody reads this data later, so it's consumed right away.
+                self.consume(data.len())?;
+            }
+            msg::CHANNEL_WINDOW_ADJUST => {
+                fields.u32()?;
+                self.remote_window = self.remote_window.saturating_add(fields.u32()?);
+            }
+            msg::CHANNEL_EOF => self.eof = true,
+            msg::CHANNEL_CLOSE if !self.closed => {
+                self.closed = true;
+                self.eof = true;
+                self.transport
+                    .write_message(wire::Message::new(msg::CHANNEL_CLOSE).u32(self.remote_id))?;
+            }
+            msg::CHANNEL_REQUEST => {
+                fields.u32()?;
+                let request = fields.string()?;
+                let want_reply = fields.bool()?;
+                if request == b"exit-status" {
+                    self.exit_status = Some(fields.u32()?);
+                }
+                if want_reply {
+                    self.transport
+                        .write_message(wire::Message::new(msg::CHANNEL_FAILURE).u32(self.remote_id))?;
+                }
+            }
+            msg::GLOBAL_REQUEST => reply_to_global_request(&mut self.transport, message)?,
+            // Messages we don't care about, like `EXT_INFO` or the answers to `exit-signal` requests.
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Grant the server to send more data once enough of it was consumed.
+    fn consume(&mut self, amount: usize) -> Result<(), Error> {
+        self.consumed += amount as u32;
+        if self.consumed >= WINDOW_SIZE / 2 && !self.closed {
+            self.transport.write_message(
+                wire::Message::new(msg::CHANNEL_WINDOW_ADJUST)
+                    .u32(self.remote_id)
+                    .u32(self.consumed),
+            )?;
+            self.consumed = 0;
+        }
+        Ok(())
+    }
+
+    /// Turn the end of the output into an error if the remote command failed.
+    fn end_of_output(&mut self) -> std::io::Result<usize> {
+        // Wait for the channel to be closed to learn about the exit status, which is sent after the end of the output.
+        while !self.closed {
+            let message = self.transport.read_message().map_err(Error::from)?;
+            self.handle(&message)?;
+        }
+        match self.exit_status {
+            Some(status) if status != 0 => Err(std::io::Error::other(format!(
+                "The remote command failed with exit status {status}: {}",
+                self.stderr.trim().to_str_lossy()
+            ))),
+            _ => Ok(0),
+        }
+    }
+}
+
+impl<S: Read + Write> Read for Channel<S> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            if self.pos < self.buf.len() {
+                let n = out.len().min(self.buf.len() - self.pos);
+                out[..n].copy_from_slice(&self.buf[self.pos..][..n]);
+                self.pos += n;
+                self.consume(n)?;
+                return Ok(n);
+            }
+            if self.eof {
+                return self.end_of_output();
+            }
+            let message = self.transport.read_message().map_err(Error::from)?;
+            self.handle(&message)?;
+        }
+    }
+}
+
+impl<S: Read + Write> Write for Channel<S> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        while self.remote_window == 0 && !self.closed {
+            let message = self.transport.read_message().map_err(Error::from)?;
+            self.handle(&message)?;
+        }
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        let n = data
+            .len()
+            .min(self.remote_window as usize)
+            .min(self.remote_max_packet.min(MAX_PACKET_SIZE) as usize);
+        self.transport
+            .write_message(
+                wire::Message::new(msg::CHANNEL_DATA)
+                    .u32(self.remote_id)
+                    .string(&data[..n]),
+            )
+            .map_err(Error::from)?;
+        self.remote_window -= n as u32;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        // Each message is flushed when it's sent.
+        Ok(())
+    }
+}
+
+/// Reply to a global request of the server, like the `hostkeys-00@openssh.com` announcement, which we don't support.
+fn reply_to_global_request<S: Read + Write>(transport: &mut Transport<S>, message: &[u8]) -> Result<(), Error> {
+    let mut fields = wire::Reader::new(&message[1..]);
+    let _name = fields.string()?;
+    if fields.bool()? {
+        transport.write_message([msg::REQUEST_FAILURE])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,407 @@
+//! Reading of `ssh_config` files to learn how to connect to a host, like `ssh` does.
+//!
+//! Only the options that affect how `gix-ssh` connects are evaluated: `HostName`, `Port`, `User`, `IdentityFile`,
+//! `ProxyJump`, `UserKnownHostsFile` and `StrictHostKeyChecking`.
+//! Sections are started by `Host`, and `Include` is supported when reading files.
+//! `Match` sections are only applied if they use the `all` criterion, all others are skipped as the criteria aren't evaluated.
+use std::path::{Path, PathBuf};
+
+use crate::pattern;
+
+/// The maximum amount of nested `Include` directives, as used by `ssh` as well.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// The error returned when reading `ssh_config` files.
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not read ssh configuration file at '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("'Include' directives are nested more than {MAX_INCLUDE_DEPTH} levels deep at '{}'", path.display())]
+    IncludeDepth { path: PathBuf },
+}
+
+/// The parsed content of one or more `ssh_config` files, in the order they are consulted.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone)]
+struct Section {
+    condition: Condition,
+    /// The lower-cased keywords along with their arguments.
+    options: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    /// Options before the first `Host` or `Match` line, or in a `Match all` section.
+    Always,
+    /// The patterns of a `Host` line.
+    Host(Vec<String>),
+    /// A `Match` line we can't evaluate.
+    Never,
+}
+
+impl Condition {
+    fn applies_to(&self, host: &str) -> bool {
+        match self {
+            Condition::Always => true,
+            Condition::Host(patterns) => pattern::matches_list(patterns.iter().map(String::as_str), host),
+            Condition::Never => false,
+        }
+    }
+}
+
+/// What to do if the host key of a server isn't known yet, as configured by `StrictHostKeyChecking`.
+///
+/// Note that connecting to hosts whose key changed always fails, unlike with `ssh` which allows it in some configurations.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StrictHostKeyChecking {
+    /// Refuse to connect.
+    Yes,
+    /// Add the key to the user's `known_hosts` file and connect.
+    AcceptNew,
+    /// Like [`AcceptNew`](Self::AcceptNew).
+    No,
+    /// Ask the user if the key should be trusted, and add it to the user's `known_hosts` file if so.
+    #[default]
+    Ask,
+}
+
+/// A host to connect through, as configured with `ProxyJump`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Jump {
+    /// The user to log in as, if set.
+    pub user: Option<String>,
+    /// The host to connect to, which is itself looked up in the configuration.
+    pub host: String,
+    /// The port to connect to, if set.
+    pub port: Option<u16>,
+}
+
+/// The configuration that applies to a particular host, as returned by [`Config::resolve()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Host {
+    /// The name or address to actually connect to, which is the host itself unless `HostName` is set.
+    pub host_name: String,
+    /// The port to connect to, if configured.
+    pub port: Option<u16>,
+    /// The user to log in as, if configured.
+    pub user: Option<String>,
+    /// The files holding private keys to authenticate with, in order.
+    pub identity_files: Vec<PathBuf>,
+    /// The hosts to connect through, in order, or an empty list to connect directly.
+    pub proxy_jump: Vec<Jump>,
+    /// The `known_hosts` files of the user, if configured.
+    pub user_known_hosts_files: Option<Vec<PathBuf>>,
+    /// What to do with unknown host keys, if configured.
+    pub strict_host_key_checking: Option<StrictHostKeyChecking>,
+}
+
+/// Initialization
+impl Config {
+    /// Parse `input` as `ssh_config` file, ignoring all `Include` directives.
+    pub fn from_bytes(input: &[u8]) -> Self {
+        let mut config = Config::default();
+        config
+            .parse(input, None, 0, Condition::Always)
+            .expect("cannot fail without includes");
+        config
+    }
+
+    /// Read the `ssh_config` file at `path`, which results in an empty configuration if it doesn't exist.
+    ///
+    /// Relative paths in `Include` directives are resolved relative to the directory containing `path`.
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        let mut config = Config::default();
+        config.append_path(path)?;
+        Ok(config)
+    }
+
+    /// Read the user's `~/.ssh/config` file followed by the system-wide `/etc/ssh/ssh_config` file.
+    pub fn from_default_files() -> Result<Self, Error> {
+        let mut config = Config::default();
+        if let Some(home) = gix_path::env::home_dir() {
+            config.append_path(&home.join(".ssh").join("config"))?;
+        }
+        config.append_path(Path::new("/etc/ssh/ssh_config"))?;
+        Ok(config)
+    }
+
+    /// Append the configuration at `path`, which has lower precedence than everything read so far.
+    pub fn append_path(&mut self, path: &Path) -> Result<(), Error> {
+        self.include(path, path.parent().unwrap_or(Path::new(".")), 0, Condition::Always)
+    }
+
+    fn include(&mut self, path: &Path, base: &Path, depth: usize, condition: Condition) -> Result<(), Error> {
+        if depth > MAX_INCLUDE_DEPTH {
+            return Err(Error::IncludeDepth { path: path.into() });
+        }
+        let input = match std::fs::read(path) {
+            Ok(input) => input,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => {
+                return Err(Error::Io {
+                    source: err,
+                    path: path.into(),
+                });
+            }
+        };
+        self.parse(&input, Some(base), depth, condition)
+    }
+
+    fn parse(&mut self, input: &[u8], base: Option<&Path>, depth: usize, condition: Condition) -> Result<(), Error> {
+        self.sections.push(Section {
+            condition,
+            options: Vec::new(),
+        });
+        for line in input.split(|b| *b == b'\n') {
+            let line = String::from_utf8_lossy(line);
+            let Some((keyword, args)) = parse_line(&line) else {
+                continue;
+            };
+            match keyword.as_str() {
+                "host" => self.sections.push(Section {
+                    condition: Condition::Host(args.iter().map(|pattern| pattern.to_lowercase()).collect()),
+                    options: Vec::new(),
+                }),
+                "match" => self.sections.push(Section {
+                    condition: if args.len() == 1 && args[0].eq_ignore_ascii_case("all") {
+                        Condition::Always
+                    } else {
+                        Condition::Never
+                    },
+                    options: Vec::new(),
+                }),
+                "include" => {
+                    let Some(base) = base else { continue };
+                    let current = self.sections.last().expect("always present").condition.clone();
+                    for path in args.iter().flat_map(|arg| expand_include(arg, base)) {
+                        self.include(&path, base, depth + 1, current.clone())?;
+                    }
+                    self.sections.push(Section {
+                        condition: current,
+                        options: Vec::new(),
+                    });
+                }
+                _ => self
+                    .sections
+                    .last_mut()
+                    .expect("always present")
+                    .options
+                    .push((keyword, args)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Querying
+impl Config {
+    /// Return the configuration for `host`, the name of the host as given by the user, for instance in a URL.
+    ///
+    /// For each option, the first value found in a matching section is used,
+    /// except for `IdentityFile` whose values are accumulated.
+    pub fn resolve(&self, host: &str) -> Host {
+        let lowercase_host = host.to_lowercase();
+        let mut host_name = None;
+        let mut port = None;
+        let mut user = None;
+        let mut identity_files = Vec::new();
+        let mut proxy_jump = None;
+        let mut user_known_hosts_files = None;
+        let mut strict_host_key_checking = None;
+        for section in self
+            .sections
+            .iter()
+            .filter(|section| section.condition.applies_to(&lowercase_host))
+        {
+            for (keyword, args) in &section.options {
+                let Some(value) = args.first() else { continue };
+                match keyword.as_str() {
+                    "hostname" => {
+                        host_name.get_or_insert_with(|| value.clone());
+                    }
+                    "port" if port.is_none() => port = value.parse::<u16>().ok().map(Some),
+                    "user" => {
+                        user.get_or_insert_with(|| value.clone());
+                    }
+                    "identityfile" => identity_files.push(value.clone()),
+                    "proxyjump" => {
+                        proxy_jump.get_or_insert_with(|| value.clone());
+                    }
+                    "userknownhostsfile" => {
+                        user_known_hosts_files.get_or_insert_with(|| args.clone());
+                    }
+                    "stricthostkeychecking" if strict_host_key_checking.is_none() => {
+                        strict_host_key_checking = parse_strict_host_key_checking(value).map(Some);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let host_name = host_name.map_or_else(|| host.to_owned(), |name| expand_tokens(&name, host, None, None));
+        let port = port.flatten();
+        let expand_path = |path: &str| expand_tilde(&expand_tokens(path, &host_name, user.as_deref(), port));
+        Host {
+            identity_files: identity_files
+                .iter()
+                .filter(|path| !path.eq_ignore_ascii_case("none"))
+                .map(|path| expand_path(path))
+                .collect(),
+            user_known_hosts_files: user_known_hosts_files
+                .map(|files| files.iter().map(|path| expand_path(path)).collect::<Vec<_>>())
+                .filter(|files| !files.iter().any(|path| path.as_os_str() == "none")),
+            proxy_jump: proxy_jump
+                .filter(|jumps| !jumps.eq_ignore_ascii_case("none"))
+                .map(|jumps| jumps.split(',').filter_map(parse_jump).collect())
+                .unwrap_or_default(),
+            strict_host_key_checking: strict_host_key_checking.flatten(),
+            host_name,
+            port,
+            user,
+        }
+    }
+}
+
+/// Split `line` into its lower-cased keyword and its arguments, or return `None` if it's empty or a comment.
+fn parse_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let keyword_end = line.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(line.len());
+    let (keyword, rest) = line.split_at(keyword_end);
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim_start();
+
+    let mut args = Vec::new();
+    let mut chars = rest.chars().peekable();
+    while chars.peek().is_some() {
+        let mut arg = String::new();
+        let mut in_quotes = false;
+        for c in chars.by_ref() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                c if c.is_whitespace() && !in_quotes => break,
+                c => arg.push(c),
+            }
+        }
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        args.push(arg);
+    }
+    Some((keyword.to_lowercase(), args))
+}
+
+fn parse_strict_host_key_checking(value: &str) -> Option<StrictHostKeyChecking> {
+    Some(match value.to_lowercase().as_str() {
+        "yes" | "true" => StrictHostKeyChecking::Yes,
+        "accept-new" => StrictHostKeyChecking::AcceptNew,
+        "no" | "off" | "false" => StrictHostKeyChecking::No,
+        "ask" => StrictHostKeyChecking::Ask,
+        _ => return None,
+    })
+}
+
+/// Parse a single jump host like `[ssh://][user@]host[:port]`, where `host` may be an IPv6 address in brackets.
+fn parse_jump(spec: &str) -> Option<Jump> {
+    let spec = spec.trim();
+    let spec = spec.strip_prefix("ssh://").unwrap_or(spec);
+    let (user, host_and_port) = match spec.rsplit_once('@') {
+        Some((user, rest)) => (Some(user.to_owned()), rest),
+        None => (None, spec),
+    };
+    let (host, port) = match host_and_port.strip_prefix('[') {
+        Some(bracketed) => {
+            let (host, rest) = bracketed.split_once(']')?;
+            (host, rest.strip_prefix(':'))
+        }
+        None => match host_and_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_and_port, None),
+        },
+    };
+    let port = match port {
+        Some(port) => Some(port.parse().ok()?),
+        None => None,
+    };
+    (!host.is_empty()).then(|| Jump {
+        user,
+        host: host.to_owned(),
+        port,
+    })
+}
+
+/// Substitute the tokens `%h` with `host`, `%r` with the remote `user`, `%p` with `port`,
+/// `%u` with the local user, `%d` with the home directory and `%%` with `%`.
+fn expand_tokens(input: &str, host: &str, user: Option<&str>, port: Option<u16>) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => out.push('%'),
+            Some('h') => out.push_str(host),
+            Some('r') => out.push_str(
+                &user
+                    .map_or_else(local_user, |user| Some(user.to_owned()))
+                    .unwrap_or_default(),
+            ),
+            Some('u') => out.push_str(&local_user().unwrap_or_default()),
+            Some('p') => out.push_str(&port.unwrap_or(22).to_string()),
+            Some('d') => {
+                if let Some(home) = gix_path::env::home_dir() {
+                    out.push_str(&home.to_string_lossy());
+                }
+            }
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+/// Replace a leading `~/` in `path` with the home directory.
+fn expand_tilde(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), gix_path::env::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => path.into(),
+    }
+}
+
+/// Return the paths of the files to include for `arg`, which may contain wildcards in its last component.
+fn expand_include(arg: &str, base: &Path) -> Vec<PathBuf> {
+    let path = expand_tilde(arg);
+    let path = if path.is_relative() { base.join(path) } else { path };
+    let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+    match (file_name, path.parent()) {
+        (Some(pattern), Some(dir)) if pattern.contains(['*', '?']) => {
+            let mut paths: Vec<_> = std::fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .filter(|entry| pattern::matches(&pattern, &entry.file_name().to_string_lossy()))
+                .map(|entry| entry.path())
+                .collect();
+            paths.sort();
+            paths
+        }
+        _ => vec![path],
+    }
+}
+
+/// Return the name of the user running this process.
+pub(crate) fn local_user() -> Option<String> {
+    std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok()
+}
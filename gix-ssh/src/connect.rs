@@ -0,0 +1,266 @@
+use std::path::PathBuf;
+
+use crate::{Config, auth, channel, config, known_hosts, transport};
+
+/// The most hosts we connect through to reach a host, which protects against `ProxyJump` directives that form a cycle.
+const MAX_JUMPS: usize = 8;
+
+/// The options for use in [`connect()`](function::connect()).
+#[derive(Default, Clone)]
+pub struct Options {
+    /// The configuration to learn how to reach hosts from.
+    pub config: Config,
+    /// The path to the socket of an `ssh-agent` to authenticate with, typically the value of `SSH_AUTH_SOCK`.
+    pub agent: Option<PathBuf>,
+    /// The options for asking for passwords, passphrases and whether to trust host keys.
+    pub prompt: gix_prompt::Options,
+}
+
+impl Options {
+    /// Create options from the default `ssh_config` files, the agent in `SSH_AUTH_SOCK` and the
+    /// prompt-related environment variables, like `GIT_ASKPASS`, `SSH_ASKPASS` and `GIT_TERMINAL_PROMPT`.
+    pub fn from_environment() -> Result<Self, config::Error> {
+        Ok(Options {
+            config: Config::from_default_files()?,
+            agent: std::env::var_os("SSH_AUTH_SOCK").map(Into::into),
+            prompt: gix_prompt::Options::default().apply_environment(true, true, true),
+        })
+    }
+}
+
+/// The error returned by [`connect()`](function::connect()).
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not connect to {host}:{port}")]
+    Io {
+        source: std::io::Error,
+        host: String,
+        port: u16,
+    },
+    #[error(transparent)]
+    Transport(#[from] transport::Error),
+    #[error(transparent)]
+    KnownHosts(#[from] known_hosts::Error),
+    #[error("The host key of '{host}' isn't known, its fingerprint is {fingerprint}")]
+    UnknownHostKey { host: String, fingerprint: String },
+    #[error(
+        "The host key of '{host}' doesn't match the one in the known_hosts file, which may mean that someone is impersonating the host. Its fingerprint is {fingerprint}"
+    )]
+    HostKeyMismatch { host: String, fingerprint: String },
+    #[error("The host key of '{host}' with fingerprint {fingerprint} was revoked")]
+    RevokedHostKey { host: String, fingerprint: String },
+    #[error(transparent)]
+    Auth(#[from] auth::Error),
+    #[error("Could not connect through a jump host")]
+    Channel(#[from] channel::Error),
+    #[error("No user was given to log in to '{host}' as, and the local user name is unknown")]
+    MissingUser { host: String },
+    #[error("'{host}' can only be reached through more than {MAX_JUMPS} jump hosts")]
+    TooManyJumps { host: String },
+}
+
+pub(super) mod function {
+    use std::net::TcpStream;
+
+    use super::{Error, MAX_JUMPS, Options};
+    use crate::{Session, Stream, Transport, auth, config, known_hosts};
+
+    /// Connect to `host`, a name that is looked up in the `ssh_config` of `options`, on `port` as `user`, verify its host key
+    /// and authenticate, to return a session ready to execute a command.
+    ///
+    /// `port` and `user` override the configuration if set, as they would if given on the command-line of `ssh`.
+    /// If the configuration doesn't set a user either, the name of the local user is used.
+    pub fn connect(host: &str, port: Option<u16>, user: Option<&str>, options: &Options) -> Result<Session, Error> {
+        connect_through(host, port, user, None, options, 0)
+    }
+
+    /// Like [`connect()`], but use `jumps` instead of the configured `ProxyJump` value if set,
+    /// with `depth` being the amount of hosts we are connecting through already.
+    fn connect_through(
+        host: &str,
+        port: Option<u16>,
+        user: Option<&str>,
+        jumps: Option<&[config::Jump]>,
+        options: &Options,
+        depth: usize,
+    ) -> Result<Session, Error> {
+        if depth > MAX_JUMPS {
+            return Err(Error::TooManyJumps { host: host.into() });
+        }
+        let resolved = options.config.resolve(host);
+        let port = port.or(resolved.port).unwrap_or(22);
+        let user = user
+            .map(ToOwned::to_owned)
+            .or_else(|| resolved.user.clone())
+            .or_else(config::local_user)
+            .ok_or_else(|| Error::MissingUser { host: host.into() })?;
+
+        let stream: Box<dyn Stream> = match jumps.unwrap_or(&resolved.proxy_jump).split_last() {
+            // Like `ssh`, only the last jump host uses its own configuration to learn how to reach it.
+            Some((last, previous)) => {
+                let jump = connect_through(
+                    &last.host,
+                    last.port,
+                    last.user.as_deref(),
+                    (!previous.is_empty()).then_some(previous),
+                    options,
+                    depth + 1,
+                )?;
+                Box::new(jump.direct_tcpip(&resolved.host_name, port)?)
+            }
+            None => Box::new(
+                TcpStream::connect((resolved.host_name.as_str(), port)).map_err(|source| Error::Io {
+                    source,
+                    host: resolved.host_name.clone(),
+                    port,
+                })?,
+            ),
+        };
+
+        let mut transport = handshake(stream, &resolved, port, options)?;
+        let home_ssh_dir = gix_path::env::home_dir().map(|home| home.join(".ssh"));
+        let identity_files = if resolved.identity_files.is_empty() {
+            home_ssh_dir
+                .iter()
+                .flat_map(|dir| ["id_ed25519", "id_ecdsa", "id_rsa"].map(|name| dir.join(name)))
+                .collect()
+        } else {
+            resolved.identity_files
+        };
+        auth::authenticate(
+            &mut transport,
+            &resolved.host_name,
+            &auth::Options {
+                user,
+                identity_files,
+                agent: options.agent.clone(),
+                prompt: options.prompt.clone(),
+            },
+        )?;
+        Ok(Session::new(transport))
+    }
+
+    /// Establish the transport over `stream` and verify the host key of `host` against the `known_hosts` files.
+    fn handshake(
+        stream: Box<dyn Stream>,
+        host: &config::Host,
+        port: u16,
+        options: &Options,
+    ) -> Result<Transport<Box<dyn Stream>>, Error> {
+        let user_files = host.user_known_hosts_files.clone().unwrap_or_else(|| {
+            gix_path::env::home_dir()
+                .map(|home| {
+                    let dir = home.join(".ssh");
+                    vec![dir.join("known_hosts"), dir.join("known_hosts2")]
+                })
+                .unwrap_or_default()
+        });
+        let global_files = [
+            std::path::Path::new("/etc/ssh/ssh_known_hosts"),
+            std::path::Path::new("/etc/ssh/ssh_known_hosts2"),
+        ];
+        let mut known_hosts =
+            known_hosts::File::from_paths(user_files.iter().map(std::path::PathBuf::as_path).chain(global_files))?;
+
+        // Prefer the algorithms of keys we know, so the server presents a key we can verify.
+        let known_algorithms: Vec<&str> = known_hosts
+            .key_algorithms(&host.host_name, port)
+            .iter()
+            .flat_map(|algorithm| match algorithm {
+                ssh_key::Algorithm::Rsa { .. } => vec!["rsa-sha2-512", "rsa-sha2-256"],
+                algorithm => vec![algorithm.as_str()],
+            })
+            .filter_map(|name| {
+                crate::transport::HOST_KEY_ALGORITHMS
+                    .iter()
+                    .copied()
+                    .find(|supported| *supported == name)
+            })
+            .collect();
+        let mut algorithms = known_algorithms.clone();
+        algorithms.extend(
+            crate::transport::HOST_KEY_ALGORITHMS
+                .iter()
+                .filter(|name| !known_algorithms.contains(name)),
+        );
+
+        let policy = host.strict_host_key_checking.unwrap_or_default();
+        let mut rejection = None;
+        let result = Transport::connect(stream, &algorithms, &mut |key| {
+            let res = verify_host_key(
+                &mut known_hosts,
+                user_files.first().map(std::path::PathBuf::as_path),
+                &host.host_name,
+                port,
+                key,
+                policy,
+                &options.prompt,
+            );
+            res.map_err(|err| {
+                let message = err.to_string();
+                rejection = Some(err);
+                message.into()
+            })
+        });
+        match (result, rejection) {
+            (Ok(transport), _) => Ok(transport),
+            (Err(_), Some(err)) => Err(err),
+            (Err(err), None) => Err(err.into()),
+        }
+    }
+
+    fn verify_host_key(
+        known_hosts: &mut known_hosts::File,
+        user_file: Option<&std::path::Path>,
+        host: &str,
+        port: u16,
+        key: &ssh_key::PublicKey,
+        policy: config::StrictHostKeyChecking,
+        prompt: &gix_prompt::Options,
+    ) -> Result<(), Error> {
+        let fingerprint = key.fingerprint(ssh_key::HashAlg::Sha256).to_string();
+        match known_hosts.verify(host, port, key) {
+            known_hosts::Verdict::Match => return Ok(()),
+            known_hosts::Verdict::Mismatch => {
+                return Err(Error::HostKeyMismatch {
+                    host: host.into(),
+                    fingerprint,
+                });
+            }
+            known_hosts::Verdict::Revoked => {
+                return Err(Error::RevokedHostKey {
+                    host: host.into(),
+                    fingerprint,
+                });
+            }
+            known_hosts::Verdict::Unknown => {}
+        }
+
+        let trusted = match policy {
+            config::StrictHostKeyChecking::Yes => false,
+            config::StrictHostKeyChecking::AcceptNew | config::StrictHostKeyChecking::No => true,
+            config::StrictHostKeyChecking::Ask => {
+                let mut prompt = prompt.clone();
+                if prompt.mode != gix_prompt::Mode::Disable {
+                    prompt.mode = gix_prompt::Mode::Visible;
+                }
+                let question = format!(
+                    "The authenticity of host '{host}' can't be established.\n{} key fingerprint is {fingerprint}.\nAre you sure you want to continue connecting (yes/no)? ",
+                    key.algorithm().as_str(),
+                );
+                gix_prompt::ask(&question, &prompt).is_ok_and(|answer| answer.trim().eq_ignore_ascii_case("yes"))
+            }
+        };
+        if !trusted {
+            return Err(Error::UnknownHostKey {
+                host: host.into(),
+                fingerprint,
+            });
+        }
+        if let Some(path) = user_file {
+            known_hosts.append(path, host, port, key)?;
+        }
+        Ok(())
+    }
+}
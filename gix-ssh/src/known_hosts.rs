@@ -0,0 +1,216 @@
+//! Reading and extending `known_hosts` files to verify the identity of hosts.
+//!
+//! Host patterns with wildcards and negations are supported, as are hashed host names and the `@revoked` marker.
+//! Lines with the `@cert-authority` marker are ignored as certificates aren't supported.
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use base64::Engine;
+use hmac::{Hmac, Mac};
+
+use crate::pattern;
+
+/// The result of [`File::verify()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The key is known for the host.
+    Match,
+    /// The host is known with a different key of the same type, which may mean that someone is impersonating the host.
+    Mismatch,
+    /// The key was revoked.
+    Revoked,
+    /// The host isn't known with a key of this type.
+    Unknown,
+}
+
+/// The error returned when reading or extending `known_hosts` files.
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not read or write the known_hosts file at '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error("Could not encode the host key")]
+    Encode(#[from] ssh_key::Error),
+}
+
+/// The entries of one or more `known_hosts` files.
+#[derive(Debug, Clone, Default)]
+pub struct File {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    revoked: bool,
+    hosts: Hosts,
+    key: ssh_key::public::KeyData,
+}
+
+#[derive(Debug, Clone)]
+enum Hosts {
+    Patterns(Vec<String>),
+    Hashed { salt: Vec<u8>, hash: Vec<u8> },
+}
+
+impl Hosts {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Hosts::Patterns(patterns) => pattern::matches_list(patterns.iter().map(String::as_str), name),
+            Hosts::Hashed { salt, hash } => {
+                let mut mac = Hmac::<sha1::Sha1>::new_from_slice(salt).expect("HMAC accepts keys of any length");
+                mac.update(name.as_bytes());
+                mac.verify_slice(hash).is_ok()
+            }
+        }
+    }
+}
+
+/// Initialization
+impl File {
+    /// Parse `input` in the format of a `known_hosts` file, skipping lines that can't be parsed.
+    pub fn from_bytes(input: &[u8]) -> Self {
+        File {
+            entries: input
+                .split(|b| *b == b'\n')
+                .filter_map(|line| parse_line(&String::from_utf8_lossy(line)))
+                .collect(),
+        }
+    }
+
+    /// Read all files at `paths` and combine their entries, skipping files that don't exist.
+    pub fn from_paths<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+        for path in paths {
+            match std::fs::read(path) {
+                Ok(input) => entries.extend(Self::from_bytes(&input).entries),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    return Err(Error::Io {
+                        source: err,
+                        path: path.into(),
+                    });
+                }
+            }
+        }
+        Ok(File { entries })
+    }
+}
+
+/// Querying
+impl File {
+    /// Check if `key` is the known key of `host` when reached on `port`.
+    pub fn verify(&self, host: &str, port: u16, key: &ssh_key::PublicKey) -> Verdict {
+        let name = host_entry_name(host, port);
+        let key = key.key_data();
+        if self.entries.iter().any(|entry| entry.revoked && entry.key == *key) {
+            return Verdict::Revoked;
+        }
+        let mut verdict = Verdict::Unknown;
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| !entry.revoked && entry.hosts.matches(&name))
+        {
+            if entry.key == *key {
+                return Verdict::Match;
+            }
+            if entry.key.algorithm() == key.algorithm() {
+                verdict = Verdict::Mismatch;
+            }
+        }
+        verdict
+    }
+
+    /// Return the algorithms of all keys known for `host` when reached on `port`, in the order they appear in the files.
+    pub fn key_algorithms(&self, host: &str, port: u16) -> Vec<ssh_key::Algorithm> {
+        let name = host_entry_name(host, port);
+        let mut algorithms = Vec::new();
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| !entry.revoked && entry.hosts.matches(&name))
+        {
+            let algorithm = entry.key.algorithm();
+            if !algorithms.contains(&algorithm) {
+                algorithms.push(algorithm);
+            }
+        }
+        algorithms
+    }
+}
+
+/// Mutation
+impl File {
+    /// Remember `key` for `host` when reached on `port`, and append it to the `known_hosts` file at `path`,
+    /// creating it and its parent directory if needed.
+    pub fn append(&mut self, path: &Path, host: &str, port: u16, key: &ssh_key::PublicKey) -> Result<(), Error> {
+        let name = host_entry_name(host, port);
+        let mut key = key.clone();
+        key.set_comment("");
+        let line = format!("{name} {}\n", key.to_openssh()?);
+        let io_err = |source| Error::Io {
+            source,
+            path: path.into(),
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(io_err)?;
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(io_err)?;
+        self.entries.push(Entry {
+            revoked: false,
+            hosts: Hosts::Patterns(vec![name]),
+            key: key.key_data().clone(),
+        });
+        Ok(())
+    }
+}
+
+/// Return the name under which `host` is stored if reached on `port`.
+fn host_entry_name(host: &str, port: u16) -> String {
+    let host = host.to_lowercase();
+    if port == 22 { host } else { format!("[{host}]:{port}") }
+}
+
+fn parse_line(line: &str) -> Option<Entry> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let mut fields = line.split_whitespace();
+    let mut hosts = fields.next()?;
+    let mut revoked = false;
+    if let Some(marker) = hosts.strip_prefix('@') {
+        match marker {
+            "revoked" => revoked = true,
+            _ => return None,
+        }
+        hosts = fields.next()?;
+    }
+    let algorithm = fields.next()?;
+    let key = fields.next()?;
+    let key = ssh_key::PublicKey::from_openssh(&format!("{algorithm} {key}")).ok()?;
+
+    let hosts = match hosts.strip_prefix("|1|") {
+        Some(hashed) => {
+            let (salt, hash) = hashed.split_once('|')?;
+            let engine = base64::engine::general_purpose::STANDARD;
+            Hosts::Hashed {
+                salt: engine.decode(salt).ok()?,
+                hash: engine.decode(hash).ok()?,
+            }
+        }
+        None => Hosts::Patterns(hosts.split(',').map(str::to_lowercase).collect()),
+    };
+    Some(Entry {
+        revoked,
+        hosts,
+        key: key.key_data().clone(),
+    })
+}
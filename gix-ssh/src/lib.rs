@@ -0,0 +1,62 @@
+//! A native implementation of the client side of the SSH protocol, which makes it possible to run commands like
+//! `git-upload-pack` on remote hosts without invoking an `ssh` program.
+//!
+//! It reads `ssh_config` files to learn how to reach a host, verifies host keys against `known_hosts` files,
+//! authenticates with keys held by an `ssh-agent`, with key files or with passwords obtained through `gix-prompt`,
+//! and can reach hosts through jump hosts.
+//!
+//! ### Supported Algorithms
+//!
+//! The set of algorithms is deliberately small, but covers what current servers offer:
+//!
+//! * **key exchange**: `curve25519-sha256`, along with the strict key exchange extension
+//! * **host keys and authentication keys**: `ssh-ed25519`, `ecdsa-sha2-nistp256`, `rsa-sha2-512` and `rsa-sha2-256`
+//! * **ciphers**: `aes128-ctr` and `aes256-ctr`
+//! * **message authentication**: `hmac-sha2-256` and `hmac-sha2-512`
+//!
+//! Compression isn't supported.
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+/// A stream to run the SSH protocol over, typically a TCP connection or a [channel](channel::Channel)
+/// to another host when connecting through a jump host.
+pub trait Stream: std::io::Read + std::io::Write + Send {}
+
+impl<T> Stream for T where T: std::io::Read + std::io::Write + Send {}
+
+///
+pub mod wire;
+
+///
+pub mod msg;
+
+///
+pub mod transport;
+pub use transport::Transport;
+
+///
+pub mod auth;
+
+///
+#[cfg(unix)]
+pub mod agent;
+
+///
+pub mod channel;
+pub use channel::Channel;
+
+///
+pub mod config;
+pub use config::Config;
+
+///
+pub mod known_hosts;
+
+mod pattern;
+
+///
+pub mod connect;
+pub use connect::function::connect;
+
+mod session;
+pub use session::Session;
@@ -0,0 +1,46 @@
+use std::io::{Read, Write};
+
+use crate::{Channel, Stream, Transport, channel};
+
+/// An authenticated connection to a host, as returned by [`connect()`](crate::connect()), which can be turned into
+/// a single [`Channel`].
+pub struct Session<S = Box<dyn Stream>> {
+    transport: Transport<S>,
+}
+
+/// Lifecycle
+impl<S: Read + Write> Session<S> {
+    /// Create a new instance from a `transport` over which authentication already succeeded.
+    pub fn new(transport: Transport<S>) -> Self {
+        Session { transport }
+    }
+
+    /// Execute `command` on the remote host after asking to set the variables in `env`,
+    /// and return the channel connected to its standard input and output.
+    pub fn exec(self, command: &str, env: &[(&str, &str)]) -> Result<Channel<S>, channel::Error> {
+        let mut channel = Channel::open_session(self.transport)?;
+        for (name, value) in env {
+            channel.set_env(name, value)?;
+        }
+        channel.exec(command)?;
+        Ok(channel)
+    }
+
+    /// Open a channel to `port` on `host` as reached from the remote host.
+    pub fn direct_tcpip(self, host: &str, port: u16) -> Result<Channel<S>, channel::Error> {
+        Channel::open_direct_tcpip(self.transport, host, port)
+    }
+
+    /// Return the underlying transport.
+    pub fn into_transport(self) -> Transport<S> {
+        self.transport
+    }
+}
+
+/// Access
+impl<S> Session<S> {
+    /// Return the underlying transport, for instance to learn about the host key of the server.
+    pub fn transport(&self) -> &Transport<S> {
+        &self.transport
+    }
+}
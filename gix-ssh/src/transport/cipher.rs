@@ -0,0 +1,132 @@
+use aes::{Aes128, Aes256};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac as _};
+use sha2::{Sha256, Sha512};
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+
+/// The ciphers we support, in order of preference.
+pub(crate) const CIPHERS: &[&str] = &["aes128-ctr", "aes256-ctr"];
+/// The message authentication codes we support, in order of preference.
+pub(crate) const MACS: &[&str] = &["hmac-sha2-256", "hmac-sha2-512"];
+
+/// The state of one direction of a connection.
+#[derive(Default)]
+pub(crate) struct Direction {
+    pub cipher: Cipher,
+    pub mac: Mac,
+    /// The sequence number of the next packet, which is part of its message authentication code.
+    pub seq: u32,
+}
+
+#[derive(Default)]
+pub(crate) enum Cipher {
+    /// No encryption, used until the first key exchange is complete.
+    #[default]
+    None,
+    Aes128Ctr(Box<Aes128Ctr>),
+    Aes256Ctr(Box<Aes256Ctr>),
+}
+
+impl Cipher {
+    /// Return the length of the key and of the initialization vector of the cipher called `name`.
+    pub fn key_and_iv_len(name: &str) -> (usize, usize) {
+        match name {
+            "aes128-ctr" => (16, 16),
+            "aes256-ctr" => (32, 16),
+            _ => unreachable!("BUG: only negotiated ciphers are used"),
+        }
+    }
+
+    pub fn new(name: &str, key: &[u8], iv: &[u8]) -> Self {
+        match name {
+            "aes128-ctr" => Cipher::Aes128Ctr(Box::new(
+                Aes128Ctr::new_from_slices(key, iv).expect("key and iv have the correct length"),
+            )),
+            "aes256-ctr" => Cipher::Aes256Ctr(Box::new(
+                Aes256Ctr::new_from_slices(key, iv).expect("key and iv have the correct length"),
+            )),
+            _ => unreachable!("BUG: only negotiated ciphers are used"),
+        }
+    }
+
+    /// The size of the blocks packets are aligned to.
+    pub fn block_size(&self) -> usize {
+        match self {
+            Cipher::None => 8,
+            Cipher::Aes128Ctr(_) | Cipher::Aes256Ctr(_) => 16,
+        }
+    }
+
+    /// Encrypt or decrypt `buf` in place.
+    pub fn apply(&mut self, buf: &mut [u8]) {
+        match self {
+            Cipher::None => {}
+            Cipher::Aes128Ctr(cipher) => cipher.apply_keystream(buf),
+            Cipher::Aes256Ctr(cipher) => cipher.apply_keystream(buf),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) enum Mac {
+    /// No authentication, used until the first key exchange is complete.
+    #[default]
+    None,
+    Sha256(Vec<u8>),
+    Sha512(Vec<u8>),
+}
+
+impl Mac {
+    /// Return the length of the key of the message authentication code called `name`.
+    pub fn key_len(name: &str) -> usize {
+        match name {
+            "hmac-sha2-256" => 32,
+            "hmac-sha2-512" => 64,
+            _ => unreachable!("BUG: only negotiated MACs are used"),
+        }
+    }
+
+    pub fn new(name: &str, key: Vec<u8>) -> Self {
+        match name {
+            "hmac-sha2-256" => Mac::Sha256(key),
+            "hmac-sha2-512" => Mac::Sha512(key),
+            _ => unreachable!("BUG: only negotiated MACs are used"),
+        }
+    }
+
+    /// The length of the authentication code appended to each packet.
+    pub fn len(&self) -> usize {
+        match self {
+            Mac::None => 0,
+            Mac::Sha256(_) => 32,
+            Mac::Sha512(_) => 64,
+        }
+    }
+
+    /// Compute the authentication code of the unencrypted `packet` with sequence number `seq`.
+    pub fn compute(&self, seq: u32, packet: &[u8]) -> Vec<u8> {
+        match self {
+            Mac::None => Vec::new(),
+            Mac::Sha256(key) => hmac::<Hmac<Sha256>>(key, seq, packet).finalize().into_bytes().to_vec(),
+            Mac::Sha512(key) => hmac::<Hmac<Sha512>>(key, seq, packet).finalize().into_bytes().to_vec(),
+        }
+    }
+
+    /// Return `true` if `tag` is the authentication code of `packet` with sequence number `seq`, in constant time.
+    pub fn verify(&self, seq: u32, packet: &[u8], tag: &[u8]) -> bool {
+        match self {
+            Mac::None => tag.is_empty(),
+            Mac::Sha256(key) => hmac::<Hmac<Sha256>>(key, seq, packet).verify_slice(tag).is_ok(),
+            Mac::Sha512(key) => hmac::<Hmac<Sha512>>(key, seq, packet).verify_slice(tag).is_ok(),
+        }
+    }
+}
+
+fn hmac<M: hmac::Mac + hmac::digest::KeyInit>(key: &[u8], seq: u32, packet: &[u8]) -> M {
+    let mut mac = <M as hmac::Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&seq.to_be_bytes());
+    mac.update(packet);
+    mac
+}
@@ -0,0 +1,427 @@
+use std::io::{Read, Write};
+
+use rand_core::RngCore;
+use sha2::{Digest, Sha256};
+use signature::{Signer, Verifier};
+
+use super::{
+    Error, Identity, Transport, VerifyHostKeyFn,
+    cipher::{self, Cipher, Mac},
+};
+use crate::{msg, wire};
+
+/// The key exchange methods we support, in order of preference.
+const KEX_ALGORITHMS: &[&str] = &["curve25519-sha256", "curve25519-sha256@libssh.org"];
+/// Signals support for `EXT_INFO` messages, see RFC 8308.
+const EXT_INFO_CLIENT: &str = "ext-info-c";
+/// Signals support for the strict key exchange of OpenSSH, which protects against prefix truncation attacks.
+const STRICT_KEX_CLIENT: &str = "kex-strict-c-v00@openssh.com";
+const STRICT_KEX_SERVER: &str = "kex-strict-s-v00@openssh.com";
+
+/// The fields of a `KEXINIT` message that we use.
+struct KexInit<'a> {
+    kex: Vec<&'a str>,
+    host_key: Vec<&'a str>,
+    cipher_c2s: Vec<&'a str>,
+    cipher_s2c: Vec<&'a str>,
+    mac_c2s: Vec<&'a str>,
+    mac_s2c: Vec<&'a str>,
+    compression_c2s: Vec<&'a str>,
+    compression_s2c: Vec<&'a str>,
+    first_kex_packet_follows: bool,
+}
+
+impl<'a> KexInit<'a> {
+    fn parse(payload: &'a [u8]) -> Result<Self, wire::Error> {
+        let mut msg = wire::Reader::new(payload);
+        msg.byte()?;
+        msg.take(16)?;
+        let kex = msg.name_list()?;
+        let host_key = msg.name_list()?;
+        let cipher_c2s = msg.name_list()?;
+        let cipher_s2c = msg.name_list()?;
+        let mac_c2s = msg.name_list()?;
+        let mac_s2c = msg.name_list()?;
+        let compression_c2s = msg.name_list()?;
+        let compression_s2c = msg.name_list()?;
+        msg.name_list()?;
+        msg.name_list()?;
+        let first_kex_packet_follows = msg.bool()?;
+        Ok(KexInit {
+            kex,
+            host_key,
+            cipher_c2s,
+            cipher_s2c,
+            mac_c2s,
+            mac_s2c,
+            compression_c2s,
+            compression_s2c,
+            first_kex_packet_follows,
+        })
+    }
+}
+
+/// The algorithms both sides agreed on.
+struct Algorithms {
+    host_key: String,
+    cipher_c2s: String,
+    cipher_s2c: String,
+    mac_c2s: String,
+    mac_s2c: String,
+}
+
+/// Pick the first algorithm of the `client` that the `server` supports as well.
+fn choose(kind: &'static str, client: &[&str], server: &[&str]) -> Result<String, Error> {
+    client
+        .iter()
+        .find(|name| server.contains(name))
+        .map(ToString::to_string)
+        .ok_or_else(|| Error::NoCommonAlgorithm {
+            kind,
+            ours: client.iter().map(ToString::to_string).collect(),
+            theirs: server.iter().map(ToString::to_string).collect(),
+        })
+}
+
+/// Return the name of the host key algorithm used to sign with `key`.
+fn host_key_algorithm(key: &ssh_key::PrivateKey) -> String {
+    match key.algorithm() {
+        ssh_key::Algorithm::Rsa { .. } => "rsa-sha2-512".into(),
+        other => other.as_str().into(),
+    }
+}
+
+/// Return `true` if `key` can be used with the host key algorithm `name`.
+fn key_matches_algorithm(key: &ssh_key::PublicKey, name: &str) -> bool {
+    match key.algorithm() {
+        ssh_key::Algorithm::Rsa { .. } => name.starts_with("rsa-sha2-"),
+        other => other.as_str() == name,
+    }
+}
+
+impl<S: Read + Write> Transport<S> {
+    /// Exchange keys with the peer, using the `KEXINIT` message it already sent if `peer_kexinit` is set.
+    ///
+    /// During the first key exchange, clients must pass `verify_host_key`, and later exchanges assure the host key didn't change.
+    pub(super) fn key_exchange(
+        &mut self,
+        peer_kexinit: Option<Vec<u8>>,
+        verify_host_key: Option<&mut VerifyHostKeyFn<'_>>,
+    ) -> Result<(), Error> {
+        let is_client = matches!(self.identity, Identity::Client { .. });
+        let is_first = self.session_id.is_none();
+
+        let local_kexinit = self.local_kexinit(is_first);
+        self.write_packet(&local_kexinit)?;
+        let peer_kexinit = match peer_kexinit {
+            Some(payload) => payload,
+            None => self.read_kex_message(msg::KEXINIT, is_first)?,
+        };
+        let (client_kexinit, server_kexinit) = if is_client {
+            (&local_kexinit, &peer_kexinit)
+        } else {
+            (&peer_kexinit, &local_kexinit)
+        };
+        let client = KexInit::parse(client_kexinit)?;
+        let server = KexInit::parse(server_kexinit)?;
+
+        if is_first {
+            let strict_kex = client.kex.contains(&STRICT_KEX_CLIENT) && server.kex.contains(&STRICT_KEX_SERVER);
+            // The strict key exchange requires `KEXINIT` to be the very first message.
+            if strict_kex && self.recv.seq != 1 {
+                return Err(Error::StrictKeyExchange);
+            }
+            self.strict_kex = strict_kex;
+        }
+
+        let kex = choose("key exchange", &client.kex, &server.kex)?;
+        let algorithms = Algorithms {
+            host_key: choose("host key", &client.host_key, &server.host_key)?,
+            cipher_c2s: choose("cipher", &client.cipher_c2s, &server.cipher_c2s)?,
+            cipher_s2c: choose("cipher", &client.cipher_s2c, &server.cipher_s2c)?,
+            mac_c2s: choose("mac", &client.mac_c2s, &server.mac_c2s)?,
+            mac_s2c: choose("mac", &client.mac_s2c, &server.mac_s2c)?,
+        };
+        choose("compression", &client.compression_c2s, &server.compression_c2s)?;
+        choose("compression", &client.compression_s2c, &server.compression_s2c)?;
+
+        let peer = if is_client { &server } else { &client };
+        let peer_guessed_wrong = peer.first_kex_packet_follows
+            && (peer.kex.first() != Some(&kex.as_str())
+                || peer.host_key.first() != Some(&algorithms.host_key.as_str()));
+        if peer_guessed_wrong {
+            self.read_packet()?;
+        }
+
+        let (shared_secret, exchange_hash) = if is_client {
+            self.client_key_agreement(client_kexinit, server_kexinit, &algorithms, verify_host_key)?
+        } else {
+            self.server_key_agreement(client_kexinit, server_kexinit, &algorithms)?
+        };
+        let session_id = self.session_id.get_or_insert_with(|| exchange_hash.clone()).clone();
+        let derive = |letter: u8, len: usize| derive_key(&shared_secret, &exchange_hash, letter, &session_id, len);
+
+        let (c2s_key_len, c2s_iv_len) = Cipher::key_and_iv_len(&algorithms.cipher_c2s);
+        let (s2c_key_len, s2c_iv_len) = Cipher::key_and_iv_len(&algorithms.cipher_s2c);
+        let client_to_server = cipher::Direction {
+            cipher: Cipher::new(
+                &algorithms.cipher_c2s,
+                &derive(b'C', c2s_key_len),
+                &derive(b'A', c2s_iv_len),
+            ),
+            mac: Mac::new(&algorithms.mac_c2s, derive(b'E', Mac::key_len(&algorithms.mac_c2s))),
+            seq: 0,
+        };
+        let server_to_client = cipher::Direction {
+            cipher: Cipher::new(
+                &algorithms.cipher_s2c,
+                &derive(b'D', s2c_key_len),
+                &derive(b'B', s2c_iv_len),
+            ),
+            mac: Mac::new(&algorithms.mac_s2c, derive(b'F', Mac::key_len(&algorithms.mac_s2c))),
+            seq: 0,
+        };
+        let (send, recv) = if is_client {
+            (client_to_server, server_to_client)
+        } else {
+            (server_to_client, client_to_server)
+        };
+
+        self.write_packet(&[msg::NEWKEYS])?;
+        self.send = cipher::Direction {
+            seq: if self.strict_kex { 0 } else { self.send.seq },
+            ..send
+        };
+        self.read_kex_message(msg::NEWKEYS, is_first)?;
+        self.recv = cipher::Direction {
+            seq: if self.strict_kex { 0 } else { self.recv.seq },
+            ..recv
+        };
+        Ok(())
+    }
+
+    fn local_kexinit(&self, is_first: bool) -> Vec<u8> {
+        let mut cookie = [0u8; 16];
+        rand_core::OsRng.fill_bytes(&mut cookie);
+        let mut kex = KEX_ALGORITHMS.to_vec();
+        let host_key_algorithms: Vec<String> = match &self.identity {
+            Identity::Client {
+                host_key_algorithms, ..
+            } => {
+                if is_first {
+                    kex.extend([EXT_INFO_CLIENT, STRICT_KEX_CLIENT]);
+                }
+                host_key_algorithms.clone()
+            }
+            Identity::Server { host_key } => {
+                if is_first {
+                    kex.push(STRICT_KEX_SERVER);
+                }
+                vec![host_key_algorithm(host_key)]
+            }
+        };
+        let host_key_algorithms: Vec<&str> = host_key_algorithms.iter().map(String::as_str).collect();
+        wire::Message::new(msg::KEXINIT)
+            .raw(&cookie)
+            .name_list(&kex)
+            .name_list(&host_key_algorithms)
+            .name_list(cipher::CIPHERS)
+            .name_list(cipher::CIPHERS)
+            .name_list(cipher::MACS)
+            .name_list(cipher::MACS)
+            .name_list(&["none"])
+            .name_list(&["none"])
+            .name_list(&[])
+            .name_list(&[])
+            .bool(false)
+            .u32(0)
+            .into_bytes()
+    }
+
+    /// Read the next message, which must be of type `expected`.
+    ///
+    /// Messages that don't affect the key exchange are skipped, unless the strict key exchange is used during the first key exchange.
+    fn read_kex_message(&mut self, expected: u8, is_first: bool) -> Result<Vec<u8>, Error> {
+        loop {
+            let payload = self.read_packet()?;
+            let actual = *payload.first().ok_or(Error::EmptyMessage)?;
+            if actual == expected {
+                return Ok(payload);
+            }
+            match actual {
+                msg::DISCONNECT => return Err(super::disconnected(&payload)),
+                msg::IGNORE | msg::DEBUG if !(is_first && self.strict_kex) => continue,
+                _ => return Err(Error::UnexpectedMessage { expected, actual }),
+            }
+        }
+    }
+
+    fn client_key_agreement(
+        &mut self,
+        client_kexinit: &[u8],
+        server_kexinit: &[u8],
+        algorithms: &Algorithms,
+        verify_host_key: Option<&mut VerifyHostKeyFn<'_>>,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let secret = x25519_dalek::EphemeralSecret::random_from_rng(rand_core::OsRng);
+        let client_public = x25519_dalek::PublicKey::from(&secret);
+        self.write_packet(
+            wire::Message::new(msg::KEX_ECDH_INIT)
+                .string(client_public.as_bytes())
+                .as_ref(),
+        )?;
+
+        let reply = self.read_kex_message(msg::KEX_ECDH_REPLY, self.session_id.is_none())?;
+        let mut reply = wire::Reader::new(&reply[1..]);
+        let host_key_blob = reply.string()?;
+        let server_public = reply.string()?;
+        let signature = reply.string()?;
+
+        let server_public: [u8; 32] = server_public.try_into().map_err(|_| Error::InvalidSharedSecret)?;
+        let shared_secret = secret.diffie_hellman(&x25519_dalek::PublicKey::from(server_public));
+        if !shared_secret.was_contributory() {
+            return Err(Error::InvalidSharedSecret);
+        }
+        let exchange_hash = exchange_hash(
+            self,
+            client_kexinit,
+            server_kexinit,
+            host_key_blob,
+            client_public.as_bytes(),
+            &server_public,
+            shared_secret.as_bytes(),
+        );
+
+        let host_key = ssh_key::PublicKey::from_bytes(host_key_blob)?;
+        let signature = ssh_key::Signature::try_from(signature)?;
+        if !key_matches_algorithm(&host_key, &algorithms.host_key)
+            || signature.algorithm().as_str() != algorithms.host_key
+        {
+            return Err(Error::HostKeyAlgorithm {
+                expected: algorithms.host_key.clone(),
+                actual: signature.algorithm().as_str().into(),
+            });
+        }
+        Verifier::verify(&host_key, &exchange_hash, &signature)?;
+
+        let Identity::Client {
+            host_key: known_host_key,
+            ..
+        } = &mut self.identity
+        else {
+            unreachable!("BUG: only clients perform the client side of the key agreement")
+        };
+        match (known_host_key.as_ref(), verify_host_key) {
+            (Some(known), _) => {
+                if known.key_data() != host_key.key_data() {
+                    return Err(Error::HostKeyChanged);
+                }
+            }
+            (None, verify) => {
+                if let Some(verify) = verify {
+                    verify(&host_key).map_err(Error::HostKeyRejected)?;
+                }
+                *known_host_key = Some(host_key);
+            }
+        }
+        Ok((shared_secret.as_bytes().to_vec(), exchange_hash))
+    }
+
+    fn server_key_agreement(
+        &mut self,
+        client_kexinit: &[u8],
+        server_kexinit: &[u8],
+        algorithms: &Algorithms,
+    ) -> Result<(Vec<u8>, Vec<u8>), Error> {
+        let init = self.read_kex_message(msg::KEX_ECDH_INIT, self.session_id.is_none())?;
+        let client_public: [u8; 32] = wire::Reader::new(&init[1..])
+            .string()?
+            .try_into()
+            .map_err(|_| Error::InvalidSharedSecret)?;
+        let secret = x25519_dalek::EphemeralSecret::random_from_rng(rand_core::OsRng);
+        let server_public = x25519_dalek::PublicKey::from(&secret);
+        let shared_secret = secret.diffie_hellman(&x25519_dalek::PublicKey::from(client_public));
+        if !shared_secret.was_contributory() {
+            return Err(Error::InvalidSharedSecret);
+        }
+
+        let Identity::Server { host_key } = &self.identity else {
+            unreachable!("BUG: only servers perform the server side of the key agreement")
+        };
+        debug_assert_eq!(host_key_algorithm(host_key), algorithms.host_key);
+        let host_key_blob = host_key.public_key().to_bytes()?;
+        let exchange_hash = exchange_hash(
+            self,
+            client_kexinit,
+            server_kexinit,
+            &host_key_blob,
+            &client_public,
+            server_public.as_bytes(),
+            shared_secret.as_bytes(),
+        );
+        let signature: Vec<u8> = host_key.try_sign(&exchange_hash)?.try_into()?;
+        self.write_packet(
+            wire::Message::new(msg::KEX_ECDH_REPLY)
+                .string(&host_key_blob)
+                .string(server_public.as_bytes())
+                .string(&signature)
+                .as_ref(),
+        )?;
+        Ok((shared_secret.as_bytes().to_vec(), exchange_hash))
+    }
+}
+
+/// Compute the exchange hash `H` as defined in RFC 8731.
+fn exchange_hash<S>(
+    transport: &Transport<S>,
+    client_kexinit: &[u8],
+    server_kexinit: &[u8],
+    host_key_blob: &[u8],
+    client_public: &[u8],
+    server_public: &[u8],
+    shared_secret: &[u8],
+) -> Vec<u8> {
+    let is_client = matches!(transport.identity, Identity::Client { .. });
+    let (client_version, server_version) = if is_client {
+        (&transport.local_version, &transport.peer_version)
+    } else {
+        (&transport.peer_version, &transport.local_version)
+    };
+    let mut buf = Vec::new();
+    for field in [
+        client_version.as_slice(),
+        server_version.as_slice(),
+        client_kexinit,
+        server_kexinit,
+        host_key_blob,
+        client_public,
+        server_public,
+    ] {
+        wire::put_string(&mut buf, field);
+    }
+    wire::put_mpint(&mut buf, shared_secret);
+    Sha256::digest(&buf).to_vec()
+}
+
+/// Derive `len` bytes of key material for `letter` as described in RFC 4253, section 7.2.
+fn derive_key(shared_secret: &[u8], exchange_hash: &[u8], letter: u8, session_id: &[u8], len: usize) -> Vec<u8> {
+    let mut secret = Vec::new();
+    wire::put_mpint(&mut secret, shared_secret);
+    let mut key = Sha256::new()
+        .chain_update(&secret)
+        .chain_update(exchange_hash)
+        .chain_update([letter])
+        .chain_update(session_id)
+        .finalize()
+        .to_vec();
+    while key.len() < len {
+        let more = Sha256::new()
+            .chain_update(&secret)
+            .chain_update(exchange_hash)
+            .chain_update(&key)
+            .finalize();
+        key.extend_from_slice(&more);
+    }
+    key.truncate(len);
+    key
+}
@@ -0,0 +1,306 @@
+//! The SSH transport layer as described in [RFC 4253](https://www.rfc-editor.org/rfc/rfc4253), which exchanges
+//! encrypted and authenticated messages over a [stream](crate::Stream).
+use std::io::{BufRead, BufReader, Read, Write};
+
+use bstr::{BStr, BString, ByteSlice};
+use rand_core::RngCore;
+
+use crate::{msg, wire};
+
+mod cipher;
+mod kex;
+
+/// The host key algorithms a client supports, in order of preference.
+pub const HOST_KEY_ALGORITHMS: &[&str] = &["ssh-ed25519", "ecdsa-sha2-nistp256", "rsa-sha2-512", "rsa-sha2-256"];
+
+/// The largest packet we accept, which is larger than the 35000 bytes every implementation must support.
+const MAX_PACKET_LEN: usize = 256 * 1024;
+/// The longest identification line we accept, along with any line that a server may send before it.
+const MAX_VERSION_LINE_LEN: u64 = 8 * 1024;
+
+/// The error returned by [`Transport`] methods.
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("An IO error occurred when talking to the peer")]
+    Io(#[from] std::io::Error),
+    #[error("The peer identified itself as {version:?}, which doesn't speak SSH 2.0")]
+    UnsupportedVersion { version: BString },
+    #[error("The peer didn't identify itself before sending other data")]
+    MissingVersion,
+    #[error("Could not agree on a {kind} algorithm, we support {ours:?} and the peer supports {theirs:?}")]
+    NoCommonAlgorithm {
+        kind: &'static str,
+        ours: Vec<String>,
+        theirs: Vec<String>,
+    },
+    #[error("The peer sent a malformed message")]
+    Decode(#[from] wire::Error),
+    #[error("The peer sent a packet of invalid length {len}")]
+    InvalidPacketLength { len: usize },
+    #[error("The peer sent a packet with invalid padding")]
+    InvalidPadding,
+    #[error("The message authentication code of a received packet didn't match")]
+    MacMismatch,
+    #[error("Expected message {expected}, but received message {actual}")]
+    UnexpectedMessage { expected: u8, actual: u8 },
+    #[error("The peer sent messages before its KEXINIT message, which isn't allowed with the strict key exchange")]
+    StrictKeyExchange,
+    #[error("The peer sent an empty message")]
+    EmptyMessage,
+    #[error("The host key of the peer could not be decoded")]
+    HostKey(#[from] ssh_key::Error),
+    #[error("The host key signature of the key exchange could not be verified")]
+    HostKeySignature(#[from] signature::Error),
+    #[error("The host key of type {actual} doesn't match the negotiated host key algorithm {expected}")]
+    HostKeyAlgorithm { expected: String, actual: String },
+    #[error("The host key of the peer changed when the keys were exchanged again")]
+    HostKeyChanged,
+    #[error("The key exchange produced an invalid shared secret")]
+    InvalidSharedSecret,
+    #[error("The host key of the peer was rejected")]
+    HostKeyRejected(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("The peer disconnected with reason {reason}: {description}")]
+    Disconnected { reason: u32, description: String },
+}
+
+/// A function to decide if the host key of a server can be trusted, returning an error if not.
+pub type VerifyHostKeyFn<'a> =
+    dyn FnMut(&ssh_key::PublicKey) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> + 'a;
+
+/// The side of a connection a [`Transport`] represents, along with the host key it knows.
+enum Identity {
+    Client {
+        /// The host key algorithms to offer, in order of preference.
+        host_key_algorithms: Vec<String>,
+        /// The host key of the server, once the first key exchange is complete.
+        host_key: Option<ssh_key::PublicKey>,
+    },
+    Server {
+        host_key: ssh_key::PrivateKey,
+    },
+}
+
+/// An encrypted connection to a peer over which messages can be exchanged, with the keys being exchanged again
+/// automatically whenever the peer asks for it.
+pub struct Transport<S> {
+    stream: BufReader<S>,
+    identity: Identity,
+    local_version: BString,
+    peer_version: BString,
+    session_id: Option<Vec<u8>>,
+    send: cipher::Direction,
+    recv: cipher::Direction,
+    strict_kex: bool,
+}
+
+/// Lifecycle
+impl<S: Read + Write> Transport<S> {
+    /// Perform the client side of the handshake over `stream`, offering `host_key_algorithms` in order of preference,
+    /// or [`HOST_KEY_ALGORITHMS`] if empty, and calling `verify_host_key` to decide if the server can be trusted.
+    pub fn connect(
+        stream: S,
+        host_key_algorithms: &[&str],
+        verify_host_key: &mut VerifyHostKeyFn<'_>,
+    ) -> Result<Self, Error> {
+        let host_key_algorithms = if host_key_algorithms.is_empty() {
+            HOST_KEY_ALGORITHMS
+        } else {
+            host_key_algorithms
+        };
+        let mut transport = Self::new(
+            stream,
+            Identity::Client {
+                host_key_algorithms: host_key_algorithms.iter().map(ToString::to_string).collect(),
+                host_key: None,
+            },
+        )?;
+        transport.key_exchange(None, Some(verify_host_key))?;
+        Ok(transport)
+    }
+
+    /// Perform the server side of the handshake over `stream`, proving its identity with `host_key`.
+    ///
+    /// This is useful for testing and for embedding small servers, as only the transport layer is implemented
+    /// for servers.
+    pub fn accept(stream: S, host_key: ssh_key::PrivateKey) -> Result<Self, Error> {
+        let mut transport = Self::new(stream, Identity::Server { host_key })?;
+        transport.key_exchange(None, None)?;
+        Ok(transport)
+    }
+
+    fn new(stream: S, identity: Identity) -> Result<Self, Error> {
+        let local_version: BString = format!("SSH-2.0-gix_ssh_{}", env!("CARGO_PKG_VERSION")).into();
+        let mut stream = BufReader::new(stream);
+        {
+            let out = stream.get_mut();
+            out.write_all(&local_version)?;
+            out.write_all(b"\r\n")?;
+            out.flush()?;
+        }
+        let is_client = matches!(identity, Identity::Client { .. });
+        let peer_version = read_version(&mut stream, is_client)?;
+        Ok(Transport {
+            stream,
+            identity,
+            local_version,
+            peer_version,
+            session_id: None,
+            send: Default::default(),
+            recv: Default::default(),
+            strict_kex: false,
+        })
+    }
+}
+
+/// Access
+impl<S> Transport<S> {
+    /// Return the identification string of the peer, like `SSH-2.0-OpenSSH_9.6`.
+    pub fn peer_version(&self) -> &BStr {
+        self.peer_version.as_ref()
+    }
+
+    /// Return the identifier of this session, which is the hash of the first key exchange.
+    pub fn session_id(&self) -> &[u8] {
+        self.session_id.as_deref().expect("set after the handshake")
+    }
+
+    /// Return the host key of the server, which is our own key if we are the server.
+    pub fn host_key(&self) -> &ssh_key::PublicKey {
+        match &self.identity {
+            Identity::Client { host_key, .. } => host_key.as_ref().expect("set after the handshake"),
+            Identity::Server { host_key } => host_key.public_key(),
+        }
+    }
+}
+
+/// Messages
+impl<S: Read + Write> Transport<S> {
+    /// Read the next message of the upper protocol layers, handling messages of the transport layer along the way.
+    ///
+    /// This includes exchanging keys again if the peer initiates it, and failing if the peer disconnects.
+    pub fn read_message(&mut self) -> Result<Vec<u8>, Error> {
+        loop {
+            let payload = self.read_packet()?;
+            match payload.first().copied() {
+                None => return Err(Error::EmptyMessage),
+                Some(msg::IGNORE | msg::DEBUG | msg::UNIMPLEMENTED) => continue,
+                Some(msg::DISCONNECT) => return Err(disconnected(&payload)),
+                Some(msg::KEXINIT) => self.key_exchange(Some(payload), None)?,
+                Some(_) => return Ok(payload),
+            }
+        }
+    }
+
+    /// Send `payload`, a message typically created with [`wire::Message`], to the peer.
+    pub fn write_message(&mut self, payload: impl AsRef<[u8]>) -> Result<(), Error> {
+        self.write_packet(payload.as_ref())
+    }
+
+    /// Tell the peer we are disconnecting for `reason`, one of the `DISCONNECT_*` constants in the [`msg`] module,
+    /// with a human-readable `description`.
+    pub fn disconnect(&mut self, reason: u32, description: &str) -> Result<(), Error> {
+        self.write_message(
+            wire::Message::new(msg::DISCONNECT)
+                .u32(reason)
+                .string(description)
+                .string(""),
+        )
+    }
+
+    fn read_packet(&mut self) -> Result<Vec<u8>, Error> {
+        let block_size = self.recv.cipher.block_size();
+        let mut packet = vec![0; block_size];
+        self.stream.read_exact(&mut packet)?;
+        self.recv.cipher.apply(&mut packet);
+
+        let len = u32::from_be_bytes([packet[0], packet[1], packet[2], packet[3]]) as usize;
+        if len > MAX_PACKET_LEN || len + 4 < block_size || (len + 4) % block_size != 0 {
+            return Err(Error::InvalidPacketLength { len });
+        }
+        packet.resize(len + 4, 0);
+        self.stream.read_exact(&mut packet[block_size..])?;
+        self.recv.cipher.apply(&mut packet[block_size..]);
+
+        let mac_len = self.recv.mac.len();
+        if mac_len != 0 {
+            let mut tag = vec![0; mac_len];
+            self.stream.read_exact(&mut tag)?;
+            if !self.recv.mac.verify(self.recv.seq, &packet, &tag) {
+                return Err(Error::MacMismatch);
+            }
+        }
+        self.recv.seq = self.recv.seq.wrapping_add(1);
+
+        let padding = packet[4] as usize;
+        if padding < 4 || padding + 1 > len {
+            return Err(Error::InvalidPadding);
+        }
+        packet.truncate(4 + len - padding);
+        packet.drain(..5);
+        Ok(packet)
+    }
+
+    fn write_packet(&mut self, payload: &[u8]) -> Result<(), Error> {
+        let block_size = self.send.cipher.block_size();
+        let mut padding = block_size - (5 + payload.len()) % block_size;
+        if padding < 4 {
+            padding += block_size;
+        }
+        let len = 1 + payload.len() + padding;
+        let mut packet = Vec::with_capacity(4 + len + self.send.mac.len());
+        packet.extend_from_slice(&(len as u32).to_be_bytes());
+        packet.push(padding as u8);
+        packet.extend_from_slice(payload);
+        let padding_start = packet.len();
+        packet.resize(padding_start + padding, 0);
+        rand_core::OsRng.fill_bytes(&mut packet[padding_start..]);
+
+        let tag = self.send.mac.compute(self.send.seq, &packet);
+        self.send.cipher.apply(&mut packet);
+        packet.extend_from_slice(&tag);
+        self.send.seq = self.send.seq.wrapping_add(1);
+
+        let out = self.stream.get_mut();
+        out.write_all(&packet)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+fn read_version<S: Read>(stream: &mut BufReader<S>, allow_preamble: bool) -> Result<BString, Error> {
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        stream
+            .by_ref()
+            .take(MAX_VERSION_LINE_LEN)
+            .read_until(b'\n', &mut line)?;
+        if line.is_empty() {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        let version = line.trim_end_with(|c| c == '\r' || c == '\n');
+        if version.starts_with(b"SSH-") {
+            if !(version.starts_with(b"SSH-2.0-") || version.starts_with(b"SSH-1.99-")) {
+                return Err(Error::UnsupportedVersion {
+                    version: version.into(),
+                });
+            }
+            return Ok(version.into());
+        }
+        if !allow_preamble {
+            return Err(Error::MissingVersion);
+        }
+    }
+}
+
+fn disconnected(payload: &[u8]) -> Error {
+    let mut msg = wire::Reader::new(&payload[1..]);
+    match (msg.u32(), msg.string()) {
+        (Ok(reason), Ok(description)) => Error::Disconnected {
+            reason,
+            description: description.to_str_lossy().into_owned(),
+        },
+        _ => Error::Decode(wire::Error::Truncated),
+    }
+}
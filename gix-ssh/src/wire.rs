@@ -0,0 +1,156 @@
+//! Encoding and decoding of the data types used in SSH messages, as described in [RFC 4251, section 5](https://www.rfc-editor.org/rfc/rfc4251#section-5).
+
+/// The error returned when decoding a message with [`Reader`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("The message ended unexpectedly")]
+    Truncated,
+    #[error("A string in the message wasn't valid UTF-8")]
+    Utf8(#[from] std::str::Utf8Error),
+}
+
+/// A builder for SSH messages, starting with the message number.
+#[derive(Debug, Clone, Default)]
+pub struct Message(Vec<u8>);
+
+impl Message {
+    /// Start a new message of type `kind`, one of the constants in the [`msg`](crate::msg) module.
+    pub fn new(kind: u8) -> Self {
+        Message(vec![kind])
+    }
+
+    /// Append a single byte.
+    pub fn byte(mut self, value: u8) -> Self {
+        self.0.push(value);
+        self
+    }
+
+    /// Append `bytes` verbatim, without length prefix.
+    pub fn raw(mut self, bytes: &[u8]) -> Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    /// Append a `boolean`.
+    pub fn bool(self, value: bool) -> Self {
+        self.byte(value.into())
+    }
+
+    /// Append a `uint32`.
+    pub fn u32(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Append a `string`, which is arbitrary binary data prefixed with its length.
+    pub fn string(mut self, bytes: impl AsRef<[u8]>) -> Self {
+        put_string(&mut self.0, bytes.as_ref());
+        self
+    }
+
+    /// Append a `name-list`, a comma-separated list of `names`.
+    pub fn name_list(self, names: &[&str]) -> Self {
+        self.string(names.join(","))
+    }
+
+    /// Append an `mpint` created from `unsigned_be`, the big-endian bytes of a non-negative number.
+    pub fn mpint(mut self, unsigned_be: &[u8]) -> Self {
+        put_mpint(&mut self.0, unsigned_be);
+        self
+    }
+
+    /// Return the encoded message.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for Message {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Append `bytes` to `out` as `string`.
+pub fn put_string(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Append `unsigned_be`, the big-endian bytes of a non-negative number, to `out` as `mpint`.
+pub fn put_mpint(out: &mut Vec<u8>, unsigned_be: &[u8]) {
+    let start = unsigned_be.iter().position(|b| *b != 0).unwrap_or(unsigned_be.len());
+    let digits = &unsigned_be[start..];
+    let needs_sign_byte = digits.first().is_some_and(|b| b & 0x80 != 0);
+    out.extend_from_slice(&((digits.len() + usize::from(needs_sign_byte)) as u32).to_be_bytes());
+    if needs_sign_byte {
+        out.push(0);
+    }
+    out.extend_from_slice(digits);
+}
+
+/// A decoder for the fields of an SSH message.
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Read fields from `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    /// Read a single byte.
+    pub fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Read a `boolean`.
+    pub fn bool(&mut self) -> Result<bool, Error> {
+        Ok(self.byte()? != 0)
+    }
+
+    /// Read a `uint32`.
+    pub fn u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Read a `string` as binary data.
+    pub fn string(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    /// Read a `string` that is expected to be UTF-8 encoded.
+    pub fn utf8(&mut self) -> Result<&'a str, Error> {
+        Ok(std::str::from_utf8(self.string()?)?)
+    }
+
+    /// Read a `name-list`, which is empty if the list is empty.
+    pub fn name_list(&mut self) -> Result<Vec<&'a str>, Error> {
+        let list = self.utf8()?;
+        Ok(if list.is_empty() {
+            Vec::new()
+        } else {
+            list.split(',').collect()
+        })
+    }
+
+    /// Read `len` bytes verbatim.
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < len {
+            return Err(Error::Truncated);
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    /// Return all bytes that weren't read yet.
+    pub fn rest(&self) -> &'a [u8] {
+        self.data
+    }
+}
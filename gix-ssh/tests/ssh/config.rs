@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use gix_ssh::{
+    Config,
+    config::{Jump, StrictHostKeyChecking},
+};
+
+#[test]
+fn first_value_wins_and_identity_files_accumulate() {
+    let config = Config::from_bytes(
+        b"# leading options apply to all hosts
+IdentityFile /keys/global
+
+Host *.example.com !internal.example.com
+  HostName %h.proxy
+  Port 2222
+  User alice
+  IdentityFile /keys/%r@%h
+
+Host *
+  Port 22
+  User nobody
+  StrictHostKeyChecking=accept-new
+",
+    );
+    let host = config.resolve("Git.Example.com");
+    assert_eq!(
+        host.host_name, "Git.Example.com.proxy",
+        "%h expands to the host as given"
+    );
+    assert_eq!(host.port, Some(2222), "the first value wins");
+    assert_eq!(host.user.as_deref(), Some("alice"));
+    assert_eq!(
+        host.identity_files,
+        [
+            PathBuf::from("/keys/global"),
+            "/keys/alice@Git.Example.com.proxy".into()
+        ],
+        "identity files accumulate in order, and expand tokens with the final host name"
+    );
+    assert_eq!(host.strict_host_key_checking, Some(StrictHostKeyChecking::AcceptNew));
+
+    let host = config.resolve("internal.example.com");
+    assert_eq!(host.host_name, "internal.example.com", "negated patterns exclude hosts");
+    assert_eq!(host.port, Some(22));
+    assert_eq!(host.user.as_deref(), Some("nobody"));
+}
+
+#[test]
+fn match_sections_only_apply_with_all() {
+    let config = Config::from_bytes(
+        b"Match host foo exec \"false\"
+  User ignored
+Match all
+  User everyone
+",
+    );
+    assert_eq!(config.resolve("foo").user.as_deref(), Some("everyone"));
+}
+
+#[test]
+fn proxy_jump() {
+    let config = Config::from_bytes(
+        b"Host a
+  ProxyJump ssh://me@bastion:2200,[::1]:22,plain
+Host b
+  ProxyJump none
+Host *
+  ProxyJump fallback
+",
+    );
+    assert_eq!(
+        config.resolve("a").proxy_jump,
+        [
+            Jump {
+                user: Some("me".into()),
+                host: "bastion".into(),
+                port: Some(2200),
+            },
+            Jump {
+                user: None,
+                host: "::1".into(),
+                port: Some(22),
+            },
+            Jump {
+                user: None,
+                host: "plain".into(),
+                port: None,
+            },
+        ]
+    );
+    assert!(config.resolve("b").proxy_jump.is_empty(), "'none' disables jumps");
+    assert_eq!(config.resolve("c").proxy_jump.len(), 1);
+}
+
+#[test]
+fn include_is_relative_to_the_including_file() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir(dir.path().join("conf.d"))?;
+    std::fs::write(dir.path().join("conf.d").join("a.conf"), "Port 1234\n")?;
+    std::fs::write(dir.path().join("conf.d").join("b.conf"), "Port 5678\nUser b\n")?;
+    std::fs::write(
+        dir.path().join("config"),
+        "Host example\n  Include conf.d/*.conf\nHost *\n  User fallback\n",
+    )?;
+
+    let config = Config::from_path(&dir.path().join("config"))?;
+    let host = config.resolve("example");
+    assert_eq!(host.port, Some(1234), "included files are read in sorted order");
+    assert_eq!(host.user.as_deref(), Some("b"), "included files inherit the condition");
+    assert_eq!(config.resolve("other").port, None);
+    assert_eq!(config.resolve("other").user.as_deref(), Some("fallback"));
+    Ok(())
+}
+
+#[test]
+fn missing_files_are_empty() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let config = Config::from_path(&dir.path().join("does-not-exist"))?;
+    assert_eq!(config.resolve("host").host_name, "host");
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use gix_ssh::{Config, connect};
+
+use crate::server::{Auth, Server};
+
+fn user_key() -> ssh_key::PrivateKey {
+    ssh_key::PrivateKey::random(&mut rand_core::OsRng, ssh_key::Algorithm::Ed25519).unwrap()
+}
+
+/// Return options to reach `server` as host `test` with `extra` configuration, storing host keys in `dir`.
+fn options(server: &Server, dir: &Path, extra: &str) -> connect::Options {
+    let config = format!(
+        "Host test\n  HostName 127.0.0.1\n  Port {port}\n{extra}\nHost *\n  User git\n  UserKnownHostsFile {known_hosts}\n  StrictHostKeyChecking accept-new\n",
+        port = server.addr.port(),
+        known_hosts = known_hosts(dir).display(),
+    );
+    connect::Options {
+        config: Config::from_bytes(config.as_bytes()),
+        agent: None,
+        prompt: gix_prompt::Options {
+            askpass: None,
+            mode: gix_prompt::Mode::Disable,
+        },
+    }
+}
+
+fn known_hosts(dir: &Path) -> PathBuf {
+    dir.join("known_hosts")
+}
+
+fn write_key(dir: &Path, key: &ssh_key::PrivateKey) -> PathBuf {
+    let path = dir.join("id_ed25519");
+    key.write_openssh_file(&path, ssh_key::LineEnding::LF).unwrap();
+    path
+}
+
+#[test]
+fn key_file_and_interactive_exec() -> crate::Result {
+    let key = user_key();
+    let server = Server::start(Auth {
+        user: "git".into(),
+        authorized_keys: vec![key.public_key().clone()],
+        password: None,
+    });
+    let dir = tempfile::tempdir()?;
+    let key_path = write_key(dir.path(), &key);
+    let options = options(&server, dir.path(), &format!("  IdentityFile {}", key_path.display()));
+
+    let session = gix_ssh::connect("test", None, None, &options)?;
+    let mut channel = session.exec("cat", &[("GIT_PROTOCOL", "version=2")])?;
+    for message in ["hello\n", "world\n"] {
+        channel.write_all(message.as_bytes())?;
+        let mut buf = vec![0; message.len()];
+        channel.read_exact(&mut buf)?;
+        assert_eq!(
+            buf,
+            message.as_bytes(),
+            "each message is echoed before the next one is sent"
+        );
+    }
+    channel.send_eof()?;
+    let mut rest = Vec::new();
+    channel.read_to_end(&mut rest)?;
+    assert!(rest.is_empty());
+    assert_eq!(channel.exit_status(), Some(0));
+    assert_eq!(
+        server.log(),
+        ["auth publickey git", "env GIT_PROTOCOL=version=2", "exec cat"]
+    );
+
+    let known_hosts = std::fs::read_to_string(known_hosts(dir.path()))?;
+    assert_eq!(
+        known_hosts,
+        format!("[127.0.0.1]:{} {}\n", server.addr.port(), server.host_key.to_openssh()?),
+        "new host keys are accepted and remembered"
+    );
+    Ok(())
+}
+
+#[test]
+fn failing_commands_report_their_stderr() -> crate::Result {
+    let key = user_key();
+    let server = Server::start(Auth {
+        user: "git".into(),
+        authorized_keys: vec![key.public_key().clone()],
+        password: None,
+    });
+    let dir = tempfile::tempdir()?;
+    let key_path = write_key(dir.path(), &key);
+    let options = options(&server, dir.path(), &format!("  IdentityFile {}", key_path.display()));
+
+    let mut channel =
+        gix_ssh::connect("test", None, None, &options)?.exec("echo 'repository not found' >&2; exit 3", &[])?;
+    let err = channel.read_to_end(&mut Vec::new()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The remote command failed with exit status 3: repository not found"
+    );
+    assert_eq!(channel.exit_status(), Some(3));
+    assert_eq!(channel.stderr(), b"repository not found\n");
+    Ok(())
+}
+
+#[test]
+fn encrypted_key_file_and_password_with_askpass() -> crate::Result {
+    let key = user_key();
+    let server = Server::start(Auth {
+        user: "alice".into(),
+        authorized_keys: vec![key.public_key().clone()],
+        password: Some("secret".into()),
+    });
+    let dir = tempfile::tempdir()?;
+    let key_path = write_key(dir.path(), &key.encrypt(&mut rand_core::OsRng, "secret")?);
+    let askpass = dir.path().join("askpass.sh");
+    std::fs::write(
+        &askpass,
+        "#!/bin/sh\necho \"$1\" >>\"$(dirname \"$0\")/prompts\"\necho secret\n",
+    )?;
+    make_executable(&askpass)?;
+
+    let mut options = options(&server, dir.path(), &format!("  IdentityFile {}", key_path.display()));
+    options.prompt.askpass = Some(askpass.clone());
+    gix_ssh::connect("test", None, Some("alice"), &options)?;
+    let prompts = std::fs::read_to_string(dir.path().join("prompts"))?;
+    assert_eq!(
+        prompts,
+        format!("Enter passphrase for key '{}': \n", key_path.display())
+    );
+
+    std::fs::remove_file(&key_path)?;
+    gix_ssh::connect("test", None, Some("alice"), &options)?;
+    let prompts = std::fs::read_to_string(dir.path().join("prompts"))?;
+    assert!(
+        prompts.ends_with("alice@127.0.0.1's password: \n"),
+        "without usable key, the password is used"
+    );
+    assert_eq!(server.log(), ["auth publickey alice", "auth password alice"]);
+
+    options.prompt.askpass = None;
+    let err = gix_ssh::connect("test", None, Some("alice"), &options)
+        .err()
+        .expect("cannot ask for the password");
+    assert!(matches!(
+        err,
+        connect::Error::Auth(gix_ssh::auth::Error::Denied { ref user, .. }) if user == "alice"
+    ));
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn agent() -> crate::Result {
+    let key = user_key();
+    let server = Server::start(Auth {
+        user: "git".into(),
+        authorized_keys: vec![key.public_key().clone()],
+        password: None,
+    });
+    let dir = tempfile::tempdir()?;
+    let mut options = options(&server, dir.path(), "");
+    options.agent = Some(agent::start(dir.path(), vec![user_key(), key]));
+
+    gix_ssh::connect("test", None, None, &options)?;
+    assert_eq!(server.log(), ["auth publickey git"]);
+    Ok(())
+}
+
+#[test]
+fn proxy_jump() -> crate::Result {
+    let key = user_key();
+    let server = Server::start(Auth {
+        user: "git".into(),
+        authorized_keys: vec![key.public_key().clone()],
+        password: None,
+    });
+    let dir = tempfile::tempdir()?;
+    let key_path = write_key(dir.path(), &key);
+    let port = server.addr.port();
+    let options = options(
+        &server,
+        dir.path(),
+        &format!(
+            "  ProxyJump jump\nHost jump\n  HostName 127.0.0.1\n  Port {port}\n  IdentityFile {}",
+            key_path.display()
+        ),
+    );
+    // The target has no identity file, so the jump host has to be reached with its own configuration.
+    let err = gix_ssh::connect("test", None, None, &options)
+        .err()
+        .expect("no key to authenticate to the target with");
+    assert!(matches!(err, connect::Error::Auth(_)), "{err:?}");
+
+    let options = {
+        let mut options = options;
+        let config = format!(
+            "Host test\n  HostName 127.0.0.1\n  Port {port}\n  ProxyJump jump\nHost jump\n  HostName 127.0.0.1\n  Port {port}\nHost *\n  User git\n  IdentityFile {}\n  UserKnownHostsFile {}\n",
+            key_path.display(),
+            known_hosts(dir.path()).display()
+        );
+        options.config = Config::from_bytes(config.as_bytes());
+        options
+    };
+    let mut channel = gix_ssh::connect("test", None, None, &options)?.exec("echo hi", &[])?;
+    let mut out = String::new();
+    channel.read_to_string(&mut out)?;
+    assert_eq!(out, "hi\n");
+    assert!(
+        server.log().contains(&format!("direct-tcpip 127.0.0.1:{port}")),
+        "the target was reached through the jump host"
+    );
+    Ok(())
+}
+
+#[test]
+fn unknown_and_changed_host_keys_are_rejected() -> crate::Result {
+    let server = Server::start(Auth::default());
+    let dir = tempfile::tempdir()?;
+    let strict = options(&server, dir.path(), "  StrictHostKeyChecking yes");
+    let err = gix_ssh::connect("test", None, None, &strict)
+        .err()
+        .expect("unknown host key");
+    assert!(
+        matches!(err, connect::Error::UnknownHostKey { ref host, .. } if host == "127.0.0.1"),
+        "{err:?}"
+    );
+    assert!(!known_hosts(dir.path()).exists(), "nothing is written");
+
+    let other_key = user_key().public_key().to_openssh()?;
+    std::fs::write(
+        known_hosts(dir.path()),
+        format!("[127.0.0.1]:{} {other_key}\n", server.addr.port()),
+    )?;
+    let options = options(&server, dir.path(), "");
+    let err = gix_ssh::connect("test", None, None, &options)
+        .err()
+        .expect("changed host key");
+    assert!(matches!(err, connect::Error::HostKeyMismatch { .. }), "{err:?}");
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+mod agent {
+    use std::{
+        io::{Read, Write},
+        os::unix::net::UnixListener,
+        path::{Path, PathBuf},
+    };
+
+    use gix_ssh::wire;
+    use signature::Signer;
+
+    /// Start an agent stand-in holding `keys` with a socket in `dir`, and return the path to the socket.
+    pub fn start(dir: &Path, keys: Vec<ssh_key::PrivateKey>) -> PathBuf {
+        let path = dir.join("agent.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                loop {
+                    let mut len = [0; 4];
+                    if stream.read_exact(&mut len).is_err() {
+                        break;
+                    }
+                    let mut request = vec![0; u32::from_be_bytes(len) as usize];
+                    stream.read_exact(&mut request).unwrap();
+                    let response = match request[0] {
+                        11 => keys
+                            .iter()
+                            .fold(wire::Message::new(12).u32(keys.len() as u32), |msg, key| {
+                                msg.string(key.public_key().to_bytes().unwrap()).string("comment")
+                            }),
+                        13 => {
+                            let mut fields = wire::Reader::new(&request[1..]);
+                            let blob = fields.string().unwrap();
+                            let data = fields.string().unwrap();
+                            let key = keys
+                                .iter()
+                                .find(|key| key.public_key().to_bytes().unwrap() == blob)
+                                .unwrap();
+                            let signature: Vec<u8> = Signer::<ssh_key::Signature>::sign(key, data).try_into().unwrap();
+                            wire::Message::new(14).string(signature)
+                        }
+                        _ => wire::Message::new(5),
+                    };
+                    let mut frame = Vec::new();
+                    wire::put_string(&mut frame, response.as_ref());
+                    stream.write_all(&frame).unwrap();
+                }
+            }
+        });
+        path
+    }
+}
+
+#[test]
+fn git_upload_pack() -> crate::Result {
+    let key = user_key();
+    let server = Server::start(Auth {
+        user: "git".into(),
+        authorized_keys: vec![key.public_key().clone()],
+        password: None,
+    });
+    let dir = tempfile::tempdir()?;
+    let key_path = write_key(dir.path(), &key);
+    let repo = dir.path().join("repo.git");
+    let status = std::process::Command::new("git")
+        .args(["init", "--bare", "--quiet"])
+        .arg(&repo)
+        .status()?;
+    assert!(status.success());
+    let options = options(&server, dir.path(), &format!("  IdentityFile {}", key_path.display()));
+
+    let mut channel = gix_ssh::connect("test", None, None, &options)?.exec(
+        &format!("git upload-pack '{}'", repo.display()),
+        &[("GIT_PROTOCOL", "version=2")],
+    )?;
+    let mut first_line = [0; 14];
+    channel.read_exact(&mut first_line)?;
+    assert_eq!(&first_line, b"000eversion 2\n", "the server speaks protocol version 2");
+    // Ask for nothing and terminate the conversation.
+    channel.write_all(b"0000")?;
+    channel.send_eof()?;
+    channel.read_to_end(&mut Vec::new())?;
+    assert_eq!(channel.exit_status(), Some(0));
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use base64::Engine;
+use gix_ssh::known_hosts::{File, Verdict};
+use hmac::Mac;
+
+fn key() -> ssh_key::PublicKey {
+    ssh_key::PrivateKey::random(&mut rand_core::OsRng, ssh_key::Algorithm::Ed25519)
+        .unwrap()
+        .public_key()
+        .clone()
+}
+
+fn line(hosts: &str, key: &ssh_key::PublicKey) -> String {
+    format!("{hosts} {}\n", key.to_openssh().unwrap())
+}
+
+#[test]
+fn patterns_and_ports() {
+    let (known, other) = (key(), key());
+    let file = File::from_bytes(
+        format!(
+            "# comment\n{}{}garbage\n@cert-authority * {}",
+            line("*.example.com,!evil.example.com", &known),
+            line("[git.local]:2222", &known),
+            other.to_openssh().unwrap()
+        )
+        .as_bytes(),
+    );
+    assert_eq!(file.verify("GIT.example.com", 22, &known), Verdict::Match);
+    assert_eq!(file.verify("git.example.com", 22, &other), Verdict::Mismatch);
+    assert_eq!(file.verify("evil.example.com", 22, &known), Verdict::Unknown);
+    assert_eq!(file.verify("git.example.com", 2222, &known), Verdict::Unknown);
+    assert_eq!(file.verify("git.local", 2222, &known), Verdict::Match);
+    assert_eq!(file.verify("git.local", 22, &known), Verdict::Unknown);
+    assert_eq!(
+        file.verify("anything", 22, &other),
+        Verdict::Unknown,
+        "certificate authorities are ignored"
+    );
+    assert_eq!(
+        file.key_algorithms("git.example.com", 22),
+        [ssh_key::Algorithm::Ed25519]
+    );
+}
+
+#[test]
+fn hashed_host_names() {
+    let known = key();
+    let salt = b"0123456789abcdefghij";
+    let mut mac = hmac::Hmac::<sha1::Sha1>::new_from_slice(salt).unwrap();
+    mac.update(b"[example.com]:2222");
+    let engine = base64::engine::general_purpose::STANDARD;
+    let hosts = format!(
+        "|1|{}|{}",
+        engine.encode(salt),
+        engine.encode(mac.finalize().into_bytes())
+    );
+
+    let file = File::from_bytes(line(&hosts, &known).as_bytes());
+    assert_eq!(file.verify("example.com", 2222, &known), Verdict::Match);
+    assert_eq!(file.verify("example.com", 22, &known), Verdict::Unknown);
+}
+
+#[test]
+fn revoked_keys_are_rejected_for_all_hosts() {
+    let revoked = key();
+    let file = File::from_bytes(
+        format!(
+            "{}@revoked * {}",
+            line("example.com", &revoked),
+            revoked.to_openssh().unwrap()
+        )
+        .as_bytes(),
+    );
+    assert_eq!(file.verify("example.com", 22, &revoked), Verdict::Revoked);
+    assert_eq!(file.verify("other.com", 22, &revoked), Verdict::Revoked);
+}
+
+#[test]
+fn append_creates_the_file_and_remembers_the_key() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join(".ssh").join("known_hosts");
+    let mut file = File::from_paths([path.as_path()])?;
+    let known = key();
+    assert_eq!(file.verify("example.com", 2222, &known), Verdict::Unknown);
+
+    file.append(&path, "Example.com", 2222, &known)?;
+    assert_eq!(file.verify("example.com", 2222, &known), Verdict::Match);
+    assert_eq!(
+        File::from_paths([path.as_path()])?.verify("example.com", 2222, &known),
+        Verdict::Match,
+        "the key was written"
+    );
+    assert!(std::fs::read_to_string(&path)?.starts_with("[example.com]:2222 ssh-ed25519 "));
+    Ok(())
+}
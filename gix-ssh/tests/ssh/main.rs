@@ -0,0 +1,6 @@
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+mod config;
+mod connect;
+mod known_hosts;
+mod server;
@@ -0,0 +1,362 @@
+//! A stand-in for `sshd` which runs commands with `sh` and connects to other hosts, enough to test the client against.
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
+
+use gix_ssh::{Transport, msg, wire};
+use signature::Verifier;
+
+/// The credentials the server accepts.
+#[derive(Default, Clone)]
+pub struct Auth {
+    pub user: String,
+    pub authorized_keys: Vec<ssh_key::PublicKey>,
+    pub password: Option<String>,
+}
+
+pub struct Server {
+    pub addr: SocketAddr,
+    pub host_key: ssh_key::PublicKey,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Server {
+    /// Start serving connections in the background, accepting the credentials in `auth`.
+    pub fn start(auth: Auth) -> Server {
+        let host_key = ssh_key::PrivateKey::random(&mut rand_core::OsRng, ssh_key::Algorithm::Ed25519).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = Server {
+            addr: listener.local_addr().unwrap(),
+            host_key: host_key.public_key().clone(),
+            log: Default::default(),
+        };
+        let log = server.log.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let (host_key, auth, log) = (host_key.clone(), auth.clone(), log.clone());
+                std::thread::spawn(move || {
+                    // Errors are expected when clients reject the host key or give up.
+                    serve(stream, host_key, &auth, &log).ok();
+                });
+            }
+        });
+        server
+    }
+
+    /// Return a line for each authentication, environment variable, command and forwarded connection, in order.
+    pub fn log(&self) -> Vec<String> {
+        self.log.lock().unwrap().clone()
+    }
+}
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+fn serve(stream: TcpStream, host_key: ssh_key::PrivateKey, auth: &Auth, log: &Mutex<Vec<String>>) -> Result {
+    let input = Input::new(stream)?;
+    let mut transport = Transport::accept(input.clone(), host_key)?;
+    let request = transport.read_message()?;
+    assert_eq!(request[0], msg::SERVICE_REQUEST);
+    transport.write_message(wire::Message::new(msg::SERVICE_ACCEPT).string("ssh-userauth"))?;
+    authenticate(&mut transport, auth, log)?;
+
+    let open = transport.read_message()?;
+    assert_eq!(open[0], msg::CHANNEL_OPEN);
+    let mut fields = wire::Reader::new(&open[1..]);
+    let kind = fields.utf8()?.to_owned();
+    let remote_id = fields.u32()?;
+    let _window = fields.u32()?;
+    let _max_packet = fields.u32()?;
+    let confirmation = wire::Message::new(msg::CHANNEL_OPEN_CONFIRMATION)
+        .u32(remote_id)
+        .u32(0)
+        .u32(u32::MAX)
+        .u32(32 * 1024);
+
+    let (events, rx) = mpsc::channel();
+    let backend = match kind.as_str() {
+        "direct-tcpip" => {
+            let (host, port) = (fields.utf8()?, fields.u32()?);
+            log.lock().unwrap().push(format!("direct-tcpip {host}:{port}"));
+            let target = TcpStream::connect((host, port as u16))?;
+            transport.write_message(confirmation)?;
+            pump(target.try_clone()?, Event::Stdout, events.clone());
+            Backend::Tcp(target)
+        }
+        "session" => {
+            transport.write_message(confirmation)?;
+            let mut env = Vec::new();
+            let command = loop {
+                let request = transport.read_message()?;
+                let mut fields = wire::Reader::new(&request[1..]);
+                let _recipient = fields.u32()?;
+                let (name, want_reply) = (fields.utf8()?, fields.bool()?);
+                match name {
+                    "env" => env.push((fields.utf8()?.to_owned(), fields.utf8()?.to_owned())),
+                    "exec" => break fields.utf8()?.to_owned(),
+                    _ => unreachable!("unexpected request {name}"),
+                }
+                assert!(!want_reply, "only exec requests want a reply");
+            };
+            for (name, value) in &env {
+                log.lock().unwrap().push(format!("env {name}={value}"));
+            }
+            log.lock().unwrap().push(format!("exec {command}"));
+            let mut child = Command::new("sh")
+                .args(["-c", &command])
+                .envs(env)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            transport.write_message(wire::Message::new(msg::CHANNEL_SUCCESS).u32(remote_id))?;
+            pump(child.stdout.take().expect("piped"), Event::Stdout, events.clone());
+            pump(child.stderr.take().expect("piped"), Event::Stderr, events.clone());
+            Backend::Process(child)
+        }
+        _ => unreachable!("unexpected channel type {kind}"),
+    };
+    drop(events);
+    relay(&mut transport, &input, remote_id, backend, &rx)
+}
+
+/// Forward data between the channel and `backend` until both sides are done.
+fn relay(
+    transport: &mut Transport<Input>,
+    input: &Input,
+    remote_id: u32,
+    mut backend: Backend,
+    events: &mpsc::Receiver<Event>,
+) -> Result {
+    let mut open_outputs = match backend {
+        Backend::Tcp(_) => 1,
+        Backend::Process(_) => 2,
+    };
+    let mut closed = false;
+    loop {
+        while let Ok(event) = events.try_recv() {
+            match event {
+                Event::Stdout(data) => {
+                    for chunk in data.chunks(32 * 1024) {
+                        transport.write_message(wire::Message::new(msg::CHANNEL_DATA).u32(remote_id).string(chunk))?;
+                    }
+                }
+                Event::Stderr(data) => transport.write_message(
+                    wire::Message::new(msg::CHANNEL_EXTENDED_DATA)
+                        .u32(remote_id)
+                        .u32(1)
+                        .string(data),
+                )?,
+                Event::Eof => open_outputs -= 1,
+            }
+        }
+        if open_outputs == 0 && !closed {
+            transport.write_message(wire::Message::new(msg::CHANNEL_EOF).u32(remote_id))?;
+            if let Backend::Process(child) = &mut backend {
+                let status = child.wait()?.code().unwrap_or(255);
+                transport.write_message(
+                    wire::Message::new(msg::CHANNEL_REQUEST)
+                        .u32(remote_id)
+                        .string("exit-status")
+                        .bool(false)
+                        .u32(status as u32),
+                )?;
+            }
+            transport.write_message(wire::Message::new(msg::CHANNEL_CLOSE).u32(remote_id))?;
+            closed = true;
+        }
+        if !input.is_pending()? {
+            continue;
+        }
+        let message = transport.read_message()?;
+        let mut fields = wire::Reader::new(&message[1..]);
+        match message[0] {
+            msg::CHANNEL_DATA => {
+                let _recipient = fields.u32()?;
+                let data = fields.string()?;
+                match &mut backend {
+                    Backend::Tcp(target) => target.write_all(data)?,
+                    Backend::Process(child) => {
+                        // The command may have exited already.
+                        if let Some(stdin) = child.stdin.as_mut() {
+                            stdin.write_all(data).ok();
+                        }
+                    }
+                }
+            }
+            msg::CHANNEL_EOF => match &mut backend {
+                Backend::Tcp(target) => target.shutdown(std::net::Shutdown::Write)?,
+                Backend::Process(child) => drop(child.stdin.take()),
+            },
+            msg::CHANNEL_CLOSE => {
+                if let Backend::Tcp(target) = &backend {
+                    target.shutdown(std::net::Shutdown::Both).ok();
+                }
+                if !closed {
+                    transport.write_message(wire::Message::new(msg::CHANNEL_CLOSE).u32(remote_id))?;
+                }
+                return Ok(());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn authenticate(transport: &mut Transport<Input>, auth: &Auth, log: &Mutex<Vec<String>>) -> Result {
+    let mut methods = vec!["publickey"];
+    if auth.password.is_some() {
+        methods.push("password");
+    }
+    loop {
+        let request = transport.read_message()?;
+        assert_eq!(request[0], msg::USERAUTH_REQUEST);
+        let mut fields = wire::Reader::new(&request[1..]);
+        let user = fields.utf8()?;
+        let _service = fields.utf8()?;
+        let method = fields.utf8()?;
+        let user_matches = user == auth.user;
+        let success = match method {
+            "publickey" => {
+                let has_signature = fields.bool()?;
+                let algorithm = fields.utf8()?;
+                let blob = fields.string()?;
+                let key = ssh_key::PublicKey::from_bytes(blob)?;
+                let authorized = user_matches
+                    && auth
+                        .authorized_keys
+                        .iter()
+                        .any(|authorized| authorized.key_data() == key.key_data());
+                if authorized && !has_signature {
+                    transport.write_message(wire::Message::new(msg::USERAUTH_PK_OK).string(algorithm).string(blob))?;
+                    continue;
+                }
+                authorized && {
+                    let signed_len = request.len() - fields.rest().len();
+                    let signature = ssh_key::Signature::try_from(fields.string()?)?;
+                    let mut signed = Vec::new();
+                    wire::put_string(&mut signed, transport.session_id());
+                    signed.extend_from_slice(&request[..signed_len]);
+                    Verifier::verify(&key, &signed, &signature).is_ok()
+                }
+            }
+            "password" => {
+                let _change = fields.bool()?;
+                user_matches && auth.password.as_deref() == Some(fields.utf8()?)
+            }
+            _ => false,
+        };
+        if success {
+            log.lock().unwrap().push(format!("auth {method} {user}"));
+            transport.write_message([msg::USERAUTH_SUCCESS])?;
+            return Ok(());
+        }
+        transport.write_message(
+            wire::Message::new(msg::USERAUTH_FAILURE)
+                .name_list(&methods)
+                .bool(false),
+        )?;
+    }
+}
+
+enum Backend {
+    Tcp(TcpStream),
+    Process(std::process::Child),
+}
+
+enum Event {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+    Eof,
+}
+
+/// Send everything read from `source` as events created with `event`, followed by [`Event::Eof`].
+fn pump(mut source: impl Read + Send + 'static, event: fn(Vec<u8>) -> Event, events: mpsc::Sender<Event>) {
+    std::thread::spawn(move || {
+        let mut buf = [0; 8192];
+        while let Ok(n @ 1..) = source.read(&mut buf) {
+            if events.send(event(buf[..n].to_vec())).is_err() {
+                return;
+            }
+        }
+        events.send(Event::Eof).ok();
+    });
+}
+
+/// The connection to the client, which hands out received data one byte at a time so the transport never buffers
+/// data beyond the message it reads, which allows to wait for either more messages or command output.
+#[derive(Clone)]
+struct Input {
+    stream: Arc<TcpStream>,
+    buf: Arc<Mutex<VecDeque<u8>>>,
+}
+
+impl Input {
+    fn new(stream: TcpStream) -> std::io::Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(Input {
+            stream: Arc::new(stream),
+            buf: Default::default(),
+        })
+    }
+
+    /// Return `true` if there is data to read, waiting a little for it to arrive.
+    fn is_pending(&self) -> std::io::Result<bool> {
+        let mut buf = self.buf.lock().unwrap();
+        if !buf.is_empty() {
+            return Ok(true);
+        }
+        self.stream.set_read_timeout(Some(Duration::from_millis(10)))?;
+        let mut chunk = [0; 8192];
+        let res = (&*self.stream).read(&mut chunk);
+        self.stream.set_read_timeout(None)?;
+        match res {
+            Ok(n) => {
+                buf.extend(&chunk[..n]);
+                // Let the transport notice the end of the stream.
+                Ok(true)
+            }
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let mut buf = self.buf.lock().unwrap();
+        if buf.is_empty() {
+            let mut chunk = [0; 8192];
+            let n = (&*self.stream).read(&mut chunk)?;
+            buf.extend(&chunk[..n]);
+        }
+        match (buf.pop_front(), out.first_mut()) {
+            (Some(byte), Some(first)) => {
+                *first = byte;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+impl Write for Input {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        (&*self.stream).write(data)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        (&*self.stream).flush()
+    }
+}
@@ -0,0 +1,163 @@
+use std::{ffi::OsString, path::PathBuf, process::Stdio};
+
+use bstr::{BString, ByteSlice};
+
+use crate::fsmonitor::{Changes, FsMonitor, Response, Token};
+
+/// The version of the protocol to use when talking to the hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Version {
+    /// The hook receives a timestamp in nanoseconds and outputs the changed paths.
+    V1,
+    /// The hook receives an opaque token and outputs a new token, followed by the changed paths.
+    V2,
+}
+
+/// The error returned by [`Hook::query_changes()`](FsMonitor::query_changes()).
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not launch fsmonitor hook: {command:?}")]
+    SpawnCommand {
+        source: std::io::Error,
+        command: std::process::Command,
+    },
+    #[error("The fsmonitor hook {command:?} failed with {status}")]
+    Exit {
+        status: std::process::ExitStatus,
+        command: std::process::Command,
+    },
+    #[error("The fsmonitor hook {command:?} didn't output a token")]
+    MissingToken { command: std::process::Command },
+}
+
+/// A filesystem monitor which runs a hook program, like it's configured via `core.fsmonitor`, to learn about changes.
+///
+/// The hook is invoked with the protocol version and the last token, and is expected to output all paths
+/// that changed since, separated by null bytes. With version 2 of the protocol, the output starts with the new token.
+#[derive(Debug, Clone)]
+pub struct Hook {
+    command: OsString,
+    worktree: PathBuf,
+    version: Option<Version>,
+}
+
+/// Lifecycle
+impl Hook {
+    /// Create a new instance to run `command` in the `worktree` directory, using the protocol `version`.
+    ///
+    /// If `version` is `None`, version 2 is tried first with a fallback to version 1, just like Git does.
+    pub fn new(command: impl Into<OsString>, worktree: impl Into<PathBuf>, version: Option<Version>) -> Self {
+        Hook {
+            command: command.into(),
+            worktree: worktree.into(),
+            version,
+        }
+    }
+}
+
+/// Access
+impl Hook {
+    /// Return the protocol version that is used, or `None` if it wasn't determined yet.
+    pub fn version(&self) -> Option<Version> {
+        self.version
+    }
+}
+
+impl FsMonitor for Hook {
+    type Error = Error;
+
+    fn query_changes(&mut self, token: Option<&Token>) -> Result<Response, Self::Error> {
+        let res = match self.version {
+            Some(Version::V1) => self.query_v1(token),
+            Some(Version::V2) => self.query_v2(token),
+            None => match self.query_v2(token) {
+                Ok(res) => {
+                    self.version = Some(Version::V2);
+                    Ok(res)
+                }
+                Err(_) => {
+                    let res = self.query_v1(token)?;
+                    self.version = Some(Version::V1);
+                    Ok(res)
+                }
+            },
+        }?;
+        Ok(match token {
+            None => Response {
+                token: res.token,
+                changes: Changes::All,
+            },
+            Some(_) => res,
+        })
+    }
+}
+
+impl Hook {
+    fn query_v1(&self, token: Option<&Token>) -> Result<Response, Error> {
+        // Take the time before the query to be sure we won't miss changes that happen while it runs.
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        let nanos_since_1970 = match token {
+            Some(Token::V1 { nanos_since_1970 }) => *nanos_since_1970,
+            Some(Token::V2 { token }) => token.to_str().ok().and_then(|t| t.parse().ok()).unwrap_or(0),
+            None => 0,
+        };
+        let (output, _command) = self.run("1", nanos_since_1970.to_string())?;
+        Ok(Response {
+            token: Token::V1 { nanos_since_1970: now },
+            changes: parse_paths(&output),
+        })
+    }
+
+    fn query_v2(&self, token: Option<&Token>) -> Result<Response, Error> {
+        let token = match token {
+            Some(Token::V1 { nanos_since_1970 }) => nanos_since_1970.to_string(),
+            Some(Token::V2 { token }) => gix_path::from_bstr(token.as_bstr()).to_string_lossy().into_owned(),
+            None => String::new(),
+        };
+        let (output, command) = self.run("2", token)?;
+        let Some((token, paths)) = output.split_once_str(b"\0") else {
+            return Err(Error::MissingToken { command });
+        };
+        Ok(Response {
+            token: Token::V2 {
+                token: BString::from(token),
+            },
+            changes: parse_paths(paths),
+        })
+    }
+
+    fn run(&self, version: &str, token: String) -> Result<(Vec<u8>, std::process::Command), Error> {
+        let mut command: std::process::Command = gix_command::prepare(self.command.clone())
+            .command_may_be_shell_script()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .arg(version)
+            .arg(token)
+            .into();
+        command.current_dir(&self.worktree);
+        gix_trace::debug!(cmd = ?command, "launching fsmonitor hook");
+        let output = match command.output() {
+            Ok(output) => output,
+            Err(source) => return Err(Error::SpawnCommand { source, command }),
+        };
+        if !output.status.success() {
+            return Err(Error::Exit {
+                status: output.status,
+                command,
+            });
+        }
+        Ok((output.stdout, command))
+    }
+}
+
+fn parse_paths(output: &[u8]) -> Changes {
+    let mut paths = output.split_str(b"\0").filter(|path| !path.is_empty()).peekable();
+    if paths.peek().is_some_and(|path| path.starts_with(b"/")) {
+        return Changes::All;
+    }
+    Changes::Paths(paths.map(BString::from).collect())
+}
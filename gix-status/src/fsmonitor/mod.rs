@@ -0,0 +1,142 @@
+//! Use a filesystem monitor to learn which index entries may have changed, to avoid checking all of them.
+//!
+//! The filesystem monitor hands out a [token](Token) with each answer, which is kept in the index via the
+//! [`FsMonitor`](gix_index::extension::FsMonitor) extension. Given that token, it reports all paths that changed since.
+//! Entries that didn't change keep their [`FSMONITOR_VALID`](gix_index::entry::Flags::FSMONITOR_VALID) flag, which lets
+//! [`index_as_worktree()`](crate::index_as_worktree()) skip them entirely.
+use bstr::BString;
+pub use gix_index::extension::fs_monitor::Token;
+
+/// The changes reported by a [filesystem monitor](FsMonitor).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changes {
+    /// Everything may have changed, for instance because the monitor was just started or lost track.
+    All,
+    /// Only the given worktree-relative paths changed.
+    ///
+    /// Paths with a trailing slash denote directories, which invalidates everything inside of them.
+    Paths(Vec<BString>),
+}
+
+/// The answer of a [filesystem monitor](FsMonitor) to a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The token to store for use in the next query.
+    pub token: Token,
+    /// The changes since the token that was passed to the query.
+    pub changes: Changes,
+}
+
+/// A filesystem monitor which knows which paths changed since a point in time, identified by a [token](Token).
+pub trait FsMonitor {
+    /// The error that can occur when querying the monitor.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Return all changes since `token` was handed out, along with a new token to identify the current point in time.
+    ///
+    /// If `token` is `None`, the monitor is queried for the first time and all paths should be considered changed.
+    fn query_changes(&mut self, token: Option<&Token>) -> Result<Response, Self::Error>;
+}
+
+/// The outcome of [`refresh()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of entries that the filesystem monitor vouches for, and that don't need to be checked for changes.
+    pub entries_valid: usize,
+    /// The amount of paths that the filesystem monitor reported as changed.
+    pub paths_reported: usize,
+    /// If `true`, the filesystem monitor couldn't tell which paths changed so all entries need to be checked.
+    pub all_invalidated: bool,
+}
+
+/// Query `monitor` for all changes since the token stored in `index` and set the
+/// [`FSMONITOR_VALID`](gix_index::entry::Flags::FSMONITOR_VALID) flag on all entries that didn't change
+/// according to the monitor. The new token is stored in `index`, which should be written back once the status
+/// computation is complete and unchanged entries were marked with [`mark_valid()`].
+///
+/// Changed paths also invalidate the respective directories in the untracked cache, if present.
+///
+/// If `monitor` fails, the fsmonitor extension is removed from the `index` and all entries lose their
+/// `FSMONITOR_VALID` flag before the error is returned, so the status will check all entries.
+pub fn refresh<M: FsMonitor>(index: &mut gix_index::State, monitor: &mut M) -> Result<Outcome, M::Error> {
+    let _span = gix_features::trace::coarse!("gix_status::fsmonitor::refresh()");
+    use gix_index::entry::Flags;
+
+    let previous = index.fs_monitor().cloned();
+    let mut valid = vec![previous.is_some(); index.entries().len()];
+    let num_entries = valid.len();
+    if previous.as_ref().is_some_and(|previous| {
+        previous
+            .for_each_dirty_entry(num_entries, |idx| valid[idx] = false)
+            .is_none()
+    }) {
+        valid.iter_mut().for_each(|v| *v = false);
+    }
+
+    let response = match monitor.query_changes(previous.as_ref().map(gix_index::extension::FsMonitor::token)) {
+        Ok(response) => response,
+        Err(err) => {
+            index.remove_fs_monitor();
+            for entry in index.entries_mut() {
+                entry.flags.remove(Flags::FSMONITOR_VALID);
+            }
+            return Err(err);
+        }
+    };
+
+    let mut out = Outcome::default();
+    match response.changes {
+        Changes::All => {
+            out.all_invalidated = true;
+            valid.iter_mut().for_each(|v| *v = false);
+        }
+        Changes::Paths(paths) => {
+            out.paths_reported = paths.len();
+            for path in &paths {
+                let path = path.strip_suffix(b"/").unwrap_or(path.as_slice());
+                if let Ok(idx) = index.entry_index_by_path(path.into()) {
+                    valid[idx] = false;
+                }
+                let mut dir = BString::from(path);
+                dir.push(b'/');
+                if let Some(range) = index.prefixed_entries_range(dir.as_ref()) {
+                    valid[range].iter_mut().for_each(|v| *v = false);
+                }
+                if let Some(untracked) = index.untracked_mut() {
+                    untracked.invalidate_path(path.into());
+                }
+            }
+        }
+    }
+
+    for (entry, valid) in index.entries_mut().iter_mut().zip(valid) {
+        entry.flags.set(Flags::FSMONITOR_VALID, valid);
+        out.entries_valid += usize::from(valid);
+    }
+    index.set_fs_monitor(Some(gix_index::extension::FsMonitor::new(response.token)));
+    Ok(out)
+}
+
+/// Set the [`FSMONITOR_VALID`](gix_index::entry::Flags::FSMONITOR_VALID) flag on all entries of `index` that were checked
+/// by the status and found unchanged, i.e. all entries for which `is_changed(entry_index)` returns `false`.
+///
+/// Conflicting entries, submodules and entries added with `--intent-to-add` are never marked as they are always checked.
+///
+/// Note that this must only be called if all entries were checked, i.e. if the status wasn't limited by a pathspec.
+pub fn mark_valid(index: &mut gix_index::State, mut is_changed: impl FnMut(usize) -> bool) {
+    for (idx, entry) in index.entries_mut().iter_mut().enumerate() {
+        let valid = can_mark_valid(entry) && !is_changed(idx);
+        entry.flags.set(gix_index::entry::Flags::FSMONITOR_VALID, valid);
+    }
+}
+
+/// Return `true` if [`mark_valid()`] would mark `entry` as valid if it was found unchanged.
+pub fn can_mark_valid(entry: &gix_index::Entry) -> bool {
+    entry.stage_raw() == 0
+        && !entry.mode.is_submodule()
+        && !entry.flags.contains(gix_index::entry::Flags::INTENT_TO_ADD)
+}
+
+///
+pub mod hook;
+pub use hook::Hook;
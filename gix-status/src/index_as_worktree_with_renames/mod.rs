@@ -113,6 +113,7 @@ pub(super) mod function {
                                         excludes: excludes.as_mut(),
                                         objects: &objects,
                                         explicit_traversal_root: Some(worktree),
+                                        untracked_cache: dirwalk_ctx.untracked_cache,
                                     },
                                     options,
                                     &mut collect,
@@ -365,6 +365,10 @@ pub struct DirwalkContext<'a> {
     ///
     /// [Read more in `gix-dir`](gix_dir::walk::Context::ignore_case_index_lookup).
     pub ignore_case_index_lookup: Option<&'a gix_index::AccelerateLookup<'a>>,
+    /// If set, the untracked cache to use to avoid reading directories that didn't change, and to update with what was seen.
+    ///
+    /// [Read more in `gix-dir`](gix_dir::walk::Context::untracked_cache).
+    pub untracked_cache: Option<&'a mut gix_dir::walk::UntrackedCache>,
 }
 
 /// Observe the status of an entry by comparing an index entry to the worktree, along
@@ -37,6 +37,8 @@ use portable_atomic::AtomicU64;
 pub mod index_as_worktree;
 pub use index_as_worktree::function::index_as_worktree;
 
+pub mod fsmonitor;
+
 #[cfg(windows)]
 pub(crate) mod fscache;
 
@@ -0,0 +1,192 @@
+use gix_index::entry::Flags;
+use gix_status::fsmonitor::{self, Changes, FsMonitor, Response, Token};
+
+use crate::fixture_path;
+
+struct Fixed {
+    changes: Option<Changes>,
+    tokens_seen: Vec<Option<Token>>,
+}
+
+impl FsMonitor for Fixed {
+    type Error = std::io::Error;
+
+    fn query_changes(&mut self, token: Option<&Token>) -> Result<Response, Self::Error> {
+        self.tokens_seen.push(token.cloned());
+        let changes = self
+            .changes
+            .take()
+            .ok_or_else(|| std::io::Error::other("monitor is down"))?;
+        Ok(Response {
+            token: Token::V2 {
+                token: format!("token-{}", self.tokens_seen.len()).into(),
+            },
+            changes,
+        })
+    }
+}
+
+fn valid_paths(index: &gix_index::State) -> Vec<&bstr::BStr> {
+    index
+        .entries()
+        .iter()
+        .filter(|e| e.flags.contains(Flags::FSMONITOR_VALID))
+        .map(|e| e.path(index))
+        .collect()
+}
+
+fn write_and_read(index: gix_index::State) -> crate::Result<gix_index::State> {
+    let mut buf = Vec::new();
+    gix_index::File::from_state(index, "unused").write_to(&mut buf, Default::default())?;
+    let (index, _) = gix_index::State::from_bytes(
+        &buf,
+        filetime::FileTime::now(),
+        gix_testtools::object_hash(),
+        Default::default(),
+    )?;
+    Ok(index)
+}
+
+#[test]
+fn refresh_keeps_entries_valid_unless_reported() -> crate::Result {
+    let worktree = fixture_path("status_unchanged");
+    let mut index: gix_index::State = gix_index::File::at(
+        worktree.join(".git").join("index"),
+        gix_testtools::object_hash(),
+        false,
+        Default::default(),
+    )?
+    .into();
+    assert!(index.fs_monitor().is_none());
+
+    let mut monitor = Fixed {
+        changes: Some(Changes::Paths(vec!["dir/content".into()])),
+        tokens_seen: Vec::new(),
+    };
+    let out = fsmonitor::refresh(&mut index, &mut monitor)?;
+    assert_eq!(
+        out,
+        fsmonitor::Outcome {
+            entries_valid: 0,
+            paths_reported: 1,
+            all_invalidated: false
+        },
+        "without previous token, nothing can be trusted"
+    );
+    assert_eq!(monitor.tokens_seen, [None]);
+    assert_eq!(
+        index.fs_monitor().map(|fsm| fsm.token().clone()),
+        Some(Token::V2 {
+            token: "token-1".into()
+        })
+    );
+
+    let executable = index.entry_index_by_path("executable".into()).expect("present");
+    fsmonitor::mark_valid(&mut index, |idx| idx == executable);
+    let num_entries = index.entries().len();
+    assert_eq!(valid_paths(&index).len(), num_entries - 1, "all but the changed entry");
+
+    let mut index = write_and_read(index)?;
+    assert!(
+        valid_paths(&index).is_empty(),
+        "the in-memory flags aren't set when reading, only the extension is"
+    );
+    monitor.changes = Some(Changes::Paths(vec!["dir/".into(), "untracked".into()]));
+    let out = fsmonitor::refresh(&mut index, &mut monitor)?;
+    assert_eq!(
+        monitor.tokens_seen.last(),
+        Some(&Some(Token::V2 {
+            token: "token-1".into()
+        })),
+        "the token written into the index is used for the query"
+    );
+    let valid = valid_paths(&index);
+    assert_eq!(out.entries_valid, valid.len());
+    assert!(
+        valid
+            .iter()
+            .all(|path| !path.starts_with(b"dir/") && *path != "executable"),
+        "entries below reported directories and previously changed ones are invalid: {valid:?}"
+    );
+    assert_eq!(valid.len(), num_entries - 1 - 2, "dir/ contains two entries");
+
+    monitor.changes = None;
+    let err = fsmonitor::refresh(&mut index, &mut monitor).unwrap_err();
+    assert_eq!(err.to_string(), "monitor is down");
+    assert!(valid_paths(&index).is_empty(), "on error, nothing is trusted anymore");
+    assert!(index.fs_monitor().is_none(), "and the token is discarded");
+    Ok(())
+}
+
+#[cfg(unix)]
+mod hook {
+    use std::os::unix::fs::PermissionsExt;
+
+    use gix_status::fsmonitor::{Changes, FsMonitor, Hook, Token, hook::Version};
+
+    fn hook(script: &str) -> crate::Result<(gix_testtools::tempfile::TempDir, Hook)> {
+        let dir = gix_testtools::tempfile::tempdir()?;
+        let path = dir.path().join("fsmonitor-hook");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}"))?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        let hook = Hook::new(&path, dir.path(), None);
+        Ok((dir, hook))
+    }
+
+    #[test]
+    fn version_2_is_preferred() -> crate::Result {
+        let (_dir, mut hook) = hook(
+            r#"test "$1" = 2 || exit 1
+printf 'new-token\0a\0dir/b\0' && printf "$2" > last-token"#,
+        )?;
+        let res = hook.query_changes(Some(&Token::V2 { token: "old".into() }))?;
+        assert_eq!(hook.version(), Some(Version::V2));
+        assert_eq!(
+            res.token,
+            Token::V2 {
+                token: "new-token".into()
+            }
+        );
+        assert_eq!(res.changes, Changes::Paths(vec!["a".into(), "dir/b".into()]));
+        assert_eq!(std::fs::read(_dir.path().join("last-token"))?, b"old");
+
+        let res = hook.query_changes(None)?;
+        assert_eq!(
+            res.changes,
+            Changes::All,
+            "the first query always invalidates everything"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn version_1_is_used_as_fallback() -> crate::Result {
+        let (_dir, mut hook) = hook(r#"test "$1" = 1 && test "$2" = 42 && printf 'a\0'"#)?;
+        let res = hook.query_changes(Some(&Token::V1 { nanos_since_1970: 42 }))?;
+        assert_eq!(hook.version(), Some(Version::V1));
+        assert!(
+            matches!(res.token, Token::V1 { nanos_since_1970 } if nanos_since_1970 > 42),
+            "the token is the time of the query"
+        );
+        assert_eq!(res.changes, Changes::Paths(vec!["a".into()]));
+        Ok(())
+    }
+
+    #[test]
+    fn trivial_responses_invalidate_everything() -> crate::Result {
+        let (_dir, mut hook) = hook(r#"printf 'token\0/\0'"#)?;
+        let res = hook.query_changes(Some(&Token::V2 { token: "old".into() }))?;
+        assert_eq!(res.changes, Changes::All);
+        Ok(())
+    }
+
+    #[test]
+    fn failures_are_errors() -> crate::Result {
+        let (_dir, mut hook) = hook("exit 1")?;
+        let err = hook
+            .query_changes(Some(&Token::V2 { token: "old".into() }))
+            .unwrap_err();
+        assert!(err.to_string().contains("failed with exit status: 1"), "{err}");
+        Ok(())
+    }
+}
@@ -80,6 +80,7 @@ fn changed_and_untracked_and_renamed() {
         percentage: Some(0.3),
         limit: 0,
         track_empty: true,
+        matching: Default::default(),
     };
     let out = fixture_filtered_detailed(
         "status_many.sh",
@@ -331,6 +332,7 @@ fn fixture_filtered_detailed(
             git_dir_realpath: &git_dir_real,
             current_dir: &cwd,
             ignore_case_index_lookup: None,
+            untracked_cache: None,
         },
     };
     let options = Options {
@@ -6,6 +6,7 @@ pub use gix_testtools::{
     Result, scripted_fixture_read_only, scripted_fixture_writable, scripted_fixture_writable_with_args_single_archive,
 };
 
+mod fsmonitor;
 mod index_as_worktree;
 #[cfg(feature = "worktree-rewrites")]
 mod index_as_worktree_with_renames;
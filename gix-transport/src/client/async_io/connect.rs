@@ -1,13 +1,20 @@
 pub use crate::client::non_io_types::connect::{Error, Options};
 
-#[cfg(feature = "async-std")]
+#[cfg(feature = "async-client-connect")]
 pub(crate) mod function {
-    use crate::client::{async_io::Transport, git::async_io::Connection, non_io_types::connect::Error};
+    use crate::client::{async_io::Transport, non_io_types::connect::Error};
 
     /// A general purpose connector connecting to a repository identified by the given `url`.
     ///
     /// This includes connections to
-    /// [git daemons][crate::client::git::connect()] only at the moment.
+    /// [local repositories](crate::client::async_io::file::connect()),
+    /// [repositories over ssh](crate::client::async_io::ssh::connect()),
+    /// and [git daemons](crate::client::git::async_io::connect()).
+    /// As there is no HTTP client built in, `http` and `https` URLs are unsupported here, but can be connected to
+    /// with [`http::connect_http()`](crate::client::async_io::http::connect_http()) and an HTTP implementation of choice.
+    ///
+    /// None of the transports depend on a particular async runtime, unless the `async-std` feature is enabled
+    /// which makes git daemons [connect with `async-std`](crate::client::git::async_io::Connection::new_tcp()).
     ///
     /// Use `options` to further control specifics of the transport resulting from the connection.
     pub async fn connect<Url, E>(url: Url, options: super::Options) -> Result<Box<dyn Transport + Send>, Error>
@@ -17,6 +24,22 @@ pub(crate) mod function {
     {
         let mut url = url.try_into().map_err(gix_url::parse::Error::from)?;
         Ok(match url.scheme {
+            gix_url::Scheme::File => {
+                if url.user().is_some() || url.password().is_some() || url.host().is_some() || url.port.is_some() {
+                    return Err(Error::UnsupportedUrlTokens {
+                        url: url.to_bstring(),
+                        scheme: url.scheme,
+                    });
+                }
+                Box::new(
+                    crate::client::async_io::file::connect(url.path, options.version, options.trace)
+                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
+                )
+            }
+            gix_url::Scheme::Ssh => Box::new({
+                crate::client::async_io::ssh::connect(url, options.version, options.ssh, options.trace)
+                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?
+            }),
             gix_url::Scheme::Git => {
                 if url.user().is_some() {
                     return Err(Error::UnsupportedUrlTokens {
@@ -25,22 +48,32 @@ pub(crate) mod function {
                     });
                 }
                 let path = std::mem::take(&mut url.path);
-                Box::new(
-                    Connection::new_tcp(
-                        url.host().expect("host is present in url"),
-                        url.port,
-                        path,
-                        options.version,
-                        options.trace,
-                    )
-                    .await
-                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
+                #[cfg(feature = "async-std")]
+                let connection = crate::client::git::async_io::Connection::new_tcp(
+                    url.host().expect("host is present in url"),
+                    url.port,
+                    path,
+                    options.version,
+                    options.trace,
+                )
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+                #[cfg(not(feature = "async-std"))]
+                let connection = crate::client::git::async_io::connect(
+                    url.host().expect("host is present in url"),
+                    path,
+                    options.version,
+                    url.port,
+                    options.trace,
                 )
+                .await
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+                Box::new(connection)
             }
             scheme => return Err(Error::UnsupportedScheme(scheme)),
         })
     }
 }
 
-#[cfg(feature = "async-std")]
+#[cfg(feature = "async-client-connect")]
 pub use function::connect;
@@ -0,0 +1,160 @@
+use std::{any::Any, borrow::Cow, error::Error, ffi::OsString, process};
+
+use async_trait::async_trait;
+use blocking::Unblock;
+use bstr::{BStr, BString};
+
+use crate::{
+    Protocol, Service,
+    client::{
+        self, MessageKind, WriteMode,
+        async_io::{RequestWriter, SetServiceResponse, ssh},
+        git::{self, async_io::Connection},
+        spawn::{self, Invocation, Spawned},
+    },
+};
+
+type ProcessConnection = Connection<Unblock<Box<dyn std::io::Read + Send>>, Unblock<process::ChildStdin>>;
+
+/// A utility to spawn a helper process to actually transmit data, possibly over `ssh`.
+///
+/// The pipes of the spawned process are read from and written to on a thread-pool, which makes this transport
+/// independent of the async runtime that drives it.
+///
+/// It can only be instantiated using the local [`connect()`] or [ssh connect][super::ssh::connect()].
+pub struct SpawnProcessOnDemand {
+    invocation: Option<Invocation>,
+    connection: Option<ProcessConnection>,
+    child: Option<process::Child>,
+    url: BString,
+    trace: bool,
+}
+
+impl SpawnProcessOnDemand {
+    pub(crate) fn new_ssh(
+        url: gix_url::Url,
+        program: impl Into<OsString>,
+        path: BString,
+        ssh_kind: ssh::ProgramKind,
+        ssh_disallow_shell: bool,
+        version: Protocol,
+        trace: bool,
+    ) -> SpawnProcessOnDemand {
+        Self::new(
+            Invocation::new_ssh(url, program, path, ssh_kind, ssh_disallow_shell, version),
+            trace,
+        )
+    }
+
+    fn new_local(path: BString, version: Protocol, trace: bool) -> SpawnProcessOnDemand {
+        Self::new(Invocation::new_local(path, version), trace)
+    }
+
+    fn new(invocation: Invocation, trace: bool) -> SpawnProcessOnDemand {
+        SpawnProcessOnDemand {
+            url: invocation.url.to_bstring(),
+            invocation: Some(invocation),
+            child: None,
+            connection: None,
+            trace,
+        }
+    }
+
+    fn take_invocation(&mut self) -> Result<Invocation, client::Error> {
+        self.invocation.take().ok_or_else(|| {
+            client::Error::Io(std::io::Error::other(
+                "a previous attempt to spawn the process was cancelled",
+            ))
+        })
+    }
+}
+
+impl client::TransportWithoutIO for SpawnProcessOnDemand {
+    fn set_identity(&mut self, identity: gix_sec::identity::Account) -> Result<(), client::Error> {
+        let mut invocation = self.take_invocation()?;
+        let res = invocation.set_identity(identity);
+        self.url = invocation.url.to_bstring();
+        self.invocation = Some(invocation);
+        res
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Borrowed(self.url.as_ref())
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        true
+    }
+
+    fn configure(&mut self, _config: &dyn Any) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        Ok(())
+    }
+}
+
+impl Drop for SpawnProcessOnDemand {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.take() {
+            spawn::kill(child);
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl client::async_io::Transport for SpawnProcessOnDemand {
+    async fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        // Spawning may have to find the program to run by invoking `git` itself, so it's done on the thread-pool as well.
+        let mut invocation = self.take_invocation()?;
+        let (invocation, res) = blocking::unblock(move || {
+            let res = invocation.spawn(service);
+            (invocation, res)
+        })
+        .await;
+        let Spawned { child, stdout, stdin } = res?;
+        self.connection = Some(Connection::new(
+            Unblock::new(stdout),
+            Unblock::new(stdin),
+            invocation.desired_version,
+            invocation.path.clone(),
+            None::<(&str, _)>,
+            git::ConnectMode::Process,
+            self.trace,
+        ));
+        self.invocation = Some(invocation);
+        if let Some(previous) = self.child.replace(child) {
+            spawn::kill(previous);
+        }
+        self.connection
+            .as_mut()
+            .expect("connection to be there right after setting it")
+            .handshake(service, extra_parameters)
+            .await
+    }
+
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        self.connection
+            .as_mut()
+            .ok_or(client::Error::MissingHandshake)?
+            .request(write_mode, on_into_read, trace)
+    }
+}
+
+/// Connect to a locally readable repository at `path` using the given `desired_version`.
+/// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+///
+/// This will spawn a `git` process locally once the handshake is performed.
+pub fn connect(
+    path: impl Into<BString>,
+    desired_version: Protocol,
+    trace: bool,
+) -> Result<SpawnProcessOnDemand, std::convert::Infallible> {
+    Ok(SpawnProcessOnDemand::new_local(path.into(), desired_version, trace))
+}
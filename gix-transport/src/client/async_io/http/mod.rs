@@ -0,0 +1,354 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    io,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use async_trait::async_trait;
+use base64::Engine;
+use bstr::{BStr, ByteSlice};
+use futures_io::{AsyncBufRead, AsyncRead};
+use futures_lite::{AsyncReadExt, future::poll_fn};
+pub use traits::Http;
+
+pub use crate::client::http::{Error, GetResponse, PostBodyDataKind, PostResponse, redirect};
+use crate::{
+    Protocol, Service,
+    client::{
+        self, MessageKind,
+        async_io::{self, ExtendedBufRead, HandleProgress, ReadlineBufRead, RequestWriter, SetServiceResponse},
+        capabilities::async_recv::Handshake,
+        http::{append_url, git_protocol_header},
+    },
+    packetline::{PacketLineRef, async_io::StreamingPeekableIter},
+};
+
+mod traits;
+
+/// A transport for supporting arbitrary async http clients by abstracting interactions with them into the [Http] trait,
+/// using the `stateless-rpc` mode of the smart-HTTP protocol for protocol versions 1 and 2.
+pub struct Transport<H: Http> {
+    url: String,
+    user_agent_header: &'static str,
+    desired_version: Protocol,
+    actual_version: Protocol,
+    http: H,
+    service: Option<Service>,
+    line_provider: Option<StreamingPeekableIter<H::ResponseBody>>,
+    identity: Option<gix_sec::identity::Account>,
+    trace: bool,
+}
+
+impl<H: Http> Transport<H> {
+    /// Create a new instance with `http` as implementation to communicate to `url` using the given `desired_version`.
+    /// Note that we will always fallback to other versions as supported by the server.
+    /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    pub fn new_http(http: H, url: gix_url::Url, desired_version: Protocol, trace: bool) -> Self {
+        let identity = url
+            .user()
+            .zip(url.password())
+            .map(|(user, pass)| gix_sec::identity::Account {
+                username: user.to_string(),
+                password: pass.to_string(),
+                oauth_refresh_token: None,
+            });
+        Transport {
+            url: url.to_bstring().to_string(),
+            user_agent_header: concat!("User-Agent: git/oxide-", env!("CARGO_PKG_VERSION")),
+            desired_version,
+            actual_version: Default::default(),
+            service: None,
+            http,
+            line_provider: None,
+            identity,
+            trace,
+        }
+    }
+
+    /// Returns the identity that the transport uses when connecting to the remote.
+    pub fn identity(&self) -> Option<&gix_sec::identity::Account> {
+        self.identity.as_ref()
+    }
+
+    /// Update our url with the backend's accepted redirect target, clearing credentials if the
+    /// redirected authority must not reuse the original identity.
+    fn sync_redirected_base_url(&mut self) {
+        let Some(redirected_url) = self.http.redirected_base_url() else {
+            return;
+        };
+        if redirected_url == self.url {
+            return;
+        }
+
+        if !redirect::can_reuse_identity(&redirected_url, &self.url) {
+            self.identity = None;
+        }
+        self.url = redirected_url;
+    }
+
+    fn add_basic_auth_if_present(&self, headers: &mut Vec<String>) -> Result<(), client::Error> {
+        if let Some(gix_sec::identity::Account {
+            username,
+            password,
+            oauth_refresh_token: _,
+        }) = &self.identity
+        {
+            #[cfg(not(feature = "http-client-insecure-credentials"))]
+            if self.url.starts_with("http://") {
+                return Err(client::Error::AuthenticationRefused(
+                    "Will not send credentials in clear text over http",
+                ));
+            }
+            headers.push(format!(
+                "Authorization: Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"))
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn check_content_type(service: Service, kind: &str, headers: &[u8]) -> Result<(), client::Error> {
+    let wanted_content_type = format!("application/x-{}-{}", service.as_str(), kind);
+    if !headers.lines().any(|l| {
+        let mut tokens = l.split_str(":");
+        tokens.next().zip(tokens.next()).is_some_and(|(name, value)| {
+            name.eq_ignore_ascii_case(b"content-type") && value.trim() == wanted_content_type.as_bytes()
+        })
+    }) {
+        return Err(client::Error::Http(Error::Detail {
+            description: format!(
+                "Didn't find '{wanted_content_type}' header to indicate 'smart' protocol, and 'dumb' protocol is not supported."
+            ),
+        }));
+    }
+    Ok(())
+}
+
+impl<H: Http> client::TransportWithoutIO for Transport<H> {
+    fn set_identity(&mut self, identity: gix_sec::identity::Account) -> Result<(), client::Error> {
+        self.identity = Some(identity);
+        Ok(())
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Borrowed(self.url.as_str().into())
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        false
+    }
+
+    fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.http.configure(config)
+    }
+}
+
+#[async_trait(?Send)]
+impl<H: Http> async_io::Transport for Transport<H> {
+    async fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        let url = append_url(self.url.as_ref(), &format!("info/refs?service={}", service.as_str()));
+        let mut headers = vec![self.user_agent_header.to_owned()];
+        headers.extend(git_protocol_header(self.desired_version, extra_parameters));
+        self.add_basic_auth_if_present(&mut headers)?;
+        let res = self.http.get(url.as_ref(), &self.url, headers).await;
+        self.sync_redirected_base_url();
+        let GetResponse {
+            headers: mut response_headers,
+            mut body,
+        } = res?;
+        let mut header_bytes = Vec::new();
+        response_headers.read_to_end(&mut header_bytes).await?;
+        if let Err(err) = check_content_type(service, "advertisement", &header_bytes) {
+            const MAX_ERROR_BODY_DRAIN_BYTES: u64 = 1024 * 1024;
+            futures_lite::io::copy((&mut body).take(MAX_ERROR_BODY_DRAIN_BYTES), futures_lite::io::sink())
+                .await
+                .ok();
+            return Err(err);
+        }
+
+        let line_reader = self
+            .line_provider
+            .get_or_insert_with(|| StreamingPeekableIter::new(body, &[PacketLineRef::Flush], self.trace));
+
+        // the service announcement is only sent sometimes depending on the exact server/protocol version/used protocol (http?)
+        // eat the announcement when its there to avoid errors later (and check that the correct service was announced).
+        // Ignore the announcement otherwise.
+        let line_ = line_reader
+            .peek_line()
+            .await
+            .ok_or(client::Error::ExpectedLine("capabilities, version or service"))???;
+        let line = line_.as_text().ok_or(client::Error::ExpectedLine("text"))?;
+
+        if let Some(announced_service) = line.as_bstr().strip_prefix(b"# service=") {
+            if announced_service != service.as_str().as_bytes() {
+                return Err(client::Error::Http(Error::Detail {
+                    description: format!(
+                        "Expected to see service {:?}, but got {:?}",
+                        service.as_str(),
+                        announced_service
+                    ),
+                }));
+            }
+
+            line_reader.as_read().read_to_end(&mut Vec::new()).await?;
+        }
+
+        let Handshake {
+            capabilities,
+            refs,
+            protocol: actual_protocol,
+        } = Handshake::from_lines_with_version_detection(line_reader).await?;
+        self.actual_version = actual_protocol;
+        self.service = Some(service);
+        Ok(SetServiceResponse {
+            actual_protocol,
+            capabilities,
+            refs,
+        })
+    }
+
+    fn request(
+        &mut self,
+        write_mode: client::WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        let service = self.service.ok_or(client::Error::MissingHandshake)?;
+        let url = append_url(&self.url, service.as_str());
+        let mut headers = vec![
+            self.user_agent_header.to_owned(),
+            format!("Content-Type: application/x-{}-request", service.as_str()),
+            format!("Accept: application/x-{}-result", service.as_str()),
+        ];
+        self.add_basic_auth_if_present(&mut headers)?;
+        if self.actual_version != Protocol::V1 {
+            headers.push(format!("Git-Protocol: version={}", self.actual_version as usize));
+        }
+
+        let res = self.http.post(&url, &self.url, headers, write_mode.into());
+        self.sync_redirected_base_url();
+        let PostResponse {
+            headers,
+            body,
+            post_body,
+        } = res?;
+        let line_provider = self
+            .line_provider
+            .as_mut()
+            .expect("handshake to have been called first");
+        line_provider.replace(body);
+        Ok(RequestWriter::new_from_bufread(
+            post_body,
+            Box::new(HeadersThenBody::<H, _> {
+                service,
+                headers: Some(headers),
+                header_bytes: Vec::new(),
+                body: line_provider.as_read_without_sidebands(),
+            }),
+            write_mode,
+            on_into_read,
+            trace,
+        ))
+    }
+}
+
+/// Read all response headers and check them before passing on the body.
+struct HeadersThenBody<H: Http, B: Unpin> {
+    service: Service,
+    headers: Option<H::Headers>,
+    header_bytes: Vec<u8>,
+    body: B,
+}
+
+impl<H: Http, B: Unpin> HeadersThenBody<H, B> {
+    fn poll_handle_headers(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(headers) = self.headers.as_mut() {
+            loop {
+                let buf = ready!(Pin::new(&mut *headers).poll_fill_buf(cx))?;
+                if buf.is_empty() {
+                    break;
+                }
+                let len = buf.len();
+                self.header_bytes.extend_from_slice(buf);
+                Pin::new(&mut *headers).consume(len);
+            }
+            self.headers = None;
+            check_content_type(self.service, "result", &self.header_bytes).map_err(io::Error::other)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    async fn handle_headers(&mut self) -> io::Result<()> {
+        poll_fn(|cx| self.poll_handle_headers(cx)).await
+    }
+}
+
+impl<H: Http, B: AsyncRead + Unpin> AsyncRead for HeadersThenBody<H, B> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_handle_headers(cx))?;
+        Pin::new(&mut this.body).poll_read(cx, buf)
+    }
+}
+
+impl<H: Http, B: AsyncBufRead + Unpin> AsyncBufRead for HeadersThenBody<H, B> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        ready!(this.poll_handle_headers(cx))?;
+        Pin::new(&mut this.body).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        Pin::new(&mut self.get_mut().body).consume(amt);
+    }
+}
+
+#[async_trait(?Send)]
+impl<H: Http, B: ReadlineBufRead + Unpin> ReadlineBufRead for HeadersThenBody<H, B> {
+    async fn readline(&mut self) -> Option<io::Result<Result<PacketLineRef<'_>, gix_packetline::decode::Error>>> {
+        if let Err(err) = self.handle_headers().await {
+            return Some(Err(err));
+        }
+        self.body.readline().await
+    }
+
+    async fn readline_str(&mut self, line: &mut String) -> io::Result<usize> {
+        self.handle_headers().await?;
+        self.body.readline_str(line).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<'a, H: Http, B: ExtendedBufRead<'a> + Unpin> ExtendedBufRead<'a> for HeadersThenBody<H, B> {
+    fn set_progress_handler(&mut self, handle_progress: Option<HandleProgress<'a>>) {
+        self.body.set_progress_handler(handle_progress);
+    }
+
+    async fn peek_data_line(&mut self) -> Option<io::Result<Result<&[u8], client::Error>>> {
+        if let Err(err) = self.handle_headers().await {
+            return Some(Err(err));
+        }
+        self.body.peek_data_line().await
+    }
+
+    fn reset(&mut self, version: Protocol) {
+        self.body.reset(version);
+    }
+
+    fn stopped_at(&self) -> Option<MessageKind> {
+        self.body.stopped_at()
+    }
+}
+
+/// Connect to the given `url` via HTTP/S using the `desired_version` of the `git` protocol, with `http` as implementation.
+/// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+pub fn connect_http<H: Http>(http: H, url: gix_url::Url, desired_version: Protocol, trace: bool) -> Transport<H> {
+    Transport::new_http(http, url, desired_version, trace)
+}
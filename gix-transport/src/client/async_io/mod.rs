@@ -9,5 +9,15 @@ pub use traits::{SetServiceResponse, Transport, TransportV2Ext};
 
 ///
 pub mod connect;
-#[cfg(feature = "async-std")]
+#[cfg(feature = "async-client-connect")]
 pub use connect::function::connect;
+
+///
+#[cfg(feature = "async-client-connect")]
+pub mod file;
+///
+pub mod http;
+
+///
+#[cfg(feature = "async-client-connect")]
+pub mod ssh;
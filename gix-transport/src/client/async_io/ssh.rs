@@ -0,0 +1,30 @@
+use gix_url::Url;
+
+pub use crate::client::ssh::{Error, ProgramKind, connect, invocation};
+use crate::{Protocol, client::async_io::file::SpawnProcessOnDemand};
+
+/// Connect to `host` using the ssh program to obtain data from the repository at `path` on the remote.
+///
+/// This works just like its blocking counterpart, but the pipes of the `ssh` program are read and written on a thread-pool to not block the async runtime.
+/// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+#[expect(
+    clippy::result_large_err,
+    reason = "will be removed once `gix-error` is used consistently"
+)]
+pub fn connect(
+    url: Url,
+    desired_version: Protocol,
+    options: connect::Options,
+    trace: bool,
+) -> Result<SpawnProcessOnDemand, Error> {
+    let (ssh_cmd, kind, path) = crate::client::ssh::prepare(&url, &options)?;
+    Ok(SpawnProcessOnDemand::new_ssh(
+        url,
+        ssh_cmd,
+        path,
+        kind,
+        options.disallow_shell,
+        desired_version,
+        trace,
+    ))
+}
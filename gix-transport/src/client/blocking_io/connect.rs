@@ -11,9 +11,11 @@ pub(crate) mod function {
     ///
     /// This includes connections to
     /// [local repositories](crate::client::blocking_io::file::connect()),
-    /// [repositories over ssh](crate::client::blocking_io::ssh::connect()),
+    /// [repositories over ssh](crate::client::blocking_io::ssh::connect()), or natively over ssh if
+    /// [configured](crate::client::ssh::connect::Options::native) and compiled in,
     /// [git daemons](crate::client::blocking_io::connect::connect()),
     /// and if compiled in connections to [git repositories over https](crate::client::blocking_io::http::connect()).
+    /// All other schemes are handled by [remote helpers](crate::client::blocking_io::helper::connect()).
     ///
     /// Use `options` to further control specifics of the transport resulting from the connection.
     pub fn connect<Url, E>(url: Url, options: super::Options) -> Result<Box<dyn Transport + Send>, Error>
@@ -23,7 +25,10 @@ pub(crate) mod function {
     {
         let mut url = url.try_into().map_err(gix_url::parse::Error::from)?;
         Ok(match url.scheme {
-            gix_url::Scheme::Ext(_) => return Err(Error::UnsupportedScheme(url.scheme)),
+            gix_url::Scheme::Ext(_) => Box::new(
+                crate::client::blocking_io::helper::connect(url, options.version, options.trace)
+                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
+            ),
             gix_url::Scheme::File => {
                 if url.user().is_some() || url.password().is_some() || url.host().is_some() || url.port.is_some() {
                     return Err(Error::UnsupportedUrlTokens {
@@ -36,6 +41,13 @@ pub(crate) mod function {
                         .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
                 )
             }
+            #[cfg(not(feature = "ssh-client-native"))]
+            gix_url::Scheme::Ssh if options.ssh.native => return Err(Error::CompiledWithoutNativeSsh),
+            #[cfg(feature = "ssh-client-native")]
+            gix_url::Scheme::Ssh if options.ssh.native => Box::new({
+                crate::client::blocking_io::ssh::native::connect(url, options.version, options.trace)
+                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?
+            }),
             gix_url::Scheme::Ssh => Box::new({
                 crate::client::blocking_io::ssh::connect(url, options.version, options.ssh, options.trace)
                     .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?
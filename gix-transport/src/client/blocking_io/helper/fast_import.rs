@@ -0,0 +1,551 @@
+//! Parse and write the streams of `git fast-import`, which remote helpers use to `import` and `export` history.
+//!
+//! Object ids, commit-ishs and signatures are kept textual just like they appear in the stream, so it's up to the consumer
+//! to resolve marks like `:1`, hexadecimal object ids or reference names.
+use std::io::{BufRead, Write};
+
+use bstr::{BString, ByteSlice, ByteVec};
+
+/// The error returned by [`Parser::next_command()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("The fast-import stream ended unexpectedly")]
+    UnexpectedEof,
+    #[error("Unexpected line in fast-import stream: {line:?}")]
+    UnexpectedLine { line: BString },
+    #[error("Could not parse the number in {line:?}")]
+    InvalidNumber { line: BString },
+    #[error("Could not unquote path {path:?}")]
+    Unquote {
+        path: BString,
+        source: gix_quote::ansi_c::undo::Error,
+    },
+}
+
+/// A single command of a fast-import stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// A blob to store, optionally with a mark for later reference.
+    Blob(Blob),
+    /// A commit to create on a branch.
+    Commit(Commit),
+    /// An annotated tag to create.
+    Tag(Tag),
+    /// Set a reference to a commit, or prepare it to be (re)created by a following commit.
+    Reset(Reset),
+    /// Write out everything imported so far.
+    Checkpoint,
+    /// A message to display as progress.
+    Progress(BString),
+    /// The end of the stream.
+    Done,
+    /// A `feature` the stream needs, like `done` or `import-marks=<file>`.
+    Feature(BString),
+    /// An `option` for the importer, like `quiet`.
+    Option(BString),
+}
+
+/// A blob to store.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Blob {
+    /// The mark to refer to the blob by.
+    pub mark: Option<u64>,
+    /// The id the blob had in the original repository.
+    pub original_oid: Option<BString>,
+    /// The content of the blob.
+    pub data: Vec<u8>,
+}
+
+/// A commit to create.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// The full name of the reference to update, like `refs/heads/main`.
+    pub ref_name: BString,
+    /// The mark to refer to the commit by.
+    pub mark: Option<u64>,
+    /// The id the commit had in the original repository.
+    pub original_oid: Option<BString>,
+    /// The author as `Name <email> <time> <tz>`, which is the committer if `None`.
+    pub author: Option<BString>,
+    /// The committer as `Name <email> <time> <tz>`.
+    pub committer: BString,
+    /// The encoding of the `message`.
+    pub encoding: Option<BString>,
+    /// The commit message.
+    pub message: Vec<u8>,
+    /// The commit-ish of the first parent, or `None` to use the commit `ref_name` currently points to.
+    pub from: Option<BString>,
+    /// The commit-ishs of all other parents.
+    pub merges: Vec<BString>,
+    /// The changes to apply to the tree of the first parent, in order.
+    pub changes: Vec<FileChange>,
+}
+
+/// An annotated tag to create.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Tag {
+    /// The name of the tag, without `refs/tags/`.
+    pub name: BString,
+    /// The mark to refer to the tag by.
+    pub mark: Option<u64>,
+    /// The commit-ish the tag points to.
+    pub from: BString,
+    /// The id the tag had in the original repository.
+    pub original_oid: Option<BString>,
+    /// The tagger as `Name <email> <time> <tz>`.
+    pub tagger: Option<BString>,
+    /// The tag message.
+    pub message: Vec<u8>,
+}
+
+/// A reference to set or to prepare for being recreated.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Reset {
+    /// The full name of the reference, like `refs/heads/main`.
+    pub ref_name: BString,
+    /// The commit-ish to point the reference to, or `None` to let the next commit to it start without parent.
+    pub from: Option<BString>,
+}
+
+/// A change to the tree of a [`Commit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    /// Add or change the entry at `path`.
+    Modify {
+        /// The octal mode, like `100644`, `100755`, `120000`, `160000` or `040000`.
+        mode: BString,
+        /// The object the entry points to.
+        data: DataRef,
+        /// The path of the entry.
+        path: BString,
+    },
+    /// Remove the entry at `path`.
+    Delete {
+        /// The path of the entry.
+        path: BString,
+    },
+    /// Copy the entry at `source` to `destination`.
+    Copy {
+        /// The path of the entry to copy.
+        source: BString,
+        /// The path of the copy.
+        destination: BString,
+    },
+    /// Move the entry at `source` to `destination`.
+    Rename {
+        /// The path of the entry to move.
+        source: BString,
+        /// The path to move the entry to.
+        destination: BString,
+    },
+    /// Remove all entries.
+    DeleteAll,
+}
+
+/// The object of a [`FileChange::Modify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataRef {
+    /// An object previously marked with the given number.
+    Mark(u64),
+    /// An existing object, identified by its hexadecimal id.
+    Id(BString),
+    /// A blob whose content is given right away.
+    Inline(Vec<u8>),
+}
+
+/// A parser for fast-import streams, reading one [`Command`] at a time.
+///
+/// It never reads past the end of a command, so it may be used on streams that continue with other data after [`Command::Done`].
+pub struct Parser<R> {
+    read: R,
+    peeked: Option<BString>,
+}
+
+impl<R: BufRead> Parser<R> {
+    /// Create a new instance reading from `read`.
+    pub fn new(read: R) -> Self {
+        Parser { read, peeked: None }
+    }
+
+    /// Return the next command, or `None` if the stream ended without [`Command::Done`].
+    pub fn next_command(&mut self) -> Result<Option<Command>, Error> {
+        let line = loop {
+            match self.next_line()? {
+                None => return Ok(None),
+                Some(line) if line.is_empty() || line.starts_with(b"#") => continue,
+                Some(line) => break line,
+            }
+        };
+        let (name, rest) = line.split_once_str(b" ").unwrap_or((line.as_slice(), b""));
+        Ok(Some(match name {
+            b"blob" => {
+                let mut blob = Blob::default();
+                loop {
+                    let line = self.required_line()?;
+                    if let Some(mark) = line.strip_prefix(b"mark ") {
+                        blob.mark = Some(parse_mark(mark, &line)?);
+                    } else if let Some(oid) = line.strip_prefix(b"original-oid ") {
+                        blob.original_oid = Some(oid.into());
+                    } else {
+                        blob.data = self.data(&line)?;
+                        break;
+                    }
+                }
+                Command::Blob(blob)
+            }
+            b"commit" => {
+                let mut commit = Commit {
+                    ref_name: rest.into(),
+                    ..Default::default()
+                };
+                loop {
+                    let line = self.required_line()?;
+                    if let Some(mark) = line.strip_prefix(b"mark ") {
+                        commit.mark = Some(parse_mark(mark, &line)?);
+                    } else if let Some(oid) = line.strip_prefix(b"original-oid ") {
+                        commit.original_oid = Some(oid.into());
+                    } else if let Some(author) = line.strip_prefix(b"author ") {
+                        commit.author = Some(author.into());
+                    } else if let Some(committer) = line.strip_prefix(b"committer ") {
+                        commit.committer = committer.into();
+                    } else if let Some(encoding) = line.strip_prefix(b"encoding ") {
+                        commit.encoding = Some(encoding.into());
+                    } else {
+                        commit.message = self.data(&line)?;
+                        break;
+                    }
+                }
+                while let Some(line) = self.next_line()? {
+                    if let Some(from) = line.strip_prefix(b"from ") {
+                        commit.from = Some(from.into());
+                    } else if let Some(merge) = line.strip_prefix(b"merge ") {
+                        commit.merges.push(merge.into());
+                    } else if line.is_empty() {
+                        break;
+                    } else if let Some(change) = self.file_change(&line)? {
+                        commit.changes.push(change);
+                    } else {
+                        self.peeked = Some(line);
+                        break;
+                    }
+                }
+                Command::Commit(commit)
+            }
+            b"tag" => {
+                let mut tag = Tag {
+                    name: rest.into(),
+                    ..Default::default()
+                };
+                loop {
+                    let line = self.required_line()?;
+                    if let Some(mark) = line.strip_prefix(b"mark ") {
+                        tag.mark = Some(parse_mark(mark, &line)?);
+                    } else if let Some(from) = line.strip_prefix(b"from ") {
+                        tag.from = from.into();
+                    } else if let Some(oid) = line.strip_prefix(b"original-oid ") {
+                        tag.original_oid = Some(oid.into());
+                    } else if let Some(tagger) = line.strip_prefix(b"tagger ") {
+                        tag.tagger = Some(tagger.into());
+                    } else {
+                        tag.message = self.data(&line)?;
+                        break;
+                    }
+                }
+                Command::Tag(tag)
+            }
+            b"reset" => {
+                let mut reset = Reset {
+                    ref_name: rest.into(),
+                    from: None,
+                };
+                if let Some(line) = self.next_line()? {
+                    match line.strip_prefix(b"from ") {
+                        Some(from) => reset.from = Some(from.into()),
+                        None => self.peeked = Some(line),
+                    }
+                }
+                Command::Reset(reset)
+            }
+            b"checkpoint" => Command::Checkpoint,
+            b"progress" => Command::Progress(rest.into()),
+            b"done" => Command::Done,
+            b"feature" => Command::Feature(rest.into()),
+            b"option" => Command::Option(rest.into()),
+            _ => return Err(Error::UnexpectedLine { line }),
+        }))
+    }
+
+    /// Return the reader this instance was created with.
+    pub fn into_inner(self) -> R {
+        self.read
+    }
+
+    fn next_line(&mut self) -> Result<Option<BString>, Error> {
+        if let Some(line) = self.peeked.take() {
+            return Ok(Some(line));
+        }
+        let mut line = Vec::new();
+        if self.read.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        Ok(Some(line.into()))
+    }
+
+    fn required_line(&mut self) -> Result<BString, Error> {
+        self.next_line()?.ok_or(Error::UnexpectedEof)
+    }
+
+    /// Read the content announced by `line`, which is either `data <count>` or `data <<<delimiter>`.
+    fn data(&mut self, line: &[u8]) -> Result<Vec<u8>, Error> {
+        let spec = line
+            .strip_prefix(b"data ")
+            .ok_or_else(|| Error::UnexpectedLine { line: line.into() })?;
+        let mut data = Vec::new();
+        if let Some(delimiter) = spec.strip_prefix(b"<<") {
+            loop {
+                let line = self.required_line()?;
+                if line == delimiter {
+                    break;
+                }
+                data.extend_from_slice(&line);
+                data.push(b'\n');
+            }
+        } else {
+            let count = parse_number(spec, line)?;
+            data.resize(count as usize, 0);
+            self.read.read_exact(&mut data).map_err(|err| match err.kind() {
+                std::io::ErrorKind::UnexpectedEof => Error::UnexpectedEof,
+                _ => err.into(),
+            })?;
+            // The newline after the data is optional.
+            if self.read.fill_buf()?.first() == Some(&b'\n') {
+                self.read.consume(1);
+            }
+        }
+        Ok(data)
+    }
+
+    fn file_change(&mut self, line: &BString) -> Result<Option<FileChange>, Error> {
+        Ok(Some(if let Some(rest) = line.strip_prefix(b"M ") {
+            let mut fields = rest.splitn(3, |b| *b == b' ');
+            let (Some(mode), Some(data), Some(path)) = (fields.next(), fields.next(), fields.next()) else {
+                return Err(Error::UnexpectedLine { line: line.clone() });
+            };
+            let data = if data == b"inline" {
+                let data_line = self.required_line()?;
+                DataRef::Inline(self.data(&data_line)?)
+            } else if let Some(mark) = data.strip_prefix(b":") {
+                DataRef::Mark(parse_number(mark, line)?)
+            } else {
+                DataRef::Id(data.into())
+            };
+            FileChange::Modify {
+                mode: mode.into(),
+                data,
+                path: unquote(path)?,
+            }
+        } else if let Some(path) = line.strip_prefix(b"D ") {
+            FileChange::Delete { path: unquote(path)? }
+        } else if let Some(rest) = line.strip_prefix(b"C ") {
+            let (source, destination) = split_paths(rest, line)?;
+            FileChange::Copy { source, destination }
+        } else if let Some(rest) = line.strip_prefix(b"R ") {
+            let (source, destination) = split_paths(rest, line)?;
+            FileChange::Rename { source, destination }
+        } else if line == "deleteall" {
+            FileChange::DeleteAll
+        } else {
+            return Ok(None);
+        }))
+    }
+}
+
+fn parse_number(number: &[u8], line: &[u8]) -> Result<u64, Error> {
+    number
+        .to_str()
+        .ok()
+        .and_then(|number| number.parse().ok())
+        .ok_or_else(|| Error::InvalidNumber { line: line.into() })
+}
+
+fn parse_mark(mark: &[u8], line: &[u8]) -> Result<u64, Error> {
+    let number = mark
+        .strip_prefix(b":")
+        .ok_or_else(|| Error::InvalidNumber { line: line.into() })?;
+    parse_number(number, line)
+}
+
+fn unquote(path: &[u8]) -> Result<BString, Error> {
+    if path.starts_with(b"\"") {
+        let (path, _consumed) = gix_quote::ansi_c::undo(path.as_bstr()).map_err(|source| Error::Unquote {
+            path: path.into(),
+            source,
+        })?;
+        Ok(path.into_owned())
+    } else {
+        Ok(path.into())
+    }
+}
+
+/// Split `<source> <destination>`, where `source` has to be quoted if it contains a space.
+fn split_paths(paths: &[u8], line: &[u8]) -> Result<(BString, BString), Error> {
+    let source_end = if paths.starts_with(b"\"") {
+        let (_path, consumed) = gix_quote::ansi_c::undo(paths.as_bstr()).map_err(|source| Error::Unquote {
+            path: paths.into(),
+            source,
+        })?;
+        consumed
+    } else {
+        paths
+            .find_byte(b' ')
+            .ok_or_else(|| Error::UnexpectedLine { line: line.into() })?
+    };
+    let destination = paths[source_end..]
+        .strip_prefix(b" ")
+        .ok_or_else(|| Error::UnexpectedLine { line: line.into() })?;
+    Ok((unquote(&paths[..source_end])?, unquote(destination)?))
+}
+
+/// Serialization
+impl Command {
+    /// Write this command to `out` in the format understood by `git fast-import`.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match self {
+            Command::Blob(blob) => {
+                out.write_all(b"blob\n")?;
+                write_mark(blob.mark, out)?;
+                write_field(b"original-oid", blob.original_oid.as_ref(), out)?;
+                write_data(&blob.data, out)
+            }
+            Command::Commit(commit) => {
+                writeln!(out, "commit {}", commit.ref_name)?;
+                write_mark(commit.mark, out)?;
+                write_field(b"original-oid", commit.original_oid.as_ref(), out)?;
+                write_field(b"author", commit.author.as_ref(), out)?;
+                write_field(b"committer", Some(&commit.committer), out)?;
+                write_field(b"encoding", commit.encoding.as_ref(), out)?;
+                write_data(&commit.message, out)?;
+                write_field(b"from", commit.from.as_ref(), out)?;
+                for merge in &commit.merges {
+                    write_field(b"merge", Some(merge), out)?;
+                }
+                for change in &commit.changes {
+                    change.write_to(out)?;
+                }
+                out.write_all(b"\n")
+            }
+            Command::Tag(tag) => {
+                writeln!(out, "tag {}", tag.name)?;
+                write_mark(tag.mark, out)?;
+                write_field(b"from", Some(&tag.from), out)?;
+                write_field(b"original-oid", tag.original_oid.as_ref(), out)?;
+                write_field(b"tagger", tag.tagger.as_ref(), out)?;
+                write_data(&tag.message, out)
+            }
+            Command::Reset(reset) => {
+                writeln!(out, "reset {}", reset.ref_name)?;
+                write_field(b"from", reset.from.as_ref(), out)?;
+                out.write_all(b"\n")
+            }
+            Command::Checkpoint => out.write_all(b"checkpoint\n\n"),
+            Command::Progress(message) => writeln!(out, "progress {message}\n"),
+            Command::Done => out.write_all(b"done\n"),
+            Command::Feature(feature) => writeln!(out, "feature {feature}"),
+            Command::Option(option) => writeln!(out, "option {option}"),
+        }
+    }
+}
+
+impl FileChange {
+    /// Write this change to `out` as part of a commit.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        match self {
+            FileChange::Modify { mode, data, path } => {
+                write!(out, "M {mode} ")?;
+                match data {
+                    DataRef::Mark(mark) => write!(out, ":{mark} ")?,
+                    DataRef::Id(id) => write!(out, "{id} ")?,
+                    DataRef::Inline(_) => out.write_all(b"inline ")?,
+                }
+                write_path(path, false, out)?;
+                out.write_all(b"\n")?;
+                if let DataRef::Inline(data) = data {
+                    write_data(data, out)?;
+                }
+                Ok(())
+            }
+            FileChange::Delete { path } => {
+                out.write_all(b"D ")?;
+                write_path(path, false, out)?;
+                out.write_all(b"\n")
+            }
+            FileChange::Copy { source, destination } | FileChange::Rename { source, destination } => {
+                out.write_all(if matches!(self, FileChange::Copy { .. }) {
+                    b"C "
+                } else {
+                    b"R "
+                })?;
+                write_path(source, true, out)?;
+                out.write_all(b" ")?;
+                write_path(destination, false, out)?;
+                out.write_all(b"\n")
+            }
+            FileChange::DeleteAll => out.write_all(b"deleteall\n"),
+        }
+    }
+}
+
+fn write_mark(mark: Option<u64>, out: &mut dyn Write) -> std::io::Result<()> {
+    match mark {
+        Some(mark) => writeln!(out, "mark :{mark}"),
+        None => Ok(()),
+    }
+}
+
+fn write_field(name: &[u8], value: Option<&BString>, out: &mut dyn Write) -> std::io::Result<()> {
+    match value {
+        Some(value) => {
+            out.write_all(name)?;
+            out.write_all(b" ")?;
+            out.write_all(value)?;
+            out.write_all(b"\n")
+        }
+        None => Ok(()),
+    }
+}
+
+fn write_data(data: &[u8], out: &mut dyn Write) -> std::io::Result<()> {
+    writeln!(out, "data {}", data.len())?;
+    out.write_all(data)?;
+    out.write_all(b"\n")
+}
+
+/// Write `path`, quoting it if it would otherwise be misread, which includes spaces if `is_source` is `true`.
+fn write_path(path: &[u8], is_source: bool, out: &mut dyn Write) -> std::io::Result<()> {
+    let needs_quoting = path.starts_with(b"\"")
+        || path
+            .iter()
+            .any(|b| matches!(b, b'\n' | b'\\' | b'"') || (is_source && *b == b' '));
+    if !needs_quoting {
+        return out.write_all(path);
+    }
+    let mut quoted = BString::from("\"");
+    for b in path {
+        match b {
+            b'\n' => quoted.push_str(b"\\n"),
+            b'\\' | b'"' => {
+                quoted.push(b'\\');
+                quoted.push(*b);
+            }
+            b => quoted.push(*b),
+        }
+    }
+    quoted.push(b'"');
+    out.write_all(&quoted)
+}
@@ -0,0 +1,212 @@
+//! Talk to remote helpers, the `git-remote-<transport>` programs that `git` uses for URLs like `<transport>::<address>`
+//! or `<transport>://<address>` with a scheme it doesn't know natively.
+//!
+//! [`Helper`] speaks the remote-helper protocol with such a program, while [`RemoteHelper`] is a [`Transport`](crate::client::blocking_io::Transport)
+//! that uses it to fetch. If the helper can `connect` or `stateless-connect` to `git-upload-pack`, the packetline protocol is spoken
+//! through it directly. Otherwise, the helper is asked to `fetch` or `import` the references that are wanted, and as the objects
+//! end up in the local repository that way, an empty pack is received by the caller.
+//! `import` needs an [`Import`] implementation to apply the fast-import stream of the helper, configured via [`Options`].
+use std::{
+    ffi::OsString,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::Service;
+
+///
+pub mod fast_import;
+
+mod process;
+pub use process::{Export, FetchOutcome, Helper, List, PushStatus, Ref, Value};
+
+mod transport;
+pub use transport::RemoteHelper;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The error used by [`Helper`] and [`RemoteHelper`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not find remote helper 'git-remote-{transport}' in Git's exec-path or on the PATH")]
+    NotFound { transport: String },
+    #[error("Could not spawn the remote helper {program:?}")]
+    Spawn { program: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("The remote helper requires the unsupported capability '{capability}'")]
+    UnsupportedCapability { capability: BString },
+    #[error("The remote helper doesn't support the '{capability}' capability")]
+    MissingCapability { capability: &'static str },
+    #[error("The remote helper neither supports fetching nor connecting to '{}'", service.as_str())]
+    UnsupportedService { service: Service },
+    #[error("The remote helper responded with {line:?} to '{command}'")]
+    UnexpectedResponse { command: &'static str, line: BString },
+    #[error("The remote helper could not set option '{name}': {message}")]
+    Option { name: BString, message: BString },
+    #[error("The object id of reference '{name}' is unknown as the remote helper can't import it")]
+    UnknownValue { name: BString },
+    #[error("The remote helper imports history, but no importer was configured")]
+    MissingImporter,
+    #[error("Could not apply the fast-import stream of the remote helper")]
+    Import(#[source] BoxError),
+    #[error("The object {id} wasn't advertised by the remote helper")]
+    UnadvertisedObject { id: BString },
+    #[error("The argument '{argument}' isn't supported when fetching via remote helpers")]
+    UnsupportedArgument { argument: BString },
+}
+
+/// The capabilities a remote helper advertised in response to `capabilities`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    lines: Vec<BString>,
+}
+
+impl Capabilities {
+    /// Create a new instance from the lines sent by the helper, where mandatory capabilities are prefixed with `*`.
+    pub fn from_lines(lines: impl IntoIterator<Item = BString>) -> Result<Self, Error> {
+        let mut out = Vec::new();
+        for line in lines {
+            let line = match line.strip_prefix(b"*") {
+                Some(mandatory) => {
+                    let name = mandatory.split_str(" ").next().unwrap_or_default();
+                    if !KNOWN_CAPABILITIES.iter().any(|known| known.as_bytes() == name) {
+                        return Err(Error::UnsupportedCapability {
+                            capability: mandatory.into(),
+                        });
+                    }
+                    mandatory.into()
+                }
+                None => line,
+            };
+            out.push(line);
+        }
+        Ok(Capabilities { lines: out })
+    }
+
+    /// Return `true` if the capability `name` was advertised.
+    pub fn contains(&self, name: &str) -> bool {
+        self.lines.iter().any(|line| line == name.as_bytes())
+    }
+
+    /// Return the values of all capabilities with a value, like `refspec <value>` or `import-marks <value>`.
+    pub fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a BStr> + 'a {
+        self.lines.iter().filter_map(move |line| {
+            line.strip_prefix(name.as_bytes())
+                .and_then(|rest| rest.strip_prefix(b" "))
+                .map(ByteSlice::as_bstr)
+        })
+    }
+
+    /// Return the name the reference `name` of the remote is imported to according to the `refspec` capabilities,
+    /// or `name` itself if none of them matches.
+    pub fn private_ref_name(&self, name: &BStr) -> BString {
+        self.values("refspec")
+            .filter_map(|spec| {
+                let spec = spec.strip_prefix(b"+").unwrap_or(spec);
+                let (source, destination) = spec.split_once_str(":")?;
+                match source.find_byte(b'*') {
+                    Some(pos) => {
+                        let (prefix, suffix) = (&source[..pos], &source[pos + 1..]);
+                        let matched = name.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                        Some(destination.replacen("*", matched, 1).into())
+                    }
+                    None => (source == name.as_bytes()).then(|| destination.into()),
+                }
+            })
+            .next()
+            .unwrap_or_else(|| name.to_owned())
+    }
+}
+
+/// All capabilities this implementation understands, which may be marked mandatory by helpers.
+const KNOWN_CAPABILITIES: &[&str] = &[
+    "import",
+    "export",
+    "push",
+    "fetch",
+    "connect",
+    "stateless-connect",
+    "option",
+    "check-connectivity",
+    "object-format",
+    "no-private-update",
+    "progress",
+    "bidi-import",
+    "signed-tags",
+    "refspec",
+    "import-marks",
+    "export-marks",
+];
+
+/// Apply the fast-import streams of remote helpers that only support `import`, like `git fast-import` would.
+pub trait Import {
+    /// Read all [commands](fast_import::Command) from `stream` until [`Done`](fast_import::Command::Done) and apply them,
+    /// then return the ids that the references in `ref_names` point to in the same order, or `None` if they don't exist.
+    ///
+    /// `capabilities` are those of the helper, which include `import-marks` and `export-marks`.
+    fn import(
+        &mut self,
+        stream: &mut dyn std::io::BufRead,
+        capabilities: &Capabilities,
+        ref_names: &[BString],
+    ) -> Result<Vec<Option<BString>>, BoxError>;
+}
+
+/// Options for [`RemoteHelper`], passed via [`configure()`](crate::client::TransportWithoutIO::configure()).
+#[derive(Default, Clone)]
+pub struct Options {
+    /// The name of the remote, passed as first argument to the helper, or `None` to pass the URL instead.
+    pub remote_name: Option<BString>,
+    /// The repository to fetch into, passed to the helper as `GIT_DIR`.
+    pub git_dir: Option<PathBuf>,
+    /// Pairs of `option <name> <value>` to send to helpers that support the `option` capability, like `("verbosity", "0")`.
+    pub options: Vec<(BString, BString)>,
+    /// The implementation to apply fast-import streams with, needed for helpers that only support `import`.
+    pub import: Option<Arc<Mutex<dyn Import + Send>>>,
+}
+
+impl std::fmt::Debug for Options {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Options")
+            .field("remote_name", &self.remote_name)
+            .field("git_dir", &self.git_dir)
+            .field("options", &self.options)
+            .field("import", &self.import.as_ref().map(|_| "<importer>"))
+            .finish()
+    }
+}
+
+/// Find the program implementing the remote helper for `transport`, which is `git-remote-<transport>` in Git's
+/// exec-path or on the `PATH`, in that order.
+pub fn find(transport: &str) -> Option<PathBuf> {
+    let name = format!("git-remote-{transport}");
+    gix_path::env::core_dir_program(&name).or_else(|| {
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path).find_map(|dir| {
+            [
+                OsString::from(format!("{name}{}", std::env::consts::EXE_SUFFIX)),
+                OsString::from(&name),
+            ]
+            .into_iter()
+            .map(|file_name| dir.join(file_name))
+            .find(|candidate| candidate.is_file())
+        })
+    })
+}
+
+/// Connect to `url` using the remote helper for its [`Ext`](gix_url::Scheme::Ext) scheme, with `desired_version` of the protocol
+/// to use if the helper can connect to `git-upload-pack`.
+/// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+///
+/// The helper is spawned on [handshake](crate::client::blocking_io::Transport::handshake()).
+pub fn connect(url: gix_url::Url, desired_version: crate::Protocol, trace: bool) -> Result<RemoteHelper, Error> {
+    let transport = url.scheme.as_str();
+    let program = find(transport).ok_or_else(|| Error::NotFound {
+        transport: transport.to_owned(),
+    })?;
+    Ok(RemoteHelper::new(program, url, desired_version, trace))
+}
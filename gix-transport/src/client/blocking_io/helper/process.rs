@@ -0,0 +1,385 @@
+use std::{
+    ffi::OsStr,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use super::{Capabilities, Error};
+use crate::Service;
+
+/// A running remote helper, which received the `capabilities` command already.
+///
+/// Commands are sent in the order the methods are called, so they must be called in an order the helper accepts.
+/// The helper is asked to shut down when this instance is dropped.
+pub struct Helper {
+    program: PathBuf,
+    child: Option<Child>,
+    stdin: Option<ChildStdin>,
+    stdout: Option<BufReader<ChildStdout>>,
+    capabilities: Capabilities,
+    trace: bool,
+}
+
+/// The references a remote helper returned for `list`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct List {
+    /// All references in the order they were listed.
+    pub refs: Vec<Ref>,
+    /// The object format of all ids, if the helper stated it.
+    pub object_format: Option<BString>,
+}
+
+/// A reference as listed by a remote helper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ref {
+    /// The full name of the reference, like `refs/heads/main` or `HEAD`.
+    pub name: BString,
+    /// The value of the reference.
+    pub value: Value,
+    /// Additional attributes, like `unchanged`.
+    pub attributes: Vec<BString>,
+}
+
+/// The value of a [`Ref`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// The hexadecimal id of the object the reference points to.
+    Id(BString),
+    /// The name of the reference this one points to.
+    Symbolic(BString),
+    /// The helper doesn't know the value before the reference is imported.
+    Unknown,
+}
+
+/// The result of [`Helper::fetch()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FetchOutcome {
+    /// Files that keep the fetched pack from being garbage collected, which should be deleted once references are updated.
+    pub lock_files: Vec<PathBuf>,
+    /// If `true`, the helper guarantees that all objects reachable from the fetched ones are present.
+    pub connectivity_ok: bool,
+}
+
+/// The status of a reference after [pushing](Helper::push()) or [exporting](Export::finish()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushStatus {
+    /// The name of the remote reference.
+    pub name: BString,
+    /// The reason for why the reference wasn't updated, which may be empty.
+    pub result: Result<(), BString>,
+}
+
+/// A writer for the fast-export stream sent to a remote helper, obtained with [`Helper::export()`].
+pub struct Export<'a> {
+    helper: &'a mut Helper,
+}
+
+impl Write for Export<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.helper.stdin()?.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.helper.stdin()?.flush()
+    }
+}
+
+impl Export<'_> {
+    /// Call this after writing the whole stream, which has to end with [`done`](super::fast_import::Command::Done),
+    /// to learn which references were updated.
+    pub fn finish(self) -> Result<Vec<PushStatus>, Error> {
+        self.helper.stdin()?.flush()?;
+        self.helper.read_push_statuses("export")
+    }
+}
+
+impl Helper {
+    /// Run `program` with the `remote` name and the `address` to connect to as arguments, with `GIT_DIR` set to `git_dir`
+    /// and additional `envs`, and ask it for its capabilities.
+    /// If `trace` is `true`, all commands and responses are passed to the facilities of the `gix-trace` crate.
+    pub fn spawn<'a>(
+        program: impl Into<PathBuf>,
+        remote: &BStr,
+        address: &BStr,
+        git_dir: Option<&Path>,
+        envs: impl IntoIterator<Item = (&'a str, &'a OsStr)>,
+        trace: bool,
+    ) -> Result<Self, Error> {
+        let program = program.into();
+        let mut cmd = Command::new(&program);
+        cmd.arg(gix_path::from_bstr(remote).as_ref())
+            .arg(gix_path::from_bstr(address).as_ref())
+            .envs(envs)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        if let Some(git_dir) = git_dir {
+            cmd.env("GIT_DIR", git_dir);
+        }
+        gix_features::trace::debug!(cmd = ?cmd);
+        let mut child = cmd.spawn().map_err(|source| Error::Spawn {
+            program: program.clone(),
+            source,
+        })?;
+        let stdin = child.stdin.take().expect("configured");
+        let stdout = BufReader::new(child.stdout.take().expect("configured"));
+        let mut helper = Helper {
+            program,
+            child: Some(child),
+            stdin: Some(stdin),
+            stdout: Some(stdout),
+            capabilities: Capabilities::default(),
+            trace,
+        };
+        helper.send(&["capabilities".into()])?;
+        let lines = helper.read_lines_until_blank("capabilities")?;
+        helper.capabilities = Capabilities::from_lines(lines)?;
+        Ok(helper)
+    }
+
+    /// Return the path to the program that is running.
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Return the capabilities the helper advertised.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Set option `name` to `value`, and return `false` if the helper doesn't support it.
+    pub fn option(&mut self, name: &BStr, value: &BStr) -> Result<bool, Error> {
+        self.require("option")?;
+        self.send(&[format!("option {name} {value}").into()])?;
+        let line = self.read_line("option")?;
+        if line == "ok" {
+            Ok(true)
+        } else if line == "unsupported" {
+            Ok(false)
+        } else if let Some(message) = line.strip_prefix(b"error") {
+            Err(Error::Option {
+                name: name.to_owned(),
+                message: message.trim_start().into(),
+            })
+        } else {
+            Err(Error::UnexpectedResponse {
+                command: "option",
+                line,
+            })
+        }
+    }
+
+    /// List all references of the remote, which are meant to be pushed to if `for_push` is `true`.
+    pub fn list(&mut self, for_push: bool) -> Result<List, Error> {
+        self.send(&[if for_push { "list for-push" } else { "list" }.into()])?;
+        let mut list = List::default();
+        for line in self.read_lines_until_blank("list")? {
+            if let Some(keyword) = line.strip_prefix(b":") {
+                if let Some(format) = keyword.strip_prefix(b"object-format ") {
+                    list.object_format = Some(format.into());
+                }
+                continue;
+            }
+            let mut fields = line.split_str(" ");
+            let (Some(value), Some(name)) = (fields.next(), fields.next()) else {
+                return Err(Error::UnexpectedResponse { command: "list", line });
+            };
+            list.refs.push(Ref {
+                name: name.into(),
+                value: if value == b"?" {
+                    Value::Unknown
+                } else if let Some(target) = value.strip_prefix(b"@") {
+                    Value::Symbolic(target.into())
+                } else {
+                    Value::Id(value.into())
+                },
+                attributes: fields.map(Into::into).collect(),
+            });
+        }
+        Ok(list)
+    }
+
+    /// Fetch the objects with the given `(id, name)` pairs into the repository.
+    pub fn fetch(&mut self, refs: &[(BString, BString)]) -> Result<FetchOutcome, Error> {
+        self.require("fetch")?;
+        let mut batch: Vec<BString> = refs
+            .iter()
+            .map(|(id, name)| format!("fetch {id} {name}").into())
+            .collect();
+        batch.push(BString::default());
+        self.send(&batch)?;
+        let mut outcome = FetchOutcome::default();
+        for line in self.read_lines_until_blank("fetch")? {
+            if let Some(file) = line.strip_prefix(b"lock ") {
+                outcome
+                    .lock_files
+                    .push(gix_path::from_bstr(file.as_bstr()).into_owned());
+            } else if line == "connectivity-ok" {
+                outcome.connectivity_ok = true;
+            } else {
+                return Err(Error::UnexpectedResponse { command: "fetch", line });
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Ask the helper to import the references with the given `names`, and return the fast-import stream it responds with.
+    ///
+    /// The stream must be read up to and including its final `done` command before sending other commands.
+    pub fn import(&mut self, names: &[BString]) -> Result<&mut dyn BufRead, Error> {
+        self.require("import")?;
+        let mut batch: Vec<BString> = names.iter().map(|name| format!("import {name}").into()).collect();
+        batch.push(BString::default());
+        self.send(&batch)?;
+        Ok(self.stdout())
+    }
+
+    /// Start sending a fast-export stream to the helper to update the remote with.
+    pub fn export(&mut self) -> Result<Export<'_>, Error> {
+        self.require("export")?;
+        self.send(&["export".into()])?;
+        Ok(Export { helper: self })
+    }
+
+    /// Push according to each of `refspecs`, like `+refs/heads/main:refs/heads/main`, and return the status for each remote reference.
+    pub fn push(&mut self, refspecs: &[BString]) -> Result<Vec<PushStatus>, Error> {
+        self.require("push")?;
+        let mut batch: Vec<BString> = refspecs.iter().map(|spec| format!("push {spec}").into()).collect();
+        batch.push(BString::default());
+        self.send(&batch)?;
+        self.read_push_statuses("push")
+    }
+
+    /// Ask the helper to connect to `service`, and return `true` if it did so the packetline protocol can be spoken
+    /// with it from now on, or `false` if it asks to fall back to the other commands.
+    pub fn connect(&mut self, service: Service) -> Result<bool, Error> {
+        self.connect_inner("connect", service)
+    }
+
+    /// Like [`connect()`](Self::connect()), but for a connection using the stateless protocol version 2, where each response
+    /// ends with a response-end packet.
+    pub fn stateless_connect(&mut self, service: Service) -> Result<bool, Error> {
+        self.connect_inner("stateless-connect", service)
+    }
+
+    /// Turn this instance into the child process and its pipes after a successful `connect`.
+    pub(crate) fn into_parts(mut self) -> (Child, BufReader<ChildStdout>, ChildStdin) {
+        let child = self.child.take().expect("only taken here");
+        let stdin = self.stdin.take().expect("only taken here");
+        let stdout = self.stdout.take().expect("only taken here");
+        (child, stdout, stdin)
+    }
+}
+
+impl Helper {
+    fn connect_inner(&mut self, command: &'static str, service: Service) -> Result<bool, Error> {
+        self.require(command)?;
+        self.send(&[format!("{command} {}", service.as_str()).into()])?;
+        let line = self.read_line(command)?;
+        if line.is_empty() {
+            Ok(true)
+        } else if line == "fallback" {
+            Ok(false)
+        } else {
+            Err(Error::UnexpectedResponse { command, line })
+        }
+    }
+
+    fn require(&self, capability: &'static str) -> Result<(), Error> {
+        if self.capabilities.contains(capability) {
+            Ok(())
+        } else {
+            Err(Error::MissingCapability { capability })
+        }
+    }
+
+    fn stdin(&mut self) -> std::io::Result<&mut ChildStdin> {
+        self.stdin
+            .as_mut()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "the remote helper was shut down"))
+    }
+
+    fn stdout(&mut self) -> &mut BufReader<ChildStdout> {
+        self.stdout.as_mut().expect("present until the process is taken")
+    }
+
+    fn send(&mut self, lines: &[BString]) -> Result<(), Error> {
+        let trace = self.trace;
+        let stdin = self.stdin()?;
+        for line in lines {
+            if trace {
+                gix_features::trace::trace!("helper << {}", line);
+            }
+            stdin.write_all(line)?;
+            stdin.write_all(b"\n")?;
+        }
+        stdin.flush()?;
+        Ok(())
+    }
+
+    fn read_line(&mut self, command: &'static str) -> Result<BString, Error> {
+        let mut line = Vec::new();
+        if self.stdout().read_until(b'\n', &mut line)? == 0 {
+            return Err(Error::UnexpectedResponse {
+                command,
+                line: "<end of stream>".into(),
+            });
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        let line = BString::from(line);
+        if self.trace {
+            gix_features::trace::trace!("helper >> {}", line);
+        }
+        Ok(line)
+    }
+
+    fn read_lines_until_blank(&mut self, command: &'static str) -> Result<Vec<BString>, Error> {
+        let mut lines = Vec::new();
+        loop {
+            let line = self.read_line(command)?;
+            if line.is_empty() {
+                return Ok(lines);
+            }
+            lines.push(line);
+        }
+    }
+
+    fn read_push_statuses(&mut self, command: &'static str) -> Result<Vec<PushStatus>, Error> {
+        self.read_lines_until_blank(command)?
+            .into_iter()
+            .map(|line| {
+                if let Some(name) = line.strip_prefix(b"ok ") {
+                    Ok(PushStatus {
+                        name: name.into(),
+                        result: Ok(()),
+                    })
+                } else if let Some(rest) = line.strip_prefix(b"error ") {
+                    let (name, reason) = rest.split_once_str(" ").unwrap_or((rest, b""));
+                    Ok(PushStatus {
+                        name: name.into(),
+                        result: Err(reason.into()),
+                    })
+                } else {
+                    Err(Error::UnexpectedResponse { command, line })
+                }
+            })
+            .collect()
+    }
+}
+
+impl Drop for Helper {
+    fn drop(&mut self) {
+        if let Some(mut stdin) = self.stdin.take() {
+            // A blank line asks the helper to exit, just like closing its input does.
+            stdin.write_all(b"\n").ok();
+        }
+        if let Some(mut child) = self.child.take() {
+            child.wait().ok();
+        }
+    }
+}
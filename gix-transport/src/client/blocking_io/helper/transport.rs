@@ -0,0 +1,547 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    ffi::OsString,
+    io::{Cursor, Read, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+use gix_packetline::{Channel, PacketLineRef};
+
+use super::{Error, Helper, Import, Options, Value};
+use crate::{
+    Protocol, Service,
+    client::{
+        self, MessageKind, WriteMode,
+        blocking_io::{RequestWriter, SetServiceResponse},
+        capabilities::blocking_recv::Handshake,
+        git::blocking_io::Connection,
+    },
+    packetline::blocking_io::{StreamingPeekableIter, encode},
+};
+
+/// A transport using a remote helper, which is spawned on [handshake](crate::client::blocking_io::Transport::handshake()).
+pub struct RemoteHelper {
+    program: PathBuf,
+    url: gix_url::Url,
+    desired_version: Protocol,
+    options: Options,
+    trace: bool,
+    state: State,
+}
+
+enum State {
+    Idle,
+    /// The helper connected us to the service, which we talk to directly.
+    Connected {
+        child: Child,
+        connection: Connection<Box<dyn Read + Send>, ChildStdin>,
+        stateless: bool,
+    },
+    /// The helper only fetches or imports, so we emulate a server speaking protocol version 2 on top of it.
+    Emulated {
+        server: Arc<Mutex<Server>>,
+        line_provider: StreamingPeekableIter<Response>,
+    },
+}
+
+impl RemoteHelper {
+    /// Create a new instance that runs the helper `program` to connect to `url`, with the `desired_version` of the protocol
+    /// to use if it can connect to `git-upload-pack`.
+    /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    ///
+    /// Use [`connect()`](super::connect()) to find the program by the scheme of `url`.
+    pub fn new(program: PathBuf, url: gix_url::Url, desired_version: Protocol, trace: bool) -> Self {
+        RemoteHelper {
+            program,
+            url,
+            desired_version,
+            options: Options::default(),
+            trace,
+            state: State::Idle,
+        }
+    }
+
+    /// Return the address to pass to the helper, which is everything after `<transport>::` or the whole URL otherwise.
+    fn address(&self) -> BString {
+        let url = self.url.to_bstring();
+        let prefix = format!("{}::", self.url.scheme.as_str());
+        match url.strip_prefix(prefix.as_bytes()) {
+            Some(address) => address.into(),
+            None => url,
+        }
+    }
+
+    fn spawn(&self) -> Result<Helper, Error> {
+        let protocol = OsString::from(format!("version={}", self.desired_version as usize));
+        let envs = (self.desired_version != Protocol::V1).then_some(("GIT_PROTOCOL", protocol.as_os_str()));
+        let mut helper = Helper::spawn(
+            &self.program,
+            self.options
+                .remote_name
+                .clone()
+                .unwrap_or_else(|| self.url.to_bstring())
+                .as_bstr(),
+            self.address().as_bstr(),
+            self.options.git_dir.as_deref(),
+            envs,
+            self.trace,
+        )?;
+        if helper.capabilities().contains("option") {
+            if helper.capabilities().contains("object-format") {
+                helper.option("object-format".into(), "true".into())?;
+            }
+            for (name, value) in &self.options.options {
+                helper.option(name.as_bstr(), value.as_bstr())?;
+            }
+        }
+        Ok(helper)
+    }
+
+    fn connect_to(&mut self, service: Service) -> Result<(), Error> {
+        self.shutdown();
+        let mut helper = self.spawn()?;
+        let capabilities = helper.capabilities();
+        let stateless = service == Service::UploadPack
+            && self.desired_version == Protocol::V2
+            && capabilities.contains("stateless-connect");
+        let can_fetch = capabilities.contains("fetch") || capabilities.contains("import");
+        let connected = if stateless {
+            helper.stateless_connect(service)?
+        } else if capabilities.contains("connect") {
+            helper.connect(service)?
+        } else {
+            false
+        };
+
+        self.state = if connected {
+            let (child, stdout, stdin) = helper.into_parts();
+            let reader: Box<dyn Read + Send> = if stateless {
+                Box::new(SkipResponseEnd::new(stdout))
+            } else {
+                Box::new(stdout)
+            };
+            State::Connected {
+                child,
+                connection: Connection::new_for_spawned_process(
+                    reader,
+                    stdin,
+                    self.desired_version,
+                    self.url.path.clone(),
+                    self.trace,
+                )
+                .custom_url(Some(self.url.to_bstring())),
+                stateless,
+            }
+        } else if service == Service::UploadPack && can_fetch {
+            let list = helper.list(false)?;
+            let mut advertisement = Vec::new();
+            encode::text_to_write(b"version 2", &mut advertisement)?;
+            encode::text_to_write(b"ls-refs=unborn", &mut advertisement)?;
+            encode::text_to_write(b"fetch", &mut advertisement)?;
+            if let Some(format) = &list.object_format {
+                encode::text_to_write(format!("object-format={format}").as_bytes(), &mut advertisement)?;
+            }
+            encode::flush_to_write(&mut advertisement)?;
+            let server = Arc::new(Mutex::new(Server {
+                helper,
+                refs: list.refs,
+                object_format: list.object_format,
+                import: self.options.import.clone(),
+                lock_files: Vec::new(),
+                request: Vec::new(),
+                response: Cursor::new(advertisement),
+            }));
+            State::Emulated {
+                line_provider: StreamingPeekableIter::new(
+                    Response(server.clone()),
+                    &[PacketLineRef::Flush],
+                    self.trace,
+                ),
+                server,
+            }
+        } else {
+            return Err(Error::UnsupportedService { service });
+        };
+        Ok(())
+    }
+
+    /// Close the connection to the helper and wait for it to exit, removing the lock files of fetched packs
+    /// as references are updated by now.
+    fn shutdown(&mut self) {
+        match std::mem::replace(&mut self.state, State::Idle) {
+            State::Idle => {}
+            State::Connected {
+                mut child, connection, ..
+            } => {
+                drop(connection);
+                child.wait().ok();
+            }
+            State::Emulated { server, line_provider } => {
+                drop(line_provider);
+                let server = server.lock().unwrap_or_else(PoisonError::into_inner);
+                for lock_file in &server.lock_files {
+                    std::fs::remove_file(lock_file).ok();
+                }
+            }
+        }
+    }
+}
+
+impl Drop for RemoteHelper {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl client::TransportWithoutIO for RemoteHelper {
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Owned(self.url.to_bstring())
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        matches!(self.state, State::Connected { stateless: false, .. })
+    }
+
+    fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        if let Some(options) = config.downcast_ref::<Options>() {
+            self.options = options.clone();
+        }
+        Ok(())
+    }
+}
+
+impl client::blocking_io::Transport for RemoteHelper {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        self.connect_to(service).map_err(client::Error::RemoteHelper)?;
+        match &mut self.state {
+            State::Idle => unreachable!("connected or failed"),
+            State::Connected { connection, .. } => connection.handshake(service, extra_parameters),
+            State::Emulated { line_provider, .. } => {
+                let Handshake {
+                    capabilities,
+                    refs,
+                    protocol: actual_protocol,
+                } = Handshake::from_lines_with_version_detection(line_provider)?;
+                Ok(SetServiceResponse {
+                    actual_protocol,
+                    capabilities,
+                    refs,
+                })
+            }
+        }
+    }
+
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        match &mut self.state {
+            State::Idle => Err(client::Error::MissingHandshake),
+            State::Connected { connection, .. } => connection.request(write_mode, on_into_read, trace),
+            State::Emulated { server, line_provider } => Ok(RequestWriter::new_from_bufread(
+                RequestBody(server.clone()),
+                Box::new(line_provider.as_read_without_sidebands()),
+                write_mode,
+                on_into_read,
+                trace,
+            )),
+        }
+    }
+}
+
+/// The state of the emulated server, which handles a request once it is complete and its response is read.
+struct Server {
+    helper: Helper,
+    refs: Vec<super::Ref>,
+    object_format: Option<BString>,
+    import: Option<Arc<Mutex<dyn Import + Send>>>,
+    lock_files: Vec<PathBuf>,
+    request: Vec<u8>,
+    response: Cursor<Vec<u8>>,
+}
+
+/// The request as written by the client.
+struct RequestBody(Arc<Mutex<Server>>);
+
+impl Write for RequestBody {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut server = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        server.request.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The response to the request, which is produced when it's read for the first time.
+struct Response(Arc<Mutex<Server>>);
+
+impl Read for Response {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut server = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let is_exhausted = server.response.position() as usize == server.response.get_ref().len();
+        if is_exhausted && !server.request.is_empty() {
+            let request = std::mem::take(&mut server.request);
+            let response = server.respond(&request).map_err(std::io::Error::other)?;
+            server.response = Cursor::new(response);
+        }
+        server.response.read(buf)
+    }
+}
+
+/// An empty pack, with the trailing hash for SHA1 and SHA256 respectively.
+const EMPTY_PACK_HEADER: &[u8] = b"PACK\0\0\0\x02\0\0\0\0";
+const EMPTY_PACK_SHA1: [u8; 20] = [
+    2, 157, 8, 130, 59, 216, 168, 234, 181, 16, 173, 106, 199, 92, 130, 60, 253, 62, 211, 30,
+];
+const EMPTY_PACK_SHA256: [u8; 32] = [
+    126, 216, 144, 216, 164, 87, 96, 243, 238, 207, 115, 4, 91, 29, 16, 71, 8, 90, 244, 119, 109, 198, 131, 215, 142,
+    172, 130, 32, 61, 241, 153, 63,
+];
+
+impl Server {
+    fn respond(&mut self, request: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut lines = StreamingPeekableIter::new(request, &[PacketLineRef::Flush], false);
+        let mut command = None;
+        let mut arguments = Vec::<BString>::new();
+        let mut in_arguments = false;
+        while let Some(line) = lines.read_line() {
+            match line?.map_err(std::io::Error::other)? {
+                PacketLineRef::Data(data) => {
+                    let data = data.strip_suffix(b"\n").unwrap_or(data);
+                    if in_arguments {
+                        arguments.push(data.into());
+                    } else if let Some(name) = data.strip_prefix(b"command=") {
+                        command = Some(BString::from(name));
+                    }
+                }
+                PacketLineRef::Delimiter => in_arguments = true,
+                PacketLineRef::Flush | PacketLineRef::ResponseEnd => {}
+            }
+        }
+        let mut out = Vec::new();
+        match command {
+            None => {}
+            Some(command) if command == "ls-refs" => self.ls_refs(&arguments, &mut out)?,
+            Some(command) if command == "fetch" => self.fetch(&arguments, &mut out)?,
+            Some(command) => {
+                return Err(Error::UnsupportedArgument {
+                    argument: format!("command={command}").into(),
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    fn ls_refs(&mut self, arguments: &[BString], out: &mut Vec<u8>) -> Result<(), Error> {
+        let prefixes: Vec<&[u8]> = arguments
+            .iter()
+            .filter_map(|argument| argument.strip_prefix(b"ref-prefix "))
+            .collect();
+        let has = |name: &str| arguments.iter().any(|argument| argument == name);
+        let (symrefs, unborn) = (has("symrefs"), has("unborn"));
+        let matches = |name: &BStr| prefixes.is_empty() || prefixes.iter().any(|prefix| name.starts_with(prefix));
+
+        let mut wanted = Vec::new();
+        for r in self.refs.iter().filter(|r| matches(r.name.as_bstr())) {
+            wanted.push(r.name.clone());
+            if let Value::Symbolic(target) = &r.value {
+                wanted.push(target.clone());
+            }
+        }
+        let unknown: Vec<BString> = self
+            .refs
+            .iter()
+            .filter(|r| r.value == Value::Unknown && wanted.contains(&r.name))
+            .map(|r| r.name.clone())
+            .collect();
+        if !unknown.is_empty() {
+            self.import_refs(&unknown)?;
+        }
+
+        for r in self.refs.iter().filter(|r| matches(r.name.as_bstr())) {
+            let line = match &r.value {
+                Value::Id(id) => format!("{id} {}", r.name),
+                Value::Symbolic(target) => match (self.resolve(target.as_bstr()), unborn) {
+                    (Some(id), _) if symrefs => format!("{id} {} symref-target:{target}", r.name),
+                    (Some(id), _) => format!("{id} {}", r.name),
+                    (None, true) if symrefs => format!("unborn {} symref-target:{target}", r.name),
+                    (None, true) => format!("unborn {}", r.name),
+                    (None, false) => continue,
+                },
+                Value::Unknown => continue,
+            };
+            encode::text_to_write(line.as_bytes(), &mut *out)?;
+        }
+        encode::flush_to_write(out)?;
+        Ok(())
+    }
+
+    fn fetch(&mut self, arguments: &[BString], out: &mut Vec<u8>) -> Result<(), Error> {
+        let mut wants = Vec::new();
+        let mut done = false;
+        for argument in arguments {
+            if let Some(id) = argument.strip_prefix(b"want ") {
+                let name = self
+                    .refs
+                    .iter()
+                    .find(|r| matches!(&r.value, Value::Id(value) if value == id))
+                    .map(|r| r.name.clone())
+                    .ok_or_else(|| Error::UnadvertisedObject { id: id.into() })?;
+                wants.push((BString::from(id), name));
+            } else if argument == "done" {
+                done = true;
+            } else if ["deepen", "shallow", "filter", "want-ref", "packfile-uris"]
+                .iter()
+                .any(|unsupported| argument.starts_with(unsupported.as_bytes()))
+            {
+                return Err(Error::UnsupportedArgument {
+                    argument: argument.clone(),
+                });
+            }
+        }
+
+        if self.helper.capabilities().contains("fetch") {
+            let outcome = self.helper.fetch(&wants)?;
+            self.lock_files.extend(outcome.lock_files);
+        }
+        // Otherwise, everything that is wanted was imported already when listing references.
+
+        if !done {
+            // All objects are local now, so there is nothing to negotiate.
+            encode::text_to_write(b"acknowledgments", &mut *out)?;
+            encode::text_to_write(b"NAK", &mut *out)?;
+            encode::text_to_write(b"ready", &mut *out)?;
+            encode::delim_to_write(&mut *out)?;
+        }
+        encode::text_to_write(b"packfile", &mut *out)?;
+        let mut pack = EMPTY_PACK_HEADER.to_vec();
+        if self.object_format.as_ref().is_some_and(|format| format == "sha256") {
+            pack.extend_from_slice(&EMPTY_PACK_SHA256);
+        } else {
+            pack.extend_from_slice(&EMPTY_PACK_SHA1);
+        }
+        encode::band_to_write(Channel::Data, &pack, &mut *out)?;
+        encode::flush_to_write(out)?;
+        Ok(())
+    }
+
+    /// Import the references with `names`, and learn their values from the importer.
+    fn import_refs(&mut self, names: &[BString]) -> Result<(), Error> {
+        let capabilities = self.helper.capabilities().clone();
+        if !capabilities.contains("import") {
+            return Err(Error::UnknownValue { name: names[0].clone() });
+        }
+        let import = self.import.clone().ok_or(Error::MissingImporter)?;
+        let private_names: Vec<BString> = names
+            .iter()
+            .map(|name| capabilities.private_ref_name(name.as_bstr()))
+            .collect();
+        let stream = self.helper.import(names)?;
+        let ids = import
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .import(stream, &capabilities, &private_names)
+            .map_err(Error::Import)?;
+        for (name, id) in names.iter().zip(ids) {
+            match id {
+                Some(id) => {
+                    if let Some(r) = self.refs.iter_mut().find(|r| &r.name == name) {
+                        r.value = Value::Id(id);
+                    }
+                }
+                // The helper didn't produce it, so there is nothing to advertise.
+                None => self.refs.retain(|r| &r.name != name),
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the id `name` points to, following symbolic references.
+    fn resolve(&self, name: &BStr) -> Option<&BString> {
+        let mut name = name;
+        for _ in 0..5 {
+            match &self.refs.iter().find(|r| r.name == name)?.value {
+                Value::Id(id) => return Some(id),
+                Value::Symbolic(target) => name = target.as_bstr(),
+                Value::Unknown => return None,
+            }
+        }
+        None
+    }
+}
+
+/// A reader for the output of `stateless-connect`, which removes the response-end packets after each response
+/// as these aren't expected by a stateful [`Connection`].
+struct SkipResponseEnd<R> {
+    inner: R,
+    header: [u8; 4],
+    header_pos: usize,
+    remaining: usize,
+}
+
+impl<R> SkipResponseEnd<R> {
+    fn new(inner: R) -> Self {
+        SkipResponseEnd {
+            inner,
+            header: [0; 4],
+            header_pos: 4,
+            remaining: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for SkipResponseEnd<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            if self.header_pos < self.header.len() {
+                let n = (self.header.len() - self.header_pos).min(buf.len());
+                buf[..n].copy_from_slice(&self.header[self.header_pos..][..n]);
+                self.header_pos += n;
+                return Ok(n);
+            }
+            if self.remaining > 0 {
+                let n = self.remaining.min(buf.len());
+                let n = self.inner.read(&mut buf[..n])?;
+                self.remaining -= n;
+                return Ok(n);
+            }
+            let mut header = [0; 4];
+            match self.inner.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(0),
+                Err(err) => return Err(err),
+            }
+            if header == *b"0002" {
+                continue;
+            }
+            let len = std::str::from_utf8(&header)
+                .ok()
+                .and_then(|hex| usize::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid packet line header {:?}", header.as_bstr()),
+                    )
+                })?;
+            self.header = header;
+            self.header_pos = 0;
+            self.remaining = len.saturating_sub(4);
+        }
+    }
+}
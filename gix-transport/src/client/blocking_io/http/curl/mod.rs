@@ -9,7 +9,7 @@ use std::{
 use gix_features::io;
 use parking_lot::Mutex;
 
-use crate::client::blocking_io::http::{self, traits::PostBodyDataKind};
+use crate::client::blocking_io::http::{self, PostBodyDataKind};
 
 mod remote;
 
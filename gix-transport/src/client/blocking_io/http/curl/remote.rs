@@ -15,12 +15,11 @@ use gix_features::io::pipe;
 use parking_lot::Mutex;
 
 use crate::client::blocking_io::http::{
-    self,
+    self, PostBodyDataKind,
     curl::Error,
     curl::curl_is_spurious,
     options::{FollowRedirects, HttpVersion, ProxyAuthMethod, SslVersion},
     redirect::{self, Action as RedirectAction},
-    traits::PostBodyDataKind,
 };
 
 enum StreamOrBuffer {
@@ -8,7 +8,9 @@ use std::{
 
 use base64::Engine;
 use bstr::BStr;
-pub use traits::{Error, GetResponse, Http, PostBodyDataKind, PostResponse};
+pub use traits::Http;
+
+pub use crate::client::http::{Error, GetResponse, PostBodyDataKind, PostResponse};
 
 use crate::{
     Protocol, Service,
@@ -20,6 +22,7 @@ use crate::{
             http::options::{HttpVersion, SslVersionRangeInclusive},
         },
         capabilities::blocking_recv::Handshake,
+        http::{append_url, git_protocol_header},
     },
     packetline::{PacketLineRef, blocking_io::StreamingPeekableIter},
 };
@@ -325,15 +328,6 @@ impl<H: Http> Transport<H> {
     }
 }
 
-fn append_url(base: &str, suffix: &str) -> String {
-    let mut buf = base.to_owned();
-    if base.as_bytes().last() != Some(&b'/') {
-        buf.push('/');
-    }
-    buf.push_str(suffix);
-    buf
-}
-
 impl<H: Http> client::TransportWithoutIO for Transport<H> {
     fn set_identity(&mut self, identity: gix_sec::identity::Account) -> Result<(), client::Error> {
         self.identity = Some(identity);
@@ -362,27 +356,8 @@ impl<H: Http> blocking_io::Transport for Transport<H> {
         let url = append_url(self.url.as_ref(), &format!("info/refs?service={}", service.as_str()));
         let static_headers = [Cow::Borrowed(self.user_agent_header)];
         let mut dynamic_headers = Vec::<Cow<'_, str>>::new();
-        if self.desired_version != Protocol::V1 || !extra_parameters.is_empty() {
-            let mut parameters = if self.desired_version != Protocol::V1 {
-                let mut p = format!("version={}", self.desired_version as usize);
-                if !extra_parameters.is_empty() {
-                    p.push(':');
-                }
-                p
-            } else {
-                String::new()
-            };
-            parameters.push_str(
-                &extra_parameters
-                    .iter()
-                    .map(|(key, value)| match value {
-                        Some(value) => format!("{key}={value}"),
-                        None => key.to_string(),
-                    })
-                    .collect::<Vec<_>>()
-                    .join(":"),
-            );
-            dynamic_headers.push(format!("Git-Protocol: {parameters}").into());
+        if let Some(header) = git_protocol_header(self.desired_version, extra_parameters) {
+            dynamic_headers.push(header.into());
         }
         self.add_basic_auth_if_present(&mut dynamic_headers)?;
         let GetResponse { headers, mut body } = self
@@ -580,5 +555,5 @@ pub fn connect<H: Http + Default>(url: gix_url::Url, desired_version: Protocol,
     Transport::new(url, desired_version, trace)
 }
 
-///
-pub mod redirect;
+#[doc(inline)]
+pub use crate::client::http::redirect;
@@ -9,11 +9,10 @@ use gix_features::io::pipe;
 use parking_lot::Mutex;
 
 use crate::client::blocking_io::http::{
-    self,
+    self, PostBodyDataKind,
     options::FollowRedirects,
     redirect::{self, Action as RedirectAction},
     reqwest::Remote,
-    traits::PostBodyDataKind,
 };
 
 /// The error returned by the 'remote' helper, a purely internal construct to perform http requests.
@@ -0,0 +1,165 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{
+    Protocol, Service,
+    client::{
+        self, MessageKind, WriteMode,
+        blocking_io::{RequestWriter, SetServiceResponse},
+        git::blocking_io::Connection,
+    },
+};
+
+type Channel = gix_ssh::Channel<Box<dyn gix_ssh::Stream>>;
+
+/// One half of a [`Channel`], which is shared between the reader and the writer of a connection.
+///
+/// As the protocol alternates between writing requests and reading responses, the halves never compete for the lock.
+struct Half(Arc<Mutex<Channel>>);
+
+impl std::io::Read for Half {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.lock().expect("no panic while holding the lock").read(buf)
+    }
+}
+
+impl std::io::Write for Half {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().expect("no panic while holding the lock").write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.lock().expect("no panic while holding the lock").flush()
+    }
+}
+
+/// A transport that connects to a host using the native SSH implementation of `gix-ssh` once the handshake is performed.
+///
+/// It can only be instantiated using [`connect()`].
+pub struct ConnectOnDemand {
+    url: gix_url::Url,
+    path: BString,
+    desired_version: Protocol,
+    connection: Option<Connection<Half, Half>>,
+    trace: bool,
+}
+
+impl client::TransportWithoutIO for ConnectOnDemand {
+    fn set_identity(&mut self, identity: gix_sec::identity::Account) -> Result<(), client::Error> {
+        self.url
+            .set_user((!identity.username.is_empty()).then_some(identity.username));
+        Ok(())
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Owned(self.url.to_bstring())
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        true
+    }
+
+    fn configure(&mut self, _config: &dyn Any) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        Ok(())
+    }
+}
+
+impl client::blocking_io::Transport for ConnectOnDemand {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        if self.path.trim().first() == Some(&b'-') {
+            return Err(client::Error::AmbiguousPath {
+                path: self.path.clone(),
+            });
+        }
+        let options = gix_ssh::connect::Options::from_environment().map_err(std::io::Error::other)?;
+        let session = gix_ssh::connect(
+            self.url.host().expect("validated when connecting"),
+            self.url.port,
+            self.url.user(),
+            &options,
+        )
+        .map_err(into_io_error)?;
+
+        let protocol = format!("version={}", self.desired_version as usize);
+        let env: &[(&str, &str)] = if self.desired_version == Protocol::V1 {
+            &[]
+        } else {
+            &[("GIT_PROTOCOL", &protocol)]
+        };
+        let command = format!(
+            "{} {}",
+            service.as_str(),
+            gix_quote::single(self.path.as_ref()).to_str_lossy()
+        );
+        gix_features::trace::debug!(command, "gix_transport::ssh::native::ConnectOnDemand");
+        let channel = Arc::new(Mutex::new(session.exec(&command, env).map_err(std::io::Error::from)?));
+
+        let connection = self.connection.insert(Connection::new_for_spawned_process(
+            Half(channel.clone()),
+            Half(channel),
+            self.desired_version,
+            self.path.clone(),
+            self.trace,
+        ));
+        connection.handshake(service, extra_parameters)
+    }
+
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+        trace: bool,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        self.connection
+            .as_mut()
+            .ok_or(client::Error::MissingHandshake)?
+            .request(write_mode, on_into_read, trace)
+    }
+}
+
+/// Turn `err` into an IO error whose kind lets callers know if they should retry with different credentials.
+fn into_io_error(err: gix_ssh::connect::Error) -> std::io::Error {
+    use gix_ssh::connect::Error;
+    let kind = match &err {
+        Error::Io { source, .. } => source.kind(),
+        Error::Auth(gix_ssh::auth::Error::Denied { .. }) => std::io::ErrorKind::PermissionDenied,
+        _ => std::io::ErrorKind::Other,
+    };
+    std::io::Error::new(kind, err)
+}
+
+/// Connect to `url` using the native SSH implementation to obtain data from the repository at the path of `url`
+/// on the remote once the handshake is performed.
+///
+/// The connection is configured by `~/.ssh/config`, host keys are verified with the `known_hosts` files, and authentication
+/// uses the agent in `SSH_AUTH_SOCK`, key files and passwords, obtained with the help of `GIT_ASKPASS` or `SSH_ASKPASS` if set.
+///
+/// The `desired_version` is the preferred protocol version when establishing the connection, but note that it can be
+/// downgraded by servers not supporting it.
+/// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+#[expect(
+    clippy::result_large_err,
+    reason = "will be removed once `gix-error` is used consistently"
+)]
+pub fn connect(url: gix_url::Url, desired_version: Protocol, trace: bool) -> Result<ConnectOnDemand, super::Error> {
+    if url.scheme != gix_url::Scheme::Ssh || url.host().is_none() {
+        return Err(super::Error::UnsupportedScheme(url));
+    }
+    Ok(ConnectOnDemand {
+        path: gix_url::expand_path::for_shell(url.path.clone()),
+        url,
+        desired_version,
+        connection: None,
+        trace,
+    })
+}
@@ -197,3 +197,65 @@ mod async_net {
         }
     }
 }
+
+///
+#[cfg(feature = "async-client-connect")]
+pub mod connect {
+    use std::net::{TcpStream, ToSocketAddrs};
+
+    use blocking::Unblock;
+    use bstr::BString;
+
+    use super::Connection;
+    pub use crate::client::git::connect::Error;
+    use crate::client::git::{self, connect::parse_host};
+
+    /// Connect to a git daemon running on `host` and optionally `port` and a repository at `path`.
+    ///
+    /// Use `desired_version` to specify a preferred protocol to use, knowing that it can be downgraded by a server not supporting it.
+    /// If `trace` is `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
+    ///
+    /// Name resolution, connecting and all reads and writes are performed on a thread-pool, which makes the connection
+    /// usable with any async runtime.
+    pub async fn connect(
+        host: &str,
+        path: BString,
+        desired_version: crate::Protocol,
+        port: Option<u16>,
+        trace: bool,
+    ) -> Result<Connection<Unblock<TcpStream>, Unblock<TcpStream>>, Error> {
+        let host = host.to_owned();
+        let (read, write, vhost) = blocking::unblock(move || -> Result<_, Error> {
+            let addr = (host.as_str(), port.unwrap_or(9418))
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("Could not resolve '{host}' to an IP address"),
+                    )
+                })?;
+            let read = TcpStream::connect_timeout(&addr, std::time::Duration::from_secs(5))?;
+            let write = read.try_clone()?;
+            let vhost = std::env::var("GIT_OVERRIDE_VIRTUAL_HOST")
+                .ok()
+                .map(parse_host)
+                .transpose()?
+                .unwrap_or((host, port));
+            Ok((read, write, vhost))
+        })
+        .await?;
+        Ok(Connection::new(
+            Unblock::new(read),
+            Unblock::new(write),
+            desired_version,
+            path,
+            Some(vhost),
+            git::ConnectMode::Daemon,
+            trace,
+        ))
+    }
+}
+
+#[cfg(feature = "async-client-connect")]
+pub use connect::connect;
@@ -178,36 +178,8 @@ pub mod connect {
     use super::Connection;
     use crate::client::git;
 
-    /// The error used in [`connect()`].
-    #[derive(Debug, thiserror::Error)]
-    #[expect(missing_docs)]
-    pub enum Error {
-        #[error("An IO error occurred when connecting to the server")]
-        Io(#[from] std::io::Error),
-        #[error("Could not parse {host:?} as virtual host with format <host>[:port]")]
-        VirtualHostInvalid { host: String },
-    }
-
-    impl crate::IsSpuriousError for Error {
-        fn is_spurious(&self) -> bool {
-            match self {
-                Error::Io(err) => err.is_spurious(),
-                _ => false,
-            }
-        }
-    }
-
-    fn parse_host(input: String) -> Result<(String, Option<u16>), Error> {
-        let mut tokens = input.splitn(2, ':');
-        Ok(match (tokens.next(), tokens.next()) {
-            (Some(host), None) => (host.to_owned(), None),
-            (Some(host), Some(port)) => (
-                host.to_owned(),
-                Some(port.parse().map_err(|_| Error::VirtualHostInvalid { host: input })?),
-            ),
-            _ => unreachable!("we expect at least one token, the original string"),
-        })
-    }
+    pub use crate::client::git::connect::Error;
+    use crate::client::git::connect::parse_host;
 
     /// Connect to a git daemon running on `host` and optionally `port` and a repository at `path`.
     ///
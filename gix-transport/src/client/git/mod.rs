@@ -165,6 +165,40 @@ mod message {
 #[cfg(feature = "async-client")]
 pub mod async_io;
 
+#[cfg(any(feature = "blocking-client", feature = "async-client-connect"))]
+pub(crate) mod connect {
+    /// The error used in `connect()`.
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error("An IO error occurred when connecting to the server")]
+        Io(#[from] std::io::Error),
+        #[error("Could not parse {host:?} as virtual host with format <host>[:port]")]
+        VirtualHostInvalid { host: String },
+    }
+
+    impl crate::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::Io(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+
+    pub(crate) fn parse_host(input: String) -> Result<(String, Option<u16>), Error> {
+        let mut tokens = input.splitn(2, ':');
+        Ok(match (tokens.next(), tokens.next()) {
+            (Some(host), None) => (host.to_owned(), None),
+            (Some(host), Some(port)) => (
+                host.to_owned(),
+                Some(port.parse().map_err(|_| Error::VirtualHostInvalid { host: input })?),
+            ),
+            _ => unreachable!("we expect at least one token, the original string"),
+        })
+    }
+}
+
 ///
 #[cfg(feature = "blocking-client")]
 pub mod blocking_io;
@@ -0,0 +1,127 @@
+//! Types shared by the blocking and async HTTP transports, which don't depend on the kind of IO.
+use crate::{Protocol, client::WriteMode};
+
+///
+pub mod redirect;
+
+/// The error used by the blocking and async `Http` traits.
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Could not initialize the http client")]
+    InitHttpClient {
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error("{description}")]
+    Detail { description: String },
+    #[error("An IO error occurred while uploading the body of a POST request")]
+    PostBody(#[from] std::io::Error),
+}
+
+impl crate::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::PostBody(err) => err.is_spurious(),
+            #[cfg(any(feature = "http-client-reqwest", feature = "http-client-curl"))]
+            Error::InitHttpClient { source } => {
+                #[cfg(feature = "http-client-curl")]
+                if let Some(err) = source.downcast_ref::<crate::client::blocking_io::http::curl::Error>() {
+                    return err.is_spurious();
+                }
+                #[cfg(feature = "http-client-reqwest")]
+                if let Some(err) = source.downcast_ref::<crate::client::blocking_io::http::reqwest::remote::Error>() {
+                    return err.is_spurious();
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The return value of `Http::get()`.
+pub struct GetResponse<H, B> {
+    /// The response headers.
+    pub headers: H,
+    /// The response body.
+    pub body: B,
+}
+
+/// The return value of `Http::post()`.
+pub struct PostResponse<H, B, PB> {
+    /// The body to post to the server as part of the request.
+    ///
+    /// **Note**: Implementations should drop the handle to avoid deadlocks.
+    pub post_body: PB,
+    /// The headers of the post response.
+    pub headers: H,
+    /// The body of the post response.
+    pub body: B,
+}
+
+/// Whether or not the post body is expected to fit into memory or not.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum PostBodyDataKind {
+    /// We know how much data we are sending and think it will fit into memory. This allows to collect it into a buffer
+    /// and send it with `Content-Length: <body-len>`.
+    BoundedAndFitsIntoMemory,
+    /// We don't know how much data we will send and assume it won't fit into memory. This enables streaming mode.
+    Unbounded,
+}
+
+impl From<WriteMode> for PostBodyDataKind {
+    fn from(m: WriteMode) -> Self {
+        match m {
+            WriteMode::Binary => PostBodyDataKind::Unbounded,
+            WriteMode::OneLfTerminatedLinePerWriteCall => PostBodyDataKind::BoundedAndFitsIntoMemory,
+        }
+    }
+}
+
+impl<A, B, C> From<PostResponse<A, B, C>> for GetResponse<A, B> {
+    fn from(v: PostResponse<A, B, C>) -> Self {
+        GetResponse {
+            headers: v.headers,
+            body: v.body,
+        }
+    }
+}
+
+pub(crate) fn append_url(base: &str, suffix: &str) -> String {
+    let mut buf = base.to_owned();
+    if base.as_bytes().last() != Some(&b'/') {
+        buf.push('/');
+    }
+    buf.push_str(suffix);
+    buf
+}
+
+/// Return the `Git-Protocol` header to send along with the initial `GET` request, if one is needed.
+pub(crate) fn git_protocol_header(
+    desired_version: Protocol,
+    extra_parameters: &[(&str, Option<&str>)],
+) -> Option<String> {
+    if desired_version == Protocol::V1 && extra_parameters.is_empty() {
+        return None;
+    }
+    let mut parameters = if desired_version != Protocol::V1 {
+        let mut p = format!("version={}", desired_version as usize);
+        if !extra_parameters.is_empty() {
+            p.push(':');
+        }
+        p
+    } else {
+        String::new()
+    };
+    parameters.push_str(
+        &extra_parameters
+            .iter()
+            .map(|(key, value)| match value {
+                Some(value) => format!("{key}={value}"),
+                None => key.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(":"),
+    );
+    Some(format!("Git-Protocol: {parameters}"))
+}
@@ -8,6 +8,7 @@ pub struct Error {
     expected_url: String,
 }
 
+#[cfg(feature = "http-client")]
 #[derive(Default, Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum Action {
     Follow,
@@ -16,6 +17,7 @@ pub(crate) enum Action {
     Stop,
 }
 
+#[cfg(feature = "http-client")]
 impl Action {
     pub(crate) fn from_request(may_follow_redirects: bool, has_configured_request_headers: bool) -> Self {
         match (may_follow_redirects, has_configured_request_headers) {
@@ -32,7 +34,9 @@ fn parse_two_urls(a: &str, b: &str) -> Option<(gix_url::Url, gix_url::Url)> {
     Some((a, b))
 }
 
-pub(crate) fn scheme_is_safe(redirect_url: &str, original_url: &str) -> bool {
+/// Return `true` if following a redirect from `original_url` to `redirect_url` doesn't downgrade the security of the connection,
+/// i.e. both are HTTP URLs and `https` isn't replaced by `http`.
+pub fn scheme_is_safe(redirect_url: &str, original_url: &str) -> bool {
     let Some((redirect_url, original_url)) = parse_two_urls(redirect_url, original_url) else {
         return false;
     };
@@ -70,7 +74,12 @@ pub(crate) fn can_reuse_identity(redirect_url: &str, original_url: &str) -> bool
     false
 }
 
-pub(crate) fn base_url(redirect_url: &str, base_url: &str, url: String) -> Result<String, Error> {
+/// Return the effective base URL after a redirect of `url` to `redirect_url`, with `base_url` being a prefix of `url`,
+/// or fail if the redirect changes the scheme unsafely or doesn't preserve the part of `url` following `base_url`.
+///
+/// This is useful for `Http` implementations to determine the value they
+/// return from `redirected_base_url()`.
+pub fn base_url(redirect_url: &str, base_url: &str, url: String) -> Result<String, Error> {
     let tail = url
         .strip_prefix(base_url)
         .expect("BUG: caller assures `base_url` is subset of `url`");
@@ -89,7 +98,8 @@ pub(crate) fn base_url(redirect_url: &str, base_url: &str, url: String) -> Resul
         .map(ToOwned::to_owned)
 }
 
-pub(crate) fn swap_tails(effective_base_url: Option<&str>, base_url: &str, mut url: String) -> String {
+/// Replace `base_url` in `url` with `effective_base_url` if a redirect was followed earlier, or return `url` unchanged.
+pub fn swap_tails(effective_base_url: Option<&str>, base_url: &str, mut url: String) -> String {
     match effective_base_url {
         Some(effective_base) => {
             url.replace_range(..base_url.len(), effective_base);
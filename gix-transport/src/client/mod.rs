@@ -21,3 +21,13 @@ pub use non_io_types::{Error, MessageKind, WriteMode};
 ///
 #[cfg(any(feature = "blocking-client", feature = "async-client"))]
 pub mod git;
+
+#[cfg(any(feature = "http-client", feature = "async-client"))]
+pub(crate) mod http;
+
+///
+#[cfg(any(feature = "blocking-client", feature = "async-client-connect"))]
+pub mod ssh;
+
+#[cfg(any(feature = "blocking-client", feature = "async-client-connect"))]
+mod spawn;
@@ -38,9 +38,9 @@ pub(crate) mod connect {
     pub struct Options {
         /// Use `version` to set the desired protocol version to use when connecting, but note that the server may downgrade it.
         pub version: crate::Protocol,
-        #[cfg(feature = "blocking-client")]
+        #[cfg(any(feature = "blocking-client", feature = "async-client-connect"))]
         /// Options to use if the scheme of the URL is `ssh`.
-        pub ssh: crate::client::blocking_io::ssh::connect::Options,
+        pub ssh: crate::client::ssh::connect::Options,
         /// If `true`, all packetlines received or sent will be passed to the facilities of the `gix-trace` crate.
         pub trace: bool,
     }
@@ -67,6 +67,9 @@ pub(crate) mod connect {
         #[cfg(not(any(feature = "http-client-curl", feature = "http-client-reqwest")))]
         #[error("'{0}' is not compiled in. Compile with the 'http-client-curl' or 'http-client-reqwest' cargo feature")]
         CompiledWithoutHttp(gix_url::Scheme),
+        #[cfg(not(feature = "ssh-client-native"))]
+        #[error("Native SSH support is not compiled in. Compile with the 'ssh-client-native' cargo feature")]
+        CompiledWithoutNativeSsh,
     }
 
     // TODO: maybe fix this workaround: want `IsSpuriousError`  in `Connection(…)`
@@ -74,8 +77,8 @@ pub(crate) mod connect {
         fn is_spurious(&self) -> bool {
             match self {
                 Error::Connection(err) => {
-                    #[cfg(feature = "blocking-client")]
-                    if let Some(err) = err.downcast_ref::<crate::client::git::blocking_io::connect::Error>() {
+                    #[cfg(any(feature = "blocking-client", feature = "async-client-connect"))]
+                    if let Some(err) = err.downcast_ref::<crate::client::git::connect::Error>() {
                         return err.is_spurious();
                     }
                     if let Some(err) = err.downcast_ref::<crate::client::Error>() {
@@ -94,20 +97,24 @@ mod error {
 
     use bstr::BString;
 
-    #[cfg(feature = "http-client")]
-    use crate::client::blocking_io::http;
-    #[cfg(feature = "blocking-client")]
-    use crate::client::blocking_io::ssh;
     use crate::client::capabilities;
+    #[cfg(any(feature = "http-client", feature = "async-client"))]
+    use crate::client::http;
+    #[cfg(any(feature = "blocking-client", feature = "async-client-connect"))]
+    use crate::client::ssh;
 
-    #[cfg(feature = "http-client")]
+    #[cfg(any(feature = "http-client", feature = "async-client"))]
     type HttpError = http::Error;
-    #[cfg(feature = "blocking-client")]
+    #[cfg(any(feature = "blocking-client", feature = "async-client-connect"))]
     type SshInvocationError = ssh::invocation::Error;
-    #[cfg(not(feature = "http-client"))]
+    #[cfg(not(any(feature = "http-client", feature = "async-client")))]
     type HttpError = std::convert::Infallible;
-    #[cfg(not(feature = "blocking-client"))]
+    #[cfg(not(any(feature = "blocking-client", feature = "async-client-connect")))]
     type SshInvocationError = std::convert::Infallible;
+    #[cfg(feature = "blocking-client")]
+    type RemoteHelperError = crate::client::blocking_io::helper::Error;
+    #[cfg(not(feature = "blocking-client"))]
+    type RemoteHelperError = std::convert::Infallible;
 
     /// The error used in most methods of the [`client`][crate::client] module
     #[derive(thiserror::Error, Debug)]
@@ -143,6 +150,8 @@ mod error {
         Http(#[from] HttpError),
         #[error(transparent)]
         SshInvocation(SshInvocationError),
+        #[error(transparent)]
+        RemoteHelper(RemoteHelperError),
         #[error("The repository path '{path}' could be mistaken for a command-line argument")]
         AmbiguousPath { path: BString },
     }
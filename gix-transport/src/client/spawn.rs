@@ -0,0 +1,293 @@
+//! Spawning of local programs that speak the `git` protocol through their standard input and output, possibly over `ssh`,
+//! which is shared by the blocking and async transports.
+use std::{
+    ffi::OsString,
+    io::Write,
+    process::{self, Stdio},
+};
+
+use bstr::{BString, ByteSlice, io::BufReadExt};
+
+use crate::{
+    Protocol, Service,
+    client::{self, ssh},
+};
+
+// from https://github.com/git/git/blob/20de7e7e4f4e9ae52e6cc7cfaa6469f186ddb0fa/environment.c#L115:L115
+const ENV_VARS_TO_REMOVE: &[&str] = &[
+    "GIT_ALTERNATE_OBJECT_DIRECTORIES",
+    "GIT_CONFIG",
+    "GIT_CONFIG_PARAMETERS",
+    "GIT_OBJECT_DIRECTORY",
+    "GIT_DIR",
+    "GIT_WORK_TREE",
+    "GIT_IMPLICIT_WORK_TREE",
+    "GIT_GRAFT_FILE",
+    "GIT_INDEX_FILE",
+    "GIT_NO_REPLACE_OBJECTS",
+    "GIT_REPLACE_REF_BASE",
+    "GIT_PREFIX",
+    "GIT_INTERNAL_SUPER_PREFIX",
+    "GIT_SHALLOW_FILE",
+    "GIT_COMMON_DIR",
+    "GIT_CONFIG_COUNT",
+];
+
+/// Everything needed to spawn the program that serves a repository, either locally or through `ssh`.
+pub(crate) struct Invocation {
+    pub(crate) desired_version: Protocol,
+    pub(crate) url: gix_url::Url,
+    pub(crate) path: BString,
+    ssh_cmd: Option<(OsString, ssh::ProgramKind)>,
+    /// The environment variables to set in the invoked command.
+    envs: Vec<(&'static str, String)>,
+    ssh_disallow_shell: bool,
+}
+
+/// The result of [`Invocation::spawn()`].
+pub(crate) struct Spawned {
+    /// The running process, which should be killed once the connection isn't needed anymore.
+    pub child: process::Child,
+    /// The standard output of the process, which fails with errors extracted from `stderr` for `ssh` programs.
+    pub stdout: Box<dyn std::io::Read + Send>,
+    /// The standard input of the process.
+    pub stdin: process::ChildStdin,
+}
+
+impl Invocation {
+    pub(crate) fn new_ssh(
+        url: gix_url::Url,
+        program: impl Into<OsString>,
+        path: BString,
+        ssh_kind: ssh::ProgramKind,
+        ssh_disallow_shell: bool,
+        version: Protocol,
+    ) -> Self {
+        Invocation {
+            url,
+            path,
+            ssh_cmd: Some((program.into(), ssh_kind)),
+            envs: Default::default(),
+            ssh_disallow_shell,
+            desired_version: version,
+        }
+    }
+
+    pub(crate) fn new_local(path: BString, version: Protocol) -> Self {
+        Invocation {
+            url: gix_url::Url::from_parts(gix_url::Scheme::File, None, None, None, None, path.clone(), true)
+                .expect("valid url"),
+            path,
+            ssh_cmd: None,
+            envs: if version != Protocol::V1 {
+                vec![("GIT_PROTOCOL", format!("version={}", version as usize))]
+            } else {
+                Default::default()
+            },
+            ssh_disallow_shell: false,
+            desired_version: version,
+        }
+    }
+
+    pub(crate) fn prepare_command(
+        &self,
+        service: Service,
+    ) -> Result<(gix_command::Prepare, Option<ssh::ProgramKind>, OsString), client::Error> {
+        let (mut cmd, ssh_kind, cmd_name) = match &self.ssh_cmd {
+            Some((command, kind)) => (
+                kind.prepare_invocation(command, &self.url, self.desired_version, self.ssh_disallow_shell)
+                    .map_err(client::Error::SshInvocation)?
+                    .stderr(Stdio::piped()),
+                Some(*kind),
+                command.to_owned(),
+            ),
+            None => (
+                gix_command::prepare(service.as_str()).stderr(Stdio::null()),
+                None,
+                OsString::from(service.as_str()),
+            ),
+        };
+        if self.path.trim().first() == Some(&b'-') {
+            return Err(client::Error::AmbiguousPath {
+                path: self.path.clone(),
+            });
+        }
+        let repo_path = if self.ssh_cmd.is_some() {
+            cmd.args.push(service.as_str().into());
+            gix_quote::single(self.path.as_ref()).to_os_str_lossy().into_owned()
+        } else {
+            self.path.to_os_str_lossy().into_owned()
+        };
+        cmd.args.push(repo_path);
+        Ok((cmd, ssh_kind, cmd_name))
+    }
+
+    /// Prepare an invocation for when the program of `service`, e.g. `git-upload-pack`, isn't present in `PATH`,
+    /// which can happen on Windows in particular, where `git` may be installed without its subcommands
+    /// being directly available (#2313).
+    ///
+    /// Prefer the same program from git's own `--exec-path`, which is where `git` itself finds it, and
+    /// otherwise let the `git` itself run it as subcommand.
+    /// This is only used for local repositories - remote shells keep the standard invocation.
+    pub(crate) fn prepare_fallback_command(&self, service: Service) -> (gix_command::Prepare, OsString) {
+        let (mut cmd, cmd_name) = match gix_path::env::core_dir_program(service.as_str()) {
+            Some(program) => {
+                let cmd_name = program.clone().into_os_string();
+                (gix_command::prepare(program).stderr(Stdio::null()), cmd_name)
+            }
+            None => {
+                let subcommand = service.as_git_subcommand();
+                let git = gix_path::env::exe_invocation();
+                let cmd = gix_command::prepare(git).stderr(Stdio::null()).arg(subcommand);
+
+                let mut cmd_name: OsString = git.into();
+                cmd_name.push(" ");
+                cmd_name.push(subcommand);
+                (cmd, cmd_name)
+            }
+        };
+        cmd.args.push(self.path.to_os_str_lossy().into_owned());
+        (cmd, cmd_name)
+    }
+
+    /// Spawn the program serving `service` with its standard input and output connected to pipes.
+    ///
+    /// Note that the environment is only applied to the first spawned program.
+    pub(crate) fn spawn(&mut self, service: Service) -> Result<Spawned, client::Error> {
+        let (cmd, ssh_kind, cmd_name) = self.prepare_command(service)?;
+        let envs = std::mem::take(&mut self.envs);
+        let into_std_command = |mut cmd: gix_command::Prepare| {
+            cmd.stdin = Stdio::piped();
+            cmd.stdout = Stdio::piped();
+
+            let mut cmd = std::process::Command::from(cmd);
+            for env_to_remove in ENV_VARS_TO_REMOVE {
+                cmd.env_remove(env_to_remove);
+            }
+            cmd.envs(envs.iter().map(|(k, v)| (k, v)));
+            cmd
+        };
+
+        let mut cmd = into_std_command(cmd);
+        gix_features::trace::debug!(command = ?cmd, "gix_transport::SpawnProcessOnDemand");
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(err) if ssh_kind.is_none() && err.kind() == std::io::ErrorKind::NotFound => {
+                // The service program wasn't found in `PATH`, but as `git` itself can be found,
+                // the service can still be run through it (#2313).
+                let (cmd, cmd_name) = self.prepare_fallback_command(service);
+                let mut cmd = into_std_command(cmd);
+                gix_features::trace::debug!(command = ?cmd, "gix_transport::SpawnProcessOnDemand (fallback)");
+                cmd.spawn().map_err(|err| client::Error::InvokeProgram {
+                    source: err,
+                    command: cmd_name,
+                })?
+            }
+            Err(err) => {
+                return Err(client::Error::InvokeProgram {
+                    source: err,
+                    command: cmd_name,
+                });
+            }
+        };
+        let stdout: Box<dyn std::io::Read + Send> = match ssh_kind {
+            Some(ssh_kind) => Box::new(supervise_stderr(
+                ssh_kind,
+                child.stderr.take().expect("configured beforehand"),
+                child.stdout.take().expect("configured"),
+            )),
+            None => Box::new(child.stdout.take().expect("stdout configured")),
+        };
+        let stdin = child.stdin.take().expect("stdin configured");
+        Ok(Spawned { child, stdout, stdin })
+    }
+
+    pub(crate) fn set_identity(&mut self, identity: gix_sec::identity::Account) -> Result<(), client::Error> {
+        if self.url.scheme == gix_url::Scheme::Ssh {
+            self.url
+                .set_user((!identity.username.is_empty()).then_some(identity.username));
+            Ok(())
+        } else {
+            Err(client::Error::AuthenticationUnsupported)
+        }
+    }
+}
+
+/// Kill `child` and wait for it.
+///
+/// The child process (e.g. `ssh`) may still be running at this point, so kill it before joining/waiting.
+/// In the happy-path case, it should have already exited gracefully, but in error cases or if the user
+/// interrupted the operation, it will likely still be running.
+pub(crate) fn kill(mut child: process::Child) {
+    child.kill().ok();
+    child.wait().ok();
+}
+
+struct ReadStdoutFailOnError {
+    recv: std::sync::mpsc::Receiver<std::io::Error>,
+    read: std::process::ChildStdout,
+}
+
+impl std::io::Read for ReadStdoutFailOnError {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let res = self.read.read(buf);
+        self.swap_err_if_present_in_stderr(buf.len(), res)
+    }
+}
+
+impl ReadStdoutFailOnError {
+    fn swap_err_if_present_in_stderr(&self, wanted: usize, res: std::io::Result<usize>) -> std::io::Result<usize> {
+        match self.recv.try_recv().ok() {
+            Some(err) => Err(err),
+            None => match res {
+                Ok(n) if n == wanted => Ok(n),
+                Ok(n) => {
+                    // TODO: fix this
+                    // When parsing refs this seems to happen legitimately
+                    // (even though we read packet lines only and should always know exactly how much to read)
+                    // Maybe this still happens in `read_exact()` as sometimes we just don't get enough bytes
+                    // despite knowing how many.
+                    // To prevent deadlock, we have to set a timeout which slows down legitimate parts of the protocol.
+                    // This code was specifically written to make the `cargo` test-suite pass, and we can reduce
+                    // the timeouts even more once there is a native ssh transport that is used by `cargo`, it will
+                    // be able to handle these properly.
+                    // Alternatively, one could implement something like `read2` to avoid blocking on stderr entirely.
+                    self.recv
+                        .recv_timeout(std::time::Duration::from_millis(5))
+                        .ok()
+                        .map_or(Ok(n), Err)
+                }
+                Err(err) => Err(self.recv.recv().ok().unwrap_or(err)),
+            },
+        }
+    }
+}
+
+fn supervise_stderr(
+    ssh_kind: ssh::ProgramKind,
+    stderr: std::process::ChildStderr,
+    stdout: std::process::ChildStdout,
+) -> ReadStdoutFailOnError {
+    let (send, recv) = std::sync::mpsc::sync_channel(1);
+    std::thread::Builder::new()
+        .name("supervise ssh stderr".into())
+        .stack_size(128 * 1024)
+        .spawn(move || -> std::io::Result<()> {
+            let mut process_stderr = std::io::stderr();
+            for line in std::io::BufReader::new(stderr).byte_lines() {
+                let line = line?;
+                match ssh_kind.line_to_err(line.into()) {
+                    Ok(err) => {
+                        send.send(err).ok();
+                    }
+                    Err(line) => {
+                        process_stderr.write_all(&line).ok();
+                        writeln!(&process_stderr).ok();
+                    }
+                }
+            }
+            Ok(())
+        })
+        .expect("named threads with small stack work on all platforms");
+    ReadStdoutFailOnError { read: stdout, recv }
+}
@@ -5,7 +5,7 @@ use gix_url::ArgumentSafety::*;
 
 use crate::{
     Protocol,
-    client::blocking_io::ssh::{self, ProgramKind},
+    client::ssh::{self, ProgramKind},
 };
 
 impl ProgramKind {
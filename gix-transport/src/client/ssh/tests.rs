@@ -1,6 +1,6 @@
 mod options {
     mod ssh_command {
-        use crate::client::blocking_io::ssh::{ProgramKind, connect::Options};
+        use crate::client::ssh::{ProgramKind, connect::Options};
 
         #[test]
         fn no_field_means_ssh() {
@@ -46,7 +46,7 @@ mod program_kind {
     mod from_os_str {
         use std::ffi::OsStr;
 
-        use crate::client::blocking_io::ssh::ProgramKind;
+        use crate::client::ssh::ProgramKind;
 
         #[test]
         fn known_variants_are_derived_from_basename() {
@@ -101,7 +101,7 @@ mod program_kind {
 
         use crate::{
             Protocol,
-            client::blocking_io::ssh::{self, ProgramKind},
+            client::ssh::{self, ProgramKind},
         };
 
         #[test]
@@ -280,7 +280,7 @@ mod program_kind {
     mod line_to_err {
         use std::io::ErrorKind;
 
-        use crate::client::blocking_io::ssh::ProgramKind;
+        use crate::client::ssh::ProgramKind;
 
         #[test]
         fn all() {
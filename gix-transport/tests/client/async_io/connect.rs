@@ -0,0 +1,64 @@
+use gix_transport::{
+    Protocol, Service,
+    client::{self, TransportWithoutIO, async_io::Transport},
+};
+
+#[async_std::test]
+async fn file_url_spawns_upload_pack_without_blocking_the_runtime() -> crate::Result {
+    let dir = std::env::temp_dir().join(format!("gix-transport-async-file-{}", std::process::id()));
+    let status = std::process::Command::new(gix_path::env::exe_invocation())
+        .args(["init", "--bare", "--quiet"])
+        .arg(&dir)
+        .status()?;
+    assert!(status.success(), "git can initialize the remote repository");
+
+    let url = gix_url::Url::from_parts(
+        gix_url::Scheme::File,
+        None,
+        None,
+        None,
+        None,
+        gix_path::into_bstr(dir.clone()).into_owned(),
+        false,
+    )?;
+    let res = async {
+        let mut c = client::async_io::connect(
+            url,
+            client::async_io::connect::Options {
+                version: Protocol::V2,
+                ..Default::default()
+            },
+        )
+        .await?;
+        assert!(c.connection_persists_across_multiple_requests());
+        let res = c.handshake(Service::UploadPack, &[]).await?;
+        assert_eq!(res.actual_protocol, Protocol::V2);
+        assert!(res.capabilities.contains("ls-refs"));
+        crate::Result::Ok(())
+    }
+    .await;
+    std::fs::remove_dir_all(&dir)?;
+    res
+}
+
+#[async_std::test]
+async fn ssh_url_is_supported_and_spawns_nothing_until_handshake() -> crate::Result {
+    let c = client::async_io::connect("ssh://user@host.xy/repo.git", Default::default()).await?;
+    assert_eq!(c.to_url().as_ref(), "ssh://user@host.xy/repo.git");
+    Ok(())
+}
+
+#[async_std::test]
+async fn http_urls_need_an_http_implementation() {
+    let err = client::async_io::connect("https://example.com/repo.git", Default::default())
+        .await
+        .err()
+        .expect("there is no built-in async http client");
+    assert!(
+        matches!(
+            err,
+            client::async_io::connect::Error::UnsupportedScheme(gix_url::Scheme::Https)
+        ),
+        "{err:?}"
+    );
+}
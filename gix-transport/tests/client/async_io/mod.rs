@@ -0,0 +1,3 @@
+#[cfg(feature = "async-client-connect")]
+mod connect;
+mod http;
@@ -0,0 +1,45 @@
+use gix_transport::client::{self, blocking_io::connect, ssh};
+
+fn native_ssh() -> connect::Options {
+    connect::Options {
+        ssh: ssh::connect::Options {
+            native: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+#[cfg(not(feature = "ssh-client-native"))]
+fn native_ssh_needs_to_be_compiled_in() {
+    let err = client::blocking_io::connect::connect("ssh://user@host.xy/repo.git", native_ssh())
+        .err()
+        .expect("not compiled in");
+    assert!(matches!(err, connect::Error::CompiledWithoutNativeSsh));
+}
+
+#[test]
+#[cfg(feature = "ssh-client-native")]
+fn native_ssh_connects_only_on_handshake() -> crate::Result {
+    use gix_transport::{
+        Service,
+        client::{TransportWithoutIO, blocking_io::Transport},
+    };
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+    let url = format!("ssh://user@127.0.0.1:{port}/repo.git");
+    let mut c = client::blocking_io::connect::connect(url.as_str(), native_ssh())?;
+    assert_eq!(c.to_url().as_ref(), url.as_str());
+    assert!(c.connection_persists_across_multiple_requests());
+
+    let err = c
+        .handshake(Service::UploadPack, &[])
+        .err()
+        .expect("nothing listens on the port anymore");
+    assert!(
+        matches!(&err, client::Error::Io(err) if err.kind() == std::io::ErrorKind::ConnectionRefused),
+        "{err:?}"
+    );
+    Ok(())
+}
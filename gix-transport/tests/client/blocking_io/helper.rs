@@ -0,0 +1,528 @@
+mod fast_import {
+    use gix_transport::client::blocking_io::helper::fast_import::{
+        Blob, Command, Commit, DataRef, FileChange, Parser, Reset, Tag,
+    };
+
+    fn parse_all(mut input: &[u8]) -> crate::Result<Vec<Command>> {
+        let mut parser = Parser::new(&mut input);
+        let mut out = Vec::new();
+        while let Some(command) = parser.next_command()? {
+            let is_done = command == Command::Done;
+            out.push(command);
+            if is_done {
+                break;
+            }
+        }
+        Ok(out)
+    }
+
+    #[test]
+    fn parse_and_write_round_trip() -> crate::Result {
+        let input = b"feature done
+# a comment
+blob
+mark :1
+original-oid 1111111111111111111111111111111111111111
+data 6
+hello
+
+reset refs/heads/main
+commit refs/heads/main
+mark :2
+author A U Thor <author@example.com> 1112911993 +0100
+committer C O Mitter <committer@example.com> 1112912993 +0100
+data <<EOM
+subject
+
+body
+EOM
+from :1
+merge 2222222222222222222222222222222222222222
+M 100644 :1 a
+M 100755 inline \"with \\\"quotes\\\"\"
+data 3
+sh
+D gone
+C \"with space\" copy
+R old new
+deleteall
+
+tag v1
+from :2
+tagger T A Gger <tagger@example.com> 1112913993 +0100
+data 7
+message
+reset refs/heads/other
+from refs/heads/main
+
+checkpoint
+progress 50%
+done
+trailing data that isn't read";
+        let commands = parse_all(input)?;
+        let expected = vec![
+            Command::Feature("done".into()),
+            Command::Blob(Blob {
+                mark: Some(1),
+                original_oid: Some("1111111111111111111111111111111111111111".into()),
+                data: b"hello\n".to_vec(),
+            }),
+            Command::Reset(Reset {
+                ref_name: "refs/heads/main".into(),
+                from: None,
+            }),
+            Command::Commit(Commit {
+                ref_name: "refs/heads/main".into(),
+                mark: Some(2),
+                original_oid: None,
+                author: Some("A U Thor <author@example.com> 1112911993 +0100".into()),
+                committer: "C O Mitter <committer@example.com> 1112912993 +0100".into(),
+                encoding: None,
+                message: b"subject\n\nbody\n".to_vec(),
+                from: Some(":1".into()),
+                merges: vec!["2222222222222222222222222222222222222222".into()],
+                changes: vec![
+                    FileChange::Modify {
+                        mode: "100644".into(),
+                        data: DataRef::Mark(1),
+                        path: "a".into(),
+                    },
+                    FileChange::Modify {
+                        mode: "100755".into(),
+                        data: DataRef::Inline(b"sh\n".to_vec()),
+                        path: "with \"quotes\"".into(),
+                    },
+                    FileChange::Delete { path: "gone".into() },
+                    FileChange::Copy {
+                        source: "with space".into(),
+                        destination: "copy".into(),
+                    },
+                    FileChange::Rename {
+                        source: "old".into(),
+                        destination: "new".into(),
+                    },
+                    FileChange::DeleteAll,
+                ],
+            }),
+            Command::Tag(Tag {
+                name: "v1".into(),
+                mark: None,
+                from: ":2".into(),
+                original_oid: None,
+                tagger: Some("T A Gger <tagger@example.com> 1112913993 +0100".into()),
+                message: b"message".to_vec(),
+            }),
+            Command::Reset(Reset {
+                ref_name: "refs/heads/other".into(),
+                from: Some("refs/heads/main".into()),
+            }),
+            Command::Checkpoint,
+            Command::Progress("50%".into()),
+            Command::Done,
+        ];
+        assert_eq!(commands, expected);
+
+        let mut written = Vec::new();
+        for command in &commands {
+            command.write_to(&mut written)?;
+        }
+        assert_eq!(
+            parse_all(&written)?,
+            expected,
+            "what's written parses back to the same commands"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        for input in [&b"unknown\n"[..], b"blob\ndata 10\nshort", b"blob\nmark 1\ndata 0\n"] {
+            assert!(parse_all(input).is_err(), "{:?}", std::str::from_utf8(input));
+        }
+    }
+}
+
+#[cfg(unix)]
+mod transport {
+    use std::{
+        io::{BufRead, Read, Write},
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+    };
+
+    use bstr::{BString, ByteSlice};
+    use gix_transport::{
+        Protocol, Service,
+        client::{
+            self, MessageKind, TransportWithoutIO, WriteMode,
+            blocking_io::{
+                Transport,
+                helper::{self, Capabilities, Import, Options, RemoteHelper, fast_import},
+            },
+        },
+    };
+
+    fn helper_program(name: &str) -> PathBuf {
+        Path::new("tests/fixtures/remote-helpers")
+            .join(format!("git-remote-{name}"))
+            .canonicalize()
+            .expect("fixture exists")
+    }
+
+    /// A directory that is removed on drop, to be used as `GIT_DIR`.
+    struct GitDir(PathBuf);
+
+    impl GitDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("gix-transport-helper-{name}-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).expect("temp dir is writable");
+            GitDir(dir)
+        }
+
+        fn log(&self) -> String {
+            std::fs::read_to_string(self.0.join("helper.log")).unwrap_or_default()
+        }
+    }
+
+    impl Drop for GitDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    fn remote_helper(name: &str, url: &str, options: Options) -> crate::Result<RemoteHelper> {
+        let mut transport = RemoteHelper::new(
+            helper_program(name),
+            gix_url::parse(url.as_bytes().as_bstr())?,
+            Protocol::V2,
+            false,
+        );
+        transport
+            .configure(&options)
+            .map_err(|err| err as Box<dyn std::error::Error>)?;
+        Ok(transport)
+    }
+
+    /// Send a V2 `command` with `arguments`, and return all lines of the response.
+    fn request(transport: &mut RemoteHelper, command: &str, arguments: &[&str]) -> crate::Result<Vec<BString>> {
+        let mut writer = transport.request(WriteMode::OneLfTerminatedLinePerWriteCall, MessageKind::Flush, false)?;
+        writer.write_all(format!("command={command}").as_bytes())?;
+        writer.write_message(MessageKind::Delimiter)?;
+        for argument in arguments {
+            writer.write_all(argument.as_bytes())?;
+        }
+        let mut reader = writer.into_read()?;
+        let mut lines = Vec::new();
+        while let Some(line) = reader.readline() {
+            let line = line??;
+            lines.push(line.as_slice().unwrap_or_default().trim_end().into());
+        }
+        Ok(lines)
+    }
+
+    #[test]
+    fn fetch_is_emulated_with_protocol_v2() -> crate::Result {
+        let git_dir = GitDir::new("fetch");
+        let mut transport = remote_helper(
+            "fetching",
+            "fetching::some/address",
+            Options {
+                remote_name: Some("origin".into()),
+                git_dir: Some(git_dir.0.clone()),
+                options: vec![("verbosity".into(), "0".into()), ("progress".into(), "false".into())],
+                import: None,
+            },
+        )?;
+        assert_eq!(transport.to_url().as_ref(), "fetching::some/address");
+
+        {
+            let response = transport.handshake(Service::UploadPack, &[])?;
+            assert_eq!(response.actual_protocol, Protocol::V2);
+            assert!(response.capabilities.contains("ls-refs"));
+            assert!(response.capabilities.contains("fetch"));
+        }
+        assert!(
+            !transport.connection_persists_across_multiple_requests(),
+            "each request is answered on its own"
+        );
+
+        let refs = request(&mut transport, "ls-refs", &["symrefs", "unborn"])?;
+        assert_eq!(
+            refs,
+            [
+                "1111111111111111111111111111111111111111 HEAD symref-target:refs/heads/main",
+                "1111111111111111111111111111111111111111 refs/heads/main",
+                "unborn refs/heads/dangling symref-target:refs/heads/unborn",
+                "2222222222222222222222222222222222222222 refs/tags/v1",
+            ]
+        );
+        let refs = request(&mut transport, "ls-refs", &["ref-prefix refs/tags/"])?;
+        assert_eq!(refs, ["2222222222222222222222222222222222222222 refs/tags/v1"]);
+
+        let mut writer = transport.request(WriteMode::OneLfTerminatedLinePerWriteCall, MessageKind::Flush, false)?;
+        writer.write_all(b"command=fetch")?;
+        writer.write_message(MessageKind::Delimiter)?;
+        writer.write_all(b"want 2222222222222222222222222222222222222222")?;
+        writer.write_all(b"done")?;
+        let mut reader = writer.into_read()?;
+        let line = reader.readline().expect("section header")??;
+        assert_eq!(line.as_slice(), Some(&b"packfile\n"[..]));
+        let mut pack = Vec::new();
+        reader.read_to_end(&mut pack)?;
+        assert_eq!(pack[0], 1, "the pack is sent on the data channel");
+        assert_eq!(
+            &pack[1..13],
+            b"PACK\0\0\0\x02\0\0\0\0",
+            "an empty pack as the objects are there already"
+        );
+        assert_eq!(pack.len(), 1 + 12 + 20);
+        drop(reader);
+
+        let lock_file = git_dir.0.join("fetch.keep");
+        assert!(lock_file.is_file(), "it's kept until the transport is dropped");
+        drop(transport);
+        assert!(!lock_file.exists(), "it's removed once the references are updated");
+        assert_eq!(
+            git_dir.log(),
+            "args origin some/address
+capabilities
+option verbosity 0
+option progress false
+list
+fetch 2222222222222222222222222222222222222222 refs/tags/v1
+
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_fetch_arguments() -> crate::Result {
+        let git_dir = GitDir::new("unsupported");
+        let mut transport = remote_helper(
+            "fetching",
+            "fetching://host/path",
+            Options {
+                git_dir: Some(git_dir.0.clone()),
+                ..Default::default()
+            },
+        )?;
+        transport.handshake(Service::UploadPack, &[])?;
+        let err = request(
+            &mut transport,
+            "fetch",
+            &["want 1111111111111111111111111111111111111111", "deepen 1", "done"],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("'deepen 1' isn't supported"), "{err}");
+        let err = request(
+            &mut transport,
+            "fetch",
+            &["want 3333333333333333333333333333333333333333", "done"],
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("wasn't advertised"), "{err}");
+        drop(transport);
+        assert!(
+            git_dir
+                .log()
+                .starts_with("args fetching://host/path fetching://host/path\n"),
+            "without remote name, the URL is passed twice"
+        );
+        Ok(())
+    }
+
+    /// Parse the stream and pretend that each reference points to the commit with the `:2` mark.
+    #[derive(Default)]
+    struct Importer {
+        commands: Vec<fast_import::Command>,
+        ref_names: Vec<BString>,
+    }
+
+    impl Import for Importer {
+        fn import(
+            &mut self,
+            stream: &mut dyn BufRead,
+            _capabilities: &Capabilities,
+            ref_names: &[BString],
+        ) -> Result<Vec<Option<BString>>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+            let mut parser = fast_import::Parser::new(stream);
+            while let Some(command) = parser.next_command()? {
+                if command == fast_import::Command::Done {
+                    break;
+                }
+                self.commands.push(command);
+            }
+            self.ref_names = ref_names.to_vec();
+            Ok(ref_names
+                .iter()
+                .map(|_| Some("4444444444444444444444444444444444444444".into()))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn references_with_unknown_values_are_imported() -> crate::Result {
+        let git_dir = GitDir::new("import");
+        let importer = Arc::new(Mutex::new(Importer::default()));
+        let mut transport = remote_helper(
+            "importing",
+            "importing::address",
+            Options {
+                git_dir: Some(git_dir.0.clone()),
+                import: Some(importer.clone()),
+                ..Default::default()
+            },
+        )?;
+        transport.handshake(Service::UploadPack, &[])?;
+        let refs = request(&mut transport, "ls-refs", &["symrefs", "ref-prefix HEAD"])?;
+        assert_eq!(
+            refs,
+            ["4444444444444444444444444444444444444444 HEAD symref-target:refs/heads/main"],
+            "the target of HEAD is imported as well"
+        );
+        {
+            let importer = importer.lock().expect("not poisoned");
+            assert_eq!(importer.ref_names, ["refs/importing/main"], "refspecs are applied");
+            assert_eq!(importer.commands.len(), 3, "feature, blob and commit");
+            let fast_import::Command::Commit(commit) = &importer.commands[2] else {
+                panic!("a commit is the last command")
+            };
+            assert_eq!(
+                commit.changes,
+                [fast_import::FileChange::Modify {
+                    mode: "100644".into(),
+                    data: fast_import::DataRef::Mark(1),
+                    path: "a file".into(),
+                }]
+            );
+        }
+
+        let refs = request(&mut transport, "ls-refs", &[])?;
+        assert_eq!(
+            refs,
+            [
+                "4444444444444444444444444444444444444444 refs/heads/main",
+                "4444444444444444444444444444444444444444 HEAD",
+            ],
+            "imports happen only once"
+        );
+        let response = request(
+            &mut transport,
+            "fetch",
+            &["want 4444444444444444444444444444444444444444"],
+        )?;
+        assert_eq!(
+            response[..5],
+            ["acknowledgments", "NAK", "ready", "", "packfile"],
+            "negotiation ends right away, and the packfile section follows after the delimiter"
+        );
+        drop(transport);
+        assert_eq!(
+            git_dir.log(),
+            "capabilities\nlist\nimport refs/heads/main\n\n",
+            "the helper is asked to exit with a blank line"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn import_needs_importer() -> crate::Result {
+        let git_dir = GitDir::new("no-importer");
+        let mut transport = remote_helper(
+            "importing",
+            "importing::address",
+            Options {
+                git_dir: Some(git_dir.0.clone()),
+                ..Default::default()
+            },
+        )?;
+        transport.handshake(Service::UploadPack, &[])?;
+        let err = request(&mut transport, "ls-refs", &[]).unwrap_err();
+        assert!(err.to_string().contains("no importer was configured"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn handshake_errors() -> crate::Result {
+        let git_dir = GitDir::new("errors");
+        let options = Options {
+            git_dir: Some(git_dir.0.clone()),
+            ..Default::default()
+        };
+        let mut transport = remote_helper("failing", "failing::address", options.clone())?;
+        let err = transport.handshake(Service::UploadPack, &[]).err().expect("failure");
+        assert!(
+            matches!(
+                &err,
+                client::Error::RemoteHelper(helper::Error::UnsupportedCapability { capability }) if capability == "frobnicate"
+            ),
+            "{err:?}"
+        );
+
+        let mut transport = remote_helper("fetching", "fetching::address", options)?;
+        let err = transport.handshake(Service::ReceivePack, &[]).err().expect("failure");
+        assert!(
+            matches!(
+                &err,
+                client::Error::RemoteHelper(helper::Error::UnsupportedService {
+                    service: Service::ReceivePack
+                })
+            ),
+            "{err:?}"
+        );
+
+        let err = helper::connect(
+            gix_url::parse("does-not-exist::address".as_bytes().as_bstr())?,
+            Protocol::V2,
+            false,
+        )
+        .err()
+        .expect("not found");
+        assert!(matches!(err, helper::Error::NotFound { transport } if transport == "does-not-exist"));
+        Ok(())
+    }
+
+    #[test]
+    fn helper_commands() -> crate::Result {
+        let git_dir = GitDir::new("commands");
+        let mut helper = helper::Helper::spawn(
+            helper_program("fetching"),
+            "origin".into(),
+            "address".into(),
+            Some(&git_dir.0),
+            None,
+            false,
+        )?;
+        let capabilities = helper.capabilities();
+        assert!(capabilities.contains("fetch"));
+        assert!(!capabilities.contains("import"));
+        assert_eq!(
+            capabilities.private_ref_name("refs/heads/main".into()),
+            "refs/fetching/main"
+        );
+        assert_eq!(
+            capabilities.private_ref_name("refs/tags/v1".into()),
+            "refs/tags/v1",
+            "refs that don't match are used as is"
+        );
+
+        assert!(helper.option("verbosity".into(), "1".into())?);
+        assert!(!helper.option("depth".into(), "1".into())?);
+        let list = helper.list(false)?;
+        assert_eq!(list.refs.len(), 4);
+        assert_eq!(list.refs[0].value, helper::Value::Symbolic("refs/heads/main".into()));
+        assert_eq!(list.refs[3].attributes, ["unchanged"]);
+        assert!(matches!(
+            helper.import(&["refs/heads/main".into()]),
+            Err(helper::Error::MissingCapability { capability: "import" })
+        ));
+        let outcome = helper.fetch(&[(
+            "1111111111111111111111111111111111111111".into(),
+            "refs/heads/main".into(),
+        )])?;
+        assert!(outcome.connectivity_ok);
+        assert_eq!(outcome.lock_files, [git_dir.0.join("fetch.keep")]);
+        Ok(())
+    }
+}
@@ -1,2 +1,4 @@
+mod connect;
+mod helper;
 #[cfg(any(feature = "http-client-curl", feature = "http-client-reqwest"))]
 mod http;
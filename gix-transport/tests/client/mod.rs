@@ -1,3 +1,5 @@
+#[cfg(all(feature = "async-client", not(feature = "blocking-client")))]
+mod async_io;
 #[cfg(feature = "blocking-client")]
 mod blocking_io;
 mod capabilities;
@@ -57,6 +57,7 @@ pub fn parse(input: impl AsBStr) -> Result<Url, parse::Error> {
             parse::file_url(input, protocol_end)
         }
         InputScheme::Url { protocol_end } => parse::url(input, protocol_end),
+        InputScheme::Helper { transport_end } => Ok(parse::helper(input, transport_end)),
         InputScheme::Scp { colon } => parse::scp(input, colon),
     }
 }
@@ -147,7 +148,8 @@ pub struct Url {
     pub host: Option<String>,
     /// When serializing, use the alternative forms as it was parsed as such.
     ///
-    /// Alternative forms include SCP-like syntax (`user@host:path`) and bare file paths.
+    /// Alternative forms include SCP-like syntax (`user@host:path`), bare file paths and `<transport>::<address>`
+    /// for remote helpers, with the address stored in [`path`](Self::path).
     /// When `true`, password and port cannot be serialized (will panic in debug builds).
     pub serialize_alternative_form: bool,
     /// The port to use when connecting to a host. If `None`, standard ports depending on `scheme` will be used.
@@ -384,6 +386,10 @@ impl Url {
             && self.port.is_none()
         {
             self.write_alternative_form_to(out)
+        } else if self.serialize_alternative_form && matches!(self.scheme, Scheme::Ext(_)) && self.host.is_none() {
+            out.write_all(self.scheme.as_str().as_bytes())?;
+            out.write_all(b"::")?;
+            out.write_all(&self.path)
         } else {
             self.write_canonical_form_to(out)
         }
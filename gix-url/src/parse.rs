@@ -58,11 +58,22 @@ impl UrlKind {
 
 pub(crate) enum InputScheme {
     Url { protocol_end: usize },
+    Helper { transport_end: usize },
     Scp { colon: usize },
     Local,
 }
 
 pub(crate) fn find_scheme(input: &BStr) -> InputScheme {
+    // Like `git`, `<transport>::<address>` selects the remote helper for `transport`, even if `address` is a URL itself.
+    let transport_end = input
+        .iter()
+        .enumerate()
+        .position(|(idx, b)| !(b.is_ascii_alphanumeric() || (idx != 0 && matches!(b, b'+' | b'-' | b'.'))))
+        .unwrap_or(input.len());
+    if transport_end != 0 && input[transport_end..].starts_with(b"::") {
+        return InputScheme::Helper { transport_end };
+    }
+
     // TODO: url's may only contain `:/`, we should additionally check if the characters used for
     //       protocol are all valid
     if let Some(protocol_end) = input.find("://") {
@@ -179,6 +190,18 @@ pub(crate) fn url(input: &BStr, protocol_end: usize) -> Result<crate::Url, Error
     })
 }
 
+pub(crate) fn helper(input: &BStr, transport_end: usize) -> crate::Url {
+    crate::Url {
+        serialize_alternative_form: true,
+        scheme: Scheme::Ext(input[..transport_end].to_str_lossy().into_owned()),
+        user: None,
+        password: None,
+        host: None,
+        port: None,
+        path: input[transport_end + "::".len()..].into(),
+    }
+}
+
 pub(crate) fn scp(input: &BStr, colon: usize) -> Result<crate::Url, Error> {
     let input = input_to_utf8(input, UrlKind::Scp)?;
 
@@ -16,7 +16,8 @@ pub enum Scheme {
     Https,
     /// Any other protocol or transport that isn't known at compile time.
     ///
-    /// It's used to support plug-in transports.
+    /// It's used to support plug-in transports, like remote helpers named `git-remote-<name>` which are selected
+    /// by URLs like `<name>://…` or `<name>::<address>`.
     Ext(String),
 }
 
@@ -4,8 +4,8 @@ use gix_url::parse::Error::*;
 use crate::parse::parse;
 
 #[test]
-fn relative_path_due_to_double_colon() {
-    assert_matches!(parse("invalid:://host.xz/path/to/repo.git/"), Err(RelativeUrl { .. }));
+fn relative_path_due_to_colon_in_scheme() {
+    assert_matches!(parse("in:valid://host.xz/path/to/repo.git/"), Err(RelativeUrl { .. }));
 }
 
 #[test]
@@ -162,4 +162,32 @@ mod unknown {
             url(Scheme::Ext("abc".into()), None, "example.com", None, b"/~byron/hello"),
         )
     }
+
+    #[test]
+    fn transport_prefix_selects_a_remote_helper_with_the_address_as_path() -> crate::Result {
+        for (input, transport, address) in [
+            ("hg::https://example.com/repo", "hg", "https://example.com/repo"),
+            ("foo::bar", "foo", "bar"),
+            ("my-helper.v2::/path/to/store", "my-helper.v2", "/path/to/store"),
+            ("s3::", "s3", ""),
+            ("invalid:://host.xz/path", "invalid", "//host.xz/path"),
+        ] {
+            let url = gix_url::parse(input)?;
+            assert_eq!(url.scheme, Scheme::Ext(transport.into()));
+            assert_eq!(url.path, address);
+            assert_eq!((url.host(), url.user()), (None, None));
+            assert_eq!(url.to_bstring(), input, "it round-trips");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn transport_prefix_needs_a_valid_transport_name() -> crate::Result {
+        assert!(
+            !matches!(gix_url::parse("-foo::bar")?.scheme, Scheme::Ext(_)),
+            "transports can't start with a dash"
+        );
+        assert_eq!(gix_url::parse("user@host:path::with-colons")?.scheme, Scheme::Ssh);
+        Ok(())
+    }
 }
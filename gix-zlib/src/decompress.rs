@@ -43,6 +43,16 @@ impl Decompress {
         Self(inner)
     }
 
+    /// Create a new instance that decompresses streams with a `gzip` header, like HTTP bodies sent with `Content-Encoding: gzip`.
+    ///
+    /// Note that [`reset()`](Self::reset()) turns it into an instance that expects a zlib header.
+    pub fn new_gzip() -> Self {
+        let config = zlib_rs::InflateConfig::default();
+        let header = true;
+        let inner = zlib_rs::Inflate::new(header, 16 + config.window_bits as u8);
+        Self(inner)
+    }
+
     /// Reset the state to allow handling a new stream.
     pub fn reset(&mut self) {
         self.0.reset(true);
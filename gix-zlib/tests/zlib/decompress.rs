@@ -30,3 +30,28 @@ fn lifecycle_counters_and_flush_modes() {
         Status::StreamEnd
     );
 }
+
+#[test]
+fn gzip_streams() {
+    // `printf 'hello gzip' | gzip -n`
+    let input = [
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0x48, 0xaf,
+        0xca, 0x2c, 0x00, 0x00, 0x19, 0x6a, 0xd2, 0xdf, 0x0a, 0x00, 0x00, 0x00,
+    ];
+    let mut output = [0; 32];
+    let mut state = Decompress::new_gzip();
+    assert_eq!(
+        state
+            .decompress(&input, &mut output, FlushDecompress::Finish)
+            .expect("valid gzip stream"),
+        Status::StreamEnd
+    );
+    assert_eq!(&output[..state.total_out() as usize], b"hello gzip");
+
+    assert!(
+        Decompress::new()
+            .decompress(&input, &mut output, FlushDecompress::Finish)
+            .is_err(),
+        "zlib streams are expected by default"
+    );
+}
@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_ref::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+
+use super::{Error, Options, Outcome, Target, update};
+use crate::{Repository, bstr::BString};
+
+impl Repository {
+    /// Check out `target` by updating the worktree and the index to match its tree, and point `HEAD` to it,
+    /// similar to `git checkout` or `git switch`.
+    ///
+    /// Only files that differ between the current `HEAD` and `target` are written or removed, using all configured filters.
+    /// If this would overwrite local changes to these files, or untracked files that are in the way, the operation fails
+    /// with [conflicts](Error::Conflicts) before anything was changed, unless [`force`](Options::force) is set.
+    /// Local changes to all other files are kept.
+    ///
+    /// `HEAD` is moved last, and a `checkout: moving from <old> to <new>` entry is written into its reflog.
+    /// Use `progress` to learn about the amount of files and bytes written, and `should_interrupt` to stop
+    /// the operation early, which leaves the index and `HEAD` untouched.
+    pub fn checkout<P>(
+        &self,
+        target: impl Into<Target>,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error>
+    where
+        P: gix_features::progress::NestedProgress,
+        P::SubProgress: gix_features::progress::NestedProgress + 'static,
+    {
+        self.checkout_inner(target.into(), &mut progress, should_interrupt, options)
+    }
+
+    fn checkout_inner(
+        &self,
+        target: Target,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+        Options { force }: Options,
+    ) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::checkout()");
+        let workdir = self.workdir().ok_or_else(|| Error::BareRepository {
+            git_dir: self.git_dir().to_owned(),
+        })?;
+
+        let new_commit = match &target {
+            Target::Branch(name) => self.find_reference(name.as_ref())?.peel_to_id()?.detach(),
+            Target::Detached(id) => *id,
+        };
+        let target_tree = self.find_commit(new_commit)?.tree_id()?.detach();
+        let head_tree = self.head_tree_id_or_empty()?.detach();
+
+        let mut index = self.checkout_index_from_disk()?;
+        let head = self.checkout_index_from_tree(head_tree)?;
+        let target_state = self.checkout_index_from_tree(target_tree)?;
+        let worktree = self.checkout_worktree_changes(&index, should_interrupt)?;
+
+        let mut plan = update::two_way_merge(workdir, &index, &head, &target_state, &worktree, force)
+            .map_err(|conflicts| Error::Conflicts { conflicts })?;
+        let outcome = self.checkout_apply(workdir, &mut plan, progress, should_interrupt)?;
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+
+        *index = plan.index;
+        index.write(self.index_write_options()?)?;
+        self.checkout_move_head(&target, new_commit)?;
+        Ok(outcome)
+    }
+
+    /// Point `HEAD` to `target`, which points to `new_commit`, and log this in the reflog of `HEAD`.
+    fn checkout_move_head(&self, target: &Target, new_commit: gix_hash::ObjectId) -> Result<(), Error> {
+        let head = self.head()?;
+        let from: BString = match head.referent_name() {
+            Some(name) => name.shorten().to_owned(),
+            None => head.id().map(|id| id.to_string()).unwrap_or_default().into(),
+        };
+        let to: BString = match target {
+            Target::Branch(name) => name.shorten().to_owned(),
+            Target::Detached(id) => id.to_string().into(),
+        };
+        let log = |mode| LogChange {
+            mode,
+            force_create_reflog: false,
+            message: format!("checkout: moving from {from} to {to}").into(),
+        };
+        let head_name: gix_ref::FullName = "HEAD".try_into().expect("valid");
+        match target {
+            Target::Branch(name) => {
+                // Symbolic refs don't get a reflog entry, so write it separately while `HEAD` still points to the previous commit.
+                // Like Git, log it even if the previous commit is the same, as it's still a switch to another branch.
+                let (file_lock_fail, packed_refs_lock_fail) = self
+                    .config
+                    .lock_timeout()
+                    .map_err(crate::reference::edit::Error::from)?;
+                self.refs
+                    .transaction()
+                    .log_unchanged_values(true)
+                    .prepare(
+                        Some(RefEdit {
+                            change: Change::Update {
+                                log: log(RefLog::Only),
+                                expected: PreviousValue::Any,
+                                new: gix_ref::Target::Object(new_commit),
+                            },
+                            name: head_name.clone(),
+                            deref: false,
+                        }),
+                        file_lock_fail,
+                        packed_refs_lock_fail,
+                    )
+                    .map_err(crate::reference::edit::Error::from)?
+                    .commit(
+                        self.committer()
+                            .transpose()
+                            .map_err(crate::reference::edit::Error::from)?,
+                    )
+                    .map_err(crate::reference::edit::Error::from)?;
+                self.edit_reference(RefEdit {
+                    change: Change::Update {
+                        log: log(RefLog::AndReference),
+                        expected: PreviousValue::Any,
+                        new: gix_ref::Target::Symbolic(name.clone()),
+                    },
+                    name: head_name,
+                    deref: false,
+                })?;
+            }
+            Target::Detached(id) => {
+                self.edit_reference(RefEdit {
+                    change: Change::Update {
+                        log: log(RefLog::AndReference),
+                        expected: PreviousValue::Any,
+                        new: gix_ref::Target::Object(*id),
+                    },
+                    name: head_name,
+                    deref: false,
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
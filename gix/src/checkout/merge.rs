@@ -0,0 +1,576 @@
+//! Merge another commit into `HEAD`, the index and the worktree, and persist unresolved conflicts so the merge can be
+//! [continued](crate::Repository::merge_continue()) or [aborted](crate::Repository::merge_abort()) later, by us or by `git`.
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_index::entry::Stage;
+use gix_ref::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+
+use super::{Conflict, MERGE_HEAD, MERGE_MODE, MERGE_MSG, Target, update};
+use crate::{
+    Repository,
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    merge::tree::{TreatAsUnresolved, apply_index_entries::RemovalMode},
+};
+
+/// Control if and when [`Repository::merge()`] may fast-forward instead of creating a merge commit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FastForward {
+    /// Fast-forward if possible, and create a merge commit otherwise.
+    #[default]
+    Allow,
+    /// Always create a merge commit, like `git merge --no-ff`.
+    Never,
+    /// Only fast-forward and fail otherwise, like `git merge --ff-only`.
+    Only,
+}
+
+/// Options for use in [`Repository::merge()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Determine if fast-forwards are allowed.
+    pub fast_forward: FastForward,
+    /// If `true`, create the merge commit right away if there are no conflicts.
+    /// Otherwise, the merge is left in progress like with `git merge --no-commit` so it can be [continued](Repository::merge_continue()).
+    pub commit: bool,
+    /// The message of the merge commit, or `None` to generate one like `Merge branch 'name'`.
+    pub message: Option<BString>,
+}
+
+impl Default for Options {
+    /// Fast-forward if possible, and commit the merge if there is no conflict, like `git merge` does.
+    fn default() -> Self {
+        Options {
+            fast_forward: FastForward::Allow,
+            commit: true,
+            message: None,
+        }
+    }
+}
+
+/// The outcome of [`Repository::merge()`].
+#[derive(Debug)]
+pub enum Outcome {
+    /// The commit to merge is already reachable from `HEAD`, so nothing was changed.
+    UpToDate,
+    /// `HEAD` was fast-forwarded to the commit to merge.
+    FastForward {
+        /// The commit `HEAD` now points to.
+        commit: gix_hash::ObjectId,
+        /// What changed in the worktree.
+        checkout: super::Outcome,
+    },
+    /// The merge had no conflicts and was committed.
+    Merged {
+        /// The newly created merge commit `HEAD` now points to.
+        commit: gix_hash::ObjectId,
+        /// What changed in the worktree.
+        checkout: super::Outcome,
+    },
+    /// The merge was written to the index and the worktree, and is now in progress until it is
+    /// [continued](Repository::merge_continue()) or [aborted](Repository::merge_abort()).
+    InProgress {
+        /// The paths that have conflicts, with their stages in the index and conflict markers in the worktree.
+        /// It's empty if the merge wasn't committed because [`Options::commit`] was `false`.
+        unmerged_paths: Vec<BString>,
+        /// What changed in the worktree.
+        checkout: super::Outcome,
+    },
+}
+
+/// The error returned by [`Repository::merge()`], [`Repository::merge_continue()`] and [`Repository::merge_abort()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Repository at \"{}\" is a bare repository and cannot merge into its worktree", git_dir.display())]
+    BareRepository { git_dir: std::path::PathBuf },
+    #[error("A merge is already in progress and needs to be continued or aborted first")]
+    MergeInProgress,
+    #[error("There is no merge in progress as MERGE_HEAD is missing")]
+    NoMergeInProgress,
+    #[error("{} path(s) have local changes that would be overwritten by the merge", conflicts.len())]
+    Conflicts { conflicts: Vec<Conflict> },
+    #[error("The merge can't be fast-forwarded")]
+    NotFastForward,
+    #[error("{} path(s) are still unmerged and need to be resolved first", rela_paths.len())]
+    UnmergedPaths { rela_paths: Vec<BString> },
+    #[error("Refusing to commit the merge with an empty message")]
+    EmptyMessage,
+    #[error("Could not parse '{}' from MERGE_HEAD", line)]
+    ParseMergeHead {
+        line: BString,
+        source: gix_hash::decode::Error,
+    },
+    #[error("The merge was interrupted before the index and HEAD were updated")]
+    Interrupted,
+    #[error("Could not access merge state at \"{}\"", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    HeadId(#[from] crate::reference::head_id::Error),
+    #[error(transparent)]
+    FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    MergeBase(#[from] crate::repository::merge_base::Error),
+    #[error(transparent)]
+    TreeMergeOptions(#[from] crate::repository::tree_merge_options::Error),
+    #[error(transparent)]
+    MergeCommits(#[from] crate::repository::merge_commits::Error),
+    #[error(transparent)]
+    EditTree(#[from] crate::repository::edit_tree::Error),
+    #[error(transparent)]
+    UpsertTreeEntry(#[from] gix_object::tree::editor::Error),
+    #[error(transparent)]
+    WriteTree(#[from] crate::object::tree::editor::write::Error),
+    #[error(transparent)]
+    NewCommit(#[from] crate::repository::new_commit::Error),
+    #[error(transparent)]
+    Update(#[from] update::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::repository::index_write_options::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+}
+
+impl Repository {
+    /// Merge `target` into `HEAD`, updating the index and the worktree, similar to `git merge`.
+    ///
+    /// If `HEAD` can be fast-forwarded, only files that differ are written. Otherwise, the commits are merged and,
+    /// if there are no conflicts, the merge is committed with a `merge <name>: Merge made by the 'ort' strategy.` entry in the reflog.
+    /// Conflicts are written as stages into the index and with conflict markers into the worktree, along with
+    /// `MERGE_HEAD`, `MERGE_MSG` and `MERGE_MODE` so the merge can be resolved, and then be finished with
+    /// [`merge_continue()`](Self::merge_continue()) or `git commit`, or be [aborted](Self::merge_abort()).
+    /// `ORIG_HEAD` is set to the commit `HEAD` pointed to before the merge.
+    ///
+    /// The merge is refused if the index doesn't match `HEAD`, or if it would overwrite local changes in the worktree.
+    /// Use `progress` to learn about the amount of files and bytes written, and `should_interrupt` to stop
+    /// the operation early, which leaves the index and `HEAD` untouched.
+    pub fn merge<P>(
+        &self,
+        target: impl Into<Target>,
+        options: Options,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error>
+    where
+        P: gix_features::progress::NestedProgress,
+        P::SubProgress: gix_features::progress::NestedProgress + 'static,
+    {
+        self.merge_inner(target.into(), options, &mut progress, should_interrupt)
+    }
+
+    /// Finish the merge that is in progress by committing the index with the message in `MERGE_MSG` and all commits
+    /// in `MERGE_HEAD` as additional parents, and return the id of the new commit. Lines starting with `#` are removed
+    /// from the message, just like `git merge --continue` would.
+    ///
+    /// This fails if there are still unmerged paths in the index.
+    pub fn merge_continue(&self) -> Result<gix_hash::ObjectId, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::merge_continue()");
+        let merge_heads = self.merge_heads()?;
+        let index = self.checkout_index_from_disk()?;
+        let unmerged_paths = unmerged_paths(&index);
+        if !unmerged_paths.is_empty() {
+            return Err(Error::UnmergedPaths {
+                rela_paths: unmerged_paths,
+            });
+        }
+
+        let mut tree = self.edit_tree(gix_hash::ObjectId::empty_tree(self.object_hash()))?;
+        for entry in index.entries() {
+            if let Some(mode) = entry.mode.to_tree_entry_mode() {
+                tree.upsert(entry.path(&index), mode.kind(), entry.id)?;
+            }
+        }
+        let tree = tree.write()?;
+
+        let message = self.read_merge_state_file(MERGE_MSG)?.unwrap_or_default();
+        let message = cleanup_message(message.as_ref());
+        if message.is_empty() {
+            return Err(Error::EmptyMessage);
+        }
+        let head = self.head_id()?.detach();
+        let commit = self
+            .new_commit(message.to_str_lossy(), tree, std::iter::once(head).chain(merge_heads))?
+            .id;
+        self.merge_move_head(
+            head,
+            commit,
+            format!(
+                "commit (merge): {}",
+                gix_object::commit::MessageRef::from_bytes(&message).summary()
+            ),
+        )?;
+        self.checkout_remove_merge_state()?;
+        Ok(commit)
+    }
+
+    /// Abort the merge that is in progress by making the index and the worktree match `ORIG_HEAD` again, like `git merge --abort`.
+    ///
+    /// Only files that are unmerged or were changed by the merge are written or removed, while local changes to
+    /// all other files are kept. Use `progress` to learn about the amount of files and bytes written,
+    /// and `should_interrupt` to stop the operation early, which leaves the index and the merge state untouched.
+    pub fn merge_abort<P>(&self, mut progress: P, should_interrupt: &AtomicBool) -> Result<super::Outcome, Error>
+    where
+        P: gix_features::progress::NestedProgress,
+        P::SubProgress: gix_features::progress::NestedProgress + 'static,
+    {
+        self.merge_abort_inner(&mut progress, should_interrupt)
+    }
+
+    fn merge_inner(
+        &self,
+        target: Target,
+        options: Options,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::merge()");
+        let workdir = self.workdir().ok_or_else(|| Error::BareRepository {
+            git_dir: self.git_dir().to_owned(),
+        })?;
+        if self.read_merge_state_file(MERGE_HEAD)?.is_some() {
+            return Err(Error::MergeInProgress);
+        }
+
+        let (their, their_name) = match &target {
+            Target::Branch(name) => (
+                self.find_reference(name.as_ref())?.peel_to_id()?.detach(),
+                name.shorten().to_owned(),
+            ),
+            Target::Detached(id) => (*id, id.to_string().into()),
+        };
+        let our = self.head_id()?.detach();
+        let merge_base = self.merge_base(our, their).map(crate::Id::detach);
+        match merge_base {
+            Ok(base) if base == their => return Ok(Outcome::UpToDate),
+            Ok(base) if base == our && options.fast_forward != FastForward::Never => {
+                let checkout =
+                    self.merge_fast_forward(workdir, our, their, their_name.as_ref(), progress, should_interrupt)?;
+                return Ok(Outcome::FastForward {
+                    commit: their,
+                    checkout,
+                });
+            }
+            Ok(_) | Err(crate::repository::merge_base::Error::NotFound { .. })
+                if options.fast_forward == FastForward::Only =>
+            {
+                return Err(Error::NotFastForward);
+            }
+            Ok(_) | Err(crate::repository::merge_base::Error::NotFound { .. }) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let mut index = self.checkout_index_from_disk()?;
+        let our_tree = self.find_commit(our)?.tree_id()?.detach();
+        let head = self.checkout_index_from_tree(our_tree)?;
+        let staged_changes = staged_changes(&index, &head);
+        if !staged_changes.is_empty() {
+            return Err(Error::Conflicts {
+                conflicts: staged_changes,
+            });
+        }
+
+        let mut merge = self.merge_commits(
+            our,
+            their,
+            gix_merge::blob::builtin_driver::text::Labels {
+                ancestor: None,
+                current: Some("HEAD".into()),
+                other: Some(their_name.as_ref()),
+            },
+            self.tree_merge_options()?.into(),
+        )?;
+        let merged_tree = merge.tree_merge.tree.write()?.detach();
+        let target_state = self.checkout_index_from_tree(merged_tree)?;
+        let worktree = self.checkout_worktree_changes(&index, should_interrupt)?;
+        let mut plan = update::two_way_merge(workdir, &index, &head, &target_state, &worktree, false)
+            .map_err(|conflicts| Error::Conflicts { conflicts })?;
+
+        let checkout = self.checkout_apply(workdir, &mut plan, progress, should_interrupt)?;
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        self.checkout_set_orig_head(our)?;
+
+        let how = TreatAsUnresolved::git();
+        let has_conflicts = merge.tree_merge.has_unresolved_conflicts(how);
+        *index = plan.index;
+        merge
+            .tree_merge
+            .index_changed_after_applying_conflicts(&mut index, how, RemovalMode::Prune);
+        index.write(self.index_write_options()?)?;
+
+        let message = match options.message {
+            Some(message) => message,
+            None => self.merge_message(&target, their_name.as_ref())?,
+        };
+        if !has_conflicts && options.commit {
+            let commit = self.new_commit(message.to_str_lossy(), merged_tree, [our, their])?.id;
+            self.merge_move_head(
+                our,
+                commit,
+                format!("merge {their_name}: Merge made by the 'ort' strategy."),
+            )?;
+            return Ok(Outcome::Merged { commit, checkout });
+        }
+
+        let unmerged_paths = unmerged_paths(&index);
+        let mut merge_msg = message;
+        if !merge_msg.ends_with(b"\n") {
+            merge_msg.push(b'\n');
+        }
+        if !unmerged_paths.is_empty() {
+            merge_msg.push_str("\n# Conflicts:\n");
+            for path in &unmerged_paths {
+                merge_msg.push_str("#\t");
+                merge_msg.extend_from_slice(path);
+                merge_msg.push(b'\n');
+            }
+        }
+        self.write_merge_state_file(MERGE_HEAD, format!("{their}\n").as_bytes())?;
+        self.write_merge_state_file(MERGE_MSG, &merge_msg)?;
+        self.write_merge_state_file(
+            MERGE_MODE,
+            if options.fast_forward == FastForward::Never {
+                b"no-ff"
+            } else {
+                b""
+            },
+        )?;
+        Ok(Outcome::InProgress {
+            unmerged_paths,
+            checkout,
+        })
+    }
+
+    fn merge_abort_inner(
+        &self,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<super::Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::merge_abort()");
+        let workdir = self.workdir().ok_or_else(|| Error::BareRepository {
+            git_dir: self.git_dir().to_owned(),
+        })?;
+        self.merge_heads()?;
+
+        let head = self.head_id()?.detach();
+        let orig_head = match self
+            .try_find_reference("ORIG_HEAD")
+            .map_err(crate::reference::find::existing::Error::from)?
+        {
+            Some(mut orig_head) => orig_head.peel_to_id()?.detach(),
+            None => head,
+        };
+        let mut index = self.checkout_index_from_disk()?;
+        let tree = self.find_commit(orig_head)?.tree_id()?.detach();
+        let target = self.checkout_index_from_tree(tree)?;
+        // Without any known worktree changes, only paths that differ from the target in the index are updated,
+        // which keeps local changes to files the merge didn't touch.
+        let mut plan = update::two_way_merge(workdir, &index, &target, &target, &BTreeMap::new(), true)
+            .unwrap_or_else(|_| unreachable!("forced merges don't conflict"));
+        let outcome = self.checkout_apply(workdir, &mut plan, progress, should_interrupt)?;
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        *index = plan.index;
+        index.write(self.index_write_options()?)?;
+
+        if orig_head != head {
+            self.merge_move_head(head, orig_head, format!("reset: moving to {orig_head}"))?;
+        }
+        self.checkout_remove_merge_state()?;
+        Ok(outcome)
+    }
+
+    fn merge_fast_forward(
+        &self,
+        workdir: &Path,
+        our: gix_hash::ObjectId,
+        their: gix_hash::ObjectId,
+        their_name: &BStr,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<super::Outcome, Error> {
+        let mut index = self.checkout_index_from_disk()?;
+        let head = self.checkout_index_from_tree(self.find_commit(our)?.tree_id()?.detach())?;
+        let target = self.checkout_index_from_tree(self.find_commit(their)?.tree_id()?.detach())?;
+        let worktree = self.checkout_worktree_changes(&index, should_interrupt)?;
+        let mut plan = update::two_way_merge(workdir, &index, &head, &target, &worktree, false)
+            .map_err(|conflicts| Error::Conflicts { conflicts })?;
+
+        let outcome = self.checkout_apply(workdir, &mut plan, progress, should_interrupt)?;
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        self.checkout_set_orig_head(our)?;
+        *index = plan.index;
+        index.write(self.index_write_options()?)?;
+        self.merge_move_head(our, their, format!("merge {their_name}: Fast-forward"))?;
+        Ok(outcome)
+    }
+
+    /// Generate the message git would use when merging `target` named `their_name` into the current branch.
+    fn merge_message(&self, target: &Target, their_name: &BStr) -> Result<BString, Error> {
+        let mut message = BString::from(match target {
+            Target::Branch(name) if name.as_bstr().starts_with(b"refs/heads/") => {
+                format!("Merge branch '{their_name}'")
+            }
+            Target::Branch(name) if name.as_bstr().starts_with(b"refs/remotes/") => {
+                format!("Merge remote-tracking branch '{their_name}'")
+            }
+            Target::Branch(name) if name.as_bstr().starts_with(b"refs/tags/") => format!("Merge tag '{their_name}'"),
+            Target::Branch(_) | Target::Detached(_) => format!("Merge commit '{their_name}'"),
+        });
+        // Like `merge.suppressDest` does by default, don't mention the most common branch names.
+        let head = self.head_name()?;
+        let destination = head.as_ref().map_or("HEAD".into(), gix_ref::FullName::shorten);
+        if destination != "main" && destination != "master" {
+            message.push_str(" into ");
+            message.extend_from_slice(destination);
+        }
+        Ok(message)
+    }
+
+    /// Move `HEAD`, or the branch it points to, from `previous` to `new`, logging `message`.
+    fn merge_move_head(
+        &self,
+        previous: gix_hash::ObjectId,
+        new: gix_hash::ObjectId,
+        message: String,
+    ) -> Result<(), Error> {
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: message.into(),
+                },
+                expected: PreviousValue::MustExistAndMatch(gix_ref::Target::Object(previous)),
+                new: gix_ref::Target::Object(new),
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: true,
+        })?;
+        Ok(())
+    }
+
+    /// Return the ids of all commits in `MERGE_HEAD`, or fail if there is no merge in progress.
+    fn merge_heads(&self) -> Result<Vec<gix_hash::ObjectId>, Error> {
+        let merge_head = self
+            .read_merge_state_file(MERGE_HEAD)?
+            .ok_or(Error::NoMergeInProgress)?;
+        merge_head
+            .lines()
+            .map(ByteSlice::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                gix_hash::ObjectId::from_hex(line).map_err(|source| Error::ParseMergeHead {
+                    line: line.into(),
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    fn read_merge_state_file(&self, name: &str) -> Result<Option<BString>, Error> {
+        let path = self.git_dir().join(name);
+        match std::fs::read(&path) {
+            Ok(content) => Ok(Some(content.into())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(Error::Io { path, source }),
+        }
+    }
+
+    fn write_merge_state_file(&self, name: &str, content: &[u8]) -> Result<(), Error> {
+        let path = self.git_dir().join(name);
+        std::fs::write(&path, content).map_err(|source| Error::Io { path, source })
+    }
+}
+
+/// Return the paths of all unmerged entries in `index`, once each.
+fn unmerged_paths(index: &gix_index::State) -> Vec<BString> {
+    let mut paths: Vec<BString> = Vec::new();
+    for entry in index
+        .entries()
+        .iter()
+        .filter(|entry| entry.stage() != Stage::Unconflicted)
+    {
+        let path = entry.path(index);
+        if paths.last().is_none_or(|last| last != path) {
+            paths.push(path.to_owned());
+        }
+    }
+    paths
+}
+
+/// Return all paths at which `index` differs from `head`, the state of the tree `HEAD` points to.
+fn staged_changes(index: &gix_index::State, head: &gix_index::State) -> Vec<Conflict> {
+    let mut changes = BTreeMap::<&BStr, Conflict>::new();
+    for entry in index.entries() {
+        let rela_path = entry.path(index);
+        if entry.stage() != Stage::Unconflicted {
+            changes.insert(
+                rela_path,
+                Conflict::Unmerged {
+                    rela_path: rela_path.to_owned(),
+                },
+            );
+        } else if head
+            .entry_by_path_and_stage(rela_path, Stage::Unconflicted)
+            .is_none_or(|head| head.id != entry.id || head.mode != entry.mode)
+        {
+            changes.insert(
+                rela_path,
+                Conflict::LocalChange {
+                    rela_path: rela_path.to_owned(),
+                },
+            );
+        }
+    }
+    for entry in head.entries() {
+        let rela_path = entry.path(head);
+        if index.entry_index_by_path(rela_path).is_err() {
+            changes.insert(
+                rela_path,
+                Conflict::LocalChange {
+                    rela_path: rela_path.to_owned(),
+                },
+            );
+        }
+    }
+    changes.into_values().collect()
+}
+
+/// Remove comment lines and surrounding or repeated empty lines from `message`, like `git commit --cleanup=strip` does.
+fn cleanup_message(message: &BStr) -> BString {
+    let mut out = BString::default();
+    let mut pending_empty_line = false;
+    for line in message.lines().filter(|line| !line.starts_with(b"#")) {
+        let line = line.trim_end();
+        if line.is_empty() {
+            pending_empty_line = !out.is_empty();
+            continue;
+        }
+        if pending_empty_line {
+            out.push(b'\n');
+            pending_empty_line = false;
+        }
+        out.extend_from_slice(line);
+        out.push(b'\n');
+    }
+    out
+}
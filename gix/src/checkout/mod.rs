@@ -0,0 +1,145 @@
+//! Switch the worktree, the index and `HEAD` to another commit, as well as [resetting](reset) and [restoring](restore) files,
+//! or [merging](merge) other commits.
+use crate::bstr::{BStr, BString};
+
+mod function;
+
+///
+#[cfg(all(feature = "merge", feature = "revision"))]
+pub mod merge;
+///
+pub mod reset;
+///
+pub mod restore;
+///
+pub mod update;
+
+/// The file that contains the ids of the commits being merged, one per line.
+const MERGE_HEAD: &str = "MERGE_HEAD";
+/// The file that contains the message to use for the merge commit.
+const MERGE_MSG: &str = "MERGE_MSG";
+/// The file that records the way the merge was started, like `no-ff`.
+const MERGE_MODE: &str = "MERGE_MODE";
+
+/// What to check out with [`Repository::checkout()`](crate::Repository::checkout()).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// Check out the commit the branch with the given full name, like `refs/heads/main`, points to,
+    /// and make `HEAD` a symbolic reference to it.
+    Branch(gix_ref::FullName),
+    /// Check out the given commit and detach `HEAD` by pointing it to it directly.
+    Detached(gix_hash::ObjectId),
+}
+
+impl From<gix_ref::FullName> for Target {
+    fn from(name: gix_ref::FullName) -> Self {
+        Target::Branch(name)
+    }
+}
+
+impl From<gix_hash::ObjectId> for Target {
+    fn from(id: gix_hash::ObjectId) -> Self {
+        Target::Detached(id)
+    }
+}
+
+/// Options for use in [`Repository::checkout()`](crate::Repository::checkout()).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, discard local changes to tracked files and overwrite untracked files that are in the way,
+    /// similar to `git checkout --force`.
+    ///
+    /// Otherwise, the checkout is refused if it would lose any local change.
+    pub force: bool,
+}
+
+/// The outcome of [`Repository::checkout()`](crate::Repository::checkout()) and similar operations that change the worktree.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The outcome of writing all changed files, with all other files left untouched.
+    pub checkout: gix_worktree_state::checkout::Outcome,
+    /// The amount of files that were removed from the worktree as they aren't tracked anymore.
+    pub files_removed: usize,
+}
+
+/// A path that prevents [`Repository::checkout()`](crate::Repository::checkout()) from updating the worktree
+/// as changes would be lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// The worktree file or its index entry have local changes that would be overwritten or removed.
+    LocalChange {
+        /// The repository-relative path of the file.
+        rela_path: BString,
+    },
+    /// An untracked file or directory is in the way of a file that would be checked out.
+    Untracked {
+        /// The repository-relative path of the file to check out.
+        rela_path: BString,
+    },
+    /// The index entry is unmerged and would have to be resolved first.
+    Unmerged {
+        /// The repository-relative path of the unmerged entry.
+        rela_path: BString,
+    },
+}
+
+impl Conflict {
+    /// Return the repository-relative path that is affected by the conflict.
+    pub fn rela_path(&self) -> &BStr {
+        match self {
+            Conflict::LocalChange { rela_path }
+            | Conflict::Untracked { rela_path }
+            | Conflict::Unmerged { rela_path } => rela_path.as_ref(),
+        }
+    }
+}
+
+/// The error returned by [`Repository::checkout()`](crate::Repository::checkout()).
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Repository at \"{}\" is a bare repository and cannot have its worktree checked out", git_dir.display())]
+    BareRepository { git_dir: std::path::PathBuf },
+    #[error("{} path(s) have local changes that would be overwritten by the checkout", conflicts.len())]
+    Conflicts { conflicts: Vec<Conflict> },
+    #[error("The checkout was interrupted before the index and HEAD were updated")]
+    Interrupted,
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+    #[error(transparent)]
+    Update(#[from] update::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::repository::index_write_options::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+}
+
+/// The progress ids used in [`Repository::checkout()`](crate::Repository::checkout()) and similar operations.
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of files checked out thus far.
+    CheckoutFiles,
+    /// The amount of bytes written in total, the aggregate of the size of the content of all files thus far.
+    BytesWritten,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::CheckoutFiles => *b"COCF",
+            ProgressId::BytesWritten => *b"COCB",
+        }
+    }
+}
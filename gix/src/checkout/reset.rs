@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_ref::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+
+use super::{MERGE_HEAD, Outcome, update};
+use crate::Repository;
+
+/// How much of the repository [`Repository::reset()`] changes to match the given commit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Only move the branch `HEAD` points to, or `HEAD` itself if it is detached, like `git reset --soft`.
+    Soft,
+    /// Move `HEAD` and make the index match the tree of the commit, but keep the worktree, like `git reset --mixed`.
+    #[default]
+    Mixed,
+    /// Move `HEAD` and make the index and the worktree match the tree of the commit, discarding all local changes
+    /// to tracked files, like `git reset --hard`.
+    Hard,
+}
+
+/// The error returned by [`Repository::reset()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Repository at \"{}\" is a bare repository and cannot have its worktree reset", git_dir.display())]
+    BareRepository { git_dir: std::path::PathBuf },
+    #[error("Cannot do a soft reset in the middle of a merge")]
+    SoftResetDuringMerge,
+    #[error("The reset was interrupted before the index and HEAD were updated")]
+    Interrupted,
+    #[error(transparent)]
+    FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Update(#[from] update::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::repository::index_write_options::Error),
+    #[error(transparent)]
+    FindHead(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+}
+
+impl Repository {
+    /// Reset `HEAD`, or the branch it points to, to `commit`, and depending on `mode`, the index and the worktree as well,
+    /// similar to `git reset`. A `reset: moving to <commit>` entry is written into the affected reflogs,
+    /// and `ORIG_HEAD` is set to the commit `HEAD` pointed to before.
+    ///
+    /// All modes but [`Mode::Soft`] end a merge that is in progress, while [`Mode::Soft`] refuses to reset during a merge.
+    ///
+    /// With [`Mode::Hard`], only files that differ from the tree of `commit` are written or removed, while untracked
+    /// files are left alone. Use `progress` to learn about the amount of files and bytes written, and `should_interrupt`
+    /// to stop the operation early, which leaves the index and `HEAD` untouched.
+    pub fn reset<P>(
+        &self,
+        commit: impl Into<gix_hash::ObjectId>,
+        mode: Mode,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error>
+    where
+        P: gix_features::progress::NestedProgress,
+        P::SubProgress: gix_features::progress::NestedProgress + 'static,
+    {
+        self.reset_inner(commit.into(), mode, &mut progress, should_interrupt)
+    }
+
+    fn reset_inner(
+        &self,
+        commit: gix_hash::ObjectId,
+        mode: Mode,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::reset()", mode = ?mode);
+        let tree = self.find_commit(commit)?.tree_id()?.detach();
+        let mut outcome = Outcome::default();
+        match mode {
+            Mode::Soft => {
+                if self.git_dir().join(MERGE_HEAD).is_file() {
+                    return Err(Error::SoftResetDuringMerge);
+                }
+            }
+            Mode::Mixed => {
+                let mut index = self.checkout_index_from_disk()?;
+                let mut target = self.checkout_index_from_tree(tree)?;
+                // The worktree is unchanged, so unchanged entries can keep their stat information.
+                for (entry, path) in target.entries_mut_with_paths() {
+                    if let Some(previous) = index
+                        .entry_by_path_and_stage(path, gix_index::entry::Stage::Unconflicted)
+                        .filter(|previous| previous.id == entry.id && previous.mode == entry.mode)
+                    {
+                        entry.stat = previous.stat;
+                    }
+                }
+                update::carry_over(&index, &mut target);
+                *index = target;
+                index.write(self.index_write_options()?)?;
+            }
+            Mode::Hard => {
+                let workdir = self.workdir().ok_or_else(|| Error::BareRepository {
+                    git_dir: self.git_dir().to_owned(),
+                })?;
+                let mut index = self.checkout_index_from_disk()?;
+                let target = self.checkout_index_from_tree(tree)?;
+                let worktree = self.checkout_worktree_changes(&index, should_interrupt)?;
+                let mut plan = update::two_way_merge(workdir, &index, &target, &target, &worktree, true)
+                    .unwrap_or_else(|_| unreachable!("forced merges don't conflict"));
+                outcome = self.checkout_apply(workdir, &mut plan, progress, should_interrupt)?;
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                *index = plan.index;
+                index.write(self.index_write_options()?)?;
+            }
+        }
+
+        if let Some(previous) = self.head()?.id() {
+            self.checkout_set_orig_head(previous.detach())?;
+        }
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("reset: moving to {commit}").into(),
+                },
+                expected: PreviousValue::Any,
+                new: gix_ref::Target::Object(commit),
+            },
+            name: "HEAD".try_into().expect("valid"),
+            deref: true,
+        })?;
+        if mode != Mode::Soft {
+            self.checkout_remove_merge_state()?;
+        }
+        Ok(outcome)
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::BTreeSet,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_index::entry::Stage;
+
+use super::{Outcome, update};
+use crate::{
+    Repository,
+    bstr::{BStr, BString},
+};
+
+/// Options for use in [`Repository::restore()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The tree-ish to restore files from, similar to `git restore --source`.
+    ///
+    /// If `None`, the worktree is restored from the index, and the index is restored from `HEAD`.
+    pub source: Option<gix_hash::ObjectId>,
+    /// If `true`, restore the index, similar to `git restore --staged`.
+    pub staged: bool,
+    /// If `true`, restore the worktree, similar to `git restore --worktree`.
+    pub worktree: bool,
+}
+
+impl Default for Options {
+    /// Restore only the worktree from the index, like `git restore` without further arguments does.
+    fn default() -> Self {
+        Options {
+            source: None,
+            staged: false,
+            worktree: true,
+        }
+    }
+}
+
+/// The error returned by [`Repository::restore()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error("Repository at \"{}\" is a bare repository and cannot have its worktree restored", git_dir.display())]
+    BareRepository { git_dir: std::path::PathBuf },
+    #[error("At least one pathspec is needed to know which paths to restore")]
+    MissingPathspec,
+    #[error("Path '{rela_path}' is unmerged and can't be restored from the index")]
+    Unmerged { rela_path: BString },
+    #[error("The restore was interrupted before the index was updated")]
+    Interrupted,
+    #[error(transparent)]
+    FindSource(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelSourceToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    HeadTreeId(#[from] crate::reference::head_tree_id::Error),
+    #[error(transparent)]
+    Pathspec(#[from] crate::pathspec::init::Error),
+    #[error(transparent)]
+    Update(#[from] update::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    IndexWriteOptions(#[from] crate::repository::index_write_options::Error),
+}
+
+impl Repository {
+    /// Restore all files matching `patterns` in the worktree or the index, or both, from the source given in `options`,
+    /// similar to `git restore`. Tracked files that match but don't exist in the source are removed.
+    ///
+    /// Files in the worktree are overwritten without checking for local changes.
+    /// Use `progress` to learn about the amount of files and bytes written, and `should_interrupt` to stop the operation early,
+    /// which leaves the index untouched.
+    pub fn restore<P>(
+        &self,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        options: Options,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error>
+    where
+        P: gix_features::progress::NestedProgress,
+        P::SubProgress: gix_features::progress::NestedProgress + 'static,
+    {
+        let patterns: Vec<BString> = patterns.into_iter().map(|p| p.as_ref().to_owned()).collect();
+        self.restore_inner(&patterns, options, &mut progress, should_interrupt)
+    }
+
+    fn restore_inner(
+        &self,
+        patterns: &[BString],
+        Options {
+            source,
+            staged,
+            worktree,
+        }: Options,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let _span = gix_trace::coarse!("gix::Repository::restore()");
+        if patterns.is_empty() {
+            return Err(Error::MissingPathspec);
+        }
+        let workdir = if worktree {
+            Some(self.workdir().ok_or_else(|| Error::BareRepository {
+                git_dir: self.git_dir().to_owned(),
+            })?)
+        } else {
+            None
+        };
+
+        let mut index = self.checkout_index_from_disk()?;
+        let source_tree = match source {
+            Some(id) => Some(self.find_object(id)?.peel_to_tree()?.id),
+            None if staged => Some(self.head_tree_id_or_empty()?.detach()),
+            None => None,
+        };
+        let source = source_tree
+            .map(|tree| self.checkout_index_from_tree(tree))
+            .transpose()?;
+
+        let mut pathspec = self.pathspec(
+            true,
+            patterns,
+            true,
+            &index,
+            gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+        )?;
+        let index_matches: Vec<_> = index
+            .entries()
+            .iter()
+            .filter(|entry| pathspec.is_included(entry.path(&index), Some(false)))
+            .cloned()
+            .collect();
+        let source_matches: Vec<_> = source.as_ref().map_or_else(Vec::new, |source| {
+            source
+                .entries()
+                .iter()
+                .filter(|entry| pathspec.is_included(entry.path(source), Some(false)))
+                .cloned()
+                .collect()
+        });
+        drop(pathspec);
+
+        // The state the matching paths should have, and the paths of tracked files that don't exist in it.
+        let (restored, restored_backing) = match &source {
+            Some(source) => (source_matches, source),
+            None => {
+                if let Some(entry) = index_matches.iter().find(|entry| entry.stage() != Stage::Unconflicted) {
+                    return Err(Error::Unmerged {
+                        rela_path: entry.path(&index).to_owned(),
+                    });
+                }
+                (index_matches.clone(), &*index)
+            }
+        };
+        let removed: Vec<BString> = index_matches
+            .iter()
+            .map(|entry| entry.path(&index))
+            .filter(|path| restored_backing.entry_by_path(path).is_none())
+            .map(ToOwned::to_owned)
+            .collect();
+
+        let mut new_index = staged.then(|| {
+            let matched_paths: BTreeSet<_> = index_matches.iter().map(|entry| entry.path(&index)).collect();
+            let mut new_index = gix_index::State::new(index.object_hash());
+            for entry in index.entries() {
+                let path = entry.path(&index);
+                if !matched_paths.contains(path) {
+                    new_index.dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, path);
+                }
+            }
+            for entry in &restored {
+                let path = entry.path(restored_backing);
+                let stat = index
+                    .entry_by_path_and_stage(path, Stage::Unconflicted)
+                    .filter(|previous| previous.id == entry.id && previous.mode == entry.mode)
+                    .map_or_else(Default::default, |previous| previous.stat);
+                new_index.dangerously_push_entry(stat, entry.id, entry.flags, entry.mode, path);
+            }
+            new_index.sort_entries();
+            new_index
+        });
+
+        let mut outcome = Outcome::default();
+        if let Some(workdir) = workdir {
+            let mut write = gix_index::State::new(index.object_hash());
+            for entry in &restored {
+                write.dangerously_push_entry(
+                    Default::default(),
+                    entry.id,
+                    entry.flags,
+                    entry.mode,
+                    entry.path(restored_backing),
+                );
+            }
+            write.sort_entries();
+
+            outcome.files_removed = self.checkout_remove(workdir, &removed)?;
+            outcome.checkout = self.checkout_write(workdir, &mut write, progress, should_interrupt)?;
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            match new_index.as_mut() {
+                Some(new_index) => update::copy_stats(&write, new_index),
+                None if source_tree.is_none() => {
+                    // The files now match the index again.
+                    update::copy_stats(&write, &mut index);
+                    index.write(self.index_write_options()?)?;
+                }
+                None => {}
+            }
+        }
+
+        if let Some(new_index) = new_index {
+            *index = new_index;
+            index.write(self.index_write_options()?)?;
+        }
+        Ok(outcome)
+    }
+}
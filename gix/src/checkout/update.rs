@@ -0,0 +1,503 @@
+//! Shared machinery to change the index and the worktree so they match a tree, without touching files that don't change.
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_index::{
+    Entry,
+    entry::{Flags, Stage},
+};
+use gix_ref::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
+
+use super::{Conflict, MERGE_HEAD, MERGE_MODE, MERGE_MSG, Outcome, ProgressId};
+use crate::{
+    Progress, Repository,
+    bstr::{BStr, BString, ByteSlice},
+};
+
+/// The error returned when the worktree is updated during a [checkout](crate::Repository::checkout()),
+/// a [reset](crate::Repository::reset()) or a [restore](crate::Repository::restore()).
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error("Could not create index from tree at {id}")]
+    IndexFromTree {
+        id: gix_hash::ObjectId,
+        source: gix_index::init::from_tree::Error,
+    },
+    #[error("Couldn't obtain configuration for core.protect*")]
+    BooleanConfig(#[from] crate::config::boolean::Error),
+    #[error("Could not determine which files have local changes")]
+    IndexWorktreeStatus(#[from] crate::status::index_worktree::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    IndexCheckout(#[from] gix_worktree_state::checkout::Error),
+    #[error("Failed to reopen object database as Arc (only if thread-safety wasn't compiled in)")]
+    OpenArcOdb(#[source] std::io::Error),
+    #[error("Could not remove '{}' from the worktree", path.display())]
+    RemoveFile { path: PathBuf, source: std::io::Error },
+    #[error("Could not remove merge state at \"{}\"", path.display())]
+    RemoveMergeState { path: PathBuf, source: std::io::Error },
+}
+
+/// How a tracked file in the worktree differs from its index entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum WorktreeChange {
+    /// The file was changed in content, mode or type.
+    Modified,
+    /// The file doesn't exist anymore.
+    Missing,
+}
+
+/// The changes to apply to the index and the worktree.
+pub(super) struct Plan {
+    /// The index as it should be once the worktree was updated.
+    pub index: gix_index::State,
+    /// The entries whose files should be written to the worktree.
+    pub write: gix_index::State,
+    /// The paths of files to remove from the worktree.
+    pub remove: Vec<BString>,
+}
+
+impl Repository {
+    /// Read the index from disk, or return an empty one if there is none yet, to be changed and written back.
+    ///
+    /// The shared index snapshot isn't used as it can't see changes made within the resolution of file modification times,
+    /// as may be the case if the previous operation wrote the index just now.
+    pub(super) fn checkout_index_from_disk(&self) -> Result<gix_index::File, Error> {
+        match self.open_index() {
+            Ok(index) => Ok(index),
+            Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                Ok(gix_index::File::from_state(
+                    gix_index::State::new(self.object_hash()),
+                    self.index_path(),
+                ))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Return an index created from `tree`, which may also be the empty tree.
+    pub(super) fn checkout_index_from_tree(&self, tree: gix_hash::ObjectId) -> Result<gix_index::State, Error> {
+        gix_index::State::from_tree(&tree, self, self.config.protect_options()?)
+            .map_err(|err| Error::IndexFromTree { id: tree, source: err })
+    }
+
+    /// Return all tracked files in `index` that differ from what's in the worktree.
+    pub(super) fn checkout_worktree_changes(
+        &self,
+        index: &gix_index::State,
+        should_interrupt: &AtomicBool,
+    ) -> Result<BTreeMap<BString, WorktreeChange>, Error> {
+        use gix_status::index_as_worktree::{Change, EntryStatus};
+        let mut recorder = gix_status::index_as_worktree_with_renames::Recorder::default();
+        self.index_worktree_status(
+            index,
+            None::<&BStr>,
+            &mut recorder,
+            gix_status::index_as_worktree::traits::FastEq,
+            NoSubmoduleStatus,
+            &mut gix_features::progress::Discard,
+            should_interrupt,
+            Default::default(),
+        )?;
+        Ok(recorder
+            .records
+            .into_iter()
+            .filter_map(|entry| match entry {
+                gix_status::index_as_worktree_with_renames::Entry::Modification { rela_path, status, .. } => {
+                    let change = match status {
+                        EntryStatus::Change(Change::Removed) => WorktreeChange::Missing,
+                        EntryStatus::Change(_) => WorktreeChange::Modified,
+                        EntryStatus::Conflict { .. } | EntryStatus::NeedsUpdate(_) | EntryStatus::IntentToAdd => {
+                            return None;
+                        }
+                    };
+                    Some((rela_path.to_owned(), change))
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Remove all files of `plan` from the worktree at `workdir` before writing the ones that changed,
+    /// and update the stat information of the entries in `plan.index` that were written.
+    pub(super) fn checkout_apply(
+        &self,
+        workdir: &Path,
+        plan: &mut Plan,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let files_removed = self.checkout_remove(workdir, &plan.remove)?;
+        let checkout = self.checkout_write(workdir, &mut plan.write, progress, should_interrupt)?;
+        copy_stats(&plan.write, &mut plan.index);
+        Ok(Outcome {
+            checkout,
+            files_removed,
+        })
+    }
+
+    /// Point `ORIG_HEAD` to `id`, the commit `HEAD` pointed to before it is changed.
+    pub(super) fn checkout_set_orig_head(&self, id: gix_hash::ObjectId) -> Result<(), crate::reference::edit::Error> {
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: Default::default(),
+                },
+                expected: PreviousValue::Any,
+                new: gix_ref::Target::Object(id),
+            },
+            name: "ORIG_HEAD".try_into().expect("valid"),
+            deref: false,
+        })?;
+        Ok(())
+    }
+
+    /// Remove `MERGE_HEAD`, `MERGE_MSG` and `MERGE_MODE` to end the merge that may be in progress.
+    pub(super) fn checkout_remove_merge_state(&self) -> Result<(), Error> {
+        for name in [MERGE_HEAD, MERGE_MSG, MERGE_MODE] {
+            let path = self.git_dir().join(name);
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(source) => return Err(Error::RemoveMergeState { path, source }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove all files at `paths` from the worktree at `workdir`, along with directories that became empty,
+    /// and return the amount of removed files.
+    pub(super) fn checkout_remove(&self, workdir: &Path, paths: &[BString]) -> Result<usize, Error> {
+        let mut files_removed = 0;
+        for rela_path in paths {
+            if remove_from_worktree(workdir, rela_path.as_ref())? {
+                files_removed += 1;
+            }
+        }
+        Ok(files_removed)
+    }
+
+    /// Write all entries of `index` into the worktree at `workdir`, overwriting what's there, and update their stat information.
+    pub(super) fn checkout_write(
+        &self,
+        workdir: &Path,
+        index: &mut gix_index::State,
+        progress: &mut dyn gix_features::progress::DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<gix_worktree_state::checkout::Outcome, Error> {
+        if index.entries().is_empty() {
+            return Ok(Default::default());
+        }
+        let mut opts = self.checkout_options(gix_worktree::stack::state::attributes::Source::IdMappingThenWorktree)?;
+        opts.destination_is_initially_empty = false;
+        opts.overwrite_existing = true;
+
+        let mut files = progress.add_child_with_id("checkout".to_string(), ProgressId::CheckoutFiles.into());
+        let mut bytes = progress.add_child_with_id("writing".to_string(), ProgressId::BytesWritten.into());
+
+        files.init(Some(index.entries().len()), crate::progress::count("files"));
+        bytes.init(None, crate::progress::bytes());
+
+        let start = std::time::Instant::now();
+        let outcome = gix_worktree_state::checkout(
+            index,
+            workdir,
+            self.objects.clone().into_arc().map_err(Error::OpenArcOdb)?,
+            &files,
+            &bytes,
+            should_interrupt,
+            opts,
+        )?;
+        files.show_throughput(start);
+        bytes.show_throughput(start);
+        Ok(outcome)
+    }
+}
+
+/// Perform a two-way merge of the `target` tree into the `index`, with `head` being the tree the index is based on.
+/// `worktree` lists all tracked files that differ from their entries in `index`.
+///
+/// Paths that didn't change between `head` and `target` keep their `index` entries and files, and all other paths
+/// are updated to match `target` if that doesn't lose local changes. Otherwise, these are returned as conflicts.
+/// If `force` is `true`, the resulting index will match `target` exactly, and all files that differ are written,
+/// without ever producing conflicts.
+///
+/// Files of entries that are skipped in the worktree of a sparse checkout are neither written nor removed,
+/// and what can't be obtained from `target` is [carried over](carry_over()) from `index`.
+pub(super) fn two_way_merge(
+    workdir: &Path,
+    index: &gix_index::State,
+    head: &gix_index::State,
+    target: &gix_index::State,
+    worktree: &BTreeMap<BString, WorktreeChange>,
+    force: bool,
+) -> Result<Plan, Vec<Conflict>> {
+    #[derive(Default)]
+    struct Slot<'a> {
+        head: Option<&'a Entry>,
+        index: Option<&'a Entry>,
+        unmerged: bool,
+        target: Option<&'a Entry>,
+    }
+    enum Action<'a> {
+        Keep(&'a Entry),
+        Update(&'a Entry),
+        Remove { delete_file: bool },
+    }
+
+    let mut slots = BTreeMap::<&BStr, Slot<'_>>::new();
+    for entry in head.entries() {
+        slots.entry(entry.path(head)).or_default().head = Some(entry);
+    }
+    for entry in index.entries() {
+        let slot = slots.entry(entry.path(index)).or_default();
+        if entry.stage() == Stage::Unconflicted {
+            slot.index = Some(entry);
+        } else {
+            slot.unmerged = true;
+        }
+    }
+    for entry in target.entries() {
+        slots.entry(entry.path(target)).or_default().target = Some(entry);
+    }
+
+    let mut plan = Plan {
+        index: gix_index::State::new(target.object_hash()),
+        write: gix_index::State::new(target.object_hash()),
+        remove: Vec::new(),
+    };
+    let mut conflicts = Vec::new();
+    for (rela_path, slot) in slots {
+        let change = worktree.get(rela_path).copied();
+        let skip_worktree = slot
+            .index
+            .is_some_and(|entry| entry.flags.contains(Flags::SKIP_WORKTREE));
+        let action = if force {
+            match slot.target {
+                Some(_) if !slot.unmerged && change.is_none() && is_same(slot.index, slot.target) => {
+                    Some(Action::Keep(slot.index.expect("same as target")))
+                }
+                Some(target) => Some(Action::Update(target)),
+                None if slot.index.is_some() || slot.unmerged => Some(Action::Remove { delete_file: true }),
+                None => None,
+            }
+        } else if slot.unmerged {
+            conflicts.push(Conflict::Unmerged {
+                rela_path: rela_path.to_owned(),
+            });
+            continue;
+        } else if is_same(slot.index, slot.target) || is_same(slot.head, slot.target) {
+            slot.index.map(Action::Keep)
+        } else if is_same(slot.index, slot.head) {
+            if slot.index.is_some() && change == Some(WorktreeChange::Modified) {
+                conflicts.push(Conflict::LocalChange {
+                    rela_path: rela_path.to_owned(),
+                });
+                continue;
+            }
+            match slot.target {
+                Some(_) if slot.index.is_none() && is_obstructed(workdir, rela_path, index) => {
+                    conflicts.push(Conflict::Untracked {
+                        rela_path: rela_path.to_owned(),
+                    });
+                    continue;
+                }
+                Some(target) => Some(Action::Update(target)),
+                None => Some(Action::Remove {
+                    delete_file: change != Some(WorktreeChange::Missing),
+                }),
+            }
+        } else {
+            conflicts.push(Conflict::LocalChange {
+                rela_path: rela_path.to_owned(),
+            });
+            continue;
+        };
+
+        match action {
+            Some(Action::Keep(entry)) => {
+                plan.index
+                    .dangerously_push_entry(entry.stat, entry.id, entry.flags, entry.mode, rela_path);
+            }
+            Some(Action::Update(entry)) if skip_worktree => {
+                plan.index
+                    .dangerously_push_entry(Default::default(), entry.id, entry.flags, entry.mode, rela_path);
+            }
+            Some(Action::Update(entry)) => {
+                for state in [&mut plan.index, &mut plan.write] {
+                    state.dangerously_push_entry(Default::default(), entry.id, entry.flags, entry.mode, rela_path);
+                }
+            }
+            Some(Action::Remove { delete_file: true }) if !skip_worktree => plan.remove.push(rela_path.to_owned()),
+            Some(Action::Remove { .. }) | None => {}
+        }
+    }
+
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+    plan.index.sort_entries();
+    plan.write.sort_entries();
+    carry_over(index, &mut plan.index);
+    Ok(plan)
+}
+
+/// Carry over what can't be obtained from a tree from the `previous` index to the `index` that replaces it.
+///
+/// Entries keep their skip-worktree flag of a sparse checkout, and unchanged entries remain valid for the filesystem monitor.
+/// The untracked-cache and filesystem-monitor extensions are kept as well, as the latter derives its dirty bits
+/// from the entries when written.
+pub(super) fn carry_over(previous: &gix_index::State, index: &mut gix_index::State) {
+    for (entry, path) in index.entries_mut_with_paths() {
+        let Some(previous) = previous.entry_by_path_and_stage(path, Stage::Unconflicted) else {
+            continue;
+        };
+        if previous.flags.contains(Flags::SKIP_WORKTREE) {
+            entry.flags |= Flags::SKIP_WORKTREE | Flags::EXTENDED;
+        }
+        if previous.flags.contains(Flags::FSMONITOR_VALID)
+            && previous.id == entry.id
+            && previous.mode == entry.mode
+            && previous.stat == entry.stat
+        {
+            entry.flags |= Flags::FSMONITOR_VALID;
+        }
+    }
+    index.set_untracked(previous.untracked().cloned());
+    index.set_fs_monitor(previous.fs_monitor().cloned());
+}
+
+/// Set the stat information of all entries in `dst` to the one of the entries at the same path in `src`, if there is one.
+pub(super) fn copy_stats(src: &gix_index::State, dst: &mut gix_index::State) {
+    for entry in src.entries() {
+        if let Some(dst_entry) = dst.entry_mut_by_path_and_stage(entry.path(src), Stage::Unconflicted) {
+            dst_entry.stat = entry.stat;
+        }
+    }
+}
+
+fn is_same(a: Option<&Entry>, b: Option<&Entry>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.id == b.id && a.mode == b.mode,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Return `true` if the untracked file at `rela_path` is in the way of a file that should be checked out, or
+/// if one of its leading directories is an untracked file.
+/// Directories are only in the way if they contain files that aren't tracked in `index`.
+fn is_obstructed(workdir: &Path, rela_path: &BStr, index: &gix_index::State) -> bool {
+    let mut path = workdir.to_owned();
+    let mut components = rela_path.split_str("/").peekable();
+    while let Some(component) = components.next() {
+        path.push(gix_path::from_bstr(component.as_bstr()));
+        let Ok(meta) = std::fs::symlink_metadata(&path) else {
+            return false;
+        };
+        let is_leading = components.peek().is_some();
+        if is_leading {
+            if meta.is_dir() {
+                continue;
+            }
+            let leading_path = &rela_path[..rela_path.len() - components.map(|c| c.len() + 1).sum::<usize>()];
+            return index.entry_by_path(leading_path.as_bstr()).is_none();
+        }
+        return if meta.is_dir() {
+            has_untracked_files(&path, rela_path.to_owned(), index)
+        } else {
+            true
+        };
+    }
+    false
+}
+
+fn has_untracked_files(dir: &Path, mut rela_path: BString, index: &gix_index::State) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return true;
+    };
+    let dir_len = rela_path.len();
+    for entry in entries {
+        let Ok(entry) = entry else {
+            return true;
+        };
+        let Ok(name) = gix_path::os_str_into_bstr(&entry.file_name()).map(ToOwned::to_owned) else {
+            return true;
+        };
+        rela_path.truncate(dir_len);
+        rela_path.push(b'/');
+        rela_path.extend_from_slice(&name);
+        let is_dir = entry.file_type().is_ok_and(|ft| ft.is_dir());
+        let untracked = if is_dir {
+            has_untracked_files(&entry.path(), rela_path.clone(), index)
+        } else {
+            index.entry_by_path(rela_path.as_ref()).is_none()
+        };
+        if untracked {
+            return true;
+        }
+    }
+    false
+}
+
+/// Remove the file at `rela_path` in `workdir`, along with all leading directories that became empty.
+/// Return `true` if a file was removed.
+///
+/// Leading symlinks are never followed, and directories are only removed if they are empty,
+/// which keeps the content of submodules.
+fn remove_from_worktree(workdir: &Path, rela_path: &BStr) -> Result<bool, Error> {
+    let mut path = workdir.to_owned();
+    let mut components = rela_path.split_str("/").peekable();
+    while let Some(component) = components.next() {
+        path.push(gix_path::from_bstr(component.as_bstr()));
+        if components.peek().is_some() && !std::fs::symlink_metadata(&path).is_ok_and(|meta| meta.file_type().is_dir())
+        {
+            return Ok(false);
+        }
+    }
+
+    let res = match std::fs::symlink_metadata(&path) {
+        Ok(meta) if meta.is_dir() => return Ok(std::fs::remove_dir(&path).is_ok()),
+        Ok(meta) if meta.file_type().is_symlink() => gix_fs::symlink::remove(&path),
+        Ok(_) => std::fs::remove_file(&path),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => Err(err),
+    };
+    res.map_err(|source| Error::RemoveFile {
+        path: path.clone(),
+        source,
+    })?;
+
+    let mut dir = path.parent();
+    while let Some(parent) = dir.filter(|dir| *dir != workdir) {
+        if std::fs::remove_dir(parent).is_err() {
+            break;
+        }
+        dir = parent.parent();
+    }
+    Ok(true)
+}
+
+/// Submodules are considered unchanged, as their worktree is never changed by a checkout.
+#[derive(Clone)]
+struct NoSubmoduleStatus;
+
+impl gix_status::index_as_worktree::traits::SubmoduleStatus for NoSubmoduleStatus {
+    type Output = ();
+    type Error = std::convert::Infallible;
+
+    fn status(&mut self, _entry: &gix_index::Entry, _rela_path: &BStr) -> Result<Option<Self::Output>, Self::Error> {
+        Ok(None)
+    }
+}
@@ -60,6 +60,18 @@ impl PrepareFetch {
         self.ref_name = name.map(TryInto::try_into).transpose()?.map(ToOwned::to_owned);
         Ok(self)
     }
+
+    /// Unbundle the bundle or the bundles of the bundle list at `uri` before fetching from the remote, similar to
+    /// `git clone --bundle-uri=<uri>`, which leaves fewer objects to be negotiated and received.
+    ///
+    /// `uri` may be a `file://` or `http(s)://` URL, or a path. If unset, and `transfer.bundleURI` is enabled, the bundles
+    /// advertised by the remote are used.
+    /// Bundles that fail to download or apply are skipped, and their objects will be fetched instead.
+    #[cfg(feature = "blocking-network-client")]
+    pub fn with_bundle_uri(mut self, uri: impl Into<BString>) -> Self {
+        self.bundle_uri = Some(uri.into());
+        self
+    }
 }
 
 /// Consumption
@@ -363,6 +363,15 @@ impl PrepareFetch {
                 config = None;
             }
         }
+        #[cfg(feature = "blocking-network-client")]
+        let mut pending_pack = pending_pack;
+        #[cfg(feature = "blocking-network-client")]
+        pending_pack.bootstrap_from_bundles(
+            &repo,
+            self.bundle_uri.as_ref().map(AsRef::as_ref),
+            &mut progress,
+            should_interrupt,
+        );
         let reflog_message = {
             let mut b = self.url.to_bstring();
             b.insert_str(0, "clone: from ");
@@ -44,6 +44,9 @@ pub struct PrepareFetch {
     ref_name: Option<gix_ref::PartialName>,
     /// If `true`, drop removes the entire worktree. Otherwise leave it alone.
     remove_worktree_on_drop: bool,
+    /// The location of a bundle or bundle list to unbundle before fetching, instead of using the bundles advertised by the remote.
+    #[cfg(feature = "blocking-network-client")]
+    bundle_uri: Option<BString>,
 }
 
 /// The error returned by [`PrepareFetch::new()`].
@@ -143,6 +146,8 @@ impl PrepareFetch {
             shallow: remote::fetch::Shallow::NoChange,
             ref_name: None,
             remove_worktree_on_drop,
+            #[cfg(feature = "blocking-network-client")]
+            bundle_uri: None,
         })
     }
 }
@@ -207,7 +212,7 @@ mod access_feat {
 }
 
 ///
-#[cfg(any(feature = "async-network-client-async-std", feature = "blocking-network-client"))]
+#[cfg(any(feature = "async-network-client-connect", feature = "blocking-network-client"))]
 pub mod fetch;
 
 mod access;
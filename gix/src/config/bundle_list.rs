@@ -0,0 +1,26 @@
+use gix_protocol::bundle_uri::{List, list};
+
+use crate::bstr::BString;
+
+/// Read all `bundle.*` values of `config` into a bundle list, as needed to advertise bundles to clients,
+/// and to read a downloaded list.
+pub(crate) fn from_config(config: &gix_config::File) -> Result<List, list::Error> {
+    let mut pairs = Vec::<(BString, BString)>::new();
+    for section in config.sections_by_name("bundle").into_iter().flatten() {
+        let prefix = match section.header().subsection_name() {
+            Some(subsection) => format!("bundle.{subsection}."),
+            None => "bundle.".into(),
+        };
+        let mut names = Vec::<String>::new();
+        for name in section.value_names() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        for name in names {
+            let key = BString::from(format!("{prefix}{name}"));
+            pairs.extend(section.values(&name).into_iter().map(|value| (key.clone(), value)));
+        }
+    }
+    List::from_key_values(pairs.iter().map(|(key, value)| (key.as_ref(), value.as_ref())))
+}
@@ -24,16 +24,12 @@ impl Cache {
 
     #[cfg(feature = "blob-diff")]
     pub(crate) fn diff_algorithm(&self) -> Result<gix_diff::blob::Algorithm, config::diff::algorithm::Error> {
-        use crate::config::{cache::util::ApplyLeniencyDefault, diff::algorithm::Error, tree::Diff};
+        use crate::config::{cache::util::ApplyLeniencyDefault, tree::Diff};
         self.diff_algorithm
             .get_or_try_init(|| {
                 let name = self.resolved.string(Diff::ALGORITHM).unwrap_or_else(|| "myers".into());
                 config::tree::Diff::ALGORITHM
                     .try_into_algorithm(name)
-                    .or_else(|err| match err {
-                        Error::Unimplemented { .. } if self.lenient_config => Ok(gix_diff::blob::Algorithm::Histogram),
-                        err => Err(err),
-                    })
                     .with_lenient_default(self.lenient_config)
             })
             .copied()
@@ -42,7 +38,7 @@ impl Cache {
     #[cfg(feature = "blob-diff")]
     pub(crate) fn diff_drivers(&self) -> Result<Vec<gix_diff::blob::Driver>, config::diff::drivers::Error> {
         use crate::config::cache::util::ApplyLeniencyDefault;
-        let mut out = Vec::<gix_diff::blob::Driver>::new();
+        let mut out: Vec<_> = gix_diff::blob::Driver::builtins().collect();
         for section in self
             .resolved
             .sections_by_name("diff")
@@ -81,15 +77,33 @@ impl Cache {
             if let Some(textconv) = section.value(config::tree::Diff::DRIVER_TEXTCONV.name) {
                 driver.binary_to_text_command = textconv.into();
             }
+            if let Some(word_regex) = section.value(config::tree::Diff::DRIVER_WORD_REGEX.name) {
+                driver.word_regex = word_regex.into();
+            }
+            // Like in `git`, the last of both keys wins.
+            if let Some(key) = section
+                .value_names()
+                .filter(|name| {
+                    name.eq_ignore_ascii_case(config::tree::Diff::DRIVER_XFUNCNAME.name)
+                        || name.eq_ignore_ascii_case(config::tree::Diff::DRIVER_FUNCNAME.name)
+                })
+                .last()
+            {
+                if let Some(pattern) = section.value(&key) {
+                    driver.funcname = Some(gix_diff::blob::funcname::Pattern {
+                        pattern,
+                        syntax: if key.eq_ignore_ascii_case(config::tree::Diff::DRIVER_XFUNCNAME.name) {
+                            gix_diff::blob::funcname::Syntax::Extended
+                        } else {
+                            gix_diff::blob::funcname::Syntax::Basic
+                        },
+                        ignore_case: false,
+                    });
+                }
+            }
             if let Some(algorithm) = section.value("algorithm") {
                 driver.algorithm = config::tree::Diff::DRIVER_ALGORITHM
                     .try_into_algorithm(algorithm)
-                    .or_else(|err| match err {
-                        config::diff::algorithm::Error::Unimplemented { .. } if self.lenient_config => {
-                            Ok(gix_diff::blob::Algorithm::Histogram)
-                        }
-                        err => Err(err),
-                    })
                     .with_lenient_default(self.lenient_config)
                     .map_err(|err| config::diff::drivers::Error {
                         name: driver.name.clone(),
@@ -3,6 +3,8 @@ use gix_features::threading::OnceCell;
 
 use crate::{Repository, bstr::BString, repository::identity};
 
+#[cfg(any(feature = "server", feature = "blocking-network-client"))]
+pub(crate) mod bundle_list;
 pub(crate) mod cache;
 mod snapshot;
 #[cfg(feature = "credentials")]
@@ -162,8 +164,6 @@ pub mod diff {
         pub enum Error {
             #[error("Unknown diff algorithm named '{name}'")]
             Unknown { name: BString },
-            #[error("The '{name}' algorithm is not yet implemented")]
-            Unimplemented { name: BString },
         }
     }
 
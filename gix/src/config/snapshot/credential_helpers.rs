@@ -87,7 +87,6 @@ pub(super) mod function {
     /// - Upper-case scheme and host will be lower-cased automatically when parsing into a url, so prompts differ compared to git.
     /// - A **difference in prompt might affect the matching of getting existing stored credentials**, and it's a question of this being
     ///   a feature or a bug.
-    // TODO: when dealing with `http.*.*` configuration, generalize this algorithm as needed and support precedence.
     pub fn credential_helpers(
         mut url: gix_url::Url,
         config: &gix_config::File,
@@ -106,89 +105,67 @@ pub(super) mod function {
         let mut programs = Vec::new();
         let mut context_options = gix_credentials::protocol::ContextOptions::default();
         let url_had_user_initially = url.user().is_some();
-        normalize(&mut url);
 
-        if let Some(credential_sections) = config.sections_by_name_and_filter("credential", &mut filter) {
-            for section in credential_sections {
-                let section = match section.header().subsection_name() {
-                    Some(pattern) => gix_url::parse(pattern).ok().and_then(|mut pattern| {
-                        normalize(&mut pattern);
-                        let is_http = matches!(pattern.scheme, gix_url::Scheme::Https | gix_url::Scheme::Http);
-                        let scheme = &pattern.scheme;
-                        let host = pattern.host();
-                        let ports = if is_http {
-                            (pattern.port_or_default(), url.port_or_default())
-                        } else {
-                            (pattern.port, url.port)
-                        };
-                        let path = (!(is_http && pattern.path_is_root())).then_some(&pattern.path);
-
-                        if path.is_some_and(|path| path != &url.path) {
-                            return None;
-                        }
-                        if pattern.user().is_some() && pattern.user() != url.user() {
-                            return None;
-                        }
-                        (scheme == &url.scheme && host_matches(host, url.host()) && ports.0 == ports.1).then_some((
-                            section,
+        if let Some(credential_sections) = config.sections_by_url_filter("credential", &url, &mut filter) {
+            for (section, _quality) in credential_sections {
+                let (helper_key, username_key, use_http_path_key, protect_protocol_key) =
+                    if section.header().subsection_name().is_some() {
+                        (
                             &credential::UrlParameter::HELPER,
                             &credential::UrlParameter::USERNAME,
                             &credential::UrlParameter::USE_HTTP_PATH,
                             &credential::UrlParameter::PROTECT_PROTOCOL,
-                        ))
-                    }),
-                    None => Some((
-                        section,
-                        &Credential::HELPER,
-                        &Credential::USERNAME,
-                        &Credential::USE_HTTP_PATH,
-                        &Credential::PROTECT_PROTOCOL,
-                    )),
-                };
-                if let Some((section, helper_key, username_key, use_http_path_key, protect_protocol_key)) = section {
-                    for value in section.values(helper_key.name) {
-                        if value.trim().is_empty() {
-                            programs.clear();
-                        } else {
-                            programs.push(gix_credentials::Program::from_custom_definition(value));
-                        }
-                    }
-                    if let Some(Some(user)) = (!url_had_user_initially).then(|| {
-                        section
-                            .value(username_key.name)
-                            .filter(|n| !n.trim().is_empty())
-                            .and_then(|n| {
-                                let n: Vec<_> = n.into();
-                                n.into_string().ok()
-                            })
-                    }) {
-                        url.set_user(Some(user));
-                    }
-                    if let Some(toggle) = section
-                        .value(use_http_path_key.name)
-                        .map(|val| {
-                            gix_config::Boolean::try_from(val)
-                                .map_err(|err| Error::InvalidUseHttpPath {
-                                    source: err,
-                                    section: section.header().to_bstring(),
-                                })
-                                .map(|b| b.0)
-                        })
-                        .transpose()?
-                    {
-                        use_http_path = toggle;
+                        )
+                    } else {
+                        (
+                            &Credential::HELPER,
+                            &Credential::USERNAME,
+                            &Credential::USE_HTTP_PATH,
+                            &Credential::PROTECT_PROTOCOL,
+                        )
+                    };
+                for value in section.values(helper_key.name) {
+                    if value.trim().is_empty() {
+                        programs.clear();
+                    } else {
+                        programs.push(gix_credentials::Program::from_custom_definition(value));
                     }
-                    if let Some(toggle) = section
-                        .value(protect_protocol_key.name)
-                        .map(|value| {
-                            protect_protocol_key
-                                .enrich_error(gix_config::Boolean::try_from(value).map(|value| Some(value.0)))
+                }
+                if let Some(Some(user)) = (!url_had_user_initially).then(|| {
+                    section
+                        .value(username_key.name)
+                        .filter(|n| !n.trim().is_empty())
+                        .and_then(|n| {
+                            let n: Vec<_> = n.into();
+                            n.into_string().ok()
                         })
-                        .transpose()?
-                        .flatten()
-                    {
-                        context_options.protect_protocol = toggle;
-                    }
+                }) {
+                    url.set_user(Some(user));
+                }
+                if let Some(toggle) = section
+                    .value(use_http_path_key.name)
+                    .map(|val| {
+                        gix_config::Boolean::try_from(val)
+                            .map_err(|err| Error::InvalidUseHttpPath {
+                                source: err,
+                                section: section.header().to_bstring(),
+                            })
+                            .map(|b| b.0)
+                    })
+                    .transpose()?
+                {
+                    use_http_path = toggle;
+                }
+                if let Some(toggle) = section
+                    .value(protect_protocol_key.name)
+                    .map(|value| {
+                        protect_protocol_key
+                            .enrich_error(gix_config::Boolean::try_from(value).map(|value| Some(value.0)))
+                    })
+                    .transpose()?
+                    .flatten()
+                {
+                    context_options.protect_protocol = toggle;
                 }
             }
         }
@@ -232,29 +209,6 @@ pub(super) mod function {
         ))
     }
 
-    fn host_matches(pattern: Option<&str>, host: Option<&str>) -> bool {
-        match (pattern, host) {
-            (Some(pattern), Some(host)) => {
-                let lfields = pattern.split('.');
-                let rfields = host.split('.');
-                if lfields.clone().count() != rfields.clone().count() {
-                    return false;
-                }
-                lfields.zip(rfields).all(|(pat, value)| {
-                    gix_glob::wildmatch(pat.into(), value.into(), gix_glob::wildmatch::Mode::empty())
-                })
-            }
-            (None, None) => true,
-            (Some(_), None) | (None, Some(_)) => false,
-        }
-    }
-
-    fn normalize(url: &mut gix_url::Url) {
-        if !url.path_is_root() && url.path.ends_with(b"/") {
-            url.path.pop();
-        }
-    }
-
     trait IgnoreEmptyPath {
         fn ignore_empty(self) -> Self;
     }
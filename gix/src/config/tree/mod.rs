@@ -59,11 +59,17 @@ pub(crate) mod root {
         pub const REMOTE: sections::Remote = sections::Remote;
         /// The `safe` section.
         pub const SAFE: sections::Safe = sections::Safe;
+        /// The `splitIndex` section.
+        pub const SPLIT_INDEX: sections::SplitIndex = sections::SplitIndex;
         /// The `ssh` section.
         pub const SSH: sections::Ssh = sections::Ssh;
         /// The `status` section.
         #[cfg(feature = "status")]
         pub const STATUS: sections::Status = sections::Status;
+        /// The `transfer` section.
+        pub const TRANSFER: sections::Transfer = sections::Transfer;
+        /// The `uploadpack` section.
+        pub const UPLOAD_PACK: sections::UploadPack = sections::UploadPack;
         /// The `user` section.
         pub const USER: sections::User = sections::User;
         /// The `url` section.
@@ -94,9 +100,12 @@ pub(crate) mod root {
                 &Self::PUSH,
                 &Self::REMOTE,
                 &Self::SAFE,
+                &Self::SPLIT_INDEX,
                 &Self::SSH,
                 #[cfg(feature = "status")]
                 &Self::STATUS,
+                &Self::TRANSFER,
+                &Self::UPLOAD_PACK,
                 &Self::USER,
                 &Self::URL,
             ]
@@ -107,8 +116,8 @@ pub(crate) mod root {
 mod sections;
 pub use sections::{
     Author, Branch, Checkout, Clone, Committer, Core, Credential, Extensions, Fetch, Gitoxide, Http, Index, Init,
-    Mailmap, Merge, Pack, Protocol, Push, Remote, Safe, Ssh, Url, User, branch, checkout, core, credential, extensions,
-    fetch, gitoxide, http, index, protocol, push, remote, ssh,
+    Mailmap, Merge, Pack, Protocol, Push, Remote, Safe, SplitIndex, Ssh, Transfer, UploadPack, Url, User, branch,
+    checkout, core, credential, extensions, fetch, gitoxide, http, index, protocol, push, remote, split_index, ssh,
 };
 #[cfg(feature = "blob-diff")]
 pub use sections::{Diff, diff};
@@ -33,6 +33,15 @@ impl Core {
     pub const FILE_MODE: keys::Boolean = keys::Boolean::new_boolean("fileMode", &config::Tree::CORE);
     /// The `core.fsCache` key.
     pub const FS_CACHE: keys::Boolean = keys::Boolean::new_boolean("fsCache", &config::Tree::CORE);
+    /// The `core.fsmonitor` key.
+    pub const FSMONITOR: keys::Any = keys::Any::new("fsmonitor", &config::Tree::CORE).with_deviation(
+        "only hooks are supported, the built-in daemon that would be used if it is `true` is treated like `false`",
+    );
+    /// The `core.fsmonitorHookVersion` key.
+    pub const FSMONITOR_HOOK_VERSION: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("fsmonitorHookVersion", &config::Tree::CORE);
+    /// The `core.hooksPath` key.
+    pub const HOOKS_PATH: keys::Path = keys::Path::new_path("hooksPath", &config::Tree::CORE);
     /// The `core.ignoreCase` key.
     pub const IGNORE_CASE: keys::Boolean = keys::Boolean::new_boolean("ignoreCase", &config::Tree::CORE);
     /// The `core.filesRefLockTimeout` key.
@@ -58,10 +67,15 @@ impl Core {
     /// The `core.repositoryFormatVersion` key.
     pub const REPOSITORY_FORMAT_VERSION: keys::UnsignedInteger =
         keys::UnsignedInteger::new_unsigned_integer("repositoryFormatVersion", &config::Tree::CORE);
+    /// The `core.splitIndex` key.
+    pub const SPLIT_INDEX: keys::Boolean = keys::Boolean::new_boolean("splitIndex", &config::Tree::CORE);
     /// The `core.symlinks` key.
     pub const SYMLINKS: keys::Boolean = keys::Boolean::new_boolean("symlinks", &config::Tree::CORE);
     /// The `core.trustCTime` key.
     pub const TRUST_C_TIME: keys::Boolean = keys::Boolean::new_boolean("trustCTime", &config::Tree::CORE);
+    /// The `core.untrackedCache` key.
+    pub const UNTRACKED_CACHE: UntrackedCache =
+        UntrackedCache::new_with_validate("untrackedCache", &config::Tree::CORE, validate::UntrackedCache);
     /// The `core.worktree` key.
     pub const WORKTREE: keys::Any = keys::Any::new("worktree", &config::Tree::CORE)
         .with_environment_override("GIT_WORK_TREE")
@@ -120,6 +134,9 @@ impl Section for Core {
             &Self::EDITOR,
             &Self::FILE_MODE,
             &Self::FS_CACHE,
+            &Self::FSMONITOR,
+            &Self::FSMONITOR_HOOK_VERSION,
+            &Self::HOOKS_PATH,
             &Self::IGNORE_CASE,
             &Self::FILES_REF_LOCK_TIMEOUT,
             &Self::PACKED_REFS_TIMEOUT,
@@ -127,8 +144,10 @@ impl Section for Core {
             &Self::LOG_ALL_REF_UPDATES,
             &Self::PRECOMPOSE_UNICODE,
             &Self::REPOSITORY_FORMAT_VERSION,
+            &Self::SPLIT_INDEX,
             &Self::SYMLINKS,
             &Self::TRUST_C_TIME,
+            &Self::UNTRACKED_CACHE,
             &Self::WORKTREE,
             &Self::PROTECT_HFS,
             &Self::PROTECT_NTFS,
@@ -162,6 +181,9 @@ pub type LogAllRefUpdates = keys::Any<validate::LogAllRefUpdates>;
 /// The `core.disambiguate` key.
 pub type Disambiguate = keys::Any<validate::Disambiguate>;
 
+/// The `core.untrackedCache` key.
+pub type UntrackedCache = keys::Any<validate::UntrackedCache>;
+
 #[cfg(feature = "attributes")]
 mod filter {
     use super::validate;
@@ -353,6 +375,28 @@ mod log_all_ref_updates {
     }
 }
 
+mod untracked_cache {
+    use crate::{bstr::ByteSlice, config, config::tree::core::UntrackedCache};
+
+    impl UntrackedCache {
+        /// Returns `Some(true)` if the untracked cache should be used and added to the index if it doesn't have one yet,
+        /// `Some(false)` if it should be removed from the index, and `None` if an existing untracked cache should be kept and used,
+        /// without adding one if it doesn't exist yet, which is what `keep` means.
+        pub fn try_into_untracked_cache(
+            &'static self,
+            value: impl gix_utils::AsBStr,
+        ) -> Result<Option<bool>, config::key::GenericErrorWithValue> {
+            let value = value.as_bstr();
+            if value.as_bstr().eq_ignore_ascii_case(b"keep") {
+                return Ok(None);
+            }
+            let value = gix_config::Boolean::try_from(value.as_bstr())
+                .map_err(|err| config::key::GenericErrorWithValue::from_value(self, value.into()).with_source(err))?;
+            Ok(Some(value.into()))
+        }
+    }
+}
+
 mod check_stat {
     use crate::{bstr::ByteSlice, config, config::tree::core::CheckStat};
 
@@ -449,6 +493,15 @@ mod validate {
         }
     }
 
+    #[derive(Clone, Copy)]
+    pub struct UntrackedCache;
+    impl keys::Validate for UntrackedCache {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::UNTRACKED_CACHE.try_into_untracked_cache(value)?;
+            Ok(())
+        }
+    }
+
     #[derive(Clone, Copy)]
     pub struct CheckStat;
     impl keys::Validate for CheckStat {
@@ -5,8 +5,8 @@ use crate::{
 
 impl Diff {
     /// The `diff.algorithm` key.
-    pub const ALGORITHM: Algorithm = Algorithm::new_with_validate("algorithm", &config::Tree::DIFF, validate::Algorithm)
-                                        .with_deviation("'patience' diff is not implemented and can default to 'histogram' if lenient config is used, and defaults to histogram if unset for fastest and best results");
+    pub const ALGORITHM: Algorithm =
+        Algorithm::new_with_validate("algorithm", &config::Tree::DIFF, validate::Algorithm);
     /// The `diff.renameLimit` key.
     pub const RENAME_LIMIT: keys::UnsignedInteger = keys::UnsignedInteger::new_unsigned_integer(
         "renameLimit",
@@ -24,6 +24,10 @@ impl Diff {
     /// The `diff.renames` key.
     pub const RENAMES: Renames = Renames::new_renames("renames", &config::Tree::DIFF);
 
+    /// The `diff.wordRegex` key.
+    pub const WORD_REGEX: keys::String = keys::String::new_string("wordRegex", &config::Tree::DIFF)
+        .with_note("The regex syntax is the one of the regex crate, not POSIX extended regular expressions");
+
     /// The `diff.<driver>.command` key.
     pub const DRIVER_COMMAND: keys::Program = keys::Program::new_program("command", &config::Tree::DIFF)
         .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
@@ -37,6 +41,18 @@ impl Diff {
     /// The `diff.<driver>.binary` key.
     pub const DRIVER_BINARY: Binary = Binary::new_with_validate("binary", &config::Tree::DIFF, validate::Binary)
         .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")));
+    /// The `diff.<driver>.wordRegex` key.
+    pub const DRIVER_WORD_REGEX: keys::String = keys::String::new_string("wordRegex", &config::Tree::DIFF)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")))
+        .with_note("The regex syntax is the one of the regex crate, not POSIX extended regular expressions");
+    /// The `diff.<driver>.xfuncname` key.
+    pub const DRIVER_XFUNCNAME: keys::String = keys::String::new_string("xfuncname", &config::Tree::DIFF)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")))
+        .with_note("POSIX extended regular expressions are translated into the syntax of the regex crate");
+    /// The `diff.<driver>.funcname` key.
+    pub const DRIVER_FUNCNAME: keys::String = keys::String::new_string("funcname", &config::Tree::DIFF)
+        .with_subsection_requirement(Some(SubSectionRequirement::Parameter("driver")))
+        .with_note("POSIX basic regular expressions are translated into the syntax of the regex crate");
 
     /// The `diff.external` key.
     pub const EXTERNAL: keys::Program =
@@ -54,10 +70,14 @@ impl Section for Diff {
             &Self::IGNORE_SUBMODULES,
             &Self::RENAME_LIMIT,
             &Self::RENAMES,
+            &Self::WORD_REGEX,
             &Self::DRIVER_COMMAND,
             &Self::DRIVER_TEXTCONV,
             &Self::DRIVER_ALGORITHM,
             &Self::DRIVER_BINARY,
+            &Self::DRIVER_WORD_REGEX,
+            &Self::DRIVER_XFUNCNAME,
+            &Self::DRIVER_FUNCNAME,
             &Self::EXTERNAL,
         ]
     }
@@ -78,7 +98,6 @@ pub type Binary = keys::Any<validate::Binary>;
 mod algorithm {
     use crate::{
         bstr::ByteSlice,
-        config,
         config::{
             diff::algorithm,
             key,
@@ -112,7 +131,7 @@ mod algorithm {
             } else if name.eq_ignore_ascii_case(b"histogram") {
                 gix_diff::blob::Algorithm::Histogram
             } else if name.eq_ignore_ascii_case(b"patience") {
-                return Err(config::diff::algorithm::Error::Unimplemented { name: name.into() });
+                gix_diff::blob::Algorithm::Patience
             } else {
                 return Err(algorithm::Error::Unknown { name: name.into() });
             };
@@ -106,6 +106,11 @@ pub mod remote;
 pub struct Safe;
 mod safe;
 
+/// The `splitIndex` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct SplitIndex;
+pub mod split_index;
+
 /// The `ssh` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct Ssh;
@@ -118,6 +123,16 @@ pub struct Status;
 #[cfg(feature = "status")]
 pub mod status;
 
+/// The `transfer` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct Transfer;
+mod transfer;
+
+/// The `uploadpack` top-level section.
+#[derive(Copy, Clone, Default)]
+pub struct UploadPack;
+mod upload_pack;
+
 /// The `user` top-level section.
 #[derive(Copy, Clone, Default)]
 pub struct User;
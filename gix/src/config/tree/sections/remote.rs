@@ -31,6 +31,10 @@ impl Remote {
         http::ProxyAuthMethod::new_proxy_auth_method("proxyAuthMethod", &config::Tree::REMOTE)
             .with_subsection_requirement(NAME_PARAMETER)
             .with_deviation("implemented like git, but never actually tried");
+    /// The `remote.<name>.skipDefaultUpdate` key
+    pub const SKIP_DEFAULT_UPDATE: keys::Boolean =
+        keys::Boolean::new_boolean("skipDefaultUpdate", &config::Tree::REMOTE)
+            .with_subsection_requirement(NAME_PARAMETER);
 }
 
 impl Section for Remote {
@@ -48,6 +52,7 @@ impl Section for Remote {
             &Self::PUSH,
             &Self::PROXY,
             &Self::PROXY_AUTH_METHOD,
+            &Self::SKIP_DEFAULT_UPDATE,
         ]
     }
 }
@@ -0,0 +1,84 @@
+use crate::{
+    config,
+    config::tree::{Key, Section, SplitIndex, keys},
+};
+
+impl SplitIndex {
+    /// The `splitIndex.maxPercentChange` key.
+    pub const MAX_PERCENT_CHANGE: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("maxPercentChange", &config::Tree::SPLIT_INDEX);
+    /// The `splitIndex.sharedIndexExpire` key.
+    pub const SHARED_INDEX_EXPIRE: SharedIndexExpire = SharedIndexExpire::new_with_validate(
+        "sharedIndexExpire",
+        &config::Tree::SPLIT_INDEX,
+        validate::SharedIndexExpire,
+    );
+}
+
+/// The `splitIndex.sharedIndexExpire` key.
+pub type SharedIndexExpire = keys::Any<validate::SharedIndexExpire>;
+
+mod shared_index_expire {
+    use crate::{
+        bstr::ByteSlice,
+        config,
+        config::{key::GenericErrorWithValue, tree::split_index::SharedIndexExpire},
+    };
+
+    impl SharedIndexExpire {
+        /// Parse `value` into the time before which unused shared index files expire, with `now` as reference for relative dates,
+        /// or `None` if they should never expire.
+        ///
+        /// Like in Git, the components of relative dates may also be separated by dots, as in the default of `2.weeks.ago`.
+        pub fn try_into_expiry(
+            &'static self,
+            value: impl gix_utils::AsBStr,
+            now: std::time::SystemTime,
+        ) -> Result<Option<std::time::SystemTime>, config::key::GenericErrorWithValue> {
+            let value = value.as_bstr();
+            if value == "never" || gix_config::Boolean::try_from(value.as_bstr()).is_ok_and(|b| !b.0) {
+                return Ok(None);
+            }
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| {
+                    gix_date::parse(value, Some(now))
+                        .or_else(|_| gix_date::parse(&value.replace('.', " "), Some(now)))
+                        .ok()
+                })
+                .map(|time| {
+                    let seconds = std::time::Duration::from_secs(time.seconds.unsigned_abs());
+                    Some(if time.seconds < 0 {
+                        std::time::UNIX_EPOCH - seconds
+                    } else {
+                        std::time::UNIX_EPOCH + seconds
+                    })
+                })
+                .ok_or_else(|| GenericErrorWithValue::from_value(self, value.into()))
+        }
+    }
+}
+
+impl Section for SplitIndex {
+    fn name(&self) -> &str {
+        "splitIndex"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::MAX_PERCENT_CHANGE, &Self::SHARED_INDEX_EXPIRE]
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    #[derive(Clone, Copy)]
+    pub struct SharedIndexExpire;
+    impl keys::Validate for SharedIndexExpire {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::SplitIndex::SHARED_INDEX_EXPIRE.try_into_expiry(value, std::time::SystemTime::now())?;
+            Ok(())
+        }
+    }
+}
@@ -13,7 +13,7 @@ impl Ssh {
 /// The `ssh.variant` key.
 pub type Variant = keys::Any<validate::Variant>;
 
-#[cfg(feature = "blocking-network-client")]
+#[cfg(any(feature = "blocking-network-client", feature = "async-network-client-connect"))]
 mod variant {
     use crate::{bstr::ByteSlice, config, config::tree::ssh::Variant};
 
@@ -21,11 +21,9 @@ mod variant {
         pub fn try_into_variant(
             &'static self,
             value: impl gix_utils::AsBStr,
-        ) -> Result<
-            Option<gix_protocol::transport::client::blocking_io::ssh::ProgramKind>,
-            config::key::GenericErrorWithValue,
-        > {
-            use gix_protocol::transport::client::blocking_io::ssh::ProgramKind;
+        ) -> Result<Option<gix_protocol::transport::client::ssh::ProgramKind>, config::key::GenericErrorWithValue>
+        {
+            use gix_protocol::transport::client::ssh::ProgramKind;
 
             let value = value.as_bstr();
             Ok(Some(match value.as_bstr().as_bytes() {
@@ -57,7 +55,7 @@ mod validate {
     pub struct Variant;
     impl keys::Validate for Variant {
         fn validate(&self, _value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
-            #[cfg(feature = "blocking-network-client")]
+            #[cfg(any(feature = "blocking-network-client", feature = "async-network-client-connect"))]
             super::Ssh::VARIANT.try_into_variant(_value)?;
             Ok(())
         }
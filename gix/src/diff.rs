@@ -127,6 +127,56 @@ pub mod rename {
     }
 }
 
+/// Types for use with [`Repository::diff_tree_to_parents()`](crate::Repository::diff_tree_to_parents()), to see what a merge
+/// commit changed relative to all of its parents at once, like `git show` does for merges.
+#[cfg(feature = "blob-diff")]
+pub mod combined {
+    use gix_diff::blob::combined::Hunk;
+
+    use crate::bstr::BStr;
+
+    /// The mode and id of an entry in a tree.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct Entry {
+        /// The mode of the entry.
+        pub mode: gix_object::tree::EntryMode,
+        /// The id of the object the entry points to.
+        pub id: gix_hash::ObjectId,
+    }
+
+    /// The way the content of a [`Change`] differs.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum Content<'a> {
+        /// The hunks of the combined diff of the result relative to all parents, which may be empty if only modes changed,
+        /// or if all hunks were dropped as the diff is [dense](gix_diff::blob::combined::Options::dense).
+        Hunks(&'a [Hunk<'a>]),
+        /// At least one version of the file is binary, so there are no hunks, like `git` shows `Binary files differ`.
+        Binary,
+    }
+
+    /// A file that changed relative to all parents of a merge.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub struct Change<'a> {
+        /// The location of the file, relative to the repository.
+        pub location: &'a BStr,
+        /// The entry of the file in each parent, in the order of the parents, or `None` if it didn't exist there.
+        pub parents: &'a [Option<Entry>],
+        /// The entry of the file in the result, or `None` if it was deleted.
+        pub entry: Option<Entry>,
+        /// How the content of the file differs.
+        pub content: Content<'a>,
+    }
+
+    impl Change<'_> {
+        /// Return `true` if the mode of the file in the result is different from its mode in any of the parents,
+        /// which is also the case if it was added or deleted.
+        pub fn mode_differs(&self) -> bool {
+            let mode = self.entry.map(|entry| entry.mode);
+            self.parents.iter().any(|parent| parent.map(|entry| entry.mode) != mode)
+        }
+    }
+}
+
 ///
 #[cfg(feature = "blob-diff")]
 pub(crate) mod utils {
@@ -233,6 +283,8 @@ pub(crate) mod utils {
         let diff_cache = gix_diff::blob::Platform::new(
             gix_diff::blob::platform::Options {
                 algorithm: Some(diff_algo),
+                whitespace: Default::default(),
+                word_regex: repo.config.resolved.string(crate::config::tree::Diff::WORD_REGEX),
                 skip_internal_diff_if_external_is_configured: false,
             },
             gix_diff::blob::Pipeline::new(
@@ -9,6 +9,36 @@ mod options;
 ///
 pub mod iter;
 
+///
+pub mod untracked_cache {
+    use crate::config;
+
+    /// The error returned by [Repository::untracked_cache()](crate::Repository::untracked_cache()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error("A working tree is required to use an untracked cache")]
+        MissingWorkDir,
+        #[error(transparent)]
+        ConfigValue(#[from] config::key::GenericErrorWithValue),
+        #[error(transparent)]
+        ExcludesFilePath(#[from] gix_config::path::interpolate::Error),
+        #[error(transparent)]
+        EnvironmentPermission(#[from] gix_sec::permission::Error<std::path::PathBuf>),
+        #[error(transparent)]
+        StatOptions(#[from] config::stat_options::Error),
+        #[error(transparent)]
+        Prefix(#[from] gix_path::realpath::Error),
+        #[error("Could not read exclude file at '{}'", path.display())]
+        ReadExcludeFile {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        HashExcludeFile(#[from] gix_hash::hasher::Error),
+    }
+}
+
 /// An iterator for entries in a directory walk.
 ///
 /// ### Parallel Operation
@@ -196,6 +196,9 @@ pub use types::{
 #[cfg(feature = "attributes")]
 pub use types::{Pathspec, PathspecDetached, Submodule};
 
+///
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+pub mod checkout;
 ///
 pub mod clone;
 pub mod commit;
@@ -227,6 +230,9 @@ pub mod diff;
 #[cfg(feature = "merge")]
 pub mod merge;
 
+#[cfg(feature = "server")]
+pub mod server;
+
 /// Try to open a git repository in `directory` and search upwards through its parents until one is found,
 /// using default trust options which matters in case the found repository isn't owned by the current user.
 ///
@@ -21,6 +21,9 @@ pub mod virtual_merge_base {
 
 ///
 pub mod commit {
+    use crate::bstr::BString;
+    pub use gix_merge::commit::Strategy;
+
     /// The outcome produced by [`Repository::merge_commits()`](crate::Repository::merge_commits()).
     #[derive(Clone)]
     pub struct Outcome<'a> {
@@ -48,15 +51,13 @@ pub mod commit {
         allow_missing_merge_base: bool,
         tree_merge: crate::merge::tree::Options,
         use_first_merge_base: bool,
+        strategy: Strategy,
+        subtree_prefix: Option<BString>,
     }
 
     impl From<gix_merge::tree::Options> for Options {
         fn from(value: gix_merge::tree::Options) -> Self {
-            Options {
-                tree_merge: value.into(),
-                use_first_merge_base: false,
-                allow_missing_merge_base: false,
-            }
+            crate::merge::tree::Options::from(value).into()
         }
     }
 
@@ -66,6 +67,8 @@ pub mod commit {
                 tree_merge: value,
                 use_first_merge_base: false,
                 allow_missing_merge_base: false,
+                strategy: Strategy::default(),
+                subtree_prefix: None,
             }
         }
     }
@@ -76,16 +79,26 @@ pub mod commit {
                 allow_missing_merge_base,
                 tree_merge,
                 use_first_merge_base,
+                strategy,
+                subtree_prefix,
             }: Options,
         ) -> Self {
             gix_merge::commit::Options {
                 allow_missing_merge_base,
                 tree_merge: tree_merge.into(),
                 use_first_merge_base,
+                strategy,
+                subtree_prefix,
             }
         }
     }
 
+    impl Options {
+        pub(crate) fn renormalize(&self) -> Option<bool> {
+            self.tree_merge.renormalize()
+        }
+    }
+
     /// Builder
     impl Options {
         /// If `true`, merging unrelated commits is allowed, with the merge-base being assumed as empty tree.
@@ -100,6 +113,60 @@ pub mod commit {
             self.use_first_merge_base = use_first_merge_base;
             self
         }
+
+        /// Set the `strategy` to use for merging, like `git merge -s <strategy>`.
+        pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+            self.strategy = strategy;
+            self
+        }
+
+        /// If `Some(prefix)`, shift *their* tree by the subtree at `prefix`, like `git merge -Xsubtree=<prefix>`.
+        ///
+        /// This also works with the default strategy, and determines the subtree to use with [`Strategy::Subtree`].
+        pub fn with_subtree_prefix(mut self, prefix: Option<BString>) -> Self {
+            self.subtree_prefix = prefix;
+            self
+        }
+
+        /// Choose a side for conflicting file content like `git merge -Xours` and `git merge -Xtheirs`, or `None` to
+        /// keep conflict markers.
+        ///
+        /// See [`tree::Options::with_file_favor()`](crate::merge::tree::Options::with_file_favor()) for details.
+        pub fn with_file_favor(mut self, file_favor: Option<crate::merge::tree::FileFavor>) -> Self {
+            self.tree_merge = self.tree_merge.with_file_favor(file_favor);
+            self
+        }
+
+        /// Override the `merge.renormalize` configuration, like `git merge -Xrenormalize` and `git merge -Xno-renormalize` do.
+        ///
+        /// See [`tree::Options::with_renormalize()`](crate::merge::tree::Options::with_renormalize()) for details.
+        pub fn with_renormalize(mut self, renormalize: Option<bool>) -> Self {
+            self.tree_merge = self.tree_merge.with_renormalize(renormalize);
+            self
+        }
+    }
+
+    ///
+    pub mod octopus {
+        /// The outcome produced by [`Repository::merge_commits_octopus()`](crate::Repository::merge_commits_octopus()).
+        #[derive(Clone)]
+        pub struct Outcome<'a> {
+            /// The outcome of the last tree-merge, with the tree editor to write to obtain the actual tree id.
+            ///
+            /// If no tree-merge was needed as all commits could be fast-forwarded to or were already merged,
+            /// it's an unchanged tree without any conflicts.
+            pub tree_merge: crate::merge::tree::Outcome<'a>,
+            /// The commits that were merged in the order they were merged in, including the ones that were fast-forwarded to.
+            pub merged_commits: Vec<gix_hash::ObjectId>,
+            /// The commits that were already reachable from *our* commit or one of the commits merged before.
+            pub up_to_date_commits: Vec<gix_hash::ObjectId>,
+            /// The commits that weren't merged as the merge of the last of the [merged commits](Self::merged_commits)
+            /// had unresolved conflicts.
+            pub unmerged_commits: Vec<gix_hash::ObjectId>,
+            /// A list of virtual commits that were created to merge multiple merge-bases into one for any of the merges.
+            /// As they are not reachable by anything they will be garbage collected, but knowing them provides options.
+            pub virtual_merge_bases: Vec<gix_hash::ObjectId>,
+        }
     }
 }
 
@@ -107,7 +174,7 @@ pub mod commit {
 pub mod tree {
     use gix_merge::blob::builtin_driver;
     pub use gix_merge::tree::{
-        Conflict, ContentMerge, Resolution, ResolutionFailure, TreatAsUnresolved, apply_index_entries,
+        Conflict, ContentMerge, Resolution, ResolutionFailure, TreatAsUnresolved, apply_index_entries, submodule,
         treat_as_unresolved,
     };
 
@@ -160,6 +227,7 @@ pub mod tree {
         inner: gix_merge::tree::Options,
         file_favor: Option<FileFavor>,
         tree_favor: Option<TreeFavor>,
+        renormalize: Option<bool>,
     }
 
     impl From<gix_merge::tree::Options> for Options {
@@ -168,10 +236,17 @@ pub mod tree {
                 inner: opts,
                 file_favor: None,
                 tree_favor: None,
+                renormalize: None,
             }
         }
     }
 
+    impl Options {
+        pub(crate) fn renormalize(&self) -> Option<bool> {
+            self.renormalize
+        }
+    }
+
     impl From<Options> for gix_merge::tree::Options {
         fn from(value: Options) -> Self {
             let mut opts = value.inner;
@@ -281,5 +356,15 @@ pub mod tree {
             self.tree_favor = tree_favor;
             self
         }
+
+        /// When `None`, the default, the `merge.renormalize` configuration decides if all sides of a content merge are
+        /// renormalized, i.e. converted to what would be checked out and back.
+        ///
+        /// With `Some(renormalize)` this configuration is overridden, like `git merge -Xrenormalize` does.
+        /// This avoids conflicts if line-ending conversions or other filters changed on one side.
+        pub fn with_renormalize(mut self, renormalize: Option<bool>) -> Self {
+            self.renormalize = renormalize;
+            self
+        }
     }
 }
@@ -62,6 +62,8 @@ pub mod diff {
     impl Platform<'_> {
         /// Perform a diff on lines between the old and the new version of a blob, passing each hunk of lines to `process_hunk`.
         /// The diffing algorithm is determined by the `diff.algorithm` configuration, or individual diff drivers.
+        /// Lines are compared according to the [whitespace options](gix_diff::blob::platform::Options::whitespace) of the
+        /// resource cache, and hunks that [can be ignored](gix_diff::blob::whitespace::Options::is_ignorable()) are skipped.
         /// Note that `process_hunk` is not called if one of the involved resources are binary, but that can be determined
         /// by introspecting the outcome.
         // TODO: more tests (only tested insertion right now)
@@ -77,13 +79,15 @@ pub mod diff {
 
             let prep = self.resource_cache.prepare_diff()?;
             match prep.operation {
-                Operation::InternalDiff { algorithm } => {
+                Operation::InternalDiff {
+                    algorithm, whitespace, ..
+                } => {
                     let input = prep.interned_input();
-                    let diff = gix_diff::blob::diff_with_slider_heuristics(algorithm, &input);
+                    let diff = gix_diff::blob::whitespace::diff_with_slider_heuristics(algorithm, &input, whitespace);
                     let mut err = None;
                     let mut lines = Vec::new();
 
-                    for hunk in diff.hunks() {
+                    for hunk in diff.hunks().filter(|hunk| !whitespace.is_ignorable(hunk, &input)) {
                         if err.is_some() {
                             break;
                         }
@@ -129,6 +133,8 @@ pub mod diff {
         }
 
         /// Count the amount of removed and inserted lines efficiently.
+        /// Lines are compared according to the [whitespace options](gix_diff::blob::platform::Options::whitespace) of the
+        /// resource cache, and lines of hunks that [can be ignored](gix_diff::blob::whitespace::Options::is_ignorable()) aren't counted.
         /// Note that nothing will happen if one of the inputs is binary, and `None` will be returned.
         pub fn line_counts(
             &mut self,
@@ -137,12 +143,29 @@ pub mod diff {
 
             let prep = self.resource_cache.prepare_diff()?;
             match prep.operation {
-                Operation::InternalDiff { algorithm } => {
+                Operation::InternalDiff {
+                    algorithm, whitespace, ..
+                } => {
                     let input = prep.interned_input();
-                    let diff = gix_diff::blob::Diff::compute(algorithm, &input);
+                    let (removals, insertions) = if whitespace == Default::default() {
+                        let diff = gix_diff::blob::Diff::compute(algorithm, &input);
+                        (diff.count_removals(), diff.count_additions())
+                    } else {
+                        let diff =
+                            gix_diff::blob::whitespace::diff_with_slider_heuristics(algorithm, &input, whitespace);
+                        diff.hunks().filter(|hunk| !whitespace.is_ignorable(hunk, &input)).fold(
+                            (0, 0),
+                            |(removals, insertions), hunk| {
+                                (
+                                    removals + hunk.before.len() as u32,
+                                    insertions + hunk.after.len() as u32,
+                                )
+                            },
+                        )
+                    };
                     Ok(Some(gix_diff::blob::DiffLineStats {
-                        removals: diff.count_removals(),
-                        insertions: diff.count_additions(),
+                        removals,
+                        insertions,
                         before: input.before.len(),
                         after: input.after.len(),
                         similarity: 0.0,
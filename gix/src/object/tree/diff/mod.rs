@@ -170,7 +170,7 @@ pub struct Stats {
 
 ///
 pub mod stats {
-    /// The error returned by [`stats()`](super::Platform::stats()).
+    /// The error returned by [`stats()`](super::Platform::stats()) and [`file_stats()`](super::Platform::file_stats()).
     #[derive(Debug, thiserror::Error)]
     #[expect(missing_docs)]
     pub enum Error {
@@ -218,6 +218,108 @@ impl Platform<'_, '_> {
             lines_removed,
         })
     }
+
+    /// Calculate statistics about each file that changed between our current and the `other` tree, ordered by location,
+    /// for rendering them with [`gix_diff::stats`] like `git diff --stat` does.
+    ///
+    /// Blobs are diffed with the [diff algorithm](gix_diff::blob::Driver::algorithm) configured for them, and
+    /// [binary](gix_diff::blob::Driver::is_binary) blobs are reported with their sizes instead.
+    /// Submodules count one line for the commit they are pointing to, just like `git` shows them as `Subproject commit <id>`.
+    /// Files that changed but don't have changed lines, like those with only whitespace changes that are ignored, are omitted
+    /// unless their mode or location changed as well.
+    pub fn file_stats(&mut self, other: &Tree<'_>) -> Result<Vec<gix_diff::stats::File>, stats::Error> {
+        use gix_diff::{blob::platform::resource::Data, stats::Counts};
+
+        let mut resource_cache = self.lhs.repo.diff_resource_cache_for_tree_diff()?;
+        let mut files = Vec::new();
+        self.for_each_to_obtain_tree(other, |change| {
+            let (source_location, previous, current) = match change {
+                Change::Addition { entry_mode, id, .. } => (None, None, Some((entry_mode, id))),
+                Change::Deletion { entry_mode, id, .. } => (None, Some((entry_mode, id)), None),
+                Change::Modification {
+                    previous_entry_mode,
+                    previous_id,
+                    entry_mode,
+                    id,
+                    ..
+                } => (None, Some((previous_entry_mode, previous_id)), Some((entry_mode, id))),
+                Change::Rewrite {
+                    source_location,
+                    source_entry_mode,
+                    source_id,
+                    entry_mode,
+                    id,
+                    ..
+                } => (
+                    Some(source_location.to_owned()),
+                    Some((source_entry_mode, source_id)),
+                    Some((entry_mode, id)),
+                ),
+            };
+            if previous.is_some_and(|(mode, _)| mode.is_tree()) || current.is_some_and(|(mode, _)| mode.is_tree()) {
+                return Ok(std::ops::ControlFlow::Continue(()));
+            }
+
+            let same_content =
+                matches!((previous, current), (Some((_, previous_id)), Some((_, id))) if previous_id == id);
+            let mode_changed =
+                matches!((previous, current), (Some((previous_mode, _)), Some((mode, _))) if previous_mode != mode);
+            let counts = if previous.is_some_and(|(mode, _)| mode.is_commit())
+                || current.is_some_and(|(mode, _)| mode.is_commit())
+            {
+                Counts::Lines {
+                    insertions: u64::from(current.is_some() && !same_content),
+                    removals: u64::from(previous.is_some() && !same_content),
+                }
+            } else {
+                let mut platform = change.diff(&mut resource_cache)?;
+                match platform.line_counts()? {
+                    Some(_) if same_content => Counts::Lines {
+                        insertions: 0,
+                        removals: 0,
+                    },
+                    Some(counts) => Counts::Lines {
+                        insertions: counts.insertions.into(),
+                        removals: counts.removals.into(),
+                    },
+                    None => {
+                        let prep = platform.resource_cache.prepare_diff()?;
+                        let size = |data: Data<'_>| match data {
+                            Data::Missing => 0,
+                            Data::Buffer { buf, .. } => buf.len() as u64,
+                            Data::Binary { size } => size,
+                        };
+                        if same_content {
+                            Counts::Binary {
+                                size_before: 0,
+                                size_after: 0,
+                            }
+                        } else {
+                            Counts::Binary {
+                                size_before: size(prep.old.data),
+                                size_after: size(prep.new.data),
+                            }
+                        }
+                    }
+                }
+            };
+            resource_cache.clear_resource_cache_keep_allocation();
+
+            let is_modification = previous.is_some() && current.is_some() && source_location.is_none();
+            if is_modification && !mode_changed && counts.is_empty() {
+                return Ok(std::ops::ControlFlow::Continue(()));
+            }
+            files.push(gix_diff::stats::File {
+                source_location,
+                location: change.location().to_owned(),
+                counts,
+            });
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(std::ops::ControlFlow::Continue(()))
+        })?;
+
+        files.sort_by(|a, b| a.location.cmp(&b.location));
+        Ok(files)
+    }
 }
 
 ///
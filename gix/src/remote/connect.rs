@@ -81,20 +81,18 @@ impl<'repo> Remote<'repo> {
     /// The transport used for connection can be configured via `transport_mut().configure()` assuming the actually
     /// used transport is well known. If that's not the case, the transport can be created by hand and passed to
     /// [to_connection_with_transport()][Self::to_connection_with_transport()].
-    #[cfg(any(feature = "blocking-network-client", feature = "async-network-client-async-std"))]
+    #[cfg(any(feature = "blocking-network-client", feature = "async-network-client-connect"))]
     #[gix_protocol::bisync::bisync]
     pub async fn connect(
         &self,
         direction: crate::remote::Direction,
     ) -> Result<Connection<'_, 'static, 'repo, Box<dyn Transport + Send>>, Error> {
         let (url, version) = self.sanitized_url_and_version(direction)?;
-        #[cfg(feature = "blocking-network-client")]
         let scheme_is_ssh = url.scheme == gix_url::Scheme::Ssh;
         let transport = connect::connect(
             url,
             connect::Options {
                 version,
-                #[cfg(feature = "blocking-network-client")]
                 ssh: scheme_is_ssh
                     .then(|| self.repo.ssh_connect_options())
                     .transpose()?
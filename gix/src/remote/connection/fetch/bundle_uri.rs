@@ -0,0 +1,406 @@
+//! Bootstrap a fetch from the bundles a remote points to with the `bundle-uri` command, or from a bundle list given by the user.
+use std::{
+    io::{BufReader, Read, Seek},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_protocol::{
+    BundleUriCommand,
+    bundle_uri::{Header, Heuristic, List, Mode, header},
+};
+use gix_ref::{
+    Target,
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+};
+use gix_transport::client::blocking_io::Transport;
+
+use super::{PrepareDetached, config};
+use crate::{
+    DynNestedProgress, Repository,
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    config::{cache::util::ApplyLeniency, tree::Transfer},
+};
+
+/// The maximum amount of bundle lists that may point to other bundle lists, the same as in `git`.
+const MAX_LIST_DEPTH: usize = 4;
+
+/// The error returned when obtaining or applying bundles.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    Command(#[from] gix_protocol::bundle_uri::Error),
+    #[error(transparent)]
+    List(#[from] gix_protocol::bundle_uri::list::Error),
+    #[error("The bundle list could not be parsed as git configuration")]
+    ParseList(#[from] gix_config::file::init::Error),
+    #[error("Bundle lists are nested more than {MAX_LIST_DEPTH} levels deep")]
+    ListTooDeep,
+    #[error(transparent)]
+    Header(#[from] header::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Url(#[from] gix_url::parse::Error),
+    #[error("Cannot download from '{url}' as the '{scheme}' scheme isn't supported for bundles")]
+    UnsupportedScheme { url: BString, scheme: gix_url::Scheme },
+    #[cfg(any(
+        feature = "blocking-http-transport-curl",
+        feature = "blocking-http-transport-reqwest"
+    ))]
+    #[error(transparent)]
+    TransportOptions(#[from] crate::config::transport::Error),
+    #[cfg(any(
+        feature = "blocking-http-transport-curl",
+        feature = "blocking-http-transport-reqwest"
+    ))]
+    #[error("Could not download via HTTP")]
+    Http(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("The bundle uses the {actual} object format, but the repository uses {expected}")]
+    ObjectHash {
+        actual: gix_hash::Kind,
+        expected: gix_hash::Kind,
+    },
+    #[error("The bundle is filtered with '{filter}', which isn't supported")]
+    Filtered { filter: BString },
+    #[error(transparent)]
+    WritePackOptions(#[from] Box<super::Error>),
+    #[error(transparent)]
+    WritePack(#[from] gix_pack::bundle::write::Error),
+    #[error(transparent)]
+    RefName(#[from] gix_validate::reference::name::Error),
+    #[error(transparent)]
+    EditRefs(#[from] crate::reference::edit::Error),
+}
+
+/// A downloaded bundle whose pack wasn't written yet.
+struct Downloaded {
+    url: BString,
+    header: Header,
+    file: gix_tempfile::Handle<gix_tempfile::handle::Writable>,
+}
+
+impl<T> PrepareDetached<'_, T>
+where
+    T: Transport,
+{
+    /// Download and unbundle the bundles at `bundle_uri`, or if it's `None` and `transfer.bundleURI` is enabled,
+    /// the bundles the remote advertises with the `bundle-uri` command.
+    /// Bundle lists are followed, and their bundles are unbundled once all of their prerequisites are present.
+    /// For each bundle, `refs/heads/<name>` is written to `refs/bundles/<name>` so negotiation can use them.
+    ///
+    /// Return the amount of unbundled bundles. Problems are reported to `progress`, but are not fatal as the fetch
+    /// will obtain all missing objects, just like in `git`.
+    ///
+    /// ### Deviation
+    ///
+    /// All bundles of a list are downloaded, even if the [creation token](Heuristic::CreationToken) heuristic is used,
+    /// and we don't remember the last unbundled creation token in `fetch.bundleCreationToken`.
+    pub(crate) fn bootstrap_from_bundles(
+        &mut self,
+        repo: &Repository,
+        bundle_uri: Option<&BStr>,
+        progress: &mut dyn DynNestedProgress,
+        should_interrupt: &AtomicBool,
+    ) -> usize {
+        let downloaded = match self.download_bundles(repo, bundle_uri, progress) {
+            Ok(downloaded) => downloaded,
+            Err(err) => {
+                progress.info(format!("Could not obtain bundles: {err}"));
+                return 0;
+            }
+        };
+        if downloaded.is_empty() {
+            return 0;
+        }
+        let write_pack_options = match write_pack_options(repo) {
+            Ok(options) => options,
+            Err(err) => {
+                progress.info(format!("Could not unbundle: {err}"));
+                return 0;
+            }
+        };
+
+        let mut pending = downloaded;
+        let mut num_unbundled = 0;
+        loop {
+            let num_pending = pending.len();
+            let mut idx = 0;
+            while idx < pending.len() {
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return num_unbundled;
+                }
+                if pending[idx]
+                    .header
+                    .prerequisites
+                    .iter()
+                    .all(|(id, _comment)| repo.has_object(id))
+                {
+                    let bundle = pending.remove(idx);
+                    let url = bundle.url.clone();
+                    match unbundle(repo, bundle, write_pack_options.clone(), progress, should_interrupt) {
+                        Ok(()) => num_unbundled += 1,
+                        Err(err) => progress.info(format!("Could not unbundle '{url}': {err}")),
+                    }
+                } else {
+                    idx += 1;
+                }
+            }
+            if pending.is_empty() || pending.len() == num_pending {
+                break;
+            }
+        }
+        for bundle in pending {
+            progress.info(format!(
+                "Skipping bundle '{}' as its prerequisites are missing",
+                bundle.url
+            ));
+        }
+        num_unbundled
+    }
+
+    fn download_bundles(
+        &mut self,
+        repo: &Repository,
+        bundle_uri: Option<&BStr>,
+        progress: &mut dyn DynNestedProgress,
+    ) -> Result<Vec<Downloaded>, Error> {
+        let mut out = Vec::new();
+        if let Some(uri) = bundle_uri {
+            download_bundle_or_list(repo, uri, 0, &mut out, progress)?;
+            return Ok(out);
+        }
+
+        if !Transfer::BUNDLE_URI
+            .enrich_error(repo.config.resolved.boolean(Transfer::BUNDLE_URI))
+            .with_leniency(repo.config.lenient_config)?
+            .unwrap_or(false)
+        {
+            return Ok(out);
+        }
+        let con = self
+            .con
+            .as_mut()
+            .expect("bundles are obtained before receiving the pack");
+        let Some(handshake) = con.handshake.as_ref().filter(|handshake| {
+            handshake.server_protocol_version == gix_transport::Protocol::V2
+                && BundleUriCommand::is_supported(&handshake.capabilities)
+        }) else {
+            return Ok(out);
+        };
+        let list = BundleUriCommand::new(&handshake.capabilities, repo.config.user_agent_tuple())
+            .invoke_blocking(&mut con.transport.inner, con.trace)?;
+        let base = con.transport.inner.to_url().into_owned();
+        download_list(repo, &list, Some(base.as_ref()), 0, &mut out, progress);
+        Ok(out)
+    }
+}
+
+/// Download the bundles of `list` into `out`, resolving relative URIs against `base`.
+fn download_list(
+    repo: &Repository,
+    list: &List,
+    base: Option<&BStr>,
+    depth: usize,
+    out: &mut Vec<Downloaded>,
+    progress: &mut dyn DynNestedProgress,
+) {
+    let mut bundles: Vec<_> = list.bundles.iter().collect();
+    if list.heuristic == Some(Heuristic::CreationToken) {
+        bundles.sort_by_key(|bundle| bundle.creation_token.unwrap_or_default());
+    }
+    for bundle in bundles {
+        let url = resolve(base, bundle.uri.as_ref());
+        match download_bundle_or_list(repo, url.as_ref(), depth, out, progress) {
+            Ok(()) if list.mode == Mode::Any => break,
+            Ok(()) => {}
+            Err(err) => progress.info(format!("Could not download bundle '{}' from '{url}': {err}", bundle.id)),
+        }
+    }
+}
+
+/// Download `url` and keep it in `out` if it's a bundle, or download the bundles it lists otherwise.
+fn download_bundle_or_list(
+    repo: &Repository,
+    url: &BStr,
+    depth: usize,
+    out: &mut Vec<Downloaded>,
+    progress: &mut dyn DynNestedProgress,
+) -> Result<(), Error> {
+    let mut file = gix_tempfile::new(
+        repo.objects.store_ref().path(),
+        gix_tempfile::ContainingDirectory::Exists,
+        gix_tempfile::AutoRemove::Tempfile,
+    )?;
+    download(repo, url, &mut file)?;
+    file.rewind()?;
+    match Header::from_read(&mut BufReader::new(&mut file)) {
+        Ok(header) => {
+            out.push(Downloaded {
+                url: url.to_owned(),
+                header,
+                file,
+            });
+            Ok(())
+        }
+        Err(header::Error::UnknownSignature { .. }) => {
+            if depth == MAX_LIST_DEPTH {
+                return Err(Error::ListTooDeep);
+            }
+            file.rewind()?;
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)?;
+            let config =
+                gix_config::File::from_bytes_owned(&mut buf, gix_config::file::Metadata::api(), Default::default())?;
+            let list = crate::config::bundle_list::from_config(&config)?;
+            let base = url.rfind_byte(b'/').map(|pos| url[..pos].as_bstr());
+            download_list(repo, &list, base, depth + 1, out, progress);
+            Ok(())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Resolve `uri` against `base` if it's relative, where `base` is treated like a directory.
+fn resolve(base: Option<&BStr>, uri: &BStr) -> BString {
+    let is_absolute = uri.contains_str("://") || gix_path::try_from_bstr(uri).is_ok_and(|path| path.is_absolute());
+    let Some(base) = base.filter(|_| !is_absolute) else {
+        return uri.to_owned();
+    };
+    let mut base = base.trim_end_with(|c| c == '/').as_bstr().to_owned();
+    let mut uri = uri.as_bytes();
+    loop {
+        if let Some(rest) = uri.strip_prefix(b"./") {
+            uri = rest;
+        } else if let Some(rest) = uri.strip_prefix(b"../") {
+            uri = rest;
+            if let Some(pos) = base.rfind_byte(b'/') {
+                base.truncate(pos);
+            }
+        } else {
+            break;
+        }
+    }
+    base.push_byte(b'/');
+    base.push_str(uri);
+    base
+}
+
+#[cfg_attr(
+    not(any(
+        feature = "blocking-http-transport-curl",
+        feature = "blocking-http-transport-reqwest"
+    )),
+    allow(unused_variables)
+)]
+fn download(repo: &Repository, url: &BStr, out: &mut dyn std::io::Write) -> Result<(), Error> {
+    let parsed = gix_url::parse(url)?;
+    match parsed.scheme {
+        gix_url::Scheme::File => {
+            std::io::copy(
+                &mut std::fs::File::open(gix_path::from_bstr(parsed.path.as_bstr()))?,
+                out,
+            )?;
+        }
+        #[cfg(any(
+            feature = "blocking-http-transport-curl",
+            feature = "blocking-http-transport-reqwest"
+        ))]
+        gix_url::Scheme::Http | gix_url::Scheme::Https => {
+            use gix_transport::client::blocking_io::http::Http;
+            #[cfg(feature = "blocking-http-transport-curl")]
+            let mut http = gix_transport::client::blocking_io::http::curl::Curl::default();
+            #[cfg(all(
+                feature = "blocking-http-transport-reqwest",
+                not(feature = "blocking-http-transport-curl")
+            ))]
+            let mut http = gix_transport::client::blocking_io::http::reqwest::Remote::default();
+            if let Some(options) = repo.transport_options(url, None)? {
+                http.configure(options.as_ref()).map_err(Error::Http)?;
+            }
+            let url = url.to_str().map_err(|err| Error::Http(err.into()))?;
+            let mut response = http
+                .get(url, url, std::iter::empty::<&str>())
+                .map_err(|err| Error::Http(err.into()))?;
+            std::io::copy(&mut response.headers, &mut std::io::sink())?;
+            std::io::copy(&mut response.body, out)?;
+        }
+        scheme => {
+            return Err(Error::UnsupportedScheme {
+                url: url.to_owned(),
+                scheme,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn write_pack_options(repo: &Repository) -> Result<gix_pack::bundle::write::Options, Error> {
+    Ok(gix_pack::bundle::write::Options {
+        thread_limit: config::index_threads(repo).map_err(Box::new)?,
+        index_version: config::pack_index_version(repo).map_err(Box::new)?,
+        iteration_mode: gix_pack::data::input::Mode::Verify,
+        object_hash: repo.object_hash(),
+        alloc_limit_bytes: repo.config.alloc_limit_bytes,
+        compression: repo.config.loose_compression,
+    })
+}
+
+/// Write the pack of `bundle` into `repo` and point `refs/bundles/*` to its branches.
+fn unbundle(
+    repo: &Repository,
+    mut bundle: Downloaded,
+    options: gix_pack::bundle::write::Options,
+    progress: &mut dyn DynNestedProgress,
+    should_interrupt: &AtomicBool,
+) -> Result<(), Error> {
+    let header = &bundle.header;
+    if header.object_hash != repo.object_hash() {
+        return Err(Error::ObjectHash {
+            actual: header.object_hash,
+            expected: repo.object_hash(),
+        });
+    }
+    if let Some(filter) = &header.filter {
+        return Err(Error::Filtered { filter: filter.clone() });
+    }
+
+    bundle.file.rewind()?;
+    let mut pack = BufReader::new(&mut bundle.file);
+    Header::from_read(&mut pack)?;
+    let outcome = gix_pack::Bundle::write_to_directory(
+        &mut pack,
+        Some(&repo.objects.store_ref().path().join("pack")),
+        progress,
+        should_interrupt,
+        Some(repo.objects.clone()),
+        options,
+    )?;
+
+    let mut edits = Vec::new();
+    for (id, name) in &header.refs {
+        // Checking for the object also makes the new pack known to the object database.
+        let Some(short_name) = name.strip_prefix(b"refs/heads/").filter(|_| repo.has_object(id)) else {
+            continue;
+        };
+        edits.push(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: "bundle-uri: unbundle".into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Object(*id),
+            },
+            name: format!("refs/bundles/{}", short_name.as_bstr()).try_into()?,
+            deref: false,
+        });
+    }
+    repo.edit_references(edits)?;
+    if let Some(path) = outcome.keep_path {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
@@ -195,6 +195,8 @@ where
     }
 }
 
+#[cfg(feature = "blocking-network-client")]
+mod bundle_uri;
 mod config;
 mod receive_pack;
 ///
@@ -1,5 +1,6 @@
 use std::{ops::DerefMut, path::PathBuf, sync::atomic::AtomicBool};
 
+use gix_object::Exists;
 use gix_odb::store::RefreshMode;
 use gix_protocol::fetch::{Arguments, negotiate};
 #[cfg(feature = "async-network-client")]
@@ -48,6 +49,12 @@ where
     /// A known application for this behaviour is in `remote-helper` implementations which should send this path via `lock <path>` to stdout
     /// to inform git about the file that it will remove once it updated the refs accordingly.
     ///
+    /// ### Included Tags
+    ///
+    /// With [`Tags::Included`](crate::remote::fetch::Tags::Included), the remote sends the annotated tags that point to objects in the pack.
+    /// Like `git`, tags pointing to objects we already have are fetched with another request that doesn't deepen shallow history.
+    /// This request isn't possible on stateful connections with protocol V0 or V1, so these tags aren't fetched then.
+    ///
     /// ### Deviation
     ///
     /// When **updating refs**, the `git-fetch` docs state the following:
@@ -88,7 +95,7 @@ where
     pub(crate) async fn receive<P>(
         mut self,
         repo: &crate::Repository,
-        progress: P,
+        mut progress: P,
         should_interrupt: &AtomicBool,
     ) -> Result<Outcome, Error>
     where
@@ -135,15 +142,14 @@ where
             trace_packetlines: con.trace,
         };
 
-        let negotiator = repo
+        let algorithm = repo
             .config
             .resolved
             .string(Fetch::NEGOTIATION_ALGORITHM)
             .map(|n| Fetch::NEGOTIATION_ALGORITHM.try_into_negotiation_algorithm(n))
             .transpose()
             .with_leniency(repo.config.lenient_config)?
-            .unwrap_or(Algorithm::Consecutive)
-            .into_negotiator();
+            .unwrap_or(Algorithm::Consecutive);
         let graph_repo = {
             let mut r = repo.clone();
             // assure that checking for unknown server refs doesn't trigger ODB refreshes.
@@ -159,12 +165,13 @@ where
             objects: &graph_repo.objects,
             refs: &graph_repo.refs,
             graph: &mut graph,
-            alternates,
+            alternates: alternates.clone(),
             ref_map,
             shallow: &self.shallow,
             tags: con.remote.fetch_tags,
-            negotiator,
+            negotiator: algorithm.into_negotiator(),
             open_options: repo.options.clone(),
+            received_objects: matches!(self.dry_run, fetch::DryRun::No).then_some(&repo.objects),
         };
 
         let write_pack_options = gix_pack::bundle::write::Options {
@@ -175,6 +182,22 @@ where
             alloc_limit_bytes: repo.config.alloc_limit_bytes,
             compression: repo.config.loose_compression,
         };
+        let write_pack = |reader: &mut dyn std::io::BufRead,
+                          progress: &mut dyn gix_features::progress::DynNestedProgress,
+                          should_interrupt: &AtomicBool|
+         -> Result<_, gix_pack::bundle::write::Error> {
+            gix_pack::Bundle::write_to_directory(
+                reader,
+                Some(&repo.objects.store_ref().path().join("pack")),
+                progress,
+                should_interrupt,
+                Some(Box::new({
+                    let repo = repo.clone();
+                    repo.objects
+                })),
+                write_pack_options.clone(),
+            )
+        };
         let mut write_pack_bundle = None;
 
         let res = gix_protocol::fetch(
@@ -182,17 +205,7 @@ where
             |reader, progress, should_interrupt| -> Result<bool, gix_pack::bundle::write::Error> {
                 let mut may_read_to_end = false;
                 write_pack_bundle = if matches!(self.dry_run, fetch::DryRun::No) {
-                    let res = gix_pack::Bundle::write_to_directory(
-                        reader,
-                        Some(&repo.objects.store_ref().path().join("pack")),
-                        progress,
-                        should_interrupt,
-                        Some(Box::new({
-                            let repo = repo.clone();
-                            repo.objects
-                        })),
-                        write_pack_options,
-                    )?;
+                    let res = write_pack(reader, progress, should_interrupt)?;
                     may_read_to_end = true;
                     Some(res)
                 } else {
@@ -200,17 +213,78 @@ where
                 };
                 Ok(may_read_to_end)
             },
-            progress,
+            &mut progress,
             should_interrupt,
             context,
             fetch_options,
         )
         .await?;
-        let negotiate = res.map(|v| outcome::Negotiate {
+        let mut negotiate = res.map(|v| outcome::Negotiate {
             graph: graph.detach(),
             rounds: v.negotiate.rounds,
         });
 
+        // Like `git`, fetch the included tags the remote didn't send as they point to objects we had already, and don't deepen
+        // shallow history while doing so. This needs another request, which stateful connections of protocol V0 and V1 can't
+        // handle as the remote hangs up after sending the pack.
+        let tags_to_backfill = tags_to_backfill(repo, ref_map, con.remote.fetch_tags);
+        let mut backfilled_pack_bundle = None;
+        if matches!(self.dry_run, fetch::DryRun::No)
+            && !tags_to_backfill.is_empty()
+            && (handshake.server_protocol_version == gix_protocol::transport::Protocol::V2
+                || !con.transport.inner.connection_persists_across_multiple_requests())
+        {
+            let tags_ref_map = gix_protocol::fetch::RefMap {
+                mappings: tags_to_backfill,
+                refspecs: ref_map.refspecs.clone(),
+                extra_refspecs: ref_map.extra_refspecs.clone(),
+                fixes: Vec::new(),
+                remote_refs: Vec::new(),
+                object_hash: ref_map.object_hash,
+            };
+            let mut graph = graph_repo.revision_graph(cache.as_ref());
+            let res = gix_protocol::fetch(
+                &mut Negotiate {
+                    objects: &graph_repo.objects,
+                    refs: &graph_repo.refs,
+                    graph: &mut graph,
+                    alternates,
+                    ref_map: &tags_ref_map,
+                    shallow: &gix_protocol::fetch::Shallow::NoChange,
+                    tags: gix_protocol::fetch::Tags::All,
+                    negotiator: algorithm.into_negotiator(),
+                    open_options: repo.options.clone(),
+                    received_objects: Some(&repo.objects),
+                },
+                |reader, progress, should_interrupt| -> Result<bool, gix_pack::bundle::write::Error> {
+                    backfilled_pack_bundle = Some(write_pack(reader, progress, should_interrupt)?);
+                    Ok(true)
+                },
+                &mut progress,
+                should_interrupt,
+                gix_protocol::fetch::Context {
+                    handshake: &mut handshake,
+                    transport: &mut con.transport.inner,
+                    user_agent: repo.config.user_agent_tuple(),
+                    trace_packetlines: con.trace,
+                },
+                gix_protocol::fetch::Options {
+                    shallow_file: repo.shallow_file(),
+                    shallow: &gix_protocol::fetch::Shallow::NoChange,
+                    tags: gix_protocol::fetch::Tags::None,
+                    reject_shallow_remote: false,
+                },
+            )
+            .await?;
+            if write_pack_bundle.is_none() {
+                negotiate = res.map(|v| outcome::Negotiate {
+                    graph: graph.detach(),
+                    rounds: v.negotiate.rounds,
+                });
+                write_pack_bundle = backfilled_pack_bundle.take();
+            }
+        }
+
         if matches!(handshake.server_protocol_version, gix_protocol::transport::Protocol::V2) {
             gix_protocol::indicate_end_of_interaction(&mut con.transport.inner, con.trace)
                 .await
@@ -230,7 +304,7 @@ where
             self.write_packed_refs,
         )?;
 
-        if let Some(bundle) = write_pack_bundle.as_mut() {
+        for bundle in write_pack_bundle.iter_mut().chain(backfilled_pack_bundle.iter_mut()) {
             if !update_refs.edits.is_empty() || bundle.index.num_objects == 0 {
                 if let Some(path) = bundle.keep_path.take() {
                     std::fs::remove_file(&path).map_err(|err| Error::RemovePackKeepFile { path, source: err })?;
@@ -258,6 +332,31 @@ where
     }
 }
 
+/// Return the mappings of tags that are included implicitly, but weren't sent by the remote even though the objects they
+/// point to are present.
+fn tags_to_backfill(
+    repo: &crate::Repository,
+    ref_map: &gix_protocol::fetch::RefMap,
+    fetch_tags: gix_protocol::fetch::Tags,
+) -> Vec<gix_protocol::fetch::refmap::Mapping> {
+    if !matches!(fetch_tags, gix_protocol::fetch::Tags::Included) {
+        return Vec::new();
+    }
+    let is_implicit_tag = negotiate::make_refmapping_ignore_predicate(fetch_tags, ref_map);
+    ref_map
+        .mappings
+        .iter()
+        .filter(|mapping| is_implicit_tag(mapping))
+        .filter(|mapping| match &mapping.remote {
+            gix_protocol::fetch::refmap::Source::Ref(gix_protocol::handshake::Ref::Peeled { tag, object, .. }) => {
+                !repo.objects.exists(tag) && repo.objects.exists(object)
+            }
+            _ => false,
+        })
+        .cloned()
+        .collect()
+}
+
 struct Negotiate<'a, 'b, 'c> {
     objects: &'a crate::OdbHandle,
     refs: &'a gix_ref::file::Store,
@@ -268,6 +367,8 @@ struct Negotiate<'a, 'b, 'c> {
     tags: gix_protocol::fetch::Tags,
     negotiator: Box<dyn gix_negotiate::Negotiator>,
     open_options: crate::open::Options,
+    /// The object database that receives the pack, or `None` if it is a dry-run.
+    received_objects: Option<&'a crate::OdbHandle>,
 }
 
 impl gix_protocol::fetch::Negotiate for Negotiate<'_, '_, '_> {
@@ -322,4 +423,8 @@ impl gix_protocol::fetch::Negotiate for Negotiate<'_, '_, '_> {
             previous_response,
         )
     }
+
+    fn has_shallow_commit(&mut self, id: &gix_hash::oid) -> bool {
+        self.received_objects.is_none_or(|objects| objects.exists(id))
+    }
 }
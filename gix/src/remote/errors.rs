@@ -60,3 +60,54 @@ pub mod find {
         }
     }
 }
+
+///
+pub mod group {
+    use crate::{bstr::BString, config};
+
+    /// The error returned by [`Repository::remote_group(…)`](crate::Repository::remote_group()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindExisting(#[from] super::find::existing::Error),
+        #[error(transparent)]
+        SkipDefaultUpdate(#[from] config::boolean::Error),
+        #[error("Neither a remote group nor a remote named {name:?} exists")]
+        NotFound { name: BString },
+    }
+
+    ///
+    #[cfg(feature = "blocking-network-client")]
+    pub mod fetch {
+        use crate::bstr::BString;
+
+        /// The error returned by [`Repository::fetch_remote_group(…)`](crate::Repository::fetch_remote_group()).
+        #[derive(Debug, thiserror::Error)]
+        #[expect(missing_docs)]
+        pub enum Error {
+            #[error(transparent)]
+            Group(#[from] super::Error),
+            #[error("The refspecs to fetch from remote {name:?} are invalid")]
+            RefSpecs {
+                name: BString,
+                source: gix_refspec::parse::Error,
+            },
+            #[error("Could not connect to remote {name:?}")]
+            Connect {
+                name: BString,
+                source: crate::remote::connect::Error,
+            },
+            #[error("Could not prepare fetching from remote {name:?}")]
+            PrepareFetch {
+                name: BString,
+                source: crate::remote::fetch::prepare::Error,
+            },
+            #[error("Could not fetch from remote {name:?}")]
+            Fetch {
+                name: BString,
+                source: crate::remote::fetch::Error,
+            },
+        }
+    }
+}
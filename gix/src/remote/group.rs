@@ -0,0 +1,21 @@
+pub use super::errors::group::Error;
+
+///
+#[cfg(feature = "blocking-network-client")]
+pub mod fetch {
+    use crate::bstr::BString;
+    pub use crate::remote::errors::group::fetch::Error;
+
+    /// The options for [`Repository::fetch_remote_group(…)`](crate::Repository::fetch_remote_group()).
+    #[derive(Debug, Clone, Default)]
+    pub struct Options {
+        /// If non-empty, fetch these refspecs instead of the ones configured for each remote, without fetching tags implicitly.
+        pub refspecs: Vec<BString>,
+        /// If `true`, don't change the repository, but do as much as possible to learn what would be changed.
+        pub dry_run: bool,
+        /// How to change the shallow boundary of the repository when fetching from each remote.
+        pub shallow: crate::remote::fetch::Shallow,
+        /// How to obtain the ref-map of each remote.
+        pub ref_map: crate::remote::ref_map::Options,
+    }
+}
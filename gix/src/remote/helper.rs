@@ -0,0 +1,371 @@
+//! Support for remote helpers, the `git-remote-<transport>` programs used for URLs like `<transport>::<address>`.
+//!
+//! The transport handles helpers that can connect or fetch on its own, while helpers that only `import` history need
+//! an [`Importer`] to apply their fast-import streams to the repository, just like `git fast-import` would.
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use gix_hash::ObjectId;
+use gix_ref::{
+    FullName,
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+};
+use gix_transport::client::blocking_io::helper::{
+    Capabilities, Import,
+    fast_import::{Command, DataRef, FileChange, Parser},
+};
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The error returned by [`Importer::apply()`].
+#[derive(Debug, thiserror::Error)]
+#[expect(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Open(#[from] Box<crate::open::Error>),
+    #[error(transparent)]
+    Parse(#[from] gix_transport::client::blocking_io::helper::fast_import::Error),
+    #[error("The mark :{mark} wasn't defined")]
+    UnknownMark { mark: u64 },
+    #[error("Could not resolve {name:?} to an object")]
+    Resolve { name: BString },
+    #[error("Invalid mode {mode:?} for path {path:?}")]
+    InvalidMode { mode: BString, path: BString },
+    #[error("Could not find {path:?} to copy or rename it")]
+    MissingPath { path: BString },
+    #[error("Could not parse the signature {signature:?}")]
+    Signature { signature: BString },
+    #[error("Could not parse the marks file at '{}'", path.display())]
+    Marks { path: std::path::PathBuf },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    FindCommit(#[from] crate::object::find::existing::with_conversion::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    EditTree(#[from] crate::repository::edit_tree::Error),
+    #[error(transparent)]
+    TreeEditor(#[from] gix_object::tree::editor::Error),
+    #[error(transparent)]
+    WriteTree(#[from] crate::object::tree::editor::write::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    EditReferences(#[from] crate::reference::edit::Error),
+}
+
+/// Apply fast-import streams of remote helpers to a repository.
+///
+/// References are updated forcefully, as it's assumed that they are private to the helper as configured by its `refspec` capability.
+///
+/// The repository is reopened for each import, which keeps the importer `Send` even without the `parallel` feature.
+#[derive(Clone)]
+pub struct Importer {
+    git_dir: PathBuf,
+    options: crate::open::Options,
+    committer: Option<gix_actor::Signature>,
+}
+
+impl Importer {
+    /// Create a new instance to import into `repo`, using its current committer for reflog entries.
+    pub fn new(repo: &crate::Repository) -> Self {
+        Importer {
+            git_dir: repo.git_dir().to_owned(),
+            options: repo.open_options().clone(),
+            committer: repo
+                .committer()
+                .and_then(Result::ok)
+                .and_then(|committer| committer.to_owned().ok()),
+        }
+    }
+
+    fn open(&self) -> Result<crate::Repository, Error> {
+        Ok(crate::open_opts(&self.git_dir, self.options.clone()).map_err(Box::new)?)
+    }
+
+    /// Read all commands from `stream` until `done` and apply them, with marks read from the `import-marks` files
+    /// and written to the `export-marks` files of the helper `capabilities`.
+    ///
+    /// Return the final values of all references that were changed.
+    pub fn apply(
+        &self,
+        stream: &mut dyn std::io::BufRead,
+        capabilities: &Capabilities,
+    ) -> Result<BTreeMap<BString, ObjectId>, Error> {
+        let repo = self.open()?;
+        let mut state = State {
+            repo: &repo,
+            marks: HashMap::new(),
+            refs: BTreeMap::new(),
+        };
+        for path in capabilities.values("import-marks") {
+            state.read_marks(&gix_path::from_bstr(path))?;
+        }
+
+        let mut parser = Parser::new(stream);
+        while let Some(command) = parser.next_command()? {
+            match command {
+                Command::Blob(blob) => {
+                    let id = repo.write_blob(&blob.data)?.detach();
+                    state.mark(blob.mark, id);
+                }
+                Command::Commit(commit) => {
+                    let mut parents = Vec::new();
+                    match &commit.from {
+                        Some(from) => parents.push(state.resolve(from.as_bstr())?),
+                        None => {
+                            if let Some(tip) = state.tip(commit.ref_name.as_bstr())? {
+                                parents.push(tip);
+                            }
+                        }
+                    }
+                    for merge in &commit.merges {
+                        parents.push(state.resolve(merge.as_bstr())?);
+                    }
+                    let base = match parents.first() {
+                        Some(parent) => repo.find_commit(*parent)?.tree_id()?.detach(),
+                        None => ObjectId::empty_tree(repo.object_hash()),
+                    };
+                    let tree = state.apply_changes(base, commit.changes)?;
+                    let committer = signature(commit.committer.as_bstr())?;
+                    let author = match &commit.author {
+                        Some(author) => signature(author.as_bstr())?,
+                        None => committer.clone(),
+                    };
+                    let id = repo
+                        .write_object(gix_object::Commit {
+                            tree,
+                            parents: parents.into(),
+                            author,
+                            committer,
+                            encoding: commit.encoding,
+                            message: commit.message.into(),
+                            extra_headers: Vec::new(),
+                        })?
+                        .detach();
+                    state.mark(commit.mark, id);
+                    state.refs.insert(commit.ref_name, Some(id));
+                }
+                Command::Tag(tag) => {
+                    let target = state.resolve(tag.from.as_bstr())?;
+                    let target_kind = repo.find_header(target)?.kind();
+                    let tagger = tag
+                        .tagger
+                        .as_ref()
+                        .map(|tagger| signature(tagger.as_bstr()))
+                        .transpose()?;
+                    let id = repo
+                        .write_object(gix_object::Tag {
+                            target,
+                            target_kind,
+                            name: tag.name.clone(),
+                            tagger,
+                            message: tag.message.into(),
+                            pgp_signature: None,
+                        })?
+                        .detach();
+                    state.mark(tag.mark, id);
+                    state.refs.insert(format!("refs/tags/{}", tag.name).into(), Some(id));
+                }
+                Command::Reset(reset) => {
+                    let id = reset.from.map(|from| state.resolve(from.as_bstr())).transpose()?;
+                    state.refs.insert(reset.ref_name, id);
+                }
+                Command::Done => break,
+                Command::Checkpoint | Command::Progress(_) | Command::Feature(_) | Command::Option(_) => {}
+            }
+        }
+
+        let refs: BTreeMap<BString, ObjectId> = state
+            .refs
+            .iter()
+            .filter_map(|(name, id)| id.map(|id| (name.clone(), id)))
+            .collect();
+        let edits = refs
+            .iter()
+            .map(|(name, id)| {
+                Ok(RefEdit {
+                    change: Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: "fast-import".into(),
+                        },
+                        expected: PreviousValue::Any,
+                        new: (*id).into(),
+                    },
+                    name: FullName::try_from(name.clone()).map_err(|_| Error::Resolve { name: name.clone() })?,
+                    deref: false,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut time = Default::default();
+        repo.edit_references_as(
+            edits,
+            self.committer.as_ref().map(|committer| committer.to_ref(&mut time)),
+        )?;
+
+        for path in capabilities.values("export-marks") {
+            state.write_marks(&gix_path::from_bstr(path))?;
+        }
+        Ok(refs)
+    }
+}
+
+impl Import for Importer {
+    fn import(
+        &mut self,
+        stream: &mut dyn std::io::BufRead,
+        capabilities: &Capabilities,
+        ref_names: &[BString],
+    ) -> Result<Vec<Option<BString>>, BoxError> {
+        let refs = self.apply(stream, capabilities)?;
+        let repo = self.open()?;
+        ref_names
+            .iter()
+            .map(|name| {
+                Ok(match refs.get(name) {
+                    Some(id) => Some(id.to_string().into()),
+                    None => repo
+                        .try_find_reference(name.as_bstr())?
+                        .map(|mut r| r.peel_to_id())
+                        .transpose()?
+                        .map(|id| id.to_string().into()),
+                })
+            })
+            .collect()
+    }
+}
+
+struct State<'repo> {
+    repo: &'repo crate::Repository,
+    marks: HashMap<u64, ObjectId>,
+    /// The references changed by the stream, with `None` for those that were reset to be recreated.
+    refs: BTreeMap<BString, Option<ObjectId>>,
+}
+
+impl State<'_> {
+    fn mark(&mut self, mark: Option<u64>, id: ObjectId) {
+        if let Some(mark) = mark {
+            self.marks.insert(mark, id);
+        }
+    }
+
+    /// Resolve a commit-ish, which is a mark, a hexadecimal object id or the name of a reference.
+    fn resolve(&self, name: &BStr) -> Result<ObjectId, Error> {
+        if let Some(mark) = name.strip_prefix(b":") {
+            let mark = mark
+                .to_str()
+                .ok()
+                .and_then(|mark| mark.parse().ok())
+                .ok_or_else(|| Error::Resolve { name: name.to_owned() })?;
+            return self.marks.get(&mark).copied().ok_or(Error::UnknownMark { mark });
+        }
+        if let Ok(id) = ObjectId::from_hex(name) {
+            return Ok(id);
+        }
+        self.tip(name)?.ok_or_else(|| Error::Resolve { name: name.to_owned() })
+    }
+
+    /// Return the commit the reference `name` points to, if there is one.
+    fn tip(&self, name: &BStr) -> Result<Option<ObjectId>, Error> {
+        if let Some(id) = self.refs.get(name) {
+            return Ok(*id);
+        }
+        Ok(self
+            .repo
+            .try_find_reference(name)?
+            .map(|mut r| r.peel_to_id())
+            .transpose()?
+            .map(crate::Id::detach))
+    }
+
+    fn apply_changes(&self, base: ObjectId, changes: Vec<FileChange>) -> Result<ObjectId, Error> {
+        let repo = self.repo;
+        let mut editor = repo.edit_tree(base)?;
+        for change in changes {
+            let is_rename = matches!(change, FileChange::Rename { .. });
+            match change {
+                FileChange::Modify { mode, data, path } => {
+                    let kind = gix_object::tree::EntryMode::from_bytes(&mode)
+                        .map(|mode| mode.kind())
+                        .ok_or_else(|| Error::InvalidMode {
+                            mode: mode.clone(),
+                            path: path.clone(),
+                        })?;
+                    let id = match data {
+                        DataRef::Mark(mark) => self.marks.get(&mark).copied().ok_or(Error::UnknownMark { mark })?,
+                        DataRef::Id(id) => self.resolve(id.as_bstr())?,
+                        DataRef::Inline(data) => repo.write_blob(&data)?.detach(),
+                    };
+                    editor.upsert(path.as_bstr(), kind, id)?;
+                }
+                FileChange::Delete { path } => {
+                    editor.remove(path.as_bstr())?;
+                }
+                FileChange::Copy { source, destination } | FileChange::Rename { source, destination } => {
+                    let (kind, id) = editor
+                        .get(source.as_bstr())
+                        .map(|entry| (entry.mode().kind(), entry.object_id()))
+                        .ok_or_else(|| Error::MissingPath { path: source.clone() })?;
+                    if is_rename {
+                        editor.remove(source.as_bstr())?;
+                    }
+                    editor.upsert(destination.as_bstr(), kind, id)?;
+                }
+                FileChange::DeleteAll => {
+                    editor = repo.edit_tree(ObjectId::empty_tree(repo.object_hash()))?;
+                }
+            }
+        }
+        Ok(editor.write()?.detach())
+    }
+
+    /// Read marks from `path`, with lines like `:<mark> <id>`, if it exists.
+    fn read_marks(&mut self, path: &Path) -> Result<(), Error> {
+        let marks = match std::fs::read(path) {
+            Ok(marks) => marks,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        for line in marks.lines() {
+            let parsed = line
+                .strip_prefix(b":")
+                .and_then(|line| line.split_once_str(" "))
+                .and_then(|(mark, id)| Some((mark.to_str().ok()?.parse().ok()?, ObjectId::from_hex(id).ok()?)));
+            let (mark, id) = parsed.ok_or_else(|| Error::Marks { path: path.to_owned() })?;
+            self.marks.insert(mark, id);
+        }
+        Ok(())
+    }
+
+    fn write_marks(&self, path: &Path) -> Result<(), Error> {
+        let mut marks: Vec<_> = self.marks.iter().collect();
+        marks.sort();
+        let mut out = Vec::new();
+        for (mark, id) in marks {
+            out.extend_from_slice(format!(":{mark} {id}\n").as_bytes());
+        }
+        std::fs::write(path, out)?;
+        Ok(())
+    }
+}
+
+fn signature(signature: &BStr) -> Result<gix_actor::Signature, Error> {
+    gix_actor::SignatureRef::from_bytes(signature)
+        .map(Into::into)
+        .map_err(|_| Error::Signature {
+            signature: signature.to_owned(),
+        })
+}
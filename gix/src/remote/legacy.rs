@@ -0,0 +1,59 @@
+//! Read remotes from `$GIT_COMMON_DIR/remotes/<name>` and `$GIT_COMMON_DIR/branches/<name>`, which predate
+//! remotes in `git-config` files, just like [`git` does](https://github.com/git/git/blob/master/remote.c).
+use std::path::Path;
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+/// The urls and ref-specs of a remote as read from one of the legacy files.
+#[derive(Default)]
+pub(crate) struct Files {
+    pub urls: Vec<BString>,
+    pub fetch_specs: Vec<BString>,
+    pub push_specs: Vec<BString>,
+}
+
+/// Read the remote `name` from the `remotes` directory in `common_dir`, or from the `branches` directory if the former
+/// doesn't provide a url, or return `None` if neither of them knows the remote.
+///
+/// `default_branch` is the name of the branch to fetch and push if a `branches` file doesn't name it.
+pub(crate) fn read(common_dir: &Path, name: &BStr, default_branch: &BStr) -> Option<Files> {
+    if !is_valid_nick(name) {
+        return None;
+    }
+    let file_name = gix_path::try_from_bstr(name).ok()?;
+    let mut out = Files::default();
+    if let Ok(content) = std::fs::read(common_dir.join("remotes").join(&file_name)) {
+        for line in content.lines() {
+            let line = line.trim_end();
+            if let Some(url) = line.strip_prefix(b"URL:") {
+                out.urls.push(url.trim_start().into());
+            } else if let Some(spec) = line.strip_prefix(b"Push:") {
+                out.push_specs.push(spec.trim_start().into());
+            } else if let Some(spec) = line.strip_prefix(b"Pull:") {
+                out.fetch_specs.push(spec.trim_start().into());
+            }
+        }
+    }
+    if out.urls.is_empty() {
+        if let Some(line) = std::fs::read(common_dir.join("branches").join(&file_name))
+            .ok()
+            .and_then(|content| content.lines().next().map(|line| line.trim().to_owned()))
+            .filter(|line| !line.is_empty())
+        {
+            let (url, branch) = match line.find_byte(b'#') {
+                Some(pos) => (&line[..pos], line[pos + 1..].as_bstr()),
+                None => (line.as_slice(), default_branch),
+            };
+            out.urls.push(url.into());
+            out.fetch_specs
+                .push(format!("refs/heads/{branch}:refs/heads/{name}").into());
+            out.push_specs.push(format!("HEAD:refs/heads/{branch}").into());
+        }
+    }
+    (!out.urls.is_empty() || !out.fetch_specs.is_empty() || !out.push_specs.is_empty()).then_some(out)
+}
+
+/// Names that could be paths are never looked up as file.
+fn is_valid_nick(name: &BStr) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.iter().any(|b| *b == b'/' || *b == b'\\')
+}
@@ -43,6 +43,11 @@ mod build;
 mod errors;
 pub use errors::find;
 
+///
+pub mod group;
+
+pub(crate) mod legacy;
+
 ///
 pub mod init;
 
@@ -58,6 +63,10 @@ mod connection;
 #[cfg(any(feature = "async-network-client", feature = "blocking-network-client"))]
 pub use connection::{AuthenticateFn, Connection, ref_map};
 
+///
+#[cfg(feature = "blocking-network-client")]
+pub mod helper;
+
 ///
 pub mod save;
 
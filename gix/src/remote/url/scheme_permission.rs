@@ -43,7 +43,7 @@ impl TryFrom<&BStr> for Allow {
 
 #[derive(Debug, Clone)]
 pub(crate) struct SchemePermission {
-    /// `None`, env-var is unset or wasn't queried, otherwise true if `GIT_PROTOCOL_FROM_USER` is `1`.
+    /// `None` if the env-var is unset, otherwise true if `GIT_PROTOCOL_FROM_USER` is `1`.
     user_allowed: Option<bool>,
     /// The general allow value from `protocol.allow`.
     allow: Option<Allow>,
@@ -63,7 +63,6 @@ impl SchemePermission {
             .map(|value| Protocol::ALLOW.try_into_allow(value, None))
             .transpose()?;
 
-        let mut saw_user = allow == Some(Allow::User);
         let allow_per_scheme = match config.sections_by_name_and_filter("protocol", &mut filter) {
             Some(it) => {
                 let mut map = BTreeMap::default();
@@ -80,7 +79,6 @@ impl SchemePermission {
                         .map(|value| Protocol::ALLOW.try_into_allow(value, Some(scheme.as_str())))
                         .transpose()?
                     {
-                        saw_user |= value == Allow::User;
                         map.insert(scheme, value);
                     }
                 }
@@ -89,11 +87,10 @@ impl SchemePermission {
             None => Default::default(),
         };
 
-        let user_allowed = saw_user.then(|| {
-            config
-                .string_filter(gitoxide::Allow::PROTOCOL_FROM_USER, &mut filter)
-                .is_none_or(|val| val == "1")
-        });
+        // Remote helpers for unknown protocols are allowed per user by default, so this is always needed.
+        let user_allowed = config
+            .string_filter(gitoxide::Allow::PROTOCOL_FROM_USER, &mut filter)
+            .map(|val| val == "1");
         Ok(SchemePermission {
             allow,
             allow_per_scheme,
@@ -110,8 +107,10 @@ impl SchemePermission {
                 use gix_url::Scheme::*;
                 match scheme {
                     File | Git | Ssh | Http | Https => true,
-                    Ext(_) => false,
-                    // TODO: figure out what 'ext' really entails, and what 'other' protocols are which aren't representable for us yet
+                    // Like `git`, never run arbitrary commands via `git-remote-ext` unless configured.
+                    Ext(name) if name == "ext" => false,
+                    // All other remote helpers are allowed unless the URL may not be from the user.
+                    Ext(_) => Allow::User.to_bool(self.user_allowed),
                 }
             },
             |allow| allow.to_bool(self.user_allowed),
@@ -75,11 +75,10 @@ impl crate::Repository {
     }
 
     /// Obtain options for use when connecting via `ssh`.
-    #[cfg(feature = "blocking-network-client")]
+    #[cfg(any(feature = "blocking-network-client", feature = "async-network-client-connect"))]
     pub fn ssh_connect_options(
         &self,
-    ) -> Result<gix_protocol::transport::client::blocking_io::ssh::connect::Options, config::ssh_connect_options::Error>
-    {
+    ) -> Result<gix_protocol::transport::client::ssh::connect::Options, config::ssh_connect_options::Error> {
         use crate::config::{
             cache::util::ApplyLeniency,
             tree::{Core, Ssh, gitoxide},
@@ -95,7 +94,7 @@ impl crate::Repository {
                 config.string_filter(gitoxide::Ssh::COMMAND_WITHOUT_SHELL_FALLBACK, &mut trusted)
             })
             .map(|cmd| gix_path::from_bstr(cmd).into_owned().into());
-        let opts = gix_protocol::transport::client::blocking_io::ssh::connect::Options {
+        let opts = gix_protocol::transport::client::ssh::connect::Options {
             disallow_shell: fallback_active,
             command: ssh_command,
             kind: config
@@ -103,6 +102,19 @@ impl crate::Repository {
                 .and_then(|variant| Ssh::VARIANT.try_into_variant(variant).transpose())
                 .transpose()
                 .with_leniency(self.options.lenient_config)?,
+            native: config
+                .string_filter(gitoxide::Ssh::NATIVE, &mut trusted)
+                .map(|value| {
+                    gix_config::Boolean::try_from(value.as_bstr())
+                        .map(|native| native.0)
+                        .map_err(|err| {
+                            config::key::GenericErrorWithValue::from_value(&gitoxide::Ssh::NATIVE, value)
+                                .with_source(err)
+                        })
+                })
+                .transpose()
+                .with_leniency(self.options.lenient_config)?
+                .unwrap_or(false),
         };
         Ok(opts)
     }
@@ -35,7 +35,8 @@ impl crate::Repository {
 
     /// Obtain the branch-independent name for a remote for use in the given `direction`, or `None` if it could not be determined.
     ///
-    /// For _fetching_, use the only configured remote, or default to `origin` if it exists.
+    /// For _fetching_, use the only configured remote, or default to `origin` if it exists, which includes
+    /// `origin` remotes only defined in the legacy `remotes/` or `branches/` directories.
     /// For _pushing_, use the `remote.pushDefault` trusted configuration key, or fall back to the rules for _fetching_.
     ///
     /// # Notes
@@ -72,7 +73,7 @@ impl crate::Repository {
         name.or_else(|| {
             let names = self.remote_names();
             match names.len() {
-                0 => None,
+                0 => self.try_find_remote("origin").is_some().then(|| "origin".into()),
                 1 => names.into_iter().next(),
                 _more_than_one => names
                     .iter()
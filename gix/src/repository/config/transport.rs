@@ -14,13 +14,11 @@ impl crate::Repository {
     /// For transports that support proxy authentication, the
     /// [default authentication method](crate::config::Snapshot::credential_helpers()) will be used with the url of the proxy
     /// if it contains a user name.
-    #[cfg_attr(
-        not(any(
-            feature = "blocking-http-transport-reqwest",
-            feature = "blocking-http-transport-curl"
-        )),
-        allow(unused_variables)
-    )]
+    ///
+    /// URLs handled by remote helpers receive [helper options](gix_transport::client::blocking_io::helper::Options) that
+    /// pass this repository and the `remote_name` to the helper, along with an [importer](crate::remote::helper::Importer)
+    /// for helpers that only support `import`.
+    #[cfg_attr(not(feature = "blocking-network-client"), allow(unused_variables))]
     pub fn transport_options<'a>(
         &self,
         url: impl Into<&'a BStr>,
@@ -55,7 +53,7 @@ impl crate::Repository {
                         config,
                         config::{
                             cache::util::ApplyLeniency,
-                            tree::{Key, Remote, gitoxide},
+                            tree::{Key, Remote, Section, gitoxide},
                         },
                     };
                     fn try_to_string(
@@ -88,19 +86,11 @@ impl crate::Repository {
                     }
 
                     fn ssl_version(
-                        config: &gix_config::File,
-                        key_str: &'static str,
+                        value: Option<BString>,
                         key: &'static config::tree::http::SslVersion,
-                        mut filter: fn(&gix_config::file::Metadata) -> bool,
                         lenient: bool,
                     ) -> Result<Option<SslVersion>, config::transport::Error> {
-                        debug_assert_eq!(
-                            key_str,
-                            key.logical_name(),
-                            "BUG: hardcoded and generated key names must match"
-                        );
-                        config
-                            .string_filter(key_str, &mut filter)
+                        value
                             .filter(|v| !v.is_empty())
                             .map(|v| {
                                 key.try_into_ssl_version(v)
@@ -132,11 +122,13 @@ impl crate::Repository {
                     let config = &self.config.resolved;
                     let mut trusted_only = self.filter_config_section();
                     let lenient = self.config.lenient_config;
+                    // Keys in the `http` section may be scoped to URLs, like `http.<url>.proxy`, and are looked up for `url`.
+                    let http = config::tree::Http.name();
                     opts.extra_headers = {
                         let key = "http.extraHeader";
                         debug_assert_eq!(key, &config::tree::Http::EXTRA_HEADER.logical_name());
                         config
-                            .strings_filter(key, &mut trusted_only)
+                            .strings_by_url_filter(http, config::tree::Http::EXTRA_HEADER.name, &url, &mut trusted_only)
                             .map(|values| config::tree::Http::EXTRA_HEADER.try_into_extra_header(values))
                             .transpose()
                             .map_err(|err| config::transport::Error::IllformedUtf8 {
@@ -147,23 +139,38 @@ impl crate::Repository {
                     };
 
                     opts.follow_redirects = {
-                        let key = "http.followRedirects";
+                        let key = &config::tree::Http::FOLLOW_REDIRECTS;
 
-                        config::tree::Http::FOLLOW_REDIRECTS
-                            .try_into_follow_redirects(
-                                config.string_filter(key, &mut trusted_only).unwrap_or_default(),
-                                || config.boolean_filter(key, &mut trusted_only).with_leniency(lenient),
-                            )
-                            .map_err(config::transport::http::Error::InvalidFollowRedirects)?
+                        key.try_into_follow_redirects(
+                            config
+                                .string_by_url_filter(http, key.name, &url, &mut trusted_only)
+                                .unwrap_or_default(),
+                            || {
+                                config
+                                    .boolean_by_url_filter(http, key.name, &url, &mut trusted_only)
+                                    .with_leniency(lenient)
+                            },
+                        )
+                        .map_err(config::transport::http::Error::InvalidFollowRedirects)?
                     };
 
                     opts.low_speed_time_seconds = config::tree::Http::LOW_SPEED_TIME
-                        .try_into_u64(config.integer_filter("http.lowSpeedTime", &mut trusted_only))
+                        .try_into_u64(config.integer_by_url_filter(
+                            http,
+                            config::tree::Http::LOW_SPEED_TIME.name,
+                            &url,
+                            &mut trusted_only,
+                        ))
                         .with_leniency(lenient)
                         .map_err(config::transport::http::Error::from)?
                         .unwrap_or_default();
                     opts.low_speed_limit_bytes_per_second = config::tree::Http::LOW_SPEED_LIMIT
-                        .try_into_u32(config.integer_filter("http.lowSpeedLimit", &mut trusted_only))
+                        .try_into_u32(config.integer_by_url_filter(
+                            http,
+                            config::tree::Http::LOW_SPEED_LIMIT.name,
+                            &url,
+                            &mut trusted_only,
+                        ))
                         .with_leniency(lenient)
                         .map_err(config::transport::http::Error::from)?
                         .unwrap_or_default();
@@ -181,7 +188,7 @@ impl crate::Repository {
                                 let key = "http.proxy";
                                 debug_assert_eq!(key, config::tree::Http::PROXY.logical_name());
                                 let http_proxy = config
-                                    .string_filter(key, &mut trusted_only)
+                                    .string_by_url_filter(http, config::tree::Http::PROXY.name, &url, &mut trusted_only)
                                     .map(|v| (v, key.into(), &config::tree::Http::PROXY))
                                     .or_else(|| {
                                         let key = "gitoxide.http.proxy";
@@ -242,7 +249,12 @@ impl crate::Repository {
                                         let key = "http.proxyAuthMethod";
                                         debug_assert_eq!(key, config::tree::Http::PROXY_AUTH_METHOD.logical_name());
                                         config
-                                            .string_filter(key, &mut trusted_only)
+                                            .string_by_url_filter(
+                                                http,
+                                                config::tree::Http::PROXY_AUTH_METHOD.name,
+                                                &url,
+                                                &mut trusted_only,
+                                            )
                                             .map(|v| (v, key.into(), &config::tree::Http::PROXY_AUTH_METHOD))
                                     })
                             })
@@ -275,16 +287,15 @@ impl crate::Repository {
                     {
                         let key = "http.userAgent";
                         opts.user_agent = config
-                            .string_filter(key, &mut trusted_only)
+                            .string_by_url_filter(http, config::tree::Http::USER_AGENT.name, &url, &mut trusted_only)
                             .and_then(|v| try_to_string(v, lenient, key, &config::tree::Http::USER_AGENT).transpose())
                             .transpose()?
                             .or_else(|| Some(crate::env::agent().into()));
                     }
 
                     {
-                        let key = "http.version";
                         opts.http_version = config
-                            .string_filter(key, &mut trusted_only)
+                            .string_by_url_filter(http, config::tree::Http::VERSION.name, &url, &mut trusted_only)
                             .map(|v| {
                                 config::tree::Http::VERSION
                                     .try_into_http_version(v)
@@ -302,9 +313,8 @@ impl crate::Repository {
                     }
 
                     let may_use_cainfo = {
-                        let key = "http.schannelUseSSLCAInfo";
-                        config::tree::Http::SCHANNEL_USE_SSL_CA_INFO
-                            .enrich_error(config.boolean_filter(key, &mut trusted_only))
+                        let key = &config::tree::Http::SCHANNEL_USE_SSL_CA_INFO;
+                        key.enrich_error(config.boolean_by_url_filter(http, key.name, &url, &mut trusted_only))
                             .with_leniency(lenient)
                             .map_err(config::transport::http::Error::from)?
                             .unwrap_or(true)
@@ -314,7 +324,7 @@ impl crate::Repository {
                         let key = "http.sslCAInfo";
                         debug_assert_eq!(key, config::tree::Http::SSL_CA_INFO.logical_name());
                         opts.ssl_ca_info = config
-                            .path_filter(key, &mut trusted_only)
+                            .path_by_url_filter(http, config::tree::Http::SSL_CA_INFO.name, &url, &mut trusted_only)
                             .map(|p| {
                                 use crate::config::cache::interpolate_context;
                                 p.interpolate(interpolate_context(
@@ -328,27 +338,22 @@ impl crate::Repository {
                     }
 
                     {
+                        let key = &config::tree::Http::SSL_VERSION;
                         opts.ssl_version = ssl_version(
-                            config,
-                            "http.sslVersion",
-                            &config::tree::Http::SSL_VERSION,
-                            trusted_only,
+                            config.string_by_url_filter(http, key.name, &url, &mut trusted_only),
+                            key,
                             lenient,
                         )?
                         .map(|v| SslVersionRangeInclusive { min: v, max: v });
                         let min_max = ssl_version(
-                            config,
-                            "gitoxide.http.sslVersionMin",
+                            config.string_filter("gitoxide.http.sslVersionMin", &mut trusted_only),
                             &gitoxide::Http::SSL_VERSION_MIN,
-                            trusted_only,
                             lenient,
                         )
                         .and_then(|min| {
                             ssl_version(
-                                config,
-                                "gitoxide.http.sslVersionMax",
+                                config.string_filter("gitoxide.http.sslVersionMax", &mut trusted_only),
                                 &gitoxide::Http::SSL_VERSION_MAX,
-                                trusted_only,
                                 lenient,
                             )
                             .map(|max| min.zip(max))
@@ -374,9 +379,9 @@ impl crate::Repository {
                         if ssl_no_verify {
                             opts.ssl_verify = false;
                         } else {
-                            let key = "http.sslVerify";
-                            opts.ssl_verify = config::tree::Http::SSL_VERIFY
-                                .enrich_error(config.boolean_filter(key, &mut trusted_only))
+                            let key = &config::tree::Http::SSL_VERIFY;
+                            opts.ssl_verify = key
+                                .enrich_error(config.boolean_by_url_filter(http, key.name, &url, &mut trusted_only))
                                 .with_leniency(lenient)
                                 .map_err(config::transport::http::Error::from)?
                                 .unwrap_or(true);
@@ -385,9 +390,9 @@ impl crate::Repository {
 
                     #[cfg(feature = "blocking-http-transport-curl")]
                     {
-                        let key = "http.schannelCheckRevoke";
-                        let schannel_check_revoke = config::tree::Http::SCHANNEL_CHECK_REVOKE
-                            .enrich_error(config.boolean_filter(key, &mut trusted_only))
+                        let key = &config::tree::Http::SCHANNEL_CHECK_REVOKE;
+                        let schannel_check_revoke = key
+                            .enrich_error(config.boolean_by_url_filter(http, key.name, &url, &mut trusted_only))
                             .with_leniency(lenient)
                             .map_err(config::transport::http::Error::from)?;
                         let backend =
@@ -399,7 +404,27 @@ impl crate::Repository {
                     Ok(Some(Box::new(opts)))
                 }
             }
-            File | Git | Ssh | Ext(_) => Ok(None),
+            Ext(_) => {
+                #[cfg(not(feature = "blocking-network-client"))]
+                {
+                    Ok(None)
+                }
+                #[cfg(feature = "blocking-network-client")]
+                {
+                    use std::sync::{Arc, Mutex};
+
+                    use gix_transport::client::blocking_io::helper;
+
+                    let opts = helper::Options {
+                        remote_name: remote_name.map(ToOwned::to_owned),
+                        git_dir: Some(self.git_dir().to_owned()),
+                        options: Vec::new(),
+                        import: Some(Arc::new(Mutex::new(crate::remote::helper::Importer::new(self)))),
+                    };
+                    Ok(Some(Box::new(opts)))
+                }
+            }
+            File | Git | Ssh => Ok(None),
         }
     }
 }
@@ -2,7 +2,9 @@ use gix_object::TreeRefIter;
 
 use crate::{
     Repository, Tree,
-    repository::{diff_resource_cache, diff_tree_to_tree},
+    bstr::BString,
+    diff::combined,
+    repository::{diff_resource_cache, diff_tree_to_parents, diff_tree_to_tree},
 };
 
 /// Diff-utilities
@@ -78,6 +80,160 @@ impl Repository {
         Ok(out)
     }
 
+    /// Call `for_each` with each file of `tree` that differs from its version in all `parent_trees`, along with the hunks of
+    /// the combined diff of its content relative to all parents, computed with `options`, like `git show` does for merge commits.
+    ///
+    /// Files are ordered by location, and the diff of their contents is based on the diff of each parent to `tree`
+    /// without rewrite tracking. Submodules are diffed like `git` does, as `Subproject commit <id>` line.
+    /// Files without hunks are omitted unless their [mode differs](combined::Change::mode_differs()), which happens
+    /// with [dense](gix_diff::blob::combined::Options::dense) diffs if the result of the merge matches one of the
+    /// parents in all hunks.
+    pub fn diff_tree_to_parents<E>(
+        &self,
+        parent_trees: &[Tree<'_>],
+        tree: &Tree<'_>,
+        options: gix_diff::blob::combined::Options,
+        mut for_each: impl FnMut(combined::Change<'_>) -> Result<std::ops::ControlFlow<()>, E>,
+    ) -> Result<(), diff_tree_to_parents::Error>
+    where
+        E: Into<Box<dyn std::error::Error + Sync + Send + 'static>>,
+    {
+        use gix_diff::{
+            blob::{ResourceKind, platform::prepare_diff::Operation},
+            tree_with_rewrites::Change,
+        };
+
+        let as_entry = |mode: gix_object::tree::EntryMode, id: gix_hash::ObjectId| {
+            (!mode.is_tree()).then_some(combined::Entry { mode, id })
+        };
+        let mut changes_by_parent = Vec::with_capacity(parent_trees.len());
+        for parent_tree in parent_trees {
+            let changes: std::collections::HashMap<BString, (Option<combined::Entry>, Option<combined::Entry>)> = self
+                .diff_tree_to_tree(parent_tree, tree, crate::diff::Options::default())?
+                .into_iter()
+                .filter_map(|change| {
+                    let (location, previous, current) = match change {
+                        Change::Addition {
+                            location,
+                            entry_mode,
+                            id,
+                            ..
+                        } => (location, None, as_entry(entry_mode, id)),
+                        Change::Deletion {
+                            location,
+                            entry_mode,
+                            id,
+                            ..
+                        } => (location, as_entry(entry_mode, id), None),
+                        Change::Modification {
+                            location,
+                            previous_entry_mode,
+                            previous_id,
+                            entry_mode,
+                            id,
+                        } => (
+                            location,
+                            as_entry(previous_entry_mode, previous_id),
+                            as_entry(entry_mode, id),
+                        ),
+                        Change::Rewrite { .. } => unreachable!("BUG: rewrites are not tracked"),
+                    };
+                    (previous.is_some() || current.is_some()).then_some((location, (previous, current)))
+                })
+                .collect();
+            changes_by_parent.push(changes);
+        }
+        let Some((first_parent_changes, other_parent_changes)) = changes_by_parent.split_first() else {
+            return Ok(());
+        };
+        let mut locations: Vec<_> = first_parent_changes
+            .keys()
+            .filter(|location| {
+                other_parent_changes
+                    .iter()
+                    .all(|changes| changes.contains_key(*location))
+            })
+            .collect();
+        locations.sort();
+
+        let mut resource_cache = self.diff_resource_cache_for_tree_diff()?;
+        let is_diffable = |entry: Option<combined::Entry>| entry.is_some_and(|entry| !entry.mode.is_commit());
+        let submodule_line = |entry: Option<combined::Entry>| {
+            entry
+                .filter(|entry| entry.mode.is_commit())
+                .map(|entry| format!("Subproject commit {}\n", entry.id).into_bytes())
+        };
+        for location in locations {
+            let parents: Vec<_> = changes_by_parent.iter().map(|changes| changes[location].0).collect();
+            let entry = first_parent_changes[location].1;
+
+            let (mut algorithm, mut is_binary) = (None, false);
+            let mut parents_data = Vec::with_capacity(parents.len());
+            let mut result_data = submodule_line(entry);
+            for parent in &parents {
+                let mut parent_data = submodule_line(*parent);
+                if is_diffable(*parent) || is_diffable(entry) {
+                    let null_id = gix_hash::ObjectId::null(self.object_hash());
+                    for (side, kind) in [
+                        (*parent, ResourceKind::OldOrSource),
+                        (entry, ResourceKind::NewOrDestination),
+                    ] {
+                        let (id, mode) = side
+                            .filter(|entry| !entry.mode.is_commit())
+                            .map_or((null_id, gix_object::tree::EntryKind::Blob), |entry| {
+                                (entry.id, entry.mode.kind())
+                            });
+                        resource_cache.set_resource(id, mode, location.as_ref(), kind, &self.objects)?;
+                    }
+                    let prep = resource_cache.prepare_diff()?;
+                    match prep.operation {
+                        Operation::InternalDiff { algorithm: algo, .. } => {
+                            algorithm.get_or_insert(algo);
+                        }
+                        Operation::SourceOrDestinationIsBinary => is_binary = true,
+                        Operation::ExternalCommand { .. } => {}
+                    }
+                    parent_data = parent_data.or_else(|| prep.old.data.as_slice().map(ToOwned::to_owned));
+                    result_data = result_data.or_else(|| prep.new.data.as_slice().map(ToOwned::to_owned));
+                }
+                parents_data.push(parent_data.unwrap_or_default());
+            }
+            resource_cache.clear_resource_cache_keep_allocation();
+
+            let change = combined::Change {
+                location: location.as_ref(),
+                parents: &parents,
+                entry,
+                content: combined::Content::Binary,
+            };
+            let action = if is_binary {
+                for_each(change)
+            } else {
+                let parents_data: Vec<_> = parents_data.iter().map(Vec::as_slice).collect();
+                let hunks = gix_diff::blob::combined::diff(
+                    algorithm.unwrap_or_default(),
+                    &parents_data,
+                    result_data.as_deref().unwrap_or_default(),
+                    options,
+                )?;
+                if hunks.is_empty() && !change.mode_differs() {
+                    continue;
+                }
+                for_each(combined::Change {
+                    content: combined::Content::Hunks(&hunks),
+                    ..change
+                })
+            };
+            if action
+                .map_err(|err| diff_tree_to_parents::Error::ForEach(err.into()))?
+                .is_break()
+            {
+                break;
+            }
+        }
+        Ok(())
+    }
+
     /// Return a resource cache suitable for diffing blobs from trees directly, where no worktree checkout exists.
     ///
     /// For more control, see [`diff_resource_cache()`](Self::diff_resource_cache).
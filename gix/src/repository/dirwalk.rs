@@ -3,7 +3,9 @@ use std::sync::atomic::AtomicBool;
 use crate::{
     Repository,
     bstr::{BStr, BString},
-    config, dirwalk, is_dir_to_mode,
+    config,
+    config::cache::util::ApplyLeniency,
+    dirwalk, is_dir_to_mode,
     util::OwnedOrStaticAtomicBool,
     worktree::IndexPersistedOrInMemory,
 };
@@ -101,6 +103,7 @@ impl Repository {
                 excludes: Some(&mut excludes.inner),
                 objects: &self.objects,
                 explicit_traversal_root: (!options.empty_patterns_match_prefix).then_some(workdir),
+                untracked_cache: None,
             },
             opts,
             delegate,
@@ -114,6 +117,66 @@ impl Repository {
         })
     }
 
+    /// Return the untracked cache to accelerate directory walks over the worktree, as configured by `core.untrackedCache`,
+    /// or `None` if it shouldn't be used.
+    ///
+    /// If `core.untrackedCache` is `true`, the cache stored in `index` is used, or a new one is created if there is none.
+    /// If it is unset or `keep`, only a cache already stored in `index` will be used. If it's `false`, no cache is used.
+    ///
+    /// Note that a cache stored in `index` is discarded if it doesn't match this worktree or the current global
+    /// excludes anymore, and a new one is created instead. Once the walk is done, the cache should be
+    /// [stored in the index](gix_index::State::set_untracked()) if it [was modified](gix_dir::walk::UntrackedCache::is_modified()).
+    pub fn untracked_cache(
+        &self,
+        index: &gix_index::State,
+    ) -> Result<Option<gix_dir::walk::UntrackedCache>, dirwalk::untracked_cache::Error> {
+        use dirwalk::untracked_cache::Error;
+        let workdir = self.workdir().ok_or(Error::MissingWorkDir)?;
+        let enabled = self
+            .config
+            .resolved
+            .string(config::tree::Core::UNTRACKED_CACHE)
+            .map(|value| config::tree::Core::UNTRACKED_CACHE.try_into_untracked_cache(value))
+            .transpose()
+            .with_leniency(self.config.lenient_config)?
+            .flatten();
+        let enabled = enabled.unwrap_or_else(|| index.untracked().is_some());
+        if !enabled {
+            return Ok(None);
+        }
+
+        let oid_stat = |path: Option<std::path::PathBuf>| -> Result<Option<gix_index::extension::untracked_cache::OidStat>, Error> {
+            let Some(path) = path else { return Ok(None) };
+            let Some(stat) = gix_index::fs::Metadata::from_path_no_follow(&path)
+                .ok()
+                .and_then(|md| gix_index::entry::Stat::from_fs(&md).ok())
+            else {
+                return Ok(None);
+            };
+            let data = std::fs::read(&path).map_err(|source| Error::ReadExcludeFile { path, source })?;
+            Ok(Some(gix_index::extension::untracked_cache::OidStat {
+                stat,
+                id: gix_dir::walk::untracked_cache::exclude_file_id(data, self.object_hash())?,
+            }))
+        };
+        let excludes_file = match self.config.excludes_file()? {
+            Some(path) => Some(path),
+            None => self.config.xdg_config_path("ignore")?,
+        };
+        let workdir = crate::path::realpath_opts(workdir, self.current_dir(), crate::path::realpath::MAX_SYMLINKS)?;
+        let mut identifier = BString::from(format!("Location {}, system {}", workdir.display(), system_name()));
+        identifier.push(0);
+
+        Ok(Some(gix_dir::walk::UntrackedCache::new(
+            index.untracked().cloned(),
+            identifier,
+            oid_stat(Some(self.common_dir().join("info").join("exclude")))?,
+            oid_stat(excludes_file)?,
+            self.object_hash(),
+            self.stat_options()?,
+        )))
+    }
+
     /// Create an iterator over a running traversal, which stops if the iterator is dropped. All arguments
     /// are the same as in [`dirwalk()`](Self::dirwalk).
     ///
@@ -135,3 +198,16 @@ impl Repository {
         )
     }
 }
+
+/// The name of the operating system as `uname` would return it, which Git uses to identify untracked caches.
+fn system_name() -> &'static str {
+    match std::env::consts::OS {
+        "linux" | "android" => "Linux",
+        "macos" | "ios" => "Darwin",
+        "windows" => "Windows",
+        "freebsd" => "FreeBSD",
+        "netbsd" => "NetBSD",
+        "openbsd" => "OpenBSD",
+        other => other,
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    config::{cache::util::ApplyLeniencyDefault, tree::Index},
+    config::{
+        cache::util::{ApplyLeniency, ApplyLeniencyDefault},
+        tree::{Core, Index, SplitIndex},
+    },
     worktree,
     worktree::IndexPersistedOrInMemory,
 };
@@ -50,6 +53,62 @@ impl crate::Repository {
         Ok(index)
     }
 
+    /// Return the options to use when writing the index, as configured by `index.skipHash`, `core.splitIndex`,
+    /// `splitIndex.maxPercentChange` and `splitIndex.sharedIndexExpire`.
+    ///
+    /// Pass them to [`gix_index::File::write()`] to split the index if `core.splitIndex` is `true`, or turn a split index back
+    /// into a regular one if it is `false`. If it isn't set, a split index stays split and a regular one stays regular.
+    pub fn index_write_options(
+        &self,
+    ) -> Result<gix_index::write::Options, crate::repository::index_write_options::Error> {
+        let config = &self.config.resolved;
+        let lenient = self.config.lenient_config;
+        let skip_hash = Index::SKIP_HASH
+            .enrich_error(config.boolean(Index::SKIP_HASH))
+            .with_lenient_default(lenient)?
+            .unwrap_or_default();
+        let split_index = Core::SPLIT_INDEX
+            .enrich_error(config.boolean(Core::SPLIT_INDEX))
+            .with_lenient_default(lenient)?;
+        let split_index = if split_index != Some(false) {
+            let mut options = gix_index::write::SplitIndex {
+                only_if_split: split_index.is_none(),
+                ..Default::default()
+            };
+            if let Some(max_percent_change) = SplitIndex::MAX_PERCENT_CHANGE
+                .try_into_u64(config.integer(SplitIndex::MAX_PERCENT_CHANGE))
+                .and_then(|value| {
+                    value
+                        .map(|value| {
+                            u8::try_from(value).ok().filter(|value| *value <= 100).ok_or_else(|| {
+                                crate::config::unsigned_integer::Error::from(&SplitIndex::MAX_PERCENT_CHANGE)
+                            })
+                        })
+                        .transpose()
+                })
+                .with_leniency(lenient)?
+            {
+                options.max_percent_change = max_percent_change;
+            }
+            if let Some(expire) = config
+                .string(SplitIndex::SHARED_INDEX_EXPIRE)
+                .map(|value| SplitIndex::SHARED_INDEX_EXPIRE.try_into_expiry(&*value, std::time::SystemTime::now()))
+                .transpose()
+                .with_leniency(lenient)?
+            {
+                options.shared_index_expire = expire;
+            }
+            Some(options)
+        } else {
+            None
+        };
+        Ok(gix_index::write::Options {
+            extensions: Default::default(),
+            skip_hash,
+            split_index,
+        })
+    }
+
     /// Return a shared worktree index which is updated automatically if the in-memory snapshot has become stale as the underlying file
     /// on disk has changed.
     ///
@@ -3,11 +3,12 @@ use gix_object::Write;
 
 use crate::{
     Repository,
+    bstr::BStr,
     config::{cache::util::ApplyLeniencyDefault, tree},
     prelude::ObjectIdExt,
     repository::{
-        blob_merge_options, merge_commits, merge_resource_cache, merge_trees, tree_merge_options, virtual_merge_base,
-        virtual_merge_base_with_graph,
+        blob_merge_options, merge_commits, merge_commits_octopus, merge_resource_cache, merge_trees,
+        tree_merge_options, virtual_merge_base, virtual_merge_base_with_graph,
     },
 };
 
@@ -22,13 +23,25 @@ impl Repository {
     pub fn merge_resource_cache(
         &self,
         worktree_roots: gix_merge::blob::pipeline::WorktreeRoots,
+    ) -> Result<gix_merge::blob::Platform, merge_resource_cache::Error> {
+        self.merge_resource_cache_inner(worktree_roots, None)
+    }
+
+    /// Like [`merge_resource_cache()`](Self::merge_resource_cache()), but `renormalize` overrides `merge.renormalize` if set.
+    fn merge_resource_cache_inner(
+        &self,
+        worktree_roots: gix_merge::blob::pipeline::WorktreeRoots,
+        renormalize: Option<bool>,
     ) -> Result<gix_merge::blob::Platform, merge_resource_cache::Error> {
         let index = self.index_or_load_from_head_or_empty()?;
         let mode = {
-            let renormalize = tree::Merge::RENORMALIZE
-                .enrich_error(self.config.resolved.boolean(tree::Merge::RENORMALIZE))
-                .with_lenient_default(self.config.lenient_config)?
-                .unwrap_or_default();
+            let renormalize = match renormalize {
+                Some(renormalize) => renormalize,
+                None => tree::Merge::RENORMALIZE
+                    .enrich_error(self.config.resolved.boolean(tree::Merge::RENORMALIZE))
+                    .with_lenient_default(self.config.lenient_config)?
+                    .unwrap_or_default(),
+            };
             if renormalize {
                 gix_merge::blob::pipeline::Mode::Renormalize
             } else {
@@ -54,6 +67,29 @@ impl Repository {
         Ok(gix_merge::blob::Platform::new(filter, mode, attrs, drivers, options))
     }
 
+    /// Open the repository of the submodule at the repository-relative `path` so the commits of its gitlinks can be merged,
+    /// or return `None` if there is no such submodule or if it isn't checked out.
+    fn open_submodule_for_merge(
+        &self,
+        path: &BStr,
+    ) -> Result<
+        Option<Box<dyn gix_merge::tree::submodule::Repository>>,
+        Box<dyn std::error::Error + Send + Sync + 'static>,
+    > {
+        let Some(submodules) = self.submodules()? else {
+            return Ok(None);
+        };
+        for submodule in submodules {
+            if submodule.path()? != path {
+                continue;
+            }
+            return Ok(submodule
+                .open()?
+                .map(|repo| Box::new(MergeSubmodule(repo)) as Box<dyn gix_merge::tree::submodule::Repository>));
+        }
+        Ok(None)
+    }
+
     /// Return options for use with [`gix_merge::blob::PlatformRef::merge()`], accessible through
     /// [merge_resource_cache()](Self::merge_resource_cache).
     pub fn blob_merge_options(&self) -> Result<gix_merge::blob::platform::merge::Options, blob_merge_options::Error> {
@@ -115,6 +151,10 @@ impl Repository {
     ///
     /// Note that `ancestor_tree` can be the [empty tree hash](gix_hash::ObjectId::empty_tree) to indicate no common ancestry.
     ///
+    /// Submodules whose commit was changed on both sides are fast-forwarded if one commit contains the other, like Git does.
+    /// This requires the submodule to be checked out, which is also where merge commits are searched for to suggest them
+    /// as resolution of the conflict.
+    ///
     /// `labels` are typically chosen to identify the refs or names for `our_tree` and `their_tree` and `ancestor_tree` respectively.
     ///
     /// `options` should be initialized with [`tree_merge_options()`](Self::tree_merge_options()).
@@ -132,7 +172,7 @@ impl Repository {
         options: crate::merge::tree::Options,
     ) -> Result<crate::merge::tree::Outcome<'_>, merge_trees::Error> {
         let mut diff_cache = self.diff_resource_cache_for_tree_diff()?;
-        let mut blob_merge = self.merge_resource_cache(Default::default())?;
+        let mut blob_merge = self.merge_resource_cache_inner(Default::default(), options.renormalize())?;
         let gix_merge::tree::Outcome {
             tree,
             conflicts,
@@ -147,6 +187,7 @@ impl Repository {
             &mut Default::default(),
             &mut diff_cache,
             &mut blob_merge,
+            &mut |path| self.open_submodule_for_merge(path),
             options.into(),
         )?;
 
@@ -172,6 +213,8 @@ impl Repository {
     /// `labels` are typically chosen to identify the refs or names for `our_commit` and `their_commit`, with the ancestor being set
     /// automatically as part of the merge-base handling.
     ///
+    /// Submodules are merged as described for [`merge_trees()`](Self::merge_trees()).
+    ///
     /// `options` should be initialized with [`Repository::tree_merge_options().into()`](Self::tree_merge_options()).
     ///
     /// ### Performance
@@ -186,7 +229,7 @@ impl Repository {
         options: crate::merge::commit::Options,
     ) -> Result<crate::merge::commit::Outcome<'_>, merge_commits::Error> {
         let mut diff_cache = self.diff_resource_cache_for_tree_diff()?;
-        let mut blob_merge = self.merge_resource_cache(Default::default())?;
+        let mut blob_merge = self.merge_resource_cache_inner(Default::default(), options.renormalize())?;
         let commit_graph = self.commit_graph_if_enabled()?;
         let mut graph = self.revision_graph(commit_graph.as_ref());
         let gix_merge::commit::Outcome {
@@ -208,6 +251,7 @@ impl Repository {
             &mut blob_merge,
             self,
             &mut |id| id.to_owned().attach(self).shorten_or_id().to_string(),
+            &mut |path| self.open_submodule_for_merge(path),
             options.into(),
         )?;
 
@@ -229,6 +273,81 @@ impl Repository {
         })
     }
 
+    /// Merge all `their_commits` into `our_commit` one after another, like `git merge -s octopus` does, to yield a new tree
+    /// for a commit with all of them as parents, which is provided as [tree editor](crate::object::tree::Editor)
+    /// to inspect and finalize results at will.
+    /// The merge stops at the first merge with unresolved conflicts, leaving all following commits unmerged.
+    /// No change to the worktree or index is made, but objects may be written to the object database as merge results
+    /// are stored.
+    /// If these changes should not be observable outside of this instance, consider [enabling object memory](Self::with_object_memory).
+    ///
+    /// `labels` are typically chosen to identify the refs or names for `our_commit`, and if the label for *their* side isn't set,
+    /// the shortened id of each merged commit is used instead.
+    ///
+    /// `options` should be initialized with [`Repository::tree_merge_options().into()`](Self::tree_merge_options()), and
+    /// are used for each merge.
+    ///
+    /// See [`gix_merge::commit::octopus()`] for details.
+    ///
+    /// ### Performance
+    ///
+    /// It's highly recommended to [set an object cache](Repository::compute_object_cache_size_for_tree_diffs)
+    /// to avoid extracting the same object multiple times.
+    pub fn merge_commits_octopus(
+        &self,
+        our_commit: impl Into<gix_hash::ObjectId>,
+        their_commits: impl IntoIterator<Item = impl Into<gix_hash::ObjectId>>,
+        labels: gix_merge::blob::builtin_driver::text::Labels<'_>,
+        options: crate::merge::commit::Options,
+    ) -> Result<crate::merge::commit::octopus::Outcome<'_>, merge_commits_octopus::Error> {
+        let their_commits: Vec<_> = their_commits.into_iter().map(Into::into).collect();
+        let mut diff_cache = self.diff_resource_cache_for_tree_diff()?;
+        let mut blob_merge = self.merge_resource_cache_inner(Default::default(), options.renormalize())?;
+        let commit_graph = self.commit_graph_if_enabled()?;
+        let mut graph = self.revision_graph(commit_graph.as_ref());
+        let gix_merge::commit::octopus::Outcome {
+            tree_merge:
+                gix_merge::tree::Outcome {
+                    tree,
+                    conflicts,
+                    failed_on_first_unresolved_conflict,
+                },
+            merged_commits,
+            up_to_date_commits,
+            unmerged_commits,
+            virtual_merge_bases,
+        } = gix_merge::commit::octopus(
+            our_commit.into(),
+            &their_commits,
+            labels,
+            &mut graph,
+            &mut diff_cache,
+            &mut blob_merge,
+            self,
+            &mut |id| id.to_owned().attach(self).shorten_or_id().to_string(),
+            &mut |path| self.open_submodule_for_merge(path),
+            options.into(),
+        )?;
+
+        let validate = self.config.protect_options()?;
+        let tree_merge = crate::merge::tree::Outcome {
+            tree: crate::object::tree::Editor {
+                inner: tree,
+                validate,
+                repo: self,
+            },
+            conflicts,
+            failed_on_first_unresolved_conflict,
+        };
+        Ok(crate::merge::commit::octopus::Outcome {
+            tree_merge,
+            merged_commits,
+            up_to_date_commits,
+            unmerged_commits,
+            virtual_merge_bases,
+        })
+    }
+
     /// Create a single virtual merge-base by merging all `merge_bases` into one.
     /// If the list is empty, an error will be returned as the histories are then unrelated.
     /// If there is only one commit in the list, it is returned directly with this case clearly marked in the outcome.
@@ -271,7 +390,7 @@ impl Repository {
         };
 
         let mut diff_cache = self.diff_resource_cache_for_tree_diff()?;
-        let mut blob_merge = self.merge_resource_cache(Default::default())?;
+        let mut blob_merge = self.merge_resource_cache_inner(Default::default(), options.renormalize())?;
 
         let gix_merge::commit::virtual_merge_base::Outcome {
             virtual_merge_bases,
@@ -286,6 +405,7 @@ impl Repository {
             &mut blob_merge,
             self,
             &mut |id| id.to_owned().attach(self).shorten_or_id().to_string(),
+            &mut |path| self.open_submodule_for_merge(path),
             options.into(),
         )?;
 
@@ -296,3 +416,25 @@ impl Repository {
         })
     }
 }
+
+/// The repository of a submodule whose commits are merged as part of a tree-merge.
+struct MergeSubmodule(Repository);
+
+impl gix_merge::tree::submodule::Repository for MergeSubmodule {
+    fn objects(&self) -> &dyn gix_object::Find {
+        &self.0
+    }
+
+    fn reference_tips(
+        &mut self,
+    ) -> Result<Vec<gix_hash::ObjectId>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let mut tips: Vec<_> = self.0.head_id().ok().map(crate::Id::detach).into_iter().collect();
+        for reference in self.0.references()?.all()? {
+            // Like Git, ignore references that can't be peeled, which also happens if they point to nothing.
+            if let Ok(id) = reference?.peel_to_id() {
+                tips.push(id.detach());
+            }
+        }
+        Ok(tips)
+    }
+}
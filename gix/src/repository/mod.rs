@@ -61,7 +61,7 @@ mod thread_safe;
 mod worktree;
 
 ///
-mod new_commit {
+pub(crate) mod new_commit {
     /// The error returned by [`new_commit(…)`](crate::Repository::new_commit()).
     #[derive(Debug, thiserror::Error)]
     pub enum Error {
@@ -135,6 +135,28 @@ pub mod diff_tree_to_tree {
     }
 }
 
+///
+#[cfg(feature = "blob-diff")]
+pub mod diff_tree_to_parents {
+    /// The error returned by [Repository::diff_tree_to_parents()](crate::Repository::diff_tree_to_parents()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        TreeDiff(#[from] super::diff_tree_to_tree::Error),
+        #[error(transparent)]
+        CreateResourceCache(#[from] super::diff_resource_cache::Error),
+        #[error(transparent)]
+        SetResource(#[from] gix_diff::blob::platform::set_resource::Error),
+        #[error(transparent)]
+        PrepareDiff(#[from] gix_diff::blob::platform::prepare_diff::Error),
+        #[error(transparent)]
+        CombinedDiff(#[from] gix_diff::blob::combined::Error),
+        #[error("The user-provided callback failed")]
+        ForEach(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    }
+}
+
 ///
 #[cfg(feature = "merge")]
 pub mod blob_merge_options {
@@ -211,6 +233,26 @@ pub mod merge_commits {
     }
 }
 
+///
+#[cfg(feature = "merge")]
+pub mod merge_commits_octopus {
+    /// The error returned by [Repository::merge_commits_octopus()](crate::Repository::merge_commits_octopus()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        OpenCommitGraph(#[from] super::commit_graph_if_enabled::Error),
+        #[error(transparent)]
+        MergeResourceCache(#[from] super::merge_resource_cache::Error),
+        #[error(transparent)]
+        DiffResourceCache(#[from] super::diff_resource_cache::Error),
+        #[error(transparent)]
+        CommitMerge(#[from] gix_merge::commit::Error),
+        #[error(transparent)]
+        ValidationOptions(#[from] crate::config::boolean::Error),
+    }
+}
+
 ///
 #[cfg(feature = "merge")]
 pub mod virtual_merge_base {
@@ -488,6 +530,22 @@ pub mod pathspec_defaults_ignore_case {
     }
 }
 
+///
+#[cfg(feature = "index")]
+pub mod index_write_options {
+    /// The error returned by [`Repository::index_write_options()`](crate::Repository::index_write_options()).
+    #[derive(thiserror::Error, Debug)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Boolean(#[from] crate::config::boolean::Error),
+        #[error(transparent)]
+        MaxPercentChange(#[from] crate::config::unsigned_integer::Error),
+        #[error(transparent)]
+        SharedIndexExpire(#[from] crate::config::key::GenericErrorWithValue),
+    }
+}
+
 ///
 #[cfg(feature = "index")]
 pub mod index_or_load_from_head {
@@ -98,6 +98,112 @@ impl crate::Repository {
         self.remote_default_name(direction).map(|name| self.find_remote(name))
     }
 
+    /// Resolve the remote group `name` to the remotes it contains, in order of appearance and without duplicates.
+    ///
+    /// A group is configured as whitespace-separated list of remote names in one or more `remotes.<name>` keys.
+    /// If no such group exists, the `default` group contains all remotes that don't set `remote.<name>.skipDefaultUpdate`,
+    /// and any other group name resolves to the remote of the same name, just like `git fetch <group>` would.
+    ///
+    /// Fail if the group or one of its remotes can't be found, or if its configuration is invalid.
+    /// We will only consider groups and remotes if we deem them [trustworthy][crate::open::Options::filter_config_section()].
+    pub fn remote_group(&self, name: impl gix_utils::AsBStr) -> Result<Vec<Remote<'_>>, remote::group::Error> {
+        use crate::bstr::ByteSlice;
+        let name = name.as_bstr();
+        let mut filter = self.filter_config_section();
+        let mut members = Vec::<crate::bstr::BString>::new();
+        for value in self
+            .config
+            .resolved
+            .strings_filter(&format!("remotes.{name}"), &mut filter)
+            .unwrap_or_default()
+        {
+            for member in value.fields_with(|b| b.is_ascii_whitespace()) {
+                if !members.iter().any(|existing| existing == member) {
+                    members.push(member.into());
+                }
+            }
+        }
+
+        if members.is_empty() {
+            if name == "default" {
+                for remote_name in self.remote_names() {
+                    let skip = config::tree::Remote::SKIP_DEFAULT_UPDATE
+                        .enrich_error(
+                            self.config
+                                .resolved
+                                .boolean_filter(&format!("remote.{remote_name}.skipDefaultUpdate"), &mut filter),
+                        )?
+                        .unwrap_or_default();
+                    if !skip {
+                        members.push(remote_name);
+                    }
+                }
+            } else if self.try_find_remote(name).is_some() {
+                members.push(name.to_owned());
+            } else {
+                return Err(remote::group::Error::NotFound { name: name.to_owned() });
+            }
+        }
+
+        members
+            .into_iter()
+            .map(|name| self.find_remote(name.as_bstr()).map_err(Into::into))
+            .collect()
+    }
+
+    /// Fetch from all remotes of the group `name` as resolved by [`remote_group()`](Self::remote_group()), one after another
+    /// and reporting to the shared `progress`, just like `git fetch <group>` would.
+    ///
+    /// Return each remote along with the outcome of fetching from it, in the order they appear in the group.
+    /// Use `should_interrupt` to stop the operation early, and `options` to control how each remote is fetched.
+    /// Fail on the first remote that can't be fetched from, leaving all following remotes untouched.
+    #[cfg(feature = "blocking-network-client")]
+    pub fn fetch_remote_group<P>(
+        &self,
+        name: impl gix_utils::AsBStr,
+        mut progress: P,
+        should_interrupt: &std::sync::atomic::AtomicBool,
+        options: remote::group::fetch::Options,
+    ) -> Result<Vec<(Remote<'_>, remote::fetch::Outcome)>, remote::group::fetch::Error>
+    where
+        P: gix_features::progress::NestedProgress,
+        P::SubProgress: 'static,
+    {
+        use remote::group::fetch::Error;
+        let mut out = Vec::new();
+        for mut remote in self.remote_group(name)? {
+            let name = remote
+                .name()
+                .map_or_else(|| "<unnamed>".into(), |name| name.as_bstr().to_owned());
+            if !options.refspecs.is_empty() {
+                remote
+                    .replace_refspecs(options.refspecs.iter(), remote::Direction::Fetch)
+                    .map_err(|source| Error::RefSpecs {
+                        name: name.clone(),
+                        source,
+                    })?;
+                remote = remote.with_fetch_tags(remote::fetch::Tags::None);
+            }
+            let outcome = remote
+                .connect(remote::Direction::Fetch)
+                .map_err(|source| Error::Connect {
+                    name: name.clone(),
+                    source,
+                })?
+                .prepare_fetch(&mut progress, options.ref_map.clone())
+                .map_err(|source| Error::PrepareFetch {
+                    name: name.clone(),
+                    source,
+                })?
+                .with_dry_run(options.dry_run)
+                .with_shallow(options.shallow.clone())
+                .receive(&mut progress, should_interrupt)
+                .map_err(|source| Error::Fetch { name, source })?;
+            out.push((remote, outcome));
+        }
+        Ok(out)
+    }
+
     /// Find the configured remote with the given `name_or_url` or return `None` if it doesn't exist,
     /// for the purpose of fetching or pushing data.
     ///
@@ -183,6 +289,19 @@ impl crate::Repository {
             name_or_url: &BStr,
             key: &'static config::tree::keys::Any<T>,
             op: gix_refspec::parse::Operation,
+        ) -> Result<Vec<gix_refspec::RefSpec>, find::Error> {
+            parse_specs(specs, name_or_url, key, op).map(|mut specs| {
+                specs.sort();
+                specs.dedup();
+                specs
+            })
+        }
+
+        fn parse_specs<T: config::tree::keys::Validate>(
+            specs: Vec<crate::bstr::BString>,
+            name_or_url: &BStr,
+            key: &'static config::tree::keys::Any<T>,
+            op: gix_refspec::parse::Operation,
         ) -> Result<Vec<gix_refspec::RefSpec>, find::Error> {
             let kind = key.name;
             specs
@@ -194,12 +313,7 @@ impl crate::Repository {
                         source: err,
                     })
                 })
-                .collect::<Result<Vec<_>, _>>()
-                .map(|mut specs| {
-                    specs.sort();
-                    specs.dedup();
-                    specs
-                })
+                .collect()
         }
 
         let mut filter = self.filter_config_section();
@@ -238,7 +352,7 @@ impl crate::Repository {
                                 source: err,
                             })
                         })
-                        .collect()
+                        .collect::<Result<Vec<_>, _>>()
                 })
         };
         let urls = config_urls(&config::tree::Remote::URL, "fetch");
@@ -277,9 +391,20 @@ impl crate::Repository {
             Some(Err(err)) => return Some(Err(err)),
             None => Default::default(),
         };
+        // Like Git, only consult the files in `remotes/` and `branches/` if there is no fetch URL configured,
+        // and only if we would also trust the repository-local configuration.
+        let legacy = (!matches!(&urls, Some(Ok(urls)) if !urls.is_empty())
+            && filter(&gix_config::file::Metadata::from(gix_config::Source::Local).with(self.git_dir_trust())))
+        .then(|| {
+            let default_branch = config
+                .string(config::tree::Init::DEFAULT_BRANCH)
+                .unwrap_or_else(|| "master".into());
+            remote::legacy::read(self.common_dir(), name_or_url, default_branch.as_ref())
+        })
+        .flatten();
 
         match (urls, fetch_specs, push_urls, push_specs) {
-            (None, None, None, None) if !remote_is_configured => None,
+            (None, None, None, None) if !remote_is_configured && legacy.is_none() => None,
             (urls, fetch_specs, push_urls, push_specs) => {
                 let mut urls = match urls {
                     Some(Ok(v)) => v,
@@ -291,16 +416,52 @@ impl crate::Repository {
                     Some(Err(err)) => return Some(Err(err)),
                     None => Vec::new(),
                 };
-                let fetch_specs = match fetch_specs {
+                let mut fetch_specs = match fetch_specs {
                     Some(Ok(v)) => v,
                     Some(Err(err)) => return Some(Err(err)),
                     None => Vec::new(),
                 };
-                let push_specs = match push_specs {
+                let mut push_specs = match push_specs {
                     Some(Ok(v)) => v,
                     Some(Err(err)) => return Some(Err(err)),
                     None => Vec::new(),
                 };
+                if let Some(legacy) = legacy {
+                    for url in legacy.urls {
+                        match config::tree::Remote::URL.try_into_url(url) {
+                            Ok(url) => urls.push(url),
+                            Err(source) => {
+                                return Some(Err(find::Error::Url {
+                                    kind: "fetch",
+                                    remote_name: name_or_url.into(),
+                                    source,
+                                }));
+                            }
+                        }
+                    }
+                    let legacy_fetch_specs = parse_specs(
+                        legacy.fetch_specs,
+                        name_or_url,
+                        &config::tree::Remote::FETCH,
+                        gix_refspec::parse::Operation::Fetch,
+                    );
+                    let legacy_push_specs = parse_specs(
+                        legacy.push_specs,
+                        name_or_url,
+                        &config::tree::Remote::PUSH,
+                        gix_refspec::parse::Operation::Push,
+                    );
+                    match (legacy_fetch_specs, legacy_push_specs) {
+                        (Ok(legacy_fetch_specs), Ok(legacy_push_specs)) => {
+                            fetch_specs.extend(legacy_fetch_specs);
+                            push_specs.extend(legacy_push_specs);
+                        }
+                        (Err(err), _) | (_, Err(err)) => return Some(Err(err)),
+                    }
+                    for specs in [&mut fetch_specs, &mut push_specs] {
+                        remove_duplicates(specs);
+                    }
+                }
                 if urls.is_empty() {
                     let name_is_url = matches!(
                         remote::Name::try_from(std::borrow::Cow::Borrowed(name_or_url)),
@@ -337,3 +498,14 @@ impl crate::Repository {
         }
     }
 }
+
+/// Remove all but the first occurrence of each of `specs`, as the order of the legacy files matters.
+fn remove_duplicates(specs: &mut Vec<gix_refspec::RefSpec>) {
+    let mut unique = Vec::with_capacity(specs.len());
+    for spec in specs.drain(..) {
+        if !unique.contains(&spec) {
+            unique.push(spec);
+        }
+    }
+    *specs = unique;
+}
@@ -0,0 +1,109 @@
+//! Serve repositories to `git` clients via `git://` connections or smart-HTTP.
+//!
+//! The wire protocol is implemented by [`gix_protocol::server`], which is used with [`Directory`] to serve all repositories
+//! below a base directory, similar to `git daemon --base-path`, or with a [`Repository`](crate::Repository)
+//! as [`Source`](upload_pack::Source) and a [`Receiver`](receive_pack::Receiver) as [`Target`](gix_protocol::server::receive_pack::Target)
+//! of a custom [`Handler`].
+use std::path::{Component, PathBuf};
+
+use gix_protocol::{
+    server::{Error, Handler, Mode, Request, upload_pack},
+    transport::Service,
+};
+
+use crate::bstr::BStr;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// A [`Handler`] serving the repositories below a directory, similar to `git daemon --base-path=<base>`.
+#[derive(Debug, Clone)]
+pub struct Directory {
+    /// The directory against which the paths of all requests are resolved.
+    pub base: PathBuf,
+    /// If `true`, serve all repositories, not only those that contain the `git-daemon-export-ok` file like `git daemon` does.
+    pub export_all: bool,
+    /// If `Some`, pushes are accepted with the given options, similar to `git daemon --enable=receive-pack`.
+    /// Otherwise, they are rejected.
+    pub receive_pack: Option<receive_pack::Options>,
+}
+
+impl Directory {
+    /// Open the repository at the request `path`, which is tried as is, with a `.git` suffix, and as work tree containing `.git`,
+    /// or return `None` if there is no repository that may be served.
+    pub fn open(&self, path: &BStr) -> Option<crate::Repository> {
+        let path = gix_path::try_from_bstr(path).ok()?;
+        let path = path.strip_prefix("/").unwrap_or(&path);
+        if path
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            return None;
+        }
+        let path = self.base.join(path);
+        let with_suffix = {
+            let mut path = path.clone().into_os_string();
+            path.push(".git");
+            PathBuf::from(path)
+        };
+        let repo = [path, with_suffix]
+            .into_iter()
+            .filter(|candidate| candidate.is_dir())
+            .find_map(|candidate| crate::open(candidate).ok())?;
+        (self.export_all || repo.git_dir().join("git-daemon-export-ok").is_file()).then_some(repo)
+    }
+}
+
+impl Handler for Directory {
+    fn handle(
+        &self,
+        request: &Request,
+        mode: Mode,
+        input: &mut dyn std::io::Read,
+        output: &mut dyn std::io::Write,
+    ) -> Result<(), Error> {
+        let repo = self.open(request.path.as_ref()).ok_or_else(|| Error::NotFound {
+            path: request.path.clone(),
+        })?;
+        let agent = crate::protocol::agent(crate::env::agent());
+        match request.service {
+            Service::UploadPack => Ok(upload_pack::serve(
+                &repo,
+                request.protocol,
+                mode,
+                input,
+                output,
+                &upload_pack::Options {
+                    agent,
+                    bundle_list: bundle_list(&repo).map_err(Error::Other)?,
+                },
+            )?),
+            Service::ReceivePack => {
+                let options = self.receive_pack.ok_or(Error::ServiceDisabled {
+                    service: request.service,
+                })?;
+                Ok(gix_protocol::server::receive_pack::serve(
+                    &receive_pack::Receiver { repo: &repo, options },
+                    request.protocol,
+                    mode,
+                    input,
+                    output,
+                    &gix_protocol::server::receive_pack::Options { agent },
+                )?)
+            }
+        }
+    }
+}
+
+/// Return the bundle list to advertise if `uploadpack.advertiseBundleURIs` is enabled, as configured by the `bundle.*` values of `repo`.
+fn bundle_list(repo: &crate::Repository) -> Result<Option<gix_protocol::bundle_uri::List>, BoxError> {
+    use crate::config::tree::UploadPack;
+    let config = repo.config_snapshot();
+    if !config.boolean(UploadPack::ADVERTISE_BUNDLE_URIS).unwrap_or(false) {
+        return Ok(None);
+    }
+    Ok(Some(crate::config::bundle_list::from_config(config.plumbing())?))
+}
+
+///
+pub mod receive_pack;
+mod source;
@@ -0,0 +1,440 @@
+//! Receive pushes into a repository, similar to `git receive-pack`.
+use std::{
+    ffi::OsString,
+    io::Write,
+    path::PathBuf,
+    process::Stdio,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use gix_hash::ObjectId;
+use gix_protocol::{
+    handshake::Ref,
+    server::{
+        receive_pack::{Command, Outcome, Target, Update},
+        upload_pack::Source,
+    },
+};
+use gix_ref::{
+    FullName,
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+};
+
+use super::BoxError;
+use crate::{
+    bstr::{BString, ByteSlice},
+    config,
+};
+
+/// Options for receiving pushes with a [`Receiver`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, run the `pre-receive`, `update` and `post-receive` hooks of the repository if they exist,
+    /// allowing them to reject the push or some of its reference updates.
+    pub run_hooks: bool,
+}
+
+/// A [`Target`] that receives pushes into a repository.
+///
+/// The pack is received into a quarantine object directory within the object database, and its objects only become
+/// part of the repository once all objects needed by the updated references are known to be present and the `pre-receive`
+/// hook accepted the push. Then all accepted reference updates are applied in a single transaction.
+///
+/// Like `git` does by default, the branch checked out in a non-bare repository can't be updated, and the current branch
+/// can't be deleted.
+#[derive(Debug, Clone, Copy)]
+pub struct Receiver<'repo> {
+    /// The repository to receive pushes into.
+    pub repo: &'repo crate::Repository,
+    /// Options to configure how pushes are received.
+    pub options: Options,
+}
+
+impl Target for Receiver<'_> {
+    fn object_hash(&self) -> gix_hash::Kind {
+        self.repo.object_hash()
+    }
+
+    fn refs(&self) -> Result<Vec<Ref>, BoxError> {
+        Source::refs(self.repo)
+    }
+
+    fn receive(
+        &self,
+        update: &Update<'_>,
+        pack: Option<&mut dyn std::io::BufRead>,
+        progress: &mut dyn std::io::Write,
+    ) -> Result<Outcome, BoxError> {
+        let repo = self.repo;
+        let mut quarantine = match pack.map(|pack| Quarantine::receive(repo, pack)).transpose() {
+            Ok(quarantine) => quarantine,
+            Err(err) => {
+                return Ok(Outcome {
+                    unpack_error: Some(err.to_string().into()),
+                    commands: vec![Err("unpacker error".into()); update.commands.len()],
+                });
+            }
+        };
+
+        let mut results = update
+            .commands
+            .iter()
+            .map(|command| check_command(repo, command))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(quarantine) = &quarantine {
+            let quarantined = quarantine.repository(repo)?;
+            let hidden = ref_tips(repo)?;
+            for (command, result) in update.commands.iter().zip(&mut results) {
+                if result.is_err() || command.is_delete() {
+                    continue;
+                }
+                if let Err(err) = check_connectivity(&quarantined, command.new, &hidden) {
+                    writeln!(progress, "error: {}: {err}", command.name)?;
+                    *result = Err("missing necessary objects".into());
+                }
+            }
+        }
+
+        let hooks = Hooks {
+            repo,
+            enabled: self.options.run_hooks,
+            push_options: update.push_options,
+        };
+        let input = hook_input(update.commands, &results);
+        if !input.is_empty() {
+            let environment = quarantine.as_ref().map(Quarantine::environment).unwrap_or_default();
+            if !hooks.run("pre-receive", &[], &input, &environment, progress)? {
+                reject_accepted(&mut results, "pre-receive hook declined");
+            }
+        }
+        if update.atomic {
+            reject_all_if_one_failed(&mut results);
+        }
+
+        if let Some(quarantine) = quarantine.as_mut().filter(|_| results.iter().any(Result::is_ok)) {
+            if let Err(err) = quarantine.migrate() {
+                writeln!(progress, "error: {err}")?;
+                reject_accepted(&mut results, "unable to migrate objects to permanent storage");
+            }
+        }
+        for (command, result) in update.commands.iter().zip(&mut results) {
+            if result.is_err() {
+                continue;
+            }
+            let args = [
+                OsString::from(command.name.to_str_lossy().as_ref()),
+                command.old.to_string().into(),
+                command.new.to_string().into(),
+            ];
+            if !hooks.run("update", &args, &[], &[], progress)? {
+                *result = Err("hook declined".into());
+            }
+        }
+        if update.atomic {
+            reject_all_if_one_failed(&mut results);
+        }
+
+        let edits = update
+            .commands
+            .iter()
+            .zip(&results)
+            .filter(|(_, result)| result.is_ok())
+            .map(|(command, _)| edit(command))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !edits.is_empty() {
+            let mut repo = repo.clone();
+            let committer = repo.committer_or_set_generic_fallback()?.to_owned()?;
+            if let Err(err) = repo.edit_references_as(edits, Some(committer.to_ref(&mut Default::default()))) {
+                writeln!(progress, "error: {err}")?;
+                reject_accepted(&mut results, "failed to update ref");
+            }
+        }
+
+        let input = hook_input(update.commands, &results);
+        if !input.is_empty() {
+            // Like `git`, we ignore the exit status as there is nothing to undo.
+            hooks.run("post-receive", &[], &input, &[], progress)?;
+        }
+        Ok(Outcome {
+            unpack_error: None,
+            commands: results,
+        })
+    }
+}
+
+/// Return `Err` with the reason for rejecting `command` if it can't be applied to `repo`.
+fn check_command(repo: &crate::Repository, command: &Command) -> Result<Result<(), BString>, BoxError> {
+    let name = match FullName::try_from(command.name.as_bstr()) {
+        Ok(name) if name.as_bstr().starts_with(b"refs/") => name,
+        _ => return Ok(Err("funny refname".into())),
+    };
+    let current = match repo.try_find_reference(name.as_ref())? {
+        Some(reference) => match reference.inner.target.try_id() {
+            Some(id) => Some(id.to_owned()),
+            None => return Ok(Err("cannot update symbolic reference".into())),
+        },
+        None => None,
+    };
+    let expected = (!command.is_create()).then_some(command.old);
+    if current != expected {
+        return Ok(Err("failed to update ref".into()));
+    }
+    if repo.head_name()?.as_ref() == Some(&name) {
+        if command.is_delete() {
+            return Ok(Err("deletion of the current branch prohibited".into()));
+        }
+        if !repo.is_bare() {
+            return Ok(Err("branch is currently checked out".into()));
+        }
+    }
+    Ok(Ok(()))
+}
+
+/// Return the ids of all commits pointed to by references in `repo`.
+fn ref_tips(repo: &crate::Repository) -> Result<Vec<ObjectId>, BoxError> {
+    let mut tips = Vec::new();
+    for reference in repo.references()?.all()? {
+        let Ok(mut reference) = reference else { continue };
+        let Ok(id) = reference.peel_to_id() else { continue };
+        let Ok(object) = id.object().and_then(crate::Object::peel_tags_to_end) else {
+            continue;
+        };
+        if object.kind == gix_object::Kind::Commit {
+            tips.push(object.id);
+        }
+    }
+    Ok(tips)
+}
+
+/// Assure that `id` and all objects reachable from it exist in `repo`, assuming that this is true for the `hidden` commits.
+fn check_connectivity(repo: &crate::Repository, id: ObjectId, hidden: &[ObjectId]) -> Result<(), BoxError> {
+    let object = repo.find_object(id)?.peel_tags_to_end()?;
+    if object.kind != gix_object::Kind::Commit {
+        return Ok(());
+    }
+    let mut missing = None;
+    let mut connectivity = gix_fsck::Connectivity::new(&repo.objects, |id, kind| {
+        missing.get_or_insert((*id, kind));
+    });
+    for commit in repo.rev_walk([object.id]).with_hidden(hidden.iter().copied()).all()? {
+        connectivity.check_commit(&commit?.id)?;
+    }
+    drop(connectivity);
+    match missing {
+        Some((id, kind)) => Err(format!("{kind} {id} is missing").into()),
+        None => Ok(()),
+    }
+}
+
+/// Return the edit to apply `command`.
+fn edit(command: &Command) -> Result<RefEdit, BoxError> {
+    let expected = if command.is_create() {
+        PreviousValue::MustNotExist
+    } else {
+        PreviousValue::MustExistAndMatch(command.old.into())
+    };
+    Ok(RefEdit {
+        change: if command.is_delete() {
+            Change::Delete {
+                expected,
+                log: RefLog::AndReference,
+            }
+        } else {
+            Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: "push".into(),
+                },
+                expected,
+                new: command.new.into(),
+            }
+        },
+        name: command.name.as_bstr().try_into()?,
+        deref: false,
+    })
+}
+
+/// Return the lines passed to the `pre-receive` and `post-receive` hooks for all accepted `commands`.
+fn hook_input(commands: &[Command], results: &[Result<(), BString>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (command, _) in commands.iter().zip(results).filter(|(_, result)| result.is_ok()) {
+        out.extend_from_slice(format!("{} {} {}\n", command.old, command.new, command.name).as_bytes());
+    }
+    out
+}
+
+fn reject_accepted(results: &mut [Result<(), BString>], reason: &str) {
+    for result in results.iter_mut().filter(|result| result.is_ok()) {
+        *result = Err(reason.into());
+    }
+}
+
+fn reject_all_if_one_failed(results: &mut [Result<(), BString>]) {
+    if results.iter().any(Result::is_err) {
+        reject_accepted(results, "atomic push failure");
+    }
+}
+
+/// The hooks of a repository, which are only run if `enabled`.
+struct Hooks<'a> {
+    repo: &'a crate::Repository,
+    enabled: bool,
+    push_options: &'a [BString],
+}
+
+impl Hooks<'_> {
+    /// Run the hook `name` with `args`, `stdin` and additional `environment` variables if it exists, while sending its output
+    /// to `progress`, and return `true` if it succeeded or doesn't exist.
+    fn run(
+        &self,
+        name: &str,
+        args: &[OsString],
+        stdin: &[u8],
+        environment: &[(&str, OsString)],
+        progress: &mut dyn Write,
+    ) -> Result<bool, BoxError> {
+        if !self.enabled {
+            return Ok(true);
+        }
+        let repo = self.repo;
+        let cwd = repo.workdir().unwrap_or(repo.git_dir());
+        let hooks_dir = match repo.config.trusted_file_path(config::tree::Core::HOOKS_PATH)? {
+            Some(path) => cwd.join(path),
+            None => repo.common_dir().join("hooks"),
+        };
+        let hook = hooks_dir.join(name);
+        if !hook
+            .metadata()
+            .is_ok_and(|metadata| metadata.is_file() && gix_fs::is_executable(&metadata))
+        {
+            return Ok(true);
+        }
+
+        let mut prepare = gix_command::prepare(hook)
+            .args(args.iter().cloned())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .with_context(gix_command::Context {
+                git_dir: Some(repo.git_dir().to_owned()),
+                ..Default::default()
+            });
+        for (key, value) in environment {
+            prepare = prepare.env(key, value);
+        }
+        if name != "update" {
+            prepare = prepare.env("GIT_PUSH_OPTION_COUNT", self.push_options.len().to_string());
+            for (index, option) in self.push_options.iter().enumerate() {
+                prepare = prepare.env(
+                    format!("GIT_PUSH_OPTION_{index}"),
+                    gix_path::from_bstr(option.as_bstr()).into_owned(),
+                );
+            }
+        }
+        let mut cmd = std::process::Command::from(prepare);
+        cmd.current_dir(cwd);
+        let mut child = cmd.spawn()?;
+        let mut child_stdin = child.stdin.take().expect("configured");
+        let output = std::thread::scope(|scope| {
+            // The hook doesn't have to read its input, so failing to write it is fine.
+            scope.spawn(move || child_stdin.write_all(stdin).ok());
+            child.wait_with_output()
+        })?;
+        progress.write_all(&output.stdout)?;
+        progress.write_all(&output.stderr)?;
+        Ok(output.status.success())
+    }
+}
+
+/// An object directory within the object database of a repository to receive a pack into, which is removed when dropped.
+struct Quarantine {
+    /// The quarantine object directory.
+    path: PathBuf,
+    /// The object directory of the repository.
+    objects_dir: PathBuf,
+}
+
+impl Quarantine {
+    /// Receive `pack` into a new quarantine directory in the object database of `repo`.
+    fn receive(repo: &crate::Repository, pack: &mut dyn std::io::BufRead) -> Result<Self, BoxError> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let objects_dir = gix_path::realpath(repo.objects.store_ref().path())?;
+        let path = objects_dir.join(format!(
+            "incoming-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir(&path)?;
+        let quarantine = Quarantine { path, objects_dir };
+        std::fs::create_dir(quarantine.path.join("pack"))?;
+        std::fs::create_dir(quarantine.path.join("info"))?;
+        let mut alternates = gix_path::into_bstr(quarantine.objects_dir.as_path()).into_owned();
+        alternates.push(b'\n');
+        std::fs::write(quarantine.path.join("info").join("alternates"), alternates)?;
+
+        gix_pack::Bundle::write_to_directory(
+            pack,
+            Some(&quarantine.path.join("pack")),
+            &mut crate::progress::Discard,
+            &AtomicBool::new(false),
+            Some(repo.objects.clone()),
+            gix_pack::bundle::write::Options {
+                thread_limit: None,
+                iteration_mode: gix_pack::data::input::Mode::Verify,
+                index_version: Default::default(),
+                object_hash: repo.object_hash(),
+                alloc_limit_bytes: repo.config.alloc_limit_bytes,
+                compression: repo.config.loose_compression,
+            },
+        )?;
+        Ok(quarantine)
+    }
+
+    /// Return `repo` with an object database that contains the objects in quarantine as well.
+    fn repository(&self, repo: &crate::Repository) -> Result<crate::Repository, BoxError> {
+        let mut repo = repo.clone();
+        let objects = gix_odb::at_opts(
+            &self.path,
+            None,
+            gix_odb::store::init::Options {
+                object_hash: repo.object_hash(),
+                ..Default::default()
+            },
+        )?;
+        repo.objects = gix_odb::memory::Proxy::new(objects, repo.object_hash());
+        Ok(repo)
+    }
+
+    /// Return the environment variables that make the objects in quarantine available to `git`, as used by the `pre-receive` hook.
+    fn environment(&self) -> Vec<(&'static str, OsString)> {
+        vec![
+            ("GIT_QUARANTINE_PATH", self.path.clone().into()),
+            ("GIT_OBJECT_DIRECTORY", self.path.clone().into()),
+            ("GIT_ALTERNATE_OBJECT_DIRECTORIES", self.objects_dir.clone().into()),
+        ]
+    }
+
+    /// Move all received packs into the object database, with the index last to only make them visible once complete.
+    fn migrate(&mut self) -> std::io::Result<()> {
+        let mut files = std::fs::read_dir(self.path.join("pack"))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        files.retain(|path| path.extension().is_none_or(|extension| extension != "keep"));
+        files.sort_by_key(|path| path.extension().is_some_and(|extension| extension == "idx"));
+        let pack_dir = self.objects_dir.join("pack");
+        for file in files {
+            let destination = pack_dir.join(file.file_name().expect("read from directory"));
+            if !destination.exists() {
+                std::fs::rename(&file, destination)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Quarantine {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.path).ok();
+    }
+}
@@ -0,0 +1,213 @@
+use std::sync::{Arc, atomic::AtomicBool};
+
+use gix_hash::ObjectId;
+use gix_pack::data::output;
+use gix_protocol::{handshake::Ref, server::upload_pack};
+
+use super::BoxError;
+use crate::{bstr::BString, head};
+
+impl upload_pack::Source for crate::Repository {
+    fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash()
+    }
+
+    fn refs(&self) -> Result<Vec<Ref>, BoxError> {
+        let mut out = Vec::new();
+        match self.head()?.kind {
+            head::Kind::Symbolic(reference) => {
+                let mut reference = self.find_reference(reference.name.as_ref())?;
+                if let Ok(id) = reference.follow_to_object() {
+                    let id = id.detach();
+                    let (tag, object) = match self.peeled(id)? {
+                        Some(peeled) => (Some(id), peeled),
+                        None => (None, id),
+                    };
+                    out.push(Ref::Symbolic {
+                        full_ref_name: "HEAD".into(),
+                        target: reference.name().as_bstr().into(),
+                        tag,
+                        object,
+                    });
+                }
+            }
+            head::Kind::Unborn(name) => out.push(Ref::Unborn {
+                full_ref_name: "HEAD".into(),
+                target: name.as_bstr().into(),
+            }),
+            head::Kind::Detached { target, .. } => out.push(self.to_ref("HEAD".into(), target)?),
+        }
+        for reference in self.references()?.prefixed("refs/")? {
+            let Ok(mut reference) = reference else { continue };
+            let Ok(id) = reference.follow_to_object() else {
+                continue;
+            };
+            out.push(self.to_ref(reference.name().as_bstr().into(), id.detach())?);
+        }
+        Ok(out)
+    }
+
+    fn contains(&self, id: &gix_hash::oid) -> bool {
+        self.has_object(id)
+    }
+
+    fn write_pack(
+        &self,
+        pack: &upload_pack::Pack<'_>,
+        out: &mut dyn std::io::Write,
+        progress: &mut dyn std::io::Write,
+    ) -> Result<(), BoxError> {
+        let should_interrupt = AtomicBool::new(false);
+        let mut handle = {
+            // Entries are created in threads, so we need an object database that can be shared with them.
+            let store = gix_odb::Store::at_opts(
+                self.objects.store_ref().path().to_owned(),
+                &mut std::iter::empty(),
+                gix_odb::store::init::Options {
+                    object_hash: self.object_hash(),
+                    ..Default::default()
+                },
+            )?;
+            Arc::new(store).to_cache_arc()
+        };
+        handle.prevent_pack_unload();
+        handle.ignore_replacements = true;
+
+        let (mut tips, mut input) = (Vec::new(), Vec::new());
+        for id in pack.wants {
+            let object = self.find_object(*id)?;
+            if object.kind != gix_object::Kind::Commit {
+                input.push(*id);
+            }
+            let peeled = object.peel_tags_to_end()?;
+            if peeled.kind == gix_object::Kind::Commit {
+                tips.push(peeled.id);
+            }
+        }
+        let hidden = pack
+            .haves
+            .iter()
+            .filter(|id| {
+                self.find_header(**id)
+                    .is_ok_and(|header| header.kind() == gix_object::Kind::Commit)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        let expansion = if hidden.is_empty() {
+            output::count::objects::ObjectExpansion::TreeContents
+        } else {
+            output::count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor
+        };
+        for commit in self.rev_walk(tips).with_hidden(hidden).all()? {
+            input.push(commit?.id);
+        }
+
+        let mut counts = {
+            let (counts, _) = output::count::objects_unthreaded(
+                &handle,
+                &mut input.into_iter().map(Ok),
+                &crate::progress::Discard,
+                &should_interrupt,
+                expansion,
+            )?;
+            counts
+        };
+        if pack.include_tag {
+            let sent = counts
+                .iter()
+                .map(|count| count.id)
+                .collect::<gix_hashtable::HashSet<_>>();
+            let mut tags = Vec::new();
+            for reference in self.references()?.tags()? {
+                let Ok(mut reference) = reference else { continue };
+                let Ok(id) = reference.follow_to_object() else {
+                    continue;
+                };
+                if sent.contains(id.as_ref()) {
+                    continue;
+                }
+                if let Some(peeled) = self.peeled(id.detach())? {
+                    if sent.contains(&peeled) {
+                        tags.push(id.detach());
+                    }
+                }
+            }
+            let (tag_counts, _) = output::count::objects_unthreaded(
+                &handle,
+                &mut tags.into_iter().map(Ok),
+                &crate::progress::Discard,
+                &should_interrupt,
+                output::count::objects::ObjectExpansion::AsIs,
+            )?;
+            counts.extend(tag_counts);
+        }
+        let num_objects = counts.len();
+        writeln!(progress, "Enumerating objects: {num_objects}, done.")?;
+
+        let mut ids = Vec::with_capacity(num_objects);
+        let ofs_delta = pack.ofs_delta;
+        let entries = gix_features::parallel::InOrderIter::from(output::entry::iter_from_counts(
+            counts,
+            handle,
+            Box::new(crate::progress::Discard),
+            output::entry::iter_from_counts::Options {
+                thread_limit: None,
+                mode: output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                allow_thin_pack: pack.thin,
+                chunk_size: 1000,
+                version: Default::default(),
+                compression: self.pack_compression()?,
+            },
+        ))
+        .map(|entries| {
+            entries.map(|mut entries| {
+                for entry in &mut entries {
+                    ids.push(entry.id);
+                    if let output::entry::Kind::DeltaRef { object_index } = entry.kind {
+                        if !ofs_delta {
+                            entry.kind = output::entry::Kind::DeltaOid { id: ids[object_index] };
+                        }
+                    }
+                }
+                entries
+            })
+        });
+        let mut writer = output::bytes::FromEntriesIter::new(
+            entries,
+            out,
+            u32::try_from(num_objects)?,
+            gix_pack::data::Version::default(),
+            self.object_hash(),
+        );
+        for written in writer.by_ref() {
+            written?;
+        }
+        writeln!(progress, "Total {num_objects}")?;
+        Ok(())
+    }
+}
+
+impl crate::Repository {
+    /// Return the advertised reference named `name` pointing to `id`, which is peeled if it's an annotated tag.
+    fn to_ref(&self, full_ref_name: BString, id: ObjectId) -> Result<Ref, BoxError> {
+        Ok(match self.peeled(id)? {
+            Some(object) => Ref::Peeled {
+                full_ref_name,
+                tag: id,
+                object,
+            },
+            None => Ref::Direct {
+                full_ref_name,
+                object: id,
+            },
+        })
+    }
+
+    /// Return the object the annotated tag `id` ultimately points to, or `None` if `id` isn't a tag.
+    fn peeled(&self, id: ObjectId) -> Result<Option<ObjectId>, BoxError> {
+        if self.find_header(id)?.kind() != gix_object::Kind::Tag {
+            return Ok(None);
+        }
+        Ok(Some(self.find_object(id)?.peel_tags_to_end()?.id))
+    }
+}
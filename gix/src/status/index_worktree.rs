@@ -28,6 +28,8 @@ pub enum Error {
     StatOptions(#[from] config::stat_options::Error),
     #[error(transparent)]
     ResourceCache(#[from] crate::diff::resource_cache::Error),
+    #[error(transparent)]
+    UntrackedCache(#[from] crate::dirwalk::untracked_cache::Error),
 }
 
 /// Options for use with [Repository::index_worktree_status()].
@@ -86,6 +88,9 @@ impl Repository {
     /// ### Note
     ///
     /// This is a lower-level method, prefer the [`status`](Repository::status()) method for greater ease of use.
+    ///
+    /// If `core.untrackedCache` [permits it](Repository::untracked_cache()), the untracked cache in `index` is used
+    /// to accelerate the directory walk, but as `index` can't be changed, updates to it are discarded.
     #[expect(clippy::too_many_arguments)]
     pub fn index_worktree_status<'index, T, U, E>(
         &self,
@@ -102,6 +107,55 @@ impl Repository {
         should_interrupt: &AtomicBool,
         options: Options,
     ) -> Result<gix_status::index_as_worktree_with_renames::Outcome, Error>
+    where
+        T: Send + Clone,
+        U: Send + Clone,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut untracked_cache = self.index_worktree_untracked_cache(index, &options)?;
+        self.index_worktree_status_with_untracked_cache(
+            index,
+            patterns,
+            delegate,
+            compare,
+            submodule,
+            progress,
+            should_interrupt,
+            options,
+            untracked_cache.as_mut(),
+        )
+    }
+
+    /// Return the untracked cache to use for a status with `options` on `index`, if a directory walk is performed at all.
+    pub(super) fn index_worktree_untracked_cache(
+        &self,
+        index: &gix_index::State,
+        options: &Options,
+    ) -> Result<Option<gix_dir::walk::UntrackedCache>, Error> {
+        if options.dirwalk_options.is_none() {
+            return Ok(None);
+        }
+        Ok(self.untracked_cache(index)?)
+    }
+
+    /// Like [`index_worktree_status()`](Self::index_worktree_status()), but uses and updates `untracked_cache` if set.
+    #[expect(clippy::too_many_arguments)]
+    pub(super) fn index_worktree_status_with_untracked_cache<'index, T, U, E>(
+        &self,
+        index: &'index gix_index::State,
+        patterns: impl IntoIterator<Item = impl AsRef<BStr>>,
+        delegate: &mut impl gix_status::index_as_worktree_with_renames::VisitEntry<
+            'index,
+            ContentChange = T,
+            SubmoduleStatus = U,
+        >,
+        compare: impl CompareBlobs<Output = T> + Send + Clone,
+        submodule: impl SubmoduleStatus<Output = U, Error = E> + Send + Clone,
+        progress: &mut dyn gix_features::progress::Progress,
+        should_interrupt: &AtomicBool,
+        options: Options,
+        untracked_cache: Option<&mut gix_dir::walk::UntrackedCache>,
+    ) -> Result<gix_status::index_as_worktree_with_renames::Outcome, Error>
     where
         T: Send + Clone,
         U: Send + Clone,
@@ -153,6 +207,7 @@ impl Repository {
                     git_dir_realpath: git_dir_realpath.as_path(),
                     current_dir: cwd,
                     ignore_case_index_lookup: accelerate_lookup.as_ref(),
+                    untracked_cache,
                 },
             },
             gix_status::index_as_worktree_with_renames::Options {
@@ -4,13 +4,12 @@ use gix_status::index_as_worktree::{Change, EntryStatus};
 
 use crate::{
     bstr::BString,
-    config::cache::util::ApplyLeniencyDefault,
     status::{Platform, index_worktree, index_worktree::BuiltinSubmoduleStatus, tree_index},
     worktree::IndexPersistedOrInMemory,
 };
 
 pub(super) mod types;
-use types::{ApplyChange, Item, Iter, Outcome};
+use types::{ApplyChange, FsMonitorUpdate, Item, Iter, Outcome};
 
 /// Lifecycle
 impl<Progress> Platform<'_, Progress>
@@ -41,9 +40,46 @@ where
         self,
         patterns: impl IntoIterator<Item = BString>,
     ) -> Result<Iter, crate::status::into_iter::Error> {
+        let fs_monitor = match self.fs_monitor {
+            Some(monitor) => Some(monitor),
+            None => self
+                .repo
+                .fs_monitor_hook()?
+                .map(|hook| Box::new(hook) as Box<dyn crate::status::RefreshIndex>),
+        };
         let index = match self.index {
-            None => IndexPersistedOrInMemory::Persisted(self.repo.index_or_empty()?),
             Some(index) => index,
+            // Read an index of our own which the filesystem monitor can change, instead of cloning the shared one.
+            None if fs_monitor.is_some() => match self.repo.open_index() {
+                Ok(index) => IndexPersistedOrInMemory::InMemory(index),
+                Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                    if err.kind() == std::io::ErrorKind::NotFound =>
+                {
+                    IndexPersistedOrInMemory::Persisted(self.repo.index_or_empty()?)
+                }
+                Err(err) => return Err(err.into()),
+            },
+            None => IndexPersistedOrInMemory::Persisted(self.repo.index_or_empty()?),
+        };
+        let patterns: Vec<BString> = patterns.into_iter().collect();
+
+        let (index, fs_monitor, fs_monitor_update) = match (fs_monitor, index) {
+            (Some(mut monitor), IndexPersistedOrInMemory::InMemory(mut index)) => {
+                let previous = index.fs_monitor().cloned();
+                let outcome = monitor
+                    .refresh(&mut index)
+                    .inspect_err(|_err| {
+                        gix_trace::warn!(err = %_err, "filesystem monitor failed, checking all entries");
+                    })
+                    .ok();
+                let update = outcome.map(|_| FsMonitorUpdate {
+                    previous,
+                    changed_entries: patterns.is_empty().then(Vec::new),
+                });
+                (IndexPersistedOrInMemory::InMemory(index), outcome, update)
+            }
+            // The shared index can't be changed, so all of its entries are checked.
+            (_, index) => (index, None, None),
         };
 
         let obtain_tree_id = || -> Result<Option<gix_hash::ObjectId>, crate::status::into_iter::Error> {
@@ -54,16 +90,12 @@ where
             })
         };
 
-        let skip_hash = crate::config::tree::Index::SKIP_HASH
-            .enrich_error(self.repo.config.resolved.boolean(crate::config::tree::Index::SKIP_HASH))
-            .with_lenient_default(self.repo.config.lenient_config)?
-            .unwrap_or_default();
+        let write_options = self.repo.index_write_options()?;
         let should_interrupt = self.should_interrupt.clone().unwrap_or_default();
         let submodule = BuiltinSubmoduleStatus::new(self.repo.clone().into_sync(), self.submodules)?;
         #[cfg(feature = "parallel")]
         {
             let (tx, rx) = std::sync::mpsc::channel();
-            let patterns: Vec<_> = patterns.into_iter().collect();
             let join_tree_index = if let Some(tree_id) = obtain_tree_id()? {
                 std::thread::Builder::new()
                     .name("gix::status::tree_index::producer".into())
@@ -120,7 +152,8 @@ where
                     let mut progress = self.progress;
                     move || -> Result<_, index_worktree::Error> {
                         let repo = repo.to_thread_local();
-                        let out = repo.index_worktree_status(
+                        let mut untracked_cache = repo.index_worktree_untracked_cache(&index, &options)?;
+                        let out = repo.index_worktree_status_with_untracked_cache(
                             &index,
                             patterns,
                             &mut collect,
@@ -129,13 +162,17 @@ where
                             &mut progress,
                             &should_interrupt,
                             options,
+                            untracked_cache.as_mut(),
                         )?;
                         Ok(Outcome {
                             index_worktree: out,
                             tree_index: None,
                             worktree_index: index,
                             changes: None,
-                            skip_hash,
+                            untracked_cache,
+                            fs_monitor,
+                            fs_monitor_update: None,
+                            write_options,
                         })
                     }
                 })
@@ -145,6 +182,7 @@ where
                 rx_and_join: Some((rx, join_index_worktree, join_tree_index)),
                 should_interrupt,
                 index_changes: Vec::new(),
+                fs_monitor_update,
                 out: None,
             })
         }
@@ -155,7 +193,6 @@ where
             let repo = self.repo;
             let options = self.index_worktree_options;
             let mut progress = self.progress;
-            let patterns: Vec<BString> = patterns.into_iter().collect();
             let (mut items, tree_index) = match obtain_tree_id()? {
                 Some(tree_id) => {
                     let mut pathspec = repo.index_worktree_status_pathspec::<crate::status::into_iter::Error>(
@@ -183,7 +220,8 @@ where
                 }
                 None => (Vec::new(), None),
             };
-            let out = repo.index_worktree_status(
+            let mut untracked_cache = repo.index_worktree_untracked_cache(&index, &options)?;
+            let out = repo.index_worktree_status_with_untracked_cache(
                 &index,
                 patterns,
                 &mut collect,
@@ -192,10 +230,12 @@ where
                 &mut progress,
                 &should_interrupt,
                 options,
+                untracked_cache.as_mut(),
             )?;
             let mut iter = Iter {
                 items: Vec::new().into_iter(),
                 index_changes: Vec::new(),
+                fs_monitor_update,
                 out: None,
             };
             let mut out = Outcome {
@@ -203,7 +243,10 @@ where
                 worktree_index: index,
                 tree_index,
                 changes: None,
-                skip_hash,
+                untracked_cache,
+                fs_monitor,
+                fs_monitor_update: None,
+                write_options,
             };
             items.extend(
                 collect
@@ -212,6 +255,7 @@ where
                     .filter_map(|item| iter.maybe_keep_index_change(item)),
             );
             out.changes = (!iter.index_changes.is_empty()).then(|| std::mem::take(&mut iter.index_changes));
+            out.fs_monitor_update = iter.fs_monitor_update.take();
             iter.items = items.into_iter();
             iter.out = Some(out);
             Ok(iter)
@@ -264,6 +308,7 @@ impl Iterator for Iter {
                     break match worktree_handle.join().expect("no panic") {
                         Ok(mut out) => {
                             out.changes = Some(std::mem::take(&mut self.index_changes));
+                            out.fs_monitor_update = self.fs_monitor_update.take();
                             out.tree_index = tree_index;
                             self.out = Some(out);
                             None
@@ -294,6 +339,28 @@ impl Iter {
 
 impl Iter {
     fn maybe_keep_index_change(&mut self, item: Item) -> Option<Item> {
+        if let Some(changed_entries) = self
+            .fs_monitor_update
+            .as_mut()
+            .and_then(|update| update.changed_entries.as_mut())
+        {
+            match &item {
+                Item::IndexWorktree(index_worktree::Item::Modification {
+                    status: EntryStatus::NeedsUpdate(_),
+                    ..
+                }) => {}
+                Item::IndexWorktree(index_worktree::Item::Modification { entry_index, .. }) => {
+                    changed_entries.push(*entry_index);
+                }
+                Item::IndexWorktree(index_worktree::Item::Rewrite {
+                    source: index_worktree::RewriteSource::RewriteFromIndex { source_entry_index, .. },
+                    ..
+                }) => {
+                    changed_entries.push(*source_entry_index);
+                }
+                _ => {}
+            }
+        }
         match item {
             Item::IndexWorktree(index_worktree::Item::Modification {
                 status: EntryStatus::NeedsUpdate(stat),
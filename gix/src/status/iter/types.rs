@@ -47,6 +47,8 @@ pub struct Iter {
     pub(in crate::status) out: Option<Outcome>,
     /// The set of `(entry_index, change)` we extracted in order to potentially write back the worktree index with the changes applied.
     pub(super) index_changes: Vec<(usize, ApplyChange)>,
+    /// Information to update the index with once the filesystem monitor was used.
+    pub(super) fs_monitor_update: Option<FsMonitorUpdate>,
 }
 
 /// The item produced by the [iterator](Iter).
@@ -69,8 +71,58 @@ pub struct Outcome {
     pub tree_index: Option<tree_index::Outcome>,
     /// The worktree index that was used for the operation.
     pub worktree_index: IndexPersistedOrInMemory,
-    pub(super) skip_hash: bool,
+    pub(super) write_options: crate::index::write::Options,
     pub(super) changes: Option<Vec<(usize, ApplyChange)>>,
+    /// The untracked cache used during the directory walk, if `core.untrackedCache` permitted it.
+    pub(super) untracked_cache: Option<gix_dir::walk::UntrackedCache>,
+    /// The outcome of refreshing the index with the filesystem monitor, or `None` if none was used or if it failed.
+    pub fs_monitor: Option<gix_status::fsmonitor::Outcome>,
+    pub(super) fs_monitor_update: Option<FsMonitorUpdate>,
+}
+
+/// Information to update the index with after the filesystem monitor provided a new token.
+pub(super) struct FsMonitorUpdate {
+    /// The fsmonitor extension before the filesystem monitor was queried.
+    pub(super) previous: Option<gix_index::extension::FsMonitor>,
+    /// The indices of all entries that were seen with changes, or `None` if not all entries were checked
+    /// so none of them can be marked as valid.
+    pub(super) changed_entries: Option<Vec<usize>>,
+}
+
+impl FsMonitorUpdate {
+    /// Return `true` if applying this update to `index` changes the token or the dirty entries of its fsmonitor extension.
+    fn changes(&self, index: &gix_index::State) -> bool {
+        let Some(previous) = &self.previous else {
+            return true;
+        };
+        if index.fs_monitor().map(gix_index::extension::FsMonitor::token) != Some(previous.token()) {
+            return true;
+        }
+        let mut was_dirty = vec![false; index.entries().len()];
+        if previous
+            .for_each_dirty_entry(was_dirty.len(), |idx| was_dirty[idx] = true)
+            .is_none()
+        {
+            return true;
+        }
+        let mut is_changed = vec![false; was_dirty.len()];
+        for idx in self.changed_entries.iter().flatten() {
+            is_changed[*idx] = true;
+        }
+        index
+            .entries()
+            .iter()
+            .zip(was_dirty)
+            .zip(is_changed)
+            .any(|((entry, was_dirty), is_changed)| {
+                let is_valid = if self.changed_entries.is_some() {
+                    gix_status::fsmonitor::can_mark_valid(entry) && !is_changed
+                } else {
+                    entry.flags.contains(gix_index::entry::Flags::FSMONITOR_VALID)
+                };
+                is_valid == was_dirty
+            })
+    }
 }
 
 impl Outcome {
@@ -80,20 +132,43 @@ impl Outcome {
     /// additional work can be prevented by writing the changes back to the index.
     pub fn has_changes(&self) -> bool {
         self.changes.as_ref().is_some_and(|changes| !changes.is_empty())
+            || self
+                .fs_monitor_update
+                .as_ref()
+                .is_some_and(|update| update.changes(&self.worktree_index))
+            || self
+                .untracked_cache
+                .as_ref()
+                .is_some_and(gix_dir::walk::UntrackedCache::is_modified)
     }
 
-    /// Write the changes if there are any back to the index file.
+    /// Write the changes if there are any back to the index file, along with an updated untracked cache
+    /// and the latest token of the filesystem monitor.
     /// This can only be done once as the changes are consumed in the process, if there were any.
     pub fn write_changes(&mut self) -> Option<Result<(), gix_index::file::write::Error>> {
         let _span = gix_features::trace::coarse!("gix::status::index_worktree::Outcome::write_changes()");
-        let changes = self.changes.take()?;
+        let changes = self.changes.take();
+        let untracked_cache = self
+            .untracked_cache
+            .take()
+            .filter(gix_dir::walk::UntrackedCache::is_modified);
+        let fs_monitor_update = self
+            .fs_monitor_update
+            .take()
+            .filter(|update| update.changes(&self.worktree_index));
+        if changes.is_none() && untracked_cache.is_none() && fs_monitor_update.is_none() {
+            return None;
+        }
         let mut index = match &self.worktree_index {
             IndexPersistedOrInMemory::Persisted(persisted) => (***persisted).clone(),
             IndexPersistedOrInMemory::InMemory(index) => index.clone(),
         };
 
+        if let Some(untracked_cache) = untracked_cache {
+            index.set_untracked(Some(untracked_cache.into_extension()));
+        }
         let entries = index.entries_mut();
-        for (entry_index, change) in changes {
+        for (entry_index, change) in changes.into_iter().flatten() {
             let entry = &mut entries[entry_index];
             match change {
                 ApplyChange::SetSizeToZero => {
@@ -104,11 +179,12 @@ impl Outcome {
                 }
             }
         }
+        if let Some(mut changed_entries) = fs_monitor_update.and_then(|update| update.changed_entries) {
+            changed_entries.sort_unstable();
+            gix_status::fsmonitor::mark_valid(&mut index, |idx| changed_entries.binary_search(&idx).is_ok());
+        }
 
-        Some(index.write(crate::index::write::Options {
-            extensions: Default::default(),
-            skip_hash: self.skip_hash,
-        }))
+        Some(index.write(self.write_options))
     }
 }
 
@@ -1,6 +1,10 @@
 pub use gix_status as plumbing;
 
-use crate::{Repository, config, config::cache::util::ApplyLeniencyDefault, util::OwnedOrStaticAtomicBool};
+use crate::{
+    Repository, config,
+    config::cache::util::{ApplyLeniency, ApplyLeniencyDefault},
+    util::OwnedOrStaticAtomicBool,
+};
 
 /// A structure to hold options configuring the status request, which can then be turned into an iterator.
 pub struct Platform<'repo, Progress>
@@ -15,6 +19,27 @@ where
     index_worktree_options: index_worktree::Options,
     tree_index_renames: tree_index::TrackRenames,
     should_interrupt: Option<OwnedOrStaticAtomicBool>,
+    fs_monitor: Option<Box<dyn RefreshIndex>>,
+}
+
+/// Allow filesystem monitors with any error type to be used by the [`Platform`].
+trait RefreshIndex: Send {
+    fn refresh(
+        &mut self,
+        index: &mut gix_index::State,
+    ) -> Result<gix_status::fsmonitor::Outcome, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
+impl<T> RefreshIndex for T
+where
+    T: gix_status::fsmonitor::FsMonitor + Send,
+{
+    fn refresh(
+        &mut self,
+        index: &mut gix_index::State,
+    ) -> Result<gix_status::fsmonitor::Outcome, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(gix_status::fsmonitor::refresh(index, self)?)
+    }
 }
 
 /// How to obtain a submodule's status.
@@ -106,6 +131,7 @@ impl Repository {
             index: None,
             submodules: Submodule::default(),
             should_interrupt: None,
+            fs_monitor: None,
             head_tree: Some(None),
             tree_index_renames: Default::default(),
             index_worktree_options: index_worktree::Options {
@@ -131,6 +157,58 @@ impl Repository {
     }
 }
 
+/// Filesystem monitor
+impl Repository {
+    /// Return the filesystem monitor hook configured with `core.fsmonitor`, which is invoked with the protocol version
+    /// configured in `core.fsmonitorHookVersion`, or `None` if no hook is configured or if there is no worktree.
+    ///
+    /// ### Deviation
+    ///
+    /// If `core.fsmonitor` is `true`, Git would use its built-in filesystem monitor daemon which isn't supported,
+    /// and `None` is returned.
+    pub fn fs_monitor_hook(&self) -> Result<Option<gix_status::fsmonitor::Hook>, fs_monitor::Error> {
+        let Some(worktree) = self.workdir() else {
+            return Ok(None);
+        };
+        let key = &config::tree::Core::FSMONITOR;
+        let Some(value) = self
+            .config
+            .resolved
+            .string_filter(key, &mut self.filter_config_section())
+        else {
+            return Ok(None);
+        };
+        if value.is_empty() || gix_config::Boolean::try_from(value.as_ref() as &crate::bstr::BStr).is_ok() {
+            return Ok(None);
+        }
+        let Some(command) = self.config.trusted_file_path(key)? else {
+            return Ok(None);
+        };
+        let version = config::tree::Core::FSMONITOR_HOOK_VERSION
+            .try_into_u64(self.config.resolved.integer(config::tree::Core::FSMONITOR_HOOK_VERSION))
+            .with_leniency(self.config.lenient_config)?
+            .and_then(|version| match version {
+                1 => Some(gix_status::fsmonitor::hook::Version::V1),
+                2 => Some(gix_status::fsmonitor::hook::Version::V2),
+                _ => None,
+            });
+        Ok(Some(gix_status::fsmonitor::Hook::new(command, worktree, version)))
+    }
+}
+
+///
+pub mod fs_monitor {
+    /// The error returned by [Repository::fs_monitor_hook()](crate::Repository::fs_monitor_hook()).
+    #[derive(Debug, thiserror::Error)]
+    #[expect(missing_docs)]
+    pub enum Error {
+        #[error("Could not interpolate the path to the fsmonitor hook")]
+        HookPath(#[from] gix_config::path::interpolate::Error),
+        #[error(transparent)]
+        HookVersion(#[from] crate::config::unsigned_integer::Error),
+    }
+}
+
 ///
 pub mod is_dirty {
     use std::convert::Infallible;
@@ -216,7 +294,7 @@ pub mod into_iter {
         #[cfg(not(feature = "parallel"))]
         IndexWorktreeStatus(#[from] crate::status::index_worktree::Error),
         #[error(transparent)]
-        ConfigSkipHash(#[from] crate::config::boolean::Error),
+        IndexWriteOptions(#[from] crate::repository::index_write_options::Error),
         #[error(transparent)]
         PrepareSubmodules(#[from] crate::submodule::modules::Error),
         #[error("Could not create an index for the head tree to compare with the worktree index")]
@@ -229,6 +307,8 @@ pub mod into_iter {
         Pathspec(#[from] crate::pathspec::init::Error),
         #[error(transparent)]
         HeadTreeDiff(#[from] crate::status::tree_index::Error),
+        #[error(transparent)]
+        FsMonitor(#[from] crate::status::fs_monitor::Error),
     }
 }
 
@@ -83,6 +83,18 @@ where
         self
     }
 
+    /// Use `monitor` to learn which index entries may have changed, so that only these are checked for modifications.
+    ///
+    /// By default, the hook configured with `core.fsmonitor` is used, if there is one, see
+    /// [`Repository::fs_monitor_hook()`](crate::Repository::fs_monitor_hook()).
+    /// The token it provides is kept in the index when [writing changes](crate::status::Outcome::write_changes()).
+    ///
+    /// The monitor isn't used with a shared index passed to [`index()`](Self::index()), as it can't be changed.
+    pub fn fs_monitor(mut self, monitor: impl gix_status::fsmonitor::FsMonitor + Send + 'static) -> Self {
+        self.fs_monitor = Some(Box::new(monitor));
+        self
+    }
+
     /// Configure the index-to-worktree rename tracking with `rewrites`, which is `None` by default.
     ///
     /// Note that Git does not have configuration related to rename tracking of changes between the index
@@ -0,0 +1,21 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+git config user.name committer
+git config user.email committer@example.com
+
+echo a > a
+echo b > b
+mkdir dir
+echo c > dir/c
+git add . && git commit -q -m "initial"
+
+git checkout -q -b other
+echo other > a
+git rm -q b
+echo new > dir/new
+echo d > d
+git add . && git commit -q -m "other"
+
+git checkout -q main
@@ -0,0 +1,71 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of combined diffs of merge commits, `<merge>.<mode>.baseline` for each merge and
+# `cc` as well as `c` mode, along with a tag for each merge.
+git init -q
+git symbolic-ref HEAD refs/heads/main
+
+function baseline() {
+  local merge=${1:?first argument is the merge to show}
+  git show --format= --no-color --full-index --cc "$merge" > "${merge}.cc.baseline"
+  git show --format= --no-color --full-index -c "$merge" > "${merge}.c.baseline"
+}
+
+seq 10 > evil.txt
+seq 10 > resolved.txt
+seq 10 > one-side.txt
+seq 10 > same.txt
+seq 10 > deleted.txt
+seq 10 > mode.sh
+printf 'binary\0content' > image.bin
+git add .
+git update-index --add --cacheinfo 160000,1111111111111111111111111111111111111111,submodule
+git commit -q -m "base" && git tag base
+
+git checkout -q -b side
+sed -i 's/^2$/two/' evil.txt
+sed -i 's/^5$/five/' resolved.txt
+sed -i 's/^9$/nine/' one-side.txt
+sed -i 's/^7$/seven/' same.txt deleted.txt
+printf 'binary\0side' > image.bin
+printf 'side\n' > added.txt
+git add .
+git update-index --add --cacheinfo 160000,2222222222222222222222222222222222222222,submodule
+git commit -q -m "side"
+
+git checkout -q main
+sed -i 's/^5$/FIVE/' resolved.txt
+sed -i 's/^7$/seven/' same.txt
+sed -i 's/^1$/one/' deleted.txt
+chmod +x mode.sh
+printf 'main\n' > added.txt
+git add . && git commit -q -m "main"
+
+git merge -q side -m "merge" 2>/dev/null || true
+sed -i 's/^8$/evil/' evil.txt
+printf '1\n2\n3\n4\nFIVE and five\n6\n7\n8\n9\n10\n' > resolved.txt
+git rm -qf deleted.txt
+printf 'main\nside\n' > added.txt
+printf 'binary\0merged' > image.bin
+printf 'only in the merge\n' > new.txt
+chmod +x same.txt
+git add .
+git update-index --add --cacheinfo 160000,3333333333333333333333333333333333333333,submodule
+git commit -q -m "merge" && git tag merge
+baseline merge
+
+git checkout -q -b octopus-a base
+sed -i 's/^1$/a/' evil.txt
+git commit -q -am "a"
+git checkout -q -b octopus-b base
+sed -i 's/^10$/b/' evil.txt
+git commit -q -am "b"
+git checkout -q -b octopus-c base
+printf 'new\n' > octopus.txt
+git add octopus.txt && git commit -q -m "c"
+git checkout -q octopus-a
+git merge -q octopus-b octopus-c -m "octopus"
+sed -i 's/^5$/evil/' evil.txt
+git commit -q -a --amend -m "octopus" && git tag octopus
+baseline octopus
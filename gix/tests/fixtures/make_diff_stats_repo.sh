@@ -0,0 +1,63 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# Produce baselines of `git diff` statistics between the trees of tagged commits:
+# `<from>..<to>.<name>.baseline` files for each of the statistics in `baseline()`.
+git init -q
+git config diff.renames true
+
+function baseline() {
+  local from=${1:?first argument is the revision to diff from} to=${2:?second argument is the revision to diff to}
+  local name
+  for args in --numstat --shortstat \
+    --stat=80 --stat=50 --stat=200 --stat=60,20 --stat=80,0,2 --stat-graph-width=10 \
+    --dirstat=lines --dirstat=lines,cumulative --dirstat=files --dirstat=files,cumulative,10 --dirstat=lines,0; do
+    name=${args#--}
+    git diff --no-color "$args" "$from" "$to" > "${from}..${to}.${name}.baseline"
+  done
+}
+
+function lines() {
+  local prefix=${1:?first argument is the line prefix} count=${2:?second argument is the amount of lines}
+  seq "$count" | sed "s/^/${prefix} /"
+}
+
+mkdir -p src/core/nested src/util docs/guide assets a/very/deeply/nested/directory/structure/with/a
+lines main 20 > src/main.rs
+lines core 120 > src/core/lib.rs
+lines nested 10 > src/core/nested/mod.rs
+lines util 30 > src/util/helpers.rs
+lines guide 40 > docs/guide/intro.md
+lines readme 5 > README.md
+lines moved 30 > docs/to-be-moved.md
+lines similar 50 > src/util/similar.rs
+lines deep 3 > a/very/deeply/nested/directory/structure/with/a/rather-long-file-name.txt
+printf 'binary\0content' > assets/image.bin
+printf '#!/bin/sh\necho hi\n' > run.sh
+git add . && git commit -q -m "c1" && git tag c1
+
+# Plenty of changes to scale the graph, along with binary and mode changes.
+lines changed 300 > src/core/lib.rs
+lines main 10 > src/main.rs
+lines added 7 >> src/core/nested/mod.rs
+lines new 15 > src/util/new.rs
+rm docs/guide/intro.md
+lines deep 5 > a/very/deeply/nested/directory/structure/with/a/rather-long-file-name.txt
+printf 'binary\0content, but more of it' > assets/image.bin
+printf 'more\0binary' > assets/other.bin
+chmod +x run.sh
+echo "one more line" >> README.md
+git add . && git commit -q -m "c2" && git tag c2
+
+# Renames, with and without changes, and across directories.
+git mv docs/to-be-moved.md docs/guide/moved.md
+git mv src/util/similar.rs src/core/similar.rs
+echo "a change" >> src/core/similar.rs
+git mv src/util/helpers.rs src/util/helper.rs
+git mv assets/other.bin other.bin
+lines changed 3 > src/core/nested/mod.rs
+git add . && git commit -q -m "c3" && git tag c3
+
+baseline c1 c2
+baseline c2 c3
+baseline c1 c3
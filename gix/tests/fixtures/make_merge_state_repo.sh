@@ -0,0 +1,31 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+git config user.name committer
+git config user.email committer@example.com
+
+printf '1\n2\n3\n' > a
+echo b > b
+git add . && git commit -q -m "initial"
+git branch conflict
+git branch clean
+
+git checkout -q conflict
+printf '1\ntheirs\n3\n' > a
+echo t > t
+git add . && git commit -q -m "theirs"
+
+git checkout -q clean
+echo changed > b
+git commit -q -am "clean"
+
+git checkout -q main
+printf '1\nours\n3\n' > a
+git commit -q -am "ours"
+
+git checkout -q -b ahead
+echo e > e
+git add . && git commit -q -m "ahead"
+
+git checkout -q main
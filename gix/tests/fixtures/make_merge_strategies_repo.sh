@@ -0,0 +1,36 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+printf '%s\r\n' 1 2 3 4 5 6 7 8 9 10 >file.txt
+git add file.txt
+git commit -q -m base
+
+git checkout -q -b topic1 main
+echo one >one
+git add one
+git commit -q -m topic1
+
+git checkout -q -b topic2 main
+echo two >two
+git add two
+git commit -q -m topic2
+
+# Their side changes a line of a file committed with CRLF line endings, while our side normalizes them to LF.
+git checkout -q -b theirs main
+printf '%s\r\n' 1 2 3 4 5 6 7 8 theirs 10 >file.txt
+git commit -q -am theirs
+
+git checkout -q main
+echo "* text=auto" >.gitattributes
+printf '%s\n' 1 ours 3 4 5 6 7 8 9 10 >file.txt
+git add .gitattributes file.txt
+git commit -q -m "normalize line endings"
+
+git checkout -q -b octopus main
+git merge -q --no-edit topic1 main~1 topic2
+
+git checkout -q -b renormalized main
+git merge -q --no-edit -Xrenormalize theirs
@@ -0,0 +1,55 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+# A superproject whose branches change the commit of the submodule `sub`, which is a clone of `module`.
+
+git init -q module
+(cd module
+  git checkout -q -b main
+  echo 0 >file
+  git add file
+  git commit -q -m base
+  git tag base
+
+  git checkout -q -b a
+  echo a1 >a
+  git add a
+  git commit -q -m a1
+  git tag a1
+  echo a2 >a
+  git commit -q -am a2
+  git tag a2
+
+  git checkout -q -b b base
+  echo b1 >b
+  git add b
+  git commit -q -m b1
+  git tag b1
+
+  git checkout -q -b merged a1
+  git merge -q --no-edit b
+  git tag m
+
+  git checkout -q main
+)
+
+git init -q superproject
+(cd superproject
+  git checkout -q -b main
+  git submodule add -q ../module sub
+  git -C sub checkout -q base
+  git add sub
+  git commit -q -m "add submodule"
+
+  for commit in a1 a2 b1; do
+    git checkout -q -b "$commit" main
+    git -C sub checkout -q "$commit"
+    git add sub
+    git commit -q -m "sub at $commit"
+  done
+  git checkout -q main
+  git -C sub checkout -q base
+
+  git checkout -q -b fast-forward a1
+  git merge -q --no-edit a2
+)
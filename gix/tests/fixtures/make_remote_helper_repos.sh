@@ -0,0 +1,63 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  echo a > a
+  git add a && git commit -q -m "a"
+  git tag -m "annotated" v1
+  mkdir dir && echo b > dir/b
+  git mv a dir/a
+  git add dir && git commit -q -m "b"
+)
+
+git init -q local
+
+mkdir bin
+# Connects to `git-upload-pack` directly.
+cat > bin/git-remote-relay <<'HELPER'
+#!/bin/sh
+while read -r cmd arg; do
+  case "$cmd" in
+    capabilities) printf 'connect\n\n';;
+    connect) printf '\n'; exec git "${arg#git-}" "$2";;
+    *) exit 0;;
+  esac
+done
+HELPER
+
+# Lists refs and fetches objects into GIT_DIR with `git fetch`.
+cat > bin/git-remote-fetcher <<'HELPER'
+#!/bin/sh
+while read -r cmd arg; do
+  case "$cmd" in
+    capabilities) printf 'fetch\n\n';;
+    list)
+      git --git-dir="$2" for-each-ref --format='%(objectname) %(refname)' refs/heads refs/tags
+      printf '@refs/heads/main HEAD\n\n';;
+    fetch)
+      names="${arg#* }"
+      while read -r line && test -n "$line"; do names="$names ${line#* * }"; done
+      git fetch -q "$2" $names
+      printf '\n';;
+    *) exit 0;;
+  esac
+done
+HELPER
+
+# Can only import history as fast-import stream, with `git fast-export`.
+cat > bin/git-remote-exporter <<'HELPER'
+#!/bin/sh
+while read -r cmd arg; do
+  case "$cmd" in
+    capabilities) printf 'import\nrefspec refs/heads/*:refs/exporter/heads/*\n\n';;
+    list) printf '? refs/heads/main\n@refs/heads/main HEAD\n\n';;
+    import)
+      names="$arg"
+      while read -r line && test -n "$line"; do names="$names ${line#import }"; done
+      git --git-dir="$2" fast-export --use-done-feature --refspec 'refs/heads/*:refs/exporter/heads/*' $names;;
+    *) exit 0;;
+  esac
+done
+HELPER
+chmod +x bin/*
@@ -195,7 +195,7 @@ git init --bare bad-url-rewriting
 [remote "origin"]
   pushUrl = "file://dev/null"
 
-[url "invalid:://"]
+[url "in:valid://"]
   pushInsteadOf = "file://"
 
 [url "https://github.com/byron/"]
@@ -230,7 +230,7 @@ git init --bare multiple-bad-url-rewriting
   git config --add remote.origin.url https://github.com/foobar/gitoxide
   git config --add remote.origin.url bad:gitoxide
   git config --add url.https://github.com/byron/.insteadOf https://github.com/foobar/
-  git config --add url.invalid:://.insteadOf bad:
+  git config --add url.in:valid://.insteadOf bad:
 )
 
 git init --bare multiple-urls-with-empty-reset
@@ -272,7 +272,7 @@ git init --bare bad-push-fallback-url-rewriting
 (cd bad-push-fallback-url-rewriting
 
   git config remote.origin.url alias:repo
-  git config url.invalid:://.pushInsteadOf alias:
+  git config url.in:valid://.pushInsteadOf alias:
 )
 
 git init --bare bad-explicit-push-url-rewriting
@@ -280,7 +280,7 @@ git init --bare bad-explicit-push-url-rewriting
 
   git config remote.origin.url alias:repo
   git config remote.origin.pushUrl bad:repo
-  git config url.invalid:://.insteadOf bad:
+  git config url.in:valid://.insteadOf bad:
 )
 
 git clone --shared base protocol_denied
@@ -0,0 +1,50 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q base
+(cd base
+  echo a > a
+  git add a && git commit -q -m "a"
+  git tag -m "annotated" v1
+  echo b > b
+  git add b && git commit -q -m "b"
+  git branch other
+  mkdir dir && echo c > dir/c
+  git add dir && git commit -q -m "c"
+  git tag lightweight
+)
+
+git clone -q --bare base exported.git
+touch exported.git/git-daemon-export-ok
+
+git init -q --bare empty.git
+touch empty.git/git-daemon-export-ok
+
+git clone -q --bare base hooked.git
+touch hooked.git/git-daemon-export-ok
+(cd hooked.git/hooks
+  cat > pre-receive <<'HOOK'
+#!/bin/sh
+test -n "$GIT_QUARANTINE_PATH" || { echo "objects are not quarantined"; exit 1; }
+while read old new name; do
+  case "$new" in
+    *[!0]*) git cat-file -e "$new" || { echo "$new isn't available"; exit 1; } ;;
+  esac
+  case "$name" in
+    refs/heads/forbidden) echo "pushing to $name is forbidden"; exit 1 ;;
+  esac
+done
+HOOK
+  cat > update <<'HOOK'
+#!/bin/sh
+case "$1" in
+  refs/heads/declined) echo "update of $1 declined" >&2; exit 1 ;;
+esac
+HOOK
+  cat > post-receive <<'HOOK'
+#!/bin/sh
+cat >> post-receive.log
+echo "options: ${GIT_PUSH_OPTION_COUNT-} ${GIT_PUSH_OPTION_0-}" >> post-receive.log
+HOOK
+  chmod +x pre-receive update post-receive
+)
@@ -0,0 +1,19 @@
+#!/usr/bin/env bash
+set -eu -o pipefail
+
+git init -q
+git config user.name committer
+git config user.email committer@example.com
+
+echo a > a
+mkdir dir
+echo c > dir/c
+git add . && git commit -q -m "initial"
+
+echo changed > dir/c
+git commit -q -am "change dir/c"
+
+git sparse-checkout set --no-cone /a
+git config core.untrackedCache true
+git update-index --untracked-cache
+git status >/dev/null
@@ -0,0 +1,539 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::checkout::{Conflict, Options, Target, reset, restore};
+
+use crate::util::repo_rw;
+
+fn branch(name: &str) -> Target {
+    Target::Branch(format!("refs/heads/{name}").try_into().expect("valid name"))
+}
+
+fn last_head_reflog(repo: &gix::Repository) -> crate::Result<gix_ref::log::Line> {
+    let head = repo.find_reference("HEAD")?;
+    let mut logs = head.log_iter();
+    let last = logs.rev()?.expect("reflog exists").next().expect("at least one line")?;
+    Ok(last)
+}
+
+fn read(repo: &gix::Repository, rela_path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(repo.workdir().expect("non-bare").join(rela_path))
+}
+
+#[test]
+fn switch_branch_writes_changed_files_only_and_moves_head() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_checkout_repo.sh")?;
+    let main_id = repo.head_id()?.detach();
+    let other_id = repo.find_reference("other")?.id().detach();
+
+    let outcome = repo.checkout(
+        branch("other"),
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        Options::default(),
+    )?;
+    assert_eq!(outcome.checkout.files_updated, 3, "a, d and dir/new changed");
+    assert_eq!(outcome.files_removed, 1, "b was removed");
+
+    assert_eq!(read(&repo, "a")?, "other\n");
+    assert_eq!(read(&repo, "d")?, "d\n");
+    assert_eq!(read(&repo, "dir/new")?, "new\n");
+    assert!(!repo.workdir().expect("non-bare").join("b").exists());
+    assert!(!repo.is_dirty()?, "index and worktree match the new HEAD");
+
+    assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/other");
+    let line = last_head_reflog(&repo)?;
+    assert_eq!(line.message, "checkout: moving from main to other");
+    assert_eq!(line.previous_oid, main_id);
+    assert_eq!(line.new_oid, other_id);
+
+    repo.checkout(
+        Target::Detached(main_id),
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        Options::default(),
+    )?;
+    assert!(repo.head()?.is_detached());
+    assert_eq!(read(&repo, "b")?, "b\n", "b was restored");
+    assert!(
+        !repo.workdir().expect("non-bare").join("dir/new").exists(),
+        "files are removed when their tree entry is gone"
+    );
+    let line = last_head_reflog(&repo)?;
+    assert_eq!(line.message, format!("checkout: moving from other to {main_id}"));
+    assert_eq!(line.previous_oid, other_id);
+    assert_eq!(line.new_oid, main_id);
+    Ok(())
+}
+
+#[test]
+fn local_changes_are_kept_unless_they_would_be_overwritten() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_checkout_repo.sh")?;
+    let workdir = repo.workdir().expect("non-bare").to_owned();
+    std::fs::write(workdir.join("dir/c"), "changed\n")?;
+    std::fs::write(workdir.join("a"), "changed\n")?;
+    std::fs::write(workdir.join("d"), "untracked\n")?;
+
+    let err = repo
+        .checkout(
+            branch("other"),
+            gix::progress::Discard,
+            &AtomicBool::default(),
+            Options::default(),
+        )
+        .unwrap_err();
+    let gix::checkout::Error::Conflicts { conflicts } = err else {
+        panic!("expected conflicts, got {err:?}");
+    };
+    assert_eq!(
+        conflicts,
+        [
+            Conflict::LocalChange { rela_path: "a".into() },
+            Conflict::Untracked { rela_path: "d".into() },
+        ]
+    );
+    assert_eq!(read(&repo, "a")?, "changed\n", "nothing was changed");
+    assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/main");
+
+    std::fs::write(workdir.join("a"), "a\n")?;
+    std::fs::remove_file(workdir.join("d"))?;
+    repo.checkout(
+        branch("other"),
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        Options::default(),
+    )?;
+    assert_eq!(read(&repo, "dir/c")?, "changed\n", "unrelated local changes are kept");
+    assert_eq!(read(&repo, "a")?, "other\n");
+
+    repo.checkout(
+        branch("main"),
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        Options { force: true },
+    )?;
+    assert_eq!(read(&repo, "dir/c")?, "c\n", "forcing discards all local changes");
+    assert_eq!(read(&repo, "a")?, "a\n");
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn reset_modes() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_checkout_repo.sh")?;
+    let main_id = repo.head_id()?.detach();
+    let other_id = repo.find_reference("other")?.id().detach();
+
+    repo.reset(
+        other_id,
+        reset::Mode::Soft,
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(repo.head_id()?, other_id, "the branch HEAD points to was moved");
+    assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/main");
+    assert!(
+        repo.index()?.entry_by_path("d".into()).is_none(),
+        "the index wasn't touched"
+    );
+    assert_eq!(
+        repo.find_reference("ORIG_HEAD")?.id(),
+        main_id,
+        "ORIG_HEAD points to the previous HEAD commit"
+    );
+    let line = last_head_reflog(&repo)?;
+    assert_eq!(line.message, format!("reset: moving to {}", other_id.to_hex()));
+    assert_eq!(line.previous_oid, main_id);
+    assert_eq!(line.new_oid, other_id);
+
+    repo.reset(
+        other_id,
+        reset::Mode::Mixed,
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert!(
+        repo.index()?.entry_by_path("d".into()).is_some(),
+        "the index matches the commit now"
+    );
+    assert_eq!(read(&repo, "a")?, "a\n", "the worktree wasn't touched");
+
+    let outcome = repo.reset(
+        other_id,
+        reset::Mode::Hard,
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(outcome.checkout.files_updated, 3, "a, d and dir/new were written");
+    assert_eq!(outcome.files_removed, 0, "b was already removed from the index");
+    assert_eq!(read(&repo, "a")?, "other\n");
+    assert!(!repo.is_dirty()?);
+    Ok(())
+}
+
+#[test]
+fn reset_keeps_sparse_checkout_and_untracked_cache() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_sparse_checkout_repo.sh")?;
+    let head_id = repo.head_id()?.detach();
+    let parent_id = repo.head_commit()?.parent_ids().next().expect("one parent").detach();
+    let workdir = repo.workdir().expect("non-bare").to_owned();
+    let assert_sparse = |repo: &gix::Repository| -> crate::Result {
+        let index = repo.open_index()?;
+        let entry = index.entry_by_path("dir/c".into()).expect("still tracked");
+        assert!(entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE));
+        assert!(index.untracked().is_some(), "the untracked cache is kept");
+        assert!(
+            !workdir.join("dir/c").exists(),
+            "files outside of the sparse checkout aren't written"
+        );
+        assert!(!repo.is_dirty()?, "skipped files aren't seen as deleted");
+        Ok(())
+    };
+    assert_sparse(&repo)?;
+
+    repo.reset(
+        parent_id,
+        reset::Mode::Mixed,
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_sparse(&repo)?;
+
+    let outcome = repo.reset(
+        head_id,
+        reset::Mode::Hard,
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(outcome.checkout.files_updated, 0);
+    assert_sparse(&repo)?;
+    Ok(())
+}
+
+#[test]
+fn restore_from_index_and_source() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_checkout_repo.sh")?;
+    let workdir = repo.workdir().expect("non-bare").to_owned();
+    let other_id = repo.find_reference("other")?.id().detach();
+    std::fs::write(workdir.join("a"), "changed\n")?;
+    std::fs::write(workdir.join("b"), "changed\n")?;
+
+    let outcome = repo.restore(
+        ["a"],
+        restore::Options::default(),
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(outcome.checkout.files_updated, 1);
+    assert_eq!(read(&repo, "a")?, "a\n", "restored from the index");
+    assert_eq!(read(&repo, "b")?, "changed\n", "paths that don't match are left alone");
+
+    repo.restore(
+        ["dir", "b"],
+        restore::Options {
+            source: Some(other_id),
+            staged: true,
+            worktree: true,
+        },
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(read(&repo, "dir/new")?, "new\n");
+    assert!(!workdir.join("b").exists(), "b isn't in the source and is removed");
+    let index = repo.index()?;
+    assert!(index.entry_by_path("dir/new".into()).is_some());
+    assert!(index.entry_by_path("b".into()).is_none());
+    assert!(index.entry_by_path("a".into()).is_some());
+
+    let err = repo
+        .restore(
+            None::<&str>,
+            restore::Options::default(),
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )
+        .unwrap_err();
+    assert!(matches!(err, restore::Error::MissingPathspec));
+    Ok(())
+}
+
+#[cfg(all(feature = "merge", feature = "revision"))]
+mod merge {
+    use std::sync::atomic::AtomicBool;
+
+    use gix::checkout::{
+        merge::{Error, FastForward, Options, Outcome},
+        reset,
+    };
+    use gix_index::entry::Stage;
+
+    use super::{branch, last_head_reflog, read};
+    use crate::util::repo_rw;
+
+    fn read_state(repo: &gix::Repository, name: &str) -> std::io::Result<String> {
+        std::fs::read_to_string(repo.git_dir().join(name))
+    }
+
+    /// Resolve the conflict at `rela_path` by writing `content` into the worktree and adding it to the index.
+    fn resolve(repo: &gix::Repository, rela_path: &str, content: &str) -> crate::Result {
+        std::fs::write(repo.workdir().expect("non-bare").join(rela_path), content)?;
+        let id = repo.write_blob(content)?.detach();
+        let mut index = repo.open_index()?;
+        index.remove_entries(|_, path, entry| path == rela_path && entry.stage() != Stage::Unconflicted);
+        index.dangerously_push_entry(
+            Default::default(),
+            id,
+            gix_index::entry::Flags::empty(),
+            gix_index::entry::Mode::FILE,
+            rela_path.into(),
+        );
+        index.sort_entries();
+        index.write(Default::default())?;
+        Ok(())
+    }
+
+    #[test]
+    fn conflicts_are_persisted_and_can_be_continued() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_merge_state_repo.sh")?;
+        let main_id = repo.head_id()?.detach();
+        let conflict_id = repo.find_reference("conflict")?.id().detach();
+
+        let outcome = repo.merge(
+            branch("conflict"),
+            Options::default(),
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        let Outcome::InProgress { unmerged_paths, .. } = outcome else {
+            panic!("expected a merge in progress, got {outcome:?}");
+        };
+        assert_eq!(unmerged_paths, ["a"]);
+        assert_eq!(
+            read(&repo, "a")?,
+            "1\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> conflict\n3\n"
+        );
+        assert_eq!(read(&repo, "t")?, "t\n", "non-conflicting changes are checked out");
+        assert_eq!(read_state(&repo, "MERGE_HEAD")?, format!("{conflict_id}\n"));
+        assert_eq!(
+            read_state(&repo, "MERGE_MSG")?,
+            "Merge branch 'conflict'\n\n# Conflicts:\n#\ta\n"
+        );
+        assert_eq!(read_state(&repo, "MERGE_MODE")?, "");
+        assert_eq!(repo.find_reference("ORIG_HEAD")?.id(), main_id);
+        assert_eq!(repo.state(), Some(gix::state::InProgress::Merge));
+        assert_eq!(repo.head_id()?, main_id, "HEAD isn't moved yet");
+
+        let index = repo.open_index()?;
+        let stages: Vec<_> = index
+            .entries()
+            .iter()
+            .filter(|entry| entry.path(&index) == "a")
+            .map(gix_index::Entry::stage)
+            .collect();
+        assert_eq!(stages, [Stage::Base, Stage::Ours, Stage::Theirs]);
+
+        let err = repo
+            .merge(
+                branch("clean"),
+                Options::default(),
+                gix::progress::Discard,
+                &AtomicBool::default(),
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::MergeInProgress));
+        let err = repo.merge_continue().unwrap_err();
+        assert!(matches!(err, Error::UnmergedPaths { rela_paths } if rela_paths == ["a"]));
+
+        resolve(&repo, "a", "1\nresolved\n3\n")?;
+        let commit = repo.merge_continue()?;
+        assert_eq!(repo.head_id()?, commit);
+        let commit = repo.find_commit(commit)?;
+        assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [main_id, conflict_id]);
+        assert_eq!(commit.message_raw()?, "Merge branch 'conflict'\n");
+        assert_eq!(
+            repo.find_object(commit.tree_id()?)?
+                .peel_to_tree()?
+                .lookup_entry_by_path("a")?
+                .expect("present")
+                .object()?
+                .data,
+            b"1\nresolved\n3\n"
+        );
+        assert_eq!(repo.state(), None, "the merge state was removed");
+        assert!(!repo.git_dir().join("MERGE_MSG").exists());
+        assert!(!repo.git_dir().join("MERGE_MODE").exists());
+        assert_eq!(
+            last_head_reflog(&repo)?.message,
+            "commit (merge): Merge branch 'conflict'"
+        );
+        assert!(!repo.is_dirty()?);
+        Ok(())
+    }
+
+    #[test]
+    fn abort_restores_the_previous_state() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_merge_state_repo.sh")?;
+        let main_id = repo.head_id()?.detach();
+        let err = repo
+            .merge_abort(gix::progress::Discard, &AtomicBool::default())
+            .unwrap_err();
+        assert!(matches!(err, Error::NoMergeInProgress));
+
+        std::fs::write(repo.workdir().expect("non-bare").join("b"), "local\n")?;
+        repo.merge(
+            branch("conflict"),
+            Options::default(),
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        let outcome = repo.merge_abort(gix::progress::Discard, &AtomicBool::default())?;
+        assert_eq!(outcome.checkout.files_updated, 1, "a is restored");
+        assert_eq!(outcome.files_removed, 1, "t is removed");
+        assert_eq!(read(&repo, "a")?, "1\nours\n3\n");
+        assert_eq!(
+            read(&repo, "b")?,
+            "local\n",
+            "local changes the merge didn't touch are kept"
+        );
+        assert!(!repo.workdir().expect("non-bare").join("t").exists());
+        assert_eq!(repo.state(), None);
+        assert_eq!(repo.head_id()?, main_id);
+        assert!(
+            repo.open_index()?
+                .entries()
+                .iter()
+                .all(|entry| entry.stage() == Stage::Unconflicted)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reset_ends_a_merge_unless_soft() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_merge_state_repo.sh")?;
+        let main_id = repo.head_id()?.detach();
+        repo.merge(
+            branch("conflict"),
+            Options::default(),
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+
+        let err = repo
+            .reset(
+                main_id,
+                reset::Mode::Soft,
+                gix::progress::Discard,
+                &AtomicBool::default(),
+            )
+            .unwrap_err();
+        assert!(matches!(err, reset::Error::SoftResetDuringMerge));
+        assert_eq!(repo.state(), Some(gix::state::InProgress::Merge));
+
+        repo.reset(
+            main_id,
+            reset::Mode::Mixed,
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert_eq!(repo.state(), None);
+        for name in ["MERGE_HEAD", "MERGE_MSG", "MERGE_MODE"] {
+            assert!(!repo.git_dir().join(name).exists(), "{name} was removed");
+        }
+        assert!(
+            repo.open_index()?
+                .entries()
+                .iter()
+                .all(|entry| entry.stage() == Stage::Unconflicted)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn clean_merges_and_fast_forwards() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_merge_state_repo.sh")?;
+        let main_id = repo.head_id()?.detach();
+        let clean_id = repo.find_reference("clean")?.id().detach();
+
+        let outcome = repo.merge(
+            branch("clean"),
+            Options::default(),
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        let Outcome::Merged { commit, checkout } = outcome else {
+            panic!("expected a merge commit, got {outcome:?}");
+        };
+        assert_eq!(checkout.checkout.files_updated, 1);
+        assert_eq!(read(&repo, "b")?, "changed\n");
+        assert_eq!(repo.head_id()?, commit);
+        assert_eq!(
+            repo.find_commit(commit)?.parent_ids().collect::<Vec<_>>(),
+            [main_id, clean_id]
+        );
+        let line = last_head_reflog(&repo)?;
+        assert_eq!(line.message, "merge clean: Merge made by the 'ort' strategy.");
+        assert_eq!(repo.state(), None);
+        assert!(!repo.is_dirty()?);
+
+        let outcome = repo.merge(
+            branch("clean"),
+            Options::default(),
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert!(matches!(outcome, Outcome::UpToDate));
+
+        let err = repo
+            .merge(
+                branch("ahead"),
+                Options {
+                    fast_forward: FastForward::Only,
+                    ..Default::default()
+                },
+                gix::progress::Discard,
+                &AtomicBool::default(),
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::NotFastForward));
+        Ok(())
+    }
+
+    #[test]
+    fn fast_forward_or_no_commit() -> crate::Result {
+        let (repo, _tmp) = repo_rw("make_merge_state_repo.sh")?;
+        let main_id = repo.head_id()?.detach();
+        let ahead_id = repo.find_reference("ahead")?.id().detach();
+
+        let outcome = repo.merge(
+            branch("ahead"),
+            Options {
+                fast_forward: FastForward::Never,
+                commit: false,
+                message: None,
+            },
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert!(matches!(outcome, Outcome::InProgress { unmerged_paths, .. } if unmerged_paths.is_empty()));
+        assert_eq!(read_state(&repo, "MERGE_MODE")?, "no-ff");
+        assert_eq!(read_state(&repo, "MERGE_MSG")?, "Merge branch 'ahead'\n");
+        assert_eq!(read(&repo, "e")?, "e\n");
+        repo.merge_abort(gix::progress::Discard, &AtomicBool::default())?;
+        assert!(!repo.workdir().expect("non-bare").join("e").exists());
+
+        let outcome = repo.merge(
+            branch("ahead"),
+            Options::default(),
+            gix::progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert!(matches!(outcome, Outcome::FastForward { commit, .. } if commit == ahead_id));
+        assert_eq!(repo.head_id()?, ahead_id);
+        assert_eq!(repo.head_name()?.expect("not detached").as_bstr(), "refs/heads/main");
+        let line = last_head_reflog(&repo)?;
+        assert_eq!(line.message, "merge ahead: Fast-forward");
+        assert_eq!(line.previous_oid, main_id);
+        assert_eq!(read(&repo, "e")?, "e\n");
+        assert!(!repo.is_dirty()?);
+        Ok(())
+    }
+}
@@ -221,8 +221,8 @@ mod blocking_io {
             sorted([
                 hex_to_id("2d9d136fb0765f2e24c44a0f91984318d580d03b"),
                 hex_to_id("dfd0954dabef3b64f458321ef15571cc1a46d552"),
-                hex_to_id("dfd0954dabef3b64f458321ef15571cc1a46d552"),
-            ])
+            ]),
+            "shallow commits advertised more than once by the remote are only written once"
         );
         assert_eq!(
             repo.config_snapshot().boolean("my.marker"),
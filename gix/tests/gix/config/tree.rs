@@ -373,14 +373,12 @@ mod diff {
             ("Default", Algorithm::Myers),
             ("minimal", Algorithm::MyersMinimal),
             ("histogram", Algorithm::Histogram),
+            ("patience", Algorithm::Patience),
+            ("Patience", Algorithm::Patience),
         ] {
             assert_eq!(Diff::ALGORITHM.try_into_algorithm(actual)?, expected);
             assert!(Diff::ALGORITHM.validate(actual.into()).is_ok());
         }
-        assert_eq!(
-            Diff::ALGORITHM.try_into_algorithm("patience").unwrap_err().to_string(),
-            "The 'patience' algorithm is not yet implemented"
-        );
         assert_eq!(
             Diff::ALGORITHM.try_into_algorithm("foo").unwrap_err().to_string(),
             "Unknown diff algorithm named 'foo'"
@@ -568,6 +566,22 @@ mod core {
         Ok(())
     }
 
+    #[test]
+    fn untracked_cache() -> crate::Result {
+        for (value, expected) in [("keep", None), ("true", Some(true)), ("false", Some(false))] {
+            assert_eq!(Core::UNTRACKED_CACHE.try_into_untracked_cache(value)?, expected);
+            assert!(Core::UNTRACKED_CACHE.validate(value.into()).is_ok());
+        }
+        assert_eq!(
+            Core::UNTRACKED_CACHE
+                .try_into_untracked_cache("sometimes")
+                .unwrap_err()
+                .to_string(),
+            "The key \"core.untrackedCache=sometimes\" was invalid"
+        );
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "attributes")]
     fn safecrlf() -> crate::Result {
@@ -682,6 +696,32 @@ mod index {
     }
 }
 
+mod split_index {
+    use std::time::{Duration, SystemTime};
+
+    use gix::config::tree::{Key, SplitIndex};
+
+    #[test]
+    fn shared_index_expire() -> crate::Result {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * 24 * 60 * 60);
+        let key = &SplitIndex::SHARED_INDEX_EXPIRE;
+        for never in ["never", "false"] {
+            assert_eq!(key.try_into_expiry(never, now)?, None, "{never}");
+            assert!(key.validate(never.into()).is_ok());
+        }
+        assert_eq!(key.try_into_expiry("now", now)?, Some(now));
+        assert_eq!(
+            key.try_into_expiry("2.weeks.ago", now)?,
+            Some(now - Duration::from_secs(14 * 24 * 60 * 60))
+        );
+        assert_eq!(
+            key.try_into_expiry("nothing", now).unwrap_err().to_string(),
+            "The key \"splitIndex.sharedIndexExpire=nothing\" was invalid"
+        );
+        Ok(())
+    }
+}
+
 mod extensions {
     use gix::config::tree::{Extensions, Key};
 
@@ -1,4 +1,7 @@
-use gix_diff::blob::{Algorithm, Driver};
+use gix_diff::blob::{
+    Algorithm, Driver,
+    funcname::{Pattern, Syntax},
+};
 
 use crate::util::named_repo;
 
@@ -13,13 +16,20 @@ fn resource_cache() -> crate::Result {
             .detach(),
         Default::default(),
     )?;
+    let drivers = cache.filter.drivers();
     assert_eq!(
-        cache.filter.drivers(),
-        &[
+        drivers
+            .iter()
+            .filter(|driver| Driver::builtin(driver.name.as_ref()).is_none())
+            .cloned()
+            .collect::<Vec<_>>(),
+        [
             Driver {
                 name: "all-but-binary".into(),
                 command: Some("command".into()),
                 algorithm: Some(Algorithm::Histogram),
+                word_regex: None,
+                funcname: None,
                 binary_to_text_command: Some("textconv".into()),
                 is_binary: None
             },
@@ -35,6 +45,11 @@ fn resource_cache() -> crate::Result {
             }
         ]
     );
+    assert_eq!(
+        drivers.len(),
+        3 + Driver::builtins().count(),
+        "built-in drivers are always available"
+    );
     assert_eq!(cache.options.algorithm, Some(Algorithm::Histogram));
     assert!(
         !cache.options.skip_internal_diff_if_external_is_configured,
@@ -45,5 +60,92 @@ fn resource_cache() -> crate::Result {
         512 * 1024 * 1024,
         "the default value unless it's configured"
     );
+    assert_eq!(cache.options.word_regex, None, "no word regex is configured by default");
+    Ok(())
+}
+
+#[test]
+fn resource_cache_word_regex() -> crate::Result {
+    let mut repo = named_repo("make_diff_repo.sh")?;
+    {
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value(gix::config::tree::Diff::WORD_REGEX, "[^[:space:]]")?;
+        config.set_raw_value("diff.all-but-binary.wordRegex", "[a-z]+")?;
+    }
+    let index = repo.index()?;
+    let cache = gix::diff::resource_cache(
+        &repo,
+        gix::diff::blob::pipeline::Mode::ToGit,
+        repo.attributes_only(&index, gix_worktree::stack::state::attributes::Source::IdMapping)?
+            .detach(),
+        Default::default(),
+    )?;
+    assert_eq!(
+        cache.options.word_regex.as_ref().map(AsRef::as_ref),
+        Some("[^[:space:]]".as_bytes())
+    );
+    assert_eq!(
+        cache
+            .filter
+            .drivers()
+            .iter()
+            .find(|driver| driver.name == "all-but-binary")
+            .and_then(|driver| driver.word_regex.as_ref())
+            .map(AsRef::as_ref),
+        Some("[a-z]+".as_bytes()),
+        "drivers can override the word regex"
+    );
+    Ok(())
+}
+
+#[test]
+fn resource_cache_funcname() -> crate::Result {
+    let mut repo = named_repo("make_diff_repo.sh")?;
+    {
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("diff.all-but-binary.xfuncname", "^ignored")?;
+        config.set_raw_value("diff.all-but-binary.funcname", "^fn \\(.*\\)")?;
+        config.set_raw_value("diff.rust.xfuncname", "^(fn .*)")?;
+    }
+    let index = repo.index()?;
+    let cache = gix::diff::resource_cache(
+        &repo,
+        gix::diff::blob::pipeline::Mode::ToGit,
+        repo.attributes_only(&index, gix_worktree::stack::state::attributes::Source::IdMapping)?
+            .detach(),
+        Default::default(),
+    )?;
+    let driver = |name: &str| {
+        cache
+            .filter
+            .drivers()
+            .iter()
+            .find(|driver| driver.name == name)
+            .expect("driver is present")
+    };
+    assert_eq!(
+        driver("all-but-binary").funcname,
+        Some(Pattern {
+            pattern: "^fn \\(.*\\)".into(),
+            syntax: Syntax::Basic,
+            ignore_case: false,
+        }),
+        "the last of both keys wins"
+    );
+    let rust = driver("rust");
+    assert_eq!(
+        rust.funcname,
+        Some(Pattern {
+            pattern: "^(fn .*)".into(),
+            syntax: Syntax::Extended,
+            ignore_case: false,
+        }),
+        "built-in drivers can be overridden"
+    );
+    assert_eq!(
+        rust.word_regex,
+        Driver::builtin("rust".into()).and_then(|driver| driver.word_regex),
+        "values that aren't configured are kept"
+    );
     Ok(())
 }
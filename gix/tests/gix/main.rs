@@ -2,6 +2,8 @@
 mod util;
 use util::*;
 
+#[cfg(all(feature = "worktree-mutation", feature = "status"))]
+mod checkout;
 mod clone;
 mod commit;
 mod config;
@@ -16,6 +18,8 @@ mod remote;
 mod repository;
 #[cfg(feature = "revision")]
 mod revision;
+#[cfg(feature = "server")]
+mod server;
 #[cfg(feature = "status")]
 mod status;
 #[cfg(feature = "attributes")]
@@ -203,6 +203,7 @@ mod track_rewrites {
             limit: 1000,
             percentage: Some(0.5),
             track_empty: false,
+            matching: Default::default(),
         };
         let out = from
             .changes()?
@@ -917,6 +918,292 @@ mod track_rewrites {
     }
 }
 
+mod stats {
+    use gix::bstr::BString;
+    use gix_diff::stats::{File, Summary, dirstat};
+    use pretty_assertions::assert_eq;
+
+    use crate::{named_repo, object::tree::diff::tree_named};
+
+    #[test]
+    fn baseline() -> crate::Result {
+        let repo = named_repo("make_diff_stats_repo.sh")?;
+        let worktree_path = repo.workdir().expect("non-bare");
+
+        let mut num_baselines = 0;
+        for entry in std::fs::read_dir(worktree_path)? {
+            let file_name = entry?.file_name();
+            let file_name = file_name.to_str().expect("ascii filename");
+            let Some(name) = file_name.strip_suffix(".baseline") else {
+                continue;
+            };
+            let (from, rest) = name.split_once("..").expect("<from>..<to>.<args>");
+            let (to, args) = rest.split_once('.').expect("<to>.<args>");
+
+            let files = tree_named(&repo, from).changes()?.file_stats(&tree_named(&repo, to))?;
+            let actual = render(&files, args)?;
+            let expected: BString = std::fs::read(worktree_path.join(file_name))?.into();
+            assert_eq!(actual, expected, "{file_name}");
+            num_baselines += 1;
+        }
+        assert_eq!(num_baselines, 3 * 13, "all baselines were checked");
+        Ok(())
+    }
+
+    #[test]
+    fn file_stats_of_renames_and_binary_files() -> crate::Result {
+        let repo = named_repo("make_diff_stats_repo.sh")?;
+        let files = tree_named(&repo, "c2")
+            .changes()?
+            .file_stats(&tree_named(&repo, "c3"))?;
+        insta::assert_debug_snapshot!(files, @r#"
+        [
+            File {
+                source_location: Some(
+                    "docs/to-be-moved.md",
+                ),
+                location: "docs/guide/moved.md",
+                counts: Lines {
+                    insertions: 0,
+                    removals: 0,
+                },
+            },
+            File {
+                source_location: Some(
+                    "assets/other.bin",
+                ),
+                location: "other.bin",
+                counts: Binary {
+                    size_before: 0,
+                    size_after: 0,
+                },
+            },
+            File {
+                source_location: None,
+                location: "src/core/nested/mod.rs",
+                counts: Lines {
+                    insertions: 3,
+                    removals: 17,
+                },
+            },
+            File {
+                source_location: Some(
+                    "src/util/similar.rs",
+                ),
+                location: "src/core/similar.rs",
+                counts: Lines {
+                    insertions: 1,
+                    removals: 0,
+                },
+            },
+            File {
+                source_location: Some(
+                    "src/util/helpers.rs",
+                ),
+                location: "src/util/helper.rs",
+                counts: Lines {
+                    insertions: 0,
+                    removals: 0,
+                },
+            },
+        ]
+        "#);
+        Ok(())
+    }
+
+    /// Render `files` like `git diff --<args>` would.
+    fn render(files: &[File], args: &str) -> std::io::Result<BString> {
+        let mut out = Vec::new();
+        let (name, values) = args.split_once('=').unwrap_or((args, ""));
+        let values = values.split(',').filter(|value| !value.is_empty());
+        match name {
+            "numstat" => gix_diff::stats::numstat(files, &mut out)?,
+            "shortstat" => out.extend_from_slice(format!("{}\n", Summary::from_files(files)).as_bytes()),
+            "stat" | "stat-graph-width" => {
+                let mut values = values.map(|value| value.parse::<usize>().expect("number"));
+                let mut options = gix_diff::stats::stat::Options::default();
+                if name == "stat" {
+                    options.width = values.next().unwrap_or(options.width);
+                    options.name_width = values.next();
+                    options.count = values.next();
+                } else {
+                    options.graph_width = values.next();
+                }
+                gix_diff::stats::stat(files, &options, &mut out)?;
+            }
+            "dirstat" => {
+                let mut options = dirstat::Options::default();
+                for value in values {
+                    match value {
+                        "lines" => options.mode = dirstat::Mode::Lines,
+                        "files" => options.mode = dirstat::Mode::Files,
+                        "cumulative" => options.cumulative = true,
+                        percent => options.limit_permille = percent.parse::<u32>().expect("percentage") * 10,
+                    }
+                }
+                for dir in gix_diff::stats::dirstat(files, &options) {
+                    out.extend_from_slice(format!("{dir}\n").as_bytes());
+                }
+            }
+            other => unreachable!("BUG: '{other}' is not a supported statistic"),
+        }
+        Ok(out.into())
+    }
+}
+
+mod combined {
+    use std::{convert::Infallible, io::Write};
+
+    use gix::{
+        bstr::BString,
+        diff::combined::{Change, Content},
+    };
+    use gix_diff::blob::combined::Options;
+    use pretty_assertions::assert_eq;
+
+    use crate::named_repo;
+
+    #[test]
+    fn baseline() -> crate::Result {
+        let repo = named_repo("make_diff_combined_repo.sh")?;
+        let worktree_path = repo.workdir().expect("non-bare");
+
+        let mut num_baselines = 0;
+        for entry in std::fs::read_dir(worktree_path)? {
+            let file_name = entry?.file_name();
+            let file_name = file_name.to_str().expect("ascii filename");
+            let Some(name) = file_name.strip_suffix(".baseline") else {
+                continue;
+            };
+            let (merge, mode) = name.split_once('.').expect("<merge>.<mode>");
+            let dense = match mode {
+                "cc" => true,
+                "c" => false,
+                other => unreachable!("BUG: '{other}' is not a supported mode"),
+            };
+
+            let (tree, parent_trees) = trees_of_merge(&repo, merge)?;
+            let mut actual = Vec::new();
+            repo.diff_tree_to_parents(
+                &parent_trees,
+                &tree,
+                Options {
+                    dense,
+                    ..Default::default()
+                },
+                |change| {
+                    render(&change, dense, &mut actual)?;
+                    Ok::<_, std::io::Error>(std::ops::ControlFlow::Continue(()))
+                },
+            )?;
+            let expected: BString = std::fs::read(worktree_path.join(file_name))?.into();
+            assert_eq!(BString::from(actual), expected, "{file_name}");
+            num_baselines += 1;
+        }
+        assert_eq!(num_baselines, 2 * 2, "all baselines were checked");
+        Ok(())
+    }
+
+    #[test]
+    fn files_that_only_changed_their_mode_have_no_hunks() -> crate::Result {
+        let repo = named_repo("make_diff_combined_repo.sh")?;
+        let (tree, parent_trees) = trees_of_merge(&repo, "merge")?;
+
+        let mut changes = Vec::new();
+        repo.diff_tree_to_parents(&parent_trees, &tree, Options::default(), |change| {
+            changes.push((
+                change.location.to_owned(),
+                change.mode_differs(),
+                match change.content {
+                    Content::Hunks(hunks) => Some(hunks.len()),
+                    Content::Binary => None,
+                },
+            ));
+            Ok::<_, Infallible>(std::ops::ControlFlow::Continue(()))
+        })?;
+        assert_eq!(
+            changes,
+            [
+                ("added.txt".into(), false, Some(1)),
+                ("deleted.txt".into(), true, Some(1)),
+                ("evil.txt".into(), false, Some(1)),
+                ("image.bin".into(), false, None),
+                ("new.txt".into(), true, Some(1)),
+                ("resolved.txt".into(), false, Some(1)),
+                ("same.txt".into(), true, Some(0)),
+                ("submodule".into(), false, Some(1)),
+            ]
+        );
+        Ok(())
+    }
+
+    fn trees_of_merge<'repo>(
+        repo: &'repo gix::Repository,
+        merge: &str,
+    ) -> crate::Result<(gix::Tree<'repo>, Vec<gix::Tree<'repo>>)> {
+        let commit = repo.rev_parse_single(merge)?.object()?.into_commit();
+        let parent_trees = commit
+            .parent_ids()
+            .map(|id| id.object()?.into_commit().tree())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((commit.tree()?, parent_trees))
+    }
+
+    /// Render `change` like `git show --full-index` does for merge commits.
+    fn render(change: &Change<'_>, dense: bool, out: &mut Vec<u8>) -> std::io::Result<()> {
+        let null_id = gix_hash::Kind::Sha1.null();
+        writeln!(
+            out,
+            "diff --{} {}",
+            if dense { "cc" } else { "combined" },
+            change.location
+        )?;
+        let parent_ids: Vec<_> = change
+            .parents
+            .iter()
+            .map(|parent| parent.map_or(null_id, |entry| entry.id).to_string())
+            .collect();
+        writeln!(
+            out,
+            "index {}..{}",
+            parent_ids.join(","),
+            change.entry.map_or(null_id, |entry| entry.id)
+        )?;
+
+        let is_added = change.parents.iter().all(Option::is_none);
+        if change.mode_differs() {
+            let parent_modes: Vec<_> = change
+                .parents
+                .iter()
+                .map(|parent| format!("{:06o}", parent.map_or(0, |entry| entry.mode.value())))
+                .collect();
+            match change.entry {
+                Some(entry) if is_added => writeln!(out, "new file mode {:06o}", entry.mode.value())?,
+                Some(entry) => writeln!(out, "mode {}..{:06o}", parent_modes.join(","), entry.mode.value())?,
+                None => writeln!(out, "deleted file mode {}", parent_modes.join(","))?,
+            }
+        }
+        match change.content {
+            Content::Binary => writeln!(out, "Binary files differ")?,
+            Content::Hunks(hunks) => {
+                if is_added {
+                    writeln!(out, "--- /dev/null")?;
+                } else {
+                    writeln!(out, "--- a/{}", change.location)?;
+                }
+                match change.entry {
+                    Some(_) => writeln!(out, "+++ b/{}", change.location)?,
+                    None => writeln!(out, "+++ /dev/null")?,
+                }
+                for hunk in hunks {
+                    hunk.write_to(&mut *out, false)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 fn tree_named(repo: &gix::Repository, rev_spec: impl AsRef<str>) -> gix::Tree<'_> {
     repo.rev_parse_single(rev_spec.as_ref())
         .unwrap()
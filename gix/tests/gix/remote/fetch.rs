@@ -297,6 +297,61 @@ mod blocking_and_async_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_shallow_backfills_included_tags_without_deepening() -> crate::Result {
+        let remote_dir = TempDir::new()?;
+        let remote_repo = init_repo(remote_dir.path())?;
+        let empty_tree = gix::hash::ObjectId::empty_tree(remote_repo.object_hash());
+        let first = commit_empty(&remote_repo, "first")?;
+        let second = remote_repo.commit("HEAD", "second", empty_tree, [first])?.detach();
+
+        let tmp = TempDir::new()?;
+        let (repo, _out) = gix::prepare_clone_bare(remote_dir.path(), tmp.path())?
+            .with_shallow(fetch::Shallow::DepthAtRemote(1.try_into()?))
+            .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+        assert_eq!(shallow_ids(&repo, "shallow")?, [second]);
+
+        let tag = remote_repo
+            .tag(
+                "v1",
+                second,
+                gix::object::Kind::Commit,
+                Some(remote_repo.committer().expect("present")?),
+                "the tag points to a commit the clone already has",
+                gix::refs::transaction::PreviousValue::MustNotExist,
+            )?
+            .id()
+            .detach();
+        let third = remote_repo.commit("HEAD", "third", empty_tree, [second])?.detach();
+
+        let outcome = repo
+            .find_remote("origin")?
+            .connect(Fetch)?
+            .prepare_fetch(gix::progress::Discard, Default::default())?
+            .with_shallow(fetch::Shallow::DepthAtRemote(1.try_into()?))
+            .receive(gix::progress::Discard, &AtomicBool::default())?;
+        assert!(matches!(outcome.status, Status::Change { .. }));
+        assert_eq!(
+            repo.find_reference("refs/tags/v1")?.id(),
+            tag,
+            "the remote only includes tags pointing to objects in the pack, so the tag is fetched separately"
+        );
+        assert!(repo.find_object(tag).is_ok(), "the tag object was received");
+        let mut expected_shallow = [second, third];
+        expected_shallow.sort();
+        assert_eq!(
+            shallow_ids(&repo, "shallow")?,
+            expected_shallow,
+            "only the shallow commit of the main fetch is added, fetching the tag doesn't change the shallow boundary"
+        );
+        assert!(
+            repo.find_object(first).is_err(),
+            "history wasn't deepened while fetching the tag"
+        );
+        Ok(())
+    }
+
     #[bisync::bisync]
     #[cfg_attr(feature = "blocking-network-client", test)]
     #[cfg_attr(feature = "async-network-client-async-std", async_std::test)]
@@ -531,11 +586,6 @@ mod blocking_and_async_io {
             gix::protocol::transport::Protocol::V2,
         ] {
             for (shallow_args, expected) in [(None, [1, 7, 7]), (Some("--depth=2"), [1, 2, 2])] {
-                if version == gix::protocol::transport::Protocol::V1 && shallow_args.is_some() {
-                    // TODO: We cannot yet handle shallow mode for V1 as it will send shallow-info as part of the handshake :/.
-                    //       It's probably not the most important thing to be able to clone from a shallow remote anyway.
-                    continue;
-                }
                 for (fetch_tags, expected_ref_count) in [fetch::Tags::None, fetch::Tags::Included, fetch::Tags::All]
                     .into_iter()
                     .zip(expected)
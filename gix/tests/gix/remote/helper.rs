@@ -0,0 +1,81 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::remote::Direction::Fetch;
+use serial_test::serial;
+
+fn fixture() -> crate::Result<(gix_testtools::tempfile::TempDir, gix_testtools::Env<'static>)> {
+    let dir = gix_testtools::scripted_fixture_writable("make_remote_helper_repos.sh")?;
+    let path = std::env::join_paths(
+        std::iter::once(dir.path().join("bin"))
+            .chain(std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default())),
+    )?;
+    let env = gix_testtools::Env::new().set("PATH", path.into_string().expect("valid UTF-8"));
+    Ok((dir, env))
+}
+
+fn fetch_via(transport: &str) -> crate::Result<(gix::Repository, gix::ObjectId, gix_testtools::tempfile::TempDir)> {
+    let (dir, _env) = fixture()?;
+    let base = gix::open_opts(dir.path().join("base"), gix::open::Options::isolated())?;
+    let repo = gix::open_opts(dir.path().join("local"), crate::restricted())?;
+
+    let url = format!("{transport}::{}", base.git_dir().display());
+    let remote = repo
+        .remote_at(url.as_str())?
+        .with_refspecs(Some("+refs/heads/*:refs/remotes/origin/*"), Fetch)?;
+    remote
+        .connect(Fetch)?
+        .prepare_fetch(gix::progress::Discard, Default::default())?
+        .receive(gix::progress::Discard, &AtomicBool::default())?;
+
+    let expected = base.rev_parse_single("main")?.detach();
+    let actual = repo.find_reference("refs/remotes/origin/main")?.peel_to_id()?.detach();
+    assert_eq!(actual, expected, "{transport}: the remote branch was fetched");
+    assert!(
+        repo.find_commit(actual)?
+            .tree()?
+            .lookup_entry_by_path("dir/a")?
+            .is_some(),
+        "{transport}: all objects are available"
+    );
+    Ok((repo, expected, dir))
+}
+
+#[test]
+#[serial]
+fn fetch_through_connecting_helper() -> crate::Result {
+    fetch_via("relay")?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fetch_through_fetching_helper() -> crate::Result {
+    fetch_via("fetcher")?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn fetch_through_importing_helper() -> crate::Result {
+    let (repo, expected, _dir) = fetch_via("exporter")?;
+    assert_eq!(
+        repo.find_reference("refs/exporter/heads/main")?.peel_to_id()?,
+        expected,
+        "the private ref of the helper is written by the importer as well"
+    );
+    Ok(())
+}
+
+#[test]
+fn ext_transport_is_denied_by_default() -> crate::Result {
+    let repo = crate::remote::repo("base");
+    let remote = repo.remote_at("ext::git %S foo")?;
+    assert!(matches!(
+        remote.connect(Fetch).err(),
+        Some(gix::remote::connect::Error::ProtocolDenied {
+            url: _,
+            scheme: gix::url::Scheme::Ext(name)
+        }) if name == "ext"
+    ));
+    Ok(())
+}
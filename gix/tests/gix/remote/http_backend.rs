@@ -0,0 +1,301 @@
+//! Fetch from `git http-backend` to exercise the stateless negotiation of protocol V0 and V1 against `git` itself.
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    process::{Command, Stdio},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix::{
+    config::tree::Protocol,
+    remote::{Direction::Fetch, fetch, fetch::Status},
+};
+use gix_testtools::{git, tempfile::TempDir};
+
+/// Serve a single HTTP/1.1 request by passing it to `git http-backend` as CGI, with all repositories below `root` exported.
+fn serve_with_http_backend(root: &Path, stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut fields = request_line.split_whitespace();
+    let (method, target) = (fields.next().unwrap_or_default(), fields.next().unwrap_or_default());
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.to_ascii_lowercase(), value.trim().to_owned()));
+        }
+    }
+    let header = |name: &str| headers.iter().find(|(n, _)| n == name).map(|(_, value)| value.as_str());
+    let mut body = Vec::new();
+    if header("transfer-encoding") == Some("chunked") {
+        loop {
+            let mut size = String::new();
+            reader.read_line(&mut size)?;
+            let size = usize::from_str_radix(size.trim(), 16).expect("valid chunk size");
+            let mut chunk = vec![0; size + 2 /* CRLF */];
+            reader.read_exact(&mut chunk)?;
+            if size == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(len) = header("content-length") {
+        body.resize(len.parse().expect("valid length"), 0);
+        reader.read_exact(&mut body)?;
+    }
+
+    let mut cmd = Command::new(gix::path::env::exe_invocation());
+    cmd.arg("http-backend")
+        .env("GIT_PROJECT_ROOT", root)
+        .env("GIT_HTTP_EXPORT_ALL", "1")
+        .env("REQUEST_METHOD", method)
+        .env("PATH_INFO", path)
+        .env("QUERY_STRING", query)
+        .env("CONTENT_LENGTH", body.len().to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    for (name, var) in [
+        ("content-type", "CONTENT_TYPE"),
+        ("content-encoding", "HTTP_CONTENT_ENCODING"),
+        ("git-protocol", "GIT_PROTOCOL"),
+    ] {
+        if let Some(value) = header(name) {
+            cmd.env(var, value);
+        }
+    }
+    let mut child = cmd.spawn()?;
+    child.stdin.take().expect("configured").write_all(&body)?;
+    let output = child.wait_with_output()?;
+
+    let separator = output
+        .stdout
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|pos| (pos, 4))
+        .or_else(|| output.stdout.windows(2).position(|w| w == b"\n\n").map(|pos| (pos, 2)))
+        .expect("CGI output has headers");
+    let cgi_headers = std::str::from_utf8(&output.stdout[..separator.0]).expect("headers are ASCII");
+    let response_body = &output.stdout[separator.0 + separator.1..];
+
+    let mut out = &stream;
+    let status = cgi_headers
+        .lines()
+        .find_map(|line| line.strip_prefix("Status: "))
+        .unwrap_or("200 OK");
+    write!(out, "HTTP/1.1 {status}\r\n")?;
+    for line in cgi_headers.lines().filter(|line| !line.starts_with("Status: ")) {
+        write!(out, "{line}\r\n")?;
+    }
+    write!(
+        out,
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response_body.len()
+    )?;
+    out.write_all(response_body)?;
+    out.flush()
+}
+
+/// Run `f` with the base URL of an HTTP server which serves all repositories below `root` with `git http-backend`.
+fn with_http_backend(root: &Path, f: impl FnOnce(&str) -> crate::Result) -> crate::Result {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?.to_string();
+    let should_interrupt = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let server = scope.spawn(|| -> std::io::Result<()> {
+            loop {
+                let (stream, _) = listener.accept()?;
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+                serve_with_http_backend(root, stream)?;
+            }
+        });
+        let stop = StopOnDrop {
+            should_interrupt: &should_interrupt,
+            address: &address,
+        };
+        let res = f(&format!("http://{address}"));
+        drop(stop);
+        server.join().expect("no panic")?;
+        res
+    })
+}
+
+/// Wake up the server so it stops, even if the test panics.
+struct StopOnDrop<'a> {
+    should_interrupt: &'a AtomicBool,
+    address: &'a str,
+}
+
+impl Drop for StopOnDrop<'_> {
+    fn drop(&mut self) {
+        self.should_interrupt.store(true, Ordering::Relaxed);
+        TcpStream::connect(self.address).ok();
+    }
+}
+
+fn commits(repo: &Path, prefix: &str, count: usize) -> crate::Result {
+    for idx in 0..count {
+        git(repo, &format!("commit --allow-empty -m '{prefix} {idx}'"))?;
+    }
+    Ok(())
+}
+
+fn shallow_file(repo: &Path) -> crate::Result<String> {
+    Ok(std::fs::read_to_string(repo.join(".git").join("shallow"))?)
+}
+
+/// Return the shallow file that `git` creates when performing the same operations as we do, for comparison.
+fn shallow_file_from_git(root: &Path, url: &str, version: u8, deepen_by: u32) -> crate::Result<String> {
+    git(
+        root,
+        &format!("-c protocol.version={version} clone --depth 2 {url}/server/.git git-client"),
+    )?;
+    let client = root.join("git-client");
+    git(&client, "checkout -b local")?;
+    commits(&client, "local", 50)?;
+    git(root.join("server"), "commit --allow-empty -m 'new on server'")?;
+    git(
+        &client,
+        &format!("-c protocol.version={version} fetch --deepen={deepen_by} origin main"),
+    )?;
+    git(&client, "fsck --strict --no-dangling")?;
+    git(root.join("server"), "reset --hard HEAD~1")?;
+    shallow_file(&client)
+}
+
+#[test]
+fn shallow_fetches_with_many_stateless_negotiation_rounds() -> crate::Result {
+    for version in [
+        gix::protocol::transport::Protocol::V0,
+        gix::protocol::transport::Protocol::V1,
+    ] {
+        let version = version as u8;
+        let root = TempDir::new()?;
+        let server = root.path().join("server");
+        git(root.path(), "init -b main server")?;
+        commits(&server, "initial", 10)?;
+
+        with_http_backend(root.path(), |url| {
+            let expected_shallow = shallow_file_from_git(root.path(), url, version, 3)?;
+
+            let client = root.path().join("client");
+            let (_repo, _outcome) = gix::clone::PrepareFetch::new(
+                format!("{url}/server/.git").as_str(),
+                &client,
+                gix::create::Kind::WithWorktree,
+                Default::default(),
+                crate::restricted(),
+            )?
+            .with_in_memory_config_overrides([format!("protocol.version={version}")])
+            .with_shallow(fetch::Shallow::DepthAtRemote(2.try_into().expect("non-zero")))
+            .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+            git(&client, "checkout -b local origin/main")?;
+            commits(&client, "local", 50)?;
+            // Without a ref pointing to the common history, all local commits have to be sent as `have` first.
+            git(&client, "branch -D main")?;
+            git(&client, "update-ref -d refs/remotes/origin/main")?;
+            git(&server, "commit --allow-empty -m 'new on server'")?;
+
+            let mut repo = gix::open_opts(&client, crate::restricted())?;
+            repo.config_snapshot_mut()
+                .set_raw_value(Protocol::VERSION, version.to_string().as_str())?;
+            let outcome = repo
+                .find_remote("origin")?
+                .with_refspecs(Some("refs/heads/main:refs/remotes/origin/main"), Fetch)?
+                .connect(Fetch)?
+                .prepare_fetch(gix::progress::Discard, Default::default())?
+                .with_shallow(fetch::Shallow::Deepen(3))
+                .receive(gix::progress::Discard, &AtomicBool::default())?;
+            let Status::Change { negotiate, .. } = outcome.status else {
+                unreachable!("a pack is received")
+            };
+            assert!(
+                negotiate.rounds.len() > 1,
+                "v{version}: the local commits are unknown to the server and need multiple rounds"
+            );
+            git(&client, "fsck --strict --no-dangling")?;
+            assert_eq!(
+                shallow_file(&client)?,
+                expected_shallow,
+                "v{version}: the shallow boundary is the same as the one of `git`"
+            );
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+#[test]
+fn clone_from_shallow_remote_keeps_only_received_shallow_commits() -> crate::Result {
+    for version in [
+        gix::protocol::transport::Protocol::V0,
+        gix::protocol::transport::Protocol::V1,
+    ] {
+        let version = version as u8;
+        let root = TempDir::new()?;
+        let origin = root.path().join("origin");
+        git(root.path(), "init -b main origin")?;
+        commits(&origin, "main", 3)?;
+        git(&origin, "checkout --orphan other")?;
+        commits(&origin, "other", 3)?;
+        git(&origin, "checkout main")?;
+        git(
+            root.path(),
+            &format!("clone --depth 1 --no-single-branch file://{} server", origin.display()),
+        )?;
+        assert_eq!(
+            shallow_file(&root.path().join("server"))?.lines().count(),
+            2,
+            "both unrelated branches have their own shallow boundary"
+        );
+
+        with_http_backend(root.path(), |url| {
+            git(
+                root.path(),
+                &format!(
+                    "-c protocol.version={version} clone --single-branch --branch main {url}/server/.git git-client"
+                ),
+            )?;
+            let expected_shallow = shallow_file(&root.path().join("git-client"))?;
+            assert_eq!(
+                expected_shallow.lines().count(),
+                1,
+                "git prunes the shallow commit it didn't receive"
+            );
+
+            let client = root.path().join("client");
+            let (repo, _outcome) = gix::clone::PrepareFetch::new(
+                format!("{url}/server/.git").as_str(),
+                &client,
+                gix::create::Kind::WithWorktree,
+                Default::default(),
+                crate::restricted(),
+            )?
+            .with_in_memory_config_overrides([format!("protocol.version={version}")])
+            .configure_remote(|mut r| {
+                r.replace_refspecs(Some("refs/heads/main:refs/remotes/origin/main"), Fetch)?;
+                Ok(r)
+            })
+            .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+            assert!(repo.is_shallow());
+            assert_eq!(
+                shallow_file(&client)?,
+                expected_shallow,
+                "v{version}: only the shallow commit that was received is kept"
+            );
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
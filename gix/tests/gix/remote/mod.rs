@@ -66,6 +66,16 @@ pub(crate) fn into_daemon_remote_if_async<'repo, 'a>(
 
 mod connect;
 pub(crate) mod fetch;
+#[cfg(all(unix, feature = "blocking-network-client"))]
+mod helper;
+#[cfg(all(
+    unix,
+    any(
+        feature = "blocking-http-transport-curl",
+        feature = "blocking-http-transport-reqwest"
+    )
+))]
+mod http_backend;
 mod ref_map;
 mod save;
 mod name {
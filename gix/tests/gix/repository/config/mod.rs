@@ -32,6 +32,16 @@ mod ssh_options {
             Some(gix::protocol::transport::client::blocking_io::ssh::ProgramKind::Ssh)
         );
         assert!(!opts.disallow_shell, "we can use the shell by default");
+        assert!(!opts.native, "programs are invoked by default");
+        Ok(())
+    }
+
+    #[test]
+    fn native() -> crate::Result {
+        let mut repo = repo("ssh-all-options");
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::gitoxide::Ssh::NATIVE, "true")?;
+        assert!(repo.ssh_connect_options()?.native);
         Ok(())
     }
 
@@ -112,6 +112,45 @@ mod http {
         assert_eq!(http_version, Some(HttpVersion::V1_1));
     }
 
+    #[test]
+    fn url_specific_configuration() -> crate::Result {
+        let mut repo = repo("http-config");
+        {
+            let mut config = repo.config_snapshot_mut();
+            config.set_raw_value("http.https://*.internal.example.proxy", "wildcard-proxy")?;
+            config.set_raw_value("http.https://git.internal.example.proxy", "host-proxy")?;
+            config.set_raw_value("http.https://git.internal.example/team.sslVerify", "false")?;
+            config.set_raw_value(
+                "http.https://git.internal.example/team/.extraHeader",
+                "ExtraHeader: team",
+            )?;
+        }
+
+        let opts = http_options(&repo, None, "https://example.com/does/not/matter");
+        assert_eq!(opts.proxy.as_deref(), Some("http://localhost:9090"));
+        assert!(opts.ssl_verify);
+
+        let opts = http_options(&repo, None, "https://other.internal.example/repo");
+        assert_eq!(opts.proxy.as_deref(), Some("http://wildcard-proxy"));
+
+        let opts = http_options(&repo, None, "https://git.internal.example/team/repo");
+        assert_eq!(
+            opts.proxy.as_deref(),
+            Some("http://host-proxy"),
+            "the longest host wins"
+        );
+        assert!(!opts.ssl_verify, "paths are matched by prefix");
+        assert_eq!(
+            opts.extra_headers,
+            &["ExtraHeader: value2", "ExtraHeader: value3", "ExtraHeader: team"],
+            "multi-values accumulate"
+        );
+
+        let opts = http_options(&repo, None, "https://git.internal.example/teams/repo");
+        assert!(opts.ssl_verify, "path prefixes only match whole components");
+        Ok(())
+    }
+
     #[test]
     fn http_ssl_cainfo_suppressed_by_() {
         let repo = repo("http-disabled-cainfo");
@@ -1,4 +1,4 @@
-use crate::util::named_repo;
+use crate::util::{named_repo, named_subrepo_opts};
 
 #[test]
 fn tree_merge_options() -> crate::Result {
@@ -11,3 +11,100 @@ fn tree_merge_options() -> crate::Result {
     );
     Ok(())
 }
+
+#[test]
+fn merge_commits_octopus() -> crate::Result {
+    let repo = named_repo("make_merge_strategies_repo.sh")?;
+    let id = |spec: &str| repo.rev_parse_single(spec).map(gix::Id::detach);
+    let heads = [id("topic1")?, id("main~1")?, id("topic2")?];
+
+    let mut out = repo.merge_commits_octopus(
+        id("main")?,
+        heads,
+        Default::default(),
+        repo.tree_merge_options()?.into(),
+    )?;
+    assert_eq!(out.tree_merge.tree.write()?, id("octopus^{tree}")?, "it matches Git");
+    assert_eq!(out.merged_commits, [heads[0], heads[2]]);
+    assert_eq!(out.up_to_date_commits, [heads[1]], "ancestors don't need to be merged");
+    assert!(out.unmerged_commits.is_empty());
+
+    let options =
+        gix::merge::commit::Options::from(repo.tree_merge_options()?).with_strategy(gix::merge::commit::Strategy::Ours);
+    let mut out = repo.merge_commits_octopus(id("main")?, heads, Default::default(), options)?;
+    assert_eq!(
+        out.tree_merge.tree.write()?,
+        id("main^{tree}")?,
+        "the 'ours' strategy keeps our tree"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge_commits_with_renormalize() -> crate::Result {
+    let repo = named_repo("make_merge_strategies_repo.sh")?;
+    let id = |spec: &str| repo.rev_parse_single(spec).map(gix::Id::detach);
+    let options = gix::merge::commit::Options::from(repo.tree_merge_options()?);
+
+    let out = repo.merge_commits(id("main")?, id("theirs")?, Default::default(), options.clone())?;
+    assert!(
+        out.tree_merge
+            .has_unresolved_conflicts(gix::merge::tree::TreatAsUnresolved::default()),
+        "without renormalization, each line conflicts as their line endings differ"
+    );
+
+    let mut out = repo.merge_commits(
+        id("main")?,
+        id("theirs")?,
+        Default::default(),
+        options.with_renormalize(Some(true)),
+    )?;
+    assert_eq!(
+        out.tree_merge.tree.write()?,
+        id("renormalized^{tree}")?,
+        "it matches Git"
+    );
+    Ok(())
+}
+
+#[test]
+fn merge_commits_with_submodules() -> crate::Result {
+    let repo = named_subrepo_opts(
+        "make_merge_submodule_repo.sh",
+        "superproject",
+        gix::open::Options::isolated(),
+    )?;
+    let id = |spec: &str| repo.rev_parse_single(spec).map(gix::Id::detach);
+    let options = gix::merge::commit::Options::from(repo.tree_merge_options()?);
+
+    let mut out = repo.merge_commits(id("a1")?, id("a2")?, Default::default(), options.clone())?;
+    assert_eq!(
+        out.tree_merge.tree.write()?,
+        id("fast-forward^{tree}")?,
+        "the submodule is fast-forwarded, like Git does"
+    );
+    assert!(
+        !out.tree_merge
+            .has_unresolved_conflicts(gix::merge::tree::TreatAsUnresolved::default()),
+        "a fast-forward isn't a conflict"
+    );
+
+    let out = repo.merge_commits(id("a1")?, id("b1")?, Default::default(), options)?;
+    assert!(
+        out.tree_merge
+            .has_unresolved_conflicts(gix::merge::tree::TreatAsUnresolved::default()),
+        "diverged submodules can't be merged automatically"
+    );
+    let sub = repo.submodules()?.expect("configured").next().expect("one submodule");
+    let sub = sub.open()?.expect("checked out");
+    assert!(
+        matches!(
+            &out.tree_merge.conflicts[0].resolution,
+            Err(gix::merge::tree::ResolutionFailure::OursModifiedTheirsModifiedSubmodule {
+                reason: gix::merge::tree::submodule::Failure::Diverged { possible_merges }
+            }) if *possible_merges == [sub.rev_parse_single("m")?.detach()]
+        ),
+        "the merge commit in the submodule that contains both sides is suggested"
+    );
+    Ok(())
+}
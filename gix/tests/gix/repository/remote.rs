@@ -459,7 +459,7 @@ mod find_remote {
         let mut remote = repo.try_find_remote_without_url_rewrite("origin").expect("exists")?;
         assert_eq!(
             remote.rewrite_urls().unwrap_err().to_string(),
-            "The rewritten fetch url \"invalid:://gitoxide\" failed to parse",
+            "The rewritten fetch url \"in:valid://gitoxide\" failed to parse",
             "one malformed rewrite is reported"
         );
         assert_eq!(
@@ -530,7 +530,7 @@ mod find_remote {
         let mut remote = repo.try_find_remote_without_url_rewrite("origin").expect("exists")?;
         assert_eq!(
             remote.rewrite_urls().unwrap_err().to_string(),
-            "The rewritten push url \"invalid:://repo\" failed to parse",
+            "The rewritten push url \"in:valid://repo\" failed to parse",
             "explicit rewriting still reports the malformed push fallback rewrite"
         );
         assert_eq!(
@@ -546,7 +546,7 @@ mod find_remote {
     fn bad_explicit_push_url_rewriting_is_reported_as_push_url() -> crate::Result {
         let repo = remote::repo("bad-explicit-push-url-rewriting");
 
-        let expected_err_msg = "The rewritten push url \"invalid:://repo\" failed to parse";
+        let expected_err_msg = "The rewritten push url \"in:valid://repo\" failed to parse";
         assert_eq!(
             repo.find_remote("origin").unwrap_err().to_string(),
             expected_err_msg,
@@ -564,6 +564,75 @@ mod find_remote {
         Ok(())
     }
 
+    #[test]
+    fn legacy_remotes_and_branches_files() -> crate::Result {
+        let (repo, _tmp) = super::repo_with_files(
+            "[remote \"mixed\"]\n\tfetch = +refs/heads/*:refs/remotes/mixed/*\n[init]\n\tdefaultBranch = trunk\n",
+            &[
+                (
+                    "remotes/old",
+                    "URL: https://example.com/old\nPull: refs/heads/main:refs/heads/old\nPush:  refs/heads/main  \nunknown: ignored\n",
+                ),
+                (
+                    "remotes/mixed",
+                    "URL: https://example.com/mixed\nPull: refs/heads/b:refs/heads/b\nPull: +refs/heads/*:refs/remotes/mixed/*\nPull: refs/heads/a:refs/heads/a\n",
+                ),
+                ("branches/ancient", "https://example.com/ancient#topic\n"),
+                ("branches/plain", "https://example.com/plain\n"),
+                ("branches/old", "https://example.com/shadowed\n"),
+            ],
+        )?;
+
+        let remote = repo.find_remote("old")?;
+        assert_eq!(urls(&remote, Direction::Fetch), ["https://example.com/old"]);
+        assert_eq!(
+            remote.refspecs(Direction::Fetch),
+            [fetchspec("refs/heads/main:refs/heads/old")]
+        );
+        assert_eq!(
+            remote.refspecs(Direction::Push),
+            [pushspec("refs/heads/main")],
+            "the `remotes` file wins over the `branches` file"
+        );
+
+        let remote = repo.find_remote("ancient")?;
+        assert_eq!(urls(&remote, Direction::Fetch), ["https://example.com/ancient"]);
+        assert_eq!(
+            remote.refspecs(Direction::Fetch),
+            [fetchspec("refs/heads/topic:refs/heads/ancient")]
+        );
+        assert_eq!(remote.refspecs(Direction::Push), [pushspec("HEAD:refs/heads/topic")]);
+
+        let remote = repo.find_remote("plain")?;
+        assert_eq!(
+            remote.refspecs(Direction::Fetch),
+            [fetchspec("refs/heads/trunk:refs/heads/plain")],
+            "without branch, `init.defaultBranch` is used"
+        );
+
+        let remote = repo.find_remote("mixed")?;
+        assert_eq!(
+            urls(&remote, Direction::Fetch),
+            ["https://example.com/mixed"],
+            "a configured remote without url can obtain it from the legacy files"
+        );
+        assert_eq!(
+            remote.refspecs(Direction::Fetch),
+            [
+                fetchspec("+refs/heads/*:refs/remotes/mixed/*"),
+                fetchspec("refs/heads/b:refs/heads/b"),
+                fetchspec("refs/heads/a:refs/heads/a")
+            ],
+            "legacy refspecs are added in order, without duplicates"
+        );
+
+        assert!(matches!(
+            repo.find_remote("../remotes/old").unwrap_err(),
+            gix::remote::find::existing::Error::NotFound { .. }
+        ));
+        Ok(())
+    }
+
     fn urls(remote: &gix::Remote<'_>, direction: Direction) -> Vec<BString> {
         remote.urls(direction).map(gix::Url::to_bstring).collect()
     }
@@ -651,4 +720,150 @@ mod find_default_remote {
         );
         Ok(())
     }
+
+    #[test]
+    fn falls_back_to_legacy_origin() -> crate::Result {
+        let (repo, _tmp) = super::repo_with_files("", &[("branches/origin", "https://example.com/origin\n")])?;
+        assert_eq!(
+            repo.find_default_remote(gix::remote::Direction::Fetch)
+                .transpose()?
+                .expect("present")
+                .name()
+                .expect("always named")
+                .as_bstr(),
+            "origin"
+        );
+        Ok(())
+    }
+}
+
+mod remote_group {
+    use gix::bstr::BString;
+
+    const CONFIG: &str = r#"[remote "a"]
+	url = https://example.com/a
+[remote "b"]
+	url = https://example.com/b
+	skipDefaultUpdate = true
+[remote "c"]
+	url = https://example.com/c
+[remotes]
+	pair = c a
+	pair = \ta b
+	missing = a nope
+"#;
+
+    fn names(remotes: Vec<gix::Remote<'_>>) -> Vec<BString> {
+        remotes
+            .iter()
+            .map(|remote| remote.name().expect("named").as_bstr().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn configured_groups_and_single_remotes() -> crate::Result {
+        let (repo, _tmp) = super::repo_with_files(CONFIG, &[("remotes/legacy", "URL: https://example.com/legacy\n")])?;
+        assert_eq!(
+            names(repo.remote_group("pair")?),
+            ["c", "a", "b"],
+            "members are kept in order of appearance, without duplicates"
+        );
+        assert_eq!(names(repo.remote_group("b")?), ["b"], "a remote is its own group");
+        assert_eq!(names(repo.remote_group("legacy")?), ["legacy"]);
+
+        assert!(matches!(
+            repo.remote_group("unknown").unwrap_err(),
+            gix::remote::group::Error::NotFound { .. }
+        ));
+        assert!(matches!(
+            repo.remote_group("missing").unwrap_err(),
+            gix::remote::group::Error::FindExisting(gix::remote::find::existing::Error::NotFound { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn default_group() -> crate::Result {
+        let (repo, _tmp) = super::repo_with_files(CONFIG, &[])?;
+        assert_eq!(
+            names(repo.remote_group("default")?),
+            ["a", "c"],
+            "all remotes but the ones to skip"
+        );
+
+        let (repo, _tmp) = super::repo_with_files(&format!("{CONFIG}\tdefault = b\n"), &[])?;
+        assert_eq!(
+            names(repo.remote_group("default")?),
+            ["b"],
+            "`remotes.default` takes precedence"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_all_members() -> crate::Result {
+        let source_dir = gix::path::realpath(gix_testtools::scripted_fixture_read_only("make_basic_repo.sh")?)?;
+        let source = gix::path::into_bstr(&source_dir);
+        let (repo, _tmp) = super::repo_with_files(
+            &format!(
+                "[remote \"one\"]\n\turl = {source}\n\tfetch = +refs/heads/*:refs/remotes/one/*\n\
+                 [remote \"two\"]\n\turl = {source}\n\tfetch = +refs/heads/*:refs/remotes/two/*\n\
+                 [remotes]\n\tboth = two one\n"
+            ),
+            &[],
+        )?;
+
+        let options = gix::remote::group::fetch::Options {
+            dry_run: true,
+            ..Default::default()
+        };
+        let fetched = repo.fetch_remote_group("both", gix::progress::Discard, &Default::default(), options)?;
+        assert_eq!(
+            names(fetched.into_iter().map(|(remote, _outcome)| remote).collect()),
+            ["two", "one"]
+        );
+        assert!(
+            repo.try_find_reference("refs/remotes/one/main")?.is_none(),
+            "nothing is written in dry-run mode"
+        );
+
+        let fetched =
+            repo.fetch_remote_group("both", gix::progress::Discard, &Default::default(), Default::default())?;
+        assert_eq!(fetched.len(), 2, "each remote was fetched from");
+        let main_id = gix::open(&source_dir)?.head_id()?.detach();
+        for name in ["refs/remotes/one/main", "refs/remotes/two/main"] {
+            assert_eq!(repo.find_reference(name)?.id(), main_id);
+        }
+
+        assert!(matches!(
+            repo.fetch_remote_group(
+                "unknown",
+                gix::progress::Discard,
+                &Default::default(),
+                Default::default()
+            )
+            .unwrap_err(),
+            gix::remote::group::fetch::Error::Group(gix::remote::group::Error::NotFound { .. })
+        ));
+        Ok(())
+    }
+}
+
+/// Create a repository with `config` appended to its configuration, and with `files` written relative to its `.git` directory.
+fn repo_with_files(
+    config: &str,
+    files: &[(&str, &str)],
+) -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let git_dir = gix::init(tmp.path())?.git_dir().to_owned();
+    let mut config_file = std::fs::OpenOptions::new().append(true).open(git_dir.join("config"))?;
+    std::io::Write::write_all(&mut config_file, config.as_bytes())?;
+    for (path, content) in files {
+        let path = git_dir.join(path);
+        std::fs::create_dir_all(path.parent().expect("in directory"))?;
+        std::fs::write(path, content)?;
+    }
+    let repo = gix::open_opts(tmp.path(), crate::restricted())?;
+    Ok((repo, tmp))
 }
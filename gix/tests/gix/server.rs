@@ -0,0 +1,499 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix::{
+    protocol::server,
+    server::{Directory, receive_pack},
+};
+use gix_testtools::git;
+
+fn directory(base: &Path, export_all: bool) -> Directory {
+    Directory {
+        base: base.to_owned(),
+        export_all,
+        receive_pack: None,
+    }
+}
+
+/// Return a directory that accepts pushes and runs hooks.
+fn pushable(base: &Path) -> Directory {
+    Directory {
+        receive_pack: Some(receive_pack::Options { run_hooks: true }),
+        ..directory(base, false)
+    }
+}
+
+/// Run `f` with the address of a server that accepts connections in the background by passing them to `serve_connection`.
+fn with_server(
+    serve_connection: impl Fn(TcpStream) -> std::io::Result<()> + Sync,
+    f: impl FnOnce(&str) -> crate::Result,
+) -> crate::Result {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?.to_string();
+    let should_interrupt = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let server = scope.spawn(|| -> std::io::Result<()> {
+            loop {
+                let (stream, _) = listener.accept()?;
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+                serve_connection(stream)?;
+            }
+        });
+        let stop = StopOnDrop {
+            should_interrupt: &should_interrupt,
+            address: &address,
+        };
+        let res = f(&address);
+        drop(stop);
+        server.join().expect("no panic")?;
+        res
+    })
+}
+
+/// Wake up the server so it stops, even if the test panics.
+struct StopOnDrop<'a> {
+    should_interrupt: &'a AtomicBool,
+    address: &'a str,
+}
+
+impl Drop for StopOnDrop<'_> {
+    fn drop(&mut self) {
+        self.should_interrupt.store(true, Ordering::Relaxed);
+        TcpStream::connect(self.address).ok();
+    }
+}
+
+fn with_daemon(directory: &Directory, f: impl FnOnce(&str) -> crate::Result) -> crate::Result {
+    with_server(
+        |stream| {
+            let mut input = stream.try_clone()?;
+            server::daemon::serve_connection(directory, &mut input, &mut &stream).ok();
+            Ok(())
+        },
+        |address| f(&format!("git://{address}")),
+    )
+}
+
+/// Serve a single HTTP/1.1 request with `directory`, good enough for `git` over `http://`.
+/// Files in the `bundles` directory below its base are served as well.
+fn serve_http(directory: &Directory, stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut fields = request_line.split_whitespace();
+    let (method, target) = (fields.next().unwrap_or_default(), fields.next().unwrap_or_default());
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target, None),
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.to_ascii_lowercase(), value.trim().to_owned()));
+        }
+    }
+    let header = |name: &str| headers.iter().find(|(n, _)| n == name).map(|(_, value)| value.as_str());
+    assert_ne!(
+        header("transfer-encoding"),
+        Some("chunked"),
+        "requests are expected to be small"
+    );
+    let content_length = header("content-length").map_or(0, |len| len.parse().expect("valid length"));
+
+    let mut out = &stream;
+    if let Some(file) = path.strip_prefix("/bundles/") {
+        // Act like a CDN for bundles and bundle lists.
+        return match std::fs::read(directory.base.join("bundles").join(file)) {
+            Ok(body) => {
+                write!(
+                    out,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )?;
+                out.write_all(&body)?;
+                out.flush()
+            }
+            Err(_) => {
+                write!(
+                    out,
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                )?;
+                out.flush()
+            }
+        };
+    }
+
+    let mut body = Vec::new();
+    let response = server::http::handle(
+        &server::http::Request {
+            method,
+            path: path.into(),
+            query,
+            git_protocol: header("git-protocol"),
+            content_type: header("content-type"),
+            content_encoding: header("content-encoding"),
+        },
+        directory,
+        &mut reader.take(content_length),
+        &mut body,
+    );
+
+    write!(out, "HTTP/1.1 {} Status\r\n", response.status)?;
+    for (name, value) in &response.headers {
+        write!(out, "{name}: {value}\r\n")?;
+    }
+    write!(out, "Content-Length: {}\r\nConnection: close\r\n\r\n", body.len())?;
+    out.write_all(&body)?;
+    out.flush()
+}
+
+fn with_http_server(directory: &Directory, f: impl FnOnce(&str) -> crate::Result) -> crate::Result {
+    with_server(
+        |stream| serve_http(directory, stream),
+        |address| f(&format!("http://{address}")),
+    )
+}
+
+/// Clone `url` with protocol `version`, and after adding a commit to `exported.git` through `base`, fetch it.
+fn clone_and_fetch(root: &Path, url: &str, version: &str) -> crate::Result {
+    let destination = format!("clone-v{version}");
+    git(
+        root,
+        &format!("-c protocol.version={version} clone {url}/exported {destination}"),
+    )?;
+    let clone = root.join(&destination);
+    let base = gix::open(root.join("base"))?;
+    assert_eq!(
+        git(&clone, "rev-parse HEAD")?.trim(),
+        base.head_id()?.to_string(),
+        "HEAD is checked out"
+    );
+    assert_eq!(
+        git(&clone, "rev-parse v1 lightweight origin/other")?,
+        git(root.join("base"), "rev-parse v1 lightweight other")?,
+        "tags are fetched along with all branches"
+    );
+    git(&clone, "fsck --strict --no-dangling")?;
+
+    git(root.join("base"), &format!("commit --allow-empty -m 'for v{version}'"))?;
+    git(root.join("base"), "push ../exported.git main")?;
+    git(&clone, &format!("-c protocol.version={version} fetch"))?;
+    assert_eq!(
+        git(&clone, "rev-parse origin/main")?,
+        git(root.join("base"), "rev-parse main")?,
+        "the new commit was fetched"
+    );
+    git(&clone, "fsck --strict --no-dangling")?;
+    Ok(())
+}
+
+/// Clone `exported.git` via `url`, push a new commit to `main` along with a new branch called `name`, and delete it again.
+fn push(root: &Path, url: &str, name: &str) -> crate::Result {
+    let destination = format!("push-{name}");
+    git(root, &format!("clone {url}/exported.git {destination}"))?;
+    let clone = root.join(&destination);
+    let exported = root.join("exported.git");
+    git(&clone, &format!("commit --allow-empty -m 'for {name}'"))?;
+    git(&clone, &format!("push origin main main:{name}"))?;
+    assert_eq!(
+        git(&exported, &format!("rev-parse main {name}"))?,
+        git(&clone, "rev-parse main main")?,
+        "the branch was updated and created"
+    );
+    git(&exported, "fsck --strict --no-dangling")?;
+
+    git(&clone, &format!("push origin :{name}"))?;
+    assert!(
+        git(&exported, &format!("rev-parse --verify --quiet refs/heads/{name}")).is_err(),
+        "deletions are pushed without pack"
+    );
+    assert!(
+        std::fs::read_dir(exported.join("objects"))?.all(|entry| !entry
+            .expect("readable")
+            .file_name()
+            .to_string_lossy()
+            .starts_with("incoming-")),
+        "the quarantine is removed"
+    );
+    Ok(())
+}
+
+#[test]
+fn directory_resolves_paths_below_base() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_read_only("make_server_repos.sh")?;
+    let exported_only = directory(&root, false);
+    assert!(exported_only.open("/exported".into()).is_some(), "`.git` is appended");
+    assert!(exported_only.open("/exported.git".into()).is_some());
+    assert!(exported_only.open("exported.git".into()).is_some());
+    assert!(
+        exported_only.open("/base".into()).is_none(),
+        "`git-daemon-export-ok` is missing"
+    );
+
+    let all = directory(&root, true);
+    assert!(all.open("/base".into()).is_some(), "work trees are found as well");
+    assert!(all.open("/missing".into()).is_none());
+    assert!(all.open("/base/../base".into()).is_none(), "no escape from the base");
+    assert!(directory(&root.join("base"), true).open("/..".into()).is_none());
+    Ok(())
+}
+
+#[test]
+fn clone_and_fetch_via_git_protocol() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    let directory = directory(root.path(), false);
+    with_daemon(&directory, |url| {
+        for version in ["0", "1", "2"] {
+            clone_and_fetch(root.path(), url, version)?;
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn clone_and_fetch_via_http() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    let directory = directory(root.path(), false);
+    with_http_server(&directory, |url| {
+        for version in ["0", "2"] {
+            clone_and_fetch(root.path(), url, version)?;
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn clone_empty_repository() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    let directory = directory(root.path(), false);
+    with_daemon(&directory, |url| {
+        for version in ["0", "2"] {
+            git(
+                root.path(),
+                &format!("-c protocol.version={version} clone {url}/empty.git empty-v{version}"),
+            )?;
+            assert!(
+                gix::open(root.path().join(format!("empty-v{version}")))?
+                    .head()?
+                    .is_unborn()
+            );
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn errors_are_shown_to_the_user() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    let directory = directory(root.path(), false);
+    with_daemon(&directory, |url| {
+        let err = git(root.path(), &format!("clone {url}/base not-exported")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("access denied or repository not exported: /base"),
+            "{err}"
+        );
+
+        git(root.path(), &format!("clone {url}/exported.git pushing"))?;
+        let err = git(root.path().join("pushing"), "push origin main:new").unwrap_err();
+        assert!(err.to_string().contains("service not enabled: 'receive-pack'"), "{err}");
+        Ok(())
+    })?;
+
+    with_http_server(&directory, |url| {
+        let err = git(root.path(), &format!("clone {url}/base not-exported")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("remote: access denied or repository not exported: /base"),
+            "the message is shown even though the status is an error"
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn push_via_git_protocol_and_http() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    let directory = pushable(root.path());
+    with_daemon(&directory, |url| push(root.path(), url, "git"))?;
+    with_http_server(&directory, |url| push(root.path(), url, "http"))
+}
+
+#[test]
+fn push_with_hooks() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    let directory = pushable(root.path());
+    let hooked = root.path().join("hooked.git");
+    with_daemon(&directory, |url| {
+        git(root.path(), &format!("clone {url}/hooked.git hooked"))?;
+        let clone = root.path().join("hooked");
+        git(&clone, "commit --allow-empty -m new")?;
+        let new = git(&clone, "rev-parse HEAD")?;
+
+        let err = git(&clone, "push origin main:forbidden").unwrap_err().to_string();
+        assert!(
+            err.contains("remote: pushing to refs/heads/forbidden is forbidden"),
+            "{err}"
+        );
+        assert!(err.contains("pre-receive hook declined"), "{err}");
+        assert!(
+            git(&hooked, &format!("cat-file -e {new}")).is_err(),
+            "objects of rejected pushes are discarded"
+        );
+
+        let err = git(&clone, "push --atomic origin main main:declined")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("remote: update of refs/heads/declined declined"), "{err}");
+        assert!(err.contains("main -> main (atomic push failure)"), "{err}");
+        assert_ne!(git(&hooked, "rev-parse main")?, new, "nothing was updated");
+
+        let err = git(&clone, "push -o ci.skip origin main main:declined")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("main -> declined (hook declined)"), "{err}");
+        assert_eq!(git(&hooked, "rev-parse main")?, new, "main was updated nonetheless");
+        let log = std::fs::read_to_string(hooked.join("post-receive.log"))?;
+        assert!(
+            log.ends_with(&format!(" {} refs/heads/main\noptions: 1 ci.skip\n", new.trim())),
+            "{log}"
+        );
+        git(&hooked, "fsck --strict --no-dangling")?;
+        Ok(())
+    })
+}
+
+#[test]
+fn push_to_checked_out_branch_is_rejected() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    let directory = Directory {
+        export_all: true,
+        ..pushable(root.path())
+    };
+    with_daemon(&directory, |url| {
+        git(root.path(), &format!("clone {url}/base clone"))?;
+        let clone = root.path().join("clone");
+        git(&clone, "commit --allow-empty -m new")?;
+        let err = git(&clone, "push origin main").unwrap_err().to_string();
+        assert!(err.contains("main -> main (branch is currently checked out)"), "{err}");
+        git(&clone, "push origin main:other")?;
+        Ok(())
+    })
+}
+
+/// Prepare a bare clone of `url` into `path` without being affected by the environment.
+#[cfg(feature = "blocking-network-client")]
+fn prepare_clone(url: &str, path: &Path) -> Result<gix::clone::PrepareFetch, gix::clone::Error> {
+    gix::clone::PrepareFetch::new(
+        url,
+        path,
+        gix::create::Kind::Bare,
+        Default::default(),
+        crate::restricted(),
+    )
+}
+
+/// Create bundles of `base` below `root/bundles`, one with `other` and the tags, and one with `main` that builds on it.
+#[cfg(feature = "blocking-network-client")]
+fn create_bundles(root: &Path) -> crate::Result {
+    std::fs::create_dir(root.join("bundles"))?;
+    let base = root.join("base");
+    git(&base, "bundle create ../bundles/base.bundle other v1")?;
+    git(&base, "bundle create ../bundles/incremental.bundle other..main")?;
+    Ok(())
+}
+
+#[test]
+#[cfg(any(
+    feature = "blocking-http-transport-curl",
+    feature = "blocking-http-transport-reqwest"
+))]
+fn clone_bootstraps_from_advertised_bundles() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    create_bundles(root.path())?;
+    std::fs::write(
+        root.path().join("bundles").join("list"),
+        r#"[bundle]
+    version = 1
+    mode = all
+    heuristic = creationToken
+[bundle "incremental"]
+    uri = incremental.bundle
+    creationToken = 2
+[bundle "missing"]
+    uri = missing.bundle
+    creationToken = 3
+[bundle "base"]
+    uri = ./base.bundle
+    creationToken = 1
+"#,
+    )?;
+    let directory = directory(root.path(), false);
+    with_http_server(&directory, |url| {
+        let exported = root.path().join("exported.git");
+        git(&exported, "config uploadpack.advertiseBundleURIs true")?;
+        git(&exported, &format!("config bundle.cdn.uri {url}/bundles/list"))?;
+
+        let (repo, outcome) = prepare_clone(&format!("{url}/exported"), &root.path().join("clone"))?
+            .with_in_memory_config_overrides(["transfer.bundleURI=true"])
+            .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+
+        let base = gix::open(root.path().join("base"))?;
+        for name in ["other", "main"] {
+            assert_eq!(
+                repo.find_reference(format!("refs/bundles/{name}").as_str())?.id(),
+                base.rev_parse_single(name)?,
+                "the branches of the bundles are available for negotiation"
+            );
+        }
+        assert!(
+            matches!(outcome.status, gix::remote::fetch::Status::NoPackReceived { .. }),
+            "all objects were unbundled in order, the missing bundle is skipped, and nothing was left to fetch"
+        );
+        assert_eq!(repo.head_id()?, base.head_id()?);
+        assert_eq!(repo.find_reference("v1")?.id(), base.find_reference("v1")?.id());
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(feature = "blocking-network-client")]
+fn clone_bootstraps_from_given_bundle() -> crate::Result {
+    let root = gix_testtools::scripted_fixture_writable("make_server_repos.sh")?;
+    create_bundles(root.path())?;
+    let directory = directory(root.path(), false);
+    with_daemon(&directory, |url| {
+        let (repo, outcome) = prepare_clone(&format!("{url}/exported"), &root.path().join("clone"))?
+            .with_bundle_uri(gix::path::into_bstr(root.path().join("bundles").join("base.bundle")).into_owned())
+            .fetch_only(gix::progress::Discard, &AtomicBool::default())?;
+
+        let base = gix::open(root.path().join("base"))?;
+        assert_eq!(
+            repo.find_reference("refs/bundles/other")?.id(),
+            base.rev_parse_single("other")?
+        );
+        let gix::remote::fetch::Status::Change { negotiate, .. } = outcome.status else {
+            panic!("the commit of `main` isn't in the bundle and has to be fetched");
+        };
+        assert_eq!(
+            negotiate.rounds.iter().map(|round| round.haves_sent).sum::<usize>(),
+            2,
+            "the unbundled commits are offered during negotiation"
+        );
+        assert_eq!(repo.head_id()?, base.head_id()?);
+        Ok(())
+    })
+}
@@ -312,6 +312,96 @@ mod into_iter {
         Ok(())
     }
 
+    #[test]
+    fn untracked_cache_is_written_back_if_enabled() -> crate::Result {
+        let tmp = gix_testtools::scripted_fixture_writable("make_status_repos.sh")?;
+        let mut repo = gix::open_opts(tmp.path().join("untracked-only"), gix::open::Options::isolated())?;
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::Core::UNTRACKED_CACHE, "true")?;
+        assert!(repo.index()?.untracked().is_none(), "no untracked cache initially");
+
+        let mut status = repo.status(gix::progress::Discard)?.into_iter(None)?;
+        let mut expected: Vec<_> = status.by_ref().filter_map(Result::ok).collect();
+        expected.sort_by(|a, b| a.location().cmp(b.location()));
+        assert_eq!(expected.len(), 2, "new/ and subdir/untracked");
+        let mut outcome = status.into_outcome().expect("iteration is complete");
+        assert!(
+            outcome.has_changes(),
+            "the newly created untracked cache needs to be written"
+        );
+        outcome.write_changes().expect("there are changes")?;
+
+        let index = repo.open_index()?;
+        let cache = index.untracked().expect("the untracked cache was written");
+        assert_eq!(cache.directories().len(), 3, "the root, new and subdir");
+        assert_eq!(cache.directories()[0].untracked_entries, ["new/"]);
+
+        let mut status = repo.status(gix::progress::Discard)?.into_iter(None)?;
+        let mut actual: Vec<_> = status.by_ref().filter_map(Result::ok).collect();
+        actual.sort_by(|a, b| a.location().cmp(b.location()));
+        assert_eq!(actual, expected, "the cache doesn't affect the outcome");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn fs_monitor_hook_limits_checks_to_reported_paths() -> crate::Result {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = gix_testtools::scripted_fixture_writable("make_status_repos.sh")?;
+        let worktree = tmp.path().join("untracked-only");
+        let hook = tmp.path().join("fsmonitor-hook");
+        std::fs::write(&hook, "#!/bin/sh\nprintf 'next\\0subdir/that\\0'")?;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+        let mut repo = gix::open_opts(&worktree, gix::open::Options::isolated())?;
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::Core::FSMONITOR, hook.to_str().expect("valid UTF-8"))?;
+        // Files and the index are written within the same second, which would make them racy otherwise.
+        repo.config_snapshot_mut()
+            .set_value(&gix::config::tree::gitoxide::Core::USE_NSEC, "true")?;
+
+        let mut status = repo.status(gix::progress::Discard)?.into_iter(None)?;
+        assert_eq!(
+            status.by_ref().filter_map(Result::ok).count(),
+            2,
+            "only untracked files"
+        );
+        let mut outcome = status.into_outcome().expect("iteration is complete");
+        let fs_monitor = outcome.fs_monitor.expect("the hook was used");
+        assert!(
+            fs_monitor.all_invalidated,
+            "without a token, all entries have to be checked"
+        );
+        assert!(outcome.has_changes(), "the first token needs to be written");
+        outcome.write_changes().expect("the token needs to be written")?;
+        assert!(
+            repo.open_index()?.fs_monitor().is_some(),
+            "the token is now in the index"
+        );
+
+        std::fs::write(worktree.join("this"), "change the monitor doesn't know about")?;
+        let mut status = repo.status(gix::progress::Discard)?.into_iter(None)?;
+        assert_eq!(
+            status.by_ref().filter_map(Result::ok).count(),
+            2,
+            "the change to 'this' isn't seen as only 'subdir/that' is checked"
+        );
+        let outcome = status.into_outcome().expect("iteration is complete");
+        assert_eq!(
+            outcome.fs_monitor,
+            Some(gix_status::fsmonitor::Outcome {
+                entries_valid: 1,
+                paths_reported: 1,
+                all_invalidated: false
+            })
+        );
+        assert!(
+            !outcome.has_changes(),
+            "neither the token nor the valid entries changed"
+        );
+        Ok(())
+    }
+
     #[test]
     fn error_during_tree_traversal_causes_failure() -> crate::Result {
         let repo = repo("untracked-only")?;
@@ -307,6 +307,13 @@ pub fn main() -> Result<()> {
             crate::plumbing::options::diff::SubCommands::Tree {
                 old_treeish,
                 new_treeish,
+                stats:
+                    crate::plumbing::options::diff::StatOptions {
+                        stat,
+                        numstat,
+                        shortstat,
+                        dirstat,
+                    },
             } => prepare_and_run(
                 "diff-tree",
                 trace,
@@ -315,10 +322,61 @@ pub fn main() -> Result<()> {
                 progress_keep_open,
                 None,
                 move |_progress, out, _err| {
-                    core::repository::diff::tree(repository(Mode::Lenient)?, out, old_treeish, new_treeish)
+                    use core::repository::diff::Stats;
+                    let stats = match (stat, numstat, shortstat, dirstat) {
+                        (Some(width), ..) => Some(Stats::Stat { width }),
+                        (_, true, ..) => Some(Stats::Numstat),
+                        (_, _, true, _) => Some(Stats::Shortstat),
+                        (.., Some(params)) => Some(Stats::dirstat_from_params(&params)?),
+                        _ => None,
+                    };
+                    core::repository::diff::tree(repository(Mode::Lenient)?, out, old_treeish, new_treeish, stats)
+                },
+            ),
+            crate::plumbing::options::diff::SubCommands::Merge {
+                combined,
+                unified,
+                revspec,
+            } => prepare_and_run(
+                "diff-merge",
+                trace,
+                verbose,
+                progress,
+                progress_keep_open,
+                None,
+                move |_progress, out, _err| {
+                    core::repository::diff::merge(
+                        repository(Mode::Lenient)?,
+                        out,
+                        revspec,
+                        gix::diff::blob::combined::Options {
+                            context_size: unified,
+                            dense: !combined,
+                        },
+                    )
                 },
             ),
             crate::plumbing::options::diff::SubCommands::File {
+                whitespace:
+                    crate::plumbing::options::diff::WhitespaceOptions {
+                        ignore_all_space,
+                        ignore_space_change,
+                        ignore_space_at_eol,
+                        ignore_cr_at_eol,
+                        ignore_blank_lines,
+                    },
+                word_diff:
+                    crate::plumbing::options::diff::WordDiffOptions {
+                        word_diff,
+                        word_diff_regex,
+                        color_words,
+                    },
+                color_moved:
+                    crate::plumbing::options::diff::ColorMovedOptions {
+                        color_moved,
+                        color_moved_ws,
+                    },
+                function_context,
                 old_revspec,
                 new_revspec,
             } => prepare_and_run(
@@ -329,7 +387,62 @@ pub fn main() -> Result<()> {
                 progress_keep_open,
                 None,
                 move |_progress, out, _err| {
-                    core::repository::diff::file(repository(Mode::Lenient)?, out, old_revspec, new_revspec)
+                    use crate::plumbing::options::diff::WordDiffMode;
+                    use gix::diff::blob::word_diff::Mode as WordMode;
+                    let (mode, regex) = match (word_diff, color_words) {
+                        (_, Some(regex)) => (Some(WordMode::Color), Some(regex).filter(|regex| !regex.is_empty())),
+                        (Some(mode), None) => (
+                            Some(match mode {
+                                WordDiffMode::Plain => WordMode::Plain,
+                                WordDiffMode::Porcelain => WordMode::Porcelain,
+                                WordDiffMode::Color => WordMode::Color,
+                            }),
+                            None,
+                        ),
+                        (None, None) => (word_diff_regex.is_some().then_some(WordMode::Plain), None),
+                    };
+                    let color_moved = (color_moved.is_some() || color_moved_ws.is_some()).then(|| {
+                        use crate::plumbing::options::diff::{ColorMovedMode, ColorMovedWs};
+                        use gix::diff::blob::moved;
+                        let mut whitespace = moved::Whitespace::default();
+                        for ws in color_moved_ws.into_iter().flatten() {
+                            match ws {
+                                ColorMovedWs::No => whitespace = Default::default(),
+                                ColorMovedWs::IgnoreAllSpace => whitespace.ignore_all_space = true,
+                                ColorMovedWs::IgnoreSpaceChange => whitespace.ignore_space_change = true,
+                                ColorMovedWs::IgnoreSpaceAtEol => whitespace.ignore_space_at_eol = true,
+                                ColorMovedWs::AllowIndentationChange => whitespace.allow_indentation_change = true,
+                            }
+                        }
+                        moved::Options {
+                            mode: match color_moved.unwrap_or_default() {
+                                ColorMovedMode::Plain => moved::Mode::Plain,
+                                ColorMovedMode::Blocks => moved::Mode::Blocks,
+                                ColorMovedMode::Zebra => moved::Mode::Zebra,
+                                ColorMovedMode::DimmedZebra => moved::Mode::DimmedZebra,
+                            },
+                            whitespace,
+                        }
+                    });
+                    core::repository::diff::file(
+                        repository(Mode::Lenient)?,
+                        out,
+                        old_revspec,
+                        new_revspec,
+                        gix::diff::blob::whitespace::Options {
+                            ignore_all_space,
+                            ignore_space_change,
+                            ignore_space_at_eol,
+                            ignore_cr_at_eol,
+                            ignore_blank_lines,
+                        },
+                        mode.map(|mode| core::repository::diff::WordDiff {
+                            mode,
+                            regex: regex.or(word_diff_regex).map(Into::into),
+                        }),
+                        color_moved,
+                        function_context,
+                    )
                 },
             ),
         },
@@ -619,9 +619,35 @@ pub mod diff {
             /// A rev-spec representing the 'after' or new tree.
             #[clap(value_parser = crate::shared::AsBString)]
             new_treeish: BString,
+
+            #[clap(flatten)]
+            stats: StatOptions,
+        },
+        /// Show what a merge commit changed relative to all of its parents at once, like `git show --cc` does.
+        Merge {
+            /// Keep all hunks with changes relative to any parent, like `git show -c`, instead of dropping those in which
+            /// the merge took the version of one of the parents.
+            #[clap(long, short = 'c')]
+            combined: bool,
+            /// The amount of lines to show around changes.
+            #[clap(long, short = 'U', value_name = "LINES", default_value_t = 3)]
+            unified: u32,
+            /// A rev-spec representing the merge commit.
+            #[clap(value_parser = crate::shared::AsBString)]
+            revspec: BString,
         },
         /// Diff two versions of a file.
         File {
+            #[clap(flatten)]
+            whitespace: WhitespaceOptions,
+            #[clap(flatten)]
+            word_diff: WordDiffOptions,
+            #[clap(flatten)]
+            color_moved: ColorMovedOptions,
+            /// Show the whole function around each change as context, like `git diff --function-context`.
+            #[clap(long, short = 'W')]
+            function_context: bool,
+
             /// A rev-spec representing the 'before' or old state of the file, like '@~100:file'
             #[clap(value_parser = crate::shared::AsBString)]
             old_revspec: BString,
@@ -630,6 +656,144 @@ pub mod diff {
             new_revspec: BString,
         },
     }
+
+    #[derive(Debug, clap::Parser)]
+    pub struct StatOptions {
+        /// Show the amount of changed lines of each file along with a graph, with lines fitting into WIDTH.
+        #[clap(
+            long,
+            value_name = "WIDTH",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "80",
+            group = "statistics"
+        )]
+        pub stat: Option<usize>,
+        /// Show the amount of added and removed lines of each file, with `-` for binary files.
+        #[clap(long, group = "statistics")]
+        pub numstat: bool,
+        /// Only show the total amount of changed files, as well as added and removed lines.
+        #[clap(long, group = "statistics")]
+        pub shortstat: bool,
+        /// Show the share of changes of each directory, configured by a comma-separated list of `lines`, `files`,
+        /// `cumulative` and the minimum share in percent to show a directory, which defaults to 3.
+        ///
+        /// Unlike `git`, changes are counted by `lines` by default as counting changed bytes isn't supported.
+        #[clap(
+            long,
+            value_name = "PARAMS",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "",
+            group = "statistics"
+        )]
+        pub dirstat: Option<String>,
+    }
+
+    #[derive(Debug, clap::Parser)]
+    pub struct WhitespaceOptions {
+        /// Ignore whitespace when comparing lines.
+        #[clap(long, short = 'w')]
+        pub ignore_all_space: bool,
+        /// Ignore changes in the amount of whitespace, and whitespace at the end of lines.
+        #[clap(long, short = 'b')]
+        pub ignore_space_change: bool,
+        /// Ignore changes in whitespace at the end of lines.
+        #[clap(long)]
+        pub ignore_space_at_eol: bool,
+        /// Ignore a carriage-return at the end of lines.
+        #[clap(long)]
+        pub ignore_cr_at_eol: bool,
+        /// Ignore changes whose lines are all blank, unless they are close to other changes.
+        #[clap(long)]
+        pub ignore_blank_lines: bool,
+    }
+
+    #[derive(Debug, clap::Parser)]
+    pub struct WordDiffOptions {
+        /// Show changed words instead of changed lines, displayed in the given way.
+        #[clap(
+            long,
+            value_name = "MODE",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "plain"
+        )]
+        pub word_diff: Option<WordDiffMode>,
+        /// A regular expression to determine what's a word, instead of `diff.wordRegex` or `diff.<driver>.wordRegex`.
+        ///
+        /// Implies `--word-diff` if it's not set.
+        #[clap(long, value_name = "REGEX")]
+        pub word_diff_regex: Option<String>,
+        /// Like `--word-diff=color`, optionally with a regular expression to determine what's a word.
+        #[clap(
+            long,
+            value_name = "REGEX",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "",
+            conflicts_with = "word_diff"
+        )]
+        pub color_words: Option<String>,
+    }
+
+    #[derive(Debug, clap::Parser)]
+    pub struct ColorMovedOptions {
+        /// Display removed lines that were added elsewhere and vice versa in different colors,
+        /// grouped into blocks in the given way.
+        #[clap(
+            long,
+            value_name = "MODE",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "zebra",
+            conflicts_with_all = ["word_diff", "word_diff_regex", "color_words"]
+        )]
+        pub color_moved: Option<ColorMovedMode>,
+        /// A comma-separated list of ways to handle whitespace when finding moved lines.
+        ///
+        /// Implies `--color-moved` if it's not set.
+        #[clap(long, value_name = "MODES", value_delimiter = ',', conflicts_with_all = ["word_diff", "word_diff_regex", "color_words"])]
+        pub color_moved_ws: Option<Vec<ColorMovedWs>>,
+    }
+
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+    pub enum ColorMovedMode {
+        /// Consider each line moved, no matter how many lines were moved along with it.
+        Plain,
+        /// Only consider blocks of lines with at least 20 alphanumeric characters moved.
+        Blocks,
+        /// Like `blocks`, but use alternating colors for adjacent blocks.
+        #[default]
+        Zebra,
+        /// Like `zebra`, but dim all lines that aren't at the boundary of adjacent blocks.
+        DimmedZebra,
+    }
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+    pub enum ColorMovedWs {
+        /// Don't ignore whitespace, which resets all previously given modes.
+        No,
+        /// Ignore whitespace when comparing lines.
+        IgnoreAllSpace,
+        /// Ignore changes in the amount of whitespace.
+        IgnoreSpaceChange,
+        /// Ignore changes in whitespace at the end of lines.
+        IgnoreSpaceAtEol,
+        /// Ignore changes in indentation if all lines of a block are indented by the same amount.
+        AllowIndentationChange,
+    }
+
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+    pub enum WordDiffMode {
+        /// Enclose removed words in `[-` and `-]`, and added words in `{+` and `+}`.
+        #[default]
+        Plain,
+        /// A line-based format for use by scripts.
+        Porcelain,
+        /// Display removed words in red and added words in green.
+        Color,
+    }
 }
 
 pub mod log {
@@ -715,7 +879,7 @@ pub mod fetch {
         #[clap(flatten)]
         pub shallow: ShallowOptions,
 
-        /// The name of the remote to connect to, or the url of the remote to connect to directly.
+        /// The name of the remote or remote group to fetch from, or the url of the remote to connect to directly.
         ///
         /// If unset, the current branch will determine the remote.
         #[clap(long, short = 'r')]
@@ -120,10 +120,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "checkout.defaultRemote",
         usage: Planned("Needed for correct checkout behaviour, similar to what git does"),
     },
-    Record {
-        config: "core.untrackedCache",
-        usage: Planned("Needed for fast worktree operation"),
-    },
     Record {
         config: "checkout.guess",
         usage: Planned(""),
@@ -276,12 +272,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "fetch.output",
         usage: NotPlanned("'gix' might support it, but there is no intention on copying the 'git' CLI"),
     },
-    Record {
-        config: "remotes.<group>",
-        usage: Planned(
-            "Useful for multi-remote fetches as part of the standard API, maybe just 'group(name) -> Option<Vec<Remote>>'",
-        ),
-    },
     Record {
         config: "advice.updateSparsePath",
         usage: NotApplicable("gitoxide does not yet have an 'advice' system"),
@@ -296,20 +286,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "core.sparseCheckoutCone",
         usage: Planned("Non-cone mode is deprecated but should still fail gracefully if encountered"),
     },
-    Record {
-        config: "core.splitIndex",
-        usage: NotPlanned(
-            "We don't want to be able to create split indices, but we will read them. It's (somewhat) superseded by sparse indices",
-        ),
-    },
-    Record {
-        config: "splitIndex.maxPercentageChange",
-        usage: NotPlanned("Seems like it's superseded by sparse indices"),
-    },
-    Record {
-        config: "splitIndex.sharedIndexExpire",
-        usage: NotPlanned("Seems like it's superseded by sparse indices"),
-    },
     Record {
         config: "index.sparse",
         usage: Planned(